@@ -1,6 +1,7 @@
 use crate::point::{max_inline, Point, PointExt};
 use crate::{Envelope, RTreeObject};
-use num_traits::{Bounded, One, Zero};
+use num_traits::{Bounded, Float, One, Zero};
+use smallvec::SmallVec;
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
@@ -91,14 +92,235 @@ where
 
     /// Returns the squared distance to the AABB's [min_point](AABB::min_point)
     pub fn distance_2(&self, point: &P) -> P::Scalar {
-        if self.contains_point(point) {
-            Zero::zero()
+        // The closest point of a box containing `point` is `point` itself, so no special case
+        // is required. Avoiding the branch allows the compiler to vectorize this method.
+        self.min_point(point).sub(point).length_2()
+    }
+
+    /// Returns the squared distance between the closest points of this AABB and `other`.
+    pub(crate) fn distance_2_to_aabb(&self, other: &AABB<P>) -> P::Scalar {
+        let zero = P::Scalar::zero();
+        let gap = P::generate(|axis| {
+            let below = other.lower.nth(axis) - self.upper.nth(axis);
+            let above = self.lower.nth(axis) - other.upper.nth(axis);
+            max_inline(max_inline(below, above), zero)
+        });
+        gap.length_2()
+    }
+
+    /// Returns the AABB grown by `margin` in every direction.
+    ///
+    /// A negative margin shrinks the AABB. The result contains no points if it shrinks by more
+    /// than half the AABB's extent along any axis.
+    ///
+    /// # Example
+    /// ```
+    /// use rstar::AABB;
+    ///
+    /// let aabb = AABB::from_corners([0.0, 0.0], [2.0, 1.0]);
+    /// let expanded = aabb.expanded_by(0.5);
+    /// assert_eq!(expanded, AABB::from_corners([-0.5, -0.5], [2.5, 1.5]));
+    /// ```
+    pub fn expanded_by(&self, margin: P::Scalar) -> Self {
+        self.expanded_by_vector(&P::from_value(margin))
+    }
+
+    /// Returns the AABB grown by `margin`'s components along the respective axes.
+    ///
+    /// See [AABB::expanded_by].
+    pub fn expanded_by_vector(&self, margin: &P) -> Self {
+        AABB {
+            lower: self.lower.sub(margin),
+            upper: self.upper.add(margin),
+        }
+    }
+
+    /// Returns the AABB contained in both `self` and `other`, or `None` if they don't
+    /// intersect.
+    ///
+    /// AABBs touching each other intersect in a degenerate AABB.
+    ///
+    /// # Example
+    /// ```
+    /// use rstar::AABB;
+    ///
+    /// let left = AABB::from_corners([0.0, 0.0], [2.0, 2.0]);
+    /// let right = AABB::from_corners([1.0, 1.0], [3.0, 3.0]);
+    /// assert_eq!(
+    ///     left.intersection(&right),
+    ///     Some(AABB::from_corners([1.0, 1.0], [2.0, 2.0]))
+    /// );
+    /// assert_eq!(left.intersection(&AABB::from_point([4.0, 0.0])), None);
+    /// ```
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        let lower = self.lower.max_point(&other.lower);
+        let upper = self.upper.min_point(&other.upper);
+        if (0..P::DIMENSIONS).all(|axis| lower.nth(axis) <= upper.nth(axis)) {
+            Some(AABB { lower, upper })
         } else {
-            self.min_point(point).sub(point).length_2()
+            None
         }
     }
 }
 
+/// The start, end and quadratic coefficients of a piece of a segment, see
+/// [AABB::segment_pieces].
+type SegmentPiece<S> = (S, S, (S, S, S));
+
+impl<P> AABB<P>
+where
+    P: Point,
+    P::Scalar: Float,
+{
+    /// Returns the squared distance between this AABB and the line segment from `from` to `to`.
+    ///
+    /// The squared distance between a point moving along the segment and the AABB is a convex,
+    /// piecewise quadratic function. Its pieces are separated by the positions at which the
+    /// point crosses one of the AABB's boundary planes, the minimum of every piece is computed
+    /// in closed form.
+    pub(crate) fn segment_distance_2(&self, from: &P, to: &P) -> P::Scalar {
+        let direction = to.sub(from);
+        let at = |t: P::Scalar| self.distance_2(&from.add(&direction.mul(t)));
+
+        let mut result = at(Zero::zero()).min(at(One::one()));
+        for (start, end, (a, b, _)) in self.segment_pieces(from, &direction) {
+            if a > Zero::zero() {
+                let t = (-b / a).max(start).min(end);
+                result = result.min(at(t));
+            }
+        }
+        result
+    }
+
+    /// Returns the smallest parameter `t` in `[0, 1]` for which `from + t * (to - from)` has a
+    /// squared distance of at most `radius_2` to this AABB, or `None` if there is none.
+    pub(crate) fn segment_hit(&self, from: &P, to: &P, radius_2: P::Scalar) -> Option<P::Scalar> {
+        let direction = to.sub(from);
+        let at = |t: P::Scalar| self.distance_2(&from.add(&direction.mul(t)));
+        if at(Zero::zero()) <= radius_2 {
+            return Some(Zero::zero());
+        }
+        for (start, end, (a, b, c)) in self.segment_pieces(from, &direction) {
+            if a <= Zero::zero() {
+                // The distance is constant on this piece.
+                if at(end) <= radius_2 {
+                    return Some(start);
+                }
+                continue;
+            }
+            // The distance exceeds the radius at `start`. If the piece's minimum is close
+            // enough, the smaller root lies between `start` and the minimum.
+            let minimum = (-b / a).max(start).min(end);
+            if at(minimum) <= radius_2 {
+                let discriminant = (b * b - a * (c - radius_2)).max(Zero::zero());
+                let t = (-b - discriminant.sqrt()) / a;
+                return Some(t.max(start).min(minimum));
+            }
+        }
+        None
+    }
+
+    /// Splits the segment from `from` in `direction` into the pieces on which the squared
+    /// distance to this AABB is a single quadratic function `a * t^2 + 2 * b * t + c`.
+    ///
+    /// Returns the range of `t` and the coefficients `(a, b, c)` of every piece, in order.
+    fn segment_pieces(
+        &self,
+        from: &P,
+        direction: &P,
+    ) -> impl Iterator<Item = SegmentPiece<P::Scalar>> + '_ {
+        let zero = P::Scalar::zero();
+        let one = P::Scalar::one();
+        let mut breaks: SmallVec<[P::Scalar; 8]> = SmallVec::new();
+        breaks.push(zero);
+        breaks.push(one);
+        for axis in 0..P::DIMENSIONS {
+            let d = direction.nth(axis);
+            if d != zero {
+                for bound in [self.lower.nth(axis), self.upper.nth(axis)] {
+                    let t = (bound - from.nth(axis)) / d;
+                    if zero < t && t < one {
+                        breaks.push(t);
+                    }
+                }
+            }
+        }
+        breaks.sort_by(|l, r| l.partial_cmp(r).unwrap_or(core::cmp::Ordering::Equal));
+
+        let (from, direction) = (from.clone(), direction.clone());
+        let two = one + one;
+        (1..breaks.len()).map(move |index| {
+            let (start, end) = (breaks[index - 1], breaks[index]);
+            let middle = (start + end) / two;
+            // Sum up the quadratic terms of all axes outside the AABB within this piece.
+            let (mut a, mut b, mut c) = (zero, zero, zero);
+            for axis in 0..P::DIMENSIONS {
+                let d = direction.nth(axis);
+                let position = from.nth(axis) + d * middle;
+                let offset = if position < self.lower.nth(axis) {
+                    from.nth(axis) - self.lower.nth(axis)
+                } else if position > self.upper.nth(axis) {
+                    from.nth(axis) - self.upper.nth(axis)
+                } else {
+                    continue;
+                };
+                a = a + d * d;
+                b = b + offset * d;
+                c = c + offset * offset;
+            }
+            (start, end, (a, b, c))
+        })
+    }
+
+    /// Returns the range of parameters `t >= 0` for which `origin + t * direction` is contained
+    /// in this AABB, or `None` if the ray misses the AABB.
+    pub(crate) fn ray_intersection(
+        &self,
+        origin: &P,
+        direction: &P,
+    ) -> Option<(P::Scalar, P::Scalar)> {
+        let zero = P::Scalar::zero();
+        let (mut near, mut far) = (zero, P::Scalar::infinity());
+        for axis in 0..P::DIMENSIONS {
+            let (lower, upper) = (self.lower.nth(axis), self.upper.nth(axis));
+            let (o, d) = (origin.nth(axis), direction.nth(axis));
+            if d == zero {
+                if o < lower || o > upper {
+                    return None;
+                }
+            } else {
+                let (t1, t2) = ((lower - o) / d, (upper - o) / d);
+                near = near.max(t1.min(t2));
+                far = far.min(t1.max(t2));
+            }
+        }
+        if near <= far {
+            Some((near, far))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the earliest time `t` in `[0, 1]` at which `moving` translated by
+    /// `t * displacement` intersects this AABB, or `None` if it never does.
+    pub(crate) fn sweep_intersection(
+        &self,
+        moving: &AABB<P>,
+        displacement: &P,
+    ) -> Option<P::Scalar> {
+        // Moving the box is equivalent to moving its lower corner through this AABB grown by
+        // the box's extent, so the sweep reduces to a ray intersection.
+        let grown = AABB {
+            lower: self.lower.sub(&moving.upper),
+            upper: self.upper.sub(&moving.lower),
+        };
+        grown
+            .ray_intersection(&P::new(), displacement)
+            .map(|(near, _)| near)
+            .filter(|near| *near <= One::one())
+    }
+}
+
 impl<P> Envelope for AABB<P>
 where
     P: Point,
@@ -110,8 +332,10 @@ where
     }
 
     fn contains_point(&self, point: &P) -> bool {
-        self.lower.all_component_wise(point, |x, y| x <= y)
-            && self.upper.all_component_wise(point, |x, y| x >= y)
+        all_axes::<P>(|axis| {
+            let value = point.nth(axis);
+            (self.lower.nth(axis) <= value) & (value <= self.upper.nth(axis))
+        })
     }
 
     fn contains_envelope(&self, other: &Self) -> bool {
@@ -132,8 +356,10 @@ where
     }
 
     fn intersects(&self, other: &Self) -> bool {
-        self.lower.all_component_wise(&other.upper, |l, r| l <= r)
-            && self.upper.all_component_wise(&other.lower, |l, r| l >= r)
+        all_axes::<P>(|axis| {
+            (self.lower.nth(axis) <= other.upper.nth(axis))
+                & (other.lower.nth(axis) <= self.upper.nth(axis))
+        })
     }
 
     fn area(&self) -> P::Scalar {
@@ -195,7 +421,7 @@ where
     }
 
     fn sort_envelopes<T: RTreeObject<Envelope = Self>>(axis: usize, envelopes: &mut [T]) {
-        envelopes.sort_by(|l, r| {
+        envelopes.sort_unstable_by(|l, r| {
             l.envelope()
                 .lower
                 .nth(axis)
@@ -228,6 +454,20 @@ fn new_empty<P: Point>() -> AABB<P> {
     }
 }
 
+/// Returns `true` if `f` returns `true` for every axis.
+///
+/// In contrast to [Iterator::all], all axes are evaluated without short-circuiting. Envelope
+/// checks are evaluated extremely often, and branch-free comparisons of all axes can be
+/// vectorized by the compiler, e.g. for two or three dimensional `f32` and `f64` points.
+#[inline(always)]
+fn all_axes<P: Point>(mut f: impl FnMut(usize) -> bool) -> bool {
+    let mut result = true;
+    for axis in 0..P::DIMENSIONS {
+        result &= f(axis);
+    }
+    result
+}
+
 #[cfg(test)]
 mod test {
     use super::AABB;
@@ -246,4 +486,104 @@ mod test {
         let corner = [a[0], b[1], a[2]];
         assert_eq!(aabb.min_max_dist_2(&p), corner.distance_2(&p));
     }
+
+    #[test]
+    fn test_envelope_checks() {
+        let aabb = AABB::from_corners([0.0f32, 0.0, 0.0], [1.0, 2.0, 3.0]);
+        assert!(aabb.contains_point(&[1.0, 2.0, 3.0]));
+        assert!(aabb.contains_point(&[0.5, 0.0, 1.0]));
+        assert!(!aabb.contains_point(&[0.5, 2.5, 1.0]));
+        assert!(!aabb.contains_point(&[0.5, 1.0, f32::NAN]));
+
+        assert!(aabb.intersects(&AABB::from_corners([1.0, 2.0, 3.0], [4.0, 4.0, 4.0])));
+        assert!(!aabb.intersects(&AABB::from_corners([0.0, 0.0, 3.5], [4.0, 4.0, 4.0])));
+
+        assert_eq!(aabb.distance_2(&[0.5, 1.0, 1.5]), 0.0);
+        assert_eq!(aabb.distance_2(&[-1.0, 3.0, 1.0]), 2.0);
+    }
+
+    #[test]
+    fn test_segment_distance_2() {
+        let aabb = AABB::from_corners([0.0, 0.0], [1.0, 2.0]);
+        // Crossing the AABB.
+        assert_eq!(aabb.segment_distance_2(&[-1.0, 1.0], &[2.0, 1.5]), 0.0);
+        // Parallel to an edge.
+        assert_eq!(aabb.segment_distance_2(&[2.0, -1.0], &[2.0, 5.0]), 1.0);
+        // Closest to an end point.
+        assert_eq!(aabb.segment_distance_2(&[3.0, 3.0], &[4.0, 5.0]), 5.0);
+        // Passing a corner diagonally.
+        let distance_2: f64 = aabb.segment_distance_2(&[1.0, 4.0], &[3.0, 2.0]);
+        assert!((distance_2 - 2.0).abs() < 1e-12);
+        // Degenerate segment.
+        assert_eq!(aabb.segment_distance_2(&[-2.0, 1.0], &[-2.0, 1.0]), 4.0);
+    }
+
+    #[test]
+    fn test_distance_2_to_aabb() {
+        let aabb = AABB::from_corners([0.0, 0.0], [1.0, 1.0]);
+        assert_eq!(
+            aabb.distance_2_to_aabb(&AABB::from_corners([2.0, 3.0], [4.0, 4.0])),
+            5.0
+        );
+        assert_eq!(
+            aabb.distance_2_to_aabb(&AABB::from_corners([0.5, -2.0], [0.6, -1.0])),
+            1.0
+        );
+        assert_eq!(
+            aabb.distance_2_to_aabb(&AABB::from_corners([0.5, 0.5], [3.0, 3.0])),
+            0.0
+        );
+    }
+
+    #[test]
+    fn test_ray_intersection() {
+        let aabb = AABB::from_corners([1.0, 1.0], [3.0, 2.0]);
+        assert_eq!(
+            aabb.ray_intersection(&[0.0, 1.5], &[1.0, 0.0]),
+            Some((1.0, 3.0))
+        );
+        assert_eq!(
+            aabb.ray_intersection(&[2.0, 1.5], &[0.0, 2.0]),
+            Some((0.0, 0.25))
+        );
+        assert_eq!(
+            aabb.ray_intersection(&[0.0, 0.0], &[2.0, 1.0]),
+            Some((1.0, 1.5))
+        );
+        // Pointing away from the AABB.
+        assert_eq!(aabb.ray_intersection(&[0.0, 1.5], &[-1.0, 0.0]), None);
+        // Parallel to and outside of the AABB.
+        assert_eq!(aabb.ray_intersection(&[0.0, 3.0], &[1.0, 0.0]), None);
+        assert_eq!(aabb.ray_intersection(&[0.0, 0.0], &[1.0, 3.0]), None);
+    }
+
+    #[test]
+    fn test_sweep_intersection() {
+        let aabb = AABB::from_corners([2.0, 0.0], [3.0, 1.0]);
+        let moving = AABB::from_corners([0.0, 0.0], [1.0, 1.0]);
+        assert_eq!(aabb.sweep_intersection(&moving, &[2.0, 0.0]), Some(0.5));
+        assert_eq!(aabb.sweep_intersection(&moving, &[1.0, 0.0]), Some(1.0));
+        assert_eq!(aabb.sweep_intersection(&moving, &[0.5, 0.0]), None);
+        assert_eq!(aabb.sweep_intersection(&moving, &[4.0, 2.0]), Some(0.25));
+        assert_eq!(aabb.sweep_intersection(&moving, &[4.0, 8.0]), None);
+        assert_eq!(moving.sweep_intersection(&moving, &[0.0, 0.0]), Some(0.0));
+    }
+
+    #[test]
+    fn test_expansion_and_intersection() {
+        let aabb = AABB::from_corners([0.0, 1.0], [2.0, 3.0]);
+        assert_eq!(
+            aabb.expanded_by_vector(&[1.0, 0.5]),
+            AABB::from_corners([-1.0, 0.5], [3.0, 3.5])
+        );
+        assert_eq!(aabb.expanded_by(1.0).expanded_by(-1.0), aabb);
+        assert!(!aabb.expanded_by(-1.5).contains_point(&[1.0, 2.0]));
+        assert_eq!(aabb.intersection(&aabb), Some(aabb));
+        assert_eq!(
+            aabb.intersection(&AABB::from_corners([2.0, 0.0], [4.0, 2.0])),
+            Some(AABB::from_corners([2.0, 1.0], [2.0, 2.0]))
+        );
+        assert_eq!(aabb.intersection(&AABB::from_point([2.5, 2.0])), None);
+        assert_eq!(AABB::new_empty().intersection(&aabb), None);
+    }
 }
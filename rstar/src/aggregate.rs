@@ -0,0 +1,709 @@
+use crate::histogram::Grid;
+use crate::node::{ParentNode, RTreeNode};
+use crate::params::{DefaultParams, RTreeParams};
+use crate::point::Point;
+use crate::{Envelope, RTree, RTreeObject, AABB};
+
+use alloc::collections::BinaryHeap;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use num_traits::Float;
+
+/// Combines values of elements into an aggregate, e.g. a sum of weights or a maximum.
+///
+/// The combination must form a monoid: `combine` must be associative and `empty` must be its
+/// neutral element. [AggregateRTree] relies on this to combine the cached aggregates of whole
+/// subtrees in arbitrary groupings.
+///
+/// # Example
+/// ```
+/// use rstar::primitives::GeomWithData;
+/// use rstar::Aggregator;
+///
+/// /// Sums the weights of all elements.
+/// struct SumWeights;
+///
+/// impl Aggregator<GeomWithData<[f64; 2], f64>> for SumWeights {
+///     type Value = f64;
+///
+///     fn empty(&self) -> f64 {
+///         0.0
+///     }
+///
+///     fn leaf(&self, element: &GeomWithData<[f64; 2], f64>) -> f64 {
+///         element.data
+///     }
+///
+///     fn combine(&self, first: &f64, second: &f64) -> f64 {
+///         first + second
+///     }
+/// }
+/// ```
+pub trait Aggregator<T> {
+    /// The aggregated value.
+    type Value: Clone;
+
+    /// Returns the aggregate of no elements.
+    fn empty(&self) -> Self::Value;
+
+    /// Returns the aggregate of a single element.
+    fn leaf(&self, element: &T) -> Self::Value;
+
+    /// Combines two aggregates.
+    fn combine(&self, first: &Self::Value, second: &Self::Value) -> Self::Value;
+}
+
+/// An r-tree that stores an aggregate for every node, see [Aggregator].
+///
+/// [AggregateRTree::aggregate_in_envelope] combines the cached aggregates of all nodes that
+/// are fully contained in the query envelope without visiting their elements, which makes
+/// aggregating large areas about as fast as a small window query.
+///
+/// The aggregates are computed when the tree is loaded. Insertions and removals invalidate
+/// them, they are recomputed in a single pass by the next aggregate query. Batches of
+/// modifications thus only cause a single recomputation.
+///
+/// # Example
+/// ```
+/// use rstar::primitives::GeomWithData;
+/// use rstar::{AggregateRTree, Aggregator, AABB};
+///
+/// struct SumWeights;
+///
+/// impl Aggregator<GeomWithData<[f64; 2], f64>> for SumWeights {
+///     type Value = f64;
+///
+///     fn empty(&self) -> f64 {
+///         0.0
+///     }
+///
+///     fn leaf(&self, element: &GeomWithData<[f64; 2], f64>) -> f64 {
+///         element.data
+///     }
+///
+///     fn combine(&self, first: &f64, second: &f64) -> f64 {
+///         first + second
+///     }
+/// }
+///
+/// let elements = (0..100)
+///     .map(|i| GeomWithData::new([i as f64, 0.0], i as f64))
+///     .collect();
+/// let mut tree = AggregateRTree::bulk_load(SumWeights, elements);
+/// let envelope = AABB::from_corners([0.0, -1.0], [9.0, 1.0]);
+/// assert_eq!(tree.aggregate_in_envelope(&envelope), 45.0);
+/// assert_eq!(tree.aggregate(), 4950.0);
+/// ```
+pub struct AggregateRTree<T, A, Params = DefaultParams>
+where
+    T: RTreeObject,
+    A: Aggregator<T>,
+    Params: RTreeParams,
+{
+    tree: RTree<T, Params>,
+    aggregator: A,
+    /// The aggregate and the number of nodes of every parent node's subtree, in depth first
+    /// order. Empty if the aggregates need to be recomputed.
+    aggregates: Vec<(A::Value, usize)>,
+}
+
+impl<T, A> AggregateRTree<T, A>
+where
+    T: RTreeObject,
+    A: Aggregator<T>,
+{
+    /// Creates a new, empty tree.
+    pub fn new(aggregator: A) -> Self {
+        Self::new_with_params(aggregator)
+    }
+
+    /// Creates a new tree from a set of elements and computes all aggregates.
+    ///
+    /// See [RTree::bulk_load].
+    pub fn bulk_load(aggregator: A, elements: Vec<T>) -> Self {
+        Self::bulk_load_with_params(aggregator, elements)
+    }
+}
+
+impl<T, A, Params> AggregateRTree<T, A, Params>
+where
+    T: RTreeObject,
+    A: Aggregator<T>,
+    Params: RTreeParams,
+{
+    /// Creates a new, empty tree with custom parameters.
+    pub fn new_with_params(aggregator: A) -> Self {
+        AggregateRTree {
+            tree: RTree::new_with_params(),
+            aggregator,
+            aggregates: Vec::new(),
+        }
+    }
+
+    /// Creates a new tree from a set of elements with custom parameters.
+    pub fn bulk_load_with_params(aggregator: A, elements: Vec<T>) -> Self {
+        let mut tree = AggregateRTree {
+            tree: RTree::bulk_load_with_params(elements),
+            aggregator,
+            aggregates: Vec::new(),
+        };
+        tree.update_aggregates();
+        tree
+    }
+
+    /// Returns the underlying tree.
+    pub fn tree(&self) -> &RTree<T, Params> {
+        &self.tree
+    }
+
+    /// Consumes this wrapper and returns the underlying tree.
+    pub fn into_tree(self) -> RTree<T, Params> {
+        self.tree
+    }
+
+    /// Returns the aggregator.
+    pub fn aggregator(&self) -> &A {
+        &self.aggregator
+    }
+
+    /// Returns the number of elements in the tree.
+    pub fn size(&self) -> usize {
+        self.tree.size()
+    }
+
+    /// Inserts an element.
+    ///
+    /// See [RTree::insert].
+    pub fn insert(&mut self, t: T) {
+        self.tree.insert(t);
+        self.aggregates.clear();
+    }
+
+    /// Returns the aggregate of all elements.
+    pub fn aggregate(&mut self) -> A::Value {
+        self.update_aggregates();
+        self.aggregates[0].0.clone()
+    }
+
+    /// Returns the aggregate of all elements contained in an envelope.
+    ///
+    /// Subtrees which are fully contained in the envelope contribute their cached aggregate.
+    pub fn aggregate_in_envelope(&mut self, envelope: &T::Envelope) -> A::Value {
+        self.update_aggregates();
+        let mut result = self.aggregator.empty();
+        let mut index = 0;
+        let mut stack = Vec::new();
+        stack.push(self.tree.root());
+        while let Some(node) = stack.pop() {
+            let (ref value, count) = self.aggregates[index];
+            if !node.envelope.intersects(envelope) {
+                index += count;
+            } else if envelope.contains_envelope(&node.envelope) {
+                result = self.aggregator.combine(&result, value);
+                index += count;
+            } else {
+                index += 1;
+                // Children are pushed in reverse to visit them in depth first order.
+                for child in node.children.iter().rev() {
+                    match child {
+                        RTreeNode::Parent(ref data) => stack.push(data),
+                        RTreeNode::Leaf(ref t) => {
+                            if envelope.contains_envelope(&t.envelope()) {
+                                let value = self.aggregator.leaf(t);
+                                result = self.aggregator.combine(&result, &value);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    /// Returns the `k` elements with the highest score intersecting an envelope, highest score
+    /// first.
+    ///
+    /// `score` maps an aggregate to a score. For the aggregate of a single element this is the
+    /// element's score, for the aggregate of a subtree it must be an upper bound of the scores
+    /// of all its elements, i.e. `score` must not decrease when aggregates are combined.
+    /// Returning `None` excludes an element or subtree.
+    ///
+    /// Subtrees are visited best first by their bound, the search stops as soon as the `k`
+    /// best elements are found. Subtrees whose bound is lower than the `k`th best score are
+    /// never visited.
+    ///
+    /// # Example
+    /// ```
+    /// use rstar::primitives::GeomWithData;
+    /// use rstar::{AggregateRTree, Aggregator, AABB};
+    ///
+    /// /// Aggregates the maximum height of buildings.
+    /// struct MaxHeight;
+    ///
+    /// impl Aggregator<GeomWithData<[f64; 2], f64>> for MaxHeight {
+    ///     type Value = f64;
+    ///
+    ///     fn empty(&self) -> f64 {
+    ///         f64::NEG_INFINITY
+    ///     }
+    ///
+    ///     fn leaf(&self, building: &GeomWithData<[f64; 2], f64>) -> f64 {
+    ///         building.data
+    ///     }
+    ///
+    ///     fn combine(&self, first: &f64, second: &f64) -> f64 {
+    ///         first.max(*second)
+    ///     }
+    /// }
+    ///
+    /// let buildings = (0..1000)
+    ///     .map(|i| GeomWithData::new([i as f64, 0.0], (i % 97) as f64))
+    ///     .collect();
+    /// let mut tree = AggregateRTree::bulk_load(MaxHeight, buildings);
+    /// let viewport = AABB::from_corners([0.0, -1.0], [99.0, 1.0]);
+    /// let tallest = tree.top_k_in_envelope(&viewport, 2, |height| Some(*height));
+    /// assert_eq!(tallest[0].data, 96.0);
+    /// assert_eq!(tallest[1].data, 95.0);
+    /// ```
+    pub fn top_k_in_envelope<S, F>(&mut self, envelope: &T::Envelope, k: usize, score: F) -> Vec<&T>
+    where
+        S: PartialOrd,
+        F: Fn(&A::Value) -> Option<S>,
+    {
+        self.update_aggregates();
+        let mut result = Vec::new();
+        let mut candidates = BinaryHeap::new();
+        if let Some(value) = score(&self.aggregates[0].0) {
+            candidates.push(ScoredCandidate {
+                score: value,
+                node: Candidate::Parent(self.tree.root(), 0),
+            });
+        }
+        while result.len() < k {
+            let current = match candidates.pop() {
+                Some(current) => current,
+                None => break,
+            };
+            let (node, index) = match current.node {
+                Candidate::Leaf(t) => {
+                    result.push(t);
+                    continue;
+                }
+                Candidate::Parent(node, index) => (node, index),
+            };
+            if !node.envelope.intersects(envelope) {
+                continue;
+            }
+            let mut child_index = index + 1;
+            for child in &node.children {
+                match child {
+                    RTreeNode::Parent(ref data) => {
+                        let (ref value, count) = self.aggregates[child_index];
+                        if let Some(value) = score(value) {
+                            candidates.push(ScoredCandidate {
+                                score: value,
+                                node: Candidate::Parent(data, child_index),
+                            });
+                        }
+                        child_index += count;
+                    }
+                    RTreeNode::Leaf(ref t) => {
+                        if !t.envelope().intersects(envelope) {
+                            continue;
+                        }
+                        if let Some(value) = score(&self.aggregator.leaf(t)) {
+                            candidates.push(ScoredCandidate {
+                                score: value,
+                                node: Candidate::Leaf(t),
+                            });
+                        }
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    /// Recomputes all aggregates if the tree was modified since they were last computed.
+    fn update_aggregates(&mut self) {
+        if self.aggregates.is_empty() {
+            compute_aggregates(&self.aggregator, self.tree.root(), &mut self.aggregates);
+        }
+    }
+}
+
+impl<T, A, Params> AggregateRTree<T, A, Params>
+where
+    T: RTreeObject + PartialEq,
+    A: Aggregator<T>,
+    Params: RTreeParams,
+{
+    /// Removes an element and returns it.
+    ///
+    /// See [RTree::remove].
+    pub fn remove(&mut self, t: &T) -> Option<T> {
+        let removed = self.tree.remove(t);
+        if removed.is_some() {
+            self.aggregates.clear();
+        }
+        removed
+    }
+}
+
+impl<T, A, P, Params> AggregateRTree<T, A, Params>
+where
+    T: RTreeObject<Envelope = AABB<P>>,
+    A: Aggregator<T>,
+    P: Point,
+    P::Scalar: Float,
+    Params: RTreeParams,
+{
+    /// Aggregates the elements in every cell of a regular grid, e.g. to render a heatmap of
+    /// weights.
+    ///
+    /// The elements are binned like in [RTree::histogram], the aggregates are returned in the
+    /// same row major order. Subtrees lying within a single cell contribute their cached
+    /// aggregate.
+    ///
+    /// # Example
+    /// ```
+    /// use rstar::primitives::GeomWithData;
+    /// use rstar::{AggregateRTree, Aggregator, AABB};
+    ///
+    /// struct SumWeights;
+    ///
+    /// impl Aggregator<GeomWithData<[f64; 2], f64>> for SumWeights {
+    ///     type Value = f64;
+    ///
+    ///     fn empty(&self) -> f64 {
+    ///         0.0
+    ///     }
+    ///
+    ///     fn leaf(&self, element: &GeomWithData<[f64; 2], f64>) -> f64 {
+    ///         element.data
+    ///     }
+    ///
+    ///     fn combine(&self, first: &f64, second: &f64) -> f64 {
+    ///         first + second
+    ///     }
+    /// }
+    ///
+    /// let elements = (0..100)
+    ///     .map(|i| GeomWithData::new([i as f64, 0.0], 1.5))
+    ///     .collect();
+    /// let mut tree = AggregateRTree::bulk_load(SumWeights, elements);
+    /// let extent = AABB::from_corners([0.0, 0.0], [100.0, 0.0]);
+    /// assert_eq!(tree.histogram(extent, 4, 1), vec![37.5; 4]);
+    /// ```
+    pub fn histogram(&mut self, grid_extent: AABB<P>, nx: usize, ny: usize) -> Vec<A::Value> {
+        self.update_aggregates();
+        let grid = Grid::new(grid_extent, nx, ny);
+        let mut result = vec![self.aggregator.empty(); grid.len()];
+        let mut index = 0;
+        let mut stack = Vec::new();
+        stack.push(self.tree.root());
+        while let Some(node) = stack.pop() {
+            let (ref value, count) = self.aggregates[index];
+            if !grid.intersects(&node.envelope) {
+                index += count;
+            } else if let Some(cell) = grid.node_cell(&node.envelope) {
+                result[cell] = self.aggregator.combine(&result[cell], value);
+                index += count;
+            } else {
+                index += 1;
+                // Children are pushed in reverse to visit them in depth first order.
+                for child in node.children.iter().rev() {
+                    match child {
+                        RTreeNode::Parent(ref data) => stack.push(data),
+                        RTreeNode::Leaf(ref t) => {
+                            if let Some(cell) = grid.element_cell(&t.envelope()) {
+                                let value = self.aggregator.leaf(t);
+                                result[cell] = self.aggregator.combine(&result[cell], &value);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        result
+    }
+}
+
+/// Aggregates the maximum importance of elements for level of detail queries.
+///
+/// The importance of an element is returned by the wrapped function, larger values are more
+/// important. See [AggregateRTree::locate_in_envelope_top_n_by_importance].
+#[derive(Clone, Copy, Debug)]
+pub struct MaxImportance<F>(pub F);
+
+impl<T, F, S> Aggregator<T> for MaxImportance<F>
+where
+    F: Fn(&T) -> S,
+    S: PartialOrd + Clone,
+{
+    type Value = Option<S>;
+
+    fn empty(&self) -> Option<S> {
+        None
+    }
+
+    fn leaf(&self, element: &T) -> Option<S> {
+        Some((self.0)(element))
+    }
+
+    fn combine(&self, first: &Option<S>, second: &Option<S>) -> Option<S> {
+        match (first, second) {
+            (Some(first), Some(second)) if second > first => Some(second.clone()),
+            (Some(_), _) => first.clone(),
+            (None, _) => second.clone(),
+        }
+    }
+}
+
+impl<T, F, S, Params> AggregateRTree<T, MaxImportance<F>, Params>
+where
+    T: RTreeObject,
+    F: Fn(&T) -> S,
+    S: PartialOrd + Clone,
+    Params: RTreeParams,
+{
+    /// Returns the `n` most important elements intersecting an envelope, most important first.
+    ///
+    /// Subtrees are visited in order of their maximum importance, the search stops as soon as
+    /// `n` elements are found. Subtrees containing only less important elements are never
+    /// visited, e.g. when rendering only the most important features of a map in view.
+    /// This is [AggregateRTree::top_k_in_envelope] scoring elements by their importance.
+    ///
+    /// # Example
+    /// ```
+    /// use rstar::primitives::GeomWithData;
+    /// use rstar::{AggregateRTree, MaxImportance, AABB};
+    ///
+    /// let cities = vec![
+    ///     GeomWithData::new([0.0, 0.0], 10_000),
+    ///     GeomWithData::new([1.0, 0.0], 2_000_000),
+    ///     GeomWithData::new([2.0, 0.0], 500_000),
+    ///     GeomWithData::new([9.0, 0.0], 9_000_000),
+    /// ];
+    /// let importance = |city: &GeomWithData<[f64; 2], u32>| city.data;
+    /// let mut tree = AggregateRTree::bulk_load(MaxImportance(importance), cities);
+    /// let view = AABB::from_corners([-1.0, -1.0], [3.0, 1.0]);
+    /// let largest: Vec<_> = tree
+    ///     .locate_in_envelope_top_n_by_importance(&view, 2)
+    ///     .into_iter()
+    ///     .map(|city| city.data)
+    ///     .collect();
+    /// assert_eq!(largest, vec![2_000_000, 500_000]);
+    /// ```
+    pub fn locate_in_envelope_top_n_by_importance(
+        &mut self,
+        envelope: &T::Envelope,
+        n: usize,
+    ) -> Vec<&T> {
+        self.top_k_in_envelope(envelope, n, Option::clone)
+    }
+}
+
+/// A node or element ordered by its score, or the upper bound of the scores of its elements.
+struct ScoredCandidate<'a, T, S>
+where
+    T: RTreeObject,
+{
+    score: S,
+    node: Candidate<'a, T>,
+}
+
+enum Candidate<'a, T>
+where
+    T: RTreeObject,
+{
+    /// A parent node and the index of its aggregate.
+    Parent(&'a ParentNode<T>, usize),
+    Leaf(&'a T),
+}
+
+impl<'a, T, S> PartialEq for ScoredCandidate<'a, T, S>
+where
+    T: RTreeObject,
+    S: PartialOrd,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.score == other.score
+    }
+}
+
+impl<'a, T, S> Eq for ScoredCandidate<'a, T, S>
+where
+    T: RTreeObject,
+    S: PartialOrd,
+{
+}
+
+impl<'a, T, S> PartialOrd for ScoredCandidate<'a, T, S>
+where
+    T: RTreeObject,
+    S: PartialOrd,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<'a, T, S> Ord for ScoredCandidate<'a, T, S>
+where
+    T: RTreeObject,
+    S: PartialOrd,
+{
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.score
+            .partial_cmp(&other.score)
+            .unwrap_or(Ordering::Equal)
+    }
+}
+
+/// Appends the aggregates of a node's subtree in depth first order and returns the node's
+/// aggregate.
+fn compute_aggregates<T, A>(
+    aggregator: &A,
+    node: &ParentNode<T>,
+    aggregates: &mut Vec<(A::Value, usize)>,
+) -> A::Value
+where
+    T: RTreeObject,
+    A: Aggregator<T>,
+{
+    let index = aggregates.len();
+    aggregates.push((aggregator.empty(), 0));
+    let mut value = aggregator.empty();
+    for child in &node.children {
+        let child_value = match child {
+            RTreeNode::Parent(ref data) => compute_aggregates(aggregator, data, aggregates),
+            RTreeNode::Leaf(ref t) => aggregator.leaf(t),
+        };
+        value = aggregator.combine(&value, &child_value);
+    }
+    aggregates[index] = (value.clone(), aggregates.len() - index);
+    value
+}
+
+#[cfg(test)]
+mod test {
+    use super::{AggregateRTree, Aggregator, MaxImportance};
+    use crate::test_utilities::{create_random_points, SEED_1, SEED_2};
+    use crate::{Envelope, AABB};
+
+    struct CountAndMax;
+
+    impl Aggregator<[f64; 2]> for CountAndMax {
+        type Value = (usize, f64);
+
+        fn empty(&self) -> (usize, f64) {
+            (0, f64::NEG_INFINITY)
+        }
+
+        fn leaf(&self, point: &[f64; 2]) -> (usize, f64) {
+            (1, point[0])
+        }
+
+        fn combine(&self, first: &(usize, f64), second: &(usize, f64)) -> (usize, f64) {
+            (first.0 + second.0, first.1.max(second.1))
+        }
+    }
+
+    #[test]
+    fn test_aggregate_in_envelope() {
+        let points = create_random_points(1000, SEED_1);
+        let mut tree = AggregateRTree::bulk_load(CountAndMax, points[..500].to_vec());
+        for point in &points[500..] {
+            tree.insert(*point);
+        }
+        for point in &points[..100] {
+            assert_eq!(tree.remove(point), Some(*point));
+        }
+        let remaining = &points[100..];
+        assert_eq!(tree.aggregate().0, 900);
+
+        for [x, y] in create_random_points(50, SEED_2) {
+            let envelope = AABB::from_corners([x, y], [x + 0.3, y + 0.3]);
+            let expected = remaining
+                .iter()
+                .filter(|point| envelope.contains_point(point))
+                .fold(CountAndMax.empty(), |value, point| {
+                    CountAndMax.combine(&value, &CountAndMax.leaf(point))
+                });
+            assert_eq!(tree.aggregate_in_envelope(&envelope), expected);
+        }
+    }
+
+    #[test]
+    fn test_histogram() {
+        let points = create_random_points(2000, SEED_1);
+        let mut tree = AggregateRTree::bulk_load(CountAndMax, points.clone());
+        let extent = AABB::from_corners([0.0, 0.25], [0.75, 1.0]);
+        let counts = tree.tree().histogram(extent, 6, 3);
+        let histogram = tree.histogram(extent, 6, 3);
+        assert_eq!(histogram.len(), 18);
+        for (value, count) in histogram.iter().zip(counts) {
+            assert_eq!(value.0, count);
+        }
+        for (cell, value) in histogram.iter().enumerate() {
+            let x = 0.125 * (cell % 6) as f64;
+            let expected = points
+                .iter()
+                .filter(|point| point[0] >= x && point[0] < x + 0.125)
+                .filter(|point| point[1] >= 0.25 + 0.25 * (cell / 6) as f64)
+                .filter(|point| point[1] < 0.5 + 0.25 * (cell / 6) as f64)
+                .fold(f64::NEG_INFINITY, |max, point| max.max(point[0]));
+            assert_eq!(value.1, expected);
+        }
+    }
+
+    #[test]
+    fn test_top_k_in_envelope() {
+        let points = create_random_points(1000, SEED_1);
+        let mut tree = AggregateRTree::bulk_load(CountAndMax, points.clone());
+        let score = |(count, max): &(usize, f64)| if *count > 0 { Some(*max) } else { None };
+        for [x, y] in create_random_points(30, SEED_2) {
+            let envelope = AABB::from_corners([x, y], [x + 0.3, y + 0.3]);
+            let mut expected: Vec<_> = points
+                .iter()
+                .filter(|point| envelope.contains_point(point))
+                .map(|point| point[0])
+                .collect();
+            expected.sort_by(|l, r| r.partial_cmp(l).unwrap());
+            for k in [0, 5, 2000] {
+                let found: Vec<_> = tree
+                    .top_k_in_envelope(&envelope, k, score)
+                    .into_iter()
+                    .map(|point| point[0])
+                    .collect();
+                assert_eq!(found, expected[..k.min(expected.len())]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_top_n_by_importance() {
+        let points = create_random_points(1000, SEED_1);
+        let importance = |point: &[f64; 2]| (point[0] * 7919.0).fract();
+        let mut tree = AggregateRTree::bulk_load(MaxImportance(importance), points.clone());
+        for [x, y] in create_random_points(30, SEED_2) {
+            let envelope = AABB::from_corners([x, y], [x + 0.4, y + 0.4]);
+            let mut expected: Vec<_> = points
+                .iter()
+                .filter(|point| envelope.contains_point(point))
+                .map(importance)
+                .collect();
+            expected.sort_by(|l, r| r.partial_cmp(l).unwrap());
+            for n in [0, 1, 10, 2000] {
+                let found: Vec<_> = tree
+                    .locate_in_envelope_top_n_by_importance(&envelope, n)
+                    .into_iter()
+                    .map(importance)
+                    .collect();
+                assert_eq!(found, expected[..n.min(expected.len())]);
+            }
+        }
+    }
+}
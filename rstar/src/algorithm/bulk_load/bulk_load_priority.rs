@@ -0,0 +1,93 @@
+use crate::node::{ParentNode, RTreeNode};
+use crate::object::RTreeObject;
+use crate::params::RTreeParams;
+use crate::point::Point;
+use crate::AABB;
+
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+
+/// Compares two nodes by one of the `2 * DIMENSIONS` coordinates of their envelopes.
+///
+/// Directions `0..DIMENSIONS` compare the lower corners in ascending order, the remaining
+/// directions compare the upper corners in descending order. Smaller nodes are more extreme
+/// in the given direction.
+fn compare<T, P>(direction: usize, l: &RTreeNode<T>, r: &RTreeNode<T>) -> Ordering
+where
+    T: RTreeObject<Envelope = AABB<P>>,
+    P: Point,
+{
+    let (l, r) = (l.envelope(), r.envelope());
+    if direction < P::DIMENSIONS {
+        let axis = direction;
+        l.lower()
+            .nth(axis)
+            .partial_cmp(&r.lower().nth(axis))
+            .unwrap()
+    } else {
+        let axis = direction - P::DIMENSIONS;
+        r.upper()
+            .nth(axis)
+            .partial_cmp(&l.upper().nth(axis))
+            .unwrap()
+    }
+}
+
+/// Groups nodes into parents of at most `capacity` children using a pseudo priority r-tree.
+///
+/// Every step first extracts one "priority leaf" for each direction, containing the nodes
+/// which extend farthest in this direction. The remaining nodes are split at their median in
+/// a kd-tree fashion, cycling through all directions.
+fn group_nodes<T, P>(
+    mut nodes: Vec<RTreeNode<T>>,
+    direction: usize,
+    capacity: usize,
+    result: &mut Vec<RTreeNode<T>>,
+) where
+    T: RTreeObject<Envelope = AABB<P>>,
+    P: Point,
+{
+    for priority in 0..2 * P::DIMENSIONS {
+        if nodes.len() <= capacity {
+            break;
+        }
+        nodes.select_nth_unstable_by(capacity, |l, r| compare(priority, l, r));
+        let rest = nodes.split_off(capacity);
+        result.push(RTreeNode::Parent(ParentNode::new_parent(nodes.into())));
+        nodes = rest;
+    }
+    if nodes.len() <= capacity {
+        if !nodes.is_empty() {
+            result.push(RTreeNode::Parent(ParentNode::new_parent(nodes.into())));
+        }
+        return;
+    }
+    let median = nodes.len() / 2;
+    nodes.select_nth_unstable_by(median, |l, r| compare(direction, l, r));
+    let upper_half = nodes.split_off(median);
+    let next_direction = (direction + 1) % (2 * P::DIMENSIONS);
+    group_nodes(nodes, next_direction, capacity, result);
+    group_nodes(upper_half, next_direction, capacity, result);
+}
+
+/// Bulk loads a priority r-tree (PR-tree).
+///
+/// The tree is built bottom up: every level is created by grouping the nodes of the level
+/// below with a pseudo priority r-tree. Window queries on the resulting tree are worst case
+/// optimal, at the cost of a slower construction and possibly underfull nodes.
+pub fn bulk_load_priority<T, P, Params>(elements: Vec<T>) -> ParentNode<T>
+where
+    T: RTreeObject<Envelope = AABB<P>>,
+    P: Point,
+    Params: RTreeParams,
+{
+    let mut level: Vec<_> = elements.into_iter().map(RTreeNode::Leaf).collect();
+    let mut capacity = Params::MAX_LEAF_SIZE;
+    while level.len() > capacity {
+        let mut parents = Vec::new();
+        group_nodes(level, 0, capacity, &mut parents);
+        level = parents;
+        capacity = Params::MAX_SIZE;
+    }
+    ParentNode::new_parent(level.into())
+}
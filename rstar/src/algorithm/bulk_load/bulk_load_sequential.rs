@@ -9,7 +9,9 @@ use alloc::{vec, vec::Vec};
 #[allow(unused_imports)] // Import is required when building without std
 use num_traits::Float;
 
-use super::cluster_group_iterator::{calculate_number_of_clusters_on_axis, ClusterGroupIterator};
+use super::cluster_group_iterator::{
+    calculate_depth, calculate_number_of_clusters_on_axis, ClusterGroupIterator,
+};
 
 fn bulk_load_recursive<T, Params>(elements: Vec<T>, depth: usize) -> ParentNode<T>
 where
@@ -17,10 +19,9 @@ where
     <T::Envelope as Envelope>::Point: Point,
     Params: RTreeParams,
 {
-    let m = Params::MAX_SIZE;
-    if elements.len() <= m {
+    if elements.len() <= Params::MAX_LEAF_SIZE {
         // Reached leaf level
-        let elements: Vec<_> = elements.into_iter().map(RTreeNode::Leaf).collect();
+        let elements = elements.into_iter().map(RTreeNode::Leaf).collect();
         return ParentNode::new_parent(elements);
     }
     let number_of_clusters_on_axis =
@@ -95,8 +96,7 @@ where
     <T::Envelope as Envelope>::Point: Point,
     Params: RTreeParams,
 {
-    let m = Params::MAX_SIZE;
-    let depth = (elements.len() as f32).log(m as f32).ceil() as usize;
+    let depth = calculate_depth::<Params>(elements.len());
     bulk_load_recursive::<_, Params>(elements, depth)
 }
 
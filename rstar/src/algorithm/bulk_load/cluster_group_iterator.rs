@@ -44,6 +44,21 @@ impl<T: RTreeObject> Iterator for ClusterGroupIterator<T> {
     }
 }
 
+/// Calculates the depth of a bulk loaded tree, assuming all nodes will be filled up to
+/// `MAX_SIZE` and all leaf nodes up to `MAX_LEAF_SIZE`.
+pub fn calculate_depth<Params>(number_of_elements: usize) -> usize
+where
+    Params: RTreeParams,
+{
+    let mut depth = 1;
+    let mut capacity = Params::MAX_LEAF_SIZE;
+    while capacity < number_of_elements {
+        capacity = capacity.saturating_mul(Params::MAX_SIZE);
+        depth += 1;
+    }
+    depth
+}
+
 /// Calculates the desired number of clusters on any axis
 ///
 /// A 'cluster' refers to a set of elements that will finally form an rtree node.
@@ -52,11 +67,9 @@ where
     T: RTreeObject,
     Params: RTreeParams,
 {
-    let max_size = Params::MAX_SIZE as f32;
-    // The depth of the resulting tree, assuming all leaf nodes will be filled up to MAX_SIZE
-    let depth = (number_of_elements as f32).log(max_size).ceil() as usize;
+    let depth = calculate_depth::<Params>(number_of_elements);
     // The number of elements each subtree will hold
-    let n_subtree = max_size.powi(depth as i32 - 1);
+    let n_subtree = Params::MAX_LEAF_SIZE as f32 * (Params::MAX_SIZE as f32).powi(depth as i32 - 2);
     // How many clusters will this node contain
     let number_of_clusters = (number_of_elements as f32 / n_subtree).ceil();
 
@@ -1,4 +1,6 @@
+mod bulk_load_priority;
 mod bulk_load_sequential;
 mod cluster_group_iterator;
 
+pub use self::bulk_load_priority::bulk_load_priority;
 pub use self::bulk_load_sequential::bulk_load_sequential;
@@ -0,0 +1,188 @@
+use crate::algorithm::nearest_neighbor::{extend_heap, RTreeNodeDistanceWrapper};
+use crate::algorithm::selection_functions::SelectionFunction;
+use crate::node::{ParentNode, RTreeNode};
+use crate::point::Point;
+use crate::{Envelope, PointDistance, RTreeObject};
+
+#[cfg(doc)]
+use crate::RTree;
+
+use alloc::collections::BinaryHeap;
+use alloc::vec::Vec;
+
+/// A selection query that can be paused and resumed, returned by [`RTree::locate_cursor`].
+///
+/// Unlike an iterator, a cursor limits the work done per call rather than the number of
+/// results: every call to [`LocateCursor::poll`] visits at most a given number of nodes and
+/// elements. This allows to spread a large query over several frames of a UI or game loop
+/// without blocking a single frame for long, even if only few elements are selected.
+pub struct LocateCursor<'a, T, Func>
+where
+    T: RTreeObject + 'a,
+    Func: SelectionFunction<T>,
+{
+    func: Func,
+    current_nodes: Vec<(&'a RTreeNode<T>, usize)>,
+}
+
+impl<'a, T, Func> LocateCursor<'a, T, Func>
+where
+    T: RTreeObject,
+    Func: SelectionFunction<T>,
+{
+    pub(crate) fn new(root: &'a ParentNode<T>, func: Func) -> Self {
+        let current_nodes = if func.should_unpack_parent_at_depth(&root.envelope, 0) {
+            root.children.iter().map(|child| (child, 1)).collect()
+        } else {
+            Vec::new()
+        };
+        LocateCursor {
+            func,
+            current_nodes,
+        }
+    }
+
+    /// Continues the query, visiting at most `budget` nodes and elements.
+    ///
+    /// All selected elements are added to `results`. Returns `true` once the query is
+    /// complete, further calls won't return any results.
+    pub fn poll(&mut self, budget: usize, results: &mut Vec<&'a T>) -> bool {
+        for _ in 0..budget {
+            match self.current_nodes.pop() {
+                Some((RTreeNode::Leaf(ref t), _)) => {
+                    if self.func.should_unpack_leaf(t) {
+                        results.push(t);
+                    }
+                }
+                Some((RTreeNode::Parent(ref data), depth)) => {
+                    if self
+                        .func
+                        .should_unpack_parent_at_depth(&data.envelope, depth)
+                    {
+                        self.current_nodes
+                            .extend(data.children.iter().map(|child| (child, depth + 1)));
+                    }
+                }
+                None => break,
+            }
+        }
+        self.is_finished()
+    }
+
+    /// Returns `true` if the query is complete.
+    pub fn is_finished(&self) -> bool {
+        self.current_nodes.is_empty()
+    }
+}
+
+/// A nearest neighbor query that can be paused and resumed, returned by
+/// [`RTree::nearest_neighbor_cursor`].
+///
+/// See [`LocateCursor`] for details. The elements are returned nearest first, in the same order
+/// as [`RTree::nearest_neighbor_iter_with_distance_2`].
+pub struct NearestNeighborCursor<'a, T>
+where
+    T: PointDistance + 'a,
+{
+    nodes: BinaryHeap<RTreeNodeDistanceWrapper<'a, T>>,
+    query_point: <T::Envelope as Envelope>::Point,
+}
+
+impl<'a, T> NearestNeighborCursor<'a, T>
+where
+    T: PointDistance,
+{
+    pub(crate) fn new(
+        root: &'a ParentNode<T>,
+        query_point: <T::Envelope as Envelope>::Point,
+    ) -> Self {
+        let mut nodes = BinaryHeap::new();
+        extend_heap(&mut nodes, &root.children, &query_point);
+        NearestNeighborCursor { nodes, query_point }
+    }
+
+    /// Continues the query, visiting at most `budget` nodes and elements.
+    ///
+    /// The found elements are added to `results` together with their squared distance to the
+    /// query point. Returns `true` once all elements have been returned.
+    pub fn poll(
+        &mut self,
+        budget: usize,
+        results: &mut Vec<(&'a T, <<T::Envelope as Envelope>::Point as Point>::Scalar)>,
+    ) -> bool {
+        for _ in 0..budget {
+            match self.nodes.pop() {
+                Some(RTreeNodeDistanceWrapper {
+                    node: RTreeNode::Parent(ref data),
+                    ..
+                }) => extend_heap(&mut self.nodes, &data.children, &self.query_point),
+                Some(RTreeNodeDistanceWrapper {
+                    node: RTreeNode::Leaf(ref t),
+                    distance,
+                }) => results.push((t, distance)),
+                None => break,
+            }
+        }
+        self.is_finished()
+    }
+
+    /// Returns `true` if all elements have been returned.
+    pub fn is_finished(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Returns a lower bound for the squared distance of all elements that have not been
+    /// returned yet, or `None` if the query is complete.
+    ///
+    /// Useful to stop a query once all elements within a certain distance have been found.
+    pub fn min_distance_2(&self) -> Option<<<T::Envelope as Envelope>::Point as Point>::Scalar> {
+        self.nodes.peek().map(|node| node.distance)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::aabb::AABB;
+    use crate::algorithm::selection_functions::SelectInEnvelopeFunction;
+    use crate::test_utilities::{create_random_points, SEED_1, SEED_2};
+    use crate::RTree;
+
+    #[test]
+    fn test_locate_cursor() {
+        let tree = RTree::bulk_load(create_random_points(1000, SEED_1));
+        let envelope = AABB::from_corners([0.2, 0.2], [0.7, 0.6]);
+        let mut cursor = tree.locate_cursor(SelectInEnvelopeFunction::new(envelope));
+        let mut found = Vec::new();
+        let mut polls = 0;
+        while !cursor.poll(10, &mut found) {
+            polls += 1;
+            assert!(found.len() <= 10 * polls);
+        }
+        assert!(polls > 10);
+        let count = found.len();
+        assert!(cursor.poll(10, &mut found));
+        assert_eq!(found.len(), count);
+        let mut expected: Vec<_> = tree.locate_in_envelope(&envelope).collect();
+        expected.sort_by(|l, r| l.partial_cmp(r).unwrap());
+        found.sort_by(|l, r| l.partial_cmp(r).unwrap());
+        assert_eq!(found, expected);
+    }
+
+    #[test]
+    fn test_nearest_neighbor_cursor() {
+        let tree = RTree::bulk_load(create_random_points(1000, SEED_1));
+        for query in create_random_points(10, SEED_2) {
+            let mut cursor = tree.nearest_neighbor_cursor(&query);
+            let mut found = Vec::new();
+            while !cursor.poll(7, &mut found) {
+                if let (Some(min), Some((_, last))) = (cursor.min_distance_2(), found.last()) {
+                    assert!(*last <= min);
+                }
+            }
+            assert_eq!(cursor.min_distance_2(), None);
+            assert!(found
+                .into_iter()
+                .eq(tree.nearest_neighbor_iter_with_distance_2(&query)));
+        }
+    }
+}
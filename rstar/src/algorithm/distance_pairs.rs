@@ -0,0 +1,178 @@
+use crate::node::{ParentNode, RTreeNode};
+use crate::params::RTreeParams;
+use crate::point::Point;
+use crate::{RTree, RTreeObject, AABB};
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// A pair of nodes whose elements still need to be paired up.
+enum PendingPair<'a, T>
+where
+    T: RTreeObject,
+{
+    /// All pairs of distinct elements within a single node.
+    Within(&'a ParentNode<T>),
+    /// All pairs of one element from each node.
+    Between(&'a RTreeNode<T>, &'a RTreeNode<T>),
+}
+
+/// Iterator returned by [`RTree::all_pairs_within_distance`].
+pub struct PairsWithinDistanceIterator<'a, T, P>
+where
+    T: RTreeObject<Envelope = AABB<P>>,
+    P: Point,
+{
+    todo_list: Vec<PendingPair<'a, T>>,
+    max_squared_distance: P::Scalar,
+}
+
+impl<'a, T, P> PairsWithinDistanceIterator<'a, T, P>
+where
+    T: RTreeObject<Envelope = AABB<P>>,
+    P: Point,
+{
+    pub(crate) fn new(root: &'a ParentNode<T>, max_squared_distance: P::Scalar) -> Self {
+        PairsWithinDistanceIterator {
+            todo_list: vec![PendingPair::Within(root)],
+            max_squared_distance,
+        }
+    }
+
+    fn push_if_close(&mut self, node1: &'a RTreeNode<T>, node2: &'a RTreeNode<T>) {
+        if node1.envelope().distance_2_to_aabb(&node2.envelope()) <= self.max_squared_distance {
+            self.todo_list.push(PendingPair::Between(node1, node2));
+        }
+    }
+}
+
+impl<'a, T, P> Iterator for PairsWithinDistanceIterator<'a, T, P>
+where
+    T: RTreeObject<Envelope = AABB<P>>,
+    P: Point,
+{
+    type Item = (&'a T, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(next) = self.todo_list.pop() {
+            match next {
+                PendingPair::Within(parent) => {
+                    let children = parent.children();
+                    for (index, child1) in children.iter().enumerate() {
+                        if let RTreeNode::Parent(ref data) = child1 {
+                            self.todo_list.push(PendingPair::Within(data));
+                        }
+                        for child2 in &children[index + 1..] {
+                            self.push_if_close(child1, child2);
+                        }
+                    }
+                }
+                PendingPair::Between(RTreeNode::Leaf(t1), RTreeNode::Leaf(t2)) => {
+                    return Some((t1, t2))
+                }
+                PendingPair::Between(leaf @ RTreeNode::Leaf(_), RTreeNode::Parent(p)) => {
+                    for child in p.children() {
+                        self.push_if_close(leaf, child);
+                    }
+                }
+                PendingPair::Between(RTreeNode::Parent(p), leaf @ RTreeNode::Leaf(_)) => {
+                    for child in p.children() {
+                        self.push_if_close(child, leaf);
+                    }
+                }
+                PendingPair::Between(RTreeNode::Parent(p1), RTreeNode::Parent(p2)) => {
+                    for child1 in p1.children() {
+                        for child2 in p2.children() {
+                            self.push_if_close(child1, child2);
+                        }
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+impl<T, P, Params> RTree<T, Params>
+where
+    T: RTreeObject<Envelope = AABB<P>>,
+    P: Point,
+    Params: RTreeParams,
+{
+    /// Returns all pairs of distinct elements whose envelopes are at most a given distance
+    /// apart.
+    ///
+    /// The distance between two elements is the distance between the closest points of their
+    /// envelopes, which is the exact distance for points. Every unordered pair is returned
+    /// exactly once, in arbitrary order.
+    ///
+    /// Unlike calling [RTree::locate_within_distance] for every element, this traverses the
+    /// tree only once by descending into pairs of nodes that are close enough.
+    ///
+    /// # Example
+    /// ```
+    /// use rstar::RTree;
+    ///
+    /// let tree = RTree::bulk_load(vec![[0.0, 0.0], [1.0, 0.0], [1.5, 0.0], [5.0, 0.0]]);
+    /// // Pairs at most 1.0 apart, the distance is given squared.
+    /// assert_eq!(tree.all_pairs_within_distance(1.0 * 1.0).count(), 2);
+    /// ```
+    pub fn all_pairs_within_distance(
+        &self,
+        max_squared_distance: P::Scalar,
+    ) -> PairsWithinDistanceIterator<'_, T, P> {
+        PairsWithinDistanceIterator::new(self.root(), max_squared_distance)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::point::PointExt;
+    use crate::test_utilities::{create_random_points, create_random_rectangles, SEED_1, SEED_2};
+    use crate::{RTree, RTreeObject};
+
+    #[test]
+    fn test_all_pairs_within_distance() {
+        let points = create_random_points(1000, SEED_1);
+        let tree = RTree::bulk_load(points.clone());
+        let max_squared_distance = 0.02 * 0.02;
+        let ordered = |p1: &[f64; 2], p2: &[f64; 2]| if p1 < p2 { (*p1, *p2) } else { (*p2, *p1) };
+        let mut expected = Vec::new();
+        for (index, p1) in points.iter().enumerate() {
+            for p2 in &points[index + 1..] {
+                if p1.distance_2(p2) <= max_squared_distance {
+                    expected.push(ordered(p1, p2));
+                }
+            }
+        }
+        let mut found: Vec<_> = tree
+            .all_pairs_within_distance(max_squared_distance)
+            .map(|(p1, p2)| ordered(p1, p2))
+            .collect();
+        expected.sort_by(|l, r| l.partial_cmp(r).unwrap());
+        found.sort_by(|l, r| l.partial_cmp(r).unwrap());
+        assert!(!expected.is_empty());
+        assert_eq!(found, expected);
+
+        let rectangles = create_random_rectangles(300, SEED_2);
+        let tree = RTree::bulk_load(rectangles.clone());
+        let expected = rectangles
+            .iter()
+            .enumerate()
+            .flat_map(|(index, r1)| rectangles[index + 1..].iter().map(move |r2| (r1, r2)))
+            .filter(|(r1, r2)| {
+                r1.envelope().distance_2_to_aabb(&r2.envelope()) <= max_squared_distance
+            })
+            .count();
+        assert_eq!(
+            tree.all_pairs_within_distance(max_squared_distance).count(),
+            expected
+        );
+        assert_eq!(
+            RTree::<[f64; 2]>::new()
+                .all_pairs_within_distance(1.0)
+                .count(),
+            0
+        );
+    }
+}
@@ -5,10 +5,23 @@ use crate::object::RTreeObject;
 #[cfg(doc)]
 use crate::RTree;
 
+use alloc::collections::VecDeque;
+use core::iter::FusedIterator;
 use smallvec::SmallVec;
 
+pub use super::cursor::{LocateCursor, NearestNeighborCursor};
+pub use super::distance_pairs::PairsWithinDistanceIterator;
 pub use super::intersection_iterator::IntersectionIterator;
+pub use super::nearest_neighbor::{IntoNearestNeighborIterator, NearestNeighborStateIterator};
 pub use super::removal::DrainIterator;
+pub use super::weighted_nearest_neighbor::WeightedNearestNeighborIterator;
+pub use crate::arena::{ArenaNearestNeighborIterator, ArenaSelectionIterator};
+pub use crate::bounded::BoundedSelectionIterator;
+pub use crate::forest::ForestNearestNeighborIterator;
+pub use crate::frozen::{FrozenNearestNeighborIterator, FrozenSelectionIterator};
+pub use crate::packed::{PackedNearestNeighborIterator, PackedSelectionIterator};
+#[cfg(feature = "rayon")]
+pub use crate::parallel::ParIter;
 
 /// Iterator returned by [`RTree::locate_all_at_point`].
 pub type LocateAllAtPoint<'a, T> = SelectionIterator<'a, T, SelectAtPointFunction<T>>;
@@ -27,10 +40,9 @@ pub type LocateInEnvelopeIntersecting<'a, T> =
 pub type LocateInEnvelopeIntersectingMut<'a, T> =
     SelectionIteratorMut<'a, T, SelectInEnvelopeFuncIntersecting<T>>;
 
-/// Iterator returned by [`RTree::iter`].
-pub type RTreeIterator<'a, T> = SelectionIterator<'a, T, SelectAllFunc>;
-/// Iterator returned by [`RTree::iter_mut`].
-pub type RTreeIteratorMut<'a, T> = SelectionIteratorMut<'a, T, SelectAllFunc>;
+/// Iterator returned by [`RTree::locate_in_envelope_intersecting_exact`].
+pub type LocateInEnvelopeIntersectingExact<'a, T> =
+    SelectionIterator<'a, T, SelectInEnvelopeFuncIntersectingExact<T>>;
 
 /// Iterator returned by [`RTree::locate_within_distance`].
 pub type LocateWithinDistanceIterator<'a, T> =
@@ -43,7 +55,8 @@ where
     Func: SelectionFunction<T>,
 {
     func: Func,
-    current_nodes: SmallVec<[&'a RTreeNode<T>; 24]>,
+    current_nodes: SmallVec<[(&'a RTreeNode<T>, usize); 24]>,
+    max_remaining: usize,
 }
 
 impl<'a, T, Func> SelectionIterator<'a, T, Func>
@@ -52,15 +65,20 @@ where
     Func: SelectionFunction<T>,
 {
     pub(crate) fn new(root: &'a ParentNode<T>, func: Func) -> Self {
-        let current_nodes = if func.should_unpack_parent(&root.envelope()) {
-            root.children.iter().collect()
-        } else {
-            SmallVec::new()
-        };
+        let (current_nodes, max_remaining) =
+            if func.should_unpack_parent_at_depth(&root.envelope, 0) {
+                (
+                    root.children.iter().map(|child| (child, 1)).collect(),
+                    root.size,
+                )
+            } else {
+                (SmallVec::new(), 0)
+            };
 
         SelectionIterator {
             func,
             current_nodes,
+            max_remaining,
         }
     }
 }
@@ -73,22 +91,33 @@ where
     type Item = &'a T;
 
     fn next(&mut self) -> Option<&'a T> {
-        while let Some(next) = self.current_nodes.pop() {
+        while let Some((next, depth)) = self.current_nodes.pop() {
             match next {
                 RTreeNode::Leaf(ref t) => {
+                    self.max_remaining -= 1;
                     if self.func.should_unpack_leaf(t) {
                         return Some(t);
                     }
                 }
                 RTreeNode::Parent(ref data) => {
-                    if self.func.should_unpack_parent(&data.envelope) {
-                        self.current_nodes.extend(&data.children);
+                    if self
+                        .func
+                        .should_unpack_parent_at_depth(&data.envelope, depth)
+                    {
+                        self.current_nodes
+                            .extend(data.children.iter().map(|child| (child, depth + 1)));
+                    } else {
+                        self.max_remaining -= data.size;
                     }
                 }
             }
         }
         None
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.max_remaining))
+    }
 }
 
 /// Iterator type returned by `RTree::locate_*_mut` methods.
@@ -98,7 +127,8 @@ where
     Func: SelectionFunction<T>,
 {
     func: Func,
-    current_nodes: SmallVec<[&'a mut RTreeNode<T>; 32]>,
+    current_nodes: SmallVec<[(&'a mut RTreeNode<T>, usize); 32]>,
+    max_remaining: usize,
 }
 
 impl<'a, T, Func> SelectionIteratorMut<'a, T, Func>
@@ -107,15 +137,20 @@ where
     Func: SelectionFunction<T>,
 {
     pub(crate) fn new(root: &'a mut ParentNode<T>, func: Func) -> Self {
-        let current_nodes = if func.should_unpack_parent(&root.envelope()) {
-            root.children.iter_mut().collect()
-        } else {
-            SmallVec::new()
-        };
+        let (current_nodes, max_remaining) =
+            if func.should_unpack_parent_at_depth(&root.envelope, 0) {
+                (
+                    root.children.iter_mut().map(|child| (child, 1)).collect(),
+                    root.size,
+                )
+            } else {
+                (SmallVec::new(), 0)
+            };
 
         SelectionIteratorMut {
             func,
             current_nodes,
+            max_remaining,
         }
     }
 }
@@ -128,16 +163,168 @@ where
     type Item = &'a mut T;
 
     fn next(&mut self) -> Option<&'a mut T> {
-        while let Some(next) = self.current_nodes.pop() {
+        while let Some((next, depth)) = self.current_nodes.pop() {
             match next {
                 RTreeNode::Leaf(ref mut t) => {
+                    self.max_remaining -= 1;
                     if self.func.should_unpack_leaf(t) {
                         return Some(t);
                     }
                 }
                 RTreeNode::Parent(ref mut data) => {
-                    if self.func.should_unpack_parent(&data.envelope) {
-                        self.current_nodes.extend(&mut data.children);
+                    if self
+                        .func
+                        .should_unpack_parent_at_depth(&data.envelope, depth)
+                    {
+                        self.current_nodes
+                            .extend(data.children.iter_mut().map(|child| (child, depth + 1)));
+                    } else {
+                        self.max_remaining -= data.size;
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.max_remaining))
+    }
+}
+
+/// Iterator returned by [`RTree::iter`].
+pub struct RTreeIterator<'a, T>
+where
+    T: RTreeObject + 'a,
+{
+    /// The nodes still to visit. [Iterator::next] takes nodes from the back and
+    /// [DoubleEndedIterator::next_back] from the front.
+    nodes: VecDeque<&'a RTreeNode<T>>,
+    remaining: usize,
+}
+
+impl<'a, T> RTreeIterator<'a, T>
+where
+    T: RTreeObject,
+{
+    pub(crate) fn new(root: &'a ParentNode<T>) -> Self {
+        RTreeIterator {
+            nodes: root.children.iter().collect(),
+            remaining: root.size,
+        }
+    }
+}
+
+impl<'a, T> Iterator for RTreeIterator<'a, T>
+where
+    T: RTreeObject,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        while let Some(node) = self.nodes.pop_back() {
+            match node {
+                RTreeNode::Leaf(ref t) => {
+                    self.remaining -= 1;
+                    return Some(t);
+                }
+                RTreeNode::Parent(ref data) => self.nodes.extend(&data.children),
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for RTreeIterator<'a, T>
+where
+    T: RTreeObject,
+{
+    fn next_back(&mut self) -> Option<&'a T> {
+        while let Some(node) = self.nodes.pop_front() {
+            match node {
+                RTreeNode::Leaf(ref t) => {
+                    self.remaining -= 1;
+                    return Some(t);
+                }
+                RTreeNode::Parent(ref data) => {
+                    for child in data.children.iter().rev() {
+                        self.nodes.push_front(child);
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+impl<'a, T> ExactSizeIterator for RTreeIterator<'a, T> where T: RTreeObject {}
+
+impl<'a, T> FusedIterator for RTreeIterator<'a, T> where T: RTreeObject {}
+
+/// Iterator returned by [`RTree::iter_mut`].
+pub struct RTreeIteratorMut<'a, T>
+where
+    T: RTreeObject + 'a,
+{
+    /// See [RTreeIterator].
+    nodes: VecDeque<&'a mut RTreeNode<T>>,
+    remaining: usize,
+}
+
+impl<'a, T> RTreeIteratorMut<'a, T>
+where
+    T: RTreeObject,
+{
+    pub(crate) fn new(root: &'a mut ParentNode<T>) -> Self {
+        RTreeIteratorMut {
+            nodes: root.children.iter_mut().collect(),
+            remaining: root.size,
+        }
+    }
+}
+
+impl<'a, T> Iterator for RTreeIteratorMut<'a, T>
+where
+    T: RTreeObject,
+{
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<&'a mut T> {
+        while let Some(node) = self.nodes.pop_back() {
+            match node {
+                RTreeNode::Leaf(ref mut t) => {
+                    self.remaining -= 1;
+                    return Some(t);
+                }
+                RTreeNode::Parent(ref mut data) => self.nodes.extend(&mut data.children),
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for RTreeIteratorMut<'a, T>
+where
+    T: RTreeObject,
+{
+    fn next_back(&mut self) -> Option<&'a mut T> {
+        while let Some(node) = self.nodes.pop_front() {
+            match node {
+                RTreeNode::Leaf(ref mut t) => {
+                    self.remaining -= 1;
+                    return Some(t);
+                }
+                RTreeNode::Parent(ref mut data) => {
+                    for child in data.children.iter_mut().rev() {
+                        self.nodes.push_front(child);
                     }
                 }
             }
@@ -146,6 +333,209 @@ where
     }
 }
 
+impl<'a, T> ExactSizeIterator for RTreeIteratorMut<'a, T> where T: RTreeObject {}
+
+impl<'a, T> FusedIterator for RTreeIteratorMut<'a, T> where T: RTreeObject {}
+
+/// Iterator returned by [`RTree::iter_leaf_nodes`].
+pub struct LeafNodeIterator<'a, T>
+where
+    T: RTreeObject + 'a,
+{
+    current_nodes: SmallVec<[&'a ParentNode<T>; 24]>,
+}
+
+impl<'a, T> LeafNodeIterator<'a, T>
+where
+    T: RTreeObject,
+{
+    pub(crate) fn new(root: &'a ParentNode<T>) -> Self {
+        let mut current_nodes = SmallVec::new();
+        if !root.children.is_empty() {
+            current_nodes.push(root);
+        }
+        LeafNodeIterator { current_nodes }
+    }
+}
+
+impl<'a, T> Iterator for LeafNodeIterator<'a, T>
+where
+    T: RTreeObject,
+{
+    type Item = LeafNode<'a, T>;
+
+    fn next(&mut self) -> Option<LeafNode<'a, T>> {
+        while let Some(node) = self.current_nodes.pop() {
+            // All leaves of a tree have the same depth, a node contains either only leaves or
+            // only parents.
+            match node.children.first() {
+                Some(RTreeNode::Parent(_)) => {
+                    self.current_nodes
+                        .extend(node.children.iter().rev().filter_map(|child| match child {
+                            RTreeNode::Parent(ref data) => Some(data),
+                            RTreeNode::Leaf(_) => None,
+                        }))
+                }
+                _ => return Some(LeafNode { node }),
+            }
+        }
+        None
+    }
+}
+
+/// The elements of a node at the lowest level of a tree, see [`RTree::iter_leaf_nodes`].
+///
+/// The elements of a leaf node are close to each other and stored next to each other in memory.
+pub struct LeafNode<'a, T>
+where
+    T: RTreeObject,
+{
+    node: &'a ParentNode<T>,
+}
+
+impl<'a, T> Clone for LeafNode<'a, T>
+where
+    T: RTreeObject,
+{
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, T> Copy for LeafNode<'a, T> where T: RTreeObject {}
+
+impl<'a, T> LeafNode<'a, T>
+where
+    T: RTreeObject,
+{
+    /// Returns the smallest envelope that encompasses all elements of this node.
+    pub fn envelope(&self) -> T::Envelope {
+        self.node.envelope()
+    }
+
+    /// Returns the number of elements of this node.
+    pub fn len(&self) -> usize {
+        self.node.children.len()
+    }
+
+    /// Returns `true` if this node contains no elements.
+    ///
+    /// Only the leaf node of an empty tree is empty, [`RTree::iter_leaf_nodes`] never
+    /// returns it.
+    pub fn is_empty(&self) -> bool {
+        self.node.children.is_empty()
+    }
+
+    /// Returns the element at `index`, or `None` if it is out of bounds.
+    pub fn get(&self, index: usize) -> Option<&'a T> {
+        match self.node.children.get(index) {
+            Some(RTreeNode::Leaf(ref t)) => Some(t),
+            _ => None,
+        }
+    }
+
+    /// Returns an iterator over the elements of this node, in storage order.
+    pub fn iter(&self) -> LeafNodeElements<'a, T> {
+        LeafNodeElements {
+            children: self.node.children.iter(),
+        }
+    }
+
+    /// Returns the node containing the elements.
+    pub fn node(&self) -> &'a ParentNode<T> {
+        self.node
+    }
+}
+
+impl<'a, T> IntoIterator for LeafNode<'a, T>
+where
+    T: RTreeObject,
+{
+    type Item = &'a T;
+    type IntoIter = LeafNodeElements<'a, T>;
+
+    fn into_iter(self) -> LeafNodeElements<'a, T> {
+        self.iter()
+    }
+}
+
+/// Iterator returned by [`LeafNode::iter`].
+pub struct LeafNodeElements<'a, T>
+where
+    T: RTreeObject,
+{
+    children: core::slice::Iter<'a, RTreeNode<T>>,
+}
+
+fn unwrap_leaf<T: RTreeObject>(child: &RTreeNode<T>) -> &T {
+    match child {
+        RTreeNode::Leaf(ref t) => t,
+        RTreeNode::Parent(_) => unreachable!("Leaf nodes contain only elements"),
+    }
+}
+
+impl<'a, T> Iterator for LeafNodeElements<'a, T>
+where
+    T: RTreeObject,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        self.children.next().map(unwrap_leaf)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.children.size_hint()
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for LeafNodeElements<'a, T>
+where
+    T: RTreeObject,
+{
+    fn next_back(&mut self) -> Option<&'a T> {
+        self.children.next_back().map(unwrap_leaf)
+    }
+}
+
+impl<'a, T> ExactSizeIterator for LeafNodeElements<'a, T> where T: RTreeObject {}
+
+/// Iterator returned by [`RTree::iter_nodes_breadth_first`].
+pub struct BreadthFirstNodeIterator<'a, T>
+where
+    T: RTreeObject + 'a,
+{
+    queue: VecDeque<(usize, &'a ParentNode<T>)>,
+}
+
+impl<'a, T> BreadthFirstNodeIterator<'a, T>
+where
+    T: RTreeObject,
+{
+    pub(crate) fn new(root: &'a ParentNode<T>) -> Self {
+        let mut queue = VecDeque::new();
+        queue.push_back((0, root));
+        BreadthFirstNodeIterator { queue }
+    }
+}
+
+impl<'a, T> Iterator for BreadthFirstNodeIterator<'a, T>
+where
+    T: RTreeObject,
+{
+    type Item = (usize, &'a ParentNode<T>);
+
+    fn next(&mut self) -> Option<(usize, &'a ParentNode<T>)> {
+        let (depth, node) = self.queue.pop_front()?;
+        self.queue
+            .extend(node.children.iter().filter_map(|child| match child {
+                RTreeNode::Parent(ref data) => Some((depth + 1, data)),
+                RTreeNode::Leaf(_) => None,
+            }));
+        Some((depth, node))
+    }
+}
+
 #[cfg(test)]
 mod test {
     use crate::aabb::AABB;
@@ -304,4 +694,120 @@ mod test {
             assert!(located.contains(point));
         }
     }
+
+    #[test]
+    fn test_iter_leaf_nodes() {
+        use crate::params::{DefaultParams, RTreeParams};
+
+        assert_eq!(RTree::<[f64; 2]>::new().iter_leaf_nodes().count(), 0);
+
+        let points = create_random_points(1000, SEED_1);
+        let mut tree = RTree::new();
+        for p in &points {
+            tree.insert(*p);
+        }
+        let mut found = Vec::new();
+        for leaf in tree.iter_leaf_nodes() {
+            assert!(!leaf.is_empty() && leaf.len() <= DefaultParams::MAX_SIZE);
+            assert_eq!(leaf.iter().len(), leaf.len());
+            assert_eq!(leaf.get(leaf.len() - 1), leaf.iter().next_back());
+            assert_eq!(leaf.get(leaf.len()), None);
+            for p in leaf {
+                assert!(leaf.envelope().contains_point(p));
+                found.push(*p);
+            }
+        }
+        let mut expected = points;
+        expected.sort_by(|l, r| l.partial_cmp(r).unwrap());
+        found.sort_by(|l, r| l.partial_cmp(r).unwrap());
+        assert_eq!(found, expected);
+
+        let small = RTree::bulk_load(vec![[0.0, 0.0], [1.0, 1.0]]);
+        let leaves: Vec<_> = small.iter_leaf_nodes().collect();
+        assert_eq!(leaves.len(), 1);
+        assert_eq!(leaves[0].envelope(), small.root().envelope());
+    }
+
+    #[test]
+    fn test_iter_nodes_breadth_first() {
+        let empty = RTree::<[f64; 2]>::new();
+        let nodes: Vec<_> = empty.iter_nodes_breadth_first().collect();
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(nodes[0].0, 0);
+
+        let tree = RTree::bulk_load(create_random_points(1000, SEED_1));
+        let mut previous_depth = 0;
+        let mut sizes = Vec::new();
+        for (depth, node) in tree.iter_nodes_breadth_first() {
+            assert!(depth == previous_depth || depth == previous_depth + 1);
+            previous_depth = depth;
+            if sizes.len() == depth {
+                sizes.push(0);
+            }
+            sizes[depth] += node.size();
+        }
+        // Every level contains all elements.
+        assert!(sizes.len() > 2);
+        assert!(sizes.iter().all(|&size| size == 1000));
+        let leaf_depth = sizes.len() - 1;
+        let leaves = tree
+            .iter_nodes_breadth_first()
+            .filter(|&(depth, _)| depth == leaf_depth)
+            .count();
+        assert_eq!(leaves, tree.iter_leaf_nodes().count());
+    }
+
+    #[test]
+    fn test_iterator_sizes() {
+        let points = create_random_points(1000, SEED_1);
+        let mut tree = RTree::bulk_load(points);
+        let envelope = AABB::from_corners([0.0, 0.0], [0.5, 0.5]);
+        assert_eq!(RTree::<[f64; 2]>::new().iter().len(), 0);
+
+        let last = *tree.iter().next_back().unwrap();
+        assert_eq!(tree.iter_mut().rev().len(), 1000);
+        assert_eq!(tree.iter_mut().next_back().copied(), Some(last));
+        let mut located = tree.locate_in_envelope_mut(&envelope);
+        assert_eq!(located.size_hint(), (0, Some(1000)));
+        // The bound shrinks as soon as subtrees are skipped.
+        located.next();
+        assert!(located.size_hint().1.unwrap() < 1000);
+        drop(located);
+
+        let mut iter = tree.iter();
+        assert_eq!(iter.len(), 1000);
+        iter.next();
+        iter.next_back();
+        assert_eq!(iter.len(), 998);
+        assert_eq!(iter.count(), 998);
+
+        let forward: Vec<_> = tree.iter().collect();
+        let mut backward: Vec<_> = tree.iter().rev().collect();
+        backward.reverse();
+        assert_eq!(forward, backward);
+        // Iterate from both ends until they meet.
+        let mut iter = tree.iter();
+        let mut both = Vec::new();
+        while let Some(front) = iter.next() {
+            both.push(front);
+            both.extend(iter.next_back());
+        }
+        assert_eq!(both.len(), 1000);
+        let mut sorted = forward.clone();
+        sorted.sort_by(|l, r| l.partial_cmp(r).unwrap());
+        both.sort_by(|l, r| l.partial_cmp(r).unwrap());
+        assert_eq!(both, sorted);
+        let mut iter = tree.iter();
+        let back: Vec<_> = iter.by_ref().rev().take(500).collect();
+        assert_eq!(back.len() + iter.count(), 1000);
+
+        let mut located = tree.locate_in_envelope(&envelope);
+        located.next();
+        let (lower, upper) = located.size_hint();
+        assert_eq!(lower, 0);
+        let upper = upper.unwrap();
+        let count = located.by_ref().count();
+        assert!(count <= upper && upper < 1000);
+        assert_eq!(located.size_hint(), (0, Some(0)));
+    }
 }
@@ -1,4 +1,6 @@
 pub mod bulk_load;
+pub mod cursor;
+pub mod distance_pairs;
 pub mod intersection_iterator;
 /// Iterator types
 pub mod iterators;
@@ -6,3 +8,5 @@ pub mod nearest_neighbor;
 pub mod removal;
 pub mod rstar;
 pub mod selection_functions;
+pub mod split;
+pub mod weighted_nearest_neighbor;
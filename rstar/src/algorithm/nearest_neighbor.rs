@@ -1,17 +1,34 @@
-use crate::node::{ParentNode, RTreeNode};
+use crate::node::{Children, ParentNode, RTreeNode};
 use crate::point::{min_inline, Point};
 use crate::{Envelope, PointDistance, RTreeObject};
 
 use alloc::{collections::BinaryHeap, vec, vec::Vec};
+use core::cmp::Ordering;
 use heapless::binary_heap as static_heap;
 use num_traits::Bounded;
 
-struct RTreeNodeDistanceWrapper<'a, T>
+pub(crate) struct RTreeNodeDistanceWrapper<'a, T>
 where
     T: PointDistance + 'a,
 {
-    node: &'a RTreeNode<T>,
-    distance: <<T::Envelope as Envelope>::Point as Point>::Scalar,
+    pub(crate) node: &'a RTreeNode<T>,
+    pub(crate) distance: <<T::Envelope as Envelope>::Point as Point>::Scalar,
+}
+
+impl<'a, T> RTreeNodeDistanceWrapper<'a, T>
+where
+    T: PointDistance,
+{
+    pub(crate) fn new(
+        node: &'a RTreeNode<T>,
+        query_point: &<T::Envelope as Envelope>::Point,
+    ) -> Self {
+        let distance = match node {
+            RTreeNode::Parent(ref data) => data.envelope.distance_2(query_point),
+            RTreeNode::Leaf(ref t) => t.distance_2(query_point),
+        };
+        RTreeNodeDistanceWrapper { node, distance }
+    }
 }
 
 impl<'a, T> PartialEq for RTreeNodeDistanceWrapper<'a, T>
@@ -44,35 +61,54 @@ where
     }
 }
 
-impl<'a, T> NearestNeighborDistance2Iterator<'a, T>
-where
+pub(crate) fn extend_heap<'a, T>(
+    nodes: &mut BinaryHeap<RTreeNodeDistanceWrapper<'a, T>>,
+    children: &'a [RTreeNode<T>],
+    query_point: &<T::Envelope as Envelope>::Point,
+) where
     T: PointDistance,
 {
-    pub fn new(root: &'a ParentNode<T>, query_point: <T::Envelope as Envelope>::Point) -> Self {
-        let mut result = NearestNeighborDistance2Iterator {
-            nodes: BinaryHeap::with_capacity(20),
-            query_point,
-        };
-        result.extend_heap(&root.children);
-        result
-    }
-
-    fn extend_heap(&mut self, children: &'a [RTreeNode<T>]) {
-        let &mut NearestNeighborDistance2Iterator {
-            ref mut nodes,
-            ref query_point,
-        } = self;
-        nodes.extend(children.iter().map(|child| {
-            let distance = match child {
-                RTreeNode::Parent(ref data) => data.envelope.distance_2(query_point),
-                RTreeNode::Leaf(ref t) => t.distance_2(query_point),
-            };
+    nodes.extend(
+        children
+            .iter()
+            .map(|child| RTreeNodeDistanceWrapper::new(child, query_point)),
+    );
+}
 
+fn pop_nearest<'a, T>(
+    nodes: &mut BinaryHeap<RTreeNodeDistanceWrapper<'a, T>>,
+    query_point: &<T::Envelope as Envelope>::Point,
+) -> Option<(&'a T, <<T::Envelope as Envelope>::Point as Point>::Scalar)>
+where
+    T: PointDistance,
+{
+    while let Some(current) = nodes.pop() {
+        match current {
             RTreeNodeDistanceWrapper {
-                node: child,
+                node: RTreeNode::Parent(ref data),
+                ..
+            } => {
+                extend_heap(nodes, &data.children, query_point);
+            }
+            RTreeNodeDistanceWrapper {
+                node: RTreeNode::Leaf(ref t),
                 distance,
+            } => {
+                return Some((t, distance));
             }
-        }));
+        }
+    }
+    None
+}
+
+impl<'a, T> NearestNeighborDistance2Iterator<'a, T>
+where
+    T: PointDistance,
+{
+    pub fn new(root: &'a ParentNode<T>, query_point: <T::Envelope as Envelope>::Point) -> Self {
+        let mut nodes = BinaryHeap::with_capacity(20);
+        extend_heap(&mut nodes, &root.children, &query_point);
+        NearestNeighborDistance2Iterator { nodes, query_point }
     }
 }
 
@@ -83,23 +119,7 @@ where
     type Item = (&'a T, <<T::Envelope as Envelope>::Point as Point>::Scalar);
 
     fn next(&mut self) -> Option<Self::Item> {
-        while let Some(current) = self.nodes.pop() {
-            match current {
-                RTreeNodeDistanceWrapper {
-                    node: RTreeNode::Parent(ref data),
-                    ..
-                } => {
-                    self.extend_heap(&data.children);
-                }
-                RTreeNodeDistanceWrapper {
-                    node: RTreeNode::Leaf(ref t),
-                    distance,
-                } => {
-                    return Some((t, distance));
-                }
-            }
-        }
-        None
+        pop_nearest(&mut self.nodes, &self.query_point)
     }
 }
 
@@ -111,6 +131,96 @@ where
     query_point: <T::Envelope as Envelope>::Point,
 }
 
+/// Reusable buffers for nearest neighbor queries.
+///
+/// Every call to [RTree::nearest_neighbor_iter](crate::RTree::nearest_neighbor_iter) allocates
+/// a new priority queue. Applications issuing many small queries against the same tree can
+/// avoid these allocations by passing a state to
+/// [RTree::nearest_neighbor_iter_with](crate::RTree::nearest_neighbor_iter_with) instead. The
+/// state's buffers are cleared, but not deallocated, whenever a new query starts.
+///
+/// # Example
+/// ```
+/// use rstar::{NearestNeighborState, RTree};
+///
+/// let tree = RTree::bulk_load(vec![[0.0, 0.0], [1.0, 1.0], [2.0, 2.0]]);
+/// let mut state = NearestNeighborState::new();
+/// for query in &[[0.1, 0.0], [1.9, 2.1]] {
+///     let nearest: Vec<_> = tree.nearest_neighbor_iter_with(&mut state, query).take(2).collect();
+///     assert_eq!(nearest.len(), 2);
+/// }
+/// ```
+pub struct NearestNeighborState<'a, T>
+where
+    T: PointDistance + 'a,
+{
+    nodes: BinaryHeap<RTreeNodeDistanceWrapper<'a, T>>,
+}
+
+impl<'a, T> NearestNeighborState<'a, T>
+where
+    T: PointDistance,
+{
+    /// Creates a new state with empty buffers.
+    pub fn new() -> Self {
+        NearestNeighborState {
+            nodes: BinaryHeap::new(),
+        }
+    }
+}
+
+impl<'a, T> Default for NearestNeighborState<'a, T>
+where
+    T: PointDistance,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Iterator returned by [RTree::nearest_neighbor_iter_with](crate::RTree::nearest_neighbor_iter_with).
+pub struct NearestNeighborStateIterator<'s, 'a, T>
+where
+    T: PointDistance + 'a,
+{
+    nodes: &'s mut BinaryHeap<RTreeNodeDistanceWrapper<'a, T>>,
+    query_point: <T::Envelope as Envelope>::Point,
+}
+
+impl<'s, 'a, T> NearestNeighborStateIterator<'s, 'a, T>
+where
+    T: PointDistance,
+{
+    pub(crate) fn new(
+        root: &'a ParentNode<T>,
+        state: &'s mut NearestNeighborState<'a, T>,
+        query_point: <T::Envelope as Envelope>::Point,
+    ) -> Self {
+        let nodes = &mut state.nodes;
+        nodes.clear();
+        extend_heap(nodes, &root.children, &query_point);
+        NearestNeighborStateIterator { nodes, query_point }
+    }
+
+    /// Returns the next element together with its squared distance to the query point.
+    pub fn next_with_distance_2(
+        &mut self,
+    ) -> Option<(&'a T, <<T::Envelope as Envelope>::Point as Point>::Scalar)> {
+        pop_nearest(self.nodes, &self.query_point)
+    }
+}
+
+impl<'s, 'a, T> Iterator for NearestNeighborStateIterator<'s, 'a, T>
+where
+    T: PointDistance,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_with_distance_2().map(|(t, _distance)| t)
+    }
+}
+
 impl<'a, T> NearestNeighborIterator<'a, T>
 where
     T: PointDistance,
@@ -140,6 +250,116 @@ where
     iter: NearestNeighborDistance2Iterator<'a, T>,
 }
 
+struct OwnedNodeDistanceWrapper<T>
+where
+    T: PointDistance,
+{
+    node: RTreeNode<T>,
+    distance: <<T::Envelope as Envelope>::Point as Point>::Scalar,
+}
+
+impl<T> PartialEq for OwnedNodeDistanceWrapper<T>
+where
+    T: PointDistance,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.distance == other.distance
+    }
+}
+
+impl<T> PartialOrd for OwnedNodeDistanceWrapper<T>
+where
+    T: PointDistance,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<::core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Eq for OwnedNodeDistanceWrapper<T> where T: PointDistance {}
+
+impl<T> Ord for OwnedNodeDistanceWrapper<T>
+where
+    T: PointDistance,
+{
+    fn cmp(&self, other: &Self) -> ::core::cmp::Ordering {
+        // Inverse comparison creates a min heap
+        other.distance.partial_cmp(&self.distance).unwrap()
+    }
+}
+
+/// Iterator returned by [RTree::into_iter_sorted_by_distance](crate::RTree::into_iter_sorted_by_distance).
+pub struct IntoNearestNeighborIterator<T>
+where
+    T: PointDistance,
+{
+    nodes: BinaryHeap<OwnedNodeDistanceWrapper<T>>,
+    query_point: <T::Envelope as Envelope>::Point,
+    remaining: usize,
+}
+
+impl<T> IntoNearestNeighborIterator<T>
+where
+    T: PointDistance,
+{
+    pub(crate) fn new(root: ParentNode<T>, query_point: <T::Envelope as Envelope>::Point) -> Self {
+        let mut result = IntoNearestNeighborIterator {
+            nodes: BinaryHeap::with_capacity(20),
+            query_point,
+            remaining: root.size,
+        };
+        result.extend_heap(root.children);
+        result
+    }
+
+    fn extend_heap(&mut self, children: Children<T>) {
+        let query_point = &self.query_point;
+        self.nodes.extend(children.into_iter().map(|child| {
+            let distance = match child {
+                RTreeNode::Parent(ref data) => data.envelope.distance_2(query_point),
+                RTreeNode::Leaf(ref t) => t.distance_2(query_point),
+            };
+            OwnedNodeDistanceWrapper {
+                node: child,
+                distance,
+            }
+        }));
+    }
+
+    /// Returns the next element together with its squared distance to the query point.
+    pub fn next_with_distance_2(
+        &mut self,
+    ) -> Option<(T, <<T::Envelope as Envelope>::Point as Point>::Scalar)> {
+        while let Some(current) = self.nodes.pop() {
+            match current.node {
+                RTreeNode::Parent(data) => self.extend_heap(data.children),
+                RTreeNode::Leaf(t) => {
+                    self.remaining -= 1;
+                    return Some((t, current.distance));
+                }
+            }
+        }
+        None
+    }
+}
+
+impl<T> Iterator for IntoNearestNeighborIterator<T>
+where
+    T: PointDistance,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.next_with_distance_2().map(|(t, _distance)| t)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<T> ExactSizeIterator for IntoNearestNeighborIterator<T> where T: PointDistance {}
+
 enum SmallHeap<T: Ord> {
     Stack(static_heap::BinaryHeap<T, static_heap::Max, 32>),
     Heap(BinaryHeap<T>),
@@ -177,6 +397,47 @@ impl<T: Ord> SmallHeap<T> {
     }
 }
 
+pub fn nearest_neighbor_with_stats<'a, T>(
+    root: &'a ParentNode<T>,
+    query_point: &<T::Envelope as Envelope>::Point,
+) -> (Option<&'a T>, crate::stats::QueryStats)
+where
+    T: PointDistance,
+{
+    let mut stats = crate::stats::QueryStats {
+        nodes_visited: 1,
+        ..Default::default()
+    };
+    let mut nodes = BinaryHeap::new();
+    let mut result = None;
+    let mut current = Some(&root.children[..]);
+    while let Some(children) = current.take() {
+        let leaves = children.iter().filter(|child| child.is_leaf()).count();
+        stats.leaves_tested += leaves;
+        stats.nodes_visited += children.len() - leaves;
+        extend_heap(&mut nodes, children, query_point);
+        match nodes.pop() {
+            Some(RTreeNodeDistanceWrapper {
+                node: RTreeNode::Parent(ref data),
+                ..
+            }) => current = Some(&data.children[..]),
+            Some(RTreeNodeDistanceWrapper {
+                node: RTreeNode::Leaf(ref t),
+                ..
+            }) => result = Some(t),
+            None => {}
+        }
+    }
+    for remaining in nodes {
+        if let RTreeNode::Parent(ref data) = remaining.node {
+            stats.nodes_pruned += 1;
+            stats.leaves_pruned += data.size();
+        }
+    }
+    stats.results = result.is_some() as usize;
+    (result, stats)
+}
+
 pub fn nearest_neighbor<T>(
     node: &ParentNode<T>,
     query_point: <T::Envelope as Envelope>::Point,
@@ -274,12 +535,61 @@ where
     result
 }
 
+pub fn nearest_neighbors_by<T, F>(
+    node: &ParentNode<T>,
+    query_point: <T::Envelope as Envelope>::Point,
+    tolerance: <<T::Envelope as Envelope>::Point as Point>::Scalar,
+    compare: F,
+) -> Vec<&T>
+where
+    T: PointDistance,
+    F: FnMut(&&T, &&T) -> Ordering,
+{
+    let mut nearest_neighbors = NearestNeighborDistance2Iterator::new(node, query_point);
+    let (first, min_distance_2) = match nearest_neighbors.next() {
+        None => return vec![],
+        Some(nn) => nn,
+    };
+    let mut result = vec![first];
+    result.extend(
+        nearest_neighbors
+            .take_while(|&(_, distance_2)| distance_2 <= min_distance_2 + tolerance)
+            .map(|(t, _)| t),
+    );
+    result.sort_by(compare);
+    result
+}
+
 #[cfg(test)]
 mod test {
     use crate::object::PointDistance;
     use crate::rtree::RTree;
     use crate::test_utilities::*;
 
+    #[test]
+    fn test_into_iter_sorted_by_distance() {
+        let points = create_random_points(1000, SEED_1);
+        let tree = RTree::bulk_load(points);
+        let query = [0.3, 0.7];
+        let expected: Vec<_> = tree
+            .nearest_neighbor_iter_with_distance_2(&query)
+            .map(|(p, distance)| (*p, distance))
+            .collect();
+        let mut iter = tree.into_iter_sorted_by_distance(&query);
+        assert_eq!(iter.len(), 1000);
+        let first = iter.next_with_distance_2();
+        assert_eq!(first, Some(expected[0]));
+        assert_eq!(iter.len(), 999);
+        let rest: Vec<_> = iter.collect();
+        assert_eq!(rest.len(), 999);
+        for (found, (expected, _)) in rest.iter().zip(&expected[1..]) {
+            assert_eq!(found.distance_2(&query), expected.distance_2(&query));
+        }
+
+        let empty: RTree<[f64; 2]> = RTree::new();
+        assert_eq!(empty.into_iter_sorted_by_distance(&query).next(), None);
+    }
+
     #[test]
     fn test_nearest_neighbor_empty() {
         let tree: RTree<[f32; 2]> = RTree::new();
@@ -307,6 +617,24 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_nearest_neighbor_iter_with_state() {
+        use crate::algorithm::nearest_neighbor::NearestNeighborState;
+
+        let points = create_random_points(1000, SEED_1);
+        let tree = RTree::bulk_load(points);
+        let mut state = NearestNeighborState::new();
+        for sample_point in &create_random_points(50, SEED_2) {
+            let expected: Vec<_> = tree.nearest_neighbor_iter(sample_point).take(10).collect();
+            let mut iter = tree.nearest_neighbor_iter_with(&mut state, sample_point);
+            let (first, distance) = iter.next_with_distance_2().unwrap();
+            assert_eq!(distance, first.distance_2(sample_point));
+            let mut result = vec![first];
+            result.extend(iter.take(9));
+            assert_eq!(expected, result);
+        }
+    }
+
     #[test]
     fn test_nearest_neighbors_empty() {
         let tree: RTree<[f32; 2]> = RTree::new();
@@ -333,6 +661,41 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_nearest_neighbors_by() {
+        let mut points: Vec<_> = (0..400)
+            .map(|i| [(i % 20) as f64, (i / 20) as f64])
+            .collect();
+        let compare = |a: &&[f64; 2], b: &&[f64; 2]| a.partial_cmp(b).unwrap();
+        let tree = RTree::bulk_load(points.clone());
+        points.reverse();
+        let mut inserted = RTree::new();
+        for point in &points {
+            inserted.insert(*point);
+        }
+
+        for query in create_random_points(50, SEED_1) {
+            let query = [query[0] * 20.0, query[1] * 20.0];
+            let ties = tree.nearest_neighbors_by(&query, 0.0, compare);
+            assert_eq!(ties, inserted.nearest_neighbors_by(&query, 0.0, compare));
+            let min_distance = points
+                .iter()
+                .map(|point| point.distance_2(&query))
+                .fold(f64::INFINITY, f64::min);
+            let mut expected: Vec<_> = points
+                .iter()
+                .filter(|point| point.distance_2(&query) <= min_distance + 0.5)
+                .collect();
+            expected.sort_by(compare);
+            assert_eq!(tree.nearest_neighbors_by(&query, 0.5, compare), expected);
+        }
+        let center = tree.nearest_neighbors_by(&[9.5, 9.5], 0.0, compare);
+        assert_eq!(
+            center,
+            [&[9.0, 9.0], &[9.0, 10.0], &[10.0, 9.0], &[10.0, 10.0]]
+        );
+    }
+
     #[test]
     fn test_nearest_neighbor_iterator() {
         let mut points = create_random_points(1000, SEED_1);
@@ -1,12 +1,12 @@
 use core::mem::replace;
 
 use crate::algorithm::selection_functions::SelectionFunction;
-use crate::node::{ParentNode, RTreeNode};
+use crate::node::{Children, ParentNode, RTreeNode};
 use crate::object::RTreeObject;
 use crate::params::RTreeParams;
 use crate::{Envelope, RTree};
 
-use alloc::{vec, vec::Vec};
+use alloc::vec::Vec;
 
 #[allow(unused_imports)] // Import is required when building without std
 use num_traits::Float;
@@ -46,13 +46,14 @@ where
         // We replace with a root as a brand new RTree in case the iterator is
         // `mem::forgot`ten.
 
-        // Instead of using `new_with_params`, we avoid an allocation for
-        // the normal usage and replace root with an empty `Vec`.
+        // Instead of using `new_with_params`, we avoid reserving space for
+        // children in the normal usage and replace root with an empty node.
         let root = replace(
             rtree.root_mut(),
             ParentNode {
-                children: vec![],
+                children: Children::new(),
                 envelope: Envelope::new_empty(),
+                size: 0,
             },
         );
         let original_size = replace(rtree.size_mut(), 0);
@@ -81,6 +82,7 @@ where
         // TODO: May be make this a method on `ParentNode`
         if num_removed > 0 {
             node.envelope = crate::node::envelope_for_children(&node.children);
+            node.size -= num_removed;
         }
 
         // If there is no parent, this is the new root node to set back in the rtree
@@ -129,15 +131,20 @@ where
     type Item = T;
 
     fn next(&mut self) -> Option<Self::Item> {
-        loop {
+        'nodes: loop {
             // Get reference to top node or return None.
+            let depth = self.node_stack.len().saturating_sub(1);
             let (node, idx, remove_count) = match self.node_stack.last_mut() {
                 Some(node) => (&mut node.0, &mut node.1, &mut node.2),
                 None => return None,
             };
 
             // Try to find a selected item to return.
-            if *idx > 0 || self.removal_function.should_unpack_parent(&node.envelope) {
+            if *idx > 0
+                || self
+                    .removal_function
+                    .should_unpack_parent_at_depth(&node.envelope, depth)
+            {
                 while *idx < node.children.len() {
                     match &mut node.children[*idx] {
                         RTreeNode::Parent(_) => {
@@ -149,7 +156,7 @@ where
                                 RTreeNode::Parent(node) => node,
                             };
                             self.node_stack.push((child, 0, 0));
-                            return self.next();
+                            continue 'nodes;
                         }
                         RTreeNode::Leaf(ref leaf) => {
                             if self.removal_function.should_unpack_leaf(leaf) {
@@ -1,12 +1,16 @@
 use crate::envelope::Envelope;
-use crate::node::{envelope_for_children, ParentNode, RTreeNode};
+use crate::node::{envelope_for_children, size_for_children, Children, ParentNode, RTreeNode};
 use crate::object::RTreeObject;
-use crate::params::{InsertionStrategy, RTreeParams};
+use crate::params::{
+    InsertionHeuristic, InsertionStrategy, OverflowTreatment, RTreeParams, SplitStrategy,
+};
 use crate::point::{Point, PointExt};
 use crate::rtree::RTree;
 
 use alloc::vec::Vec;
+use core::marker::PhantomData;
 use num_traits::{Bounded, Zero};
+use smallvec::SmallVec;
 
 /// Inserts points according to the r-star heuristic.
 ///
@@ -14,9 +18,41 @@ use num_traits::{Bounded, Zero};
 /// insertion performance. This strategy is best for use cases with few
 /// insertions and many nearest neighbor queries.
 ///
+/// Overflowing nodes are split with the [SplitStrategy] `S`, which defaults to the r*-split.
+///
 /// `RStarInsertionStrategy` is used as the default insertion strategy.
 /// See [InsertionStrategy] for more information on insertion strategies.
-pub enum RStarInsertionStrategy {}
+///
+/// # Example
+/// ```
+/// use rstar::{QuadraticSplitStrategy, RStarInsertionStrategy, RTree, RTreeParams};
+///
+/// struct QuadraticSplitParams;
+///
+/// impl RTreeParams for QuadraticSplitParams {
+///     const MIN_SIZE: usize = 3;
+///     const MAX_SIZE: usize = 6;
+///     const REINSERTION_COUNT: usize = 2;
+///     type DefaultInsertionStrategy = RStarInsertionStrategy<QuadraticSplitStrategy>;
+/// }
+///
+/// let mut tree: RTree<_, QuadraticSplitParams> = RTree::new_with_params();
+/// tree.insert([0.0, 1.0]);
+/// ```
+pub struct RStarInsertionStrategy<S = RStarSplitStrategy> {
+    _split: PhantomData<fn() -> S>,
+}
+
+/// Splits overflowing nodes according to the r-star heuristic.
+///
+/// The split axis is chosen to minimize the perimeter of the resulting nodes, the split
+/// position along this axis to minimize their overlap. This yields the best query
+/// performance but is slower than the other split strategies.
+///
+/// `RStarSplitStrategy` is used as the default split strategy of [RStarInsertionStrategy] and
+/// [SplitOnlyInsertionStrategy]. See [SplitStrategy] for more information on split
+/// strategies.
+pub enum RStarSplitStrategy {}
 
 enum InsertionResult<T>
 where
@@ -27,151 +63,414 @@ where
     Complete,
 }
 
-impl InsertionStrategy for RStarInsertionStrategy {
+/// Inserts elements according to the r-star heuristic but never reinserts nodes.
+///
+/// Forced reinsertion improves the tree's quality, but it occasionally makes single
+/// insertions considerably more expensive than others. This strategy resolves every node
+/// overflow by splitting the node right away. Each insertion only modifies the nodes on a
+/// single path from the root to a leaf (and their split-off siblings), resulting in more
+/// predictable insertion times at the cost of slightly slower queries.
+///
+/// [RTreeParams::REINSERTION_COUNT] is ignored by this strategy. Overflowing nodes are split
+/// with the [SplitStrategy] `S`, which defaults to the r*-split.
+///
+/// # Example
+/// ```
+/// use rstar::{RTree, RTreeParams, SplitOnlyInsertionStrategy};
+///
+/// struct LowLatencyParams;
+///
+/// impl RTreeParams for LowLatencyParams {
+///     const MIN_SIZE: usize = 3;
+///     const MAX_SIZE: usize = 6;
+///     const REINSERTION_COUNT: usize = 0;
+///     type DefaultInsertionStrategy = SplitOnlyInsertionStrategy;
+/// }
+///
+/// let mut tree: RTree<_, LowLatencyParams> = RTree::new_with_params();
+/// tree.insert([0.0, 1.0]);
+/// ```
+pub struct SplitOnlyInsertionStrategy<S = RStarSplitStrategy> {
+    _split: PhantomData<fn() -> S>,
+}
+
+/// Inserts elements while keeping the overlap between sibling nodes as small as possible.
+///
+/// Point queries need to descend into every node whose envelope contains the query point.
+/// For highly overlapping data, e.g. large rectangles, the r-star heuristic may create
+/// siblings with considerably overlapping envelopes, which causes point queries to visit
+/// several subtrees. Similar to an R+-tree, this strategy prioritizes minimal overlap over
+/// all other criteria: the overlap increase is minimized when choosing a subtree on every
+/// level, and nodes are split along the axis and position with the smallest overlap.
+///
+/// Unlike an R+-tree, elements are never clipped or stored more than once, which means that
+/// some overlap remains if it can't be avoided. Forced reinsertion is not used,
+/// [RTreeParams::REINSERTION_COUNT] is ignored by this strategy. Only calls to [RTree::insert]
+/// use this strategy, a bulk loaded tree will not have reduced overlap.
+///
+/// # Example
+/// ```
+/// use rstar::primitives::Rectangle;
+/// use rstar::{MinimalOverlapInsertionStrategy, RTree, RTreeParams};
+///
+/// struct MinimalOverlapParams;
+///
+/// impl RTreeParams for MinimalOverlapParams {
+///     const MIN_SIZE: usize = 3;
+///     const MAX_SIZE: usize = 6;
+///     const REINSERTION_COUNT: usize = 0;
+///     type DefaultInsertionStrategy = MinimalOverlapInsertionStrategy;
+/// }
+///
+/// let mut tree: RTree<_, MinimalOverlapParams> = RTree::new_with_params();
+/// tree.insert(Rectangle::from_corners([0.0, 0.0], [2.0, 1.0]));
+/// tree.insert(Rectangle::from_corners([1.0, 0.0], [3.0, 1.0]));
+/// assert_eq!(tree.locate_all_at_point(&[1.5, 0.5]).count(), 2);
+/// ```
+pub enum MinimalOverlapInsertionStrategy {}
+
+/// Inserts elements with a user supplied [InsertionHeuristic].
+///
+/// Uses the same insertion algorithm as the built-in strategies, but lets the heuristic
+/// decide which subtree receives new elements and how overflowing nodes are treated. This
+/// allows to experiment with alternative heuristics without reimplementing the tree's
+/// bookkeeping.
+///
+/// # Example
+/// ```
+/// use rstar::{
+///     CustomInsertionStrategy, Envelope, InsertionHeuristic, ParentNode, RTree, RTreeNode,
+///     RTreeObject, RTreeParams,
+/// };
+///
+/// /// Chooses the child whose envelope needs the least area enlargement.
+/// struct LeastEnlargement;
+///
+/// impl InsertionHeuristic for LeastEnlargement {
+///     fn choose_subtree<T: RTreeObject>(node: &ParentNode<T>, envelope: &T::Envelope) -> usize {
+///         let enlargement = |child: &RTreeNode<T>| {
+///             let child_envelope = child.envelope();
+///             child_envelope.merged(envelope).area() - child_envelope.area()
+///         };
+///         let mut best = 0;
+///         for (index, child) in node.children().iter().enumerate() {
+///             if enlargement(child) < enlargement(&node.children()[best]) {
+///                 best = index;
+///             }
+///         }
+///         best
+///     }
+/// }
+///
+/// struct GuttmanParams;
+///
+/// impl RTreeParams for GuttmanParams {
+///     const MIN_SIZE: usize = 3;
+///     const MAX_SIZE: usize = 6;
+///     const REINSERTION_COUNT: usize = 0;
+///     type DefaultInsertionStrategy = CustomInsertionStrategy<LeastEnlargement>;
+/// }
+///
+/// let mut tree: RTree<_, GuttmanParams> = RTree::new_with_params();
+/// for i in 0..100 {
+///     tree.insert([i as f64, (i % 10) as f64]);
+/// }
+/// assert_eq!(tree.size(), 100);
+/// ```
+pub struct CustomInsertionStrategy<H> {
+    _heuristic: PhantomData<fn() -> H>,
+}
+
+impl<S> InsertionHeuristic for RStarInsertionStrategy<S>
+where
+    S: SplitStrategy,
+{
+    fn split<T, Params>(node: &mut ParentNode<T>) -> RTreeNode<T>
+    where
+        T: RTreeObject,
+        Params: RTreeParams,
+    {
+        S::split::<_, Params>(node)
+    }
+}
+
+impl<S> InsertionHeuristic for SplitOnlyInsertionStrategy<S>
+where
+    S: SplitStrategy,
+{
+    fn overflow_treatment<T, Params>(_: &ParentNode<T>, _: usize) -> OverflowTreatment
+    where
+        T: RTreeObject,
+        Params: RTreeParams,
+    {
+        OverflowTreatment::Split
+    }
+
+    fn split<T, Params>(node: &mut ParentNode<T>) -> RTreeNode<T>
+    where
+        T: RTreeObject,
+        Params: RTreeParams,
+    {
+        S::split::<_, Params>(node)
+    }
+}
+
+impl InsertionHeuristic for MinimalOverlapInsertionStrategy {
+    fn choose_subtree<T: RTreeObject>(node: &ParentNode<T>, envelope: &T::Envelope) -> usize {
+        rstar_choose_subtree(node, envelope, true)
+    }
+
+    fn overflow_treatment<T, Params>(_: &ParentNode<T>, _: usize) -> OverflowTreatment
+    where
+        T: RTreeObject,
+        Params: RTreeParams,
+    {
+        OverflowTreatment::Split
+    }
+
+    fn split<T, Params>(node: &mut ParentNode<T>) -> RTreeNode<T>
+    where
+        T: RTreeObject,
+        Params: RTreeParams,
+    {
+        let axis = get_minimal_overlap_split_axis::<_, Params>(node);
+        split_along_axis::<_, Params>(node, axis)
+    }
+}
+
+impl<S> InsertionStrategy for RStarInsertionStrategy<S>
+where
+    S: SplitStrategy,
+{
     fn insert<T, Params>(tree: &mut RTree<T, Params>, t: T)
     where
         Params: RTreeParams,
         T: RTreeObject,
     {
-        use InsertionAction::*;
+        insert::<_, Params, Self>(tree, t);
+    }
+}
 
-        enum InsertionAction<T: RTreeObject> {
-            PerformSplit(RTreeNode<T>),
-            PerformReinsert(RTreeNode<T>),
+impl<S> InsertionStrategy for SplitOnlyInsertionStrategy<S>
+where
+    S: SplitStrategy,
+{
+    fn insert<T, Params>(tree: &mut RTree<T, Params>, t: T)
+    where
+        Params: RTreeParams,
+        T: RTreeObject,
+    {
+        insert::<_, Params, Self>(tree, t);
+    }
+}
+
+impl InsertionStrategy for MinimalOverlapInsertionStrategy {
+    fn insert<T, Params>(tree: &mut RTree<T, Params>, t: T)
+    where
+        Params: RTreeParams,
+        T: RTreeObject,
+    {
+        insert::<_, Params, Self>(tree, t);
+    }
+}
+
+impl<H> InsertionStrategy for CustomInsertionStrategy<H>
+where
+    H: InsertionHeuristic,
+{
+    fn insert<T, Params>(tree: &mut RTree<T, Params>, t: T)
+    where
+        Params: RTreeParams,
+        T: RTreeObject,
+    {
+        insert::<_, Params, H>(tree, t);
+    }
+}
+
+fn insert<T, Params, H>(tree: &mut RTree<T, Params>, t: T)
+where
+    Params: RTreeParams,
+    T: RTreeObject,
+    H: InsertionHeuristic,
+{
+    let mut target_height = 0;
+    let mut nodes_to_reinsert = Vec::new();
+    match insert_node::<_, Params, H>(tree.root_mut(), RTreeNode::Leaf(t)) {
+        InsertionResult::Split(node) => grow_root(tree, node),
+        InsertionResult::Reinsert(nodes, real_target_height) => {
+            nodes_to_reinsert = nodes;
+            target_height = real_target_height;
         }
+        InsertionResult::Complete => {}
+    };
 
-        let first = recursive_insert::<_, Params>(tree.root_mut(), RTreeNode::Leaf(t), 0);
-        let mut target_height = 0;
-        let mut insertion_stack = Vec::new();
-        match first {
-            InsertionResult::Split(node) => insertion_stack.push(PerformSplit(node)),
-            InsertionResult::Reinsert(nodes_to_reinsert, real_target_height) => {
-                insertion_stack.extend(nodes_to_reinsert.into_iter().map(PerformReinsert));
-                target_height = real_target_height;
+    while let Some(node_to_reinsert) = nodes_to_reinsert.pop() {
+        let root = tree.root_mut();
+        match forced_insertion::<T, Params, H>(root, node_to_reinsert, target_height) {
+            InsertionResult::Split(node) => {
+                grow_root(tree, node);
+                target_height += 1;
             }
-            InsertionResult::Complete => {}
-        };
-
-        while let Some(next) = insertion_stack.pop() {
-            match next {
-                PerformSplit(node) => {
-                    // The root node was split, create a new root and increase height
-                    let new_root = ParentNode::new_root::<Params>();
-                    let old_root = ::core::mem::replace(tree.root_mut(), new_root);
-                    let new_envelope = old_root.envelope.merged(&node.envelope());
-                    let root = tree.root_mut();
-                    root.envelope = new_envelope;
-                    root.children.push(RTreeNode::Parent(old_root));
-                    root.children.push(node);
-                    target_height += 1;
-                }
-                PerformReinsert(node_to_reinsert) => {
-                    let root = tree.root_mut();
-                    match forced_insertion::<T, Params>(root, node_to_reinsert, target_height) {
-                        InsertionResult::Split(node) => insertion_stack.push(PerformSplit(node)),
-                        InsertionResult::Reinsert(_, _) => {
-                            panic!("Unexpected reinsert. This is a bug in rstar.")
-                        }
-                        InsertionResult::Complete => {}
-                    }
-                }
+            InsertionResult::Reinsert(_, _) => {
+                panic!("Unexpected reinsert. This is a bug in rstar.")
             }
+            InsertionResult::Complete => {}
         }
     }
 }
 
-fn forced_insertion<T, Params>(
-    node: &mut ParentNode<T>,
+/// Creates a new root above the split root node and its split off sibling `node`, which
+/// increases the tree's height.
+fn grow_root<T, Params>(tree: &mut RTree<T, Params>, node: RTreeNode<T>)
+where
+    Params: RTreeParams,
+    T: RTreeObject,
+{
+    let new_root = ParentNode::new_root::<Params>();
+    let old_root = ::core::mem::replace(tree.root_mut(), new_root);
+    let new_envelope = old_root.envelope.merged(&node.envelope());
+    let root = tree.root_mut();
+    root.envelope = new_envelope;
+    root.size = old_root.size + node.size();
+    root.children.push(RTreeNode::Parent(old_root));
+    root.children.push(node);
+}
+
+fn forced_insertion<T, Params, H>(
+    root: &mut ParentNode<T>,
     t: RTreeNode<T>,
     target_height: usize,
 ) -> InsertionResult<T>
 where
     T: RTreeObject,
     Params: RTreeParams,
+    H: InsertionHeuristic,
 {
-    node.envelope.merge(&t.envelope());
-    let expand_index = choose_subtree(node, &t);
+    let path = choose_path::<_, H>(root, &t, target_height);
+    let node = descend(root, &path);
+    // Force insertion into this node
+    node.children.push(t);
+    let mut result = resolve_overflow_without_reinsertion::<_, Params, H>(node);
 
-    if target_height == 0 || node.children.len() < expand_index {
-        // Force insertion into this node
-        node.children.push(t);
-        return resolve_overflow_without_reinsertion::<_, Params>(node);
+    for depth in (0..path.len()).rev() {
+        let child = match result {
+            InsertionResult::Split(child) => child,
+            other => return other,
+        };
+        let node = descend(root, &path[..depth]);
+        node.envelope.merge(&child.envelope());
+        node.children.push(child);
+        result = resolve_overflow_without_reinsertion::<_, Params, H>(node);
     }
+    result
+}
 
-    if let RTreeNode::Parent(ref mut follow) = node.children[expand_index] {
-        match forced_insertion::<_, Params>(follow, t, target_height - 1) {
+fn insert_node<T, Params, H>(root: &mut ParentNode<T>, t: RTreeNode<T>) -> InsertionResult<T>
+where
+    T: RTreeObject,
+    Params: RTreeParams,
+    H: InsertionHeuristic,
+{
+    let path = choose_path::<_, H>(root, &t, usize::MAX);
+    let node = descend(root, &path);
+    node.children.push(t);
+    let mut result = resolve_overflow::<_, Params, H>(node, path.len());
+
+    for depth in (0..path.len()).rev() {
+        let node = descend(root, &path[..depth]);
+        result = match result {
             InsertionResult::Split(child) => {
                 node.envelope.merge(&child.envelope());
                 node.children.push(child);
-                resolve_overflow_without_reinsertion::<_, Params>(node)
+                resolve_overflow::<_, Params, H>(node, depth)
             }
-            other => other,
-        }
-    } else {
-        unreachable!("This is a bug in rstar.")
+            InsertionResult::Reinsert(a, b) => {
+                node.envelope = envelope_for_children(&node.children);
+                node.size = size_for_children(&node.children);
+                InsertionResult::Reinsert(a, b)
+            }
+            InsertionResult::Complete => return InsertionResult::Complete,
+        };
     }
+    result
 }
 
-fn recursive_insert<T, Params>(
-    node: &mut ParentNode<T>,
-    t: RTreeNode<T>,
-    current_height: usize,
-) -> InsertionResult<T>
+/// The child indices leading from the root to a node.
+type InsertionPath = SmallVec<[usize; 16]>;
+
+/// Returns the child indices leading from `root` to the node that should receive `t`,
+/// descending at most `max_depth` levels. Envelopes and sizes along the path are updated
+/// to include `t`.
+///
+/// The path is walked again by [descend] when resolving overflows bottom up. This keeps
+/// insertion free of recursion at the cost of a few additional (cheap) lookups. The path is
+/// stored inline for trees of typical heights and only spills to the heap for very deep trees.
+fn choose_path<T, H>(root: &mut ParentNode<T>, t: &RTreeNode<T>, max_depth: usize) -> InsertionPath
 where
     T: RTreeObject,
-    Params: RTreeParams,
+    H: InsertionHeuristic,
 {
-    node.envelope.merge(&t.envelope());
-    let expand_index = choose_subtree(node, &t);
-
-    if node.children.len() < expand_index {
-        // Force insertion into this node
-        node.children.push(t);
-        return resolve_overflow::<_, Params>(node, current_height);
+    let mut path = InsertionPath::new();
+    let mut node = root;
+    loop {
+        node.envelope.merge(&t.envelope());
+        node.size += t.size();
+        let expand_index = match node.children.first() {
+            Some(RTreeNode::Parent(_)) if path.len() < max_depth => {
+                H::choose_subtree(node, &t.envelope())
+            }
+            _ => return path,
+        };
+        path.push(expand_index);
+        node = match node.children[expand_index] {
+            RTreeNode::Parent(ref mut follow) => follow,
+            RTreeNode::Leaf(_) => unreachable!("This is a bug in rstar."),
+        };
     }
+}
 
-    let expand = if let RTreeNode::Parent(ref mut follow) = node.children[expand_index] {
-        recursive_insert::<_, Params>(follow, t, current_height + 1)
-    } else {
-        panic!("This is a bug in rstar.")
-    };
-
-    match expand {
-        InsertionResult::Split(child) => {
-            node.envelope.merge(&child.envelope());
-            node.children.push(child);
-            resolve_overflow::<_, Params>(node, current_height)
-        }
-        InsertionResult::Reinsert(a, b) => {
-            node.envelope = envelope_for_children(&node.children);
-            InsertionResult::Reinsert(a, b)
-        }
-        other => other,
+fn descend<'a, T>(mut node: &'a mut ParentNode<T>, path: &[usize]) -> &'a mut ParentNode<T>
+where
+    T: RTreeObject,
+{
+    for &index in path {
+        node = match node.children[index] {
+            RTreeNode::Parent(ref mut child) => child,
+            RTreeNode::Leaf(_) => unreachable!("This is a bug in rstar."),
+        };
     }
+    node
 }
 
-fn choose_subtree<T>(node: &mut ParentNode<T>, to_insert: &RTreeNode<T>) -> usize
+/// Chooses the child of `node` that should receive an object with the given envelope, as
+/// proposed by the r*-tree paper. `node`'s children must be parent nodes.
+///
+/// The overlap increase is only considered on the level above the leaves, or on every level
+/// if `always_minimize_overlap` is set.
+pub(crate) fn rstar_choose_subtree<T>(
+    node: &ParentNode<T>,
+    insertion_envelope: &T::Envelope,
+    always_minimize_overlap: bool,
+) -> usize
 where
     T: RTreeObject,
 {
     let all_leaves = match node.children.first() {
-        Some(RTreeNode::Leaf(_)) => return usize::max_value(),
         Some(RTreeNode::Parent(ref data)) => data
             .children
             .first()
             .map(RTreeNode::is_leaf)
             .unwrap_or(true),
-        _ => return usize::max_value(),
+        _ => true,
     };
 
     let zero: <<T::Envelope as Envelope>::Point as Point>::Scalar = Zero::zero();
-    let insertion_envelope = to_insert.envelope();
     let mut inclusion_count = 0;
     let mut min_area = <<T::Envelope as Envelope>::Point as Point>::Scalar::max_value();
     let mut min_index = 0;
     for (index, child) in node.children.iter().enumerate() {
         let envelope = child.envelope();
-        if envelope.contains_envelope(&insertion_envelope) {
+        if envelope.contains_envelope(insertion_envelope) {
             inclusion_count += 1;
             let area = envelope.area();
             if area < min_area {
@@ -187,8 +486,8 @@ where
         for (index, child1) in node.children.iter().enumerate() {
             let envelope = child1.envelope();
             let mut new_envelope = envelope.clone();
-            new_envelope.merge(&insertion_envelope);
-            let overlap_increase = if all_leaves {
+            new_envelope.merge(insertion_envelope);
+            let overlap_increase = if all_leaves || always_minimize_overlap {
                 // Calculate minimal overlap increase
                 let mut overlap = zero;
                 let mut new_overlap = zero;
@@ -203,7 +502,8 @@ where
                 }
                 new_overlap - overlap
             } else {
-                // Don't calculate overlap increase if not all children are leaves
+                // Don't calculate overlap increase if not all children are leaves, unless
+                // minimal overlap is preferred over insertion performance
                 zero
             };
             // Calculate area increase and area
@@ -220,44 +520,130 @@ where
 }
 
 // Never returns a request for reinsertion
-fn resolve_overflow_without_reinsertion<T, Params>(node: &mut ParentNode<T>) -> InsertionResult<T>
+fn resolve_overflow_without_reinsertion<T, Params, H>(
+    node: &mut ParentNode<T>,
+) -> InsertionResult<T>
 where
     T: RTreeObject,
     Params: RTreeParams,
+    H: InsertionHeuristic,
 {
-    if node.children.len() > Params::MAX_SIZE {
-        let off_split = split::<_, Params>(node);
+    if node.children.len() > node.max_size::<Params>() {
+        let off_split = H::split::<_, Params>(node);
         InsertionResult::Split(off_split)
     } else {
         InsertionResult::Complete
     }
 }
 
-fn resolve_overflow<T, Params>(node: &mut ParentNode<T>, current_depth: usize) -> InsertionResult<T>
+fn resolve_overflow<T, Params, H>(
+    node: &mut ParentNode<T>,
+    current_depth: usize,
+) -> InsertionResult<T>
 where
     T: RTreeObject,
     Params: RTreeParams,
+    H: InsertionHeuristic,
 {
-    if Params::REINSERTION_COUNT == 0 {
-        resolve_overflow_without_reinsertion::<_, Params>(node)
-    } else if node.children.len() > Params::MAX_SIZE {
+    if node.children.len() <= node.max_size::<Params>() {
+        InsertionResult::Complete
+    } else if Params::REINSERTION_COUNT == 0
+        || H::overflow_treatment::<_, Params>(node, current_depth) == OverflowTreatment::Split
+    {
+        resolve_overflow_without_reinsertion::<_, Params, H>(node)
+    } else {
         let nodes_for_reinsertion = get_nodes_for_reinsertion::<_, Params>(node);
         InsertionResult::Reinsert(nodes_for_reinsertion, current_depth)
-    } else {
-        InsertionResult::Complete
     }
 }
 
-fn split<T, Params>(node: &mut ParentNode<T>) -> RTreeNode<T>
+impl SplitStrategy for RStarSplitStrategy {
+    fn split<T, Params>(node: &mut ParentNode<T>) -> RTreeNode<T>
+    where
+        Params: RTreeParams,
+        T: RTreeObject,
+    {
+        let axis = get_split_axis::<_, Params>(node);
+        split_along_axis::<_, Params>(node, axis)
+    }
+}
+
+fn split_along_axis<T, Params>(node: &mut ParentNode<T>, axis: usize) -> RTreeNode<T>
+where
+    T: RTreeObject,
+    Params: RTreeParams,
+{
+    let best_index = sort_for_split::<_, Params>(node, axis);
+    let off_split = node.children.drain(best_index..).collect();
+    finish_split(node, off_split)
+}
+
+/// Splits a node with the r*-split, moving the split off children into `off_split` instead
+/// of newly allocated children. `off_split` must be empty.
+pub(crate) fn rstar_split_into<T, Params>(
+    node: &mut ParentNode<T>,
+    off_split: Children<T>,
+) -> RTreeNode<T>
 where
     T: RTreeObject,
     Params: RTreeParams,
 {
     let axis = get_split_axis::<_, Params>(node);
-    let zero = <<T::Envelope as Envelope>::Point as Point>::Scalar::zero();
+    split_along_axis_into::<_, Params>(node, axis, off_split)
+}
+
+fn split_along_axis_into<T, Params>(
+    node: &mut ParentNode<T>,
+    axis: usize,
+    mut off_split: Children<T>,
+) -> RTreeNode<T>
+where
+    T: RTreeObject,
+    Params: RTreeParams,
+{
+    debug_assert!(off_split.is_empty());
+    let best_index = sort_for_split::<_, Params>(node, axis);
+    off_split.extend(node.children.drain(best_index..));
+    finish_split(node, off_split)
+}
+
+/// Sorts a node's children along the split axis and returns the index of the first child
+/// moved into the split off node.
+fn sort_for_split<T, Params>(node: &mut ParentNode<T>, axis: usize) -> usize
+where
+    T: RTreeObject,
+    Params: RTreeParams,
+{
     debug_assert!(node.children.len() >= 2);
-    // Sort along axis
     T::Envelope::sort_envelopes(axis, &mut node.children);
+    let (_, best_index) = get_split_index::<_, Params>(node);
+    best_index
+}
+
+fn finish_split<T>(node: &mut ParentNode<T>, off_split: Children<T>) -> RTreeNode<T>
+where
+    T: RTreeObject,
+{
+    let off_split = ParentNode::new_parent(off_split);
+    node.envelope = envelope_for_children(&node.children);
+    node.size -= off_split.size;
+    RTreeNode::Parent(off_split)
+}
+
+/// The `(overlap, area)` value of a split, smaller values are better.
+type SplitGoodness<T> = (
+    <<<T as RTreeObject>::Envelope as Envelope>::Point as Point>::Scalar,
+    <<<T as RTreeObject>::Envelope as Envelope>::Point as Point>::Scalar,
+);
+
+/// Returns the `(overlap, area)` value and index of the best split of a node's children,
+/// which must be sorted along the split axis.
+fn get_split_index<T, Params>(node: &ParentNode<T>) -> (SplitGoodness<T>, usize)
+where
+    T: RTreeObject,
+    Params: RTreeParams,
+{
+    let zero = <<T::Envelope as Envelope>::Point as Point>::Scalar::zero();
     let mut best = (zero, zero);
     let min_size = Params::MIN_SIZE;
     let mut best_index = min_size;
@@ -281,9 +667,27 @@ where
             best_index = k;
         }
     }
-    let off_split = node.children.split_off(best_index);
-    node.envelope = envelope_for_children(&node.children);
-    RTreeNode::Parent(ParentNode::new_parent(off_split))
+    (best, best_index)
+}
+
+/// Returns the axis along which the children of a node can be split with the smallest
+/// overlap, preferring smaller areas if the overlap is equal.
+fn get_minimal_overlap_split_axis<T, Params>(node: &mut ParentNode<T>) -> usize
+where
+    T: RTreeObject,
+    Params: RTreeParams,
+{
+    let mut best = None;
+    let mut best_axis = 0;
+    for axis in 0..<T::Envelope as Envelope>::Point::DIMENSIONS {
+        T::Envelope::sort_envelopes(axis, &mut node.children);
+        let (value, _) = get_split_index::<_, Params>(node);
+        if best.map_or(true, |best| value < best) {
+            best = Some(value);
+            best_axis = axis;
+        }
+    }
+    best_axis
 }
 
 fn get_split_axis<T, Params>(node: &mut ParentNode<T>) -> usize
@@ -334,7 +738,7 @@ where
     Params: RTreeParams,
 {
     let center = node.envelope.center();
-    // Sort with increasing order so we can use Vec::split_off
+    // Sort with increasing order so we can drain the farthest children from the end
     node.children.sort_by(|l, r| {
         let l_center = l.envelope().center();
         let r_center = r.envelope().center();
@@ -347,7 +751,9 @@ where
     let num_children = node.children.len();
     let result = node
         .children
-        .split_off(num_children - Params::REINSERTION_COUNT);
+        .drain(num_children - Params::REINSERTION_COUNT..)
+        .collect();
     node.envelope = envelope_for_children(&node.children);
+    node.size = size_for_children(&node.children);
     result
 }
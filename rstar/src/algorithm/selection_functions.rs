@@ -1,7 +1,8 @@
 use crate::envelope::Envelope;
-use crate::object::PointDistance;
 use crate::object::RTreeObject;
-use crate::Point;
+use crate::object::{ExactPredicate, PointDistance};
+use crate::{Point, AABB};
+use num_traits::Float;
 
 /// Advanced trait to iterate through an r-tree. Usually it should not be required to be implemented.
 ///
@@ -35,8 +36,245 @@ where
     fn should_unpack_leaf(&self, _leaf: &T) -> bool {
         true
     }
+
+    /// Return `true` if a parent node at a given depth should be unpacked during a search.
+    ///
+    /// The root has a depth of `0`, its children a depth of `1` and so on. Since all leaves of
+    /// an r-tree have the same depth, the depth allows to limit a search to the upper levels
+    /// of a tree, e.g. to collect the envelopes of all nodes of a certain level. The default
+    /// implementation ignores the depth and calls [Self::should_unpack_parent].
+    ///
+    /// # Example
+    /// ```
+    /// use core::cell::RefCell;
+    /// use rstar::{RTree, SelectionFunction, AABB};
+    ///
+    /// struct CollectLevel {
+    ///     depth: usize,
+    ///     envelopes: RefCell<Vec<AABB<[f64; 2]>>>,
+    /// }
+    ///
+    /// impl SelectionFunction<[f64; 2]> for CollectLevel {
+    ///     fn should_unpack_parent(&self, _: &AABB<[f64; 2]>) -> bool {
+    ///         unreachable!()
+    ///     }
+    ///
+    ///     fn should_unpack_parent_at_depth(&self, envelope: &AABB<[f64; 2]>, depth: usize) -> bool {
+    ///         if depth == self.depth {
+    ///             self.envelopes.borrow_mut().push(*envelope);
+    ///         }
+    ///         depth < self.depth
+    ///     }
+    /// }
+    ///
+    /// let tree = RTree::bulk_load((0..1000).map(|i| [i as f64, 0.0]).collect());
+    /// let func = CollectLevel { depth: 1, envelopes: RefCell::new(Vec::new()) };
+    /// assert_eq!(tree.locate_with_selection_function(&func).count(), 0);
+    /// assert_eq!(func.envelopes.borrow().len(), tree.root().children().len());
+    /// ```
+    fn should_unpack_parent_at_depth(&self, envelope: &T::Envelope, depth: usize) -> bool {
+        let _ = depth;
+        self.should_unpack_parent(envelope)
+    }
+
+    /// Returns a selection function that selects all elements selected by both `self` and
+    /// `other`.
+    ///
+    /// # Example
+    /// ```
+    /// use rstar::{RTree, SelectInEnvelopeFunction, SelectWithinDistanceFunction, SelectionFunction, AABB};
+    ///
+    /// let tree = RTree::bulk_load(vec![[0.0, 0.0], [1.0, 0.0], [2.0, 0.0], [1.0, 3.0]]);
+    /// let right = SelectInEnvelopeFunction::new(AABB::from_corners([0.5, -5.0], [5.0, 5.0]));
+    /// let close = SelectWithinDistanceFunction::new([0.0, 0.0], 4.0);
+    /// let selected: Vec<_> = tree.locate_with_selection_function(right.and(close)).collect();
+    /// assert_eq!(selected, vec![&[2.0, 0.0], &[1.0, 0.0]]);
+    /// ```
+    fn and<S>(self, other: S) -> SelectAndFunction<Self, S>
+    where
+        Self: Sized,
+        S: SelectionFunction<T>,
+    {
+        SelectAndFunction(self, other)
+    }
+
+    /// Returns a selection function that selects all elements selected by `self`, `other` or
+    /// both.
+    fn or<S>(self, other: S) -> SelectOrFunction<Self, S>
+    where
+        Self: Sized,
+        S: SelectionFunction<T>,
+    {
+        SelectOrFunction(self, other)
+    }
+
+    /// Returns a selection function that selects all elements not selected by `self`.
+    ///
+    /// The envelope of a parent node doesn't tell whether *all* of its children would be
+    /// selected, the returned function thus unpacks every parent node and cannot prune the
+    /// search. Use it to narrow down the results of a function combined with [Self::and].
+    ///
+    /// # Example
+    /// ```
+    /// use rstar::{RTree, SelectInEnvelopeFunction, SelectWithinDistanceFunction, SelectionFunction, AABB};
+    ///
+    /// let mut tree = RTree::bulk_load(vec![[0.0, 0.0], [1.0, 0.0], [2.0, 0.0], [1.0, 3.0]]);
+    /// let ring = SelectWithinDistanceFunction::new([0.0, 0.0], 4.0)
+    ///     .and(SelectWithinDistanceFunction::new([0.0, 0.0], 0.5).not());
+    /// let removed: Vec<_> = tree.drain_with_selection_function(ring).collect();
+    /// assert_eq!(removed.len(), 2);
+    /// assert_eq!(tree.size(), 2);
+    /// ```
+    fn not(self) -> SelectNotFunction<Self>
+    where
+        Self: Sized,
+    {
+        SelectNotFunction(self)
+    }
+}
+
+impl<T, S> SelectionFunction<T> for &S
+where
+    T: RTreeObject,
+    S: SelectionFunction<T> + ?Sized,
+{
+    fn should_unpack_parent(&self, envelope: &T::Envelope) -> bool {
+        (**self).should_unpack_parent(envelope)
+    }
+
+    fn should_unpack_leaf(&self, leaf: &T) -> bool {
+        (**self).should_unpack_leaf(leaf)
+    }
+
+    fn should_unpack_parent_at_depth(&self, envelope: &T::Envelope, depth: usize) -> bool {
+        (**self).should_unpack_parent_at_depth(envelope, depth)
+    }
+}
+
+/// A [SelectionFunction] that selects elements selected by two other functions, see
+/// [SelectionFunction::and].
+#[derive(Clone, Copy, Debug)]
+pub struct SelectAndFunction<A, B>(A, B);
+
+impl<T, A, B> SelectionFunction<T> for SelectAndFunction<A, B>
+where
+    T: RTreeObject,
+    A: SelectionFunction<T>,
+    B: SelectionFunction<T>,
+{
+    fn should_unpack_parent(&self, envelope: &T::Envelope) -> bool {
+        self.0.should_unpack_parent(envelope) && self.1.should_unpack_parent(envelope)
+    }
+
+    fn should_unpack_parent_at_depth(&self, envelope: &T::Envelope, depth: usize) -> bool {
+        self.0.should_unpack_parent_at_depth(envelope, depth)
+            && self.1.should_unpack_parent_at_depth(envelope, depth)
+    }
+
+    fn should_unpack_leaf(&self, leaf: &T) -> bool {
+        self.0.should_unpack_leaf(leaf) && self.1.should_unpack_leaf(leaf)
+    }
+}
+
+/// A [SelectionFunction] that selects elements selected by any of two other functions, see
+/// [SelectionFunction::or].
+#[derive(Clone, Copy, Debug)]
+pub struct SelectOrFunction<A, B>(A, B);
+
+impl<T, A, B> SelectionFunction<T> for SelectOrFunction<A, B>
+where
+    T: RTreeObject,
+    A: SelectionFunction<T>,
+    B: SelectionFunction<T>,
+{
+    fn should_unpack_parent(&self, envelope: &T::Envelope) -> bool {
+        self.0.should_unpack_parent(envelope) || self.1.should_unpack_parent(envelope)
+    }
+
+    fn should_unpack_parent_at_depth(&self, envelope: &T::Envelope, depth: usize) -> bool {
+        self.0.should_unpack_parent_at_depth(envelope, depth)
+            || self.1.should_unpack_parent_at_depth(envelope, depth)
+    }
+
+    fn should_unpack_leaf(&self, leaf: &T) -> bool {
+        self.0.should_unpack_leaf(leaf) || self.1.should_unpack_leaf(leaf)
+    }
+}
+
+/// A [SelectionFunction] that selects elements not selected by another function, see
+/// [SelectionFunction::not].
+#[derive(Clone, Copy, Debug)]
+pub struct SelectNotFunction<A>(A);
+
+impl<T, A> SelectionFunction<T> for SelectNotFunction<A>
+where
+    T: RTreeObject,
+    A: SelectionFunction<T>,
+{
+    fn should_unpack_parent(&self, _: &T::Envelope) -> bool {
+        true
+    }
+
+    fn should_unpack_leaf(&self, leaf: &T) -> bool {
+        !self.0.should_unpack_leaf(leaf)
+    }
+}
+
+/// A [SelectionFunction] defined by two closures.
+///
+/// The first closure decides whether a parent node with a given envelope should be unpacked,
+/// the second one whether an element should be selected. This avoids implementing
+/// [SelectionFunction] for one-off queries.
+///
+/// # Example
+/// ```
+/// use rstar::{RTree, SelectFromFns, AABB};
+///
+/// let mut tree = RTree::bulk_load(vec![[-2.0, 1.0], [-1.0, 5.0], [1.0, 1.0], [2.0, 5.0]]);
+/// // Remove everything west of x = 0 that is above y = 2.
+/// let func = SelectFromFns::new(
+///     |envelope: &AABB<[f64; 2]>| envelope.lower()[0] < 0.0,
+///     |point: &[f64; 2]| point[0] < 0.0 && point[1] > 2.0,
+/// );
+/// let removed: Vec<_> = tree.drain_with_selection_function(func).collect();
+/// assert_eq!(removed, vec![[-1.0, 5.0]]);
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct SelectFromFns<P, L> {
+    unpack_parent: P,
+    unpack_leaf: L,
+}
+
+impl<P, L> SelectFromFns<P, L> {
+    /// Creates a new selection function from a parent and a leaf predicate.
+    ///
+    /// The parent predicate must return `true` for every node that might contain a selected
+    /// element, otherwise these elements are not found.
+    pub fn new(unpack_parent: P, unpack_leaf: L) -> Self {
+        SelectFromFns {
+            unpack_parent,
+            unpack_leaf,
+        }
+    }
+}
+
+impl<T, P, L> SelectionFunction<T> for SelectFromFns<P, L>
+where
+    T: RTreeObject,
+    P: Fn(&T::Envelope) -> bool,
+    L: Fn(&T) -> bool,
+{
+    fn should_unpack_parent(&self, envelope: &T::Envelope) -> bool {
+        (self.unpack_parent)(envelope)
+    }
+
+    fn should_unpack_leaf(&self, leaf: &T) -> bool {
+        (self.unpack_leaf)(leaf)
+    }
 }
 
+/// A [SelectionFunction] that selects all elements whose envelope is contained in a given
+/// envelope, see [RTree::locate_in_envelope](crate::RTree::locate_in_envelope).
 pub struct SelectInEnvelopeFunction<T>
 where
     T: RTreeObject,
@@ -48,6 +286,7 @@ impl<T> SelectInEnvelopeFunction<T>
 where
     T: RTreeObject,
 {
+    /// Creates a new selection function selecting all elements contained in `envelope`.
     pub fn new(envelope: T::Envelope) -> Self {
         SelectInEnvelopeFunction { envelope }
     }
@@ -66,6 +305,9 @@ where
     }
 }
 
+/// A [SelectionFunction] that selects all elements whose envelope intersects a given
+/// envelope, see
+/// [RTree::locate_in_envelope_intersecting](crate::RTree::locate_in_envelope_intersecting).
 pub struct SelectInEnvelopeFuncIntersecting<T>
 where
     T: RTreeObject,
@@ -77,6 +319,7 @@ impl<T> SelectInEnvelopeFuncIntersecting<T>
 where
     T: RTreeObject,
 {
+    /// Creates a new selection function selecting all elements intersecting `envelope`.
     pub fn new(envelope: T::Envelope) -> Self {
         SelectInEnvelopeFuncIntersecting { envelope }
     }
@@ -95,6 +338,91 @@ where
     }
 }
 
+/// A [SelectionFunction] that selects all elements whose geometry intersects a given envelope.
+///
+/// Elements are first selected by their envelope like [SelectInEnvelopeFuncIntersecting],
+/// the remaining candidates are refined with [ExactPredicate::intersects_envelope].
+pub struct SelectInEnvelopeFuncIntersectingExact<T>
+where
+    T: RTreeObject,
+{
+    envelope: T::Envelope,
+}
+
+impl<T> SelectInEnvelopeFuncIntersectingExact<T>
+where
+    T: ExactPredicate,
+{
+    /// Creates a new selection function selecting all elements whose geometry intersects
+    /// `envelope`.
+    pub fn new(envelope: T::Envelope) -> Self {
+        SelectInEnvelopeFuncIntersectingExact { envelope }
+    }
+}
+
+impl<T> SelectionFunction<T> for SelectInEnvelopeFuncIntersectingExact<T>
+where
+    T: ExactPredicate,
+{
+    fn should_unpack_parent(&self, envelope: &T::Envelope) -> bool {
+        self.envelope.intersects(envelope)
+    }
+
+    fn should_unpack_leaf(&self, leaf: &T) -> bool {
+        leaf.envelope().intersects(&self.envelope) && leaf.intersects_envelope(&self.envelope)
+    }
+}
+
+/// A [SelectionFunction] that selects all elements whose envelope doesn't intersect a given
+/// envelope.
+///
+/// This is the complement of [SelectInEnvelopeFuncIntersecting]. Only parent nodes contained
+/// in the envelope are pruned, as all of their elements intersect it.
+///
+/// # Example
+/// ```
+/// use rstar::{RTree, SelectOutsideEnvelope, AABB};
+///
+/// let mut tree = RTree::bulk_load(vec![[0.0, 0.0], [1.0, 1.0], [5.0, 5.0], [-3.0, 0.5]]);
+/// // Evict everything outside of the working set.
+/// let working_set = AABB::from_corners([-1.0, -1.0], [2.0, 2.0]);
+/// let outside = SelectOutsideEnvelope::new(working_set);
+/// let evicted: Vec<_> = tree.drain_with_selection_function(outside).collect();
+/// assert_eq!(evicted.len(), 2);
+/// assert_eq!(tree.size(), 2);
+/// ```
+pub struct SelectOutsideEnvelope<T>
+where
+    T: RTreeObject,
+{
+    envelope: T::Envelope,
+}
+
+impl<T> SelectOutsideEnvelope<T>
+where
+    T: RTreeObject,
+{
+    /// Creates a new selection function selecting all elements not intersecting `envelope`.
+    pub fn new(envelope: T::Envelope) -> Self {
+        SelectOutsideEnvelope { envelope }
+    }
+}
+
+impl<T> SelectionFunction<T> for SelectOutsideEnvelope<T>
+where
+    T: RTreeObject,
+{
+    fn should_unpack_parent(&self, envelope: &T::Envelope) -> bool {
+        !self.envelope.contains_envelope(envelope)
+    }
+
+    fn should_unpack_leaf(&self, leaf: &T) -> bool {
+        !leaf.envelope().intersects(&self.envelope)
+    }
+}
+
+/// A [SelectionFunction] that selects all elements.
+#[derive(Clone, Copy, Debug, Default)]
 pub struct SelectAllFunc;
 
 impl<T> SelectionFunction<T> for SelectAllFunc
@@ -106,7 +434,7 @@ where
     }
 }
 
-/// A [trait.SelectionFunction] that only selects elements whose envelope
+/// A [SelectionFunction] that only selects elements whose envelope
 /// contains a specific point.
 pub struct SelectAtPointFunction<T>
 where
@@ -119,6 +447,7 @@ impl<T> SelectAtPointFunction<T>
 where
     T: PointDistance,
 {
+    /// Creates a new selection function selecting all elements containing `point`.
     pub fn new(point: <T::Envelope as Envelope>::Point) -> Self {
         SelectAtPointFunction { point }
     }
@@ -169,6 +498,8 @@ where
     }
 }
 
+/// A [SelectionFunction] that selects all elements within a given distance of a point, see
+/// [RTree::locate_within_distance](crate::RTree::locate_within_distance).
 pub struct SelectWithinDistanceFunction<T>
 where
     T: RTreeObject + PointDistance,
@@ -181,6 +512,8 @@ impl<T> SelectWithinDistanceFunction<T>
 where
     T: RTreeObject + PointDistance,
 {
+    /// Creates a new selection function selecting all elements whose squared distance to
+    /// `circle_origin` is at most `squared_max_distance`.
     pub fn new(
         circle_origin: <T::Envelope as Envelope>::Point,
         squared_max_distance: <<T::Envelope as Envelope>::Point as Point>::Scalar,
@@ -207,6 +540,162 @@ where
     }
 }
 
+/// A [SelectionFunction] that selects all elements within a given distance of a line segment.
+///
+/// An element is selected if its envelope is within the distance, which is exact for points.
+/// Parent nodes are pruned using the exact distance between the segment and their envelope.
+///
+/// # Example
+/// ```
+/// use rstar::{RTree, SelectNearSegmentFunction};
+///
+/// let mut tree = RTree::bulk_load(vec![[0.0, 1.0], [2.0, 0.5], [5.0, 2.0], [1.0, -3.0]]);
+/// // Remove everything within a corridor of width 2 along a route.
+/// let corridor = SelectNearSegmentFunction::new([0.0, 0.0], [4.0, 0.0], 1.0);
+/// let removed: Vec<_> = tree.drain_with_selection_function(corridor).collect();
+/// assert_eq!(removed.len(), 2);
+/// assert_eq!(tree.size(), 2);
+/// ```
+pub struct SelectNearSegmentFunction<P>
+where
+    P: Point,
+{
+    from: P,
+    to: P,
+    squared_max_distance: P::Scalar,
+}
+
+impl<P> SelectNearSegmentFunction<P>
+where
+    P: Point,
+    P::Scalar: Float,
+{
+    /// Creates a new selection function selecting all elements whose squared distance to the
+    /// segment from `from` to `to` is at most `squared_max_distance`.
+    pub fn new(from: P, to: P, squared_max_distance: P::Scalar) -> Self {
+        SelectNearSegmentFunction {
+            from,
+            to,
+            squared_max_distance,
+        }
+    }
+}
+
+impl<T, P> SelectionFunction<T> for SelectNearSegmentFunction<P>
+where
+    T: RTreeObject<Envelope = AABB<P>>,
+    P: Point,
+    P::Scalar: Float,
+{
+    fn should_unpack_parent(&self, envelope: &AABB<P>) -> bool {
+        envelope.segment_distance_2(&self.from, &self.to) <= self.squared_max_distance
+    }
+
+    fn should_unpack_leaf(&self, leaf: &T) -> bool {
+        leaf.envelope().segment_distance_2(&self.from, &self.to) <= self.squared_max_distance
+    }
+}
+
+/// A [SelectionFunction] that selects all elements whose envelope is hit by a ray.
+///
+/// The ray starts at an origin and extends infinitely into a direction. For points, only
+/// those exactly on the ray are selected.
+///
+/// # Example
+/// ```
+/// use rstar::primitives::Rectangle;
+/// use rstar::{RTree, SelectIntersectingRay};
+///
+/// let mut tree = RTree::bulk_load(vec![
+///     Rectangle::from_corners([1.0, -1.0], [2.0, 1.0]),
+///     Rectangle::from_corners([3.0, 0.5], [4.0, 2.0]),
+///     Rectangle::from_corners([-2.0, -1.0], [-1.0, 1.0]),
+/// ]);
+/// // Remove everything hit by a laser pointing along the x axis.
+/// let laser = SelectIntersectingRay::new([0.0, 0.0], [1.0, 0.0]);
+/// let hit: Vec<_> = tree.drain_with_selection_function(laser).collect();
+/// assert_eq!(hit, vec![Rectangle::from_corners([1.0, -1.0], [2.0, 1.0])]);
+/// ```
+pub struct SelectIntersectingRay<P>
+where
+    P: Point,
+{
+    origin: P,
+    direction: P,
+}
+
+impl<P> SelectIntersectingRay<P>
+where
+    P: Point,
+    P::Scalar: Float,
+{
+    /// Creates a new selection function for a ray starting at `origin`.
+    pub fn new(origin: P, direction: P) -> Self {
+        SelectIntersectingRay { origin, direction }
+    }
+}
+
+impl<T, P> SelectionFunction<T> for SelectIntersectingRay<P>
+where
+    T: RTreeObject<Envelope = AABB<P>>,
+    P: Point,
+    P::Scalar: Float,
+{
+    fn should_unpack_parent(&self, envelope: &AABB<P>) -> bool {
+        envelope
+            .ray_intersection(&self.origin, &self.direction)
+            .is_some()
+    }
+
+    fn should_unpack_leaf(&self, leaf: &T) -> bool {
+        leaf.envelope()
+            .ray_intersection(&self.origin, &self.direction)
+            .is_some()
+    }
+}
+
+/// Selects all elements whose envelope intersects the volume swept by a moving AABB.
+///
+/// The AABB moves from its position by `displacement`, see
+/// [RTree::locate_intersecting_swept](crate::RTree::locate_intersecting_swept).
+pub struct SelectIntersectingSwept<P>
+where
+    P: Point,
+{
+    aabb: AABB<P>,
+    displacement: P,
+}
+
+impl<P> SelectIntersectingSwept<P>
+where
+    P: Point,
+    P::Scalar: Float,
+{
+    /// Creates a new selection function for `aabb` moving by `displacement`.
+    pub fn new(aabb: AABB<P>, displacement: P) -> Self {
+        SelectIntersectingSwept { aabb, displacement }
+    }
+}
+
+impl<T, P> SelectionFunction<T> for SelectIntersectingSwept<P>
+where
+    T: RTreeObject<Envelope = AABB<P>>,
+    P: Point,
+    P::Scalar: Float,
+{
+    fn should_unpack_parent(&self, envelope: &AABB<P>) -> bool {
+        envelope
+            .sweep_intersection(&self.aabb, &self.displacement)
+            .is_some()
+    }
+
+    fn should_unpack_leaf(&self, leaf: &T) -> bool {
+        leaf.envelope()
+            .sweep_intersection(&self.aabb, &self.displacement)
+            .is_some()
+    }
+}
+
 pub struct SelectByAddressFunction<T>
 where
     T: RTreeObject,
@@ -239,3 +728,166 @@ where
         core::ptr::eq(self.element_address, leaf)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::{
+        SelectFromFns, SelectInEnvelopeFunction, SelectIntersectingRay, SelectNearSegmentFunction,
+        SelectOutsideEnvelope, SelectWithinDistanceFunction, SelectionFunction,
+    };
+    use crate::aabb::AABB;
+    use crate::envelope::Envelope;
+    use crate::object::PointDistance;
+    use crate::rtree::RTree;
+    use crate::test_utilities::{create_random_points, SEED_1};
+
+    fn check<S, F>(points: &[[f64; 2]], func: S, filter: F)
+    where
+        S: SelectionFunction<[f64; 2]>,
+        F: Fn(&[f64; 2]) -> bool,
+    {
+        let tree = RTree::bulk_load(points.to_vec());
+        let mut expected: Vec<_> = points.iter().filter(|p| filter(p)).collect();
+        let mut found: Vec<_> = tree.locate_with_selection_function(func).collect();
+        assert!(!expected.is_empty());
+        expected.sort_by(|l, r| l.partial_cmp(r).unwrap());
+        found.sort_by(|l, r| l.partial_cmp(r).unwrap());
+        assert_eq!(found, expected);
+    }
+
+    #[test]
+    fn test_combinators() {
+        let points = create_random_points(500, SEED_1);
+        let envelope = AABB::from_corners([0.0, 0.0], [0.5, 0.5]);
+        let in_envelope = || SelectInEnvelopeFunction::new(envelope);
+        let close = || SelectWithinDistanceFunction::new([0.5, 0.5], 0.1);
+        let contained = |p: &[f64; 2]| envelope.contains_point(p);
+        let within = |p: &[f64; 2]| p.distance_2(&[0.5, 0.5]) <= 0.1;
+
+        check(&points, in_envelope().and(close()), |p| {
+            contained(p) && within(p)
+        });
+        check(&points, in_envelope().or(close()), |p| {
+            contained(p) || within(p)
+        });
+        check(&points, in_envelope().not(), |p| !contained(p));
+        check(&points, close().and(in_envelope().not()), |p| {
+            within(p) && !contained(p)
+        });
+    }
+
+    #[test]
+    fn test_select_from_fns() {
+        let points = create_random_points(500, SEED_1);
+        let func = SelectFromFns::new(
+            |envelope: &AABB<[f64; 2]>| envelope.lower()[0] < 0.5,
+            |point: &[f64; 2]| point[0] < 0.5 && point[1] > 0.2,
+        );
+        check(&points, func, |p| p[0] < 0.5 && p[1] > 0.2);
+    }
+
+    #[test]
+    fn test_select_near_segment() {
+        use crate::primitives::Line;
+
+        let points = create_random_points(500, SEED_1);
+        let segment = Line::new([0.1, 0.2], [0.9, 0.6]);
+        let func = SelectNearSegmentFunction::new(segment.from, segment.to, 0.01);
+        check(&points, func, |p| segment.distance_2(p) <= 0.01);
+    }
+
+    #[test]
+    fn test_select_intersecting_ray() {
+        use crate::object::RTreeObject;
+        use crate::test_utilities::create_random_rectangles;
+
+        let rectangles = create_random_rectangles(500, SEED_1);
+        let tree = RTree::bulk_load(rectangles.clone());
+        let (origin, direction) = ([0.5, -0.5], [0.2, 1.0]);
+        // All rectangles are below y = 2.
+        let segment = ([0.5, -0.5], [1.0, 2.0]);
+        let mut expected: Vec<_> = rectangles
+            .iter()
+            .filter(|r| r.envelope().segment_distance_2(&segment.0, &segment.1) == 0.0)
+            .collect();
+        let mut found: Vec<_> = tree
+            .locate_with_selection_function(SelectIntersectingRay::new(origin, direction))
+            .collect();
+        assert!(!expected.is_empty());
+        expected.sort_by(|l, r| l.partial_cmp(r).unwrap());
+        found.sort_by(|l, r| l.partial_cmp(r).unwrap());
+        assert_eq!(found, expected);
+    }
+
+    #[test]
+    fn test_unpack_parent_at_depth() {
+        use core::cell::RefCell;
+
+        struct LimitDepth {
+            max_depth: usize,
+            depths: RefCell<Vec<usize>>,
+        }
+
+        impl SelectionFunction<[f64; 2]> for LimitDepth {
+            fn should_unpack_parent(&self, _: &AABB<[f64; 2]>) -> bool {
+                unreachable!()
+            }
+
+            fn should_unpack_parent_at_depth(&self, _: &AABB<[f64; 2]>, depth: usize) -> bool {
+                self.depths.borrow_mut().push(depth);
+                depth <= self.max_depth
+            }
+        }
+
+        let mut tree = RTree::bulk_load(create_random_points(1000, SEED_1));
+        let levels = tree.iter_nodes_breadth_first().last().unwrap().0 + 1;
+        assert!(levels > 2);
+        let limit = |max_depth| LimitDepth {
+            max_depth,
+            depths: RefCell::new(Vec::new()),
+        };
+
+        let func = limit(levels);
+        assert_eq!(tree.locate_with_selection_function(&func).count(), 1000);
+        let depths = func.depths.into_inner();
+        assert_eq!(depths.len(), tree.iter_nodes_breadth_first().count());
+        for depth in 0..levels {
+            let expected = tree
+                .iter_nodes_breadth_first()
+                .filter(|&(d, _)| d == depth)
+                .count();
+            assert_eq!(depths.iter().filter(|&&d| d == depth).count(), expected);
+        }
+
+        let func = limit(levels - 2);
+        assert_eq!(tree.locate_with_selection_function(&func).count(), 0);
+        assert_eq!(func.depths.borrow().iter().max(), Some(&(levels - 1)));
+        assert_eq!(tree.drain_with_selection_function(&func).count(), 0);
+        assert_eq!(tree.locate_with_selection_function_mut(&func).count(), 0);
+        assert_eq!(tree.size(), 1000);
+    }
+
+    #[test]
+    fn test_select_outside_envelope() {
+        use crate::object::RTreeObject;
+        use crate::test_utilities::create_random_rectangles;
+
+        let rectangles = create_random_rectangles(500, SEED_1);
+        let mut tree = RTree::bulk_load(rectangles.clone());
+        let envelope = AABB::from_corners([0.2, 0.1], [0.8, 0.7]);
+        let mut expected: Vec<_> = rectangles
+            .iter()
+            .filter(|r| !r.envelope().intersects(&envelope))
+            .cloned()
+            .collect();
+        let mut found: Vec<_> = tree
+            .drain_with_selection_function(SelectOutsideEnvelope::new(envelope))
+            .collect();
+        assert!(!expected.is_empty());
+        expected.sort_by(|l, r| l.partial_cmp(r).unwrap());
+        found.sort_by(|l, r| l.partial_cmp(r).unwrap());
+        assert_eq!(found, expected);
+        assert_eq!(tree.size(), 500 - expected.len());
+        assert!(tree.iter().all(|r| r.envelope().intersects(&envelope)));
+    }
+}
@@ -0,0 +1,190 @@
+use crate::envelope::Envelope;
+use crate::node::{envelope_for_children, Children, ParentNode, RTreeNode};
+use crate::object::RTreeObject;
+use crate::params::{RTreeParams, SplitStrategy};
+use crate::point::Point;
+
+use alloc::vec::Vec;
+use num_traits::Signed;
+
+/// Splits overflowing nodes with Guttman's linear split.
+///
+/// The two children whose centers lie farthest apart along any axis seed the two new nodes.
+/// All other children are added to the node whose envelope grows least. Splitting takes
+/// linear time, but results in more overlapping nodes than the other strategies.
+///
+/// See [SplitStrategy] for more information on split strategies.
+pub enum LinearSplitStrategy {}
+
+/// Splits overflowing nodes with Guttman's quadratic split.
+///
+/// The two children that would waste the most area if they were put into the same node seed
+/// the two new nodes. The remaining children are distributed one by one, always picking the
+/// child with the strongest preference for one of the nodes. Splitting takes quadratic time
+/// and yields trees of moderate quality.
+///
+/// See [SplitStrategy] for more information on split strategies.
+pub enum QuadraticSplitStrategy {}
+
+impl SplitStrategy for LinearSplitStrategy {
+    fn split<T, Params>(node: &mut ParentNode<T>) -> RTreeNode<T>
+    where
+        T: RTreeObject,
+        Params: RTreeParams,
+    {
+        let (first, second) = linear_pick_seeds(&node.children);
+        let seeds = (first.min(second), first.max(second));
+        distribute::<_, Params>(node, seeds, |_, entries| entries.len() - 1)
+    }
+}
+
+impl SplitStrategy for QuadraticSplitStrategy {
+    fn split<T, Params>(node: &mut ParentNode<T>) -> RTreeNode<T>
+    where
+        T: RTreeObject,
+        Params: RTreeParams,
+    {
+        let seeds = quadratic_pick_seeds(&node.children);
+        distribute::<_, Params>(node, seeds, quadratic_pick_next)
+    }
+}
+
+/// Returns the indices of the two children whose centers are separated the most along any
+/// axis.
+fn linear_pick_seeds<T>(children: &[RTreeNode<T>]) -> (usize, usize)
+where
+    T: RTreeObject,
+{
+    let centers: Vec<_> = children
+        .iter()
+        .map(|child| child.envelope().center())
+        .collect();
+    let mut best = None;
+    let mut seeds = (0, 1);
+    for axis in 0..<T::Envelope as Envelope>::Point::DIMENSIONS {
+        let (mut min, mut max) = (0, 0);
+        for (index, center) in centers.iter().enumerate() {
+            if center.nth(axis) < centers[min].nth(axis) {
+                min = index;
+            }
+            if center.nth(axis) > centers[max].nth(axis) {
+                max = index;
+            }
+        }
+        let separation = centers[max].nth(axis) - centers[min].nth(axis);
+        if min != max && best.map_or(true, |best| separation > best) {
+            best = Some(separation);
+            seeds = (min, max);
+        }
+    }
+    seeds
+}
+
+/// Returns the indices of the two children which would waste the most area if they were
+/// stored in the same node.
+fn quadratic_pick_seeds<T>(children: &[RTreeNode<T>]) -> (usize, usize)
+where
+    T: RTreeObject,
+{
+    let envelopes: Vec<_> = children.iter().map(RTreeNode::envelope).collect();
+    let mut best = None;
+    let mut seeds = (0, 1);
+    for (i, first) in envelopes.iter().enumerate() {
+        for (j, second) in envelopes.iter().enumerate().skip(i + 1) {
+            let waste = first.merged(second).area() - first.area() - second.area();
+            if best.map_or(true, |best| waste > best) {
+                best = Some(waste);
+                seeds = (i, j);
+            }
+        }
+    }
+    seeds
+}
+
+/// Returns the index of the entry with the strongest preference for one of the two groups.
+fn quadratic_pick_next<T>(groups: [&T::Envelope; 2], entries: &[RTreeNode<T>]) -> usize
+where
+    T: RTreeObject,
+{
+    let mut best = None;
+    let mut best_index = 0;
+    for (index, entry) in entries.iter().enumerate() {
+        let [first, second] = enlargements::<T>(groups, &entry.envelope());
+        let preference = (first - second).abs();
+        if best.map_or(true, |best| preference > best) {
+            best = Some(preference);
+            best_index = index;
+        }
+    }
+    best_index
+}
+
+fn enlargements<T>(
+    groups: [&T::Envelope; 2],
+    envelope: &T::Envelope,
+) -> [<<T::Envelope as Envelope>::Point as Point>::Scalar; 2]
+where
+    T: RTreeObject,
+{
+    let [first, second] = groups;
+    [
+        first.merged(envelope).area() - first.area(),
+        second.merged(envelope).area() - second.area(),
+    ]
+}
+
+/// Distributes the children of an overflowing node onto two groups, starting with the given
+/// seeds. The node keeps the first group, the second group is returned as new node.
+fn distribute<T, Params>(
+    node: &mut ParentNode<T>,
+    (first_seed, second_seed): (usize, usize),
+    pick_next: impl Fn([&T::Envelope; 2], &[RTreeNode<T>]) -> usize,
+) -> RTreeNode<T>
+where
+    T: RTreeObject,
+    Params: RTreeParams,
+{
+    debug_assert!(first_seed < second_seed);
+    let capacity = node.max_size::<Params>() + 1;
+    let mut entries: Vec<_> = node.children.drain(..).collect();
+    let second = entries.swap_remove(second_seed);
+    let first = entries.swap_remove(first_seed);
+    let mut envelopes = [first.envelope(), second.envelope()];
+    let mut groups = [
+        Children::with_capacity(capacity),
+        Children::with_capacity(capacity),
+    ];
+    groups[0].push(first);
+    groups[1].push(second);
+
+    while !entries.is_empty() {
+        // Make sure that both groups end up with at least MIN_SIZE children.
+        if let Some(group) = (0..2).find(|&g| groups[g].len() + entries.len() <= Params::MIN_SIZE) {
+            for entry in entries.drain(..) {
+                envelopes[group].merge(&entry.envelope());
+                groups[group].push(entry);
+            }
+            break;
+        }
+        let index = pick_next([&envelopes[0], &envelopes[1]], &entries);
+        let entry = entries.swap_remove(index);
+        let envelope = entry.envelope();
+        let [first, second] = enlargements::<T>([&envelopes[0], &envelopes[1]], &envelope);
+        let group = if first != second {
+            usize::from(second < first)
+        } else if envelopes[0].area() != envelopes[1].area() {
+            usize::from(envelopes[1].area() < envelopes[0].area())
+        } else {
+            usize::from(groups[1].len() < groups[0].len())
+        };
+        envelopes[group].merge(&envelope);
+        groups[group].push(entry);
+    }
+
+    let [first, second] = groups;
+    node.children = first;
+    node.envelope = envelope_for_children(&node.children);
+    let off_split = ParentNode::new_parent(second);
+    node.size -= off_split.size;
+    RTreeNode::Parent(off_split)
+}
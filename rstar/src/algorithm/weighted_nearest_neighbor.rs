@@ -0,0 +1,197 @@
+use crate::algorithm::nearest_neighbor::RTreeNodeDistanceWrapper;
+use crate::node::{ParentNode, RTreeNode};
+use crate::point::Point;
+use crate::{Envelope, PointDistance};
+
+use alloc::collections::BinaryHeap;
+use num_traits::Float;
+
+type Scalar<T> = <<<T as crate::RTreeObject>::Envelope as Envelope>::Point as Point>::Scalar;
+
+/// Combines the distance of an element with its weight, see
+/// [RTree::nearest_neighbor_iter_weighted](crate::RTree::nearest_neighbor_iter_weighted).
+///
+/// Weighted nearest neighbor queries can only prune subtrees if a lower bound of the weighted
+/// distance of all elements in a subtree is known. [AdditiveWeight] and
+/// [MultiplicativeWeight] derive this bound from a global bound of all weights.
+pub trait Weighting<T>
+where
+    T: PointDistance,
+{
+    /// Returns the weighted distance of an element, given its euclidean distance.
+    fn weighted_distance(&self, element: &T, distance: Scalar<T>) -> Scalar<T>;
+
+    /// Returns a lower bound for the weighted distance of all elements within a node, given
+    /// the euclidean distance of the node's envelope.
+    fn lower_bound(&self, distance: Scalar<T>) -> Scalar<T>;
+}
+
+/// Subtracts a weight from the distance of every element: `distance - weight(element)`.
+///
+/// This corresponds to an additively weighted voronoi diagram, e.g. elements with a larger
+/// weight "reach" further. All weights must be less or equal to `max_weight`.
+pub struct AdditiveWeight<F, S> {
+    weight: F,
+    max_weight: S,
+}
+
+impl<F, S> AdditiveWeight<F, S> {
+    /// Creates a new weighting from a weight function and the largest possible weight.
+    pub fn new(weight: F, max_weight: S) -> Self {
+        AdditiveWeight { weight, max_weight }
+    }
+}
+
+impl<T, F> Weighting<T> for AdditiveWeight<F, Scalar<T>>
+where
+    T: PointDistance,
+    F: Fn(&T) -> Scalar<T>,
+{
+    fn weighted_distance(&self, element: &T, distance: Scalar<T>) -> Scalar<T> {
+        distance - (self.weight)(element)
+    }
+
+    fn lower_bound(&self, distance: Scalar<T>) -> Scalar<T> {
+        distance - self.max_weight
+    }
+}
+
+/// Multiplies the distance of every element with a weight: `distance * weight(element)`.
+///
+/// This corresponds to a multiplicatively weighted voronoi diagram. All weights must be
+/// greater or equal to `min_weight`, which must not be negative.
+pub struct MultiplicativeWeight<F, S> {
+    weight: F,
+    min_weight: S,
+}
+
+impl<F, S> MultiplicativeWeight<F, S> {
+    /// Creates a new weighting from a weight function and the smallest possible weight.
+    pub fn new(weight: F, min_weight: S) -> Self {
+        MultiplicativeWeight { weight, min_weight }
+    }
+}
+
+impl<T, F> Weighting<T> for MultiplicativeWeight<F, Scalar<T>>
+where
+    T: PointDistance,
+    F: Fn(&T) -> Scalar<T>,
+{
+    fn weighted_distance(&self, element: &T, distance: Scalar<T>) -> Scalar<T> {
+        distance * (self.weight)(element)
+    }
+
+    fn lower_bound(&self, distance: Scalar<T>) -> Scalar<T> {
+        distance * self.min_weight
+    }
+}
+
+/// Iterator returned by
+/// [RTree::nearest_neighbor_iter_weighted](crate::RTree::nearest_neighbor_iter_weighted).
+pub struct WeightedNearestNeighborIterator<'a, T, W>
+where
+    T: PointDistance + 'a,
+{
+    nodes: BinaryHeap<RTreeNodeDistanceWrapper<'a, T>>,
+    query_point: <T::Envelope as Envelope>::Point,
+    weighting: W,
+}
+
+impl<'a, T, W> WeightedNearestNeighborIterator<'a, T, W>
+where
+    T: PointDistance,
+    Scalar<T>: Float,
+    W: Weighting<T>,
+{
+    pub(crate) fn new(
+        root: &'a ParentNode<T>,
+        query_point: <T::Envelope as Envelope>::Point,
+        weighting: W,
+    ) -> Self {
+        let mut iterator = WeightedNearestNeighborIterator {
+            nodes: BinaryHeap::new(),
+            query_point,
+            weighting,
+        };
+        iterator.extend(&root.children);
+        iterator
+    }
+
+    fn extend(&mut self, children: &'a [RTreeNode<T>]) {
+        let query_point = &self.query_point;
+        let weighting = &self.weighting;
+        self.nodes.extend(children.iter().map(|child| {
+            let distance = match child {
+                RTreeNode::Parent(ref data) => {
+                    weighting.lower_bound(data.envelope.distance_2(query_point).sqrt())
+                }
+                RTreeNode::Leaf(ref t) => {
+                    weighting.weighted_distance(t, t.distance_2(query_point).sqrt())
+                }
+            };
+            RTreeNodeDistanceWrapper {
+                node: child,
+                distance,
+            }
+        }));
+    }
+}
+
+impl<'a, T, W> Iterator for WeightedNearestNeighborIterator<'a, T, W>
+where
+    T: PointDistance,
+    Scalar<T>: Float,
+    W: Weighting<T>,
+{
+    type Item = (&'a T, Scalar<T>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(current) = self.nodes.pop() {
+            match current.node {
+                RTreeNode::Parent(ref data) => self.extend(&data.children),
+                RTreeNode::Leaf(ref t) => return Some((t, current.distance)),
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{AdditiveWeight, MultiplicativeWeight};
+    use crate::primitives::GeomWithData;
+    use crate::test_utilities::{create_random_points, SEED_1, SEED_2};
+    use crate::{PointDistance, RTree};
+
+    #[test]
+    fn test_weighted_nearest_neighbors() {
+        let weights = create_random_points(500, SEED_2);
+        let elements: Vec<_> = create_random_points(500, SEED_1)
+            .into_iter()
+            .zip(weights)
+            .map(|(point, [weight, _])| GeomWithData::new(point, weight))
+            .collect();
+        let tree = RTree::bulk_load(elements);
+        let weight = |element: &GeomWithData<[f64; 2], f64>| element.data;
+
+        for query in create_random_points(20, SEED_2) {
+            let additive: Vec<_> = tree
+                .nearest_neighbor_iter_weighted(&query, AdditiveWeight::new(weight, 1.0))
+                .collect();
+            let multiplicative: Vec<_> = tree
+                .nearest_neighbor_iter_weighted(&query, MultiplicativeWeight::new(weight, 0.0))
+                .collect();
+            for (weighted, combine) in [
+                (additive, (|d, w| d - w) as fn(f64, f64) -> f64),
+                (multiplicative, |d, w| d * w),
+            ] {
+                assert_eq!(weighted.len(), 500);
+                for (element, distance) in &weighted {
+                    let expected = combine(element.distance_2(&query).sqrt(), element.data);
+                    assert_eq!(*distance, expected);
+                }
+                assert!(weighted.windows(2).all(|pair| pair[0].1 <= pair[1].1));
+            }
+        }
+    }
+}
@@ -0,0 +1,245 @@
+use crate::binary::{crc32, ReadTreeError};
+use crate::packed::{PackedElement, PackedScalar};
+use crate::params::{DefaultParams, RTreeParams};
+use crate::{Point, RTree, AABB};
+
+use alloc::vec::Vec;
+use std::io::{self, Read, Write};
+
+const INSERT: u8 = 1;
+const REMOVE: u8 = 2;
+
+/// An [RTree] that appends every modification to a log.
+///
+/// Together with [RTree::write_to] and [RTree::read_from], the log makes a tree durable
+/// without serializing the whole tree after every change:
+///
+///  1. Load the last snapshot of the tree with [RTree::read_from].
+///  2. Apply all modifications made since the snapshot with [RTree::replay_log].
+///  3. Continue modifying the tree through a `LoggedRTree` that appends to the same log.
+///  4. Occasionally write a new snapshot and start a new, empty log.
+///
+/// Every modification is written to the log before it is applied to the tree. Every record
+/// consists of a record type (`u8`, `1` for insertions and `2` for removals), the element
+/// encoded as described by [PackedElement] and the CRC-32 checksum of the record type and the
+/// element (`u32`, little endian). Records are written with a single call to `write_all`;
+/// use a buffered writer and [LoggedRTree::flush] to group several modifications. Requires
+/// the `std` feature.
+///
+/// # Example
+/// ```
+/// use rstar::{LoggedRTree, RTree};
+///
+/// let mut tree = LoggedRTree::new(RTree::new(), Vec::new());
+/// tree.insert([0.0, 0.0]).unwrap();
+/// tree.insert([1.0, 1.0]).unwrap();
+/// tree.remove(&[0.0, 0.0]).unwrap();
+/// let (tree, log) = tree.into_parts();
+///
+/// // After a restart, the log restores all changes made since the last snapshot.
+/// let mut restored = RTree::new();
+/// let replay = restored.replay_log(log.as_slice()).unwrap();
+/// assert_eq!(replay.records, 3);
+/// assert_eq!(restored.size(), 1);
+/// assert_eq!(restored.nearest_neighbor(&[0.0, 0.0]), tree.nearest_neighbor(&[0.0, 0.0]));
+/// ```
+pub struct LoggedRTree<T, W, Params = DefaultParams>
+where
+    T: PackedElement,
+    Params: RTreeParams,
+{
+    tree: RTree<T, Params>,
+    log: W,
+}
+
+/// The result of [RTree::replay_log].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LogReplay {
+    /// The number of records that were applied.
+    pub records: usize,
+    /// The length in bytes of the log's valid part.
+    ///
+    /// If the last record was only partially written, e.g. due to a crash, it is ignored and
+    /// this is smaller than the log. Truncate the log to this length before appending to it.
+    pub valid_length: u64,
+}
+
+impl<T, P, W, Params> LoggedRTree<T, W, Params>
+where
+    T: PackedElement<Envelope = AABB<P>>,
+    P: Point,
+    P::Scalar: PackedScalar,
+    W: Write,
+    Params: RTreeParams,
+{
+    /// Creates a new logged tree that appends all modifications of `tree` to `log`.
+    pub fn new(tree: RTree<T, Params>, log: W) -> Self {
+        LoggedRTree { tree, log }
+    }
+
+    fn append(&mut self, record_type: u8, t: &T) -> io::Result<()> {
+        let mut record = alloc::vec![0; T::SIZE + 5];
+        record[0] = record_type;
+        t.write_packed(&mut record[1..]);
+        let checksum = crc32(&record[..T::SIZE + 1]);
+        record[T::SIZE + 1..].copy_from_slice(&checksum.to_le_bytes());
+        self.log.write_all(&record)
+    }
+
+    /// Logs and inserts a new element.
+    ///
+    /// The element is not inserted if it could not be logged.
+    pub fn insert(&mut self, t: T) -> io::Result<()> {
+        self.append(INSERT, &t)?;
+        self.tree.insert(t);
+        Ok(())
+    }
+
+    /// Flushes the log.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.log.flush()
+    }
+
+    /// Returns the tree.
+    pub fn tree(&self) -> &RTree<T, Params> {
+        &self.tree
+    }
+
+    /// Returns the log.
+    pub fn log(&self) -> &W {
+        &self.log
+    }
+
+    /// Returns the log, e.g. to synchronize it to disk.
+    pub fn log_mut(&mut self) -> &mut W {
+        &mut self.log
+    }
+
+    /// Returns the tree and the log.
+    pub fn into_parts(self) -> (RTree<T, Params>, W) {
+        (self.tree, self.log)
+    }
+}
+
+impl<T, P, W, Params> LoggedRTree<T, W, Params>
+where
+    T: PackedElement<Envelope = AABB<P>> + PartialEq,
+    P: Point,
+    P::Scalar: PackedScalar,
+    W: Write,
+    Params: RTreeParams,
+{
+    /// Logs and removes an element equal to `t`.
+    ///
+    /// Nothing is logged if the tree doesn't contain such an element. The element is not
+    /// removed if it could not be logged.
+    pub fn remove(&mut self, t: &T) -> io::Result<Option<T>> {
+        if !self.tree.contains(t) {
+            return Ok(None);
+        }
+        self.append(REMOVE, t)?;
+        Ok(self.tree.remove(t))
+    }
+}
+
+impl<T, P, Params> RTree<T, Params>
+where
+    T: PackedElement<Envelope = AABB<P>> + PartialEq,
+    P: Point,
+    P::Scalar: PackedScalar,
+    Params: RTreeParams,
+{
+    /// Applies all modifications recorded by a [LoggedRTree] to this tree.
+    ///
+    /// A partially written record at the end of the log is ignored, see
+    /// [LogReplay::valid_length]. Any other invalid record causes an error; all records
+    /// preceding it have been applied in this case. Requires the `std` feature.
+    pub fn replay_log<R: Read>(&mut self, mut reader: R) -> Result<LogReplay, ReadTreeError> {
+        let record_size = T::SIZE + 5;
+        let mut replay = LogReplay {
+            records: 0,
+            valid_length: 0,
+        };
+        let mut record = Vec::with_capacity(record_size);
+        loop {
+            record.clear();
+            reader
+                .by_ref()
+                .take(record_size as u64)
+                .read_to_end(&mut record)?;
+            if record.len() < record_size {
+                return Ok(replay);
+            }
+            let mut checksum = [0; 4];
+            checksum.copy_from_slice(&record[T::SIZE + 1..]);
+            if u32::from_le_bytes(checksum) != crc32(&record[..T::SIZE + 1]) {
+                return Err(ReadTreeError::ChecksumMismatch);
+            }
+            let t = T::read_packed(&record[1..]);
+            match record[0] {
+                INSERT => self.insert(t),
+                REMOVE => {
+                    self.remove(&t);
+                }
+                record_type => return Err(ReadTreeError::InvalidRecord(record_type)),
+            }
+            replay.records += 1;
+            replay.valid_length += record_size as u64;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{LogReplay, LoggedRTree};
+    use crate::binary::ReadTreeError;
+    use crate::test_utilities::{create_random_points, SEED_1};
+    use crate::RTree;
+
+    #[test]
+    fn test_replay_log() {
+        let points = create_random_points(200, SEED_1);
+        let (base, rest) = points.split_at(100);
+        let mut snapshot = Vec::new();
+        RTree::bulk_load(base.to_vec())
+            .write_to(&mut snapshot)
+            .unwrap();
+
+        let base: RTree<[f64; 2]> = RTree::read_from(snapshot.as_slice()).unwrap();
+        let mut tree = LoggedRTree::new(base, Vec::new());
+        for point in rest {
+            tree.insert(*point).unwrap();
+        }
+        for point in &points[50..150] {
+            assert_eq!(tree.remove(point).unwrap(), Some(*point));
+        }
+        assert_eq!(tree.remove(&[2.0, 2.0]).unwrap(), None);
+        let (tree, log) = tree.into_parts();
+        assert_eq!(log.len(), 200 * 21);
+
+        let mut restored: RTree<[f64; 2]> = RTree::read_from(snapshot.as_slice()).unwrap();
+        // The last record was only partially written.
+        let replay = restored.replay_log(&log[..log.len() - 3]).unwrap();
+        assert_eq!(
+            replay,
+            LogReplay {
+                records: 199,
+                valid_length: 199 * 21
+            }
+        );
+        assert_eq!(restored.size(), 101);
+        restored.replay_log(&log[199 * 21..]).unwrap();
+        let mut expected: Vec<_> = tree.iter().collect();
+        let mut found: Vec<_> = restored.iter().collect();
+        expected.sort_by(|l, r| l.partial_cmp(r).unwrap());
+        found.sort_by(|l, r| l.partial_cmp(r).unwrap());
+        assert_eq!(found, expected);
+
+        let mut corrupted = log;
+        corrupted[30] ^= 1;
+        assert!(matches!(
+            RTree::<[f64; 2]>::new().replay_log(corrupted.as_slice()),
+            Err(ReadTreeError::ChecksumMismatch)
+        ));
+    }
+}
@@ -0,0 +1,498 @@
+use crate::algorithm::selection_functions::*;
+use crate::node::{ParentNode, RTreeNode};
+use crate::params::RTreeParams;
+use crate::point::Point;
+use crate::{Envelope, PointDistance, RTree, RTreeObject};
+
+use alloc::collections::{BinaryHeap, VecDeque};
+use alloc::vec::Vec;
+use core::ops::Range;
+use smallvec::SmallVec;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A read-only r-tree storing all of its nodes in a single contiguous arena.
+///
+/// An [RTree] allocates every node separately. Converting it into an `ArenaRTree` moves all
+/// inner nodes into one vector and all elements into another one. Nodes refer to their
+/// children by index, and the children of every node are stored next to each other. This
+/// improves the cache behavior of queries and makes the tree trivial to serialize.
+///
+/// The tree cannot be modified after its creation. Use [ArenaRTree::into_elements] to
+/// recover the elements, e.g. to build a new [RTree].
+///
+/// # Example
+/// ```
+/// use rstar::{ArenaRTree, RTree, AABB};
+///
+/// let tree = RTree::bulk_load(vec![[0.0, 0.0], [1.0, 1.0], [2.0, 2.0]]);
+/// let arena = ArenaRTree::from(tree);
+///
+/// assert_eq!(arena.nearest_neighbor(&[1.2, 0.9]), Some(&[1.0, 1.0]));
+/// let envelope = AABB::from_corners([0.5, 0.5], [3.0, 3.0]);
+/// assert_eq!(arena.locate_in_envelope(&envelope).count(), 2);
+/// ```
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "T: Serialize, T::Envelope: Serialize",
+        deserialize = "T: Deserialize<'de>, T::Envelope: Deserialize<'de>"
+    ))
+)]
+pub struct ArenaRTree<T>
+where
+    T: RTreeObject,
+{
+    /// All inner nodes in breadth first order, the root is stored at index `0`.
+    nodes: Vec<ArenaNode<T::Envelope>>,
+    elements: Vec<T>,
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+struct ArenaNode<E> {
+    envelope: E,
+    /// The index of the node's first child within `nodes` or `elements`.
+    first_child: usize,
+    child_count: usize,
+    /// `true` if the children are stored in `elements`, `false` if they are stored in `nodes`.
+    has_leaf_children: bool,
+}
+
+impl<E> ArenaNode<E> {
+    fn children(&self) -> Range<usize> {
+        self.first_child..self.first_child + self.child_count
+    }
+}
+
+impl<T, Params> From<RTree<T, Params>> for ArenaRTree<T>
+where
+    T: RTreeObject,
+    Params: RTreeParams,
+{
+    fn from(tree: RTree<T, Params>) -> Self {
+        let root = tree.into_root();
+        let mut nodes = Vec::new();
+        let mut elements = Vec::new();
+        let mut queue = VecDeque::new();
+        nodes.push(ArenaNode {
+            envelope: root.envelope.clone(),
+            first_child: 0,
+            child_count: 0,
+            has_leaf_children: true,
+        });
+        queue.push_back((0, root));
+        while let Some((index, parent)) = queue.pop_front() {
+            let ParentNode { children, .. } = parent;
+            // All leaves of an r-tree have the same depth, so the children of a node are
+            // either all leaves or all parents.
+            let has_leaf_children = children.first().map_or(true, RTreeNode::is_leaf);
+            let first_child = if has_leaf_children {
+                elements.len()
+            } else {
+                nodes.len()
+            };
+            let child_count = children.len();
+            for child in children {
+                match child {
+                    RTreeNode::Leaf(t) => {
+                        assert!(
+                            has_leaf_children,
+                            "Unbalanced tree. This is a bug in rstar."
+                        );
+                        elements.push(t);
+                    }
+                    RTreeNode::Parent(data) => {
+                        assert!(
+                            !has_leaf_children,
+                            "Unbalanced tree. This is a bug in rstar."
+                        );
+                        nodes.push(ArenaNode {
+                            envelope: data.envelope.clone(),
+                            first_child: 0,
+                            child_count: 0,
+                            has_leaf_children: true,
+                        });
+                        queue.push_back((nodes.len() - 1, data));
+                    }
+                }
+            }
+            let node = &mut nodes[index];
+            node.first_child = first_child;
+            node.child_count = child_count;
+            node.has_leaf_children = has_leaf_children;
+        }
+        ArenaRTree { nodes, elements }
+    }
+}
+
+impl<T> ArenaRTree<T>
+where
+    T: RTreeObject,
+{
+    /// Returns the number of elements in the tree.
+    pub fn size(&self) -> usize {
+        self.elements.len()
+    }
+
+    /// Returns the envelope of all elements.
+    pub fn envelope(&self) -> T::Envelope {
+        self.nodes[0].envelope.clone()
+    }
+
+    /// Returns all elements of the tree.
+    ///
+    /// Elements are stored in the order of the tree's leaves: elements that are close to
+    /// each other tend to be stored next to each other.
+    pub fn elements(&self) -> &[T] {
+        &self.elements
+    }
+
+    /// Consumes the tree and returns its elements.
+    pub fn into_elements(self) -> Vec<T> {
+        self.elements
+    }
+
+    /// Returns an iterator over all elements of the tree.
+    pub fn iter(&self) -> core::slice::Iter<'_, T> {
+        self.elements.iter()
+    }
+
+    /// Returns all elements selected by a [SelectionFunction].
+    ///
+    /// See [RTree::locate_with_selection_function].
+    pub fn locate_with_selection_function<S: SelectionFunction<T>>(
+        &self,
+        selection_function: S,
+    ) -> ArenaSelectionIterator<'_, T, S> {
+        ArenaSelectionIterator::new(self, selection_function)
+    }
+
+    /// Returns all elements contained in an envelope.
+    ///
+    /// See [RTree::locate_in_envelope].
+    pub fn locate_in_envelope(
+        &self,
+        envelope: &T::Envelope,
+    ) -> ArenaSelectionIterator<'_, T, SelectInEnvelopeFunction<T>> {
+        self.locate_with_selection_function(SelectInEnvelopeFunction::new(envelope.clone()))
+    }
+
+    /// Returns all elements whose envelope intersects a given envelope.
+    ///
+    /// See [RTree::locate_in_envelope_intersecting].
+    pub fn locate_in_envelope_intersecting(
+        &self,
+        envelope: &T::Envelope,
+    ) -> ArenaSelectionIterator<'_, T, SelectInEnvelopeFuncIntersecting<T>> {
+        self.locate_with_selection_function(SelectInEnvelopeFuncIntersecting::new(envelope.clone()))
+    }
+}
+
+impl<T> ArenaRTree<T>
+where
+    T: PointDistance,
+{
+    /// Returns a single element containing a given point.
+    ///
+    /// See [RTree::locate_at_point].
+    pub fn locate_at_point(&self, point: &<T::Envelope as Envelope>::Point) -> Option<&T> {
+        self.locate_all_at_point(point).next()
+    }
+
+    /// Returns all elements containing a given point.
+    ///
+    /// See [RTree::locate_all_at_point].
+    pub fn locate_all_at_point(
+        &self,
+        point: &<T::Envelope as Envelope>::Point,
+    ) -> ArenaSelectionIterator<'_, T, SelectAtPointFunction<T>> {
+        self.locate_with_selection_function(SelectAtPointFunction::new(point.clone()))
+    }
+
+    /// Returns all elements within a certain distance of a point.
+    ///
+    /// See [RTree::locate_within_distance].
+    pub fn locate_within_distance(
+        &self,
+        query_point: <T::Envelope as Envelope>::Point,
+        max_squared_radius: <<T::Envelope as Envelope>::Point as Point>::Scalar,
+    ) -> ArenaSelectionIterator<'_, T, SelectWithinDistanceFunction<T>> {
+        self.locate_with_selection_function(SelectWithinDistanceFunction::new(
+            query_point,
+            max_squared_radius,
+        ))
+    }
+
+    /// Returns the nearest neighbor of a point.
+    ///
+    /// See [RTree::nearest_neighbor].
+    pub fn nearest_neighbor(&self, query_point: &<T::Envelope as Envelope>::Point) -> Option<&T> {
+        self.nearest_neighbor_iter_with_distance_2(query_point)
+            .next()
+            .map(|(t, _)| t)
+    }
+
+    /// Returns all elements sorted by their distance to a given point.
+    ///
+    /// See [RTree::nearest_neighbor_iter].
+    pub fn nearest_neighbor_iter(
+        &self,
+        query_point: &<T::Envelope as Envelope>::Point,
+    ) -> impl Iterator<Item = &T> + '_ {
+        self.nearest_neighbor_iter_with_distance_2(query_point)
+            .map(|(t, _)| t)
+    }
+
+    /// Returns `(element, distance^2)` tuples sorted by their distance to a given point.
+    ///
+    /// See [RTree::nearest_neighbor_iter_with_distance_2].
+    pub fn nearest_neighbor_iter_with_distance_2(
+        &self,
+        query_point: &<T::Envelope as Envelope>::Point,
+    ) -> ArenaNearestNeighborIterator<'_, T> {
+        ArenaNearestNeighborIterator::new(self, query_point.clone())
+    }
+}
+
+/// Iterator returned by the `ArenaRTree::locate_*` methods.
+pub struct ArenaSelectionIterator<'a, T, Func>
+where
+    T: RTreeObject,
+    Func: SelectionFunction<T>,
+{
+    tree: &'a ArenaRTree<T>,
+    func: Func,
+    nodes: SmallVec<[(usize, usize); 24]>,
+    elements: core::slice::Iter<'a, T>,
+}
+
+impl<'a, T, Func> ArenaSelectionIterator<'a, T, Func>
+where
+    T: RTreeObject,
+    Func: SelectionFunction<T>,
+{
+    fn new(tree: &'a ArenaRTree<T>, func: Func) -> Self {
+        let mut nodes = SmallVec::new();
+        if func.should_unpack_parent_at_depth(&tree.nodes[0].envelope, 0) {
+            nodes.push((0, 0));
+        }
+        ArenaSelectionIterator {
+            tree,
+            func,
+            nodes,
+            elements: [].iter(),
+        }
+    }
+}
+
+impl<'a, T, Func> Iterator for ArenaSelectionIterator<'a, T, Func>
+where
+    T: RTreeObject,
+    Func: SelectionFunction<T>,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        loop {
+            for element in &mut self.elements {
+                if self.func.should_unpack_leaf(element) {
+                    return Some(element);
+                }
+            }
+            let (index, depth) = self.nodes.pop()?;
+            let node = &self.tree.nodes[index];
+            if node.has_leaf_children {
+                self.elements = self.tree.elements[node.children()].iter();
+            } else {
+                let (tree, func) = (self.tree, &self.func);
+                self.nodes.extend(
+                    node.children()
+                        .filter(|&child| {
+                            func.should_unpack_parent_at_depth(
+                                &tree.nodes[child].envelope,
+                                depth + 1,
+                            )
+                        })
+                        .map(|child| (child, depth + 1)),
+                );
+            }
+        }
+    }
+}
+
+struct DistanceWrapper<S> {
+    index: usize,
+    is_leaf: bool,
+    distance: S,
+}
+
+impl<S: PartialOrd> PartialEq for DistanceWrapper<S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.distance == other.distance
+    }
+}
+
+impl<S: PartialOrd> PartialOrd for DistanceWrapper<S> {
+    fn partial_cmp(&self, other: &Self) -> Option<::core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<S: PartialOrd> Eq for DistanceWrapper<S> {}
+
+impl<S: PartialOrd> Ord for DistanceWrapper<S> {
+    fn cmp(&self, other: &Self) -> ::core::cmp::Ordering {
+        // Inverse comparison creates a min heap
+        other.distance.partial_cmp(&self.distance).unwrap()
+    }
+}
+
+/// Iterator returned by [ArenaRTree::nearest_neighbor_iter_with_distance_2].
+pub struct ArenaNearestNeighborIterator<'a, T>
+where
+    T: PointDistance,
+{
+    tree: &'a ArenaRTree<T>,
+    heap: BinaryHeap<DistanceWrapper<<<T::Envelope as Envelope>::Point as Point>::Scalar>>,
+    query_point: <T::Envelope as Envelope>::Point,
+}
+
+impl<'a, T> ArenaNearestNeighborIterator<'a, T>
+where
+    T: PointDistance,
+{
+    fn new(tree: &'a ArenaRTree<T>, query_point: <T::Envelope as Envelope>::Point) -> Self {
+        let mut result = ArenaNearestNeighborIterator {
+            tree,
+            heap: BinaryHeap::with_capacity(20),
+            query_point,
+        };
+        result.extend_heap(0);
+        result
+    }
+
+    fn extend_heap(&mut self, node_index: usize) {
+        let ArenaNearestNeighborIterator {
+            tree,
+            heap,
+            query_point,
+        } = self;
+        let node = &tree.nodes[node_index];
+        let is_leaf = node.has_leaf_children;
+        heap.extend(node.children().map(|index| {
+            let distance = if is_leaf {
+                tree.elements[index].distance_2(query_point)
+            } else {
+                tree.nodes[index].envelope.distance_2(query_point)
+            };
+            DistanceWrapper {
+                index,
+                is_leaf,
+                distance,
+            }
+        }));
+    }
+}
+
+impl<'a, T> Iterator for ArenaNearestNeighborIterator<'a, T>
+where
+    T: PointDistance,
+{
+    type Item = (&'a T, <<T::Envelope as Envelope>::Point as Point>::Scalar);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(current) = self.heap.pop() {
+            if current.is_leaf {
+                return Some((&self.tree.elements[current.index], current.distance));
+            }
+            self.extend_heap(current.index);
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ArenaRTree;
+    use crate::test_utilities::{create_random_points, create_random_rectangles, SEED_1, SEED_2};
+    use crate::{RTree, AABB};
+
+    #[test]
+    fn test_arena_queries() {
+        let points = create_random_points(1000, SEED_1);
+        let tree = RTree::bulk_load(points.clone());
+        let mut inserted = RTree::new();
+        for point in &points {
+            inserted.insert(*point);
+        }
+        for arena in [ArenaRTree::from(tree.clone()), ArenaRTree::from(inserted)] {
+            assert_eq!(arena.size(), 1000);
+            assert_eq!(arena.envelope(), tree.root().envelope());
+            for query in create_random_points(50, SEED_2) {
+                assert_eq!(
+                    arena.nearest_neighbor(&query),
+                    tree.nearest_neighbor(&query)
+                );
+                let distances: Vec<_> = arena
+                    .nearest_neighbor_iter_with_distance_2(&query)
+                    .map(|(_, distance)| distance)
+                    .collect();
+                assert_eq!(distances.len(), 1000);
+                assert!(distances.windows(2).all(|w| w[0] <= w[1]));
+
+                let envelope = AABB::from_corners(query, [query[0] + 0.1, query[1] + 0.2]);
+                let mut expected: Vec<_> = tree.locate_in_envelope(&envelope).collect();
+                let mut located: Vec<_> = arena.locate_in_envelope(&envelope).collect();
+                expected.sort_by(|l, r| l.partial_cmp(r).unwrap());
+                located.sort_by(|l, r| l.partial_cmp(r).unwrap());
+                assert_eq!(expected, located);
+                assert_eq!(
+                    arena.locate_within_distance(query, 0.01).count(),
+                    tree.locate_within_distance(query, 0.01).count()
+                );
+            }
+            for point in &points[..10] {
+                assert_eq!(arena.locate_at_point(point), Some(point));
+            }
+        }
+    }
+
+    #[test]
+    fn test_arena_intersecting() {
+        let rectangles = create_random_rectangles(200, SEED_1);
+        let tree = RTree::bulk_load(rectangles);
+        let arena = ArenaRTree::from(tree.clone());
+        let envelope = AABB::from_corners([0.2, 0.2], [0.4, 0.5]);
+        assert_eq!(
+            arena.locate_in_envelope_intersecting(&envelope).count(),
+            tree.locate_in_envelope_intersecting(&envelope).count()
+        );
+    }
+
+    #[test]
+    fn test_empty_arena() {
+        let arena = ArenaRTree::from(RTree::<[f64; 2]>::new());
+        assert_eq!(arena.size(), 0);
+        assert_eq!(arena.nearest_neighbor(&[0.0, 0.0]), None);
+        assert_eq!(arena.iter().count(), 0);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_arena_serialization() {
+        use crate::test_utilities::create_random_integers;
+
+        let points = create_random_integers::<[i32; 2]>(100, SEED_1);
+        let arena = ArenaRTree::from(RTree::bulk_load(points.clone()));
+        let json = serde_json::to_string(&arena).unwrap();
+        let parsed: ArenaRTree<[i32; 2]> = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.elements(), arena.elements());
+        for point in &points {
+            assert_eq!(parsed.locate_at_point(point), Some(point));
+        }
+    }
+}
@@ -0,0 +1,241 @@
+use crate::packed::{PackedElement, PackedRTree, PackedRTreeError, PackedScalar};
+use crate::params::RTreeParams;
+use crate::{Point, RTree, AABB};
+
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::fmt;
+use std::io::{self, Read, Write};
+
+const MAGIC: [u8; 4] = *b"RSTB";
+const VERSION: u32 = 1;
+
+const CRC_TABLE: [u32; 256] = crc_table();
+
+const fn crc_table() -> [u32; 256] {
+    let mut table = [0; 256];
+    let mut index = 0;
+    while index < 256 {
+        let mut value = index as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            value = if value & 1 == 1 {
+                0xEDB8_8320 ^ (value >> 1)
+            } else {
+                value >> 1
+            };
+            bit += 1;
+        }
+        table[index] = value;
+        index += 1;
+    }
+    table
+}
+
+/// Computes the CRC-32 (IEEE 802.3) checksum of `bytes`.
+pub(crate) fn crc32(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!0, |crc, &byte| {
+        CRC_TABLE[((crc ^ byte as u32) & 0xFF) as usize] ^ (crc >> 8)
+    })
+}
+
+/// Error returned by [RTree::read_from] and [RTree::replay_log].
+#[derive(Debug)]
+pub enum ReadTreeError {
+    /// Reading from the underlying reader failed.
+    Io(io::Error),
+    /// The data does not start with the header of an encoded tree.
+    InvalidHeader,
+    /// The data was written by an unsupported version of the format.
+    UnsupportedVersion(u32),
+    /// The checksum doesn't match the data, the data is corrupted.
+    ChecksumMismatch,
+    /// The checksum matches, but the encoded tree is invalid.
+    InvalidTree(PackedRTreeError),
+    /// A log record has an unknown type, see [LoggedRTree](crate::LoggedRTree).
+    InvalidRecord(u8),
+}
+
+impl fmt::Display for ReadTreeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReadTreeError::Io(error) => write!(f, "failed to read tree: {}", error),
+            ReadTreeError::InvalidHeader => write!(f, "invalid tree header"),
+            ReadTreeError::UnsupportedVersion(version) => {
+                write!(f, "unsupported tree format version {}", version)
+            }
+            ReadTreeError::ChecksumMismatch => write!(f, "tree checksum mismatch"),
+            ReadTreeError::InvalidTree(error) => write!(f, "invalid tree: {}", error),
+            ReadTreeError::InvalidRecord(record_type) => {
+                write!(f, "invalid log record type {}", record_type)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ReadTreeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ReadTreeError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ReadTreeError {
+    fn from(error: io::Error) -> Self {
+        ReadTreeError::Io(error)
+    }
+}
+
+impl<T, P, Params> RTree<T, Params>
+where
+    T: PackedElement<Envelope = AABB<P>>,
+    P: Point,
+    P::Scalar: PackedScalar,
+    Params: RTreeParams,
+{
+    /// Writes the tree in a compact, versioned binary format.
+    ///
+    /// The format does not depend on serde or on the platform and is simple enough to be read
+    /// by other languages. All values are stored in little endian byte order:
+    ///
+    ///  - the magic bytes `RSTB`
+    ///  - the format version (`u32`), currently `1`
+    ///  - the length of the encoded tree in bytes (`u64`)
+    ///  - the encoded tree in the format of a [PackedRTree], see there for a description
+    ///  - the CRC-32 (IEEE 802.3) checksum of the encoded tree (`u32`)
+    ///
+    /// The tree's node structure is stored as is, [RTree::read_from] restores it without
+    /// rebuilding the tree. The tree's [spatial reference](RTree::spatial_reference) is not
+    /// stored. Requires the `std` feature.
+    ///
+    /// # Example
+    /// ```
+    /// use rstar::RTree;
+    ///
+    /// let tree = RTree::bulk_load(vec![[0.0f32, 0.0], [1.0, 1.0], [2.0, 0.5]]);
+    /// let mut bytes = Vec::new();
+    /// tree.write_to(&mut bytes).unwrap();
+    ///
+    /// let restored: RTree<[f32; 2]> = RTree::read_from(bytes.as_slice()).unwrap();
+    /// assert_eq!(restored.size(), 3);
+    /// assert_eq!(restored.nearest_neighbor(&[1.8, 0.4]), Some(&[2.0, 0.5]));
+    /// ```
+    pub fn write_to<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let packed = PackedRTree::from(self);
+        let bytes = packed.as_bytes();
+        writer.write_all(&MAGIC)?;
+        writer.write_all(&VERSION.to_le_bytes())?;
+        writer.write_all(&(bytes.len() as u64).to_le_bytes())?;
+        writer.write_all(bytes)?;
+        writer.write_all(&crc32(bytes).to_le_bytes())
+    }
+
+    /// Reads a tree written by [RTree::write_to].
+    ///
+    /// The element type and parameters should match those of the written tree. The number of
+    /// dimensions and the element size are checked, the node sizes are not: a tree written
+    /// with a larger [RTreeParams::MAX_SIZE] is restored as is, its nodes are split once they
+    /// are modified.
+    pub fn read_from<R: Read>(mut reader: R) -> Result<Self, ReadTreeError> {
+        let mut header = [0; 16];
+        reader.read_exact(&mut header)?;
+        if header[..4] != MAGIC {
+            return Err(ReadTreeError::InvalidHeader);
+        }
+        let mut version = [0; 4];
+        version.copy_from_slice(&header[4..8]);
+        let version = u32::from_le_bytes(version);
+        if version != VERSION {
+            return Err(ReadTreeError::UnsupportedVersion(version));
+        }
+        let mut length = [0; 8];
+        length.copy_from_slice(&header[8..]);
+        let length = u64::from_le_bytes(length);
+
+        // Don't trust the length for allocating, the data might be truncated or corrupted.
+        let mut bytes = Vec::new();
+        reader.by_ref().take(length).read_to_end(&mut bytes)?;
+        if u64::try_from(bytes.len()) != Ok(length) {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+        }
+        let mut checksum = [0; 4];
+        reader.read_exact(&mut checksum)?;
+        if u32::from_le_bytes(checksum) != crc32(&bytes) {
+            return Err(ReadTreeError::ChecksumMismatch);
+        }
+
+        let packed = PackedRTree::<T, _>::from_bytes(bytes).map_err(ReadTreeError::InvalidTree)?;
+        let root = packed.decode_root().map_err(ReadTreeError::InvalidTree)?;
+        Ok(RTree::from_root(root))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{crc32, ReadTreeError};
+    use crate::primitives::Line;
+    use crate::test_utilities::{create_random_lines, create_random_points, SEED_1};
+    use crate::RTree;
+
+    #[test]
+    fn test_crc32() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn test_write_and_read() {
+        let mut tree = RTree::new();
+        for point in create_random_points(500, SEED_1) {
+            tree.insert(point);
+        }
+        let mut bytes = Vec::new();
+        tree.write_to(&mut bytes).unwrap();
+        let restored: RTree<[f64; 2]> = RTree::read_from(bytes.as_slice()).unwrap();
+        restored.root().sanity_check::<crate::DefaultParams>(true);
+        assert_eq!(restored.size(), 500);
+        assert!(tree.iter().eq(restored.iter()));
+
+        let lines = RTree::bulk_load(create_random_lines(100, SEED_1));
+        let mut bytes = Vec::new();
+        lines.write_to(&mut bytes).unwrap();
+        let restored: RTree<Line<[f64; 2]>> = RTree::read_from(bytes.as_slice()).unwrap();
+        assert!(lines.iter().eq(restored.iter()));
+    }
+
+    #[test]
+    fn test_read_corrupted() {
+        let tree = RTree::bulk_load(create_random_points(100, SEED_1));
+        let mut bytes = Vec::new();
+        tree.write_to(&mut bytes).unwrap();
+        let read = |bytes: &[u8]| RTree::<[f64; 2]>::read_from(bytes).map(|tree| tree.size());
+
+        assert!(matches!(read(&bytes), Ok(100)));
+        assert!(matches!(
+            read(&bytes[1..]),
+            Err(ReadTreeError::InvalidHeader)
+        ));
+        assert!(matches!(
+            read(&bytes[..bytes.len() - 1]),
+            Err(ReadTreeError::Io(_))
+        ));
+        let mut corrupted = bytes.clone();
+        corrupted[100] ^= 1;
+        assert!(matches!(
+            read(&corrupted),
+            Err(ReadTreeError::ChecksumMismatch)
+        ));
+        corrupted = bytes.clone();
+        corrupted[4] = 7;
+        assert!(matches!(
+            read(&corrupted),
+            Err(ReadTreeError::UnsupportedVersion(7))
+        ));
+        assert!(matches!(
+            RTree::<[f32; 2]>::read_from(bytes.as_slice()),
+            Err(ReadTreeError::InvalidTree(_))
+        ));
+    }
+}
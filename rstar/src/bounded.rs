@@ -0,0 +1,310 @@
+use crate::algorithm::selection_functions::{
+    SelectInEnvelopeFuncIntersecting, SelectInEnvelopeFunction, SelectionFunction,
+};
+use crate::node::{ParentNode, RTreeNode};
+use crate::params::RTreeParams;
+use crate::point::Point;
+use crate::{Envelope, PointDistance, RTree, RTreeObject};
+
+use core::fmt;
+
+type Scalar<T> = <<<T as RTreeObject>::Envelope as Envelope>::Point as Point>::Scalar;
+/// A node of a nearest neighbor query's stack, together with the distance and index of the
+/// child visited last.
+type NearestFrame<'a, T> = (&'a ParentNode<T>, Option<(Scalar<T>, usize)>);
+
+impl<T, Params> RTree<T, Params>
+where
+    T: RTreeObject,
+    Params: RTreeParams,
+{
+    /// Returns all elements selected by a [SelectionFunction], using a stack of at most
+    /// `DEPTH` frames.
+    ///
+    /// Unlike [RTree::locate_with_selection_function], which may spill its stack of pending
+    /// nodes to the heap, the returned iterator stores one frame per tree level inline and
+    /// neither allocates nor recurses. A frame consists of a node reference and a child index,
+    /// the iterator thus occupies `DEPTH * 2 * size_of::<usize>()` bytes plus the selection
+    /// function, regardless of the number of elements. It can be placed on small thread
+    /// stacks of embedded targets. Limiting [RTreeParams::MAX_HEIGHT] to `DEPTH` guarantees
+    /// that the query succeeds.
+    ///
+    /// Returns an error if the tree's [height](RTree::height) exceeds `DEPTH`.
+    ///
+    /// # Example
+    /// ```
+    /// use rstar::{RTree, SelectAllFunc};
+    ///
+    /// let tree = RTree::bulk_load((0..100).map(|i| [i, i]).collect());
+    /// let all = tree.locate_with_selection_function_bounded::<8, _>(SelectAllFunc);
+    /// assert_eq!(all.unwrap().count(), 100);
+    ///
+    /// let error = tree.locate_with_selection_function_bounded::<1, _>(SelectAllFunc);
+    /// assert!(error.is_err());
+    /// ```
+    pub fn locate_with_selection_function_bounded<const DEPTH: usize, S>(
+        &self,
+        function: S,
+    ) -> Result<BoundedSelectionIterator<'_, T, S, DEPTH>, StackDepthExceeded>
+    where
+        S: SelectionFunction<T>,
+    {
+        check_depth::<DEPTH>(self.height())?;
+        Ok(BoundedSelectionIterator::new(self.root(), function))
+    }
+
+    /// Returns all elements contained in an envelope, using a stack of at most `DEPTH` frames.
+    ///
+    /// See [RTree::locate_with_selection_function_bounded].
+    pub fn locate_in_envelope_bounded<const DEPTH: usize>(
+        &self,
+        envelope: &T::Envelope,
+    ) -> Result<
+        BoundedSelectionIterator<'_, T, SelectInEnvelopeFunction<T>, DEPTH>,
+        StackDepthExceeded,
+    > {
+        self.locate_with_selection_function_bounded(SelectInEnvelopeFunction::new(envelope.clone()))
+    }
+
+    /// Returns all elements whose envelope intersects an envelope, using a stack of at most
+    /// `DEPTH` frames.
+    ///
+    /// See [RTree::locate_with_selection_function_bounded].
+    pub fn locate_in_envelope_intersecting_bounded<const DEPTH: usize>(
+        &self,
+        envelope: &T::Envelope,
+    ) -> Result<
+        BoundedSelectionIterator<'_, T, SelectInEnvelopeFuncIntersecting<T>, DEPTH>,
+        StackDepthExceeded,
+    > {
+        self.locate_with_selection_function_bounded(SelectInEnvelopeFuncIntersecting::new(
+            envelope.clone(),
+        ))
+    }
+}
+
+impl<T, Params> RTree<T, Params>
+where
+    T: PointDistance,
+    Params: RTreeParams,
+{
+    /// Returns the nearest neighbor of a point, using a stack of at most `DEPTH` frames.
+    ///
+    /// Unlike [RTree::nearest_neighbor], this neither allocates nor recurses. A frame consists
+    /// of a node reference, a child index and a distance, the stack occupies
+    /// `DEPTH * (3 * size_of::<usize>() + size_of::<Scalar>())` bytes at most, including
+    /// padding. The children of every node are visited by increasing distance to the query
+    /// point, which takes `O(MAX_SIZE²)` instead of `O(MAX_SIZE * log(MAX_SIZE))` comparisons
+    /// per visited node, as they are not sorted into a buffer.
+    ///
+    /// Returns an error if the tree's [height](RTree::height) exceeds `DEPTH`, `Ok(None)` if
+    /// the tree is empty.
+    ///
+    /// # Example
+    /// ```
+    /// use rstar::RTree;
+    ///
+    /// let tree = RTree::bulk_load(vec![[0.0, 0.0], [1.0, 1.0], [2.0, 2.0]]);
+    /// assert_eq!(tree.nearest_neighbor_bounded::<4>(&[1.2, 0.9]), Ok(Some(&[1.0, 1.0])));
+    /// ```
+    pub fn nearest_neighbor_bounded<const DEPTH: usize>(
+        &self,
+        query_point: &<T::Envelope as Envelope>::Point,
+    ) -> Result<Option<&T>, StackDepthExceeded> {
+        check_depth::<DEPTH>(self.height())?;
+        let mut best: Option<(&T, Scalar<T>)> = None;
+        let mut stack: heapless::Vec<NearestFrame<T>, DEPTH> = heapless::Vec::new();
+        push_frame(&mut stack, (self.root(), None));
+        while let Some(&(node, last)) = stack.last() {
+            // Find the nearest child which is farther away than the last visited one.
+            let mut next: Option<(Scalar<T>, usize)> = None;
+            for (index, child) in node.children.iter().enumerate() {
+                match child {
+                    RTreeNode::Leaf(ref t) => {
+                        let distance = t.distance_2(query_point);
+                        if best.map_or(true, |(_, best)| distance < best) {
+                            best = Some((t, distance));
+                        }
+                    }
+                    RTreeNode::Parent(ref data) => {
+                        let key = (data.envelope.distance_2(query_point), index);
+                        if last.map_or(true, |last| last < key)
+                            && next.map_or(true, |next| key < next)
+                        {
+                            next = Some(key);
+                        }
+                    }
+                }
+            }
+            match next {
+                Some((distance, index)) if best.map_or(true, |(_, best)| distance < best) => {
+                    if let Some(frame) = stack.last_mut() {
+                        frame.1 = next;
+                    }
+                    if let RTreeNode::Parent(ref child) = node.children[index] {
+                        push_frame(&mut stack, (child, None));
+                    }
+                }
+                // All remaining children are farther away than the best element.
+                _ => {
+                    stack.pop();
+                }
+            }
+        }
+        Ok(best.map(|(t, _)| t))
+    }
+}
+
+/// Iterator returned by [RTree::locate_with_selection_function_bounded].
+pub struct BoundedSelectionIterator<'a, T, Func, const DEPTH: usize>
+where
+    T: RTreeObject,
+    Func: SelectionFunction<T>,
+{
+    func: Func,
+    /// The nodes on the path to the current node together with the index of their next
+    /// child.
+    stack: heapless::Vec<(&'a ParentNode<T>, usize), DEPTH>,
+}
+
+impl<'a, T, Func, const DEPTH: usize> BoundedSelectionIterator<'a, T, Func, DEPTH>
+where
+    T: RTreeObject,
+    Func: SelectionFunction<T>,
+{
+    fn new(root: &'a ParentNode<T>, func: Func) -> Self {
+        let mut stack = heapless::Vec::new();
+        if func.should_unpack_parent_at_depth(&root.envelope, 0) {
+            push_frame(&mut stack, (root, 0));
+        }
+        BoundedSelectionIterator { func, stack }
+    }
+}
+
+impl<'a, T, Func, const DEPTH: usize> Iterator for BoundedSelectionIterator<'a, T, Func, DEPTH>
+where
+    T: RTreeObject,
+    Func: SelectionFunction<T>,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        while let Some(&mut (node, ref mut next_child)) = self.stack.last_mut() {
+            let child = match node.children.get(*next_child) {
+                Some(child) => child,
+                None => {
+                    self.stack.pop();
+                    continue;
+                }
+            };
+            *next_child += 1;
+            match child {
+                RTreeNode::Leaf(ref t) => {
+                    if self.func.should_unpack_leaf(t) {
+                        return Some(t);
+                    }
+                }
+                RTreeNode::Parent(ref data) => {
+                    let depth = self.stack.len();
+                    if self
+                        .func
+                        .should_unpack_parent_at_depth(&data.envelope, depth)
+                    {
+                        push_frame(&mut self.stack, (data, 0));
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Error returned by the bounded queries, e.g. [RTree::locate_with_selection_function_bounded],
+/// if the tree is higher than the maximum stack depth.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StackDepthExceeded {
+    /// The tree's height.
+    pub height: usize,
+    /// The maximum stack depth of the query.
+    pub depth: usize,
+}
+
+impl fmt::Display for StackDepthExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "the tree's height of {} exceeds the query's stack depth of {}",
+            self.height, self.depth
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for StackDepthExceeded {}
+
+fn check_depth<const DEPTH: usize>(height: usize) -> Result<(), StackDepthExceeded> {
+    if height > DEPTH {
+        Err(StackDepthExceeded {
+            height,
+            depth: DEPTH,
+        })
+    } else {
+        Ok(())
+    }
+}
+
+/// Pushes a frame, the stack depth has been checked against the tree's height beforehand.
+fn push_frame<F, const DEPTH: usize>(stack: &mut heapless::Vec<F, DEPTH>, frame: F) {
+    if stack.push(frame).is_err() {
+        unreachable!("Stack depth exceeded. This is a bug in rstar.");
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::StackDepthExceeded;
+    use crate::test_utilities::{create_random_points, create_random_rectangles, SEED_1, SEED_2};
+    use crate::{PointDistance, RTree, AABB};
+
+    #[test]
+    fn test_bounded_queries() {
+        let rectangles = create_random_rectangles(2000, SEED_1);
+        let tree = RTree::bulk_load(rectangles);
+        let envelope = AABB::from_corners([0.2, 0.3], [0.6, 0.5]);
+        let mut expected: Vec<_> = tree.locate_in_envelope_intersecting(&envelope).collect();
+        let mut found: Vec<_> = tree
+            .locate_in_envelope_intersecting_bounded::<8>(&envelope)
+            .unwrap()
+            .collect();
+        assert!(!expected.is_empty());
+        expected.sort_by(|l, r| l.partial_cmp(r).unwrap());
+        found.sort_by(|l, r| l.partial_cmp(r).unwrap());
+        assert_eq!(found, expected);
+        assert_eq!(
+            tree.locate_in_envelope_bounded::<8>(&envelope)
+                .unwrap()
+                .count(),
+            tree.locate_in_envelope(&envelope).count()
+        );
+
+        let points = create_random_points(2000, SEED_1);
+        let tree = RTree::bulk_load(points);
+        for query_point in create_random_points(100, SEED_2) {
+            let expected = tree.nearest_neighbor(&query_point).unwrap();
+            let found = tree.nearest_neighbor_bounded::<8>(&query_point).unwrap();
+            assert_eq!(
+                found.unwrap().distance_2(&query_point),
+                expected.distance_2(&query_point)
+            );
+        }
+        let height = tree.height();
+        assert_eq!(
+            tree.nearest_neighbor_bounded::<2>(&[0.5, 0.5]),
+            Err(StackDepthExceeded { height, depth: 2 })
+        );
+        assert_eq!(
+            RTree::<[f64; 2]>::new().nearest_neighbor_bounded::<1>(&[0.5, 0.5]),
+            Ok(None)
+        );
+    }
+}
@@ -0,0 +1,318 @@
+use crate::algorithm::selection_functions::SelectFromFns;
+use crate::params::{DefaultParams, RTreeParams};
+use crate::point::Point;
+use crate::{Envelope, RTree, RTreeObject, AABB};
+
+use alloc::collections::BTreeSet;
+use alloc::vec::Vec;
+use core::mem;
+
+/// The enlarged envelope of an object stored in a [BroadPhase], together with its handle.
+#[derive(Clone, Debug)]
+struct Proxy<E> {
+    envelope: E,
+    handle: usize,
+}
+
+impl<E> RTreeObject for Proxy<E>
+where
+    E: Envelope,
+{
+    type Envelope = E;
+
+    fn envelope(&self) -> E {
+        self.envelope.clone()
+    }
+}
+
+/// The changes of the overlapping pairs of a [BroadPhase] during one step.
+///
+/// Pairs are given as handles `(a, b)` with `a < b`, sorted in ascending order.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PairChanges {
+    /// Pairs that started to overlap.
+    pub added: Vec<(usize, usize)>,
+    /// Pairs that stopped overlapping, including pairs of removed objects.
+    pub removed: Vec<(usize, usize)>,
+}
+
+/// Maintains the set of pairs of objects with overlapping envelopes across updates.
+///
+/// Objects are identified by the handles returned by [BroadPhase::insert]. After inserting,
+/// moving and removing objects, [BroadPhase::step] updates the set of overlapping pairs and
+/// returns the pairs that were added and removed. Only pairs involving objects that changed
+/// since the last step are recomputed, which is much cheaper than finding all intersecting
+/// pairs again if few objects move.
+///
+/// Like [FatRTree](crate::FatRTree), objects are stored with envelopes enlarged by a margin
+/// so that small movements don't change the tree's structure.
+///
+/// # Example
+/// ```
+/// use rstar::primitives::Rectangle;
+/// use rstar::BroadPhase;
+///
+/// let mut broad_phase = BroadPhase::new(0.1);
+/// let a = broad_phase.insert(Rectangle::from_corners([0.0, 0.0], [1.0, 1.0]));
+/// let b = broad_phase.insert(Rectangle::from_corners([2.0, 0.0], [3.0, 1.0]));
+/// assert!(broad_phase.step().added.is_empty());
+///
+/// broad_phase.update(b, Rectangle::from_corners([0.5, 0.0], [1.5, 1.0]));
+/// assert_eq!(broad_phase.step().added, vec![(a, b)]);
+/// broad_phase.remove(a);
+/// assert_eq!(broad_phase.step().removed, vec![(a, b)]);
+/// ```
+pub struct BroadPhase<T, Params = DefaultParams>
+where
+    T: RTreeObject,
+    Params: RTreeParams,
+{
+    tree: RTree<Proxy<T::Envelope>, Params>,
+    objects: Vec<Option<(T, T::Envelope)>>,
+    margin: <<T::Envelope as Envelope>::Point as Point>::Scalar,
+    pairs: BTreeSet<(usize, usize)>,
+    changed: BTreeSet<usize>,
+    removed: Vec<usize>,
+    free: Vec<usize>,
+}
+
+impl<T, P> BroadPhase<T>
+where
+    T: RTreeObject<Envelope = AABB<P>>,
+    P: Point,
+{
+    /// Creates a new, empty broad phase that enlarges envelopes by `margin` on every side.
+    pub fn new(margin: P::Scalar) -> Self {
+        Self::new_with_params(margin)
+    }
+}
+
+impl<T, P, Params> BroadPhase<T, Params>
+where
+    T: RTreeObject<Envelope = AABB<P>>,
+    P: Point,
+    Params: RTreeParams,
+{
+    /// Creates a new, empty broad phase with custom parameters.
+    pub fn new_with_params(margin: P::Scalar) -> Self {
+        BroadPhase {
+            tree: RTree::new_with_params(),
+            objects: Vec::new(),
+            margin,
+            pairs: BTreeSet::new(),
+            changed: BTreeSet::new(),
+            removed: Vec::new(),
+            free: Vec::new(),
+        }
+    }
+
+    /// Returns the number of objects.
+    pub fn size(&self) -> usize {
+        self.tree.size()
+    }
+
+    /// Returns the object with the given handle.
+    pub fn get<'a>(&'a self, handle: usize) -> Option<&'a T>
+    where
+        P: 'a,
+    {
+        self.objects
+            .get(handle)
+            .and_then(Option::as_ref)
+            .map(|(object, _)| object)
+    }
+
+    /// Inserts an object and returns its handle.
+    ///
+    /// Handles of removed objects are reused after the next [step](BroadPhase::step).
+    pub fn insert(&mut self, object: T) -> usize {
+        let fat = self.fatten(&object.envelope());
+        let handle = match self.free.pop() {
+            Some(handle) => {
+                self.objects[handle] = Some((object, fat.clone()));
+                handle
+            }
+            None => {
+                self.objects.push(Some((object, fat.clone())));
+                self.objects.len() - 1
+            }
+        };
+        self.tree.insert(Proxy {
+            envelope: fat,
+            handle,
+        });
+        self.changed.insert(handle);
+        handle
+    }
+
+    /// Replaces the object with the given handle, usually by the same object at a new position.
+    ///
+    /// Returns the previous object, or `None` if the handle is not in use. In this case,
+    /// `object` is not inserted.
+    pub fn update(&mut self, handle: usize, object: T) -> Option<T> {
+        let envelope = object.envelope();
+        let (previous, fat) = self.objects.get_mut(handle)?.take()?;
+        let fat = if fat.contains_envelope(&envelope) {
+            fat
+        } else {
+            self.remove_proxy(handle, &fat);
+            let fat = self.fatten(&envelope);
+            self.tree.insert(Proxy {
+                envelope: fat.clone(),
+                handle,
+            });
+            fat
+        };
+        self.objects[handle] = Some((object, fat));
+        self.changed.insert(handle);
+        Some(previous)
+    }
+
+    /// Removes the object with the given handle and returns it.
+    pub fn remove(&mut self, handle: usize) -> Option<T> {
+        let (object, fat) = self.objects.get_mut(handle)?.take()?;
+        self.remove_proxy(handle, &fat);
+        self.changed.insert(handle);
+        self.removed.push(handle);
+        Some(object)
+    }
+
+    /// Returns all overlapping pairs as of the last [step](BroadPhase::step).
+    ///
+    /// Pairs are given as handles `(a, b)` with `a < b`, sorted in ascending order.
+    pub fn pairs(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.pairs.iter().copied()
+    }
+
+    /// Updates the overlapping pairs and returns the changes since the last step.
+    ///
+    /// Only pairs involving objects that were inserted, updated or removed since the last step
+    /// are recomputed, every changed object requires one query of the tree.
+    pub fn step(&mut self) -> PairChanges {
+        let changed = mem::take(&mut self.changed);
+        if changed.is_empty() {
+            return PairChanges::default();
+        }
+        let mut previous = BTreeSet::new();
+        self.pairs.retain(|pair| {
+            let keep = !changed.contains(&pair.0) && !changed.contains(&pair.1);
+            if !keep {
+                previous.insert(*pair);
+            }
+            keep
+        });
+
+        let mut current = BTreeSet::new();
+        for &handle in &changed {
+            let envelope = match self.get(handle) {
+                Some(object) => object.envelope(),
+                None => continue,
+            };
+            for proxy in self.tree.locate_in_envelope_intersecting(&envelope) {
+                let other = proxy.handle;
+                if other != handle && self.get(other).unwrap().envelope().intersects(&envelope) {
+                    current.insert((handle.min(other), handle.max(other)));
+                }
+            }
+        }
+
+        let changes = PairChanges {
+            added: current.difference(&previous).copied().collect(),
+            removed: previous.difference(&current).copied().collect(),
+        };
+        self.pairs.extend(current);
+        self.free.append(&mut self.removed);
+        changes
+    }
+
+    fn fatten(&self, envelope: &AABB<P>) -> AABB<P> {
+        envelope.expanded_by(self.margin)
+    }
+
+    fn remove_proxy(&mut self, handle: usize, fat: &AABB<P>) {
+        self.tree
+            .remove_with_selection_function(SelectFromFns::new(
+                |envelope: &AABB<P>| envelope.contains_envelope(fat),
+                |proxy: &Proxy<AABB<P>>| proxy.handle == handle,
+            ))
+            .expect("Broad phase proxy is missing");
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::BroadPhase;
+    use crate::primitives::Rectangle;
+    use crate::test_utilities::{create_random_points, create_random_rectangles, SEED_1, SEED_2};
+    use crate::{Envelope, RTreeObject};
+    use alloc::collections::BTreeSet;
+
+    fn expected_pairs(rectangles: &[Option<Rectangle<[f64; 2]>>]) -> BTreeSet<(usize, usize)> {
+        let mut result = BTreeSet::new();
+        for (a, l) in rectangles.iter().enumerate() {
+            for (b, r) in rectangles.iter().enumerate().skip(a + 1) {
+                if let (Some(l), Some(r)) = (l, r) {
+                    if l.envelope().intersects(&r.envelope()) {
+                        result.insert((a, b));
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    #[test]
+    fn test_broad_phase() {
+        let mut rectangles: Vec<_> = create_random_rectangles(200, SEED_1)
+            .into_iter()
+            .map(Some)
+            .collect();
+        let mut broad_phase = BroadPhase::new(0.02);
+        for rectangle in &rectangles {
+            broad_phase.insert(rectangle.unwrap());
+        }
+        let changes = broad_phase.step();
+        assert!(changes.removed.is_empty());
+        let mut pairs: BTreeSet<_> = changes.added.into_iter().collect();
+        assert_eq!(pairs, expected_pairs(&rectangles));
+
+        let offsets = create_random_points(200, SEED_2);
+        for round in 0..5 {
+            for (index, [dx, dy]) in offsets.iter().enumerate() {
+                if (index + round) % 3 != 0 {
+                    continue;
+                }
+                if let Some(rectangle) = rectangles[index] {
+                    let envelope = rectangle.envelope();
+                    let (dx, dy) = ((dx - 0.5) * 0.1, (dy - 0.5) * 0.1);
+                    let moved = Rectangle::from_corners(
+                        [envelope.lower()[0] + dx, envelope.lower()[1] + dy],
+                        [envelope.upper()[0] + dx, envelope.upper()[1] + dy],
+                    );
+                    assert_eq!(broad_phase.update(index, moved), Some(rectangle));
+                    rectangles[index] = Some(moved);
+                }
+            }
+            let removed = round * 7;
+            assert_eq!(broad_phase.remove(removed), rectangles[removed].take());
+            assert_eq!(broad_phase.remove(removed), None);
+
+            let changes = broad_phase.step();
+            for pair in &changes.removed {
+                assert!(pairs.remove(pair));
+            }
+            for pair in &changes.added {
+                assert!(pairs.insert(*pair));
+            }
+            let expected = expected_pairs(&rectangles);
+            assert_eq!(pairs, expected);
+            assert!(broad_phase.pairs().eq(expected.into_iter()));
+            assert_eq!(broad_phase.step(), Default::default());
+        }
+        assert_eq!(broad_phase.size(), 195);
+
+        let handle = broad_phase.insert(rectangles[1].unwrap());
+        assert!(handle < 200 && rectangles[handle].is_none());
+        assert_eq!(broad_phase.get(handle), rectangles[1].as_ref());
+    }
+}
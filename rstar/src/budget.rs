@@ -0,0 +1,166 @@
+use crate::algorithm::cursor::LocateCursor;
+use crate::algorithm::selection_functions::{SelectInEnvelopeFunction, SelectionFunction};
+use crate::params::RTreeParams;
+use crate::{RTree, RTreeObject};
+
+use alloc::vec::Vec;
+
+/// The number of nodes and elements visited between two deadline checks.
+#[cfg(feature = "std")]
+const DEADLINE_CHECK_INTERVAL: usize = 64;
+
+/// Limits the work done by a query, see [RTree::locate_with_budget].
+///
+/// A query stops once it has visited `max_visits` nodes and elements or once the `deadline`
+/// has passed, whichever comes first. A budget without any limit never stops a query.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct QueryBudget {
+    /// The maximum number of nodes and elements a query may visit.
+    pub max_visits: Option<usize>,
+    /// The point in time after which a query is stopped. Requires the `std` feature.
+    ///
+    /// The deadline is checked every few dozen visited nodes, so a query may run slightly
+    /// past it.
+    #[cfg(feature = "std")]
+    pub deadline: Option<std::time::Instant>,
+}
+
+/// The result of a query with a [QueryBudget].
+#[derive(Clone, Debug, PartialEq)]
+pub struct BudgetedResult<'a, T> {
+    /// The elements found before the query was stopped.
+    pub results: Vec<&'a T>,
+    /// `true` if the budget was exhausted before the query was complete, `results` may miss
+    /// some elements in this case.
+    pub truncated: bool,
+}
+
+impl QueryBudget {
+    fn exhausted(&self, visits: usize) -> bool {
+        #[cfg(feature = "std")]
+        {
+            if let Some(deadline) = self.deadline {
+                if std::time::Instant::now() >= deadline {
+                    return true;
+                }
+            }
+        }
+        self.max_visits
+            .map_or(false, |max_visits| visits >= max_visits)
+    }
+
+    fn next_step(&self, visits: usize) -> usize {
+        let remaining = self
+            .max_visits
+            .map_or(usize::MAX, |max_visits| max_visits - visits);
+        #[cfg(feature = "std")]
+        {
+            if self.deadline.is_some() {
+                return remaining.min(DEADLINE_CHECK_INTERVAL);
+            }
+        }
+        remaining
+    }
+}
+
+impl<T, Params> RTree<T, Params>
+where
+    T: RTreeObject,
+    Params: RTreeParams,
+{
+    /// Locates elements defined by a selection function, stopping once a budget is exhausted.
+    ///
+    /// Returns the elements found so far and whether the query was stopped early. This
+    /// bounds the latency of queries that would visit large parts of the tree, e.g. due to
+    /// adversarial data.
+    ///
+    /// # Example
+    /// ```
+    /// use rstar::{QueryBudget, RTree, AABB};
+    ///
+    /// let tree = RTree::bulk_load((0..1000).map(|i| [i as f64, 0.0]).collect());
+    /// let envelope = AABB::from_corners([0.0, 0.0], [1000.0, 0.0]);
+    /// let budget = QueryBudget {
+    ///     max_visits: Some(100),
+    ///     ..Default::default()
+    /// };
+    /// let result = tree.locate_in_envelope_with_budget(&envelope, budget);
+    /// assert!(result.truncated);
+    /// assert!(result.results.len() < 100);
+    /// ```
+    pub fn locate_with_budget<S>(
+        &self,
+        selection_function: S,
+        budget: QueryBudget,
+    ) -> BudgetedResult<'_, T>
+    where
+        S: SelectionFunction<T>,
+    {
+        let mut cursor = LocateCursor::new(self.root(), selection_function);
+        let mut results = Vec::new();
+        let mut visits = 0;
+        while !cursor.is_finished() && !budget.exhausted(visits) {
+            let step = budget.next_step(visits);
+            cursor.poll(step, &mut results);
+            visits += step;
+        }
+        BudgetedResult {
+            results,
+            truncated: !cursor.is_finished(),
+        }
+    }
+
+    /// Locates elements contained in an envelope, stopping once a budget is exhausted.
+    ///
+    /// See [RTree::locate_with_budget].
+    pub fn locate_in_envelope_with_budget(
+        &self,
+        envelope: &T::Envelope,
+        budget: QueryBudget,
+    ) -> BudgetedResult<'_, T> {
+        self.locate_with_budget(SelectInEnvelopeFunction::new(envelope.clone()), budget)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::QueryBudget;
+    use crate::test_utilities::{create_random_points, SEED_1};
+    use crate::{RTree, AABB};
+
+    #[test]
+    fn test_locate_with_budget() {
+        let tree = RTree::bulk_load(create_random_points(1000, SEED_1));
+        let envelope = AABB::from_corners([0.1, 0.1], [0.9, 0.9]);
+        let expected = tree.locate_in_envelope(&envelope).count();
+
+        let unlimited = tree.locate_in_envelope_with_budget(&envelope, QueryBudget::default());
+        assert!(!unlimited.truncated);
+        assert_eq!(unlimited.results.len(), expected);
+
+        let limited = |max_visits| QueryBudget {
+            max_visits: Some(max_visits),
+            ..Default::default()
+        };
+        let truncated = tree.locate_in_envelope_with_budget(&envelope, limited(200));
+        assert!(truncated.truncated);
+        assert!(!truncated.results.is_empty() && truncated.results.len() < 200);
+        let enough = tree.locate_in_envelope_with_budget(&envelope, limited(10_000));
+        assert!(!enough.truncated);
+        assert_eq!(enough.results, unlimited.results);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_locate_with_expired_deadline() {
+        let tree = RTree::bulk_load(create_random_points(1000, SEED_1));
+        let envelope = AABB::from_corners([0.1, 0.1], [0.9, 0.9]);
+        let expired = QueryBudget {
+            deadline: Some(std::time::Instant::now()),
+            ..Default::default()
+        };
+        let result = tree.locate_in_envelope_with_budget(&envelope, expired);
+        assert!(result.truncated);
+        assert!(result.results.is_empty());
+    }
+}
@@ -0,0 +1,348 @@
+use crate::algorithm::rstar::{rstar_choose_subtree, rstar_split_into};
+use crate::node::{envelope_for_children, size_for_children, Children, ParentNode, RTreeNode};
+use crate::params::RTreeParams;
+use crate::{Envelope, RTree, RTreeObject};
+
+use alloc::vec::Vec;
+use core::fmt;
+
+/// Empty child storage reserved for nodes created by future insertions, see
+/// [RTree::reserve].
+pub(crate) struct SpareNodes<T>
+where
+    T: RTreeObject,
+{
+    pub(crate) children: Vec<Children<T>>,
+}
+
+impl<T> Default for SpareNodes<T>
+where
+    T: RTreeObject,
+{
+    fn default() -> Self {
+        SpareNodes {
+            children: Vec::new(),
+        }
+    }
+}
+
+impl<T> Clone for SpareNodes<T>
+where
+    T: RTreeObject,
+{
+    fn clone(&self) -> Self {
+        // Cloning empty children would drop their capacity.
+        SpareNodes {
+            children: self
+                .children
+                .iter()
+                .map(|children| Children::with_capacity(children.capacity()))
+                .collect(),
+        }
+    }
+}
+
+impl<T> RTree<T>
+where
+    T: RTreeObject,
+{
+    /// Creates a new, empty r-tree with node storage reserved for at least `capacity` elements.
+    ///
+    /// The first `capacity` elements inserted with [RTree::try_insert_within_capacity] never
+    /// call the allocator. See [RTree::reserve].
+    ///
+    /// # Example
+    /// ```
+    /// use rstar::RTree;
+    ///
+    /// let mut tree = RTree::with_capacity(1000);
+    /// for i in 0..1000 {
+    ///     assert!(tree.try_insert_within_capacity([i as f64, 0.0]).is_ok());
+    /// }
+    /// assert_eq!(tree.size(), 1000);
+    /// ```
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_with_params(capacity)
+    }
+}
+
+impl<T, Params> RTree<T, Params>
+where
+    T: RTreeObject,
+    Params: RTreeParams,
+{
+    /// Creates a new, empty r-tree with configurable parameters and node storage reserved for
+    /// at least `capacity` elements.
+    ///
+    /// For more information refer to [RTree::with_capacity] and [RTreeParams].
+    pub fn with_capacity_with_params(capacity: usize) -> Self {
+        let mut tree = Self::new_with_params();
+        tree.reserve(capacity);
+        tree
+    }
+
+    /// Reserves node storage for at least `additional` more elements.
+    ///
+    /// Every node is given room for an overflowing child, and enough empty nodes are set
+    /// aside for all splits caused by inserting `additional` elements with
+    /// [RTree::try_insert_within_capacity]. This lets a real time thread insert elements
+    /// without ever calling the allocator, as long as storage was reserved beforehand.
+    ///
+    /// The reservation assumes that every node except for the root has at least
+    /// [RTreeParams::MIN_SIZE] children, which holds for nodes split by
+    /// [RTree::try_insert_within_capacity]. Other insertions, removals and bulk loading may
+    /// create or drop nodes without regard to the reservation. Reserved nodes are released by
+    /// [RTree::shrink_to_fit].
+    ///
+    /// # Runtime
+    /// This method runs in `O(n)` and allocates, it should be called outside of time critical
+    /// code.
+    pub fn reserve(&mut self, additional: usize) {
+        let required = required_nodes::<Params>(self.size() + additional);
+        let (root, spare_nodes) = self.root_and_spare_nodes_mut();
+        let mut nodes = 0;
+        let mut stack = Vec::new();
+        stack.push(root);
+        while let Some(node) = stack.pop() {
+            let max_size = node.max_size::<Params>();
+            if node.children.capacity() <= max_size {
+                let len = node.children.len();
+                node.children.reserve_exact(max_size + 1 - len);
+            }
+            for child in &mut node.children {
+                if let RTreeNode::Parent(ref mut parent) = child {
+                    nodes += 1;
+                    stack.push(parent);
+                }
+            }
+        }
+        let missing = required.saturating_sub(nodes + spare_nodes.children.len());
+        let slots = Params::MAX_SIZE.max(Params::MAX_LEAF_SIZE) + 1;
+        spare_nodes
+            .children
+            .extend((0..missing).map(|_| Children::with_capacity(slots)));
+    }
+
+    /// Inserts a new element without allocating, or returns it if the reserved node storage
+    /// doesn't suffice.
+    ///
+    /// Storage is reserved with [RTree::with_capacity] or [RTree::reserve]. Insertion fails
+    /// if a node along the insertion path cannot hold another child without reallocating,
+    /// if a split requires more nodes than reserved, or if the root would need to be split
+    /// while the tree has already reached [RTreeParams::MAX_HEIGHT]. The tree is not
+    /// modified in these cases.
+    ///
+    /// Regardless of [RTreeParams::DefaultInsertionStrategy], nodes are chosen and split
+    /// like [SplitOnlyInsertionStrategy](crate::SplitOnlyInsertionStrategy) does, since
+    /// forced reinsertion cannot be bounded in advance. The insertion modifies a single path
+    /// from the root to a leaf and runs in `O(log(n))`.
+    ///
+    /// Nodes are split without allocating regardless of [RTreeParams::MAX_SIZE]. For custom
+    /// [Envelope] types, this requires [Envelope::sort_envelopes] not to allocate either.
+    ///
+    /// # Example
+    /// ```
+    /// use rstar::RTree;
+    ///
+    /// let mut tree = RTree::new();
+    /// // The root node has room for some elements, but no nodes are reserved for splits.
+    /// let result = (0..100).try_for_each(|i| tree.try_insert_within_capacity([i, 0]));
+    /// let error = result.unwrap_err();
+    /// assert!(!tree.contains(&error.element));
+    ///
+    /// tree.reserve(100);
+    /// assert!(tree.try_insert_within_capacity(error.element).is_ok());
+    /// ```
+    pub fn try_insert_within_capacity(&mut self, t: T) -> Result<(), CapacityExceeded<T>> {
+        let envelope = t.envelope();
+        let (root, spare_nodes) = self.root_and_spare_nodes_mut();
+
+        // Walk the insertion path first, nothing may be modified if the insertion fails.
+        let mut height = 1;
+        // The number of full nodes at the end of the path, which will be split.
+        let mut splits = 0;
+        let mut node = &*root;
+        loop {
+            let max_size = node.max_size::<Params>();
+            if node.children.capacity() <= max_size {
+                return Err(CapacityExceeded { element: t });
+            }
+            splits = if node.children.len() >= max_size {
+                splits + 1
+            } else {
+                0
+            };
+            node = match node.children.first() {
+                Some(RTreeNode::Parent(_)) => {
+                    match node.children[rstar_choose_subtree(node, &envelope, false)] {
+                        RTreeNode::Parent(ref child) => child,
+                        RTreeNode::Leaf(_) => unreachable!("This is a bug in rstar."),
+                    }
+                }
+                _ => break,
+            };
+            height += 1;
+        }
+        let root_split = splits == height;
+        if root_split && height >= Params::MAX_HEIGHT
+            || spare_nodes.children.len() < splits + root_split as usize
+        {
+            return Err(CapacityExceeded { element: t });
+        }
+
+        let spare = &mut spare_nodes.children;
+        if let Some(sibling) = insert_within_capacity::<_, Params>(root, t, &envelope, spare) {
+            let new_root = ParentNode {
+                envelope: Envelope::new_empty(),
+                children: spare
+                    .pop()
+                    .expect("Missing spare node. This is a bug in rstar."),
+                size: 0,
+            };
+            let old_root = core::mem::replace(root, new_root);
+            root.children.push(RTreeNode::Parent(old_root));
+            root.children.push(sibling);
+            root.envelope = envelope_for_children(&root.children);
+            root.size = size_for_children(&root.children);
+        }
+        *self.size_mut() += 1;
+        Ok(())
+    }
+
+    /// Inserts several elements without allocating, stopping at the first element for which
+    /// the reserved node storage doesn't suffice.
+    ///
+    /// This is the counterpart of bulk loading for trees with reserved storage. The elements
+    /// are inserted one by one with [RTree::try_insert_within_capacity], the elements after the
+    /// rejected one remain in the iterator.
+    ///
+    /// # Example
+    /// ```
+    /// use rstar::RTree;
+    ///
+    /// let elements = [[0.0, 0.0], [1.0, 1.0], [2.0, 2.0]];
+    /// let mut tree = RTree::with_capacity(elements.len());
+    /// assert!(tree.try_extend_within_capacity(elements).is_ok());
+    /// assert_eq!(tree.size(), 3);
+    /// ```
+    pub fn try_extend_within_capacity<I>(&mut self, elements: I) -> Result<(), CapacityExceeded<T>>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        for element in elements {
+            self.try_insert_within_capacity(element)?;
+        }
+        Ok(())
+    }
+}
+
+/// Inserts an element below `node`, taking the children of split off nodes from `spare`.
+///
+/// Returns the split off sibling of `node`, if any.
+fn insert_within_capacity<T, Params>(
+    node: &mut ParentNode<T>,
+    t: T,
+    envelope: &T::Envelope,
+    spare: &mut Vec<Children<T>>,
+) -> Option<RTreeNode<T>>
+where
+    T: RTreeObject,
+    Params: RTreeParams,
+{
+    node.envelope.merge(envelope);
+    node.size += 1;
+    match node.children.first() {
+        Some(RTreeNode::Parent(_)) => {
+            let index = rstar_choose_subtree(node, envelope, false);
+            let child = match node.children[index] {
+                RTreeNode::Parent(ref mut child) => child,
+                RTreeNode::Leaf(_) => unreachable!("This is a bug in rstar."),
+            };
+            if let Some(sibling) = insert_within_capacity::<_, Params>(child, t, envelope, spare) {
+                node.children.push(sibling);
+            }
+        }
+        _ => node.children.push(RTreeNode::Leaf(t)),
+    }
+    if node.children.len() > node.max_size::<Params>() {
+        // The children are sorted in place with an unstable sort, which doesn't allocate.
+        let off_split = spare
+            .pop()
+            .expect("Missing spare node. This is a bug in rstar.");
+        Some(rstar_split_into::<_, Params>(node, off_split))
+    } else {
+        None
+    }
+}
+
+/// Returns the number of non-root nodes to reserve for a tree with `size` elements, including
+/// the spare nodes needed by an insertion that splits every level.
+fn required_nodes<Params: RTreeParams>(size: usize) -> usize {
+    // Nodes with a single child are not accounted for, there is at least one node per level.
+    let min_size = Params::MIN_SIZE.max(2);
+    let mut nodes = 0;
+    let mut levels = 1;
+    let mut level_size = size / min_size;
+    while level_size > 1 {
+        nodes += level_size;
+        levels += 1;
+        level_size /= min_size;
+    }
+    nodes + levels + 1
+}
+
+/// Error returned by [RTree::try_insert_within_capacity] if an insertion would need to
+/// allocate.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CapacityExceeded<T> {
+    /// The element that was not inserted.
+    pub element: T,
+}
+
+impl<T> fmt::Display for CapacityExceeded<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "inserting the element requires more node storage than reserved"
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: fmt::Debug> std::error::Error for CapacityExceeded<T> {}
+
+#[cfg(test)]
+mod test {
+    use crate::test_utilities::{create_random_points, create_random_rectangles, SEED_1};
+    use crate::RTree;
+
+    #[test]
+    fn test_insert_within_capacity() {
+        let points = create_random_points(2000, SEED_1);
+        let mut tree = RTree::with_capacity(points.len());
+        assert!(tree
+            .try_extend_within_capacity(points.iter().copied())
+            .is_ok());
+        assert_eq!(tree.size(), points.len());
+        tree.root().sanity_check::<crate::DefaultParams>(true);
+        assert!(points.iter().all(|point| tree.contains(point)));
+
+        let rectangles = create_random_rectangles(500, SEED_1);
+        let mut tree = RTree::bulk_load(rectangles[..250].to_vec());
+        tree.reserve(250);
+        for rectangle in &rectangles[250..] {
+            assert!(tree.try_insert_within_capacity(*rectangle).is_ok());
+        }
+        assert_eq!(tree.size(), 500);
+        assert!(rectangles.iter().all(|rectangle| tree.contains(rectangle)));
+
+        let mut tree = RTree::new();
+        for point in &points[..6] {
+            assert!(tree.try_insert_within_capacity(*point).is_ok());
+        }
+        let error = tree.try_insert_within_capacity(points[6]).unwrap_err();
+        assert_eq!(error.element, points[6]);
+        assert_eq!(tree.size(), 6);
+        assert!(!tree.contains(&points[6]));
+    }
+}
@@ -0,0 +1,170 @@
+use crate::params::RTreeParams;
+use crate::point::{Point, PointExt};
+use crate::{Envelope, PointDistance, RTree, RTreeObject, AABB};
+
+use alloc::collections::BinaryHeap;
+use core::cmp::Ordering;
+use num_traits::{Float, One, Zero};
+
+impl<T, P, Params> RTree<T, Params>
+where
+    T: PointDistance + RTreeObject,
+    T::Envelope: Envelope<Point = P>,
+    P: Point,
+    P::Scalar: Float,
+    Params: RTreeParams,
+{
+    /// Returns the point within an envelope which is farthest away from its nearest element,
+    /// together with the distance to that element.
+    ///
+    /// This is the center of the largest empty circle centered within `envelope`, e.g. the
+    /// spot with the maximal clearance to place a new facility. Elements outside of
+    /// `envelope` are taken into account. The returned distance is at most `tolerance`
+    /// smaller than the optimum. Returns `None` if the tree is empty.
+    ///
+    /// The envelope is subdivided by branch and bound: cells are split in halves best first,
+    /// cells which can't contain a point more than `tolerance` better than the best point
+    /// found so far are discarded.
+    ///
+    /// # Panics
+    /// Panics if `tolerance` is not positive.
+    ///
+    /// # Example
+    /// ```
+    /// use rstar::{RTree, AABB};
+    ///
+    /// let tree = RTree::bulk_load(vec![[0.0, 0.0], [10.0, 0.0], [0.0, 10.0], [10.0, 10.0]]);
+    /// let envelope = AABB::from_corners([0.0, 0.0], [10.0, 10.0]);
+    /// let (center, radius) = tree.largest_empty_circle(&envelope, 1e-3).unwrap();
+    /// assert!((radius - 50.0f64.sqrt()).abs() <= 1e-3);
+    /// assert!((center[0] - 5.0).abs() < 0.01 && (center[1] - 5.0).abs() < 0.01);
+    /// ```
+    pub fn largest_empty_circle(
+        &self,
+        envelope: &AABB<P>,
+        tolerance: P::Scalar,
+    ) -> Option<(P, P::Scalar)> {
+        assert!(tolerance > Zero::zero(), "Tolerance must be positive");
+        let clearance = |point: &P| {
+            self.nearest_neighbor(point)
+                .map(|nearest| nearest.distance_2(point).sqrt())
+        };
+        let cell = |envelope: AABB<P>| {
+            let center = envelope.center();
+            let distance = clearance(&center)?;
+            let half_diagonal = PointExt::distance_2(&envelope.lower(), &center).sqrt();
+            Some(Cell {
+                bound: distance + half_diagonal,
+                distance,
+                center,
+                envelope,
+            })
+        };
+
+        let first = cell(envelope.clone())?;
+        let mut best = (first.center.clone(), first.distance);
+        let mut cells = BinaryHeap::new();
+        cells.push(first);
+        while let Some(current) = cells.pop() {
+            if current.bound - best.1 <= tolerance {
+                break;
+            }
+            let (lower, upper) = (current.envelope.lower(), current.envelope.upper());
+            let diagonal = upper.sub(&lower);
+            let axis = (0..P::DIMENSIONS)
+                .max_by(|l, r| {
+                    diagonal
+                        .nth(*l)
+                        .partial_cmp(&diagonal.nth(*r))
+                        .unwrap_or(Ordering::Equal)
+                })
+                .unwrap_or(0);
+            let two = P::Scalar::one() + P::Scalar::one();
+            let middle = (lower.nth(axis) + upper.nth(axis)) / two;
+            let mut split_upper = upper.clone();
+            *split_upper.nth_mut(axis) = middle;
+            let mut split_lower = lower.clone();
+            *split_lower.nth_mut(axis) = middle;
+            for half in [
+                AABB::from_corners(lower, split_upper),
+                AABB::from_corners(split_lower, upper),
+            ] {
+                let half = cell(half)?;
+                if half.distance > best.1 {
+                    best = (half.center.clone(), half.distance);
+                }
+                if half.bound - best.1 > tolerance {
+                    cells.push(half);
+                }
+            }
+        }
+        Some(best)
+    }
+}
+
+/// A part of the searched envelope, ordered by the upper bound of the clearance within it.
+struct Cell<P>
+where
+    P: Point,
+{
+    bound: P::Scalar,
+    distance: P::Scalar,
+    center: P,
+    envelope: AABB<P>,
+}
+
+impl<P: Point> PartialEq for Cell<P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.bound == other.bound
+    }
+}
+
+impl<P: Point> Eq for Cell<P> {}
+
+impl<P: Point> PartialOrd for Cell<P> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<P: Point> Ord for Cell<P> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.bound
+            .partial_cmp(&other.bound)
+            .unwrap_or(Ordering::Equal)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::point::PointExt;
+    use crate::test_utilities::{create_random_points, SEED_1};
+    use crate::{Envelope, RTree, AABB};
+
+    #[test]
+    fn test_largest_empty_circle() {
+        let points = create_random_points(300, SEED_1);
+        let tree = RTree::bulk_load(points.clone());
+        let envelope = AABB::from_corners([0.2, 0.3], [0.7, 0.6]);
+        let tolerance = 1e-4;
+        let (center, radius) = tree.largest_empty_circle(&envelope, tolerance).unwrap();
+        assert!(envelope.contains_point(&center));
+        let clearance = |point: &[f64; 2]| {
+            points
+                .iter()
+                .map(|p| p.distance_2(point).sqrt())
+                .fold(f64::INFINITY, f64::min)
+        };
+        assert!((clearance(&center) - radius).abs() < 1e-12);
+        for x in 0..=100 {
+            for y in 0..=100 {
+                let point = [0.2 + 0.005 * x as f64, 0.3 + 0.003 * y as f64];
+                assert!(clearance(&point) <= radius + tolerance);
+            }
+        }
+        assert_eq!(
+            RTree::<[f64; 2]>::new().largest_empty_circle(&envelope, tolerance),
+            None
+        );
+    }
+}
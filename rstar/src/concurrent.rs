@@ -0,0 +1,279 @@
+use crate::params::{DefaultParams, RTreeParams};
+use crate::{Envelope, Point, PointDistance, RTree, RTreeObject, AABB};
+
+use alloc::vec::Vec;
+use num_traits::{One, Zero};
+use std::sync::{PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+type Scalar<T> = <<<T as RTreeObject>::Envelope as Envelope>::Point as Point>::Scalar;
+
+/// An r-tree that can be modified and queried from several threads at once.
+///
+/// Elements are distributed over several inner trees (_shards_) by the position of their
+/// envelope's center within a regular grid. Every shard is protected by its own lock, so
+/// threads inserting elements into different regions rarely block each other. Queries visit
+/// every shard whose elements may be relevant, shard boundaries don't affect query results.
+///
+/// Elements outside of the grid's bounds are assigned to the closest shard. Requires the
+/// `concurrent` feature.
+///
+/// # Example
+/// ```
+/// use rstar::{ConcurrentRTree, AABB};
+///
+/// let tree = ConcurrentRTree::new(AABB::from_corners([0.0, 0.0], [100.0, 100.0]), 4);
+/// std::thread::scope(|scope| {
+///     for thread in 0..4 {
+///         let tree = &tree;
+///         scope.spawn(move || {
+///             for i in 0..100 {
+///                 tree.insert([i as f64, thread as f64 * 25.0]);
+///             }
+///         });
+///     }
+/// });
+/// assert_eq!(tree.size(), 400);
+/// assert_eq!(tree.nearest_neighbor(&[10.2, 49.0]), Some([10.0, 50.0]));
+/// ```
+pub struct ConcurrentRTree<T, Params = DefaultParams>
+where
+    T: RTreeObject,
+    Params: RTreeParams,
+{
+    shards: Vec<RwLock<RTree<T, Params>>>,
+    /// The interior grid lines along every axis.
+    splits: Vec<Vec<Scalar<T>>>,
+}
+
+impl<T> ConcurrentRTree<T>
+where
+    T: RTreeObject,
+{
+    /// Creates a new, empty tree.
+    ///
+    /// The grid covers `bounds` and is divided into `shards_per_axis` cells along every
+    /// axis, resulting in `shards_per_axis.pow(dimensions)` shards.
+    pub fn new(bounds: AABB<<T::Envelope as Envelope>::Point>, shards_per_axis: usize) -> Self {
+        Self::new_with_params(bounds, shards_per_axis)
+    }
+}
+
+impl<T, Params> ConcurrentRTree<T, Params>
+where
+    T: RTreeObject,
+    Params: RTreeParams,
+{
+    /// Creates a new, empty tree with custom parameters.
+    ///
+    /// See [ConcurrentRTree::new].
+    pub fn new_with_params(
+        bounds: AABB<<T::Envelope as Envelope>::Point>,
+        shards_per_axis: usize,
+    ) -> Self {
+        assert!(
+            shards_per_axis > 0,
+            "At least one shard per axis is required"
+        );
+        let dimensions = <T::Envelope as Envelope>::Point::DIMENSIONS;
+        let count = (0..shards_per_axis).fold(Scalar::<T>::zero(), |n, _| n + One::one());
+        let splits = (0..dimensions)
+            .map(|axis| {
+                let (lower, upper) = (bounds.lower().nth(axis), bounds.upper().nth(axis));
+                let mut k = Scalar::<T>::zero();
+                (1..shards_per_axis)
+                    .map(|_| {
+                        k = k + One::one();
+                        lower + (upper - lower) * k / count
+                    })
+                    .collect()
+            })
+            .collect();
+        let shards = (0..shards_per_axis.pow(dimensions as u32))
+            .map(|_| RwLock::new(RTree::new_with_params()))
+            .collect();
+        ConcurrentRTree { shards, splits }
+    }
+
+    fn shard_index(&self, envelope: &T::Envelope) -> usize {
+        let center = envelope.center();
+        self.splits
+            .iter()
+            .enumerate()
+            .fold(0, |index, (axis, splits)| {
+                let cell = splits
+                    .iter()
+                    .take_while(|&&split| split <= center.nth(axis))
+                    .count();
+                index * (splits.len() + 1) + cell
+            })
+    }
+
+    fn read(&self, shard: usize) -> RwLockReadGuard<'_, RTree<T, Params>> {
+        self.shards[shard]
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn write(&self, shard: usize) -> RwLockWriteGuard<'_, RTree<T, Params>> {
+        self.shards[shard]
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Returns the number of shards.
+    pub fn shard_count(&self) -> usize {
+        self.shards.len()
+    }
+
+    /// Returns the number of elements in the tree.
+    ///
+    /// Other threads may modify the tree while the shards are counted.
+    pub fn size(&self) -> usize {
+        (0..self.shards.len())
+            .map(|shard| self.read(shard).size())
+            .sum()
+    }
+
+    /// Inserts a new element. Only the element's shard is locked.
+    pub fn insert(&self, t: T) {
+        let shard = self.shard_index(&t.envelope());
+        self.write(shard).insert(t);
+    }
+
+    /// Calls `f` for every element whose envelope intersects `envelope`.
+    ///
+    /// Every shard is locked for reading while its elements are visited.
+    pub fn for_each_in_envelope_intersecting(&self, envelope: &T::Envelope, mut f: impl FnMut(&T)) {
+        for shard in 0..self.shards.len() {
+            self.read(shard)
+                .locate_in_envelope_intersecting(envelope)
+                .for_each(&mut f);
+        }
+    }
+
+    /// Consumes the tree and returns a single tree containing all elements.
+    pub fn into_rtree(self) -> RTree<T, Params> {
+        let elements = self
+            .shards
+            .into_iter()
+            .flat_map(|shard| {
+                let mut tree = shard.into_inner().unwrap_or_else(PoisonError::into_inner);
+                tree.drain().collect::<Vec<_>>()
+            })
+            .collect();
+        RTree::bulk_load_with_params(elements)
+    }
+}
+
+impl<T, Params> ConcurrentRTree<T, Params>
+where
+    T: RTreeObject + Clone,
+    Params: RTreeParams,
+{
+    /// Returns copies of all elements whose envelope intersects `envelope`.
+    ///
+    /// See [RTree::locate_in_envelope_intersecting].
+    pub fn locate_in_envelope_intersecting(&self, envelope: &T::Envelope) -> Vec<T> {
+        let mut result = Vec::new();
+        self.for_each_in_envelope_intersecting(envelope, |t| result.push(t.clone()));
+        result
+    }
+}
+
+impl<T, Params> ConcurrentRTree<T, Params>
+where
+    T: RTreeObject + PartialEq,
+    Params: RTreeParams,
+{
+    /// Returns `true` if the tree contains an element equal to `t`.
+    pub fn contains(&self, t: &T) -> bool {
+        self.read(self.shard_index(&t.envelope())).contains(t)
+    }
+
+    /// Removes and returns an element equal to `t`. Only the element's shard is locked.
+    pub fn remove(&self, t: &T) -> Option<T> {
+        self.write(self.shard_index(&t.envelope())).remove(t)
+    }
+}
+
+impl<T, Params> ConcurrentRTree<T, Params>
+where
+    T: PointDistance + Clone,
+    Params: RTreeParams,
+{
+    /// Returns a copy of the nearest neighbor of a point.
+    ///
+    /// Shards are visited in the order of their distance to the query point, shards that
+    /// cannot contain a closer element are skipped.
+    pub fn nearest_neighbor(&self, query_point: &<T::Envelope as Envelope>::Point) -> Option<T> {
+        let mut shards: Vec<_> = (0..self.shards.len())
+            .filter_map(|shard| {
+                let tree = self.read(shard);
+                if tree.size() == 0 {
+                    None
+                } else {
+                    Some((tree.root().envelope().distance_2(query_point), shard))
+                }
+            })
+            .collect();
+        shards.sort_by(|(l, _), (r, _)| l.partial_cmp(r).unwrap());
+
+        let mut best: Option<(T, Scalar<T>)> = None;
+        for (min_distance, shard) in shards {
+            if matches!(best, Some((_, distance)) if distance <= min_distance) {
+                break;
+            }
+            let tree = self.read(shard);
+            if let Some((t, distance)) = tree
+                .nearest_neighbor_iter_with_distance_2(query_point)
+                .next()
+            {
+                if best.as_ref().map_or(true, |&(_, best)| distance < best) {
+                    best = Some((t.clone(), distance));
+                }
+            }
+        }
+        best.map(|(t, _)| t)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ConcurrentRTree;
+    use crate::test_utilities::{create_random_points, SEED_1, SEED_2};
+    use crate::{RTree, AABB};
+
+    #[test]
+    fn test_concurrent_insertion() {
+        let points = create_random_points(2000, SEED_1);
+        // Only part of the points lie within the grid's bounds
+        let tree = ConcurrentRTree::new(AABB::from_corners([0.2, 0.2], [0.8, 0.8]), 3);
+        assert_eq!(tree.shard_count(), 9);
+        std::thread::scope(|scope| {
+            for chunk in points.chunks(500) {
+                let tree = &tree;
+                scope.spawn(move || chunk.iter().for_each(|point| tree.insert(*point)));
+            }
+        });
+        assert_eq!(tree.size(), 2000);
+        assert!(points.iter().all(|point| tree.contains(point)));
+
+        let reference = RTree::bulk_load(points.clone());
+        for query in create_random_points(50, SEED_2) {
+            assert_eq!(
+                tree.nearest_neighbor(&query).as_ref(),
+                reference.nearest_neighbor(&query)
+            );
+            let envelope = AABB::from_corners(query, [query[0] + 0.2, query[1] + 0.1]);
+            assert_eq!(
+                tree.locate_in_envelope_intersecting(&envelope).len(),
+                reference.locate_in_envelope_intersecting(&envelope).count()
+            );
+        }
+
+        assert_eq!(tree.remove(&points[0]), Some(points[0]));
+        assert!(!tree.contains(&points[0]));
+        let merged = tree.into_rtree();
+        assert_eq!(merged.size(), 1999);
+    }
+}
@@ -0,0 +1,152 @@
+use crate::params::{DefaultParams, RTreeParams};
+use crate::{RTree, RTreeObject};
+
+use alloc::sync::Arc;
+
+/// An [RTree] whose clones share the whole tree until one of them is modified.
+///
+/// Cloning a `CowRTree` only increments a reference count, which makes it cheap to hand a
+/// read-only view of a large tree to another thread. The first modification of a tree that
+/// is still shared with one of its clones copies the tree, later modifications are as cheap
+/// as those of an [RTree]. Queries are made on the underlying tree, see [CowRTree::tree].
+///
+/// If shared trees are modified often, e.g. to publish a new version every frame, a
+/// [FrozenRTree](crate::FrozenRTree) is the better fit: its modifications only copy the
+/// nodes on the path to the modified element.
+///
+/// # Example
+/// ```
+/// use rstar::{CowRTree, RTree};
+///
+/// let mut tree = CowRTree::from(RTree::bulk_load(vec![[0.0, 0.0], [1.0, 1.0]]));
+/// let view = tree.clone();
+///
+/// // The tree is still shared with the view, so it is copied before the insertion.
+/// tree.insert([2.0, 2.0]);
+/// assert_eq!(tree.tree().size(), 3);
+/// assert_eq!(view.tree().size(), 2);
+/// ```
+pub struct CowRTree<T, Params = DefaultParams>
+where
+    T: RTreeObject,
+    Params: RTreeParams,
+{
+    tree: Arc<RTree<T, Params>>,
+}
+
+impl<T, Params> CowRTree<T, Params>
+where
+    T: RTreeObject,
+    Params: RTreeParams,
+{
+    /// Returns the underlying tree.
+    pub fn tree(&self) -> &RTree<T, Params> {
+        &self.tree
+    }
+
+    /// Returns `true` if the tree is shared with one of its clones.
+    pub fn is_shared(&self) -> bool {
+        Arc::strong_count(&self.tree) > 1
+    }
+}
+
+impl<T, Params> CowRTree<T, Params>
+where
+    T: RTreeObject,
+    Params: RTreeParams,
+    RTree<T, Params>: Clone,
+{
+    /// Returns the underlying tree for modification, copying it first if it is shared.
+    pub fn tree_mut(&mut self) -> &mut RTree<T, Params> {
+        Arc::make_mut(&mut self.tree)
+    }
+
+    /// Consumes this wrapper and returns the underlying tree, copying it if it is shared.
+    pub fn into_tree(self) -> RTree<T, Params> {
+        Arc::try_unwrap(self.tree).unwrap_or_else(|tree| (*tree).clone())
+    }
+
+    /// Inserts an element.
+    ///
+    /// See [RTree::insert].
+    pub fn insert(&mut self, t: T) {
+        self.tree_mut().insert(t);
+    }
+}
+
+impl<T, Params> CowRTree<T, Params>
+where
+    T: RTreeObject + PartialEq,
+    Params: RTreeParams,
+    RTree<T, Params>: Clone,
+{
+    /// Removes an element and returns it.
+    ///
+    /// The tree is only copied if it is shared and contains the element. See [RTree::remove].
+    pub fn remove(&mut self, t: &T) -> Option<T> {
+        if !self.tree.contains(t) {
+            return None;
+        }
+        self.tree_mut().remove(t)
+    }
+}
+
+impl<T, Params> Clone for CowRTree<T, Params>
+where
+    T: RTreeObject,
+    Params: RTreeParams,
+{
+    /// Returns a clone sharing the tree, without copying any nodes.
+    fn clone(&self) -> Self {
+        CowRTree {
+            tree: self.tree.clone(),
+        }
+    }
+}
+
+impl<T, Params> From<RTree<T, Params>> for CowRTree<T, Params>
+where
+    T: RTreeObject,
+    Params: RTreeParams,
+{
+    fn from(tree: RTree<T, Params>) -> Self {
+        CowRTree {
+            tree: Arc::new(tree),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::CowRTree;
+    use crate::test_utilities::{create_random_points, SEED_1};
+    use crate::RTree;
+
+    #[test]
+    fn test_cow_rtree() {
+        let points = create_random_points(100, SEED_1);
+        let mut tree = CowRTree::from(RTree::bulk_load(points.clone()));
+        assert!(!tree.is_shared());
+
+        let view = tree.clone();
+        assert!(tree.is_shared());
+        assert!(core::ptr::eq(tree.tree(), view.tree()));
+        // Removing a missing element doesn't copy the tree.
+        assert_eq!(tree.remove(&[2.0, 2.0]), None);
+        assert!(core::ptr::eq(tree.tree(), view.tree()));
+
+        assert_eq!(tree.remove(&points[0]), Some(points[0]));
+        assert!(!tree.is_shared());
+        assert_eq!(tree.tree().size(), 99);
+        assert_eq!(view.tree().size(), 100);
+        assert!(view.tree().contains(&points[0]));
+
+        // A tree that is no longer shared is modified in place.
+        let view = tree.clone();
+        drop(view);
+        let unshared = tree.tree() as *const RTree<[f64; 2]>;
+        tree.insert(points[0]);
+        assert!(core::ptr::eq(tree.tree(), unshared));
+        assert_eq!(tree.into_tree().size(), 100);
+    }
+}
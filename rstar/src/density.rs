@@ -0,0 +1,100 @@
+use crate::node::RTreeNode;
+use crate::params::RTreeParams;
+use crate::point::Point;
+use crate::{Envelope, PointDistance, RTree, RTreeObject};
+
+use alloc::vec::Vec;
+use num_traits::{Float, FromPrimitive, One, Zero};
+
+/// Elements farther away than this many bandwidths are ignored by the density queries.
+///
+/// Their kernel contribution is below `exp(-18)`, about `1.5e-8`.
+const CUTOFF_BANDWIDTHS: f64 = 6.0;
+
+impl<T, P, Params> RTree<T, Params>
+where
+    T: PointDistance + RTreeObject,
+    T::Envelope: Envelope<Point = P>,
+    P: Point,
+    P::Scalar: Float + FromPrimitive,
+    Params: RTreeParams,
+{
+    /// Returns the kernel density of the elements at a point.
+    ///
+    /// Every element contributes `exp(-d² / (2 * bandwidth²))` where `d` is its distance to
+    /// the point, i.e. a gaussian kernel with standard deviation `bandwidth`. The result is
+    /// not normalized, divide it by `size() * (2π * bandwidth²)^(d / 2)` for a probability
+    /// density in `d` dimensions.
+    ///
+    /// Subtrees farther away than six bandwidths are skipped without visiting their elements.
+    /// Each skipped element contributes less than `1.5e-8`.
+    ///
+    /// # Example
+    /// ```
+    /// use rstar::RTree;
+    ///
+    /// let tree = RTree::bulk_load(vec![[0.0, 0.0], [1.0, 0.0], [100.0, 0.0]]);
+    /// let density = tree.density_at(&[0.0, 0.0], 1.0);
+    /// assert!((density - (1.0 + (-0.5f64).exp())).abs() < 1e-12);
+    /// ```
+    pub fn density_at(&self, point: &P, bandwidth: P::Scalar) -> P::Scalar {
+        let cutoff = P::Scalar::from_f64(CUTOFF_BANDWIDTHS).unwrap() * bandwidth;
+        let cutoff_2 = cutoff * cutoff;
+        let two = P::Scalar::one() + P::Scalar::one();
+        let scale = -(two * bandwidth * bandwidth).recip();
+        let mut result = P::Scalar::zero();
+        let mut stack = Vec::new();
+        stack.push(self.root());
+        while let Some(node) = stack.pop() {
+            if node.envelope.distance_2(point) > cutoff_2 {
+                continue;
+            }
+            for child in node.children() {
+                match child {
+                    RTreeNode::Parent(ref data) => stack.push(data),
+                    RTreeNode::Leaf(ref t) => {
+                        let distance_2 = t.distance_2(point);
+                        if distance_2 <= cutoff_2 {
+                            result = result + (distance_2 * scale).exp();
+                        }
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    /// Returns the kernel density of the elements at multiple points.
+    ///
+    /// See [RTree::density_at].
+    pub fn density_at_points(&self, points: &[P], bandwidth: P::Scalar) -> Vec<P::Scalar> {
+        points
+            .iter()
+            .map(|point| self.density_at(point, bandwidth))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::point::PointExt;
+    use crate::test_utilities::{create_random_points, SEED_1, SEED_2};
+    use crate::RTree;
+
+    #[test]
+    fn test_density_at() {
+        let points = create_random_points(2000, SEED_1);
+        let tree = RTree::bulk_load(points.clone());
+        let queries = create_random_points(20, SEED_2);
+        let bandwidth = 0.02;
+        let densities = tree.density_at_points(&queries, bandwidth);
+        for (query, density) in queries.iter().zip(densities) {
+            let expected: f64 = points
+                .iter()
+                .map(|point| (-point.distance_2(query) / (2.0 * bandwidth * bandwidth)).exp())
+                .sum();
+            assert!((density - expected).abs() < 1e-6);
+        }
+        assert_eq!(RTree::<[f64; 2]>::new().density_at(&[0.0, 0.0], 1.0), 0.0);
+    }
+}
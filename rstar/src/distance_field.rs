@@ -0,0 +1,223 @@
+use crate::node::RTreeNode;
+use crate::params::RTreeParams;
+use crate::point::{Point, PointExt};
+use crate::{Envelope, PointDistance, RTree, RTreeObject, AABB};
+
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ops::Range;
+use num_traits::{Float, NumCast, Zero};
+
+impl<T, P, Params> RTree<T, Params>
+where
+    T: PointDistance + RTreeObject<Envelope = AABB<P>>,
+    P: Point,
+    P::Scalar: Float,
+    Params: RTreeParams,
+{
+    /// Returns the distance from the center of every cell of a regular grid to the nearest
+    /// element, e.g. to draw buffers or isolines around the elements.
+    ///
+    /// The grid divides `grid_extent` into `nx` columns and `ny` rows, the distances are
+    /// returned in row major order like [RTree::histogram]. All distances are infinite if the
+    /// tree is empty.
+    ///
+    /// Instead of a nearest neighbor query per cell, blocks of cells are matched against the
+    /// tree in a single traversal. Nodes which are farther away from a block than the upper
+    /// bound of the distance of all its cells are discarded for the whole block, which is
+    /// then split until single cells remain.
+    ///
+    /// # Panics
+    /// Panics if the elements are not two dimensional.
+    ///
+    /// # Example
+    /// ```
+    /// use rstar::{RTree, AABB};
+    ///
+    /// let tree = RTree::bulk_load(vec![[0.5, 0.5], [3.5, 0.5]]);
+    /// let extent = AABB::from_corners([0.0, 0.0], [4.0, 1.0]);
+    /// assert_eq!(tree.distance_field(extent, 4, 1), vec![0.0, 1.0, 1.0, 0.0]);
+    /// ```
+    pub fn distance_field(&self, grid_extent: AABB<P>, nx: usize, ny: usize) -> Vec<P::Scalar> {
+        assert_eq!(P::DIMENSIONS, 2, "Distance fields require two dimensions");
+        let mut field = vec![P::Scalar::infinity(); nx * ny];
+        if self.size() > 0 && nx > 0 && ny > 0 {
+            let grid = SampleGrid::new(grid_extent, nx, ny);
+            let candidates = self.root().children.iter().collect();
+            grid.sample(0..nx, 0..ny, candidates, &mut field);
+        }
+        field
+    }
+}
+
+/// The centers of the cells of a regular grid.
+pub(crate) struct SampleGrid<P>
+where
+    P: Point,
+{
+    lower: P,
+    cell_size: P,
+    nx: usize,
+}
+
+impl<P> SampleGrid<P>
+where
+    P: Point,
+    P::Scalar: Float,
+{
+    pub(crate) fn new(extent: AABB<P>, nx: usize, ny: usize) -> Self {
+        let size = extent.upper().sub(&extent.lower());
+        let cells = [nx, ny];
+        SampleGrid {
+            lower: extent.lower(),
+            cell_size: P::generate(|axis| size.nth(axis) / cast(cells[axis])),
+            nx,
+        }
+    }
+
+    pub(crate) fn center(&self, x: usize, y: usize) -> P {
+        let index = [x, y];
+        let half: P::Scalar = cast::<P::Scalar>(1) / cast(2);
+        P::generate(|axis| {
+            self.lower.nth(axis)
+                + (cast::<P::Scalar>(index[axis]) + half) * self.cell_size.nth(axis)
+        })
+    }
+
+    /// Computes the distances of a block of cells, considering only elements of `candidates`.
+    fn sample<T>(
+        &self,
+        xs: Range<usize>,
+        ys: Range<usize>,
+        candidates: Vec<&RTreeNode<T>>,
+        field: &mut [P::Scalar],
+    ) where
+        T: PointDistance + RTreeObject<Envelope = AABB<P>>,
+    {
+        let block = AABB::from_corners(
+            self.center(xs.start, ys.start),
+            self.center(xs.end - 1, ys.end - 1),
+        );
+        let block_diagonal_2 = PointExt::distance_2(&block.lower(), &block.upper());
+        let block_center = block.center();
+        let half_diagonal = block_diagonal_2.sqrt() / cast(2);
+        // Returns a lower and an upper bound of the distance from any cell in the block to the
+        // nearest element in a node.
+        let bounds = |node: &RTreeNode<T>| {
+            let envelope = node.envelope();
+            let lower = envelope.distance_2_to_aabb(&block).sqrt();
+            let upper = match node {
+                RTreeNode::Leaf(t) => {
+                    PointDistance::distance_2(t, &block_center).sqrt() + half_diagonal
+                }
+                RTreeNode::Parent(_) => max_distance(&envelope, &block),
+            };
+            (lower, upper)
+        };
+
+        let mut threshold = P::Scalar::infinity();
+        for node in &candidates {
+            threshold = threshold.min(bounds(node).1);
+        }
+        // Nodes larger than the block are replaced by their children.
+        let mut stack = candidates;
+        let mut nodes = Vec::new();
+        while let Some(node) = stack.pop() {
+            let (lower, upper) = bounds(node);
+            if lower > threshold {
+                continue;
+            }
+            threshold = threshold.min(upper);
+            match node {
+                RTreeNode::Parent(data)
+                    if PointExt::distance_2(&data.envelope.lower(), &data.envelope.upper())
+                        > block_diagonal_2 =>
+                {
+                    stack.extend(&data.children)
+                }
+                _ => nodes.push((node, lower)),
+            }
+        }
+
+        if xs.len() == 1 && ys.len() == 1 {
+            // All remaining nodes are elements, whose upper bound is their exact distance.
+            field[ys.start * self.nx + xs.start] = threshold;
+            return;
+        }
+        let candidates: Vec<_> = nodes
+            .into_iter()
+            .filter(|(_, lower)| *lower <= threshold)
+            .map(|(node, _)| node)
+            .collect();
+        if xs.len() >= ys.len() {
+            let middle = xs.start + xs.len() / 2;
+            self.sample(xs.start..middle, ys.clone(), candidates.clone(), field);
+            self.sample(middle..xs.end, ys, candidates, field);
+        } else {
+            let middle = ys.start + ys.len() / 2;
+            self.sample(xs.clone(), ys.start..middle, candidates.clone(), field);
+            self.sample(xs, middle..ys.end, candidates, field);
+        }
+    }
+}
+
+fn cast<S: NumCast>(value: usize) -> S {
+    S::from(value).unwrap()
+}
+
+/// Returns the largest distance between a point of one envelope and a point of another.
+fn max_distance<P>(first: &AABB<P>, second: &AABB<P>) -> P::Scalar
+where
+    P: Point,
+    P::Scalar: Float,
+{
+    let mut result = P::Scalar::zero();
+    for axis in 0..P::DIMENSIONS {
+        let extent = (first.upper().nth(axis) - second.lower().nth(axis))
+            .abs()
+            .max((second.upper().nth(axis) - first.lower().nth(axis)).abs());
+        result = result + extent * extent;
+    }
+    result.sqrt()
+}
+
+#[cfg(test)]
+mod test {
+    use super::SampleGrid;
+    use crate::test_utilities::{create_random_points, create_random_rectangles, SEED_1};
+    use crate::{PointDistance, RTree, AABB};
+
+    #[test]
+    fn test_distance_field() {
+        let extent = AABB::from_corners([-0.2, 0.1], [1.1, 0.9]);
+        let grid = SampleGrid::new(extent, 37, 23);
+        let points = create_random_points(500, SEED_1);
+        let field = RTree::bulk_load(points.clone()).distance_field(extent, 37, 23);
+        for y in 0..23 {
+            for x in 0..37 {
+                let center = grid.center(x, y);
+                let expected = points
+                    .iter()
+                    .map(|point| point.distance_2(&center).sqrt())
+                    .fold(f64::INFINITY, f64::min);
+                assert_eq!(field[y * 37 + x], expected);
+            }
+        }
+
+        let rectangles = create_random_rectangles(200, SEED_1);
+        let field = RTree::bulk_load(rectangles.clone()).distance_field(extent, 37, 23);
+        for y in 0..23 {
+            for x in 0..37 {
+                let center = grid.center(x, y);
+                let expected = rectangles
+                    .iter()
+                    .map(|rectangle| rectangle.distance_2(&center).sqrt())
+                    .fold(f64::INFINITY, f64::min);
+                assert_eq!(field[y * 37 + x], expected);
+            }
+        }
+
+        let empty = RTree::<[f64; 2]>::new().distance_field(extent, 2, 2);
+        assert_eq!(empty, vec![f64::INFINITY; 4]);
+    }
+}
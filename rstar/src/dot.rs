@@ -0,0 +1,103 @@
+use crate::node::{ParentNode, RTreeNode};
+use crate::object::RTreeObject;
+use crate::params::RTreeParams;
+use crate::RTree;
+
+use alloc::format;
+use alloc::string::String;
+use core::fmt::{self, Write};
+
+impl<T, Params> RTree<T, Params>
+where
+    T: RTreeObject,
+    Params: RTreeParams,
+{
+    /// Writes the node hierarchy of this tree in the DOT format of Graphviz.
+    ///
+    /// Every parent node becomes a graph node labeled with its depth, its number of children
+    /// and leaves and its envelope. Leaves are not drawn individually. The output can be
+    /// rendered with `dot -Tsvg`, which helps to spot degenerated splits or unbalanced nodes.
+    ///
+    /// Only available with the `debug` feature.
+    ///
+    /// # Example
+    /// ```
+    /// use rstar::RTree;
+    ///
+    /// let tree = RTree::bulk_load((0..100).map(|i| [i as f64, 0.0]).collect());
+    /// let mut dot = String::new();
+    /// tree.write_dot(&mut dot).unwrap();
+    /// assert!(dot.starts_with("digraph rtree {"));
+    /// ```
+    pub fn write_dot<W: Write>(&self, out: &mut W) -> fmt::Result {
+        writeln!(out, "digraph rtree {{")?;
+        writeln!(out, "  node [shape=box, fontname=monospace];")?;
+        let mut next_id = 0;
+        write_node(out, self.root(), 0, &mut next_id)?;
+        writeln!(out, "}}")
+    }
+
+    /// Returns the node hierarchy of this tree in the DOT format of Graphviz.
+    ///
+    /// See [RTree::write_dot].
+    pub fn to_dot(&self) -> String {
+        let mut result = String::new();
+        self.write_dot(&mut result)
+            .expect("Writing to a String cannot fail");
+        result
+    }
+}
+
+/// Writes a node and its subtree, returns the node's id.
+fn write_node<T, W>(
+    out: &mut W,
+    node: &ParentNode<T>,
+    depth: usize,
+    next_id: &mut usize,
+) -> Result<usize, fmt::Error>
+where
+    T: RTreeObject,
+    W: Write,
+{
+    let id = *next_id;
+    *next_id += 1;
+    let leaves = node
+        .children
+        .iter()
+        .filter(|child| matches!(child, RTreeNode::Leaf(_)))
+        .count();
+    let envelope = format!("{:?}", node.envelope).replace('"', "\\\"");
+    writeln!(
+        out,
+        "  n{} [label=\"depth {}\\nchildren {}, leaves {}\\n{}\"];",
+        id,
+        depth,
+        node.children.len(),
+        leaves,
+        envelope
+    )?;
+    for child in &node.children {
+        if let RTreeNode::Parent(ref data) = child {
+            let child_id = write_node(out, data, depth + 1, next_id)?;
+            writeln!(out, "  n{} -> n{};", id, child_id)?;
+        }
+    }
+    Ok(id)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_utilities::{create_random_points, SEED_1};
+    use crate::RTree;
+
+    #[test]
+    fn test_write_dot() {
+        let tree = RTree::bulk_load(create_random_points(1000, SEED_1));
+        let dot = tree.to_dot();
+        let nodes = dot.lines().filter(|line| line.contains("[label=")).count();
+        let edges = dot.lines().filter(|line| line.contains("->")).count();
+        assert!(nodes > 1);
+        assert_eq!(edges, nodes - 1);
+        assert_eq!(dot.lines().last(), Some("}"));
+    }
+}
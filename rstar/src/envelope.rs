@@ -4,8 +4,9 @@ use crate::{Point, RTreeObject};
 ///
 /// An envelope defines how different bounding boxes of inserted children in an r-tree can interact,
 /// e.g. how they can be merged or intersected.
-/// This trait is not meant to be implemented by the user. Currently, only one implementation
-/// exists ([crate::AABB]) and should be used.
+/// This trait is not meant to be implemented by the user. Currently, two implementations
+/// exist: [crate::AABB], which should be used in most cases, and [crate::GeoAABB] for
+/// longitude / latitude data that wraps around the antimeridian.
 pub trait Envelope: Clone + PartialEq + ::core::fmt::Debug {
     /// The envelope's point type.
     type Point: Point;
@@ -52,12 +53,21 @@ pub trait Envelope: Clone + PartialEq + ::core::fmt::Debug {
     fn perimeter_value(&self) -> <Self::Point as Point>::Scalar;
 
     /// Sorts a given set of objects with envelopes along one of their axes.
+    ///
+    /// This is used to split nodes. The sort doesn't need to be stable, but it should not
+    /// allocate, as [RTree::try_insert_within_capacity](crate::RTree::try_insert_within_capacity)
+    /// promises to never call the allocator. `sort_unstable_by` meets both requirements.
     fn sort_envelopes<T: RTreeObject<Envelope = Self>>(axis: usize, envelopes: &mut [T]);
 
     /// Partitions objects with an envelope along a certain axis.
     ///
-    /// After calling this, envelopes[0..selection_size] are all smaller
-    /// than envelopes[selection_size + 1..].
+    /// After calling this, `envelopes[..selection_size]` are all smaller
+    /// than `envelopes[selection_size..]`.
+    ///
+    /// This is the only method used by [RTree::bulk_load](crate::RTree::bulk_load) to
+    /// divide the elements, apart from merging envelopes. Any order along the axis works,
+    /// e.g. by lower corner or by center, but envelopes that are near each other should end
+    /// up near each other, otherwise the tree's nodes overlap heavily.
     fn partition_envelopes<T: RTreeObject<Envelope = Self>>(
         axis: usize,
         envelopes: &mut [T],
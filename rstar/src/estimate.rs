@@ -0,0 +1,144 @@
+use crate::node::{ParentNode, RTreeNode};
+use crate::params::RTreeParams;
+use crate::point::Point;
+use crate::{Envelope, RTree, RTreeObject, AABB};
+
+use num_traits::{Float, One, ToPrimitive, Zero};
+
+/// An approximate number of elements, see [RTree::estimate_count_in_envelope].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CountEstimate {
+    /// The estimated number of elements.
+    pub estimate: f64,
+    /// The smallest possible number of elements.
+    pub lower_bound: usize,
+    /// The largest possible number of elements.
+    pub upper_bound: usize,
+}
+
+impl<T, P, Params> RTree<T, Params>
+where
+    T: RTreeObject<Envelope = AABB<P>>,
+    P: Point,
+    P::Scalar: Float,
+    Params: RTreeParams,
+{
+    /// Estimates the number of elements whose envelope intersects `envelope`.
+    ///
+    /// Only parent nodes are visited: subtrees fully contained in the envelope contribute
+    /// their size, leaf nodes partially overlapping the envelope contribute their size
+    /// multiplied with the fraction of their envelope covered by the query, assuming their
+    /// elements are distributed uniformly. The actual count is guaranteed to lie within the
+    /// returned bounds. This is much faster than counting, e.g. to estimate the selectivity
+    /// of a query while planning.
+    ///
+    /// # Example
+    /// ```
+    /// use rstar::{RTree, AABB};
+    ///
+    /// let points: Vec<_> = (0..1000).map(|i| [(i % 100) as f64, (i / 100) as f64]).collect();
+    /// let tree = RTree::bulk_load(points);
+    /// let envelope = AABB::from_corners([0.0, 0.0], [49.5, 9.0]);
+    /// let estimate = tree.estimate_count_in_envelope(&envelope);
+    /// let count = tree.locate_in_envelope_intersecting(&envelope).count();
+    /// assert!(estimate.lower_bound <= count && count <= estimate.upper_bound);
+    /// assert!((estimate.estimate - count as f64).abs() < 100.0);
+    /// ```
+    pub fn estimate_count_in_envelope(&self, envelope: &AABB<P>) -> CountEstimate {
+        let mut result = CountEstimate {
+            estimate: 0.0,
+            lower_bound: 0,
+            upper_bound: 0,
+        };
+        estimate_recursive(self.root(), envelope, &mut result);
+        result
+    }
+}
+
+fn estimate_recursive<T, P>(node: &ParentNode<T>, envelope: &AABB<P>, result: &mut CountEstimate)
+where
+    T: RTreeObject<Envelope = AABB<P>>,
+    P: Point,
+    P::Scalar: Float,
+{
+    if node.size == 0 || !node.envelope.intersects(envelope) {
+        return;
+    }
+    if envelope.contains_envelope(&node.envelope) {
+        result.estimate += node.size as f64;
+        result.lower_bound += node.size;
+        result.upper_bound += node.size;
+        return;
+    }
+    let mut is_leaf_node = true;
+    for child in &node.children {
+        if let RTreeNode::Parent(ref data) = child {
+            is_leaf_node = false;
+            estimate_recursive(data, envelope, result);
+        }
+    }
+    if is_leaf_node {
+        let fraction = covered_fraction(&node.envelope, envelope)
+            .to_f64()
+            .unwrap_or(1.0);
+        result.estimate += node.size as f64 * fraction;
+        result.upper_bound += node.size;
+    }
+}
+
+/// Returns the fraction of `node` covered by `envelope`.
+///
+/// Axes along which `node` has no extent are ignored, as `node` and `envelope` intersect.
+fn covered_fraction<P>(node: &AABB<P>, envelope: &AABB<P>) -> P::Scalar
+where
+    P: Point,
+    P::Scalar: Float,
+{
+    let (lower, upper) = (node.lower(), node.upper());
+    let (query_lower, query_upper) = (envelope.lower(), envelope.upper());
+    let mut fraction = P::Scalar::one();
+    for axis in 0..P::DIMENSIONS {
+        let length = upper.nth(axis) - lower.nth(axis);
+        if length > Zero::zero() {
+            let overlap = upper.nth(axis).min(query_upper.nth(axis))
+                - lower.nth(axis).max(query_lower.nth(axis));
+            fraction = fraction * overlap.max(Zero::zero()) / length;
+        }
+    }
+    fraction
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_utilities::{create_random_points, create_random_rectangles, SEED_1, SEED_2};
+    use crate::{RTree, AABB};
+
+    #[test]
+    fn test_estimate_count_in_envelope() {
+        let points = create_random_points(10_000, SEED_1);
+        let tree = RTree::bulk_load(points);
+        let rectangles = RTree::bulk_load(create_random_rectangles(1000, SEED_1));
+        for [x, y] in create_random_points(50, SEED_2) {
+            let envelope = AABB::from_corners([x, y], [x + 0.3, y + 0.2]);
+            let count = tree.locate_in_envelope_intersecting(&envelope).count();
+            let estimate = tree.estimate_count_in_envelope(&envelope);
+            assert!(estimate.lower_bound <= count && count <= estimate.upper_bound);
+            assert!(estimate.estimate >= estimate.lower_bound as f64);
+            assert!(estimate.estimate <= estimate.upper_bound as f64);
+            // Uniformly distributed points are estimated well.
+            assert!((estimate.estimate - count as f64).abs() <= 0.1 * count as f64 + 20.0);
+
+            let count = rectangles
+                .locate_in_envelope_intersecting(&envelope)
+                .count();
+            let estimate = rectangles.estimate_count_in_envelope(&envelope);
+            assert!(estimate.lower_bound <= count && count <= estimate.upper_bound);
+        }
+        let all = AABB::from_corners([0.0, 0.0], [1.0, 1.0]);
+        let estimate = tree.estimate_count_in_envelope(&all);
+        assert_eq!(estimate.lower_bound, 10_000);
+        assert_eq!(estimate.estimate, 10_000.0);
+        let empty = RTree::<[f64; 2]>::new().estimate_count_in_envelope(&all);
+        assert_eq!(empty.upper_bound, 0);
+    }
+}
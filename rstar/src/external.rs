@@ -0,0 +1,386 @@
+use crate::packed::{read_point, write_header, write_point, PackedElement, PackedScalar};
+use crate::params::{DefaultParams, RTreeParams};
+use crate::{Envelope, PackedRTree, Point, AABB};
+
+use alloc::collections::BinaryHeap;
+use alloc::vec::Vec;
+use core::cmp::Reverse;
+use num_traits::ToPrimitive;
+use smallvec::SmallVec;
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+
+/// Temporary storage used by [PackedRTree::bulk_load_external] to spill data that doesn't fit
+/// into memory.
+///
+/// # Example
+/// A storage using temporary files, based on the
+/// [tempfile](https://crates.io/crates/tempfile) crate:
+/// ```ignore
+/// struct TempFiles;
+///
+/// impl rstar::TempStorage for TempFiles {
+///     type Run = std::fs::File;
+///
+///     fn create_run(&mut self) -> std::io::Result<std::fs::File> {
+///         tempfile::tempfile()
+///     }
+/// }
+/// ```
+pub trait TempStorage {
+    /// A temporary file that can be written and read back. Runs are dropped once they are no
+    /// longer needed.
+    type Run: Read + Write + Seek;
+
+    /// Creates a new, empty run.
+    fn create_run(&mut self) -> io::Result<Self::Run>;
+}
+
+/// Returns the position of `point` on a z-order curve covering `envelope`.
+fn z_order<P>(point: &P, envelope: &AABB<P>) -> u64
+where
+    P: Point,
+    P::Scalar: ToPrimitive,
+{
+    let bits = 64 / P::DIMENSIONS;
+    let max = ((1u128 << bits) - 1) as f64;
+    let to_f64 = |value: P::Scalar| value.to_f64().unwrap_or(0.0);
+    let cells: SmallVec<[u64; 4]> = (0..P::DIMENSIONS)
+        .map(|axis| {
+            let lower = to_f64(envelope.lower().nth(axis));
+            let upper = to_f64(envelope.upper().nth(axis));
+            if upper > lower {
+                ((to_f64(point.nth(axis)) - lower) / (upper - lower) * max) as u64
+            } else {
+                0
+            }
+        })
+        .collect();
+    let mut key = 0;
+    for bit in (0..bits).rev() {
+        for cell in &cells {
+            key = (key << 1) | ((cell >> bit) & 1);
+        }
+    }
+    key
+}
+
+fn rewind<R: Seek>(run: &mut R) -> io::Result<()> {
+    run.seek(SeekFrom::Start(0)).map(|_| ())
+}
+
+fn read_u64<R: Read>(reader: &mut R) -> io::Result<u64> {
+    let mut bytes = [0; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+/// Reads `count` envelopes and writes the envelopes of every group of `node_size` envelopes.
+fn merge_level<P, R, W>(reader: R, writer: W, count: usize, node_size: usize) -> io::Result<()>
+where
+    P: Point,
+    P::Scalar: PackedScalar,
+    R: Read,
+    W: Write,
+{
+    let (mut reader, mut writer) = (BufReader::new(reader), BufWriter::new(writer));
+    let mut record = alloc::vec![0; 2 * P::SIZE];
+    for group_start in (0..count).step_by(node_size) {
+        let mut envelope = AABB::<P>::new_empty();
+        for _ in group_start..(group_start + node_size).min(count) {
+            reader.read_exact(&mut record)?;
+            envelope.merge(&AABB::from_corners(
+                read_point(&record),
+                read_point(&record[P::SIZE..]),
+            ));
+        }
+        write_envelope(&mut writer, &envelope, &mut record)?;
+    }
+    writer.flush()
+}
+
+fn write_envelope<P, W>(writer: &mut W, envelope: &AABB<P>, buffer: &mut [u8]) -> io::Result<()>
+where
+    P: Point,
+    P::Scalar: PackedScalar,
+    W: Write,
+{
+    write_point(&envelope.lower(), buffer);
+    write_point(&envelope.upper(), &mut buffer[P::SIZE..]);
+    writer.write_all(buffer)
+}
+
+impl<T, P> PackedRTree<T>
+where
+    T: PackedElement<Envelope = AABB<P>>,
+    P: Point,
+    P::Scalar: PackedScalar + ToPrimitive,
+{
+    /// Creates a packed tree from more elements than fit into memory and writes it to
+    /// `output`.
+    ///
+    /// The elements are consumed in chunks of `chunk_size` elements. Every chunk is sorted
+    /// along a z-order curve and spilled into a run of `temp_storage`, the runs are then
+    /// merged. Nodes are filled completely, every node has
+    /// [DefaultParams::MAX_SIZE](crate::DefaultParams) children except for the last node of
+    /// every level.
+    ///
+    /// Apart from the current chunk, only a small buffer for every run is kept in memory. The
+    /// written tree can be read with [PackedRTree::from_storage], e.g. through a
+    /// [PagedStorage](crate::PagedStorage) or a memory map. Requires the `std` feature.
+    ///
+    /// # Example
+    /// ```
+    /// use rstar::{PackedRTree, TempStorage};
+    /// use std::io::Cursor;
+    ///
+    /// // Real applications would use temporary files instead.
+    /// struct InMemory;
+    ///
+    /// impl TempStorage for InMemory {
+    ///     type Run = Cursor<Vec<u8>>;
+    ///
+    ///     fn create_run(&mut self) -> std::io::Result<Cursor<Vec<u8>>> {
+    ///         Ok(Cursor::new(Vec::new()))
+    ///     }
+    /// }
+    ///
+    /// let elements = (0..10_000).map(|i| [(i % 100) as f64, (i / 100) as f64]);
+    /// let mut output = Vec::new();
+    /// PackedRTree::bulk_load_external(elements, 1000, &mut InMemory, &mut output).unwrap();
+    ///
+    /// let tree = PackedRTree::<[f64; 2], _>::from_bytes(output).unwrap();
+    /// assert_eq!(tree.size(), 10_000);
+    /// assert_eq!(tree.nearest_neighbor(&[10.2, 20.9]), Some([10.0, 21.0]));
+    /// ```
+    pub fn bulk_load_external<I, S, W>(
+        elements: I,
+        chunk_size: usize,
+        temp_storage: &mut S,
+        output: W,
+    ) -> io::Result<()>
+    where
+        I: IntoIterator<Item = T>,
+        S: TempStorage,
+        W: Write,
+    {
+        assert!(chunk_size > 0, "Chunk size must be at least 1");
+        let node_size = DefaultParams::MAX_SIZE;
+
+        // Spill unsorted chunks, the envelope of all elements is required for sorting.
+        let mut runs = Vec::new();
+        let mut envelope = AABB::new_empty();
+        let mut count = 0;
+        let mut elements = elements.into_iter();
+        let mut chunk = Vec::with_capacity(chunk_size);
+        let mut record = alloc::vec![0; T::SIZE + 8];
+        loop {
+            chunk.clear();
+            chunk.extend(elements.by_ref().take(chunk_size));
+            if chunk.is_empty() {
+                break;
+            }
+            let mut run = temp_storage.create_run()?;
+            let mut writer = BufWriter::new(&mut run);
+            for t in &chunk {
+                envelope.merge(&t.envelope());
+                t.write_packed(&mut record);
+                writer.write_all(&record[..T::SIZE])?;
+            }
+            writer.flush()?;
+            drop(writer);
+            runs.push((run, chunk.len()));
+            count += chunk.len();
+        }
+
+        // Sort every run in place, prefixing every element with its position on the curve.
+        for (run, length) in &mut runs {
+            rewind(run)?;
+            let mut reader = BufReader::new(&mut *run);
+            let mut sorted = Vec::with_capacity(*length);
+            for _ in 0..*length {
+                reader.read_exact(&mut record[..T::SIZE])?;
+                let t = T::read_packed(&record);
+                sorted.push((z_order(&t.envelope().center(), &envelope), t));
+            }
+            drop(reader);
+            sorted.sort_by_key(|&(key, _)| key);
+            rewind(run)?;
+            let mut writer = BufWriter::new(&mut *run);
+            for (key, t) in sorted {
+                writer.write_all(&key.to_le_bytes())?;
+                t.write_packed(&mut record);
+                writer.write_all(&record[..T::SIZE])?;
+            }
+            writer.flush()?;
+        }
+
+        // Merge all runs into the sorted elements and the envelopes of the lowest level.
+        let mut sorted_elements = temp_storage.create_run()?;
+        let mut levels = alloc::vec![(temp_storage.create_run()?, 0)];
+        {
+            let mut readers = Vec::with_capacity(runs.len());
+            let mut heap = BinaryHeap::with_capacity(runs.len());
+            for (index, (mut run, length)) in runs.into_iter().enumerate() {
+                rewind(&mut run)?;
+                let mut reader = BufReader::new(run);
+                heap.push(Reverse((read_u64(&mut reader)?, index)));
+                readers.push((reader, length - 1));
+            }
+            let mut elements_writer = BufWriter::new(&mut sorted_elements);
+            let mut level_writer = BufWriter::new(&mut levels[0].0);
+            let mut group_envelope = AABB::new_empty();
+            let mut group_size = 0;
+            let mut envelope_buffer = alloc::vec![0; 2 * P::SIZE];
+            while let Some(Reverse((_, index))) = heap.pop() {
+                let (reader, remaining) = &mut readers[index];
+                reader.read_exact(&mut record[..T::SIZE])?;
+                elements_writer.write_all(&record[..T::SIZE])?;
+                if *remaining > 0 {
+                    *remaining -= 1;
+                    heap.push(Reverse((read_u64(reader)?, index)));
+                }
+                group_envelope.merge(&T::read_packed(&record).envelope());
+                group_size += 1;
+                if group_size == node_size || heap.is_empty() {
+                    write_envelope(&mut level_writer, &group_envelope, &mut envelope_buffer)?;
+                    group_envelope = AABB::new_empty();
+                    group_size = 0;
+                }
+            }
+            elements_writer.flush()?;
+            level_writer.flush()?;
+        }
+        levels[0].1 = (count + node_size - 1) / node_size;
+
+        // Compute the envelopes of all upper levels.
+        while levels.last().map_or(false, |&(_, length)| length > 1) {
+            let (previous, length) = levels.last_mut().unwrap();
+            let length = *length;
+            rewind(previous)?;
+            let mut next = temp_storage.create_run()?;
+            merge_level::<P, _, _>(&mut *previous, &mut next, length, node_size)?;
+            levels.push((next, (length + node_size - 1) / node_size));
+        }
+
+        // Write the tree, starting with the root.
+        let node_count: usize = levels.iter().map(|&(_, length)| length.max(1)).sum();
+        let mut output = BufWriter::new(output);
+        let mut header = Vec::new();
+        write_header::<T, P>(&mut header, node_count as u64, count as u64);
+        output.write_all(&header)?;
+        let mut envelope_buffer = alloc::vec![0; 2 * P::SIZE];
+        let mut level_start = 0;
+        for depth in (0..levels.len()).rev() {
+            let length = levels[depth].1;
+            let children_start = level_start + length;
+            let children_count = if depth == 0 {
+                count
+            } else {
+                levels[depth - 1].1
+            };
+            let run = &mut levels[depth].0;
+            rewind(run)?;
+            let mut reader = BufReader::new(run);
+            if length == 0 {
+                // An empty tree consists of an empty root.
+                write_envelope(&mut output, &AABB::<P>::new_empty(), &mut envelope_buffer)?;
+            }
+            for index in 0..length.max(1) {
+                if length > 0 {
+                    reader.read_exact(&mut envelope_buffer)?;
+                    output.write_all(&envelope_buffer)?;
+                }
+                let first = index * node_size;
+                let children = children_count.saturating_sub(first).min(node_size);
+                let first = if depth == 0 {
+                    first
+                } else {
+                    children_start + first
+                };
+                output.write_all(&(first as u64).to_le_bytes())?;
+                output.write_all(&(children as u32).to_le_bytes())?;
+                output.write_all(&((depth == 0) as u32).to_le_bytes())?;
+            }
+            level_start = children_start;
+        }
+        rewind(&mut sorted_elements)?;
+        io::copy(&mut sorted_elements, &mut output)?;
+        output.flush()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::TempStorage;
+    use crate::packed::PackedRTree;
+    use crate::primitives::Rectangle;
+    use crate::test_utilities::{create_random_points, create_random_rectangles, SEED_1, SEED_2};
+    use crate::{RTree, AABB};
+    use std::io::{self, Cursor};
+
+    struct InMemory {
+        runs: usize,
+    }
+
+    impl TempStorage for InMemory {
+        type Run = Cursor<Vec<u8>>;
+
+        fn create_run(&mut self) -> io::Result<Cursor<Vec<u8>>> {
+            self.runs += 1;
+            Ok(Cursor::new(Vec::new()))
+        }
+    }
+
+    fn load<T>(elements: &[T], chunk_size: usize) -> PackedRTree<T>
+    where
+        T: Clone + crate::PackedElement<Envelope = AABB<[f64; 2]>>,
+    {
+        let mut output = Vec::new();
+        let mut storage = InMemory { runs: 0 };
+        PackedRTree::bulk_load_external(
+            elements.iter().cloned(),
+            chunk_size,
+            &mut storage,
+            &mut output,
+        )
+        .unwrap();
+        let tree = PackedRTree::from_bytes(output).unwrap();
+        // Make sure the tree is balanced.
+        assert_eq!(tree.decode_root().unwrap().size, elements.len());
+        tree
+    }
+
+    #[test]
+    fn test_bulk_load_external() {
+        let points = create_random_points(1000, SEED_1);
+        let tree = load(&points, 64);
+        let reference = RTree::bulk_load(points.clone());
+        assert_eq!(tree.size(), 1000);
+        assert!(points.iter().all(|point| tree.contains(point)));
+        for query in create_random_points(50, SEED_2) {
+            assert_eq!(
+                tree.nearest_neighbor(&query).as_ref(),
+                reference.nearest_neighbor(&query)
+            );
+        }
+
+        let rectangles = create_random_rectangles(100, SEED_1);
+        let tree: PackedRTree<Rectangle<[f64; 2]>> = load(&rectangles, 7);
+        let query = AABB::from_corners([0.2, 0.2], [0.6, 0.4]);
+        assert_eq!(
+            tree.locate_in_envelope_intersecting(&query).count(),
+            RTree::bulk_load(rectangles)
+                .locate_in_envelope_intersecting(&query)
+                .count()
+        );
+    }
+
+    #[test]
+    fn test_bulk_load_external_small() {
+        for size in 0..20 {
+            let points = create_random_points(size, SEED_1);
+            let tree = load(&points, 3);
+            assert_eq!(tree.iter().count(), size);
+        }
+    }
+}
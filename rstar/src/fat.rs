@@ -0,0 +1,278 @@
+use crate::algorithm::intersection_iterator::IntersectionIterator;
+use crate::algorithm::selection_functions::{SelectFromFns, SelectionFunction};
+use crate::params::{DefaultParams, RTreeParams};
+use crate::point::Point;
+use crate::{Envelope, RTree, RTreeObject, AABB};
+
+use alloc::vec::Vec;
+
+/// An object stored together with its enlarged envelope.
+#[derive(Clone, Debug, PartialEq)]
+struct FatObject<T>
+where
+    T: RTreeObject,
+{
+    object: T,
+    envelope: T::Envelope,
+}
+
+impl<T> RTreeObject for FatObject<T>
+where
+    T: RTreeObject,
+{
+    type Envelope = T::Envelope;
+
+    fn envelope(&self) -> T::Envelope {
+        self.envelope.clone()
+    }
+}
+
+/// An r-tree that stores enlarged envelopes to make small movements of objects cheap.
+///
+/// Every object is stored with an envelope that is larger than its actual envelope by a
+/// constant margin on every side. As long as an object's actual envelope stays within this
+/// "fat" envelope, [FatRTree::update] replaces the object in place without modifying the
+/// tree's structure. Only objects that escape their fat envelope are removed and inserted
+/// again. This is the dynamic AABB tree commonly used as broad phase of physics engines.
+///
+/// Queries first use the fat envelopes to find candidates and then check the actual
+/// envelopes, so they return the same results as a regular tree. Larger margins avoid more
+/// tree updates at the cost of slower queries.
+///
+/// # Example
+/// ```
+/// use rstar::primitives::Rectangle;
+/// use rstar::FatRTree;
+///
+/// let mut tree = FatRTree::new(0.5);
+/// let ball = Rectangle::from_corners([0.0, 0.0], [1.0, 1.0]);
+/// tree.insert(ball);
+///
+/// // A small movement stays within the fat envelope and doesn't change the tree.
+/// let moved = Rectangle::from_corners([0.2, 0.1], [1.2, 1.1]);
+/// assert_eq!(tree.update(&ball, moved), Some(false));
+/// // A large movement requires reinserting the object.
+/// let far = Rectangle::from_corners([5.0, 5.0], [6.0, 6.0]);
+/// assert_eq!(tree.update(&moved, far), Some(true));
+/// assert_eq!(tree.locate_all_at_point(&[5.5, 5.5]).count(), 1);
+/// ```
+pub struct FatRTree<T, Params = DefaultParams>
+where
+    T: RTreeObject,
+    Params: RTreeParams,
+{
+    tree: RTree<FatObject<T>, Params>,
+    margin: <<T::Envelope as Envelope>::Point as Point>::Scalar,
+}
+
+impl<T, P> FatRTree<T>
+where
+    T: RTreeObject<Envelope = AABB<P>>,
+    P: Point,
+{
+    /// Creates a new, empty tree that enlarges envelopes by `margin` on every side.
+    pub fn new(margin: P::Scalar) -> Self {
+        Self::new_with_params(margin)
+    }
+
+    /// Creates a new tree from a set of objects.
+    ///
+    /// See [RTree::bulk_load].
+    pub fn bulk_load(margin: P::Scalar, objects: Vec<T>) -> Self {
+        Self::bulk_load_with_params(margin, objects)
+    }
+}
+
+impl<T, P, Params> FatRTree<T, Params>
+where
+    T: RTreeObject<Envelope = AABB<P>>,
+    P: Point,
+    Params: RTreeParams,
+{
+    /// Creates a new, empty tree with custom parameters.
+    pub fn new_with_params(margin: P::Scalar) -> Self {
+        FatRTree {
+            tree: RTree::new_with_params(),
+            margin,
+        }
+    }
+
+    /// Creates a new tree from a set of objects with custom parameters.
+    pub fn bulk_load_with_params(margin: P::Scalar, objects: Vec<T>) -> Self {
+        let fat = objects
+            .into_iter()
+            .map(|object| fatten(object, margin))
+            .collect();
+        FatRTree {
+            tree: RTree::bulk_load_with_params(fat),
+            margin,
+        }
+    }
+
+    /// Returns the margin by which envelopes are enlarged.
+    pub fn margin(&self) -> P::Scalar {
+        self.margin
+    }
+
+    /// Returns the number of objects in the tree.
+    pub fn size(&self) -> usize {
+        self.tree.size()
+    }
+
+    /// Returns all objects of the tree, in arbitrary order.
+    pub fn iter<'a>(&'a self) -> impl Iterator<Item = &'a T> + 'a
+    where
+        P: 'a,
+    {
+        self.tree.iter().map(|fat| &fat.object)
+    }
+
+    /// Inserts an object.
+    pub fn insert(&mut self, object: T) {
+        self.tree.insert(fatten(object, self.margin));
+    }
+
+    /// Returns all objects whose envelope intersects a given envelope.
+    pub fn locate_in_envelope_intersecting<'a>(
+        &'a self,
+        envelope: &AABB<P>,
+    ) -> impl Iterator<Item = &'a T> + 'a
+    where
+        P: 'a,
+    {
+        let envelope = envelope.clone();
+        self.tree
+            .locate_in_envelope_intersecting(&envelope)
+            .map(|fat| &fat.object)
+            .filter(move |object| object.envelope().intersects(&envelope))
+    }
+
+    /// Returns all objects whose envelope contains a given point.
+    pub fn locate_all_at_point<'a>(&'a self, point: &P) -> impl Iterator<Item = &'a T> + 'a
+    where
+        P: 'a,
+    {
+        self.locate_in_envelope_intersecting(&AABB::from_point(point.clone()))
+    }
+
+    /// Returns all pairs of distinct objects whose envelopes intersect.
+    ///
+    /// Every pair is returned once, in arbitrary order.
+    pub fn intersecting_pairs<'a>(&'a self) -> impl Iterator<Item = (&'a T, &'a T)> + 'a
+    where
+        P: 'a,
+    {
+        IntersectionIterator::new(self.tree.root(), self.tree.root())
+            .filter(|(l, r)| (*l as *const FatObject<T>) < (*r as *const FatObject<T>))
+            .map(|(l, r)| (&l.object, &r.object))
+            .filter(|(l, r)| l.envelope().intersects(&r.envelope()))
+    }
+}
+
+impl<T, P, Params> FatRTree<T, Params>
+where
+    T: RTreeObject<Envelope = AABB<P>> + PartialEq,
+    P: Point,
+    Params: RTreeParams,
+{
+    /// Removes an object and returns it.
+    pub fn remove(&mut self, object: &T) -> Option<T> {
+        self.tree
+            .remove_with_selection_function(Self::select(object))
+            .map(|fat| fat.object)
+    }
+
+    /// Replaces `old` with `new`, usually the same object at a new position.
+    ///
+    /// If `new` lies within the fat envelope of `old`, the object is replaced in place and
+    /// `Some(false)` is returned. Otherwise, the object is removed and inserted again and
+    /// `Some(true)` is returned. Returns `None` if `old` is not contained in the tree.
+    pub fn update(&mut self, old: &T, new: T) -> Option<bool> {
+        let stored = self
+            .tree
+            .locate_with_selection_function_mut(Self::select(old))
+            .next()?;
+        if stored.envelope.contains_envelope(&new.envelope()) {
+            stored.object = new;
+            return Some(false);
+        }
+        self.remove(old);
+        self.insert(new);
+        Some(true)
+    }
+
+    fn select<'a>(object: &'a T) -> impl SelectionFunction<FatObject<T>> + 'a
+    where
+        P: 'a,
+    {
+        let envelope = object.envelope();
+        SelectFromFns::new(
+            move |node: &AABB<P>| node.contains_envelope(&envelope),
+            move |fat: &FatObject<T>| fat.object == *object,
+        )
+    }
+}
+
+fn fatten<T, P>(object: T, margin: P::Scalar) -> FatObject<T>
+where
+    T: RTreeObject<Envelope = AABB<P>>,
+    P: Point,
+{
+    let envelope = object.envelope().expanded_by(margin);
+    FatObject { object, envelope }
+}
+
+#[cfg(test)]
+mod test {
+    use super::FatRTree;
+    use crate::primitives::Rectangle;
+    use crate::test_utilities::{create_random_points, create_random_rectangles, SEED_1, SEED_2};
+    use crate::{Envelope, RTree, RTreeObject, AABB};
+
+    #[test]
+    fn test_fat_tree() {
+        let rectangles = create_random_rectangles(500, SEED_1);
+        let mut tree = FatRTree::bulk_load(0.05, rectangles.clone());
+        let mut moved = Vec::new();
+        let mut reinserted = 0;
+        for (index, rectangle) in rectangles.iter().enumerate() {
+            let offset = if index % 2 == 0 { 0.01 } else { 0.2 };
+            let envelope = rectangle.envelope();
+            let new = Rectangle::from_corners(
+                [envelope.lower()[0] + offset, envelope.lower()[1]],
+                [envelope.upper()[0] + offset, envelope.upper()[1]],
+            );
+            if tree.update(rectangle, new).unwrap() {
+                reinserted += 1;
+            }
+            moved.push(new);
+        }
+        assert_eq!(reinserted, 250);
+        assert_eq!(tree.size(), 500);
+        assert_eq!(tree.update(&rectangles[1], rectangles[1]), None);
+
+        let expected = RTree::bulk_load(moved.clone());
+        for point in create_random_points(50, SEED_2) {
+            assert_eq!(
+                tree.locate_all_at_point(&point).count(),
+                expected.locate_all_at_point(&point).count()
+            );
+            let envelope = AABB::from_corners(point, [point[0] + 0.1, point[1] + 0.1]);
+            assert_eq!(
+                tree.locate_in_envelope_intersecting(&envelope).count(),
+                expected.locate_in_envelope_intersecting(&envelope).count()
+            );
+        }
+        let pairs = moved
+            .iter()
+            .enumerate()
+            .flat_map(|(i, l)| moved[i + 1..].iter().map(move |r| (l, r)))
+            .filter(|(l, r)| l.envelope().intersects(&r.envelope()))
+            .count();
+        assert_eq!(tree.intersecting_pairs().count(), pairs);
+
+        assert_eq!(tree.remove(&moved[0]), Some(moved[0]));
+        assert_eq!(tree.remove(&moved[0]), None);
+        assert_eq!(tree.iter().count(), 499);
+    }
+}
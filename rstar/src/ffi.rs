@@ -0,0 +1,412 @@
+//! A C interface to two dimensional trees of points with `u64` ids.
+//!
+//! Only available with the `ffi` feature. The functions of this module use the C calling
+//! convention and unmangled names, a header can be generated with
+//! [cbindgen](https://crates.io/crates/cbindgen). Hosts embedding this crate usually link
+//! against a small `staticlib` or `cdylib` crate depending on rstar with this feature.
+//!
+//! Trees are passed around as opaque [RStarTree] handles created by [rstar_tree_new] or
+//! [rstar_tree_bulk_load] and destroyed by [rstar_tree_free]. Queries write the ids of all
+//! results into a buffer provided by the caller and return the number of results, which may
+//! be larger than the buffer:
+//!
+//! ```c
+//! RStarTree *tree = rstar_tree_new();
+//! rstar_tree_insert(tree, 0.0, 0.0, 1);
+//! rstar_tree_insert(tree, 1.0, 1.0, 2);
+//! uint64_t ids[16];
+//! size_t found = rstar_tree_locate_in_envelope(tree, -1.0, -1.0, 0.5, 0.5, ids, 16);
+//! rstar_tree_free(tree);
+//! ```
+//!
+//! Null tree handles are treated like empty trees. Output buffers may be null if their
+//! capacity is zero.
+//!
+//! No function unwinds into the caller: coordinates must be finite and are rejected
+//! otherwise, and panics are caught and reported like invalid input, i.e. as null handles,
+//! `false` or [RSTAR_ERROR]. A panic while inserting or removing a point may leave the tree
+//! inconsistent, so the tree is poisoned: every later call with its handle fails in the same
+//! way, and the tree can only be destroyed with [rstar_tree_free].
+#![allow(unsafe_code)]
+
+use crate::primitives::GeomWithData;
+use crate::{RTree, AABB};
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::{mem, ptr, slice};
+use std::panic::{self, AssertUnwindSafe};
+
+type Element = GeomWithData<[f64; 2], u64>;
+
+/// Returned by queries instead of a number of results if their input is invalid.
+pub const RSTAR_ERROR: usize = usize::MAX;
+
+/// An opaque handle to a tree of points with ids.
+pub struct RStarTree {
+    tree: RTree<Element>,
+    /// Set if a panic interrupted a modification of the tree.
+    poisoned: bool,
+}
+
+impl RStarTree {
+    fn new(tree: RTree<Element>) -> Self {
+        RStarTree {
+            tree,
+            poisoned: false,
+        }
+    }
+
+    /// Modifies the tree, returning `fallback` if the tree is poisoned or if `f` panics. A
+    /// panic poisons the tree, as it may have left the tree's nodes inconsistent.
+    fn modify<R>(&mut self, fallback: R, f: impl FnOnce(&mut RTree<Element>) -> R) -> R {
+        if self.poisoned {
+            return fallback;
+        }
+        let tree = &mut self.tree;
+        match panic::catch_unwind(AssertUnwindSafe(|| f(tree))) {
+            Ok(result) => result,
+            Err(_) => {
+                self.poisoned = true;
+                fallback
+            }
+        }
+    }
+
+    /// Queries the tree, returning [RSTAR_ERROR] if the tree is poisoned or if `f` panics.
+    fn query(&self, f: impl FnOnce(&RTree<Element>) -> usize) -> usize {
+        if self.poisoned {
+            RSTAR_ERROR
+        } else {
+            guard(RSTAR_ERROR, || f(&self.tree))
+        }
+    }
+}
+
+/// Creates an empty tree. The tree must be destroyed with [rstar_tree_free].
+///
+/// Returns null if the tree could not be created.
+#[no_mangle]
+pub extern "C" fn rstar_tree_new() -> *mut RStarTree {
+    guard(ptr::null_mut(), || {
+        Box::into_raw(Box::new(RStarTree::new(RTree::new())))
+    })
+}
+
+/// Creates a tree from `len` points by bulk loading, which is faster than inserting them one
+/// by one. The tree must be destroyed with [rstar_tree_free].
+///
+/// Returns null if a coordinate is not finite, if a pointer is null while `len` is not zero,
+/// or if `len` is too large for the buffers to exist.
+///
+/// # Safety
+/// `coordinates` must point to `2 * len` values (x and y of every point) and `ids` to `len`
+/// values. Both may be null if `len` is zero.
+#[no_mangle]
+pub unsafe extern "C" fn rstar_tree_bulk_load(
+    coordinates: *const f64,
+    ids: *const u64,
+    len: usize,
+) -> *mut RStarTree {
+    guard(ptr::null_mut(), || {
+        let elements = if len == 0 {
+            Vec::new()
+        } else {
+            let coordinates = match len.checked_mul(2).and_then(|len| buffer(coordinates, len)) {
+                Some(coordinates) => coordinates,
+                None => return ptr::null_mut(),
+            };
+            let ids = match buffer(ids, len) {
+                Some(ids) => ids,
+                None => return ptr::null_mut(),
+            };
+            if !coordinates.iter().all(|coordinate| coordinate.is_finite()) {
+                return ptr::null_mut();
+            }
+            coordinates
+                .chunks_exact(2)
+                .zip(ids)
+                .map(|(point, id)| GeomWithData::new([point[0], point[1]], *id))
+                .collect()
+        };
+        Box::into_raw(Box::new(RStarTree::new(RTree::bulk_load(elements))))
+    })
+}
+
+/// Destroys a tree, which may be poisoned.
+///
+/// # Safety
+/// `tree` must be null or a handle returned by [rstar_tree_new] or [rstar_tree_bulk_load]
+/// which has not been destroyed before.
+#[no_mangle]
+pub unsafe extern "C" fn rstar_tree_free(tree: *mut RStarTree) {
+    if !tree.is_null() {
+        let tree = Box::from_raw(tree);
+        guard((), move || drop(tree));
+    }
+}
+
+/// Inserts a point with an id, returns `true` if it was inserted.
+///
+/// Does nothing and returns `false` if `tree` is null or poisoned or if a coordinate is not
+/// finite.
+///
+/// # Safety
+/// `tree` must be null or a valid handle that is not used concurrently.
+#[no_mangle]
+pub unsafe extern "C" fn rstar_tree_insert(tree: *mut RStarTree, x: f64, y: f64, id: u64) -> bool {
+    match tree.as_mut() {
+        Some(tree) if is_finite(x, y) => tree.modify(false, |tree| {
+            tree.insert(GeomWithData::new([x, y], id));
+            true
+        }),
+        _ => false,
+    }
+}
+
+/// Removes a point with the given id and position, returns `true` if it was found.
+///
+/// Returns `false` if the tree is poisoned or if a coordinate is not finite.
+///
+/// # Safety
+/// `tree` must be null or a valid handle that is not used concurrently.
+#[no_mangle]
+pub unsafe extern "C" fn rstar_tree_remove(tree: *mut RStarTree, x: f64, y: f64, id: u64) -> bool {
+    match tree.as_mut() {
+        Some(tree) if is_finite(x, y) => tree.modify(false, |tree| {
+            tree.remove(&GeomWithData::new([x, y], id)).is_some()
+        }),
+        _ => false,
+    }
+}
+
+/// Returns the number of points in a tree, or [RSTAR_ERROR] if the tree is poisoned.
+///
+/// # Safety
+/// `tree` must be null or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn rstar_tree_size(tree: *const RStarTree) -> usize {
+    tree.as_ref()
+        .map_or(0, |tree| tree.query(|tree| tree.size()))
+}
+
+/// Writes the ids of the `k` points nearest to `(x, y)` into `out_ids`, nearest first.
+///
+/// Returns the number of ids written, which is less than `k` if the tree contains fewer
+/// points, or [RSTAR_ERROR] if the tree is poisoned, a coordinate is not finite or `out_ids`
+/// is invalid.
+///
+/// # Safety
+/// `tree` must be null or a valid handle. `out_ids` must point to space for `k` ids.
+#[no_mangle]
+pub unsafe extern "C" fn rstar_tree_nearest_neighbors(
+    tree: *const RStarTree,
+    x: f64,
+    y: f64,
+    k: usize,
+    out_ids: *mut u64,
+) -> usize {
+    if !is_finite(x, y) {
+        return RSTAR_ERROR;
+    }
+    let tree = match tree.as_ref() {
+        Some(tree) if k > 0 => tree,
+        _ => return 0,
+    };
+    let out = match buffer_mut(out_ids, k) {
+        Some(out) => out,
+        None => return RSTAR_ERROR,
+    };
+    tree.query(|tree| {
+        let neighbors = tree.nearest_neighbor_iter(&[x, y]);
+        let mut written = 0;
+        for (slot, neighbor) in out.iter_mut().zip(neighbors) {
+            *slot = neighbor.data;
+            written += 1;
+        }
+        written
+    })
+}
+
+/// Finds all points within the rectangle spanned by `(min_x, min_y)` and `(max_x, max_y)`.
+///
+/// Writes the ids of up to `capacity` points into `out_ids` and returns the total number of
+/// points found. If the result is larger than `capacity`, the query can be repeated with a
+/// larger buffer. Returns [RSTAR_ERROR] if the tree is poisoned, a coordinate is not finite
+/// or `out_ids` is invalid.
+///
+/// # Safety
+/// `tree` must be null or a valid handle. `out_ids` must point to space for `capacity` ids.
+#[no_mangle]
+pub unsafe extern "C" fn rstar_tree_locate_in_envelope(
+    tree: *const RStarTree,
+    min_x: f64,
+    min_y: f64,
+    max_x: f64,
+    max_y: f64,
+    out_ids: *mut u64,
+    capacity: usize,
+) -> usize {
+    if !is_finite(min_x, min_y) || !is_finite(max_x, max_y) {
+        return RSTAR_ERROR;
+    }
+    let tree = match tree.as_ref() {
+        Some(tree) => tree,
+        None => return 0,
+    };
+    let out: &mut [u64] = if capacity == 0 {
+        &mut []
+    } else {
+        match buffer_mut(out_ids, capacity) {
+            Some(out) => out,
+            None => return RSTAR_ERROR,
+        }
+    };
+    tree.query(|tree| {
+        let envelope = AABB::from_corners([min_x, min_y], [max_x, max_y]);
+        let mut found = 0;
+        for element in tree.locate_in_envelope(&envelope) {
+            if let Some(slot) = out.get_mut(found) {
+                *slot = element.data;
+            }
+            found += 1;
+        }
+        found
+    })
+}
+
+/// Runs `f`, returning `fallback` instead of unwinding into the caller if it panics.
+///
+/// Modifications of a tree must use [RStarTree::modify] instead, which poisons the tree.
+fn guard<R>(fallback: R, f: impl FnOnce() -> R) -> R {
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or(fallback)
+}
+
+fn is_finite(x: f64, y: f64) -> bool {
+    x.is_finite() && y.is_finite()
+}
+
+/// Returns the buffer of `len` values at `data`, or `None` if `data` is null or the buffer
+/// would be larger than `isize::MAX` bytes.
+///
+/// # Safety
+/// If the checks pass, `data` must point to `len` values valid for the returned lifetime.
+unsafe fn buffer<'a, T>(data: *const T, len: usize) -> Option<&'a [T]> {
+    if data.is_null() || len > isize::MAX as usize / mem::size_of::<T>() {
+        None
+    } else {
+        Some(slice::from_raw_parts(data, len))
+    }
+}
+
+/// The mutable counterpart of [buffer].
+///
+/// # Safety
+/// See [buffer].
+unsafe fn buffer_mut<'a, T>(data: *mut T, len: usize) -> Option<&'a mut [T]> {
+    if data.is_null() || len > isize::MAX as usize / mem::size_of::<T>() {
+        None
+    } else {
+        Some(slice::from_raw_parts_mut(data, len))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_utilities::{create_random_points, SEED_1};
+    use core::ptr;
+
+    #[test]
+    fn test_ffi_handles() {
+        let points = create_random_points(100, SEED_1);
+        let coordinates: Vec<f64> = points.iter().flat_map(|point| point.to_vec()).collect();
+        let ids: Vec<u64> = (0..100).collect();
+        unsafe {
+            let tree = rstar_tree_bulk_load(coordinates.as_ptr(), ids.as_ptr(), 100);
+            assert_eq!(rstar_tree_size(tree), 100);
+            assert!(rstar_tree_insert(tree, 2.0, 2.0, 100));
+            assert!(rstar_tree_remove(tree, points[3][0], points[3][1], 3));
+            assert!(!rstar_tree_remove(tree, points[3][0], points[3][1], 3));
+            assert_eq!(rstar_tree_size(tree), 100);
+
+            let mut nearest = [0; 2];
+            let written = rstar_tree_nearest_neighbors(tree, 3.0, 3.0, 2, nearest.as_mut_ptr());
+            assert_eq!(written, 2);
+            assert_eq!(nearest[0], 100);
+
+            let expected = points
+                .iter()
+                .filter(|point| point[0] <= 0.5 && point[1] <= 0.5)
+                .count()
+                - usize::from(points[3][0] <= 0.5 && points[3][1] <= 0.5);
+            let mut ids = [0; 8];
+            let found =
+                rstar_tree_locate_in_envelope(tree, 0.0, 0.0, 0.5, 0.5, ids.as_mut_ptr(), 8);
+            assert_eq!(found, expected);
+            assert!(ids[..found.min(8)].iter().all(|id| {
+                let point = points[*id as usize];
+                point[0] <= 0.5 && point[1] <= 0.5
+            }));
+            let count_only =
+                rstar_tree_locate_in_envelope(tree, 0.0, 0.0, 0.5, 0.5, ptr::null_mut(), 0);
+            assert_eq!(count_only, expected);
+            rstar_tree_free(tree);
+
+            assert_eq!(rstar_tree_size(ptr::null()), 0);
+            rstar_tree_free(ptr::null_mut());
+            let empty = rstar_tree_bulk_load(ptr::null(), ptr::null(), 0);
+            assert_eq!(rstar_tree_size(empty), 0);
+            rstar_tree_free(empty);
+        }
+    }
+
+    #[test]
+    fn test_ffi_invalid_input() {
+        let coordinates = [0.0, 0.0, f64::NAN, 1.0];
+        let ids = [0, 1];
+        unsafe {
+            assert!(rstar_tree_bulk_load(coordinates.as_ptr(), ids.as_ptr(), 2).is_null());
+            assert!(rstar_tree_bulk_load(coordinates.as_ptr(), ids.as_ptr(), usize::MAX).is_null());
+            assert!(rstar_tree_bulk_load(ptr::null(), ids.as_ptr(), 2).is_null());
+
+            let tree = rstar_tree_bulk_load(coordinates.as_ptr(), ids.as_ptr(), 1);
+            assert!(!rstar_tree_insert(tree, f64::NAN, 0.0, 2));
+            assert!(!rstar_tree_insert(tree, 0.0, f64::INFINITY, 2));
+            assert!(!rstar_tree_remove(tree, f64::NAN, 0.0, 0));
+            assert_eq!(rstar_tree_size(tree), 1);
+
+            let mut out = [0; 4];
+            let nearest = rstar_tree_nearest_neighbors(tree, f64::NAN, 0.0, 4, out.as_mut_ptr());
+            assert_eq!(nearest, RSTAR_ERROR);
+            let nearest = rstar_tree_nearest_neighbors(tree, 0.0, 0.0, 4, ptr::null_mut());
+            assert_eq!(nearest, RSTAR_ERROR);
+            let found =
+                rstar_tree_locate_in_envelope(tree, 0.0, f64::NAN, 1.0, 1.0, out.as_mut_ptr(), 4);
+            assert_eq!(found, RSTAR_ERROR);
+            rstar_tree_free(tree);
+        }
+
+        assert_eq!(guard(0, || panic!("caught")), 0);
+    }
+
+    #[test]
+    fn test_ffi_poisoned_tree() {
+        unsafe {
+            let tree = rstar_tree_new();
+            assert!(rstar_tree_insert(tree, 0.0, 0.0, 0));
+            assert!(!(*tree).modify(false, |_| panic!("interrupted modification")));
+
+            assert!(!rstar_tree_insert(tree, 1.0, 1.0, 1));
+            assert!(!rstar_tree_remove(tree, 0.0, 0.0, 0));
+            assert_eq!(rstar_tree_size(tree), RSTAR_ERROR);
+            let mut out = [0; 4];
+            let nearest = rstar_tree_nearest_neighbors(tree, 0.0, 0.0, 4, out.as_mut_ptr());
+            assert_eq!(nearest, RSTAR_ERROR);
+            let found =
+                rstar_tree_locate_in_envelope(tree, 0.0, 0.0, 1.0, 1.0, out.as_mut_ptr(), 4);
+            assert_eq!(found, RSTAR_ERROR);
+            rstar_tree_free(tree);
+        }
+    }
+}
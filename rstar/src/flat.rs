@@ -0,0 +1,332 @@
+use crate::aabb::AABB;
+use crate::params::{DefaultParams, RTreeParams};
+use crate::point::Point;
+use crate::primitives::GeomWithData;
+use crate::RTree;
+
+use alloc::vec::Vec;
+
+impl<P> RTree<P>
+where
+    P: Point,
+{
+    /// Creates a new r-tree from points given as flat coordinates.
+    ///
+    /// `coordinates` contains the coordinates of all points one after another, e.g.
+    /// `[x0, y0, x1, y1, ...]` for two dimensional points. This is the layout of typed arrays
+    /// passed from JavaScript, which avoids converting every point individually when crossing
+    /// the boundary to WebAssembly.
+    ///
+    /// # Panics
+    /// Panics if the number of coordinates is not a multiple of the points' dimension.
+    ///
+    /// # Example
+    /// ```
+    /// use rstar::RTree;
+    ///
+    /// let tree = RTree::<[f64; 2]>::bulk_load_flat(&[0.0, 0.0, 1.0, 2.0, 3.0, 1.0]);
+    /// assert_eq!(tree.size(), 3);
+    /// assert!(tree.contains(&[1.0, 2.0]));
+    /// ```
+    pub fn bulk_load_flat(coordinates: &[P::Scalar]) -> Self {
+        Self::bulk_load_flat_with_params(coordinates)
+    }
+}
+
+impl<P, Params> RTree<P, Params>
+where
+    P: Point,
+    Params: RTreeParams,
+{
+    /// Creates a new r-tree with custom parameters from flat coordinates.
+    ///
+    /// See [RTree::bulk_load_flat].
+    pub fn bulk_load_flat_with_params(coordinates: &[P::Scalar]) -> Self {
+        Self::bulk_load_with_params(points_from_flat(coordinates).collect())
+    }
+
+    /// Inserts points given as flat coordinates, see [RTree::bulk_load_flat].
+    ///
+    /// # Panics
+    /// Panics if the number of coordinates is not a multiple of the points' dimension.
+    pub fn insert_flat(&mut self, coordinates: &[P::Scalar]) {
+        for point in points_from_flat(coordinates) {
+            self.insert(point);
+        }
+    }
+
+    /// Returns the coordinates of all points in the tree as a flat vector.
+    ///
+    /// The points are returned in tree order, see [RTree::bulk_load_flat] for the layout.
+    pub fn to_flat(&self) -> Vec<P::Scalar> {
+        flatten(self.iter(), self.size())
+    }
+
+    /// Returns the flat coordinates of all points contained in an envelope.
+    ///
+    /// # Example
+    /// ```
+    /// use rstar::{RTree, AABB};
+    ///
+    /// let tree = RTree::<[f64; 2]>::bulk_load_flat(&[0.0, 0.0, 1.0, 2.0, 3.0, 1.0]);
+    /// let envelope = AABB::from_corners([0.5, 0.5], [3.5, 1.5]);
+    /// assert_eq!(tree.locate_in_envelope_flat(&envelope), vec![3.0, 1.0]);
+    /// ```
+    pub fn locate_in_envelope_flat(&self, envelope: &AABB<P>) -> Vec<P::Scalar> {
+        flatten(self.locate_in_envelope(envelope), 0)
+    }
+
+    /// Returns the flat coordinates of the `k` points nearest to `query_point`, nearest first.
+    pub fn nearest_neighbors_flat(&self, query_point: &P, k: usize) -> Vec<P::Scalar> {
+        flatten(self.nearest_neighbor_iter(query_point).take(k), k)
+    }
+}
+
+/// An r-tree of points given as flat coordinates, returning query results as indices.
+///
+/// The coordinates are kept in their original layout, see [RTree::bulk_load_flat], and
+/// every point is identified by its index within them. This suits bindings to array based
+/// languages: e.g. a wrapper around a numpy array of shape `(n, dimensions)` can pass the
+/// array's buffer in, expose [FlatPointRTree::positions_as_slice] as array again and use
+/// the returned index vectors for fancy indexing, without converting any point
+/// individually.
+///
+/// # Example
+/// ```
+/// use rstar::{FlatPointRTree, AABB};
+///
+/// let coordinates = [0.0, 0.0, 1.0, 2.0, 3.0, 1.0];
+/// let tree = FlatPointRTree::<[f64; 2]>::bulk_load_from_flat(&coordinates, 2);
+/// assert_eq!(tree.positions_as_slice(), &coordinates);
+///
+/// let envelope = AABB::from_corners([0.5, 0.5], [3.5, 2.5]);
+/// let mut indices = tree.locate_in_envelope_indices(&envelope);
+/// indices.sort();
+/// assert_eq!(indices, vec![1, 2]);
+/// assert_eq!(tree.nearest_neighbor_indices(&[0.1, 0.1, 2.9, 1.2]), vec![0, 2]);
+/// ```
+pub struct FlatPointRTree<P, Params = DefaultParams>
+where
+    P: Point,
+    Params: RTreeParams,
+{
+    coordinates: Vec<P::Scalar>,
+    tree: RTree<GeomWithData<P, usize>, Params>,
+}
+
+impl<P> FlatPointRTree<P>
+where
+    P: Point,
+{
+    /// Creates a new tree from flat coordinates of points with `dimensions` coordinates each.
+    ///
+    /// The dimension is passed explicitly as it is usually only known at runtime, e.g. as
+    /// the second axis of an array.
+    ///
+    /// # Panics
+    /// Panics if `dimensions` differs from the points' dimension or if the number of
+    /// coordinates is not a multiple of it.
+    pub fn bulk_load_from_flat(coordinates: &[P::Scalar], dimensions: usize) -> Self {
+        Self::bulk_load_from_flat_with_params(coordinates, dimensions)
+    }
+}
+
+impl<P, Params> FlatPointRTree<P, Params>
+where
+    P: Point,
+    Params: RTreeParams,
+{
+    /// Creates a new tree with custom parameters from flat coordinates.
+    ///
+    /// See [FlatPointRTree::bulk_load_from_flat].
+    pub fn bulk_load_from_flat_with_params(coordinates: &[P::Scalar], dimensions: usize) -> Self {
+        assert_eq!(
+            dimensions,
+            P::DIMENSIONS,
+            "The dimension must match the points' dimension"
+        );
+        let points = points_from_flat(coordinates)
+            .enumerate()
+            .map(|(index, point)| GeomWithData::new(point, index))
+            .collect();
+        FlatPointRTree {
+            coordinates: coordinates.to_vec(),
+            tree: RTree::bulk_load_with_params(points),
+        }
+    }
+
+    /// Returns the coordinates of all points in their original order.
+    pub fn positions_as_slice(&self) -> &[P::Scalar] {
+        &self.coordinates
+    }
+
+    /// Returns the number of points in the tree.
+    pub fn size(&self) -> usize {
+        self.tree.size()
+    }
+
+    /// Returns the underlying tree, whose elements store their index as data.
+    pub fn tree(&self) -> &RTree<GeomWithData<P, usize>, Params> {
+        &self.tree
+    }
+
+    /// Returns the indices of all points contained in an envelope, in arbitrary order.
+    pub fn locate_in_envelope_indices(&self, envelope: &AABB<P>) -> Vec<usize> {
+        indices(self.tree.locate_in_envelope(envelope))
+    }
+
+    /// Returns the indices of all points within a distance of `query_point`, in arbitrary
+    /// order.
+    pub fn locate_within_distance_indices(
+        &self,
+        query_point: P,
+        max_squared_radius: P::Scalar,
+    ) -> Vec<usize> {
+        indices(
+            self.tree
+                .locate_within_distance(query_point, max_squared_radius),
+        )
+    }
+
+    /// Returns the indices of the `k` points nearest to `query_point`, nearest first.
+    pub fn nearest_neighbors_indices(&self, query_point: &P, k: usize) -> Vec<usize> {
+        indices(self.tree.nearest_neighbor_iter(query_point).take(k))
+    }
+
+    /// Returns the index of the nearest point for every query point given as flat
+    /// coordinates.
+    ///
+    /// Returns an empty vector if the tree is empty.
+    ///
+    /// # Panics
+    /// Panics if the number of coordinates is not a multiple of the points' dimension.
+    pub fn nearest_neighbor_indices(&self, query_coordinates: &[P::Scalar]) -> Vec<usize> {
+        if self.size() == 0 {
+            return Vec::new();
+        }
+        points_from_flat(query_coordinates)
+            .filter_map(|query_point: P| self.tree.nearest_neighbor(&query_point))
+            .map(|point| point.data)
+            .collect()
+    }
+}
+
+fn indices<'a, P>(points: impl Iterator<Item = &'a GeomWithData<P, usize>>) -> Vec<usize>
+where
+    P: Point + 'a,
+{
+    points.map(|point| point.data).collect()
+}
+
+fn points_from_flat<P>(coordinates: &[P::Scalar]) -> impl Iterator<Item = P> + '_
+where
+    P: Point,
+{
+    assert_eq!(
+        coordinates.len() % P::DIMENSIONS,
+        0,
+        "The number of coordinates must be a multiple of the dimension"
+    );
+    coordinates
+        .chunks_exact(P::DIMENSIONS)
+        .map(|point| P::generate(|axis| point[axis]))
+}
+
+fn flatten<'a, P>(points: impl Iterator<Item = &'a P>, capacity: usize) -> Vec<P::Scalar>
+where
+    P: Point + 'a,
+{
+    let mut result = Vec::with_capacity(capacity * P::DIMENSIONS);
+    for point in points {
+        result.extend((0..P::DIMENSIONS).map(|axis| point.nth(axis)));
+    }
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use super::FlatPointRTree;
+    use crate::point::PointExt;
+    use crate::test_utilities::{create_random_points, SEED_1, SEED_2};
+    use crate::{Envelope, RTree, AABB};
+
+    #[test]
+    fn test_flat_coordinates() {
+        let points = create_random_points(500, SEED_1);
+        let coordinates: Vec<f64> = points.iter().flat_map(|point| point.to_vec()).collect();
+        let tree = RTree::<[f64; 2]>::bulk_load_flat(&coordinates);
+        let mut inserted = RTree::<[f64; 2]>::new();
+        inserted.insert_flat(&coordinates);
+        assert!(tree.structural_eq(&inserted));
+
+        let mut flat = tree.to_flat();
+        assert_eq!(flat.len(), coordinates.len());
+        flat.sort_by(|l, r| l.partial_cmp(r).unwrap());
+        let mut expected = coordinates.clone();
+        expected.sort_by(|l, r| l.partial_cmp(r).unwrap());
+        assert_eq!(flat, expected);
+
+        let envelope = AABB::from_corners([0.2, 0.2], [0.6, 0.6]);
+        let located: Vec<f64> = tree
+            .locate_in_envelope(&envelope)
+            .flat_map(|point| point.to_vec())
+            .collect();
+        assert_eq!(tree.locate_in_envelope_flat(&envelope), located);
+
+        for query_point in create_random_points(10, SEED_2) {
+            let nearest: Vec<f64> = tree
+                .nearest_neighbor_iter(&query_point)
+                .take(3)
+                .flat_map(|point| point.to_vec())
+                .collect();
+            assert_eq!(tree.nearest_neighbors_flat(&query_point, 3), nearest);
+        }
+    }
+
+    #[test]
+    fn test_flat_point_tree() {
+        let points = create_random_points(500, SEED_1);
+        let coordinates: Vec<f64> = points.iter().flat_map(|point| point.to_vec()).collect();
+        let tree = FlatPointRTree::<[f64; 2]>::bulk_load_from_flat(&coordinates, 2);
+        assert_eq!(tree.size(), 500);
+        assert_eq!(tree.positions_as_slice(), &coordinates[..]);
+
+        let envelope = AABB::from_corners([0.2, 0.2], [0.6, 0.6]);
+        let mut located = tree.locate_in_envelope_indices(&envelope);
+        located.sort_unstable();
+        let expected: Vec<_> = (0..500)
+            .filter(|index| envelope.contains_point(&points[*index]))
+            .collect();
+        assert_eq!(located, expected);
+
+        let query_points = create_random_points(10, SEED_2);
+        let query_coordinates: Vec<f64> = query_points
+            .iter()
+            .flat_map(|point| point.to_vec())
+            .collect();
+        let nearest = tree.nearest_neighbor_indices(&query_coordinates);
+        for (query_point, index) in query_points.iter().zip(nearest) {
+            let expected = tree.nearest_neighbors_indices(query_point, 3);
+            assert_eq!(expected[0], index);
+            assert_eq!(expected.len(), 3);
+            let within = tree.locate_within_distance_indices(
+                *query_point,
+                points[expected[2]].distance_2(query_point),
+            );
+            assert!(within.len() >= 3);
+            assert!(expected.iter().all(|index| within.contains(index)));
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_flat_point_tree_dimension_mismatch() {
+        FlatPointRTree::<[f64; 2]>::bulk_load_from_flat(&[0.0, 1.0, 2.0], 3);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_flat_coordinates_incomplete_point() {
+        RTree::<[f64; 3]>::bulk_load_flat(&[0.0, 1.0, 2.0, 3.0]);
+    }
+}
@@ -0,0 +1,267 @@
+use crate::node::{ParentNode, RTreeNode};
+use crate::params::RTreeParams;
+use crate::{RTree, RTreeObject};
+
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::fmt;
+
+/// An r-tree flattened into contiguous arrays, see [RTree::flatten].
+///
+/// The layout resembles a bounding volume hierarchy as used for ray tracing on GPUs: inner
+/// nodes are stored in breadth first order with the root at index `0`, and the children of
+/// every node are stored next to each other, referenced by an index range. All arrays can be
+/// uploaded as buffers once the envelopes have been converted into the GPU's format, a
+/// shader then traverses the tree with a small stack of node indices.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FlattenedRTree<E> {
+    /// The envelope of every node.
+    pub node_envelopes: Vec<E>,
+    /// The children of every node as `[start, end)` range, which refers to `node_envelopes`
+    /// for inner nodes and to `leaf_envelopes` and `leaf_payloads` for leaf nodes.
+    pub node_children: Vec<[u32; 2]>,
+    /// The index of the first leaf node, whose children are elements. All elements of an
+    /// r-tree have the same depth, the leaf nodes are thus stored after all other nodes.
+    pub first_leaf_node: u32,
+    /// The envelope of every element.
+    pub leaf_envelopes: Vec<E>,
+    /// The index of every element within the elements returned by [RTree::iter].
+    pub leaf_payloads: Vec<u32>,
+}
+
+impl<T, Params> RTree<T, Params>
+where
+    T: RTreeObject,
+    Params: RTreeParams,
+{
+    /// Flattens the tree into contiguous arrays of envelopes and index ranges.
+    ///
+    /// The elements themselves are referenced by their index within [RTree::iter], e.g. to
+    /// upload them as separate buffer. The tree can be restored with
+    /// [RTree::from_flattened].
+    ///
+    /// # Panics
+    /// Panics if the tree has more than `u32::MAX` elements.
+    ///
+    /// # Example
+    /// ```
+    /// use rstar::{Envelope, RTree, AABB};
+    ///
+    /// let tree = RTree::bulk_load((0..100).map(|i| [i as f64, 0.0]).collect());
+    /// let flattened = tree.flatten();
+    ///
+    /// // Traverse the arrays like a shader would, counting the elements within an envelope.
+    /// let query = AABB::from_corners([9.5, -1.0], [19.5, 1.0]);
+    /// let mut count = 0;
+    /// let mut stack = vec![0];
+    /// while let Some(node) = stack.pop() {
+    ///     let [start, end] = flattened.node_children[node as usize];
+    ///     for child in start..end {
+    ///         if node < flattened.first_leaf_node {
+    ///             if flattened.node_envelopes[child as usize].intersects(&query) {
+    ///                 stack.push(child);
+    ///             }
+    ///         } else if query.contains_envelope(&flattened.leaf_envelopes[child as usize]) {
+    ///             count += 1;
+    ///         }
+    ///     }
+    /// }
+    /// assert_eq!(count, 10);
+    ///
+    /// let elements = tree.iter().cloned().collect();
+    /// let restored = RTree::<[f64; 2]>::from_flattened(&flattened, elements).unwrap();
+    /// assert!(restored.structural_eq(&tree));
+    /// ```
+    pub fn flatten(&self) -> FlattenedRTree<T::Envelope> {
+        let payloads: BTreeMap<*const T, u32> = self
+            .iter()
+            .enumerate()
+            .map(|(index, t)| (t as *const T, to_u32(index)))
+            .collect();
+        let root = self.root();
+        let mut result = FlattenedRTree {
+            node_envelopes: Vec::new(),
+            node_children: Vec::new(),
+            first_leaf_node: 0,
+            leaf_envelopes: Vec::with_capacity(self.size()),
+            leaf_payloads: Vec::with_capacity(self.size()),
+        };
+        result.node_envelopes.push(root.envelope.clone());
+        let mut first_leaf_node = None;
+        let mut queue = VecDeque::new();
+        queue.push_back(root);
+        while let Some(node) = queue.pop_front() {
+            let has_leaf_children = node.children.first().map_or(true, RTreeNode::is_leaf);
+            let start = if has_leaf_children {
+                first_leaf_node.get_or_insert(result.node_children.len());
+                result.leaf_envelopes.len()
+            } else {
+                result.node_envelopes.len()
+            };
+            for child in &node.children {
+                match child {
+                    RTreeNode::Leaf(ref t) => {
+                        result.leaf_envelopes.push(t.envelope());
+                        result.leaf_payloads.push(payloads[&(t as *const T)]);
+                    }
+                    RTreeNode::Parent(ref data) => {
+                        result.node_envelopes.push(data.envelope.clone());
+                        queue.push_back(data);
+                    }
+                }
+            }
+            let end = start + node.children.len();
+            result.node_children.push([to_u32(start), to_u32(end)]);
+        }
+        result.first_leaf_node = to_u32(first_leaf_node.unwrap_or(0));
+        result
+    }
+
+    /// Restores a tree flattened by [RTree::flatten].
+    ///
+    /// `elements` must be ordered like the elements referenced by
+    /// [FlattenedRTree::leaf_payloads]. The node envelopes are recomputed from the elements
+    /// rather than copied.
+    ///
+    /// Returns an error if the arrays don't describe a balanced tree referencing every node
+    /// and element exactly once.
+    pub fn from_flattened(
+        flattened: &FlattenedRTree<T::Envelope>,
+        elements: Vec<T>,
+    ) -> Result<Self, FlattenedRTreeError> {
+        let first_leaf_node = flattened.first_leaf_node as usize;
+        let mut elements: Vec<_> = elements.into_iter().map(Some).collect();
+        // Nodes are built bottom up, together with their height.
+        let mut nodes: Vec<Option<(ParentNode<T>, usize)>> = Vec::new();
+        nodes.resize_with(flattened.node_children.len(), || None);
+        for (index, &[start, end]) in flattened.node_children.iter().enumerate().rev() {
+            let range = start as usize..end as usize;
+            if range.is_empty() && index != 0 {
+                return Err(FlattenedRTreeError::InvalidNode(index));
+            }
+            let mut children = Vec::with_capacity(range.len());
+            let mut height = 1;
+            if index >= first_leaf_node {
+                let payloads = flattened
+                    .leaf_payloads
+                    .get(range)
+                    .ok_or(FlattenedRTreeError::InvalidNode(index))?;
+                for &payload in payloads {
+                    let t = elements
+                        .get_mut(payload as usize)
+                        .and_then(Option::take)
+                        .ok_or(FlattenedRTreeError::InvalidPayload(payload))?;
+                    children.push(RTreeNode::Leaf(t));
+                }
+            } else {
+                let mut child_height = None;
+                for child in range {
+                    let (node, node_height) = nodes
+                        .get_mut(child)
+                        .and_then(Option::take)
+                        .ok_or(FlattenedRTreeError::InvalidNode(index))?;
+                    if *child_height.get_or_insert(node_height) != node_height {
+                        return Err(FlattenedRTreeError::InvalidNode(index));
+                    }
+                    children.push(RTreeNode::Parent(node));
+                }
+                height += child_height.unwrap_or(0);
+            }
+            nodes[index] = Some((ParentNode::new_parent(children.into()), height));
+        }
+        let root = match nodes.first_mut().and_then(Option::take) {
+            Some((root, _)) => root,
+            None => return Err(FlattenedRTreeError::InvalidNode(0)),
+        };
+        if nodes.iter().any(Option::is_some) || elements.iter().any(Option::is_some) {
+            return Err(FlattenedRTreeError::Unreferenced);
+        }
+        Ok(RTree::from_root(root))
+    }
+}
+
+/// Error returned by [RTree::from_flattened].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FlattenedRTreeError {
+    /// A node's children are out of bounds, already referenced by another node, or have
+    /// different heights.
+    InvalidNode(usize),
+    /// A payload is out of bounds or referenced more than once.
+    InvalidPayload(u32),
+    /// Some nodes or elements are not referenced by any node.
+    Unreferenced,
+}
+
+impl fmt::Display for FlattenedRTreeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FlattenedRTreeError::InvalidNode(index) => write!(f, "invalid node {}", index),
+            FlattenedRTreeError::InvalidPayload(payload) => {
+                write!(f, "invalid payload {}", payload)
+            }
+            FlattenedRTreeError::Unreferenced => {
+                write!(f, "some nodes or elements are not referenced")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for FlattenedRTreeError {}
+
+fn to_u32(index: usize) -> u32 {
+    u32::try_from(index).expect("Too many elements to flatten")
+}
+
+#[cfg(test)]
+mod test {
+    use super::FlattenedRTreeError;
+    use crate::test_utilities::{create_random_rectangles, SEED_1};
+    use crate::{RTree, RTreeObject};
+
+    #[test]
+    fn test_flatten() {
+        let rectangles = create_random_rectangles(1000, SEED_1);
+        let tree = RTree::bulk_load(rectangles);
+        let flattened = tree.flatten();
+        let elements: Vec<_> = tree.iter().cloned().collect();
+
+        assert_eq!(flattened.node_envelopes[0], tree.root().envelope());
+        assert_eq!(
+            flattened.node_envelopes.len(),
+            flattened.node_children.len()
+        );
+        let mut payloads = flattened.leaf_payloads.clone();
+        payloads.sort_unstable();
+        assert!(payloads.iter().enumerate().all(|(i, p)| i == *p as usize));
+        for (envelope, payload) in flattened
+            .leaf_envelopes
+            .iter()
+            .zip(&flattened.leaf_payloads)
+        {
+            assert_eq!(*envelope, elements[*payload as usize].envelope());
+        }
+
+        let restored: RTree<_> = RTree::from_flattened(&flattened, elements.clone()).unwrap();
+        assert!(restored.structural_eq(&tree));
+
+        let mut invalid = flattened.clone();
+        invalid.leaf_payloads[1] = invalid.leaf_payloads[0];
+        let error = RTree::<_>::from_flattened(&invalid, elements.clone()).unwrap_err();
+        assert_eq!(
+            error,
+            FlattenedRTreeError::InvalidPayload(invalid.leaf_payloads[0])
+        );
+        let mut invalid = flattened.clone();
+        invalid.node_children[0][1] -= 1;
+        let error = RTree::<_>::from_flattened(&invalid, elements).unwrap_err();
+        assert_eq!(error, FlattenedRTreeError::Unreferenced);
+
+        let empty = RTree::<[f64; 2]>::new();
+        let flattened = empty.flatten();
+        assert_eq!(flattened.node_children, vec![[0, 0]]);
+        let restored = RTree::<[f64; 2]>::from_flattened(&flattened, Vec::new()).unwrap();
+        assert_eq!(restored.size(), 0);
+    }
+}
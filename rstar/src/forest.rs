@@ -0,0 +1,325 @@
+use crate::algorithm::nearest_neighbor::RTreeNodeDistanceWrapper;
+use crate::node::RTreeNode;
+use crate::params::{DefaultParams, RTreeParams};
+use crate::point::Point;
+use crate::{Envelope, PointDistance, RTree, RTreeObject};
+
+use alloc::collections::{BTreeMap, BinaryHeap};
+use core::cmp::Ordering;
+
+/// A collection of r-trees, one for every category like a layer, team or collision group.
+///
+/// Every element is stored in the tree of its category. Queries take a filter that selects
+/// the categories to search. Nearest neighbor queries traverse all selected trees at once:
+/// subtrees of one category are pruned by elements found in another one, which is
+/// considerably faster than querying every tree on its own and merging the results.
+///
+/// # Example
+/// ```
+/// use rstar::SpatialForest;
+///
+/// #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+/// enum Layer {
+///     Roads,
+///     Rivers,
+///     Buildings,
+/// }
+///
+/// let mut forest = SpatialForest::new();
+/// forest.insert(Layer::Roads, [0.0, 3.0]);
+/// forest.insert(Layer::Rivers, [2.0, 0.0]);
+/// forest.insert(Layer::Buildings, [0.5, 0.5]);
+///
+/// let not_buildings = |layer: &Layer| *layer != Layer::Buildings;
+/// assert_eq!(
+///     forest.nearest_neighbor(not_buildings, &[0.0, 0.0]),
+///     Some((&Layer::Rivers, &[2.0, 0.0]))
+/// );
+/// ```
+pub struct SpatialForest<K, T, Params = DefaultParams>
+where
+    K: Ord,
+    T: RTreeObject,
+    Params: RTreeParams,
+{
+    trees: BTreeMap<K, RTree<T, Params>>,
+}
+
+impl<K, T> SpatialForest<K, T>
+where
+    K: Ord,
+    T: RTreeObject,
+{
+    /// Creates a new, empty forest.
+    pub fn new() -> Self {
+        Self::new_with_params()
+    }
+}
+
+impl<K, T> Default for SpatialForest<K, T>
+where
+    K: Ord,
+    T: RTreeObject,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, T, Params> SpatialForest<K, T, Params>
+where
+    K: Ord,
+    T: RTreeObject,
+    Params: RTreeParams,
+{
+    /// Creates a new, empty forest with custom parameters for all of its trees.
+    pub fn new_with_params() -> Self {
+        SpatialForest {
+            trees: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the total number of elements in all categories.
+    pub fn size(&self) -> usize {
+        self.trees.values().map(RTree::size).sum()
+    }
+
+    /// Returns the tree of a category, if it contains any elements.
+    pub fn tree(&self, key: &K) -> Option<&RTree<T, Params>> {
+        self.trees.get(key)
+    }
+
+    /// Returns all categories and their trees, ordered by category.
+    pub fn trees(&self) -> impl Iterator<Item = (&K, &RTree<T, Params>)> + '_ {
+        self.trees.iter()
+    }
+
+    /// Inserts an element into the tree of a category.
+    pub fn insert(&mut self, key: K, t: T) {
+        self.trees
+            .entry(key)
+            .or_insert_with(RTree::new_with_params)
+            .insert(t);
+    }
+
+    /// Replaces the tree of a category and returns the previous one.
+    ///
+    /// This allows to bulk load the elements of a category.
+    pub fn insert_tree(&mut self, key: K, tree: RTree<T, Params>) -> Option<RTree<T, Params>> {
+        self.trees.insert(key, tree)
+    }
+
+    /// Removes all elements of a category and returns its tree.
+    pub fn remove_tree(&mut self, key: &K) -> Option<RTree<T, Params>> {
+        self.trees.remove(key)
+    }
+
+    /// Returns all elements of the selected categories whose envelope intersects a given
+    /// envelope.
+    pub fn locate_in_envelope_intersecting<'a, F>(
+        &'a self,
+        filter: F,
+        envelope: &'a T::Envelope,
+    ) -> impl Iterator<Item = (&'a K, &'a T)> + 'a
+    where
+        F: Fn(&K) -> bool + 'a,
+    {
+        self.trees
+            .iter()
+            .filter(move |(key, _)| filter(key))
+            .flat_map(move |(key, tree)| {
+                tree.locate_in_envelope_intersecting(envelope)
+                    .map(move |t| (key, t))
+            })
+    }
+}
+
+impl<K, T, Params> SpatialForest<K, T, Params>
+where
+    K: Ord,
+    T: PartialEq + RTreeObject,
+    Params: RTreeParams,
+{
+    /// Removes an element from the tree of a category and returns it.
+    ///
+    /// Trees of categories without elements are removed.
+    pub fn remove(&mut self, key: &K, t: &T) -> Option<T> {
+        let tree = self.trees.get_mut(key)?;
+        let removed = tree.remove(t);
+        if tree.size() == 0 {
+            self.trees.remove(key);
+        }
+        removed
+    }
+}
+
+impl<K, T, Params> SpatialForest<K, T, Params>
+where
+    K: Ord,
+    T: PointDistance,
+    Params: RTreeParams,
+{
+    /// Returns the element nearest to a point among all selected categories.
+    pub fn nearest_neighbor<F>(
+        &self,
+        filter: F,
+        query_point: &<T::Envelope as Envelope>::Point,
+    ) -> Option<(&K, &T)>
+    where
+        F: Fn(&K) -> bool,
+    {
+        self.nearest_neighbor_iter_with_distance_2(filter, query_point)
+            .next()
+            .map(|(key, t, _)| (key, t))
+    }
+
+    /// Returns `(category, element, distance^2)` tuples of all selected categories, sorted by
+    /// their distance to a given point.
+    pub fn nearest_neighbor_iter_with_distance_2<F>(
+        &self,
+        filter: F,
+        query_point: &<T::Envelope as Envelope>::Point,
+    ) -> ForestNearestNeighborIterator<'_, K, T>
+    where
+        F: Fn(&K) -> bool,
+    {
+        let mut nodes = BinaryHeap::new();
+        for (key, tree) in self.trees.iter().filter(|(key, _)| filter(key)) {
+            nodes.extend(tree.root().children.iter().map(|child| ForestNode {
+                key,
+                node: RTreeNodeDistanceWrapper::new(child, query_point),
+            }));
+        }
+        ForestNearestNeighborIterator {
+            nodes,
+            query_point: query_point.clone(),
+        }
+    }
+}
+
+struct ForestNode<'a, K, T>
+where
+    T: PointDistance + 'a,
+{
+    key: &'a K,
+    node: RTreeNodeDistanceWrapper<'a, T>,
+}
+
+impl<'a, K, T> PartialEq for ForestNode<'a, K, T>
+where
+    T: PointDistance,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.node == other.node
+    }
+}
+
+impl<'a, K, T> Eq for ForestNode<'a, K, T> where T: PointDistance {}
+
+impl<'a, K, T> PartialOrd for ForestNode<'a, K, T>
+where
+    T: PointDistance,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<'a, K, T> Ord for ForestNode<'a, K, T>
+where
+    T: PointDistance,
+{
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.node.cmp(&other.node)
+    }
+}
+
+/// Iterator returned by [SpatialForest::nearest_neighbor_iter_with_distance_2].
+pub struct ForestNearestNeighborIterator<'a, K, T>
+where
+    T: PointDistance + 'a,
+{
+    nodes: BinaryHeap<ForestNode<'a, K, T>>,
+    query_point: <T::Envelope as Envelope>::Point,
+}
+
+impl<'a, K, T> Iterator for ForestNearestNeighborIterator<'a, K, T>
+where
+    T: PointDistance,
+{
+    type Item = (
+        &'a K,
+        &'a T,
+        <<T::Envelope as Envelope>::Point as Point>::Scalar,
+    );
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(ForestNode { key, node }) = self.nodes.pop() {
+            match node.node {
+                RTreeNode::Parent(ref data) => {
+                    let query_point = &self.query_point;
+                    self.nodes
+                        .extend(data.children.iter().map(|child| ForestNode {
+                            key,
+                            node: RTreeNodeDistanceWrapper::new(child, query_point),
+                        }));
+                }
+                RTreeNode::Leaf(ref t) => return Some((key, t, node.distance)),
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::SpatialForest;
+    use crate::test_utilities::{create_random_points, SEED_1, SEED_2};
+    use crate::{PointDistance, RTree, AABB};
+
+    #[test]
+    fn test_forest_queries() {
+        let points = create_random_points(900, SEED_1);
+        let mut forest = SpatialForest::new();
+        for (index, point) in points.iter().enumerate() {
+            forest.insert(index % 3, *point);
+        }
+        assert_eq!(forest.size(), 900);
+        assert_eq!(forest.trees().count(), 3);
+
+        let selected = |key: &usize| *key != 1;
+        let expected: Vec<_> = points
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| selected(&(index % 3)))
+            .map(|(_, point)| *point)
+            .collect();
+        let tree = RTree::bulk_load(expected.clone());
+        for query in create_random_points(20, SEED_2) {
+            let (key, nearest) = forest.nearest_neighbor(selected, &query).unwrap();
+            assert_ne!(*key, 1);
+            assert_eq!(nearest, tree.nearest_neighbor(&query).unwrap());
+            let distances: Vec<_> = forest
+                .nearest_neighbor_iter_with_distance_2(selected, &query)
+                .map(|(_, point, distance_2)| {
+                    assert_eq!(point.distance_2(&query), distance_2);
+                    distance_2
+                })
+                .collect();
+            assert_eq!(distances.len(), 600);
+            assert!(distances.windows(2).all(|pair| pair[0] <= pair[1]));
+
+            let envelope = AABB::from_corners(query, [query[0] + 0.2, query[1] + 0.2]);
+            assert_eq!(
+                forest
+                    .locate_in_envelope_intersecting(selected, &envelope)
+                    .count(),
+                tree.locate_in_envelope_intersecting(&envelope).count()
+            );
+        }
+
+        assert_eq!(forest.remove(&1, &points[1]), Some(points[1]));
+        assert_eq!(forest.remove(&1, &points[1]), None);
+        assert_eq!(forest.size(), 899);
+    }
+}
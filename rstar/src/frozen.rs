@@ -0,0 +1,881 @@
+use crate::algorithm::selection_functions::*;
+use crate::node::{ParentNode, RTreeNode};
+use crate::params::{DefaultParams, RTreeParams};
+use crate::point::Point;
+use crate::{Envelope, PointDistance, RTree, RTreeObject};
+
+use alloc::collections::BinaryHeap;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+use smallvec::SmallVec;
+
+/// A persistent r-tree whose nodes are shared between snapshots.
+///
+/// Cloning a `FrozenRTree` is cheap: both trees share all of their nodes. Updating a tree
+/// copies only the nodes on the path from the root to the modified leaf, every other node
+/// remains shared with older snapshots. This allows readers to keep working on a snapshot
+/// while a writer prepares the next version, e.g. on another thread.
+///
+/// [FrozenRTree::insert] and [FrozenRTree::remove] return a new tree and leave the original
+/// tree untouched. [FrozenRTree::insert_mut] and [FrozenRTree::remove_mut] modify a tree in
+/// place, copying only those nodes that are still shared with other snapshots. Elements need
+/// to implement `Clone` for updates, as the elements of a copied leaf node are cloned.
+///
+/// Updates use the r-star split heuristic but never reinsert nodes.
+///
+/// # Example
+/// ```
+/// use rstar::{FrozenRTree, RTree};
+///
+/// let first = FrozenRTree::from(RTree::bulk_load(vec![[0.0, 0.0], [1.0, 1.0]]));
+/// let second = first.insert([2.0, 2.0]);
+///
+/// assert_eq!(first.size(), 2);
+/// assert_eq!(second.size(), 3);
+/// assert_eq!(first.nearest_neighbor(&[2.1, 2.0]), Some(&[1.0, 1.0]));
+/// assert_eq!(second.nearest_neighbor(&[2.1, 2.0]), Some(&[2.0, 2.0]));
+///
+/// let (third, removed) = second.remove(&[0.0, 0.0]).unwrap();
+/// assert_eq!(removed, [0.0, 0.0]);
+/// assert_eq!(third.size(), 2);
+/// ```
+pub struct FrozenRTree<T, Params = DefaultParams>
+where
+    T: RTreeObject,
+    Params: RTreeParams,
+{
+    root: Arc<FrozenParent<T>>,
+    _params: PhantomData<Params>,
+}
+
+#[derive(Clone)]
+struct FrozenParent<T>
+where
+    T: RTreeObject,
+{
+    children: Vec<FrozenNode<T>>,
+    envelope: T::Envelope,
+    size: usize,
+}
+
+enum FrozenNode<T>
+where
+    T: RTreeObject,
+{
+    Leaf(T),
+    Parent(Arc<FrozenParent<T>>),
+}
+
+impl<T> Clone for FrozenNode<T>
+where
+    T: RTreeObject + Clone,
+{
+    fn clone(&self) -> Self {
+        match self {
+            FrozenNode::Leaf(t) => FrozenNode::Leaf(t.clone()),
+            FrozenNode::Parent(data) => FrozenNode::Parent(data.clone()),
+        }
+    }
+}
+
+impl<T> RTreeObject for FrozenNode<T>
+where
+    T: RTreeObject,
+{
+    type Envelope = T::Envelope;
+
+    fn envelope(&self) -> Self::Envelope {
+        match self {
+            FrozenNode::Leaf(t) => t.envelope(),
+            FrozenNode::Parent(data) => data.envelope.clone(),
+        }
+    }
+}
+
+impl<T> FrozenNode<T>
+where
+    T: RTreeObject,
+{
+    fn size(&self) -> usize {
+        match self {
+            FrozenNode::Leaf(_) => 1,
+            FrozenNode::Parent(data) => data.size,
+        }
+    }
+}
+
+impl<T> FrozenParent<T>
+where
+    T: RTreeObject,
+{
+    fn new(children: Vec<FrozenNode<T>>) -> Self {
+        let mut result = FrozenParent {
+            children,
+            envelope: T::Envelope::new_empty(),
+            size: 0,
+        };
+        result.update();
+        result
+    }
+
+    fn update(&mut self) {
+        self.envelope = T::Envelope::new_empty();
+        for child in &self.children {
+            self.envelope.merge(&child.envelope());
+        }
+        self.size = self.children.iter().map(FrozenNode::size).sum();
+    }
+
+    fn has_leaf_children(&self) -> bool {
+        !matches!(self.children.first(), Some(FrozenNode::Parent(_)))
+    }
+}
+
+fn freeze<T>(node: ParentNode<T>) -> FrozenParent<T>
+where
+    T: RTreeObject,
+{
+    let ParentNode {
+        children,
+        envelope,
+        size,
+    } = node;
+    let children = children
+        .into_iter()
+        .map(|child| match child {
+            RTreeNode::Leaf(t) => FrozenNode::Leaf(t),
+            RTreeNode::Parent(data) => FrozenNode::Parent(Arc::new(freeze(data))),
+        })
+        .collect();
+    FrozenParent {
+        children,
+        envelope,
+        size,
+    }
+}
+
+impl<T, Params> From<RTree<T, Params>> for FrozenRTree<T, Params>
+where
+    T: RTreeObject,
+    Params: RTreeParams,
+{
+    fn from(tree: RTree<T, Params>) -> Self {
+        FrozenRTree {
+            root: Arc::new(freeze(tree.into_root())),
+            _params: PhantomData,
+        }
+    }
+}
+
+fn thaw<T>(node: Arc<FrozenParent<T>>) -> ParentNode<T>
+where
+    T: RTreeObject + Clone,
+{
+    // Moves the node out if it isn't shared. Otherwise, only this node is copied, its
+    // children are then shared and copied recursively.
+    let FrozenParent {
+        children,
+        envelope,
+        size,
+    } = Arc::try_unwrap(node).unwrap_or_else(|shared| (*shared).clone());
+    let children = children
+        .into_iter()
+        .map(|child| match child {
+            FrozenNode::Leaf(t) => RTreeNode::Leaf(t),
+            FrozenNode::Parent(data) => RTreeNode::Parent(thaw(data)),
+        })
+        .collect();
+    ParentNode {
+        children,
+        envelope,
+        size,
+    }
+}
+
+impl<T, Params> From<FrozenRTree<T, Params>> for RTree<T, Params>
+where
+    T: RTreeObject + Clone,
+    Params: RTreeParams,
+{
+    /// Converts a frozen tree back into a mutable tree.
+    ///
+    /// Nodes that are not shared with other snapshots are moved, all other nodes are copied.
+    fn from(tree: FrozenRTree<T, Params>) -> Self {
+        RTree::from_root(thaw(tree.root))
+    }
+}
+
+/// A read-only view of an [RTree] at a certain point in time, see [RTree::snapshot].
+///
+/// Snapshots are [FrozenRTree]s: cloning one is cheap and it can be sent to and shared
+/// between threads if `T` can.
+pub type RTreeSnapshot<T, Params = DefaultParams> = FrozenRTree<T, Params>;
+
+impl<T, Params> RTree<T, Params>
+where
+    T: RTreeObject + Clone,
+    Params: RTreeParams,
+{
+    /// Returns a snapshot of the tree's current contents.
+    ///
+    /// The snapshot is not affected by later modifications of the tree, so readers can keep
+    /// querying it while the owner continues to insert and remove elements.
+    ///
+    /// Taking a snapshot of an `RTree` copies all nodes and elements once. If snapshots are
+    /// taken frequently, e.g. once per frame, keep the tree in a [FrozenRTree] and modify it
+    /// with [FrozenRTree::insert_mut] and [FrozenRTree::remove_mut] instead. A snapshot is
+    /// then just a clone of that tree which shares all of its nodes; modifications only
+    /// copy the nodes that are still shared with a snapshot.
+    ///
+    /// # Example
+    /// ```
+    /// use rstar::RTree;
+    ///
+    /// let mut tree = RTree::bulk_load(vec![[0.0, 0.0], [1.0, 1.0]]);
+    /// let snapshot = tree.snapshot();
+    /// tree.insert([2.0, 2.0]);
+    ///
+    /// let reader = std::thread::spawn(move || snapshot.nearest_neighbor(&[2.0, 2.0]).copied());
+    /// assert_eq!(reader.join().unwrap(), Some([1.0, 1.0]));
+    /// assert_eq!(tree.nearest_neighbor(&[2.0, 2.0]), Some(&[2.0, 2.0]));
+    /// ```
+    pub fn snapshot(&self) -> RTreeSnapshot<T, Params> {
+        FrozenRTree::from(RTree::from_root(self.root().clone()))
+    }
+}
+
+impl<T, Params> Clone for FrozenRTree<T, Params>
+where
+    T: RTreeObject,
+    Params: RTreeParams,
+{
+    fn clone(&self) -> Self {
+        FrozenRTree {
+            root: self.root.clone(),
+            _params: PhantomData,
+        }
+    }
+}
+
+impl<T, Params> Default for FrozenRTree<T, Params>
+where
+    T: RTreeObject,
+    Params: RTreeParams,
+{
+    fn default() -> Self {
+        Self::new_with_params()
+    }
+}
+
+impl<T> FrozenRTree<T>
+where
+    T: RTreeObject,
+{
+    /// Creates a new, empty tree.
+    pub fn new() -> Self {
+        Self::new_with_params()
+    }
+
+    /// Creates a new tree from a set of elements.
+    ///
+    /// See [RTree::bulk_load].
+    pub fn bulk_load(elements: Vec<T>) -> Self {
+        Self::bulk_load_with_params(elements)
+    }
+}
+
+impl<T, Params> FrozenRTree<T, Params>
+where
+    T: RTreeObject,
+    Params: RTreeParams,
+{
+    /// Creates a new, empty tree with custom parameters.
+    pub fn new_with_params() -> Self {
+        RTree::new_with_params().into()
+    }
+
+    /// Creates a new tree from a set of elements with custom parameters.
+    pub fn bulk_load_with_params(elements: Vec<T>) -> Self {
+        RTree::bulk_load_with_params(elements).into()
+    }
+
+    /// Returns the number of elements in the tree.
+    pub fn size(&self) -> usize {
+        self.root.size
+    }
+
+    /// Returns the envelope of all elements.
+    pub fn envelope(&self) -> T::Envelope {
+        self.root.envelope.clone()
+    }
+
+    /// Returns `true` if both trees share the same root node, i.e. if one is an unmodified
+    /// clone of the other.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.root, &other.root)
+    }
+
+    /// Returns an iterator over all elements of the tree.
+    pub fn iter(&self) -> FrozenSelectionIterator<'_, T, SelectAllFunc> {
+        self.locate_with_selection_function(SelectAllFunc)
+    }
+
+    /// Returns all elements selected by a [SelectionFunction].
+    ///
+    /// See [RTree::locate_with_selection_function].
+    pub fn locate_with_selection_function<S: SelectionFunction<T>>(
+        &self,
+        selection_function: S,
+    ) -> FrozenSelectionIterator<'_, T, S> {
+        FrozenSelectionIterator::new(&self.root, selection_function)
+    }
+
+    /// Returns all elements contained in an envelope.
+    ///
+    /// See [RTree::locate_in_envelope].
+    pub fn locate_in_envelope(
+        &self,
+        envelope: &T::Envelope,
+    ) -> FrozenSelectionIterator<'_, T, SelectInEnvelopeFunction<T>> {
+        self.locate_with_selection_function(SelectInEnvelopeFunction::new(envelope.clone()))
+    }
+
+    /// Returns all elements whose envelope intersects a given envelope.
+    ///
+    /// See [RTree::locate_in_envelope_intersecting].
+    pub fn locate_in_envelope_intersecting(
+        &self,
+        envelope: &T::Envelope,
+    ) -> FrozenSelectionIterator<'_, T, SelectInEnvelopeFuncIntersecting<T>> {
+        self.locate_with_selection_function(SelectInEnvelopeFuncIntersecting::new(envelope.clone()))
+    }
+}
+
+impl<T, Params> FrozenRTree<T, Params>
+where
+    T: RTreeObject + PartialEq,
+    Params: RTreeParams,
+{
+    /// Returns `true` if the tree contains an element equal to `t`.
+    pub fn contains(&self, t: &T) -> bool {
+        self.locate_in_envelope(&t.envelope()).any(|e| e == t)
+    }
+
+    /// Returns the child indices leading from the root to a leaf equal to `t`. The last index
+    /// refers to the leaf itself.
+    fn find(&self, t: &T) -> Option<Vec<usize>> {
+        let envelope = t.envelope();
+        let mut stack: Vec<(&FrozenParent<T>, usize)> = Vec::new();
+        if self.root.envelope.contains_envelope(&envelope) {
+            stack.push((&self.root, 0));
+        }
+        while let Some(&mut (node, ref mut next)) = stack.last_mut() {
+            let index = *next;
+            *next += 1;
+            match node.children.get(index) {
+                None => {
+                    stack.pop();
+                }
+                Some(FrozenNode::Leaf(leaf)) => {
+                    if leaf == t {
+                        return Some(stack.iter().map(|&(_, next)| next - 1).collect());
+                    }
+                }
+                Some(FrozenNode::Parent(child)) => {
+                    if child.envelope.contains_envelope(&envelope) {
+                        stack.push((child, 0));
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+impl<T, Params> FrozenRTree<T, Params>
+where
+    T: RTreeObject + Clone,
+    Params: RTreeParams,
+{
+    /// Returns a new tree that additionally contains `t`.
+    ///
+    /// This tree remains unchanged and shares all nodes not affected by the insertion with the
+    /// new tree.
+    #[must_use]
+    pub fn insert(&self, t: T) -> Self {
+        let mut result = self.clone();
+        result.insert_mut(t);
+        result
+    }
+
+    /// Inserts an element in place.
+    ///
+    /// Nodes on the insertion path that are shared with other snapshots are copied first,
+    /// other snapshots are not affected.
+    pub fn insert_mut(&mut self, t: T) {
+        let envelope = t.envelope();
+        let mut path = Vec::new();
+        let mut node = Arc::make_mut(&mut self.root);
+        loop {
+            node.envelope.merge(&envelope);
+            node.size += 1;
+            if node.has_leaf_children() {
+                break;
+            }
+            let index = choose_subtree(&node.children, &envelope);
+            path.push(index);
+            node = match node.children[index] {
+                FrozenNode::Parent(ref mut child) => Arc::make_mut(child),
+                FrozenNode::Leaf(_) => unreachable!("Unbalanced tree. This is a bug in rstar."),
+            };
+        }
+        node.children.push(FrozenNode::Leaf(t));
+
+        // Resolve overflows bottom up
+        for depth in (0..=path.len()).rev() {
+            let node = descend(&mut self.root, &path[..depth]);
+            let max_size = if node.has_leaf_children() {
+                Params::MAX_LEAF_SIZE
+            } else {
+                Params::MAX_SIZE
+            };
+            if node.children.len() <= max_size {
+                break;
+            }
+            let sibling = split::<_, Params>(node);
+            if depth == 0 {
+                let old_root = FrozenNode::Parent(self.root.clone());
+                self.root = Arc::new(FrozenParent::new(alloc::vec![old_root, sibling]));
+            } else {
+                descend(&mut self.root, &path[..depth - 1])
+                    .children
+                    .push(sibling);
+            }
+        }
+    }
+}
+
+impl<T, Params> FrozenRTree<T, Params>
+where
+    T: RTreeObject + Clone + PartialEq,
+    Params: RTreeParams,
+{
+    /// Returns a new tree without an element equal to `t`, along with the removed element.
+    ///
+    /// Returns `None` if the tree contains no such element. This tree remains unchanged.
+    pub fn remove(&self, t: &T) -> Option<(Self, T)> {
+        let mut result = self.clone();
+        let removed = result.remove_mut(t)?;
+        Some((result, removed))
+    }
+
+    /// Removes an element equal to `t` in place and returns it.
+    ///
+    /// Nodes on the path to the removed element that are shared with other snapshots are
+    /// copied first, other snapshots are not affected.
+    pub fn remove_mut(&mut self, t: &T) -> Option<T> {
+        let path = self.find(t)?;
+        let (&leaf_index, parents) = path.split_last()?;
+        let removed = match descend(&mut self.root, parents)
+            .children
+            .swap_remove(leaf_index)
+        {
+            FrozenNode::Leaf(t) => t,
+            FrozenNode::Parent(_) => unreachable!("Unbalanced tree. This is a bug in rstar."),
+        };
+        for depth in (0..=parents.len()).rev() {
+            let node = descend(&mut self.root, &parents[..depth]);
+            if let Some(&child_index) = parents.get(depth) {
+                if let FrozenNode::Parent(ref child) = node.children[child_index] {
+                    if child.children.is_empty() {
+                        node.children.swap_remove(child_index);
+                    }
+                }
+            }
+            node.update();
+        }
+        Some(removed)
+    }
+}
+
+impl<T, Params> FrozenRTree<T, Params>
+where
+    T: PointDistance,
+    Params: RTreeParams,
+{
+    /// Returns all elements containing a given point.
+    ///
+    /// See [RTree::locate_all_at_point].
+    pub fn locate_all_at_point(
+        &self,
+        point: &<T::Envelope as Envelope>::Point,
+    ) -> FrozenSelectionIterator<'_, T, SelectAtPointFunction<T>> {
+        self.locate_with_selection_function(SelectAtPointFunction::new(point.clone()))
+    }
+
+    /// Returns all elements within a certain distance of a point.
+    ///
+    /// See [RTree::locate_within_distance].
+    pub fn locate_within_distance(
+        &self,
+        query_point: <T::Envelope as Envelope>::Point,
+        max_squared_radius: <<T::Envelope as Envelope>::Point as Point>::Scalar,
+    ) -> FrozenSelectionIterator<'_, T, SelectWithinDistanceFunction<T>> {
+        self.locate_with_selection_function(SelectWithinDistanceFunction::new(
+            query_point,
+            max_squared_radius,
+        ))
+    }
+
+    /// Returns the nearest neighbor of a point.
+    ///
+    /// See [RTree::nearest_neighbor].
+    pub fn nearest_neighbor(&self, query_point: &<T::Envelope as Envelope>::Point) -> Option<&T> {
+        self.nearest_neighbor_iter_with_distance_2(query_point)
+            .next()
+            .map(|(t, _)| t)
+    }
+
+    /// Returns `(element, distance^2)` tuples sorted by their distance to a given point.
+    ///
+    /// See [RTree::nearest_neighbor_iter_with_distance_2].
+    pub fn nearest_neighbor_iter_with_distance_2(
+        &self,
+        query_point: &<T::Envelope as Envelope>::Point,
+    ) -> FrozenNearestNeighborIterator<'_, T> {
+        FrozenNearestNeighborIterator::new(&self.root, query_point.clone())
+    }
+}
+
+/// Returns the node at the end of `path`, copying all nodes along the path that are shared.
+fn descend<'a, T>(root: &'a mut Arc<FrozenParent<T>>, path: &[usize]) -> &'a mut FrozenParent<T>
+where
+    T: RTreeObject + Clone,
+{
+    let mut node = Arc::make_mut(root);
+    for &index in path {
+        node = match node.children[index] {
+            FrozenNode::Parent(ref mut child) => Arc::make_mut(child),
+            FrozenNode::Leaf(_) => unreachable!("Unbalanced tree. This is a bug in rstar."),
+        };
+    }
+    node
+}
+
+/// Returns the index of the child whose area grows the least when adding `envelope`.
+fn choose_subtree<T>(children: &[FrozenNode<T>], envelope: &T::Envelope) -> usize
+where
+    T: RTreeObject,
+{
+    let mut best = None;
+    let mut best_index = 0;
+    for (index, child) in children.iter().enumerate() {
+        let child_envelope = child.envelope();
+        let area = child_envelope.area();
+        let key = (child_envelope.merged(envelope).area() - area, area);
+        if best.map_or(true, |best| key < best) {
+            best = Some(key);
+            best_index = index;
+        }
+    }
+    best_index
+}
+
+/// Splits off the children of an overflowing node, minimizing the overlap and area of both
+/// resulting nodes.
+fn split<T, Params>(node: &mut FrozenParent<T>) -> FrozenNode<T>
+where
+    T: RTreeObject,
+    Params: RTreeParams,
+{
+    let min_size = Params::MIN_SIZE;
+    let mut best = None;
+    let mut best_split = (0, min_size);
+    for axis in 0..<T::Envelope as Envelope>::Point::DIMENSIONS {
+        T::Envelope::sort_envelopes(axis, &mut node.children);
+        for k in min_size..=node.children.len() - min_size {
+            let (l, r) = node.children.split_at(k);
+            let merge_all = |nodes: &[FrozenNode<T>]| {
+                nodes
+                    .iter()
+                    .fold(T::Envelope::new_empty(), |mut envelope, child| {
+                        envelope.merge(&child.envelope());
+                        envelope
+                    })
+            };
+            let (first, second) = (merge_all(l), merge_all(r));
+            let key = (
+                first.intersection_area(&second),
+                first.area() + second.area(),
+            );
+            if best.map_or(true, |best| key < best) {
+                best = Some(key);
+                best_split = (axis, k);
+            }
+        }
+    }
+    let (axis, k) = best_split;
+    T::Envelope::sort_envelopes(axis, &mut node.children);
+    let off_split = node.children.split_off(k);
+    node.update();
+    FrozenNode::Parent(Arc::new(FrozenParent::new(off_split)))
+}
+
+/// Iterator returned by the `FrozenRTree::locate_*` methods.
+pub struct FrozenSelectionIterator<'a, T, Func>
+where
+    T: RTreeObject,
+    Func: SelectionFunction<T>,
+{
+    func: Func,
+    current_nodes: SmallVec<[(&'a FrozenNode<T>, usize); 24]>,
+}
+
+impl<'a, T, Func> FrozenSelectionIterator<'a, T, Func>
+where
+    T: RTreeObject,
+    Func: SelectionFunction<T>,
+{
+    fn new(root: &'a FrozenParent<T>, func: Func) -> Self {
+        let current_nodes = if func.should_unpack_parent_at_depth(&root.envelope, 0) {
+            root.children.iter().map(|child| (child, 1)).collect()
+        } else {
+            SmallVec::new()
+        };
+        FrozenSelectionIterator {
+            func,
+            current_nodes,
+        }
+    }
+}
+
+impl<'a, T, Func> Iterator for FrozenSelectionIterator<'a, T, Func>
+where
+    T: RTreeObject,
+    Func: SelectionFunction<T>,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        while let Some((next, depth)) = self.current_nodes.pop() {
+            match next {
+                FrozenNode::Leaf(t) => {
+                    if self.func.should_unpack_leaf(t) {
+                        return Some(t);
+                    }
+                }
+                FrozenNode::Parent(data) => {
+                    if self
+                        .func
+                        .should_unpack_parent_at_depth(&data.envelope, depth)
+                    {
+                        self.current_nodes
+                            .extend(data.children.iter().map(|child| (child, depth + 1)));
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+struct DistanceWrapper<'a, T>
+where
+    T: PointDistance,
+{
+    node: &'a FrozenNode<T>,
+    distance: <<T::Envelope as Envelope>::Point as Point>::Scalar,
+}
+
+impl<'a, T> PartialEq for DistanceWrapper<'a, T>
+where
+    T: PointDistance,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.distance == other.distance
+    }
+}
+
+impl<'a, T> PartialOrd for DistanceWrapper<'a, T>
+where
+    T: PointDistance,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<::core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<'a, T> Eq for DistanceWrapper<'a, T> where T: PointDistance {}
+
+impl<'a, T> Ord for DistanceWrapper<'a, T>
+where
+    T: PointDistance,
+{
+    fn cmp(&self, other: &Self) -> ::core::cmp::Ordering {
+        // Inverse comparison creates a min heap
+        other.distance.partial_cmp(&self.distance).unwrap()
+    }
+}
+
+/// Iterator returned by [FrozenRTree::nearest_neighbor_iter_with_distance_2].
+pub struct FrozenNearestNeighborIterator<'a, T>
+where
+    T: PointDistance,
+{
+    heap: BinaryHeap<DistanceWrapper<'a, T>>,
+    query_point: <T::Envelope as Envelope>::Point,
+}
+
+impl<'a, T> FrozenNearestNeighborIterator<'a, T>
+where
+    T: PointDistance,
+{
+    fn new(root: &'a FrozenParent<T>, query_point: <T::Envelope as Envelope>::Point) -> Self {
+        let mut result = FrozenNearestNeighborIterator {
+            heap: BinaryHeap::with_capacity(20),
+            query_point,
+        };
+        result.extend_heap(&root.children);
+        result
+    }
+
+    fn extend_heap(&mut self, children: &'a [FrozenNode<T>]) {
+        let query_point = &self.query_point;
+        self.heap.extend(children.iter().map(|node| {
+            let distance = match node {
+                FrozenNode::Leaf(t) => t.distance_2(query_point),
+                FrozenNode::Parent(data) => data.envelope.distance_2(query_point),
+            };
+            DistanceWrapper { node, distance }
+        }));
+    }
+}
+
+impl<'a, T> Iterator for FrozenNearestNeighborIterator<'a, T>
+where
+    T: PointDistance,
+{
+    type Item = (&'a T, <<T::Envelope as Envelope>::Point as Point>::Scalar);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(current) = self.heap.pop() {
+            match current.node {
+                FrozenNode::Leaf(t) => return Some((t, current.distance)),
+                FrozenNode::Parent(data) => self.extend_heap(&data.children),
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{FrozenNode, FrozenParent, FrozenRTree};
+    use crate::params::RTreeParams;
+    use crate::test_utilities::{create_random_points, SEED_1, SEED_2};
+    use crate::{DefaultParams, Envelope, RTree, RTreeObject};
+
+    fn check<T: RTreeObject>(
+        node: &FrozenParent<T>,
+        height: usize,
+        leaf_height: &mut Option<usize>,
+    ) {
+        let mut envelope = T::Envelope::new_empty();
+        let mut size = 0;
+        for child in &node.children {
+            envelope.merge(&child.envelope());
+            match child {
+                FrozenNode::Leaf(_) => {
+                    assert_eq!(*leaf_height.get_or_insert(height), height);
+                    size += 1;
+                }
+                FrozenNode::Parent(data) => {
+                    assert!(data.children.len() <= DefaultParams::MAX_SIZE);
+                    check(data, height + 1, leaf_height);
+                    size += data.size;
+                }
+            }
+        }
+        assert_eq!(node.envelope, envelope);
+        assert_eq!(node.size, size);
+    }
+
+    #[test]
+    fn test_frozen_snapshots() {
+        let points = create_random_points(1000, SEED_1);
+        let mut snapshots = vec![FrozenRTree::new()];
+        for point in &points {
+            let next = snapshots.last().unwrap().insert(*point);
+            snapshots.push(next);
+        }
+        for (size, snapshot) in snapshots.iter().enumerate() {
+            assert_eq!(snapshot.size(), size);
+            check(&snapshot.root, 0, &mut None);
+        }
+        let last = snapshots.last().unwrap();
+        assert!(points.iter().all(|point| last.contains(point)));
+        assert!(!snapshots[500].contains(&points[500]));
+
+        let tree = RTree::bulk_load(points.clone());
+        for query in create_random_points(20, SEED_2) {
+            assert_eq!(last.nearest_neighbor(&query), tree.nearest_neighbor(&query));
+            assert_eq!(
+                last.locate_within_distance(query, 0.01).count(),
+                tree.locate_within_distance(query, 0.01).count()
+            );
+        }
+
+        let mut removed = last.clone();
+        assert!(removed.ptr_eq(last));
+        for point in &points[..600] {
+            assert_eq!(removed.remove_mut(point), Some(*point));
+            check(&removed.root, 0, &mut None);
+        }
+        assert_eq!(removed.remove_mut(&points[0]), None);
+        assert_eq!(removed.size(), 400);
+        assert_eq!(removed.iter().count(), 400);
+        assert_eq!(last.size(), 1000);
+        assert!(points.iter().all(|point| last.contains(point)));
+    }
+
+    #[test]
+    fn test_rtree_snapshot() {
+        let points = create_random_points(500, SEED_1);
+        let mut tree = RTree::bulk_load(points.clone());
+        let snapshot = tree.snapshot();
+        let cheap = snapshot.clone();
+        for point in &points[..250] {
+            tree.remove(point);
+        }
+        tree.insert([2.0, 2.0]);
+        assert!(snapshot.ptr_eq(&cheap));
+        assert_eq!(snapshot.size(), 500);
+        assert!(points.iter().all(|point| snapshot.contains(point)));
+        assert!(!snapshot.contains(&[2.0, 2.0]));
+        assert_eq!(tree.size(), 251);
+    }
+
+    #[test]
+    fn test_frozen_from_rtree() {
+        let points = create_random_points(200, SEED_1);
+        let frozen = FrozenRTree::bulk_load(points.clone());
+        let (smaller, point) = frozen.remove(&points[10]).unwrap();
+        assert_eq!(point, points[10]);
+        assert_eq!(smaller.size(), 199);
+        assert_eq!(frozen.size(), 200);
+        let larger = smaller.insert([0.5, 0.5]);
+        assert!(larger.contains(&[0.5, 0.5]));
+        assert!(!smaller.contains(&[0.5, 0.5]));
+
+        let snapshot = larger.clone();
+        let mut tree = RTree::from(larger);
+        tree.root().sanity_check::<DefaultParams>(false);
+        assert_eq!(tree.size(), 200);
+        assert!(tree.remove(&[0.5, 0.5]).is_some());
+        assert!(snapshot.contains(&[0.5, 0.5]));
+        let unshared = RTree::from(snapshot);
+        assert_eq!(unshared.size(), 200);
+        assert!(unshared.contains(&[0.5, 0.5]));
+    }
+}
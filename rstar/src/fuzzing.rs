@@ -0,0 +1,206 @@
+use crate::aabb::AABB;
+use crate::envelope::Envelope;
+use crate::params::RTreeParams;
+use crate::point::Point;
+use crate::primitives::{GeomWithData, Line, Rectangle};
+use crate::{RTree, RTreeObject};
+
+use alloc::vec::Vec;
+use arbitrary::{Arbitrary, Error, Result, Unstructured};
+use num_traits::{FromPrimitive, Zero};
+
+/// Generated coordinates lie within `[-COORDINATE_BOUND, COORDINATE_BOUND]`.
+///
+/// The bound is small enough to compute areas of integer envelopes without overflows.
+const COORDINATE_BOUND: i32 = 100;
+
+/// The number of distinct floating point coordinates between two integers.
+const COORDINATE_RESOLUTION: i32 = 100;
+
+/// An object that can be generated from unstructured data, see [RTree]'s [Arbitrary]
+/// implementation.
+///
+/// Unlike [Arbitrary], implementations must only generate objects that can safely be inserted
+/// into a tree: coordinates must be finite and small enough for envelope computations not to
+/// overflow. The provided implementations generate coordinates between -100 and 100, integer
+/// coordinates are truncated.
+///
+/// Only available with the `arbitrary` feature.
+///
+/// # Example
+/// ```
+/// use arbitrary::{Arbitrary, Unstructured};
+/// use rstar::primitives::Rectangle;
+/// use rstar::{RTree, AABB};
+///
+/// let data: Vec<u8> = (0..=255).cycle().take(1000).collect();
+/// let mut u = Unstructured::new(&data);
+/// let tree = RTree::<Rectangle<[f64; 2]>>::arbitrary(&mut u).unwrap();
+/// let query = AABB::<[f64; 2]>::arbitrary(&mut u).unwrap();
+/// assert_eq!(tree.sanity_check(), Ok(()));
+/// assert!(tree.locate_in_envelope_intersecting(&query).count() <= tree.size());
+/// ```
+pub trait ArbitraryElement<'a>: RTreeObject + Sized {
+    /// Generates an element from `u`.
+    fn arbitrary_element(u: &mut Unstructured<'a>) -> Result<Self>;
+}
+
+impl<'a, P> ArbitraryElement<'a> for P
+where
+    P: Point,
+    P::Scalar: FromPrimitive,
+{
+    fn arbitrary_element(u: &mut Unstructured<'a>) -> Result<Self> {
+        let mut result = Ok(());
+        let point = P::generate(|_| match arbitrary_coordinate(u) {
+            Ok(coordinate) => coordinate,
+            Err(error) => {
+                result = Err(error);
+                Zero::zero()
+            }
+        });
+        result.map(|()| point)
+    }
+}
+
+impl<'a, P> ArbitraryElement<'a> for Rectangle<P>
+where
+    P: Point,
+    P::Scalar: FromPrimitive,
+{
+    fn arbitrary_element(u: &mut Unstructured<'a>) -> Result<Self> {
+        AABB::arbitrary(u).map(Rectangle::from_aabb)
+    }
+}
+
+impl<'a, P> ArbitraryElement<'a> for Line<P>
+where
+    P: Point,
+    P::Scalar: FromPrimitive,
+{
+    fn arbitrary_element(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Line::new(
+            P::arbitrary_element(u)?,
+            P::arbitrary_element(u)?,
+        ))
+    }
+}
+
+impl<'a, R, T> ArbitraryElement<'a> for GeomWithData<R, T>
+where
+    R: ArbitraryElement<'a>,
+    T: Arbitrary<'a>,
+{
+    fn arbitrary_element(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(GeomWithData::new(
+            R::arbitrary_element(u)?,
+            T::arbitrary(u)?,
+        ))
+    }
+}
+
+fn arbitrary_coordinate<S>(u: &mut Unstructured) -> Result<S>
+where
+    S: FromPrimitive,
+{
+    let bound = COORDINATE_BOUND * COORDINATE_RESOLUTION;
+    let steps = u.int_in_range(-bound..=bound)?;
+    S::from_f64(f64::from(steps) / f64::from(COORDINATE_RESOLUTION)).ok_or(Error::IncorrectFormat)
+}
+
+/// Generates query envelopes, see [ArbitraryElement] for the range of coordinates.
+impl<'a, P> Arbitrary<'a> for AABB<P>
+where
+    P: Point,
+    P::Scalar: FromPrimitive,
+{
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(AABB::from_corners(
+            P::arbitrary_element(u)?,
+            P::arbitrary_element(u)?,
+        ))
+    }
+}
+
+impl<'a, P> Arbitrary<'a> for Rectangle<P>
+where
+    P: Point,
+    P::Scalar: FromPrimitive,
+{
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Self::arbitrary_element(u)
+    }
+}
+
+impl<'a, P> Arbitrary<'a> for Line<P>
+where
+    P: Point,
+    P::Scalar: FromPrimitive,
+{
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Self::arbitrary_element(u)
+    }
+}
+
+/// Generates valid trees of arbitrary size.
+///
+/// The tree is either bulk loaded or built by repeated insertion, as both produce different
+/// structures. Elements that would exceed [RTreeParams::MAX_HEIGHT] are skipped.
+impl<'a, T, Params> Arbitrary<'a> for RTree<T, Params>
+where
+    T: ArbitraryElement<'a>,
+    <T::Envelope as Envelope>::Point: Point,
+    Params: RTreeParams,
+{
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let mut elements = Vec::new();
+        while u.arbitrary()? {
+            elements.push(T::arbitrary_element(u)?);
+        }
+        if u.arbitrary()? {
+            Ok(RTree::bulk_load_with_params(elements))
+        } else {
+            let mut tree = RTree::new_with_params();
+            for element in elements {
+                let _ = tree.try_insert(element);
+            }
+            Ok(tree)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ArbitraryElement;
+    use crate::primitives::{Line, Rectangle};
+    use crate::test_utilities::{create_random_integers, SEED_1};
+    use crate::{Envelope, RTree, RTreeObject, AABB};
+    use arbitrary::{Arbitrary, Unstructured};
+
+    #[test]
+    fn test_arbitrary_trees() {
+        let data: Vec<u8> = create_random_integers::<[i32; 2]>(10_000, SEED_1)
+            .into_iter()
+            .map(|[x, _]| x as u8)
+            .collect();
+        let mut u = Unstructured::new(&data);
+        let mut sizes = Vec::new();
+        while !u.is_empty() {
+            let tree = RTree::<Line<[f64; 3]>>::arbitrary(&mut u).unwrap();
+            assert_eq!(tree.sanity_check(), Ok(()));
+            sizes.push(tree.size());
+            let tree = RTree::<Rectangle<[i32; 4]>>::arbitrary(&mut u).unwrap();
+            assert_eq!(tree.sanity_check(), Ok(()));
+        }
+        assert!(sizes.iter().any(|size| *size > 3));
+
+        let mut u = Unstructured::new(&data);
+        let bound = AABB::from_corners([-100.0, -100.0], [100.0, 100.0]);
+        for _ in 0..100 {
+            let envelope = AABB::<[f64; 2]>::arbitrary(&mut u).unwrap();
+            assert_eq!(bound.merged(&envelope), bound);
+            let point = <[f64; 2]>::arbitrary_element(&mut u).unwrap();
+            assert!(bound.contains_envelope(&point.envelope()));
+        }
+    }
+}
@@ -0,0 +1,498 @@
+use crate::point::{max_inline, min_inline};
+use crate::{Envelope, RTreeNum, RTreeObject};
+use num_traits::{Bounded, Float, Zero};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A longitude / latitude bounding box that understands the antimeridian.
+///
+/// Points are given as `[longitude, latitude]` in degrees, with longitudes in `[-180, 180]`.
+/// Unlike [AABB](crate::AABB), a `GeoAABB` may wrap around the antimeridian (±180°): if its
+/// western boundary is larger than its eastern boundary, the box extends from the western
+/// boundary eastwards across the antimeridian up to the eastern boundary.
+///
+/// All [Envelope] operations respect this wrap-around: merging two boxes close to the
+/// antimeridian yields a small box crossing it (instead of one spanning the whole globe), and
+/// distances are measured on an equirectangular grid that is periodic in longitude. Features that
+/// cross the antimeridian thus no longer need to be split up in user code.
+///
+/// Distances are computed in squared degrees. Use the same metric when implementing
+/// [PointDistance](crate::PointDistance) for your own objects, e.g. by calling
+/// [GeoAABB::distance_2] on the object's envelope.
+///
+/// # Example
+/// ```
+/// use rstar::{Envelope, GeoAABB, RTree, RTreeObject, PointDistance};
+///
+/// #[derive(Debug, PartialEq)]
+/// struct City {
+///     name: &'static str,
+///     position: [f64; 2],
+/// }
+///
+/// impl RTreeObject for City {
+///     type Envelope = GeoAABB<f64>;
+///
+///     fn envelope(&self) -> Self::Envelope {
+///         GeoAABB::from_point(self.position)
+///     }
+/// }
+///
+/// impl PointDistance for City {
+///     fn distance_2(&self, point: &[f64; 2]) -> f64 {
+///         self.envelope().distance_2(point)
+///     }
+/// }
+///
+/// let tree = RTree::bulk_load(vec![
+///     City { name: "Suva", position: [178.44, -18.14] },
+///     City { name: "Apia", position: [-171.76, -13.83] },
+///     City { name: "Honolulu", position: [-157.86, 21.31] },
+/// ]);
+///
+/// // The nearest city to a point just east of the antimeridian is found west of it.
+/// let nearest = tree.nearest_neighbor(&[-179.5, -17.0]).unwrap();
+/// assert_eq!(nearest.name, "Suva");
+///
+/// // Boxes crossing the antimeridian can be used for queries as well.
+/// let pacific = GeoAABB::new(175.0, -20.0, -170.0, -10.0);
+/// assert!(pacific.crosses_antimeridian());
+/// assert_eq!(tree.locate_in_envelope(&pacific).count(), 2);
+/// ```
+#[derive(Clone, Debug, Copy, PartialEq, Eq, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GeoAABB<S>
+where
+    S: RTreeNum + Float,
+{
+    lower: [S; 2],
+    upper: [S; 2],
+}
+
+impl<S> GeoAABB<S>
+where
+    S: RTreeNum + Float,
+{
+    /// Creates a new box from its boundaries, given in degrees.
+    ///
+    /// If `west` is larger than `east`, the box crosses the antimeridian. Longitudes are
+    /// normalized into `[-180, 180]`.
+    pub fn new(west: S, south: S, east: S, north: S) -> Self {
+        GeoAABB {
+            lower: [normalize_longitude(west), min_inline(south, north)],
+            upper: [normalize_longitude(east), max_inline(south, north)],
+        }
+    }
+
+    /// Returns the box encompassing a single `[longitude, latitude]` point.
+    pub fn from_point(point: [S; 2]) -> Self {
+        Self::new(point[0], point[1], point[0], point[1])
+    }
+
+    /// Returns the western boundary of this box.
+    pub fn west(&self) -> S {
+        self.lower[0]
+    }
+
+    /// Returns the eastern boundary of this box.
+    ///
+    /// This is smaller than [west](GeoAABB::west) if the box crosses the antimeridian.
+    pub fn east(&self) -> S {
+        self.upper[0]
+    }
+
+    /// Returns the southern boundary of this box.
+    pub fn south(&self) -> S {
+        self.lower[1]
+    }
+
+    /// Returns the northern boundary of this box.
+    pub fn north(&self) -> S {
+        self.upper[1]
+    }
+
+    /// Returns `true` if this box extends across the antimeridian.
+    pub fn crosses_antimeridian(&self) -> bool {
+        !self.is_empty() && self.lower[0] > self.upper[0]
+    }
+
+    /// Returns the box's extent in longitude, in degrees.
+    pub fn longitude_span(&self) -> S {
+        if self.is_empty() {
+            Zero::zero()
+        } else if self.lower[0] <= self.upper[0] {
+            self.upper[0] - self.lower[0]
+        } else {
+            self.upper[0] - self.lower[0] + full_turn()
+        }
+    }
+
+    /// Returns the squared distance in degrees between the box and a `[longitude, latitude]`
+    /// point, taking the shorter way around the globe in longitude.
+    pub fn distance_2(&self, point: &[S; 2]) -> S {
+        if self.is_empty() {
+            return <S as Bounded>::max_value();
+        }
+        let zero = S::zero();
+        let lat = max_inline(
+            max_inline(self.lower[1] - point[1], point[1] - self.upper[1]),
+            zero,
+        );
+        let lon = if self.contains_longitude(point[0]) {
+            zero
+        } else {
+            min_inline(
+                east_offset(point[0], self.lower[0]),
+                east_offset(self.upper[0], point[0]),
+            )
+        };
+        lon * lon + lat * lat
+    }
+
+    fn is_empty(&self) -> bool {
+        self.lower[1] > self.upper[1]
+    }
+
+    fn contains_longitude(&self, longitude: S) -> bool {
+        let longitude = normalize_longitude(longitude);
+        // -180° and 180° denote the same meridian.
+        self.contains_normalized_longitude(longitude)
+            || (longitude.abs() == half_turn() && self.contains_normalized_longitude(-longitude))
+    }
+
+    fn contains_normalized_longitude(&self, longitude: S) -> bool {
+        if self.lower[0] <= self.upper[0] {
+            self.lower[0] <= longitude && longitude <= self.upper[0]
+        } else {
+            longitude >= self.lower[0] || longitude <= self.upper[0]
+        }
+    }
+
+    /// Returns the box's longitude range as up to two non-wrapping intervals.
+    fn longitude_intervals(&self) -> [Option<(S, S)>; 2] {
+        if self.lower[0] <= self.upper[0] {
+            [Some((self.lower[0], self.upper[0])), None]
+        } else {
+            [
+                Some((self.lower[0], half_turn())),
+                Some((-half_turn::<S>(), self.upper[0])),
+            ]
+        }
+    }
+}
+
+impl<S> Envelope for GeoAABB<S>
+where
+    S: RTreeNum + Float,
+{
+    type Point = [S; 2];
+
+    fn new_empty() -> Self {
+        GeoAABB {
+            lower: [<S as Bounded>::max_value(); 2],
+            upper: [<S as Bounded>::min_value(); 2],
+        }
+    }
+
+    fn contains_point(&self, point: &[S; 2]) -> bool {
+        !self.is_empty()
+            && self.lower[1] <= point[1]
+            && point[1] <= self.upper[1]
+            && self.contains_longitude(point[0])
+    }
+
+    fn contains_envelope(&self, other: &Self) -> bool {
+        if other.is_empty() {
+            return true;
+        }
+        if self.is_empty() || self.lower[1] > other.lower[1] || self.upper[1] < other.upper[1] {
+            return false;
+        }
+        let span = self.longitude_span();
+        span >= full_turn()
+            || east_offset(self.lower[0], other.lower[0]) + other.longitude_span() <= span
+    }
+
+    fn merge(&mut self, other: &Self) {
+        *self = self.merged(other);
+    }
+
+    fn merged(&self, other: &Self) -> Self {
+        if self.is_empty() {
+            return *other;
+        }
+        if other.is_empty() {
+            return *self;
+        }
+        // Try both directions around the globe and keep the narrower union.
+        let (self_span, other_span) = (self.longitude_span(), other.longitude_span());
+        let starting_here = max_inline(
+            self_span,
+            east_offset(self.lower[0], other.lower[0]) + other_span,
+        );
+        let starting_there = max_inline(
+            other_span,
+            east_offset(other.lower[0], self.lower[0]) + self_span,
+        );
+        let (west, span) = if starting_here <= starting_there {
+            (self.lower[0], starting_here)
+        } else {
+            (other.lower[0], starting_there)
+        };
+        let (west, east) = if span >= full_turn() {
+            (-half_turn::<S>(), half_turn())
+        } else {
+            (west, normalize_longitude(west + span))
+        };
+        GeoAABB {
+            lower: [west, min_inline(self.lower[1], other.lower[1])],
+            upper: [east, max_inline(self.upper[1], other.upper[1])],
+        }
+    }
+
+    fn intersects(&self, other: &Self) -> bool {
+        !self.is_empty()
+            && !other.is_empty()
+            && self.lower[1] <= other.upper[1]
+            && other.lower[1] <= self.upper[1]
+            && (self.contains_longitude(other.lower[0]) || other.contains_longitude(self.lower[0]))
+    }
+
+    fn intersection_area(&self, other: &Self) -> S {
+        let zero = S::zero();
+        if self.is_empty() || other.is_empty() {
+            return zero;
+        }
+        let lat =
+            min_inline(self.upper[1], other.upper[1]) - max_inline(self.lower[1], other.lower[1]);
+        if lat <= zero {
+            return zero;
+        }
+        let mut lon = zero;
+        for (l_min, l_max) in self.longitude_intervals().iter().flatten() {
+            for (r_min, r_max) in other.longitude_intervals().iter().flatten() {
+                let overlap = min_inline(*l_max, *r_max) - max_inline(*l_min, *r_min);
+                lon = lon + max_inline(overlap, zero);
+            }
+        }
+        lon * lat
+    }
+
+    fn area(&self) -> S {
+        if self.is_empty() {
+            return Zero::zero();
+        }
+        self.longitude_span() * (self.upper[1] - self.lower[1])
+    }
+
+    fn distance_2(&self, point: &[S; 2]) -> S {
+        self.distance_2(point)
+    }
+
+    fn min_max_dist_2(&self, point: &[S; 2]) -> S {
+        // Any element within the box is at most as far away as the box's farthest point,
+        // which makes for a valid, if slightly pessimistic, upper bound.
+        let antipode = normalize_longitude(point[0] + half_turn());
+        let lon = if self.contains_longitude(antipode) {
+            half_turn()
+        } else {
+            max_inline(
+                longitude_distance(point[0], self.lower[0]),
+                longitude_distance(point[0], self.upper[0]),
+            )
+        };
+        let lat = max_inline(
+            (point[1] - self.lower[1]).abs(),
+            (point[1] - self.upper[1]).abs(),
+        );
+        lon * lon + lat * lat
+    }
+
+    fn center(&self) -> [S; 2] {
+        let two = S::one() + S::one();
+        [
+            normalize_longitude(self.lower[0] + self.longitude_span() / two),
+            (self.lower[1] + self.upper[1]) / two,
+        ]
+    }
+
+    fn perimeter_value(&self) -> S {
+        if self.is_empty() {
+            return Zero::zero();
+        }
+        self.longitude_span() + self.upper[1] - self.lower[1]
+    }
+
+    fn sort_envelopes<T: RTreeObject<Envelope = Self>>(axis: usize, envelopes: &mut [T]) {
+        envelopes.sort_unstable_by(|l, r| {
+            l.envelope().center()[axis]
+                .partial_cmp(&r.envelope().center()[axis])
+                .unwrap()
+        });
+    }
+
+    fn partition_envelopes<T: RTreeObject<Envelope = Self>>(
+        axis: usize,
+        envelopes: &mut [T],
+        selection_size: usize,
+    ) {
+        envelopes.select_nth_unstable_by(selection_size, |l, r| {
+            l.envelope().center()[axis]
+                .partial_cmp(&r.envelope().center()[axis])
+                .unwrap()
+        });
+    }
+}
+
+fn half_turn<S: Float>() -> S {
+    S::from(180.0).unwrap()
+}
+
+fn full_turn<S: Float>() -> S {
+    S::from(360.0).unwrap()
+}
+
+/// Maps a longitude into `[-180, 180]`.
+fn normalize_longitude<S: Float>(longitude: S) -> S {
+    if longitude >= -half_turn::<S>() && longitude <= half_turn() {
+        return longitude;
+    }
+    let wrapped = (longitude + half_turn()) % full_turn();
+    if wrapped < S::zero() {
+        wrapped + half_turn()
+    } else {
+        wrapped - half_turn()
+    }
+}
+
+/// Returns how far one has to travel eastwards from `from` to reach `to`, in `[0, 360)`.
+fn east_offset<S: Float>(from: S, to: S) -> S {
+    let offset = normalize_longitude(to) - normalize_longitude(from);
+    if offset < S::zero() {
+        offset + full_turn()
+    } else if offset >= full_turn() {
+        offset - full_turn()
+    } else {
+        offset
+    }
+}
+
+/// Returns the shorter distance between two longitudes, in `[0, 180]`.
+fn longitude_distance<S: Float>(l: S, r: S) -> S {
+    let offset = east_offset(l, r);
+    if offset > half_turn() {
+        full_turn::<S>() - offset
+    } else {
+        offset
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::GeoAABB;
+    use crate::{Envelope, PointDistance, RTree, RTreeObject};
+    use approx::*;
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct GeoPoint([f64; 2]);
+
+    impl RTreeObject for GeoPoint {
+        type Envelope = GeoAABB<f64>;
+
+        fn envelope(&self) -> Self::Envelope {
+            GeoAABB::from_point(self.0)
+        }
+    }
+
+    impl PointDistance for GeoPoint {
+        fn distance_2(&self, point: &[f64; 2]) -> f64 {
+            self.envelope().distance_2(point)
+        }
+    }
+
+    #[test]
+    fn test_merge_across_antimeridian() {
+        let west_of_dateline = GeoAABB::from_point([179.0, 0.0]);
+        let east_of_dateline = GeoAABB::from_point([-179.0, 1.0]);
+        let merged = west_of_dateline.merged(&east_of_dateline);
+        assert!(merged.crosses_antimeridian());
+        assert_abs_diff_eq!(merged.longitude_span(), 2.0);
+        assert_eq!(merged, east_of_dateline.merged(&west_of_dateline));
+        assert!(merged.contains_point(&[180.0, 0.5]));
+        assert!(!merged.contains_point(&[0.0, 0.5]));
+        assert!(merged.contains_envelope(&west_of_dateline));
+        assert!(merged.contains_envelope(&east_of_dateline));
+
+        let mut empty = GeoAABB::new_empty();
+        empty.merge(&merged);
+        assert_eq!(empty, merged);
+    }
+
+    #[test]
+    fn test_merge_to_full_circle() {
+        let a = GeoAABB::new(-170.0, 0.0, 10.0, 1.0);
+        let b = GeoAABB::new(0.0, 0.0, 179.0, 1.0);
+        let merged = a.merged(&b);
+        assert_abs_diff_eq!(merged.longitude_span(), 349.0);
+        let c = GeoAABB::new(175.0, 0.0, -165.0, 1.0);
+        let full = merged.merged(&c);
+        assert_abs_diff_eq!(full.longitude_span(), 360.0);
+        assert!(full.contains_envelope(&c));
+    }
+
+    #[test]
+    fn test_intersects_and_area() {
+        let crossing = GeoAABB::<f64>::new(170.0, -10.0, -170.0, 10.0);
+        let east = GeoAABB::new(-175.0, 0.0, -160.0, 20.0);
+        let far = GeoAABB::new(0.0, -10.0, 10.0, 10.0);
+        assert!(crossing.intersects(&east));
+        assert!(east.intersects(&crossing));
+        assert!(!crossing.intersects(&far));
+        assert_abs_diff_eq!(crossing.area(), 20.0 * 20.0);
+        assert_abs_diff_eq!(crossing.intersection_area(&east), 5.0 * 10.0);
+        assert_abs_diff_eq!(crossing.intersection_area(&far), 0.0);
+        assert_abs_diff_eq!(crossing.center()[0].abs(), 180.0);
+    }
+
+    #[test]
+    fn test_distance_wraps_around() {
+        let aabb = GeoAABB::new(170.0, 0.0, 175.0, 10.0);
+        assert_abs_diff_eq!(aabb.distance_2(&[-175.0, 5.0]), 10.0 * 10.0);
+        assert_abs_diff_eq!(aabb.distance_2(&[172.0, 12.0]), 2.0 * 2.0);
+        assert_abs_diff_eq!(aabb.distance_2(&[172.0, 5.0]), 0.0);
+        assert!(aabb.min_max_dist_2(&[-175.0, 5.0]) >= aabb.distance_2(&[-175.0, 5.0]));
+    }
+
+    #[test]
+    fn test_nearest_neighbor_across_antimeridian() {
+        let mut points = Vec::new();
+        for i in 0..200 {
+            let longitude = -180.0 + i as f64 * 1.8;
+            points.push(GeoPoint([longitude, (i % 7) as f64]));
+        }
+        let bulk_loaded = RTree::bulk_load(points.clone());
+        let mut inserted = RTree::new();
+        for point in &points {
+            inserted.insert(point.clone());
+        }
+
+        for query in &[[179.9, 0.0], [-179.9, 6.0], [0.0, 3.0], [90.1, 1.0]] {
+            let expected = points
+                .iter()
+                .min_by(|l, r| {
+                    l.distance_2(query)
+                        .partial_cmp(&r.distance_2(query))
+                        .unwrap()
+                })
+                .unwrap();
+            for tree in &[&bulk_loaded, &inserted] {
+                let nearest = tree.nearest_neighbor(query).unwrap();
+                assert_abs_diff_eq!(nearest.distance_2(query), expected.distance_2(query));
+                let count = tree.locate_within_distance(*query, 4.0).count();
+                let expected_count = points.iter().filter(|p| p.distance_2(query) <= 4.0).count();
+                assert_eq!(count, expected_count);
+            }
+        }
+    }
+}
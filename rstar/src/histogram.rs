@@ -0,0 +1,180 @@
+use crate::node::{ParentNode, RTreeNode};
+use crate::params::RTreeParams;
+use crate::point::Point;
+use crate::{Envelope, RTree, RTreeObject, AABB};
+
+use alloc::vec;
+use alloc::vec::Vec;
+use num_traits::{Float, ToPrimitive, Zero};
+
+/// A regular grid of `nx` times `ny` cells covering an extent, binning elements by the
+/// center of their envelope along the first two axes.
+pub(crate) struct Grid<P>
+where
+    P: Point,
+{
+    extent: AABB<P>,
+    nx: usize,
+    ny: usize,
+}
+
+impl<P> Grid<P>
+where
+    P: Point,
+    P::Scalar: Float,
+{
+    pub(crate) fn new(extent: AABB<P>, nx: usize, ny: usize) -> Self {
+        Grid { extent, nx, ny }
+    }
+
+    /// Returns the number of cells.
+    pub(crate) fn len(&self) -> usize {
+        self.nx * self.ny
+    }
+
+    /// Returns the row major index of the cell containing an element's envelope.
+    pub(crate) fn element_cell(&self, envelope: &AABB<P>) -> Option<usize> {
+        let center = envelope.center();
+        if !self.extent.contains_point(&center) {
+            return None;
+        }
+        Some(self.cell(&center))
+    }
+
+    /// Returns the cell containing all elements of a node, or `None` if its elements may be
+    /// binned into different cells or lie outside of the grid.
+    pub(crate) fn node_cell(&self, envelope: &AABB<P>) -> Option<usize> {
+        if !self.extent.contains_envelope(envelope) {
+            return None;
+        }
+        let cell = self.cell(&envelope.lower());
+        if cell == self.cell(&envelope.upper()) {
+            Some(cell)
+        } else {
+            None
+        }
+    }
+
+    /// Returns `false` if no element of a node can be binned into the grid.
+    pub(crate) fn intersects(&self, envelope: &AABB<P>) -> bool {
+        self.len() > 0 && self.extent.intersects(envelope)
+    }
+
+    fn cell(&self, point: &P) -> usize {
+        let x = self.axis_index(point, 0, self.nx);
+        let y = self.axis_index(point, 1, self.ny);
+        y * self.nx + x
+    }
+
+    fn axis_index(&self, point: &P, axis: usize, cells: usize) -> usize {
+        let lower = self.extent.lower().nth(axis);
+        let length = self.extent.upper().nth(axis) - lower;
+        if length <= P::Scalar::zero() {
+            return 0;
+        }
+        let index = ((point.nth(axis) - lower) / length).to_f64().unwrap_or(0.0) * cells as f64;
+        (index as usize).min(cells - 1)
+    }
+}
+
+impl<T, P, Params> RTree<T, Params>
+where
+    T: RTreeObject<Envelope = AABB<P>>,
+    P: Point,
+    P::Scalar: Float,
+    Params: RTreeParams,
+{
+    /// Counts the elements in every cell of a regular grid, e.g. to render a density heatmap.
+    ///
+    /// The grid divides `grid_extent` into `nx` columns and `ny` rows along the first two
+    /// axes. An element is counted in the cell containing the center of its envelope,
+    /// elements whose center lies outside of `grid_extent` are ignored. The counts are
+    /// returned in row major order, the count of column `x` and row `y` is found at index
+    /// `y * nx + x`.
+    ///
+    /// The tree is traversed only once. Subtrees lying within a single cell contribute their
+    /// size without being visited.
+    ///
+    /// See [AggregateRTree::histogram](crate::AggregateRTree::histogram) to sum weights
+    /// instead.
+    ///
+    /// # Example
+    /// ```
+    /// use rstar::{RTree, AABB};
+    ///
+    /// let points: Vec<_> = (0..100).map(|i| [(i % 10) as f64, (i / 10) as f64]).collect();
+    /// let tree = RTree::bulk_load(points);
+    /// let extent = AABB::from_corners([0.0, 0.0], [10.0, 10.0]);
+    /// let counts = tree.histogram(extent, 2, 5);
+    /// assert_eq!(counts, vec![10; 10]);
+    /// ```
+    pub fn histogram(&self, grid_extent: AABB<P>, nx: usize, ny: usize) -> Vec<usize> {
+        let grid = Grid::new(grid_extent, nx, ny);
+        let mut counts = vec![0; grid.len()];
+        histogram_recursive(&grid, self.root(), &mut counts);
+        counts
+    }
+}
+
+fn histogram_recursive<T, P>(grid: &Grid<P>, node: &ParentNode<T>, counts: &mut [usize])
+where
+    T: RTreeObject<Envelope = AABB<P>>,
+    P: Point,
+    P::Scalar: Float,
+{
+    if node.size == 0 || !grid.intersects(&node.envelope) {
+        return;
+    }
+    if let Some(cell) = grid.node_cell(&node.envelope) {
+        counts[cell] += node.size;
+        return;
+    }
+    for child in &node.children {
+        match child {
+            RTreeNode::Parent(ref data) => histogram_recursive(grid, data, counts),
+            RTreeNode::Leaf(ref t) => {
+                if let Some(cell) = grid.element_cell(&t.envelope()) {
+                    counts[cell] += 1;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Grid;
+    use crate::test_utilities::{create_random_points, create_random_rectangles, SEED_1};
+    use crate::{RTree, RTreeObject, AABB};
+
+    #[test]
+    fn test_histogram() {
+        let extent = AABB::from_corners([0.1, 0.2], [0.9, 0.7]);
+        let grid = Grid::new(extent, 7, 4);
+
+        let points = create_random_points(5000, SEED_1);
+        let tree = RTree::bulk_load(points.clone());
+        let mut expected = vec![0; 28];
+        for point in &points {
+            if let Some(cell) = grid.element_cell(&point.envelope()) {
+                expected[cell] += 1;
+            }
+        }
+        assert!(expected.iter().all(|count| *count > 0));
+        assert_eq!(tree.histogram(extent, 7, 4), expected);
+
+        let rectangles = create_random_rectangles(1000, SEED_1);
+        let tree = RTree::bulk_load(rectangles.clone());
+        let mut expected = vec![0; 28];
+        for rectangle in &rectangles {
+            if let Some(cell) = grid.element_cell(&rectangle.envelope()) {
+                expected[cell] += 1;
+            }
+        }
+        assert_eq!(tree.histogram(extent, 7, 4), expected);
+
+        assert!(tree.histogram(extent, 0, 4).is_empty());
+        let all = AABB::from_corners([-1.0, -1.0], [2.0, 2.0]);
+        assert_eq!(tree.histogram(all, 1, 1), vec![1000]);
+    }
+}
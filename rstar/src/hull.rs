@@ -0,0 +1,251 @@
+use crate::node::{ParentNode, RTreeNode};
+use crate::params::RTreeParams;
+use crate::point::Point;
+use crate::{RTree, RTreeObject, AABB};
+
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use num_traits::{One, Zero};
+
+impl<T, P, Params> RTree<T, Params>
+where
+    T: RTreeObject<Envelope = AABB<P>>,
+    P: Point,
+    Params: RTreeParams,
+{
+    /// Returns the convex hull of all elements' envelopes, in counter clockwise order.
+    ///
+    /// For trees of points, this is the convex hull of the points. The hull starts with the
+    /// vertex with the smallest coordinates and contains no collinear vertices. Trees
+    /// containing less than three distinct corners return these corners.
+    ///
+    /// The elements extreme in eight directions are located first, descending only into the
+    /// most promising nodes. Nodes lying within the polygon spanned by these elements cannot
+    /// contribute to the hull and are skipped, usually only the nodes along the boundary of
+    /// the tree are visited. As nothing is cached, the hull is always up to date after the
+    /// tree was modified.
+    ///
+    /// # Panics
+    /// Panics if the elements are not two dimensional.
+    ///
+    /// # Example
+    /// ```
+    /// use rstar::RTree;
+    ///
+    /// let tree = RTree::bulk_load(vec![[0, 0], [2, 0], [1, 1], [2, 2], [0, 2], [1, 0]]);
+    /// assert_eq!(tree.convex_hull(), vec![[0, 0], [2, 0], [2, 2], [0, 2]]);
+    /// ```
+    pub fn convex_hull(&self) -> Vec<P> {
+        assert_eq!(P::DIMENSIONS, 2, "Convex hulls require two dimensions");
+        if self.size() == 0 {
+            return Vec::new();
+        }
+        let one = P::Scalar::one();
+        let zero = P::Scalar::zero();
+        let directions = [
+            (one, zero),
+            (one, one),
+            (zero, one),
+            (-one, one),
+            (-one, zero),
+            (-one, -one),
+            (zero, -one),
+            (one, -one),
+        ];
+        let mut candidates: Vec<_> = directions
+            .iter()
+            .filter_map(|direction| {
+                let mut best = None;
+                find_extreme(self.root(), *direction, &mut best);
+                best.map(|(corner, _)| corner)
+            })
+            .collect();
+        let polygon = monotone_chain(candidates.clone());
+        collect_candidates(self.root(), &polygon, &mut candidates);
+        monotone_chain(candidates)
+    }
+}
+
+/// Returns the corners of an envelope.
+fn corners<P: Point>(envelope: &AABB<P>) -> [P; 4] {
+    let (lower, upper) = (envelope.lower(), envelope.upper());
+    [
+        lower.clone(),
+        P::generate(|axis| {
+            if axis == 0 {
+                upper.nth(0)
+            } else {
+                lower.nth(1)
+            }
+        }),
+        upper.clone(),
+        P::generate(|axis| {
+            if axis == 0 {
+                lower.nth(0)
+            } else {
+                upper.nth(1)
+            }
+        }),
+    ]
+}
+
+fn dot<P: Point>(point: &P, (x, y): (P::Scalar, P::Scalar)) -> P::Scalar {
+    point.nth(0) * x + point.nth(1) * y
+}
+
+/// Returns the maximum of `dot(corner, direction)` over the corners of an envelope.
+fn upper_bound<P: Point>(envelope: &AABB<P>, direction: (P::Scalar, P::Scalar)) -> P::Scalar {
+    corners(envelope)
+        .iter()
+        .map(|corner| dot(corner, direction))
+        .fold(dot(&envelope.lower(), direction), |max, value| {
+            if value > max {
+                value
+            } else {
+                max
+            }
+        })
+}
+
+/// Finds the element corner maximizing `dot(corner, direction)`, visiting the children with the
+/// highest bound first and skipping children which can't improve `best`.
+fn find_extreme<T, P>(
+    node: &ParentNode<T>,
+    direction: (P::Scalar, P::Scalar),
+    best: &mut Option<(P, P::Scalar)>,
+) where
+    T: RTreeObject<Envelope = AABB<P>>,
+    P: Point,
+{
+    let mut children: Vec<_> = node
+        .children
+        .iter()
+        .map(|child| (upper_bound(&child.envelope(), direction), child))
+        .collect();
+    children.sort_by(|(l, _), (r, _)| r.partial_cmp(l).unwrap_or(Ordering::Equal));
+    for (bound, child) in children {
+        if let Some((_, value)) = best {
+            if bound <= *value {
+                return;
+            }
+        }
+        match child {
+            RTreeNode::Parent(ref data) => find_extreme(data, direction, best),
+            RTreeNode::Leaf(ref t) => {
+                let corner = corners(&t.envelope())
+                    .iter()
+                    .max_by(|l, r| {
+                        dot(*l, direction)
+                            .partial_cmp(&dot(*r, direction))
+                            .unwrap_or(Ordering::Equal)
+                    })
+                    .unwrap()
+                    .clone();
+                *best = Some((corner, bound));
+            }
+        }
+    }
+}
+
+/// Appends the corners of all elements that are not inside of a convex polygon.
+fn collect_candidates<T, P>(node: &ParentNode<T>, polygon: &[P], candidates: &mut Vec<P>)
+where
+    T: RTreeObject<Envelope = AABB<P>>,
+    P: Point,
+{
+    for child in &node.children {
+        let envelope = child.envelope();
+        if polygon.len() >= 3 && corners(&envelope).iter().all(|c| is_inside(polygon, c)) {
+            continue;
+        }
+        match child {
+            RTreeNode::Parent(ref data) => collect_candidates(data, polygon, candidates),
+            RTreeNode::Leaf(_) => candidates.extend(corners(&envelope)),
+        }
+    }
+}
+
+/// Returns `true` if a point lies inside or on the boundary of a counter clockwise polygon.
+fn is_inside<P: Point>(polygon: &[P], point: &P) -> bool {
+    let previous = polygon.iter().cycle().skip(polygon.len() - 1);
+    polygon
+        .iter()
+        .zip(previous)
+        .all(|(to, from)| cross(from, to, point) >= P::Scalar::zero())
+}
+
+/// Returns the cross product of `to - from` and `point - from`, which is positive if `point`
+/// lies to the left of the line from `from` to `to`.
+fn cross<P: Point>(from: &P, to: &P, point: &P) -> P::Scalar {
+    (to.nth(0) - from.nth(0)) * (point.nth(1) - from.nth(1))
+        - (to.nth(1) - from.nth(1)) * (point.nth(0) - from.nth(0))
+}
+
+/// Computes the convex hull of a set of points with Andrew's monotone chain algorithm.
+fn monotone_chain<P: Point>(mut points: Vec<P>) -> Vec<P> {
+    points.sort_by(|l, r| {
+        (l.nth(0), l.nth(1))
+            .partial_cmp(&(r.nth(0), r.nth(1)))
+            .unwrap_or(Ordering::Equal)
+    });
+    points.dedup();
+    if points.len() < 3 {
+        return points;
+    }
+    let half_hull = |points: &mut dyn Iterator<Item = &P>| {
+        let mut hull: Vec<P> = Vec::new();
+        for point in points {
+            while hull.len() >= 2
+                && cross(&hull[hull.len() - 2], &hull[hull.len() - 1], point) <= Zero::zero()
+            {
+                hull.pop();
+            }
+            hull.push(point.clone());
+        }
+        // The last point is the first point of the other half.
+        hull.pop();
+        hull
+    };
+    let mut hull = half_hull(&mut points.iter());
+    hull.extend(half_hull(&mut points.iter().rev()));
+    hull
+}
+
+#[cfg(test)]
+mod test {
+    use super::monotone_chain;
+    use crate::test_utilities::{create_random_points, create_random_rectangles, SEED_1, SEED_2};
+    use crate::{RTree, RTreeObject};
+
+    #[test]
+    fn test_convex_hull() {
+        let points = create_random_points(2000, SEED_1);
+        let mut tree = RTree::bulk_load(points.clone());
+        let hull = tree.convex_hull();
+        assert!(hull.len() >= 3);
+        assert_eq!(hull, monotone_chain(points.clone()));
+
+        for point in create_random_points(20, SEED_2) {
+            tree.insert([point[0] * 1.2 - 0.1, point[1] * 1.2 - 0.1]);
+        }
+        for point in &hull[..3] {
+            tree.remove(point);
+        }
+        assert_eq!(
+            tree.convex_hull(),
+            monotone_chain(tree.iter().copied().collect())
+        );
+
+        let rectangles = create_random_rectangles(500, SEED_1);
+        let tree = RTree::bulk_load(rectangles.clone());
+        let corners = rectangles
+            .iter()
+            .flat_map(|rectangle| super::corners(&rectangle.envelope()))
+            .collect();
+        assert_eq!(tree.convex_hull(), monotone_chain(corners));
+
+        assert!(RTree::<[f64; 2]>::new().convex_hull().is_empty());
+        let line = RTree::bulk_load(vec![[0.0, 0.0], [1.0, 1.0], [2.0, 2.0]]);
+        assert_eq!(line.convex_hull(), vec![[0.0, 0.0], [2.0, 2.0]]);
+    }
+}
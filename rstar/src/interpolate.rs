@@ -0,0 +1,147 @@
+use crate::distance_field::SampleGrid;
+use crate::params::RTreeParams;
+use crate::point::Point;
+use crate::primitives::GeomWithData;
+use crate::{NearestNeighborState, PointDistance, RTree, AABB};
+
+use alloc::vec::Vec;
+use num_traits::Float;
+
+impl<P, Params> RTree<GeomWithData<P, P::Scalar>, Params>
+where
+    P: Point,
+    P::Scalar: Float,
+    Params: RTreeParams,
+{
+    /// Interpolates the values of the `n` nearest points at a location by inverse distance
+    /// weighting.
+    ///
+    /// The value of each of the `n` nearest points is weighted by `1 / distance^power`. If a
+    /// point lies exactly at `location`, its value is returned. Returns `None` if the tree is
+    /// empty or `n` is zero.
+    ///
+    /// # Example
+    /// ```
+    /// use rstar::primitives::GeomWithData;
+    /// use rstar::RTree;
+    ///
+    /// let samples = vec![
+    ///     GeomWithData::new([0.0, 0.0], 10.0),
+    ///     GeomWithData::new([2.0, 0.0], 20.0),
+    ///     GeomWithData::new([9.0, 9.0], 90.0),
+    /// ];
+    /// let tree = RTree::bulk_load(samples);
+    /// assert_eq!(tree.interpolate_idw(&[1.0, 0.0], 2, 2.0), Some(15.0));
+    /// assert_eq!(tree.interpolate_idw(&[2.0, 0.0], 2, 2.0), Some(20.0));
+    /// ```
+    pub fn interpolate_idw(&self, location: &P, n: usize, power: P::Scalar) -> Option<P::Scalar> {
+        let neighbors = self
+            .nearest_neighbor_iter_with_distance_2(location)
+            .take(n)
+            .map(|(sample, distance_2)| (sample.data, distance_2));
+        idw(neighbors, power)
+    }
+
+    /// Interpolates values at the center of every cell of a regular grid, e.g. to render a
+    /// raster from scattered measurements.
+    ///
+    /// The grid and the order of the results are the same as for [RTree::distance_field],
+    /// every value is interpolated like [RTree::interpolate_idw]. The buffers of the nearest
+    /// neighbor search are reused for all cells.
+    ///
+    /// # Panics
+    /// Panics if the points are not two dimensional.
+    pub fn interpolate_idw_grid(
+        &self,
+        grid_extent: AABB<P>,
+        nx: usize,
+        ny: usize,
+        n: usize,
+        power: P::Scalar,
+    ) -> Vec<Option<P::Scalar>> {
+        assert_eq!(P::DIMENSIONS, 2, "Grids require two dimensions");
+        let grid = SampleGrid::new(grid_extent, nx, ny);
+        let mut state = NearestNeighborState::new();
+        let mut result = Vec::with_capacity(nx * ny);
+        for y in 0..ny {
+            for x in 0..nx {
+                let location = grid.center(x, y);
+                let neighbors = self
+                    .nearest_neighbor_iter_with(&mut state, &location)
+                    .take(n)
+                    .map(|sample| (sample.data, sample.distance_2(&location)));
+                result.push(idw(neighbors, power));
+            }
+        }
+        result
+    }
+}
+
+/// Computes the inverse distance weighted average of `(value, distance²)` pairs.
+fn idw<S, I>(neighbors: I, power: S) -> Option<S>
+where
+    S: Float,
+    I: Iterator<Item = (S, S)>,
+{
+    let exponent = -power / (S::one() + S::one());
+    let mut weights = S::zero();
+    let mut sum = S::zero();
+    for (value, distance_2) in neighbors {
+        if distance_2.is_zero() {
+            return Some(value);
+        }
+        let weight = distance_2.powf(exponent);
+        weights = weights + weight;
+        sum = sum + weight * value;
+    }
+    if weights.is_zero() {
+        None
+    } else {
+        Some(sum / weights)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::point::PointExt;
+    use crate::primitives::GeomWithData;
+    use crate::test_utilities::{create_random_points, SEED_1, SEED_2};
+    use crate::{RTree, AABB};
+
+    #[test]
+    fn test_interpolate_idw() {
+        let samples: Vec<_> = create_random_points(500, SEED_1)
+            .into_iter()
+            .map(|point| GeomWithData::new(point, point[0] + 2.0 * point[1]))
+            .collect();
+        let tree = RTree::bulk_load(samples.clone());
+        for location in create_random_points(20, SEED_2) {
+            let mut nearest = samples.clone();
+            nearest.sort_by(|l, r| {
+                let l = l.geom().distance_2(&location);
+                let r = r.geom().distance_2(&location);
+                l.partial_cmp(&r).unwrap()
+            });
+            let (weights, sum) = nearest[..8]
+                .iter()
+                .fold((0.0, 0.0), |(weights, sum), sample| {
+                    let weight = 1.0 / sample.geom().distance_2(&location).powf(1.5);
+                    (weights + weight, sum + weight * sample.data)
+                });
+            let value = tree.interpolate_idw(&location, 8, 3.0).unwrap();
+            assert!((value - sum / weights).abs() < 1e-9);
+        }
+        assert_eq!(tree.interpolate_idw(&[0.5, 0.5], 0, 2.0), None);
+
+        let extent = AABB::from_corners([0.0, 0.0], [1.0, 1.0]);
+        let grid = tree.interpolate_idw_grid(extent, 5, 4, 6, 2.0);
+        assert_eq!(grid.len(), 20);
+        for y in 0..4 {
+            for x in 0..5 {
+                let location = [0.1 + 0.2 * x as f64, 0.125 + 0.25 * y as f64];
+                let expected = tree.interpolate_idw(&location, 6, 2.0).unwrap();
+                assert!((grid[y * 5 + x].unwrap() - expected).abs() < 1e-9);
+            }
+        }
+    }
+}
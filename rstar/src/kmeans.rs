@@ -0,0 +1,140 @@
+use crate::algorithm::iterators::RTreeIterator;
+use crate::node::{ParentNode, RTreeNode};
+use crate::params::RTreeParams;
+use crate::point::{Point, PointExt};
+use crate::{Envelope, PointDistance, RTree, RTreeObject, AABB};
+
+use alloc::vec::Vec;
+
+impl<T, P, Params> RTree<T, Params>
+where
+    T: PointDistance + RTreeObject<Envelope = AABB<P>>,
+    P: Point,
+    Params: RTreeParams,
+{
+    /// Assigns every element to its nearest centroid, e.g. for an iteration of Lloyd's k-means
+    /// algorithm.
+    ///
+    /// Returns every element together with the index of its nearest centroid in `centroids`,
+    /// in arbitrary order. If several centroids are equally near, one of them is chosen. Returns
+    /// nothing if `centroids` is empty.
+    ///
+    /// This uses the filtering algorithm by Kanungo et al.: while descending the tree,
+    /// centroids which are farther away from every point of a node's envelope than another
+    /// centroid are dropped. Subtrees with a single remaining centroid are assigned without
+    /// computing any distances, which is the common case once the centroids are spread out.
+    ///
+    /// # Example
+    /// ```
+    /// use rstar::RTree;
+    ///
+    /// let tree = RTree::bulk_load(vec![[0.0, 0.0], [1.0, 0.0], [9.0, 0.0], [10.0, 0.0]]);
+    /// let centroids = [[0.5, 0.0], [9.5, 0.0]];
+    /// let mut sums = [[0.0, 0.0]; 2];
+    /// let mut counts = [0; 2];
+    /// for (point, centroid) in tree.assign_to_centroids(&centroids) {
+    ///     sums[centroid][0] += point[0];
+    ///     sums[centroid][1] += point[1];
+    ///     counts[centroid] += 1;
+    /// }
+    /// assert_eq!(counts, [2, 2]);
+    /// assert_eq!(sums, [[1.0, 0.0], [19.0, 0.0]]);
+    /// ```
+    pub fn assign_to_centroids(&self, centroids: &[P]) -> Vec<(&T, usize)> {
+        let mut result = Vec::with_capacity(self.size());
+        if self.size() > 0 && !centroids.is_empty() {
+            let candidates: Vec<_> = (0..centroids.len()).collect();
+            filter(self.root(), centroids, candidates, &mut result);
+        }
+        result
+    }
+}
+
+/// Assigns the elements of a node to the nearest of the candidate centroids.
+fn filter<'a, T, P>(
+    node: &'a ParentNode<T>,
+    centroids: &[P],
+    mut candidates: Vec<usize>,
+    result: &mut Vec<(&'a T, usize)>,
+) where
+    T: PointDistance + RTreeObject<Envelope = AABB<P>>,
+    P: Point,
+{
+    let envelope = &node.envelope;
+    let center = envelope.center();
+    let closest = *candidates
+        .iter()
+        .min_by(|l, r| {
+            let l = PointExt::distance_2(&centroids[**l], &center);
+            let r = PointExt::distance_2(&centroids[**r], &center);
+            l.partial_cmp(&r).unwrap()
+        })
+        .unwrap();
+    candidates.retain(|candidate| {
+        *candidate == closest
+            || !is_dominated(&centroids[*candidate], &centroids[closest], envelope)
+    });
+
+    if let [centroid] = candidates[..] {
+        result.extend(RTreeIterator::new(node).map(|t| (t, centroid)));
+        return;
+    }
+    for child in &node.children {
+        match child {
+            RTreeNode::Parent(ref data) => filter(data, centroids, candidates.clone(), result),
+            RTreeNode::Leaf(ref t) => {
+                let nearest = *candidates
+                    .iter()
+                    .min_by(|l, r| {
+                        let l = PointDistance::distance_2(t, &centroids[**l]);
+                        let r = PointDistance::distance_2(t, &centroids[**r]);
+                        l.partial_cmp(&r).unwrap()
+                    })
+                    .unwrap();
+                result.push((t, nearest));
+            }
+        }
+    }
+}
+
+/// Returns `true` if `centroid` is at least as far away from every point of `envelope` as
+/// `closest`.
+///
+/// It suffices to check the corner of the envelope extending farthest towards `centroid`.
+fn is_dominated<P: Point>(centroid: &P, closest: &P, envelope: &AABB<P>) -> bool {
+    let (lower, upper) = (envelope.lower(), envelope.upper());
+    let corner = P::generate(|axis| {
+        if centroid.nth(axis) > closest.nth(axis) {
+            upper.nth(axis)
+        } else {
+            lower.nth(axis)
+        }
+    });
+    PointExt::distance_2(centroid, &corner) >= PointExt::distance_2(closest, &corner)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::point::PointExt;
+    use crate::test_utilities::{create_random_points, SEED_1, SEED_2};
+    use crate::RTree;
+
+    #[test]
+    fn test_assign_to_centroids() {
+        let points = create_random_points(2000, SEED_1);
+        let tree = RTree::bulk_load(points);
+        for k in [1, 2, 7, 40] {
+            let centroids = create_random_points(k, SEED_2);
+            let assignment = tree.assign_to_centroids(&centroids);
+            assert_eq!(assignment.len(), 2000);
+            for (point, centroid) in assignment {
+                let nearest = centroids
+                    .iter()
+                    .map(|c| c.distance_2(point))
+                    .fold(f64::INFINITY, f64::min);
+                assert_eq!(centroids[centroid].distance_2(point), nearest);
+            }
+        }
+        assert!(tree.assign_to_centroids(&[]).is_empty());
+    }
+}
@@ -0,0 +1,186 @@
+use crate::node::{ParentNode, RTreeNode};
+use crate::params::RTreeParams;
+use crate::point::Point;
+use crate::{Envelope, PointDistance, RTree, RTreeObject, AABB};
+
+use alloc::collections::BTreeMap;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+
+impl<T, P, Params> RTree<T, Params>
+where
+    T: PointDistance + RTreeObject<Envelope = AABB<P>>,
+    P: Point,
+    Params: RTreeParams,
+{
+    /// Returns the squared distance from every element to its `k`-th nearest other element,
+    /// e.g. to compute outlier scores.
+    ///
+    /// The results are ordered like the elements returned by [RTree::iter]. The position of
+    /// an element is the center of its envelope, which is the element itself for points.
+    /// An element is never its own neighbor, but identical copies of it are. Elements without
+    /// `k` other elements return `None`.
+    ///
+    /// Instead of a nearest neighbor query per element, all elements of a leaf node are
+    /// searched together in a single traversal of the tree, pruning nodes which are farther
+    /// away from the leaf node than the `k`-th nearest neighbor of all of its elements.
+    ///
+    /// # Panics
+    /// Panics if `k` is zero.
+    ///
+    /// # Example
+    /// ```
+    /// use rstar::RTree;
+    ///
+    /// let tree = RTree::bulk_load(vec![[0.0, 0.0], [1.0, 0.0], [3.0, 0.0], [10.0, 0.0]]);
+    /// let mut distances: Vec<_> = tree
+    ///     .iter()
+    ///     .zip(tree.kth_nearest_neighbor_distances_2(2))
+    ///     .map(|(point, distance_2)| (point[0], distance_2.unwrap()))
+    ///     .collect();
+    /// distances.sort_by(|l, r| l.partial_cmp(r).unwrap());
+    /// assert_eq!(distances, vec![(0.0, 9.0), (1.0, 4.0), (3.0, 9.0), (10.0, 81.0)]);
+    /// ```
+    pub fn kth_nearest_neighbor_distances_2(&self, k: usize) -> Vec<Option<P::Scalar>> {
+        assert!(k > 0, "k must be positive");
+        let indices: BTreeMap<_, _> = self
+            .iter()
+            .enumerate()
+            .map(|(index, t)| (t as *const T, index))
+            .collect();
+        let mut result = vec![None; self.size()];
+        let mut leaf_nodes = Vec::new();
+        collect_leaf_nodes(self.root(), &mut leaf_nodes);
+        for node in leaf_nodes {
+            let mut block = Block {
+                envelope: node.envelope.clone(),
+                queries: node
+                    .children
+                    .iter()
+                    .filter_map(|child| match child {
+                        RTreeNode::Leaf(t) => Some((t, t.envelope().center(), Vec::new())),
+                        RTreeNode::Parent(_) => None,
+                    })
+                    .collect(),
+                k,
+                bound: None,
+            };
+            block.search(self.root());
+            for (t, _, nearest) in block.queries {
+                result[indices[&(t as *const T)]] = nearest.get(k - 1).cloned();
+            }
+        }
+        result
+    }
+}
+
+/// Appends all parent nodes whose children are elements.
+fn collect_leaf_nodes<'a, T>(node: &'a ParentNode<T>, result: &mut Vec<&'a ParentNode<T>>)
+where
+    T: RTreeObject,
+{
+    match node.children.first() {
+        Some(RTreeNode::Parent(_)) => {
+            for child in &node.children {
+                if let RTreeNode::Parent(ref data) = child {
+                    collect_leaf_nodes(data, result);
+                }
+            }
+        }
+        Some(RTreeNode::Leaf(_)) => result.push(node),
+        None => {}
+    }
+}
+
+/// The elements of a leaf node and the squared distances to their nearest neighbors found so
+/// far.
+struct Block<'a, T, P>
+where
+    P: Point,
+{
+    envelope: AABB<P>,
+    /// Every element, its position and the ascending distances to its nearest neighbors.
+    queries: Vec<(&'a T, P, Vec<P::Scalar>)>,
+    k: usize,
+    /// The largest distance to a `k`-th nearest neighbor, `None` if not all elements have
+    /// `k` neighbors yet.
+    bound: Option<P::Scalar>,
+}
+
+impl<'a, T, P> Block<'a, T, P>
+where
+    T: PointDistance + RTreeObject<Envelope = AABB<P>>,
+    P: Point,
+{
+    fn search(&mut self, node: &ParentNode<T>) {
+        let mut children: Vec<_> = node
+            .children
+            .iter()
+            .map(|child| (child.envelope().distance_2_to_aabb(&self.envelope), child))
+            .collect();
+        children.sort_by(|(l, _), (r, _)| l.partial_cmp(r).unwrap_or(Ordering::Equal));
+        for (distance_2, child) in children {
+            if matches!(self.bound, Some(bound) if distance_2 > bound) {
+                return;
+            }
+            match child {
+                RTreeNode::Parent(ref data) => self.search(data),
+                RTreeNode::Leaf(ref neighbor) => self.add_neighbor(neighbor),
+            }
+        }
+    }
+
+    fn add_neighbor(&mut self, neighbor: &T) {
+        let k = self.k;
+        for (t, position, nearest) in &mut self.queries {
+            if core::ptr::eq(*t, neighbor) {
+                continue;
+            }
+            let distance_2 = neighbor.distance_2(position);
+            if nearest.len() == k && distance_2 >= nearest[k - 1] {
+                continue;
+            }
+            let index = nearest.partition_point(|other| *other <= distance_2);
+            nearest.insert(index, distance_2);
+            nearest.truncate(k);
+        }
+        let mut bound = None;
+        for (_, _, nearest) in &self.queries {
+            match nearest.get(k - 1) {
+                Some(distance_2) if bound.map_or(true, |bound| *distance_2 > bound) => {
+                    bound = Some(*distance_2)
+                }
+                Some(_) => {}
+                None => return,
+            }
+        }
+        self.bound = bound;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::point::PointExt;
+    use crate::test_utilities::{create_random_points, SEED_1};
+    use crate::RTree;
+
+    #[test]
+    fn test_kth_nearest_neighbor_distances_2() {
+        let mut points = create_random_points(500, SEED_1);
+        points.push(points[0]);
+        let tree = RTree::bulk_load(points);
+        for k in [1, 3, 10] {
+            let distances = tree.kth_nearest_neighbor_distances_2(k);
+            assert_eq!(distances.len(), 501);
+            for (point, distance_2) in tree.iter().zip(distances) {
+                let mut expected: Vec<_> = tree.iter().map(|p| p.distance_2(point)).collect();
+                expected.sort_by(|l, r| l.partial_cmp(r).unwrap());
+                // The first distance is the point itself.
+                assert_eq!(distance_2, Some(expected[k]));
+            }
+        }
+        let small = RTree::bulk_load(vec![[0.0, 0.0], [1.0, 1.0]]);
+        assert_eq!(small.kth_nearest_neighbor_distances_2(2), vec![None, None]);
+    }
+}
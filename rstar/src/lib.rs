@@ -19,33 +19,201 @@
 //! # (De)Serialization
 //! Enable the `serde` feature for [Serde](https://crates.io/crates/serde) support.
 //!
+//! # Parallel iteration
+//! Enable the `rayon` feature for parallel iterators based on
+//! [Rayon](https://crates.io/crates/rayon).
+//!
+//! # Sampling
+//! Enable the `sampling` feature to draw uniform random samples of a tree's elements with
+//! [rand](https://crates.io/crates/rand), e.g. [RTree::sample].
+//!
+//! # Test utilities
+//! Enable the `test-utilities` feature to generate the random points, lines and rectangles
+//! used by this crate's own tests in downstream benchmarks and tests, see
+//! [test_utilities](crate::test_utilities).
+//!
+//! # WebAssembly
+//! The crate builds for `wasm32-unknown-unknown` without additional features. Trees of
+//! points can be created from and queried into flat coordinate slices, e.g.
+//! [RTree::bulk_load_flat], which map directly to JavaScript typed arrays.
+//! [FlatPointRTree] additionally keeps the coordinates and answers queries with indices into
+//! them, which suits bindings to array based languages such as Python with numpy.
+//!
+//! # C interface
+//! Enable the `ffi` feature for a C interface to trees of points with ids, see
+//! [ffi](crate::ffi). This is the only part of the crate using unsafe code.
+//!
+//! # Physics
+//! Enable the `parry` feature to insert bounding boxes and positioned shapes of the
+//! [parry](https://parry.rs) collision detection library into trees, e.g. to use a tree as
+//! broad phase, see [parry](crate::parry).
+//!
+//! # Fuzzing
+//! Enable the `arbitrary` feature to generate random trees, primitives and query envelopes with
+//! [arbitrary](https://crates.io/crates/arbitrary), e.g. for fuzzing code built on this crate.
+//!
 #![deny(missing_docs)]
-#![forbid(unsafe_code)]
+#![cfg_attr(not(feature = "ffi"), forbid(unsafe_code))]
+#![cfg_attr(feature = "ffi", deny(unsafe_code))]
 #![cfg_attr(not(test), no_std)]
 
 extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
 
 mod aabb;
+mod aggregate;
 mod algorithm;
+#[cfg(feature = "std")]
+mod append_log;
+mod arena;
+#[cfg(feature = "std")]
+mod binary;
+mod bounded;
+mod broad_phase;
+mod budget;
+mod capacity;
+mod clearance;
+#[cfg(feature = "concurrent")]
+mod concurrent;
+mod cow;
+mod density;
+mod distance_field;
+#[cfg(feature = "debug")]
+mod dot;
 mod envelope;
+mod estimate;
+#[cfg(feature = "std")]
+mod external;
+mod fat;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+mod flat;
+mod flatten;
+mod forest;
+mod frozen;
+#[cfg(feature = "arbitrary")]
+mod fuzzing;
+mod geo_aabb;
+mod histogram;
+mod hull;
+mod interpolate;
+mod kmeans;
+mod kth_neighbor;
+mod memory;
+mod moving;
 mod node;
 mod object;
+mod occupancy;
+mod packed;
+pub mod packed_index;
+#[cfg(feature = "std")]
+mod paged;
+#[cfg(feature = "rayon")]
+mod parallel;
 mod params;
+#[cfg(any(feature = "parry2d", feature = "parry3d"))]
+pub mod parry;
+mod periodic;
 mod point;
 pub mod primitives;
+mod projected;
+mod quality;
+mod query_cache;
 mod rtree;
+#[cfg(feature = "sampling")]
+mod sample;
+mod sanity;
+mod spatial_reference;
+mod static_tree;
+mod stats;
+mod structural;
+#[cfg(feature = "debug-render")]
+mod svg;
+mod sweep;
+mod temporal;
+mod tile;
+#[cfg(feature = "trace")]
+mod trace;
+mod trajectory;
+pub mod tuning;
+mod visitor;
+mod watcher;
+pub mod wkt;
 
-#[cfg(test)]
-mod test_utilities;
+#[cfg(any(test, feature = "test-utilities"))]
+pub mod test_utilities;
 
 pub use crate::aabb::AABB;
-pub use crate::algorithm::rstar::RStarInsertionStrategy;
-pub use crate::algorithm::selection_functions::SelectionFunction;
+pub use crate::aggregate::{AggregateRTree, Aggregator, MaxImportance};
+pub use crate::algorithm::nearest_neighbor::NearestNeighborState;
+pub use crate::algorithm::rstar::{
+    CustomInsertionStrategy, MinimalOverlapInsertionStrategy, RStarInsertionStrategy,
+    RStarSplitStrategy, SplitOnlyInsertionStrategy,
+};
+pub use crate::algorithm::selection_functions::{
+    SelectAllFunc, SelectAndFunction, SelectAtPointFunction, SelectFromFns,
+    SelectInEnvelopeFuncIntersecting, SelectInEnvelopeFuncIntersectingExact,
+    SelectInEnvelopeFunction, SelectIntersectingRay, SelectIntersectingSwept,
+    SelectNearSegmentFunction, SelectNotFunction, SelectOrFunction, SelectOutsideEnvelope,
+    SelectWithinDistanceFunction, SelectionFunction,
+};
+pub use crate::algorithm::split::{LinearSplitStrategy, QuadraticSplitStrategy};
+pub use crate::algorithm::weighted_nearest_neighbor::{
+    AdditiveWeight, MultiplicativeWeight, Weighting,
+};
+#[cfg(feature = "std")]
+pub use crate::append_log::{LogReplay, LoggedRTree};
+pub use crate::arena::ArenaRTree;
+#[cfg(feature = "std")]
+pub use crate::binary::ReadTreeError;
+pub use crate::bounded::StackDepthExceeded;
+pub use crate::broad_phase::{BroadPhase, PairChanges};
+pub use crate::budget::{BudgetedResult, QueryBudget};
+pub use crate::capacity::CapacityExceeded;
+#[cfg(feature = "concurrent")]
+pub use crate::concurrent::ConcurrentRTree;
+pub use crate::cow::CowRTree;
 pub use crate::envelope::Envelope;
+pub use crate::estimate::CountEstimate;
+#[cfg(feature = "std")]
+pub use crate::external::TempStorage;
+pub use crate::fat::FatRTree;
+pub use crate::flat::FlatPointRTree;
+pub use crate::flatten::{FlattenedRTree, FlattenedRTreeError};
+pub use crate::forest::SpatialForest;
+pub use crate::frozen::{FrozenRTree, RTreeSnapshot};
+#[cfg(feature = "arbitrary")]
+pub use crate::fuzzing::ArbitraryElement;
+pub use crate::geo_aabb::GeoAABB;
+pub use crate::memory::MemoryUsage;
+pub use crate::moving::{MovingPoint, MovingRTree};
 pub use crate::node::{ParentNode, RTreeNode};
-pub use crate::object::{PointDistance, RTreeObject};
-pub use crate::params::{DefaultParams, InsertionStrategy, RTreeParams};
+pub use crate::object::{ExactPredicate, PointDistance, RTreeObject};
+pub use crate::occupancy::{OccupancyReport, SparseSubtree};
+pub use crate::packed::{NodeStorage, PackedElement, PackedRTree, PackedRTreeError, PackedScalar};
+#[cfg(feature = "std")]
+pub use crate::paged::PagedStorage;
+pub use crate::params::{
+    check_parameters, ConstParams, DefaultParams, InsertionHeuristic, InsertionStrategy,
+    OverflowTreatment, ParamsError, RTreeParams, SplitStrategy,
+};
+pub use crate::periodic::{PeriodicAABB, PeriodicDomain};
 pub use crate::point::{Point, RTreeNum};
-pub use crate::rtree::RTree;
+pub use crate::projected::{ProjectedRTree, Projection};
+pub use crate::quality::{LevelQuality, TreeQuality};
+pub use crate::query_cache::CachedRTree;
+pub use crate::rtree::{HeightLimitExceeded, RTree};
+pub use crate::sanity::InvariantViolation;
+pub use crate::spatial_reference::{SpatialReference, SpatialReferenceMismatch};
+pub use crate::static_tree::StaticRTree;
+#[cfg(feature = "stats")]
+pub use crate::stats::QueryStats;
+pub use crate::temporal::{TemporalPoint, Timestamp};
+pub use crate::tile::Tile;
+#[cfg(feature = "trace")]
+pub use crate::trace::{QueryTrace, TraceStep, TraceStepKind};
+pub use crate::visitor::TreeVisitor;
+pub use crate::watcher::{RegionEvent, Watcher};
 
 pub use crate::algorithm::iterators;
@@ -0,0 +1,140 @@
+use crate::node::{Children, ParentNode, RTreeNode};
+use crate::params::RTreeParams;
+use crate::{RTree, RTreeObject};
+use alloc::vec::Vec;
+use core::mem::size_of;
+
+/// Describes the memory occupied by an [RTree], in bytes.
+///
+/// Returned by [RTree::memory_usage]. Elements are stored inline in the tree's nodes, any
+/// heap memory owned by the elements themselves is not accounted for.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MemoryUsage {
+    /// Memory used by the tree's internal nodes.
+    pub nodes: usize,
+    /// Memory reserved for node children but currently unused.
+    ///
+    /// Nodes reserve space for additional children to make insertion cheaper. This
+    /// overhead can be trimmed with [RTree::shrink_to_fit], except for the few children every
+    /// node stores without a separate allocation.
+    pub unused_capacity: usize,
+    /// Memory used by the tree's elements.
+    pub elements: usize,
+}
+
+impl MemoryUsage {
+    /// Returns the total number of bytes used by the tree.
+    pub fn total(&self) -> usize {
+        self.nodes + self.unused_capacity + self.elements
+    }
+}
+
+impl<T, Params> RTree<T, Params>
+where
+    T: RTreeObject,
+    Params: RTreeParams,
+{
+    /// Returns the number of bytes occupied by this tree.
+    ///
+    /// # Example
+    /// ```
+    /// use rstar::RTree;
+    ///
+    /// let mut tree = RTree::with_capacity(1000);
+    /// for i in 0..100 {
+    ///     tree.insert([i as f64, 0.0]);
+    /// }
+    /// let usage = tree.memory_usage();
+    /// assert!(usage.elements >= 100 * std::mem::size_of::<[f64; 2]>());
+    ///
+    /// tree.shrink_to_fit();
+    /// assert!(tree.memory_usage().unused_capacity < usage.unused_capacity);
+    /// assert!(tree.memory_usage().total() < usage.total());
+    /// ```
+    pub fn memory_usage(&self) -> MemoryUsage {
+        let slot = size_of::<RTreeNode<T>>();
+        let mut usage = MemoryUsage {
+            nodes: size_of::<ParentNode<T>>(),
+            ..Default::default()
+        };
+        let mut stack = Vec::new();
+        stack.push(self.root());
+        while let Some(node) = stack.pop() {
+            usage.nodes += Children::<T>::overhead();
+            usage.unused_capacity += node.children.unused_slots() * slot;
+            for child in &node.children {
+                match child {
+                    RTreeNode::Leaf(_) => usage.elements += slot,
+                    RTreeNode::Parent(ref parent) => {
+                        usage.nodes += slot;
+                        stack.push(parent);
+                    }
+                }
+            }
+        }
+        for children in &self.spare_nodes().children {
+            usage.unused_capacity += Children::<T>::overhead() + children.unused_slots() * slot;
+        }
+        usage
+    }
+
+    /// Releases the unused capacity of all nodes, as well as the nodes reserved by
+    /// [RTree::reserve].
+    ///
+    /// Useful after removing many elements or after building a tree by repeated insertion.
+    /// Nodes need to reallocate their children when elements are inserted afterwards. Nodes
+    /// with few enough children move them back into their inline storage.
+    pub fn shrink_to_fit(&mut self) {
+        let (root, spare_nodes) = self.root_and_spare_nodes_mut();
+        spare_nodes.children = Vec::new();
+        let mut stack = Vec::new();
+        stack.push(root);
+        while let Some(node) = stack.pop() {
+            node.children.shrink_to_fit();
+            for child in &mut node.children {
+                if let RTreeNode::Parent(ref mut parent) = child {
+                    stack.push(parent);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_utilities::{create_random_points, SEED_1};
+    use crate::{RStarInsertionStrategy, RTree, RTreeParams};
+
+    /// Nodes with more children than are stored inline.
+    struct LargeNodes;
+
+    impl RTreeParams for LargeNodes {
+        const MIN_SIZE: usize = 5;
+        const MAX_SIZE: usize = 16;
+        const REINSERTION_COUNT: usize = 3;
+        type DefaultInsertionStrategy = RStarInsertionStrategy;
+    }
+
+    #[test]
+    fn test_shrink_to_fit() {
+        let points = create_random_points(1000, SEED_1);
+        let mut tree: RTree<_, LargeNodes> = RTree::new_with_params();
+        for point in &points {
+            tree.insert(*point);
+        }
+        for point in &points[..500] {
+            tree.remove(point);
+        }
+        let usage = tree.memory_usage();
+        assert!(usage.unused_capacity > 0);
+        tree.shrink_to_fit();
+        let shrunk = tree.memory_usage();
+        assert!(shrunk.unused_capacity < usage.unused_capacity);
+        for (_, node) in tree.iter_nodes_breadth_first() {
+            assert!(!node.children.spilled() || node.children.capacity() == node.children.len());
+        }
+        assert_eq!(shrunk.elements, usage.elements);
+        assert_eq!(shrunk.nodes, usage.nodes);
+        assert!(points[500..].iter().all(|point| tree.contains(point)));
+    }
+}
@@ -0,0 +1,351 @@
+use crate::algorithm::selection_functions::SelectFromFns;
+use crate::params::{DefaultParams, RTreeParams};
+use crate::point::{Point, PointExt};
+use crate::{Envelope, RTree, RTreeObject, AABB};
+
+use alloc::vec::Vec;
+use num_traits::Float;
+
+/// A point moving with a constant velocity, stored in a [MovingRTree].
+#[derive(Clone, Debug, PartialEq)]
+pub struct MovingPoint<P>
+where
+    P: Point,
+{
+    position: P,
+    velocity: P,
+    time: P::Scalar,
+}
+
+impl<P> MovingPoint<P>
+where
+    P: Point,
+{
+    /// Creates a point that is located at `position` at the given time and moves by
+    /// `velocity` per unit of time.
+    pub fn new(position: P, velocity: P, time: P::Scalar) -> Self {
+        MovingPoint {
+            position,
+            velocity,
+            time,
+        }
+    }
+
+    /// Returns the point's velocity.
+    pub fn velocity(&self) -> P {
+        self.velocity.clone()
+    }
+
+    /// Returns the point's position at a given time.
+    pub fn position_at(&self, time: P::Scalar) -> P {
+        self.position.add(&self.velocity.mul(time - self.time))
+    }
+}
+
+/// A moving point together with the envelope it sweeps during the tree's time window.
+#[derive(Clone, Debug, PartialEq)]
+struct SweptPoint<P>
+where
+    P: Point,
+{
+    point: MovingPoint<P>,
+    envelope: AABB<P>,
+}
+
+impl<P> SweptPoint<P>
+where
+    P: Point,
+{
+    fn new(point: MovingPoint<P>, start: P::Scalar, end: P::Scalar) -> Self {
+        let envelope = AABB::from_corners(point.position_at(start), point.position_at(end));
+        SweptPoint { point, envelope }
+    }
+}
+
+impl<P> RTreeObject for SweptPoint<P>
+where
+    P: Point,
+{
+    type Envelope = AABB<P>;
+
+    fn envelope(&self) -> AABB<P> {
+        self.envelope.clone()
+    }
+}
+
+/// An r-tree of points moving with constant velocities.
+///
+/// Instead of rebuilding a tree of current positions every time step, the tree stores the
+/// envelope every point sweeps during a time window, similar to a TPR-tree. Queries take a
+/// time instant or interval and only return points whose actual position matches at that
+/// time. Queries within the time window prune subtrees like regular queries, queries outside
+/// of it are still correct but need to check every point. Use [MovingRTree::advance] to move
+/// the window along as time passes.
+///
+/// Points that change their velocity need to be removed and inserted again.
+///
+/// # Example
+/// ```
+/// use rstar::{MovingPoint, MovingRTree};
+///
+/// let mut tree = MovingRTree::bulk_load(
+///     0.0,
+///     10.0,
+///     vec![
+///         MovingPoint::new([0.0, 0.0], [1.0, 0.0], 0.0),
+///         MovingPoint::new([10.0, 0.0], [-1.0, 0.0], 0.0),
+///     ],
+/// );
+/// // Both points meet at [5.0, 0.0] at time 5.0.
+/// assert_eq!(tree.locate_within_distance_at(&[5.0, 0.0], 0.1, 5.0).count(), 2);
+/// assert_eq!(tree.locate_within_distance_during(&[5.0, 0.0], 0.1, 0.0, 4.0).count(), 0);
+/// assert_eq!(tree.locate_within_distance_during(&[2.0, 0.0], 0.1, 0.0, 4.0).count(), 1);
+///
+/// // Moves the time window to [12.0, 22.0].
+/// assert!(tree.advance(12.0));
+/// assert_eq!(tree.time_window(), (12.0, 22.0));
+/// ```
+pub struct MovingRTree<P, Params = DefaultParams>
+where
+    P: Point,
+    Params: RTreeParams,
+{
+    tree: RTree<SweptPoint<P>, Params>,
+    start: P::Scalar,
+    horizon: P::Scalar,
+}
+
+impl<P> MovingRTree<P>
+where
+    P: Point,
+    P::Scalar: Float,
+{
+    /// Creates a new, empty tree whose time window starts at `start` and lasts for `horizon`.
+    pub fn new(start: P::Scalar, horizon: P::Scalar) -> Self {
+        Self::new_with_params(start, horizon)
+    }
+
+    /// Creates a new tree from a set of moving points.
+    ///
+    /// See [RTree::bulk_load].
+    pub fn bulk_load(start: P::Scalar, horizon: P::Scalar, points: Vec<MovingPoint<P>>) -> Self {
+        Self::bulk_load_with_params(start, horizon, points)
+    }
+}
+
+impl<P, Params> MovingRTree<P, Params>
+where
+    P: Point,
+    P::Scalar: Float,
+    Params: RTreeParams,
+{
+    /// Creates a new, empty tree with custom parameters.
+    pub fn new_with_params(start: P::Scalar, horizon: P::Scalar) -> Self {
+        MovingRTree {
+            tree: RTree::new_with_params(),
+            start,
+            horizon,
+        }
+    }
+
+    /// Creates a new tree from a set of moving points with custom parameters.
+    pub fn bulk_load_with_params(
+        start: P::Scalar,
+        horizon: P::Scalar,
+        points: Vec<MovingPoint<P>>,
+    ) -> Self {
+        let end = start + horizon;
+        let swept = points
+            .into_iter()
+            .map(|point| SweptPoint::new(point, start, end))
+            .collect();
+        MovingRTree {
+            tree: RTree::bulk_load_with_params(swept),
+            start,
+            horizon,
+        }
+    }
+
+    /// Returns the start and end of the time window.
+    pub fn time_window(&self) -> (P::Scalar, P::Scalar) {
+        (self.start, self.start + self.horizon)
+    }
+
+    /// Moves the time window to start at `time` if `time` lies past its end.
+    ///
+    /// This rebuilds the whole tree, but only once per time window instead of every time step.
+    /// Returns `true` if the tree was rebuilt.
+    pub fn advance(&mut self, time: P::Scalar) -> bool {
+        if time <= self.start + self.horizon {
+            return false;
+        }
+        self.rebuild(time);
+        true
+    }
+
+    /// Rebuilds the tree for a time window starting at `start`.
+    pub fn rebuild(&mut self, start: P::Scalar) {
+        let end = start + self.horizon;
+        let swept = self
+            .tree
+            .drain()
+            .map(|swept| SweptPoint::new(swept.point, start, end))
+            .collect();
+        self.tree = RTree::bulk_load_with_params(swept);
+        self.start = start;
+    }
+
+    /// Returns the number of points in the tree.
+    pub fn size(&self) -> usize {
+        self.tree.size()
+    }
+
+    /// Returns all points of the tree, in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = &MovingPoint<P>> + '_ {
+        self.tree.iter().map(|swept| &swept.point)
+    }
+
+    /// Inserts a moving point.
+    pub fn insert(&mut self, point: MovingPoint<P>) {
+        let (start, end) = self.time_window();
+        self.tree.insert(SweptPoint::new(point, start, end));
+    }
+
+    /// Removes a moving point and returns it.
+    pub fn remove(&mut self, point: &MovingPoint<P>) -> Option<MovingPoint<P>> {
+        let (start, end) = self.time_window();
+        let removed = self
+            .tree
+            .remove(&SweptPoint::new(point.clone(), start, end));
+        removed.map(|swept| swept.point)
+    }
+
+    fn covers(&self, from: P::Scalar, to: P::Scalar) -> bool {
+        let (start, end) = self.time_window();
+        start <= from && to <= end
+    }
+
+    /// Returns all points located within an envelope at a given time.
+    pub fn locate_in_envelope_at(
+        &self,
+        envelope: &AABB<P>,
+        time: P::Scalar,
+    ) -> impl Iterator<Item = &MovingPoint<P>> + '_ {
+        let covered = self.covers(time, time);
+        let parent_envelope = envelope.clone();
+        let leaf_envelope = envelope.clone();
+        let func = SelectFromFns::new(
+            move |node: &AABB<P>| !covered || node.intersects(&parent_envelope),
+            move |swept: &SweptPoint<P>| {
+                leaf_envelope.contains_point(&swept.point.position_at(time))
+            },
+        );
+        self.tree
+            .locate_with_selection_function(func)
+            .map(|swept| &swept.point)
+    }
+
+    /// Returns all points within a certain distance of a point at a given time.
+    pub fn locate_within_distance_at(
+        &self,
+        query_point: &P,
+        max_squared_radius: P::Scalar,
+        time: P::Scalar,
+    ) -> impl Iterator<Item = &MovingPoint<P>> + '_ {
+        self.locate_within_distance_during(query_point, max_squared_radius, time, time)
+    }
+
+    /// Returns all points that come within a certain distance of a point at any time between
+    /// `from` and `to`.
+    pub fn locate_within_distance_during(
+        &self,
+        query_point: &P,
+        max_squared_radius: P::Scalar,
+        from: P::Scalar,
+        to: P::Scalar,
+    ) -> impl Iterator<Item = &MovingPoint<P>> + '_ {
+        let covered = self.covers(from, to);
+        let query_point = query_point.clone();
+        let query_envelope = AABB::from_point(query_point.clone());
+        let func = SelectFromFns::new(
+            move |node: &AABB<P>| !covered || node.distance_2(&query_point) <= max_squared_radius,
+            move |swept: &SweptPoint<P>| {
+                let from = swept.point.position_at(from);
+                let to = swept.point.position_at(to);
+                query_envelope.segment_distance_2(&from, &to) <= max_squared_radius
+            },
+        );
+        self.tree
+            .locate_with_selection_function(func)
+            .map(|swept| &swept.point)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{MovingPoint, MovingRTree};
+    use crate::point::PointExt;
+    use crate::test_utilities::{create_random_points, SEED_1, SEED_2};
+    use crate::{Envelope, AABB};
+
+    fn min_distance_2(point: &MovingPoint<[f64; 2]>, query: &[f64; 2], from: f64, to: f64) -> f64 {
+        (0..=1000)
+            .map(|step| from + (to - from) * step as f64 / 1000.0)
+            .map(|time| point.position_at(time).distance_2(query))
+            .fold(f64::INFINITY, f64::min)
+    }
+
+    #[test]
+    fn test_moving_queries() {
+        let velocities = create_random_points(300, SEED_2);
+        let points: Vec<_> = create_random_points(300, SEED_1)
+            .into_iter()
+            .zip(velocities)
+            .map(|(position, [vx, vy])| MovingPoint::new(position, [vx - 0.5, vy - 0.5], 0.0))
+            .collect();
+        let mut tree = MovingRTree::bulk_load(0.0, 1.0, points.clone());
+        assert_eq!(tree.size(), 300);
+
+        let query = [0.5, 0.5];
+        let envelope = AABB::from_corners([0.25, 0.25], [0.75, 0.75]);
+        for time in [0.0, 0.5, 1.0, 3.0] {
+            let expected = points
+                .iter()
+                .filter(|p| envelope.contains_point(&p.position_at(time)))
+                .count();
+            assert_eq!(
+                tree.locate_in_envelope_at(&envelope, time).count(),
+                expected
+            );
+        }
+        for (from, to) in [(0.0, 0.0), (0.2, 0.8), (0.5, 2.0)] {
+            let found = tree
+                .locate_within_distance_during(&query, 0.01, from, to)
+                .count();
+            // The sampled minimum slightly overestimates the exact one.
+            let lower = points
+                .iter()
+                .filter(|p| min_distance_2(p, &query, from, to) <= 0.0099)
+                .count();
+            let upper = points
+                .iter()
+                .filter(|p| min_distance_2(p, &query, from, to) <= 0.0101)
+                .count();
+            assert!(lower <= found && found <= upper);
+        }
+
+        assert!(!tree.advance(0.5));
+        assert!(tree.advance(1.5));
+        assert_eq!(tree.time_window(), (1.5, 2.5));
+        let expected = points
+            .iter()
+            .filter(|p| envelope.contains_point(&p.position_at(2.0)))
+            .count();
+        assert_eq!(tree.locate_in_envelope_at(&envelope, 2.0).count(), expected);
+
+        assert_eq!(tree.remove(&points[0]), Some(points[0].clone()));
+        assert_eq!(tree.remove(&points[0]), None);
+        tree.insert(points[0].clone());
+        assert_eq!(tree.iter().count(), 300);
+    }
+}
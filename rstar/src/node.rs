@@ -1,8 +1,15 @@
 use crate::envelope::Envelope;
 use crate::object::RTreeObject;
-use crate::params::RTreeParams;
+use crate::params::{DefaultParams, RTreeParams};
 
+use alloc::boxed::Box;
 use alloc::vec::Vec;
+use core::fmt;
+use core::iter::FromIterator;
+use core::mem::size_of;
+use core::ops::{Deref, DerefMut};
+use core::slice;
+use smallvec::SmallVec;
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
@@ -36,12 +43,176 @@ where
 /// node's envelope and its children.
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(from = "SerializedParentNode<T>"))]
 pub struct ParentNode<T>
 where
     T: RTreeObject,
 {
-    pub(crate) children: Vec<RTreeNode<T>>,
+    pub(crate) children: Children<T>,
     pub(crate) envelope: T::Envelope,
+    // Not serialized to keep the format compatible and to never trust sizes from untrusted
+    // input, it is recomputed from the children instead.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) size: usize,
+}
+
+/// The serialized form of a [ParentNode], without its size.
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct SerializedParentNode<T>
+where
+    T: RTreeObject,
+{
+    children: Vec<RTreeNode<T>>,
+    envelope: T::Envelope,
+}
+
+#[cfg(feature = "serde")]
+impl<T> From<SerializedParentNode<T>> for ParentNode<T>
+where
+    T: RTreeObject,
+{
+    fn from(node: SerializedParentNode<T>) -> Self {
+        ParentNode {
+            size: size_for_children(&node.children),
+            children: node.children.into(),
+            envelope: node.envelope,
+        }
+    }
+}
+
+/// The number of children a node stores inline. This fits all nodes of [DefaultParams],
+/// including the additional child of an overflowing node.
+const INLINE_CHILDREN: usize = DefaultParams::MAX_SIZE + 1;
+
+/// The children of a [ParentNode].
+///
+/// The children are stored in a single allocation made when the node is created. It only
+/// grows if a node has more than [INLINE_CHILDREN] children. The children can't be stored
+/// directly in the node, as a [ParentNode] is itself contained in an [RTreeNode].
+#[derive(Clone)]
+pub(crate) struct Children<T>(Box<SmallVec<[RTreeNode<T>; INLINE_CHILDREN]>>)
+where
+    T: RTreeObject;
+
+impl<T> Children<T>
+where
+    T: RTreeObject,
+{
+    pub(crate) fn new() -> Self {
+        Children(Box::new(SmallVec::new()))
+    }
+
+    pub(crate) fn with_capacity(capacity: usize) -> Self {
+        Children(Box::new(SmallVec::with_capacity(capacity)))
+    }
+
+    /// Returns the number of bytes the children's allocation uses besides the child slots.
+    pub(crate) fn overhead() -> usize {
+        size_of::<SmallVec<[RTreeNode<T>; INLINE_CHILDREN]>>()
+            - INLINE_CHILDREN * size_of::<RTreeNode<T>>()
+    }
+
+    /// Returns the number of allocated slots not holding a child. Once the children have moved
+    /// to the heap, the inline slots remain unused.
+    pub(crate) fn unused_slots(&self) -> usize {
+        let inline = if self.spilled() { INLINE_CHILDREN } else { 0 };
+        self.capacity() - self.len() + inline
+    }
+}
+
+impl<T> Deref for Children<T>
+where
+    T: RTreeObject,
+{
+    type Target = SmallVec<[RTreeNode<T>; INLINE_CHILDREN]>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Children<T>
+where
+    T: RTreeObject,
+{
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T> From<Vec<RTreeNode<T>>> for Children<T>
+where
+    T: RTreeObject,
+{
+    fn from(children: Vec<RTreeNode<T>>) -> Self {
+        Children(Box::new(SmallVec::from_vec(children)))
+    }
+}
+
+impl<T> FromIterator<RTreeNode<T>> for Children<T>
+where
+    T: RTreeObject,
+{
+    fn from_iter<I: IntoIterator<Item = RTreeNode<T>>>(iter: I) -> Self {
+        Children(Box::new(iter.into_iter().collect()))
+    }
+}
+
+impl<T> IntoIterator for Children<T>
+where
+    T: RTreeObject,
+{
+    type Item = RTreeNode<T>;
+    type IntoIter = smallvec::IntoIter<[RTreeNode<T>; INLINE_CHILDREN]>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Children<T>
+where
+    T: RTreeObject,
+{
+    type Item = &'a RTreeNode<T>;
+    type IntoIter = slice::Iter<'a, RTreeNode<T>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut Children<T>
+where
+    T: RTreeObject,
+{
+    type Item = &'a mut RTreeNode<T>;
+    type IntoIter = slice::IterMut<'a, RTreeNode<T>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter_mut()
+    }
+}
+
+impl<T> fmt::Debug for Children<T>
+where
+    T: RTreeObject + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T> Serialize for Children<T>
+where
+    T: RTreeObject + Serialize,
+    T::Envelope: Serialize,
+{
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
 }
 
 impl<T> RTreeObject for RTreeNode<T>
@@ -69,6 +240,14 @@ where
             RTreeNode::Parent(..) => false,
         }
     }
+
+    /// Returns the number of leaves contained in this node, including the node itself.
+    pub(crate) fn size(&self) -> usize {
+        match self {
+            RTreeNode::Leaf(..) => 1,
+            RTreeNode::Parent(ref data) => data.size,
+        }
+    }
 }
 
 impl<T> ParentNode<T>
@@ -85,20 +264,46 @@ where
         self.envelope.clone()
     }
 
+    /// Returns the number of leaves contained in this node's subtree.
+    ///
+    /// The count is maintained while inserting and removing elements and is thus
+    /// available in constant time.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
     pub(crate) fn new_root<Params>() -> Self
     where
         Params: RTreeParams,
     {
         ParentNode {
             envelope: Envelope::new_empty(),
-            children: Vec::with_capacity(Params::MAX_SIZE + 1),
+            children: Children::with_capacity(Params::MAX_SIZE.max(Params::MAX_LEAF_SIZE) + 1),
+            size: 0,
         }
     }
 
-    pub(crate) fn new_parent(children: Vec<RTreeNode<T>>) -> Self {
+    pub(crate) fn new_parent(children: Children<T>) -> Self {
         let envelope = envelope_for_children(&children);
+        let size = size_for_children(&children);
 
-        ParentNode { envelope, children }
+        ParentNode {
+            envelope,
+            children,
+            size,
+        }
+    }
+
+    /// Returns the maximum number of children of this node, which depends on whether it is a
+    /// leaf node.
+    pub(crate) fn max_size<Params>(&self) -> usize
+    where
+        Params: RTreeParams,
+    {
+        match self.children.first() {
+            Some(RTreeNode::Parent(_)) => Params::MAX_SIZE,
+            _ => Params::MAX_LEAF_SIZE,
+        }
     }
 
     #[cfg(test)]
@@ -130,7 +335,7 @@ where
         }
         let mut envelope = T::Envelope::new_empty();
         if check_max_size {
-            let max_size = Params::MAX_SIZE;
+            let max_size = self.max_size::<Params>();
             assert!(self.children.len() <= max_size);
         }
 
@@ -151,6 +356,7 @@ where
             }
         }
         assert_eq!(self.envelope, envelope);
+        assert_eq!(self.size, size_for_children(&self.children));
     }
 }
 
@@ -164,3 +370,10 @@ where
     }
     result
 }
+
+pub fn size_for_children<T>(children: &[RTreeNode<T>]) -> usize
+where
+    T: RTreeObject,
+{
+    children.iter().map(RTreeNode::size).sum()
+}
@@ -225,3 +225,47 @@ where
         }
     }
 }
+
+/// Refines envelope based queries with an exact intersection test.
+///
+/// Queries like [RTree::locate_in_envelope_intersecting](crate::RTree::locate_in_envelope_intersecting)
+/// only compare envelopes and may return objects whose geometry doesn't touch the query
+/// envelope at all, e.g. a diagonal line passing next to it. Objects implementing this trait
+/// can be queried with
+/// [RTree::locate_in_envelope_intersecting_exact](crate::RTree::locate_in_envelope_intersecting_exact)
+/// instead, which calls [ExactPredicate::intersects_envelope] for every candidate whose
+/// envelope passed the test.
+///
+/// Implementations are provided for points, [Rectangle](crate::primitives::Rectangle),
+/// [Line](crate::primitives::Line) with floating point coordinates and the wrappers
+/// [GeomWithData](crate::primitives::GeomWithData) and
+/// [CachedEnvelope](crate::primitives::CachedEnvelope).
+///
+/// # Example
+/// ```
+/// use rstar::primitives::Line;
+/// use rstar::{RTree, AABB};
+///
+/// let tree = RTree::bulk_load(vec![
+///     Line::new([0.0, 0.0], [2.0, 2.0]),
+///     Line::new([0.0, 2.0], [0.5, 1.5]),
+/// ]);
+/// let query = AABB::from_corners([1.5, 0.0], [2.0, 0.5]);
+/// assert_eq!(tree.locate_in_envelope_intersecting(&query).count(), 1);
+/// assert_eq!(tree.locate_in_envelope_intersecting_exact(&query).count(), 0);
+/// ```
+pub trait ExactPredicate: RTreeObject {
+    /// Returns `true` if the object's geometry intersects `envelope`.
+    ///
+    /// Only called if the object's envelope intersects `envelope`.
+    fn intersects_envelope(&self, envelope: &Self::Envelope) -> bool;
+}
+
+impl<P> ExactPredicate for P
+where
+    P: Point,
+{
+    fn intersects_envelope(&self, envelope: &AABB<P>) -> bool {
+        envelope.contains_point(self)
+    }
+}
@@ -0,0 +1,195 @@
+use crate::node::{ParentNode, RTreeNode};
+use crate::params::RTreeParams;
+use crate::{RTree, RTreeObject};
+
+use alloc::vec::Vec;
+
+/// A subtree with at least two leaf nodes which are on average filled below
+/// [RTreeParams::MIN_SIZE].
+#[derive(Clone, Debug, PartialEq)]
+pub struct SparseSubtree<E> {
+    /// The depth of the subtree's root, the tree's root has depth zero.
+    pub depth: usize,
+    /// The envelope of the subtree.
+    pub envelope: E,
+    /// The number of elements within the subtree.
+    pub size: usize,
+    /// The number of leaf nodes within the subtree.
+    pub leaf_nodes: usize,
+}
+
+impl<E> SparseSubtree<E> {
+    /// Returns the average number of elements per leaf node.
+    pub fn average_fill(&self) -> f64 {
+        self.size as f64 / self.leaf_nodes as f64
+    }
+}
+
+/// Describes how well the nodes of a tree are filled, returned by [RTree::occupancy].
+///
+/// A leaf node is a node whose children are elements. Removals never merge nodes, after
+/// many removals a tree may consist of mostly empty leaf nodes which makes queries slower.
+/// [OccupancyReport::sparse_subtrees] points to the regions affected, a maintenance job can
+/// use it to decide whether the tree should be rebuilt, e.g. with [RTree::bulk_load].
+///
+/// # Example
+/// ```
+/// use rstar::RTree;
+///
+/// let points: Vec<_> = (0..1000).map(|i| [(i % 40) as f64, (i / 40) as f64]).collect();
+/// let mut tree = RTree::new();
+/// for point in &points {
+///     tree.insert(*point);
+/// }
+/// assert!(tree.occupancy().sparse_subtrees().is_empty());
+/// for point in points.iter().filter(|point| point[1] < 20.0 && point[0] != 0.0) {
+///     tree.remove(point);
+/// }
+/// let report = tree.occupancy();
+/// assert!(!report.sparse_subtrees().is_empty());
+/// assert!(report.average_fill() < 10.0);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct OccupancyReport<E> {
+    histogram: Vec<usize>,
+    sparse_subtrees: Vec<SparseSubtree<E>>,
+}
+
+impl<E> OccupancyReport<E> {
+    /// Returns the number of leaf nodes by their number of children.
+    ///
+    /// The entry at index `i` counts the leaf nodes containing exactly `i` elements. The
+    /// histogram is empty for empty trees.
+    pub fn histogram(&self) -> &[usize] {
+        &self.histogram
+    }
+
+    /// Returns the number of leaf nodes.
+    pub fn leaf_nodes(&self) -> usize {
+        self.histogram.iter().sum()
+    }
+
+    /// Returns the average number of elements per leaf node, or `0.0` for empty trees.
+    pub fn average_fill(&self) -> f64 {
+        let elements: usize = self
+            .histogram
+            .iter()
+            .enumerate()
+            .map(|(children, count)| children * count)
+            .sum();
+        match self.leaf_nodes() {
+            0 => 0.0,
+            leaf_nodes => elements as f64 / leaf_nodes as f64,
+        }
+    }
+
+    /// Returns all maximal subtrees whose leaf nodes are on average filled below
+    /// [RTreeParams::MIN_SIZE].
+    ///
+    /// Only subtrees with at least two leaf nodes are considered, their elements could be
+    /// stored in fewer nodes.
+    /// Subtrees nested within a reported subtree are not reported separately. If the whole
+    /// tree is sparse, only the root is reported.
+    pub fn sparse_subtrees(&self) -> &[SparseSubtree<E>] {
+        &self.sparse_subtrees
+    }
+}
+
+impl<T, Params> RTree<T, Params>
+where
+    T: RTreeObject,
+    Params: RTreeParams,
+{
+    /// Returns a histogram of the fill of all leaf nodes and all sparse subtrees.
+    ///
+    /// See [OccupancyReport]. Visits every node once.
+    pub fn occupancy(&self) -> OccupancyReport<T::Envelope> {
+        let mut report = OccupancyReport {
+            histogram: Vec::new(),
+            sparse_subtrees: Vec::new(),
+        };
+        if self.size() > 0 {
+            collect_occupancy::<T, Params>(self.root(), 0, &mut report);
+        }
+        report
+    }
+}
+
+/// Adds all leaf nodes of a subtree to the histogram and returns the number of leaf nodes.
+///
+/// Sparse subtrees are reported top down: a node is checked after its children, which are
+/// removed from the report again if the node itself is sparse.
+fn collect_occupancy<T, Params>(
+    node: &ParentNode<T>,
+    depth: usize,
+    report: &mut OccupancyReport<T::Envelope>,
+) -> usize
+where
+    T: RTreeObject,
+    Params: RTreeParams,
+{
+    let first_reported = report.sparse_subtrees.len();
+    let mut leaf_nodes = 0;
+    let mut is_leaf_node = false;
+    for child in &node.children {
+        match child {
+            RTreeNode::Parent(ref data) => {
+                leaf_nodes += collect_occupancy::<T, Params>(data, depth + 1, report)
+            }
+            RTreeNode::Leaf(_) => is_leaf_node = true,
+        }
+    }
+    if is_leaf_node {
+        let children = node.children.len();
+        if report.histogram.len() <= children {
+            report.histogram.resize(children + 1, 0);
+        }
+        report.histogram[children] += 1;
+        leaf_nodes += 1;
+    }
+    // A single leaf node cannot be compacted any further.
+    if leaf_nodes > 1 && node.size() < Params::MIN_SIZE * leaf_nodes {
+        report.sparse_subtrees.truncate(first_reported);
+        report.sparse_subtrees.push(SparseSubtree {
+            depth,
+            envelope: node.envelope.clone(),
+            size: node.size(),
+            leaf_nodes,
+        });
+    }
+    leaf_nodes
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_utilities::{create_random_points, SEED_1};
+    use crate::{RTree, RTreeParams};
+
+    #[test]
+    fn test_occupancy() {
+        let points = create_random_points(1000, SEED_1);
+        let mut tree = RTree::bulk_load(points.clone());
+        let report = tree.occupancy();
+        let elements: usize = report
+            .histogram()
+            .iter()
+            .enumerate()
+            .map(|(children, count)| children * count)
+            .sum();
+        assert_eq!(elements, 1000);
+        assert!(report.sparse_subtrees().is_empty());
+
+        for point in &points[..990] {
+            tree.remove(point);
+        }
+        let report = tree.occupancy();
+        assert!(report.average_fill() < crate::DefaultParams::MIN_SIZE as f64);
+        assert_eq!(report.sparse_subtrees().len(), 1);
+        assert_eq!(report.sparse_subtrees()[0].depth, 0);
+        assert_eq!(report.sparse_subtrees()[0].size, 10);
+        assert_eq!(
+            RTree::<[f64; 2]>::new().occupancy().histogram(),
+            &[] as &[usize]
+        );
+    }
+}
@@ -0,0 +1,1048 @@
+use crate::algorithm::bulk_load::bulk_load_priority;
+use crate::algorithm::selection_functions::*;
+use crate::node::{ParentNode, RTreeNode};
+use crate::params::{DefaultParams, RTreeParams};
+use crate::primitives::{Line, Rectangle};
+use crate::{Envelope, Point, PointDistance, RTree, RTreeNum, RTreeObject, AABB};
+
+use alloc::collections::{BinaryHeap, VecDeque};
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::fmt;
+use core::marker::PhantomData;
+use core::ops::Range;
+use smallvec::{smallvec, SmallVec};
+
+const MAGIC: [u8; 4] = *b"RSPT";
+const VERSION: u32 = 1;
+const HEADER_SIZE: usize = 32;
+
+/// A scalar that can be stored in a [PackedRTree].
+pub trait PackedScalar: RTreeNum {
+    /// The size of an encoded scalar in bytes.
+    const SIZE: usize;
+
+    /// Writes the scalar's little endian representation to the start of `target`.
+    fn write_le(self, target: &mut [u8]);
+
+    /// Reads a scalar from the little endian representation at the start of `source`.
+    fn read_le(source: &[u8]) -> Self;
+}
+
+macro_rules! impl_packed_scalar {
+    ($($t:ty),*) => {
+        $(
+            impl PackedScalar for $t {
+                const SIZE: usize = core::mem::size_of::<$t>();
+
+                fn write_le(self, target: &mut [u8]) {
+                    target[..Self::SIZE].copy_from_slice(&self.to_le_bytes());
+                }
+
+                fn read_le(source: &[u8]) -> Self {
+                    let mut bytes = [0; core::mem::size_of::<$t>()];
+                    bytes.copy_from_slice(&source[..Self::SIZE]);
+                    <$t>::from_le_bytes(bytes)
+                }
+            }
+        )*
+    };
+}
+
+impl_packed_scalar!(i8, i16, i32, i64, i128, isize, f32, f64);
+
+/// An element that can be stored in a [PackedRTree].
+///
+/// Every element is encoded into a fixed number of bytes. This trait is implemented for all
+/// points and for [Rectangle] and [Line] if their scalar type implements [PackedScalar].
+///
+/// # Example
+/// ```
+/// use rstar::{PackedElement, RTreeObject, AABB};
+///
+/// #[derive(Clone, Copy, PartialEq, Debug)]
+/// struct City {
+///     position: [f32; 2],
+///     population: u32,
+/// }
+///
+/// impl RTreeObject for City {
+///     type Envelope = AABB<[f32; 2]>;
+///
+///     fn envelope(&self) -> Self::Envelope {
+///         AABB::from_point(self.position)
+///     }
+/// }
+///
+/// impl PackedElement for City {
+///     const SIZE: usize = 12;
+///
+///     fn write_packed(&self, target: &mut [u8]) {
+///         self.position.write_packed(target);
+///         target[8..12].copy_from_slice(&self.population.to_le_bytes());
+///     }
+///
+///     fn read_packed(source: &[u8]) -> Self {
+///         let mut population = [0; 4];
+///         population.copy_from_slice(&source[8..12]);
+///         City {
+///             position: <[f32; 2]>::read_packed(source),
+///             population: u32::from_le_bytes(population),
+///         }
+///     }
+/// }
+/// ```
+pub trait PackedElement: RTreeObject {
+    /// The size of an encoded element in bytes.
+    const SIZE: usize;
+
+    /// Writes the element to the start of `target`, which is at least [Self::SIZE] bytes long.
+    fn write_packed(&self, target: &mut [u8]);
+
+    /// Reads an element written by [PackedElement::write_packed].
+    fn read_packed(source: &[u8]) -> Self;
+}
+
+pub(crate) fn write_point<P>(point: &P, target: &mut [u8])
+where
+    P: Point,
+    P::Scalar: PackedScalar,
+{
+    let size = <P::Scalar as PackedScalar>::SIZE;
+    for axis in 0..P::DIMENSIONS {
+        point.nth(axis).write_le(&mut target[axis * size..]);
+    }
+}
+
+pub(crate) fn read_point<P>(source: &[u8]) -> P
+where
+    P: Point,
+    P::Scalar: PackedScalar,
+{
+    let size = <P::Scalar as PackedScalar>::SIZE;
+    P::generate(|axis| P::Scalar::read_le(&source[axis * size..]))
+}
+
+impl<P> PackedElement for P
+where
+    P: Point,
+    P::Scalar: PackedScalar,
+{
+    const SIZE: usize = P::DIMENSIONS * <P::Scalar as PackedScalar>::SIZE;
+
+    fn write_packed(&self, target: &mut [u8]) {
+        write_point(self, target);
+    }
+
+    fn read_packed(source: &[u8]) -> Self {
+        read_point(source)
+    }
+}
+
+impl<P> PackedElement for Rectangle<P>
+where
+    P: Point,
+    P::Scalar: PackedScalar,
+{
+    const SIZE: usize = 2 * P::SIZE;
+
+    fn write_packed(&self, target: &mut [u8]) {
+        write_point(&self.lower(), target);
+        write_point(&self.upper(), &mut target[P::SIZE..]);
+    }
+
+    fn read_packed(source: &[u8]) -> Self {
+        Rectangle::from_corners(read_point(source), read_point(&source[P::SIZE..]))
+    }
+}
+
+impl<P> PackedElement for Line<P>
+where
+    P: Point,
+    P::Scalar: PackedScalar,
+{
+    const SIZE: usize = 2 * P::SIZE;
+
+    fn write_packed(&self, target: &mut [u8]) {
+        write_point(&self.from, target);
+        write_point(&self.to, &mut target[P::SIZE..]);
+    }
+
+    fn read_packed(source: &[u8]) -> Self {
+        Line::new(read_point(source), read_point(&source[P::SIZE..]))
+    }
+}
+
+/// Random access storage containing an encoded [PackedRTree].
+///
+/// This trait is implemented for all byte buffers, i.e. for every type implementing
+/// `AsRef<[u8]>`. With the `std` feature, [PagedStorage](crate::PagedStorage) reads the tree
+/// from a file through a page cache, allowing to query trees that don't fit into memory.
+pub trait NodeStorage {
+    /// Returns the size of the storage in bytes.
+    fn size(&self) -> usize;
+
+    /// Fills `target` with the bytes starting at `offset`.
+    ///
+    /// [PackedRTree] only reads ranges that lie within [NodeStorage::size].
+    fn read(&self, offset: usize, target: &mut [u8]);
+}
+
+impl<B> NodeStorage for B
+where
+    B: AsRef<[u8]>,
+{
+    fn size(&self) -> usize {
+        self.as_ref().len()
+    }
+
+    fn read(&self, offset: usize, target: &mut [u8]) {
+        target.copy_from_slice(&self.as_ref()[offset..offset + target.len()]);
+    }
+}
+
+/// Error returned when a buffer does not contain a valid [PackedRTree].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PackedRTreeError {
+    /// The buffer does not start with the header of a packed tree.
+    InvalidHeader,
+    /// The buffer was written by an unsupported version of the format.
+    UnsupportedVersion(u32),
+    /// The dimensions or the element size stored in the header don't match the element type.
+    IncompatibleElement,
+    /// The buffer does not have the size implied by its header.
+    UnexpectedLength {
+        /// The expected length in bytes.
+        expected: usize,
+        /// The actual length in bytes.
+        found: usize,
+    },
+    /// The node with the given index refers to children that don't exist.
+    InvalidNode(usize),
+}
+
+impl fmt::Display for PackedRTreeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PackedRTreeError::InvalidHeader => write!(f, "invalid packed tree header"),
+            PackedRTreeError::UnsupportedVersion(version) => {
+                write!(f, "unsupported packed tree version {}", version)
+            }
+            PackedRTreeError::IncompatibleElement => {
+                write!(f, "packed tree was written for a different element type")
+            }
+            PackedRTreeError::UnexpectedLength { expected, found } => write!(
+                f,
+                "expected a packed tree of {} bytes, found {} bytes",
+                expected, found
+            ),
+            PackedRTreeError::InvalidNode(index) => write!(f, "invalid node {}", index),
+        }
+    }
+}
+
+/// A read-only r-tree stored in a single, flat buffer.
+///
+/// All nodes and elements are encoded into one byte buffer and refer to each other by index,
+/// there are no pointers. The buffer can be written to a file and later be queried directly,
+/// e.g. through a memory map, without loading or decoding the whole tree: queries only read
+/// the nodes they visit. Elements are decoded when they are returned, which is why queries
+/// return elements by value.
+///
+/// The buffer can be of any type implementing `AsRef<[u8]>`, e.g. a `Vec<u8>`, a byte slice or
+/// a memory map like `memmap2::Mmap`. Elements need to implement [PackedElement].
+///
+/// # Format
+/// All values are stored in little endian byte order. The buffer starts with a 32 byte
+/// header: the magic bytes `RSPT`, the format version (`u32`), the number of dimensions
+/// (`u32`), the element size in bytes (`u32`), the number of nodes (`u64`) and the number of
+/// elements (`u64`).
+///
+/// The nodes follow in level order, starting with the root. Every node stores the lower and
+/// upper corner of its envelope, the index of its first child (`u64`), its number of
+/// children (`u32`) and a flag (`u32`) that is `1` if its children are elements. All children
+/// of a node are stored consecutively. The elements follow after the last node.
+///
+/// # Example
+/// ```
+/// use rstar::{PackedRTree, RTree, AABB};
+///
+/// let tree = RTree::bulk_load(vec![[0.0, 0.0], [1.0, 1.0], [2.0, 0.5]]);
+/// let bytes = PackedRTree::from(&tree).into_inner();
+///
+/// // The bytes could now be written to a file and memory mapped later.
+/// let packed = PackedRTree::<[f64; 2], _>::from_bytes(bytes.as_slice()).unwrap();
+/// assert_eq!(packed.size(), 3);
+/// assert_eq!(packed.nearest_neighbor(&[1.8, 0.4]), Some([2.0, 0.5]));
+/// let query = AABB::from_corners([0.5, 0.5], [2.5, 2.5]);
+/// assert_eq!(packed.locate_in_envelope(&query).count(), 2);
+/// ```
+pub struct PackedRTree<T, S = Vec<u8>>
+where
+    T: RTreeObject,
+{
+    storage: S,
+    node_count: usize,
+    element_count: usize,
+    _element: PhantomData<T>,
+}
+
+struct PackedNode<P>
+where
+    P: Point,
+{
+    envelope: AABB<P>,
+    children: Range<usize>,
+    has_leaf_children: bool,
+}
+
+fn node_size<P>() -> usize
+where
+    P: Point,
+    P::Scalar: PackedScalar,
+{
+    2 * P::SIZE + 16
+}
+
+fn read_u32(source: &[u8]) -> u32 {
+    let mut bytes = [0; 4];
+    bytes.copy_from_slice(&source[..4]);
+    u32::from_le_bytes(bytes)
+}
+
+fn read_u64(source: &[u8]) -> u64 {
+    let mut bytes = [0; 8];
+    bytes.copy_from_slice(&source[..8]);
+    u64::from_le_bytes(bytes)
+}
+
+fn write_node<P>(target: &mut Vec<u8>, envelope: &AABB<P>, first: usize, count: usize, leaf: bool)
+where
+    P: Point,
+    P::Scalar: PackedScalar,
+{
+    let start = target.len();
+    target.resize(start + 2 * P::SIZE, 0);
+    write_point(&envelope.lower(), &mut target[start..]);
+    write_point(&envelope.upper(), &mut target[start + P::SIZE..]);
+    target.extend_from_slice(&(first as u64).to_le_bytes());
+    target.extend_from_slice(&(count as u32).to_le_bytes());
+    target.extend_from_slice(&(leaf as u32).to_le_bytes());
+}
+
+pub(crate) fn write_header<T, P>(target: &mut Vec<u8>, node_count: u64, element_count: u64)
+where
+    T: PackedElement<Envelope = AABB<P>>,
+    P: Point,
+    P::Scalar: PackedScalar,
+{
+    target.extend_from_slice(&MAGIC);
+    target.extend_from_slice(&VERSION.to_le_bytes());
+    target.extend_from_slice(&(P::DIMENSIONS as u32).to_le_bytes());
+    target.extend_from_slice(&(T::SIZE as u32).to_le_bytes());
+    target.extend_from_slice(&node_count.to_le_bytes());
+    target.extend_from_slice(&element_count.to_le_bytes());
+}
+
+/// Encodes a tree, returns the encoded tree and its number of nodes.
+fn pack<T, P>(root: &ParentNode<T>, size: usize) -> (Vec<u8>, usize)
+where
+    T: PackedElement<Envelope = AABB<P>>,
+    P: Point,
+    P::Scalar: PackedScalar,
+{
+    let mut nodes = Vec::new();
+    let mut elements = Vec::with_capacity(size * T::SIZE);
+    let mut node_count = 0;
+    let mut next_node = 1;
+    let mut queue = VecDeque::new();
+    queue.push_back(root);
+    while let Some(node) = queue.pop_front() {
+        let count = node.children.len();
+        if let Some(RTreeNode::Parent(_)) = node.children.first() {
+            write_node(&mut nodes, &node.envelope, next_node, count, false);
+            next_node += count;
+            queue.extend(node.children.iter().map(|child| match child {
+                RTreeNode::Parent(data) => data,
+                RTreeNode::Leaf(_) => unreachable!("Unbalanced tree. This is a bug in rstar."),
+            }));
+        } else {
+            write_node(
+                &mut nodes,
+                &node.envelope,
+                elements.len() / T::SIZE,
+                count,
+                true,
+            );
+            for child in &node.children {
+                if let RTreeNode::Leaf(t) = child {
+                    let start = elements.len();
+                    elements.resize(start + T::SIZE, 0);
+                    t.write_packed(&mut elements[start..]);
+                }
+            }
+        }
+        node_count += 1;
+    }
+
+    let mut result = Vec::with_capacity(HEADER_SIZE + nodes.len() + elements.len());
+    write_header::<T, P>(&mut result, node_count as u64, size as u64);
+    result.extend_from_slice(&nodes);
+    result.extend_from_slice(&elements);
+    (result, node_count)
+}
+
+impl<'a, T, P, Params> From<&'a RTree<T, Params>> for PackedRTree<T>
+where
+    T: PackedElement<Envelope = AABB<P>>,
+    P: Point,
+    P::Scalar: PackedScalar,
+    Params: RTreeParams,
+{
+    fn from(tree: &'a RTree<T, Params>) -> Self {
+        let (storage, node_count) = pack(tree.root(), tree.size());
+        PackedRTree {
+            storage,
+            node_count,
+            element_count: tree.size(),
+            _element: PhantomData,
+        }
+    }
+}
+
+impl<T, P> PackedRTree<T>
+where
+    T: PackedElement<Envelope = AABB<P>>,
+    P: Point,
+    P::Scalar: PackedScalar,
+{
+    /// Creates a new packed tree from a set of elements.
+    ///
+    /// See [RTree::bulk_load].
+    pub fn bulk_load(elements: Vec<T>) -> Self {
+        Self::from(&RTree::bulk_load(elements))
+    }
+
+    /// Creates a new packed tree from a set of elements using the priority r-tree algorithm.
+    ///
+    /// A priority r-tree (PR-tree) guarantees that window queries visit `O((N/B)^(1-1/d) + T/B)`
+    /// nodes in the worst case, `B` being the node size and `T` the number of results. Regular
+    /// bulk loading performs slightly better on typical data, but its worst case is
+    /// considerably worse, e.g. for data with extreme aspect ratios. Loading takes longer and
+    /// nodes may be less than half full, which is why this is only offered for static trees.
+    ///
+    /// # Example
+    /// ```
+    /// use rstar::primitives::Rectangle;
+    /// use rstar::{PackedRTree, AABB};
+    ///
+    /// let rectangles = (0..100)
+    ///     .map(|i| Rectangle::from_corners([i as f64, 0.0], [i as f64 + 0.5, 100.0]))
+    ///     .collect();
+    /// let packed = PackedRTree::bulk_load_priority(rectangles);
+    /// let query = AABB::from_corners([10.0, 10.0], [20.0, 20.0]);
+    /// assert_eq!(packed.locate_in_envelope_intersecting(&query).count(), 11);
+    /// ```
+    pub fn bulk_load_priority(elements: Vec<T>) -> Self {
+        let size = elements.len();
+        let root = bulk_load_priority::<_, _, DefaultParams>(elements);
+        let (storage, node_count) = pack(&root, size);
+        PackedRTree {
+            storage,
+            node_count,
+            element_count: size,
+            _element: PhantomData,
+        }
+    }
+}
+
+impl<T, P, B> PackedRTree<T, B>
+where
+    T: PackedElement<Envelope = AABB<P>>,
+    P: Point,
+    P::Scalar: PackedScalar,
+    B: AsRef<[u8]>,
+{
+    /// Creates a packed tree from a buffer that was created with [PackedRTree::into_inner] or
+    /// [PackedRTree::as_bytes].
+    ///
+    /// See [PackedRTree::from_storage].
+    pub fn from_bytes(data: B) -> Result<Self, PackedRTreeError> {
+        Self::from_storage(data)
+    }
+
+    /// Returns the encoded tree.
+    pub fn as_bytes(&self) -> &[u8] {
+        self.storage.as_ref()
+    }
+}
+
+impl<T, P, S> PackedRTree<T, S>
+where
+    T: PackedElement<Envelope = AABB<P>>,
+    P: Point,
+    P::Scalar: PackedScalar,
+    S: NodeStorage,
+{
+    /// Creates a packed tree from a storage containing an encoded tree.
+    ///
+    /// The header and all nodes are validated, which requires reading every node once. The
+    /// element type must be the same as the one used to create the buffer; only the number
+    /// of dimensions and the element size are checked.
+    pub fn from_storage(storage: S) -> Result<Self, PackedRTreeError> {
+        let length = storage.size();
+        if length < HEADER_SIZE {
+            return Err(PackedRTreeError::InvalidHeader);
+        }
+        let mut header = [0; HEADER_SIZE];
+        storage.read(0, &mut header);
+        if header[..4] != MAGIC {
+            return Err(PackedRTreeError::InvalidHeader);
+        }
+        let version = read_u32(&header[4..]);
+        if version != VERSION {
+            return Err(PackedRTreeError::UnsupportedVersion(version));
+        }
+        if read_u32(&header[8..]) as usize != P::DIMENSIONS
+            || read_u32(&header[12..]) as usize != T::SIZE
+        {
+            return Err(PackedRTreeError::IncompatibleElement);
+        }
+        let node_count = usize::try_from(read_u64(&header[16..]));
+        let element_count = usize::try_from(read_u64(&header[24..]));
+        let (node_count, element_count) = match (node_count, element_count) {
+            (Ok(node_count), Ok(element_count)) if node_count > 0 => (node_count, element_count),
+            _ => return Err(PackedRTreeError::InvalidHeader),
+        };
+        let expected = node_count
+            .checked_mul(node_size::<P>())
+            .zip(element_count.checked_mul(T::SIZE))
+            .and_then(|(nodes, elements)| nodes.checked_add(elements))
+            .and_then(|size| size.checked_add(HEADER_SIZE));
+        if expected != Some(length) {
+            return Err(PackedRTreeError::UnexpectedLength {
+                expected: expected.unwrap_or(usize::MAX),
+                found: length,
+            });
+        }
+
+        let result = PackedRTree {
+            storage,
+            node_count,
+            element_count,
+            _element: PhantomData,
+        };
+        for index in 0..node_count {
+            let node = result.node(index);
+            let valid = if node.has_leaf_children {
+                node.children.end <= element_count
+            } else {
+                // Children must be stored after their parent, which rules out cycles.
+                node.children.start > index && node.children.end <= node_count
+            };
+            if !valid {
+                return Err(PackedRTreeError::InvalidNode(index));
+            }
+        }
+        Ok(result)
+    }
+
+    /// Returns the storage containing the encoded tree.
+    pub fn into_inner(self) -> S {
+        self.storage
+    }
+
+    /// Returns the number of elements in the tree.
+    pub fn size(&self) -> usize {
+        self.element_count
+    }
+
+    /// Returns the envelope of all elements.
+    pub fn envelope(&self) -> AABB<P> {
+        self.node(0).envelope
+    }
+
+    fn read(&self, offset: usize, length: usize) -> SmallVec<[u8; 128]> {
+        let mut buffer = smallvec![0; length];
+        self.storage.read(offset, &mut buffer);
+        buffer
+    }
+
+    fn node(&self, index: usize) -> PackedNode<P> {
+        let record = self.read(HEADER_SIZE + index * node_size::<P>(), node_size::<P>());
+        let links = &record[2 * P::SIZE..];
+        // Invalid links are turned into empty or out of bounds ranges, which are rejected
+        // when the tree is validated.
+        let first = usize::try_from(read_u64(links)).unwrap_or(usize::MAX);
+        let count = read_u32(&links[8..]) as usize;
+        PackedNode {
+            envelope: AABB::from_corners(read_point(&record), read_point(&record[P::SIZE..])),
+            children: first..first.saturating_add(count),
+            has_leaf_children: read_u32(&links[12..]) != 0,
+        }
+    }
+
+    fn element(&self, index: usize) -> T {
+        let offset = HEADER_SIZE + self.node_count * node_size::<P>() + index * T::SIZE;
+        T::read_packed(&self.read(offset, T::SIZE))
+    }
+
+    /// Decodes all nodes and elements.
+    ///
+    /// Fails if a node is referenced more than once, if an inner node has no children or if
+    /// not all elements are stored on the same level.
+    pub(crate) fn decode_root(&self) -> Result<ParentNode<T>, PackedRTreeError> {
+        // Children are stored after their parents, so decoding backwards always finds a
+        // node's children already decoded.
+        let mut nodes: Vec<Option<(ParentNode<T>, usize)>> =
+            (0..self.node_count).map(|_| None).collect();
+        for index in (0..self.node_count).rev() {
+            let node = self.node(index);
+            let invalid = PackedRTreeError::InvalidNode(index);
+            let (children, height) = if node.has_leaf_children {
+                let children: Vec<_> = node
+                    .children
+                    .map(|child| RTreeNode::Leaf(self.element(child)))
+                    .collect();
+                (children, 0)
+            } else {
+                let mut height = None;
+                let mut children = Vec::with_capacity(node.children.len());
+                for child in node.children {
+                    let (data, child_height) = nodes[child].take().ok_or(invalid)?;
+                    if *height.get_or_insert(child_height) != child_height {
+                        return Err(invalid);
+                    }
+                    children.push(RTreeNode::Parent(data));
+                }
+                (children, height.ok_or(invalid)? + 1)
+            };
+            if index > 0 && children.is_empty() {
+                return Err(invalid);
+            }
+            nodes[index] = Some((ParentNode::new_parent(children.into()), height));
+        }
+        let (root, _) = nodes[0].take().ok_or(PackedRTreeError::InvalidNode(0))?;
+        Ok(root)
+    }
+
+    /// Returns an iterator over all elements of the tree.
+    pub fn iter(&self) -> PackedSelectionIterator<'_, T, S, SelectAllFunc> {
+        self.locate_with_selection_function(SelectAllFunc)
+    }
+
+    /// Returns all elements selected by a [SelectionFunction].
+    ///
+    /// See [RTree::locate_with_selection_function].
+    pub fn locate_with_selection_function<Func: SelectionFunction<T>>(
+        &self,
+        selection_function: Func,
+    ) -> PackedSelectionIterator<'_, T, S, Func> {
+        PackedSelectionIterator::new(self, selection_function)
+    }
+
+    /// Returns all elements contained in an envelope.
+    ///
+    /// See [RTree::locate_in_envelope].
+    pub fn locate_in_envelope(
+        &self,
+        envelope: &AABB<P>,
+    ) -> PackedSelectionIterator<'_, T, S, SelectInEnvelopeFunction<T>> {
+        self.locate_with_selection_function(SelectInEnvelopeFunction::new(envelope.clone()))
+    }
+
+    /// Returns all elements whose envelope intersects an envelope.
+    ///
+    /// See [RTree::locate_in_envelope_intersecting].
+    pub fn locate_in_envelope_intersecting(
+        &self,
+        envelope: &AABB<P>,
+    ) -> PackedSelectionIterator<'_, T, S, SelectInEnvelopeFuncIntersecting<T>> {
+        self.locate_with_selection_function(SelectInEnvelopeFuncIntersecting::new(envelope.clone()))
+    }
+}
+
+impl<T, P, S> PackedRTree<T, S>
+where
+    T: PackedElement<Envelope = AABB<P>> + PartialEq,
+    P: Point,
+    P::Scalar: PackedScalar,
+    S: NodeStorage,
+{
+    /// Returns `true` if the tree contains an element equal to `t`.
+    pub fn contains(&self, t: &T) -> bool {
+        self.locate_with_selection_function(SelectEqualsFunction::new(t))
+            .next()
+            .is_some()
+    }
+}
+
+impl<T, P, S> PackedRTree<T, S>
+where
+    T: PackedElement<Envelope = AABB<P>> + PointDistance,
+    P: Point,
+    P::Scalar: PackedScalar,
+    S: NodeStorage,
+{
+    /// Returns all elements containing a given point.
+    ///
+    /// See [RTree::locate_all_at_point].
+    pub fn locate_all_at_point(
+        &self,
+        point: &P,
+    ) -> PackedSelectionIterator<'_, T, S, SelectAtPointFunction<T>> {
+        self.locate_with_selection_function(SelectAtPointFunction::new(point.clone()))
+    }
+
+    /// Returns all elements within a certain distance of a point.
+    ///
+    /// See [RTree::locate_within_distance].
+    pub fn locate_within_distance(
+        &self,
+        query_point: P,
+        max_squared_radius: P::Scalar,
+    ) -> PackedSelectionIterator<'_, T, S, SelectWithinDistanceFunction<T>> {
+        self.locate_with_selection_function(SelectWithinDistanceFunction::new(
+            query_point,
+            max_squared_radius,
+        ))
+    }
+
+    /// Returns the nearest neighbor of a point.
+    ///
+    /// See [RTree::nearest_neighbor].
+    pub fn nearest_neighbor(&self, query_point: &P) -> Option<T> {
+        self.nearest_neighbor_iter_with_distance_2(query_point)
+            .next()
+            .map(|(t, _)| t)
+    }
+
+    /// Returns `(element, distance^2)` tuples sorted by their distance to a given point.
+    ///
+    /// See [RTree::nearest_neighbor_iter_with_distance_2].
+    pub fn nearest_neighbor_iter_with_distance_2(
+        &self,
+        query_point: &P,
+    ) -> PackedNearestNeighborIterator<'_, T, S> {
+        PackedNearestNeighborIterator::new(self, query_point.clone())
+    }
+}
+
+/// Iterator returned by the `PackedRTree::locate_*` methods.
+pub struct PackedSelectionIterator<'a, T, S, Func>
+where
+    T: RTreeObject,
+{
+    tree: &'a PackedRTree<T, S>,
+    func: Func,
+    nodes: SmallVec<[(usize, usize); 24]>,
+    elements: Range<usize>,
+}
+
+impl<'a, T, P, S, Func> PackedSelectionIterator<'a, T, S, Func>
+where
+    T: PackedElement<Envelope = AABB<P>>,
+    P: Point,
+    P::Scalar: PackedScalar,
+    S: NodeStorage,
+    Func: SelectionFunction<T>,
+{
+    fn new(tree: &'a PackedRTree<T, S>, func: Func) -> Self {
+        let mut result = PackedSelectionIterator {
+            tree,
+            func,
+            nodes: SmallVec::new(),
+            elements: 0..0,
+        };
+        if result
+            .func
+            .should_unpack_parent_at_depth(&tree.node(0).envelope, 0)
+        {
+            result.unpack(0, 0);
+        }
+        result
+    }
+
+    fn unpack(&mut self, index: usize, depth: usize) {
+        let node = self.tree.node(index);
+        if node.has_leaf_children {
+            self.elements = node.children;
+        } else {
+            self.nodes
+                .extend(node.children.rev().map(|child| (child, depth + 1)));
+        }
+    }
+}
+
+impl<'a, T, P, S, Func> Iterator for PackedSelectionIterator<'a, T, S, Func>
+where
+    T: PackedElement<Envelope = AABB<P>>,
+    P: Point,
+    P::Scalar: PackedScalar,
+    S: NodeStorage,
+    Func: SelectionFunction<T>,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        loop {
+            for index in &mut self.elements {
+                let t = self.tree.element(index);
+                if self.func.should_unpack_leaf(&t) {
+                    return Some(t);
+                }
+            }
+            let (index, depth) = self.nodes.pop()?;
+            if self
+                .func
+                .should_unpack_parent_at_depth(&self.tree.node(index).envelope, depth)
+            {
+                self.unpack(index, depth);
+            }
+        }
+    }
+}
+
+enum PackedEntry<T> {
+    Node(usize),
+    Element(T),
+}
+
+struct DistanceWrapper<T>
+where
+    T: PointDistance,
+{
+    entry: PackedEntry<T>,
+    distance: <<T::Envelope as Envelope>::Point as Point>::Scalar,
+}
+
+impl<T> PartialEq for DistanceWrapper<T>
+where
+    T: PointDistance,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.distance == other.distance
+    }
+}
+
+impl<T> PartialOrd for DistanceWrapper<T>
+where
+    T: PointDistance,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<::core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Eq for DistanceWrapper<T> where T: PointDistance {}
+
+impl<T> Ord for DistanceWrapper<T>
+where
+    T: PointDistance,
+{
+    fn cmp(&self, other: &Self) -> ::core::cmp::Ordering {
+        // Inverse comparison creates a min heap
+        other.distance.partial_cmp(&self.distance).unwrap()
+    }
+}
+
+/// Iterator returned by [PackedRTree::nearest_neighbor_iter_with_distance_2].
+pub struct PackedNearestNeighborIterator<'a, T, S>
+where
+    T: PointDistance,
+{
+    tree: &'a PackedRTree<T, S>,
+    heap: BinaryHeap<DistanceWrapper<T>>,
+    query_point: <T::Envelope as Envelope>::Point,
+}
+
+impl<'a, T, P, S> PackedNearestNeighborIterator<'a, T, S>
+where
+    T: PackedElement<Envelope = AABB<P>> + PointDistance,
+    P: Point,
+    P::Scalar: PackedScalar,
+    S: NodeStorage,
+{
+    fn new(tree: &'a PackedRTree<T, S>, query_point: P) -> Self {
+        let mut result = PackedNearestNeighborIterator {
+            tree,
+            heap: BinaryHeap::with_capacity(20),
+            query_point,
+        };
+        result.extend_heap(0);
+        result
+    }
+
+    fn extend_heap(&mut self, index: usize) {
+        let node = self.tree.node(index);
+        let (tree, query_point) = (self.tree, &self.query_point);
+        if node.has_leaf_children {
+            self.heap.extend(node.children.map(|index| {
+                let t = tree.element(index);
+                DistanceWrapper {
+                    distance: t.distance_2(query_point),
+                    entry: PackedEntry::Element(t),
+                }
+            }));
+        } else {
+            self.heap.extend(node.children.map(|index| DistanceWrapper {
+                distance: tree.node(index).envelope.distance_2(query_point),
+                entry: PackedEntry::Node(index),
+            }));
+        }
+    }
+}
+
+impl<'a, T, P, S> Iterator for PackedNearestNeighborIterator<'a, T, S>
+where
+    T: PackedElement<Envelope = AABB<P>> + PointDistance,
+    P: Point,
+    P::Scalar: PackedScalar,
+    S: NodeStorage,
+{
+    type Item = (T, P::Scalar);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(DistanceWrapper { entry, distance }) = self.heap.pop() {
+            match entry {
+                PackedEntry::Element(t) => return Some((t, distance)),
+                PackedEntry::Node(index) => self.extend_heap(index),
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{PackedElement, PackedRTree, PackedRTreeError};
+    use crate::primitives::Rectangle;
+    use crate::test_utilities::{create_random_points, create_random_rectangles, SEED_1, SEED_2};
+    use crate::{RTree, AABB};
+
+    #[test]
+    fn test_bulk_load_priority() {
+        let rectangles = create_random_rectangles(1000, SEED_1);
+        let tree = RTree::bulk_load(rectangles.clone());
+        let packed = PackedRTree::bulk_load_priority(rectangles.clone());
+        assert_eq!(packed.size(), 1000);
+        assert_eq!(packed.envelope(), tree.root().envelope());
+        assert!(rectangles
+            .iter()
+            .all(|rectangle| packed.contains(rectangle)));
+        for query in create_random_points(50, SEED_2) {
+            let envelope = AABB::from_corners(query, [query[0] + 0.2, query[1] + 0.1]);
+            assert_eq!(
+                packed.locate_in_envelope_intersecting(&envelope).count(),
+                tree.locate_in_envelope_intersecting(&envelope).count()
+            );
+            assert_eq!(
+                packed.nearest_neighbor(&query),
+                tree.nearest_neighbor(&query).copied()
+            );
+        }
+
+        let points = create_random_points(100, SEED_1);
+        for size in [0, 1, 6, 7, 100] {
+            let packed = PackedRTree::bulk_load_priority(points[..size].to_vec());
+            assert_eq!(packed.iter().count(), size);
+        }
+    }
+
+    #[test]
+    fn test_packed_queries() {
+        let points = create_random_points(1000, SEED_1);
+        let tree = RTree::bulk_load(points.clone());
+        let packed = PackedRTree::from(&tree);
+        assert_eq!(packed.size(), 1000);
+        assert_eq!(packed.envelope(), tree.root().envelope());
+        assert_eq!(packed.iter().count(), 1000);
+        assert!(points.iter().all(|point| packed.contains(point)));
+        assert!(!packed.contains(&[2.0, 2.0]));
+
+        for query in create_random_points(50, SEED_2) {
+            assert_eq!(
+                packed.nearest_neighbor(&query).as_ref(),
+                tree.nearest_neighbor(&query)
+            );
+            let distances: Vec<_> = packed
+                .nearest_neighbor_iter_with_distance_2(&query)
+                .take(10)
+                .map(|(_, distance)| distance)
+                .collect();
+            let expected: Vec<_> = tree
+                .nearest_neighbor_iter_with_distance_2(&query)
+                .take(10)
+                .map(|(_, distance)| distance)
+                .collect();
+            assert_eq!(distances, expected);
+
+            let envelope = AABB::from_corners(query, [query[0] + 0.2, query[1] + 0.1]);
+            assert_eq!(
+                packed.locate_in_envelope(&envelope).count(),
+                tree.locate_in_envelope(&envelope).count()
+            );
+            assert_eq!(
+                packed.locate_within_distance(query, 0.01).count(),
+                tree.locate_within_distance(query, 0.01).count()
+            );
+        }
+    }
+
+    #[test]
+    fn test_packed_rectangles_from_bytes() {
+        let rectangles = create_random_rectangles(200, SEED_1);
+        let tree = RTree::bulk_load(rectangles.clone());
+        let bytes = PackedRTree::from(&tree).into_inner();
+        let packed = PackedRTree::<Rectangle<[f64; 2]>, _>::from_bytes(&bytes[..]).unwrap();
+        assert_eq!(packed.as_bytes(), &bytes[..]);
+        for rectangle in &rectangles {
+            let query = AABB::from_corners(rectangle.lower(), rectangle.upper());
+            assert_eq!(
+                packed.locate_in_envelope_intersecting(&query).count(),
+                tree.locate_in_envelope_intersecting(&query).count()
+            );
+        }
+    }
+
+    #[test]
+    fn test_invalid_packed_tree() {
+        let empty = PackedRTree::<[f64; 2]>::bulk_load(Vec::new());
+        assert_eq!(empty.size(), 0);
+        assert_eq!(empty.iter().count(), 0);
+        assert_eq!(empty.nearest_neighbor(&[0.0, 0.0]), None);
+
+        let bytes = PackedRTree::bulk_load(create_random_points(100, SEED_1)).into_inner();
+        let from_bytes = |bytes: &[u8]| PackedRTree::<[f64; 2], _>::from_bytes(bytes.to_vec());
+        assert!(from_bytes(&bytes).is_ok());
+        assert_eq!(
+            from_bytes(&bytes[..10]).err(),
+            Some(PackedRTreeError::InvalidHeader)
+        );
+        assert_eq!(
+            from_bytes(&bytes[..bytes.len() - 1]).err(),
+            Some(PackedRTreeError::UnexpectedLength {
+                expected: bytes.len(),
+                found: bytes.len() - 1
+            })
+        );
+        assert_eq!(
+            PackedRTree::<[f32; 2], _>::from_bytes(&bytes[..]).err(),
+            Some(PackedRTreeError::IncompatibleElement)
+        );
+
+        let mut corrupted = bytes.clone();
+        corrupted[4] = 2;
+        assert_eq!(
+            from_bytes(&corrupted).err(),
+            Some(PackedRTreeError::UnsupportedVersion(2))
+        );
+        // Let the root refer to itself.
+        let mut corrupted = bytes;
+        corrupted[32 + 2 * <[f64; 2]>::SIZE] = 0;
+        assert_eq!(
+            from_bytes(&corrupted).err(),
+            Some(PackedRTreeError::InvalidNode(0))
+        );
+    }
+}
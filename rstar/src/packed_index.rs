@@ -0,0 +1,459 @@
+//! Import and export of packed Hilbert r-trees as used by [FlatGeobuf](https://flatgeobuf.org).
+//!
+//! A packed Hilbert r-tree stores its items sorted by the Hilbert curve index of their
+//! centers. Every node of the tree is encoded as 40 bytes: four little endian `f64` values
+//! (`min_x`, `min_y`, `max_x`, `max_y`) followed by a little endian `u64` offset. Nodes are
+//! stored level by level, starting with the root. The offset of an inner node refers to the
+//! node's first child, the offset of a leaf is the byte offset of the corresponding feature
+//! in the feature data section.
+//!
+//! # Example
+//! ```
+//! use rstar::packed_index::DEFAULT_NODE_SIZE;
+//! use rstar::RTree;
+//!
+//! let tree = RTree::bulk_load(vec![[0.0, 0.0], [1.0, 1.0], [2.0, 0.5]]);
+//! // Every feature is assumed to take up 100 bytes.
+//! let mut offset = 0;
+//! let index = tree.to_packed_index(DEFAULT_NODE_SIZE, |_| {
+//!     offset += 100;
+//!     offset - 100
+//! });
+//!
+//! let imported = RTree::from_packed_index(&index, tree.size(), DEFAULT_NODE_SIZE).unwrap();
+//! let feature = imported.locate_at_point(&[1.0, 1.0]).unwrap();
+//! assert!(feature.data < 300);
+//! ```
+
+use crate::primitives::{GeomWithData, Rectangle};
+use crate::{Point, RTree, RTreeObject, RTreeParams, AABB};
+use alloc::vec::Vec;
+use core::fmt;
+use num_traits::ToPrimitive;
+
+/// The default node size of FlatGeobuf files.
+pub const DEFAULT_NODE_SIZE: u16 = 16;
+
+/// The size of an encoded node in bytes.
+const NODE_ITEM_SIZE: usize = 40;
+
+/// An item of a tree imported from a packed index: the item's bounding rectangle and its
+/// feature offset.
+pub type PackedIndexItem = GeomWithData<Rectangle<[f64; 2]>, u64>;
+
+/// Error returned when a packed index cannot be imported.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PackedIndexError {
+    /// The node size is smaller than two.
+    InvalidNodeSize(u16),
+    /// The index does not have the size implied by the number of items and the node size.
+    UnexpectedLength {
+        /// The expected length in bytes.
+        expected: usize,
+        /// The actual length in bytes.
+        found: usize,
+    },
+    /// The index for this number of items would be larger than the address space.
+    TooManyItems(usize),
+}
+
+impl fmt::Display for PackedIndexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PackedIndexError::InvalidNodeSize(size) => write!(f, "invalid node size {}", size),
+            PackedIndexError::UnexpectedLength { expected, found } => write!(
+                f,
+                "expected an index of {} bytes, found {} bytes",
+                expected, found
+            ),
+            PackedIndexError::TooManyItems(num_items) => {
+                write!(f, "an index of {} items is too large", num_items)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PackedIndexError {}
+
+/// Returns the size of a packed index in bytes.
+///
+/// # Panics
+/// Panics if `node_size` is smaller than two or if the size doesn't fit into a `usize`.
+pub fn packed_index_size(num_items: usize, node_size: u16) -> usize {
+    assert!(node_size >= 2, "Node size must be at least 2");
+    checked_index_size(num_items, node_size).expect("Packed index size overflows usize")
+}
+
+/// Returns the size of a packed index in bytes, or `None` if it doesn't fit into a `usize`.
+fn checked_index_size(num_items: usize, node_size: u16) -> Option<usize> {
+    match level_bounds(num_items, node_size)?.first() {
+        Some(&(_, end)) => end.checked_mul(NODE_ITEM_SIZE),
+        None => Some(0),
+    }
+}
+
+/// Returns the node index ranges of all levels, starting with the leaves, or `None` if the
+/// number of nodes doesn't fit into a `usize`.
+fn level_bounds(num_items: usize, node_size: u16) -> Option<Vec<(usize, usize)>> {
+    let node_size = node_size as usize;
+    if num_items == 0 {
+        return Some(Vec::new());
+    }
+    let mut level_sizes = Vec::new();
+    let mut n = num_items;
+    let mut num_nodes = n;
+    level_sizes.push(n);
+    loop {
+        n = n / node_size + usize::from(n % node_size != 0);
+        num_nodes = num_nodes.checked_add(n)?;
+        level_sizes.push(n);
+        if n == 1 {
+            break;
+        }
+    }
+    let mut end = num_nodes;
+    let bounds = level_sizes
+        .into_iter()
+        .map(|size| {
+            let bounds = (end - size, end);
+            end -= size;
+            bounds
+        })
+        .collect();
+    Some(bounds)
+}
+
+/// Computes the position of `(x, y)` on a Hilbert curve covering a 2^16 x 2^16 grid.
+fn hilbert(x: u32, y: u32) -> u32 {
+    let mut a = x ^ y;
+    let mut b = 0xFFFF ^ a;
+    let mut c = 0xFFFF ^ (x | y);
+    let mut d = x & (y ^ 0xFFFF);
+
+    let mut aa = a | (b >> 1);
+    let mut bb = (a >> 1) ^ a;
+    let mut cc = ((c >> 1) ^ (b & (d >> 1))) ^ c;
+    let mut dd = ((a & (c >> 1)) ^ (d >> 1)) ^ d;
+
+    a = aa;
+    b = bb;
+    c = cc;
+    d = dd;
+    aa = (a & (a >> 2)) ^ (b & (b >> 2));
+    bb = (a & (b >> 2)) ^ (b & ((a ^ b) >> 2));
+    cc ^= (a & (c >> 2)) ^ (b & (d >> 2));
+    dd ^= (b & (c >> 2)) ^ ((a ^ b) & (d >> 2));
+
+    a = aa;
+    b = bb;
+    c = cc;
+    d = dd;
+    aa = (a & (a >> 4)) ^ (b & (b >> 4));
+    bb = (a & (b >> 4)) ^ (b & ((a ^ b) >> 4));
+    cc ^= (a & (c >> 4)) ^ (b & (d >> 4));
+    dd ^= (b & (c >> 4)) ^ ((a ^ b) & (d >> 4));
+
+    a = aa;
+    b = bb;
+    c = cc;
+    d = dd;
+    cc ^= (a & (c >> 8)) ^ (b & (d >> 8));
+    dd ^= (b & (c >> 8)) ^ ((a ^ b) & (d >> 8));
+
+    a = cc ^ (cc >> 1);
+    b = dd ^ (dd >> 1);
+
+    let mut i0 = x ^ y;
+    let mut i1 = b | (0xFFFF ^ (i0 | a));
+
+    i0 = (i0 | (i0 << 8)) & 0x00FF_00FF;
+    i0 = (i0 | (i0 << 4)) & 0x0F0F_0F0F;
+    i0 = (i0 | (i0 << 2)) & 0x3333_3333;
+    i0 = (i0 | (i0 << 1)) & 0x5555_5555;
+
+    i1 = (i1 | (i1 << 8)) & 0x00FF_00FF;
+    i1 = (i1 | (i1 << 4)) & 0x0F0F_0F0F;
+    i1 = (i1 | (i1 << 2)) & 0x3333_3333;
+    i1 = (i1 | (i1 << 1)) & 0x5555_5555;
+
+    (i1 << 1) | i0
+}
+
+#[derive(Clone, Copy)]
+struct NodeItem {
+    bounds: [f64; 4],
+    offset: u64,
+}
+
+impl NodeItem {
+    fn empty() -> Self {
+        NodeItem {
+            bounds: [
+                f64::INFINITY,
+                f64::INFINITY,
+                f64::NEG_INFINITY,
+                f64::NEG_INFINITY,
+            ],
+            offset: 0,
+        }
+    }
+
+    fn expand(&mut self, other: &NodeItem) {
+        for axis in 0..2 {
+            self.bounds[axis] = self.bounds[axis].min(other.bounds[axis]);
+            self.bounds[axis + 2] = self.bounds[axis + 2].max(other.bounds[axis + 2]);
+        }
+    }
+
+    fn write(&self, target: &mut Vec<u8>) {
+        for value in &self.bounds {
+            target.extend_from_slice(&value.to_le_bytes());
+        }
+        target.extend_from_slice(&self.offset.to_le_bytes());
+    }
+
+    fn read(source: &[u8]) -> Self {
+        let mut bounds = [0.0; 4];
+        for (index, value) in bounds.iter_mut().enumerate() {
+            let mut bytes = [0; 8];
+            bytes.copy_from_slice(&source[index * 8..index * 8 + 8]);
+            *value = f64::from_le_bytes(bytes);
+        }
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(&source[32..40]);
+        NodeItem {
+            bounds,
+            offset: u64::from_le_bytes(bytes),
+        }
+    }
+}
+
+impl<T, P, Params> RTree<T, Params>
+where
+    T: RTreeObject<Envelope = AABB<P>>,
+    P: Point,
+    P::Scalar: ToPrimitive,
+    Params: RTreeParams,
+{
+    /// Encodes this tree's items as a packed Hilbert r-tree compatible with FlatGeobuf.
+    ///
+    /// `feature_offset` is called once for every item, in the order in which the items are
+    /// stored in the index, and must return the byte offset of the item's feature. Features
+    /// of a FlatGeobuf file must be written in this order.
+    ///
+    /// Refer to the [packed_index](crate::packed_index) module for a description of the
+    /// format and an example.
+    ///
+    /// # Panics
+    /// Panics if the tree is not two dimensional or if `node_size` is smaller than two.
+    pub fn to_packed_index<F>(&self, node_size: u16, mut feature_offset: F) -> Vec<u8>
+    where
+        F: FnMut(&T) -> u64,
+    {
+        assert_eq!(P::DIMENSIONS, 2, "Packed indices must be two dimensional");
+        assert!(node_size >= 2, "Node size must be at least 2");
+        let to_f64 = |value: P::Scalar| value.to_f64().unwrap_or(f64::NAN);
+        let to_node = |envelope: &AABB<P>| {
+            let (lower, upper) = (envelope.lower(), envelope.upper());
+            NodeItem {
+                bounds: [
+                    to_f64(lower.nth(0)),
+                    to_f64(lower.nth(1)),
+                    to_f64(upper.nth(0)),
+                    to_f64(upper.nth(1)),
+                ],
+                offset: 0,
+            }
+        };
+
+        let extent = to_node(&self.root().envelope());
+        let width = extent.bounds[2] - extent.bounds[0];
+        let height = extent.bounds[3] - extent.bounds[1];
+        let scale = |value: f64, min: f64, size: f64| {
+            if size > 0.0 {
+                (65535.0 * (value - min) / size) as u32
+            } else {
+                0
+            }
+        };
+        let mut items: Vec<_> = self
+            .iter()
+            .map(|item| {
+                let node = to_node(&item.envelope());
+                let x = (node.bounds[0] + node.bounds[2]) / 2.0;
+                let y = (node.bounds[1] + node.bounds[3]) / 2.0;
+                let index = hilbert(
+                    scale(x, extent.bounds[0], width),
+                    scale(y, extent.bounds[1], height),
+                );
+                (index, node, item)
+            })
+            .collect();
+        items.sort_by_key(|&(index, _, _)| index);
+
+        // The items are in memory, their index can't overflow.
+        let bounds = level_bounds(items.len(), node_size).expect("Packed index is too large");
+        let num_nodes = bounds.first().map(|&(_, end)| end).unwrap_or(0);
+        let mut nodes = alloc::vec![NodeItem::empty(); num_nodes];
+        if let Some(&(leaves_start, _)) = bounds.first() {
+            for (target, (_, mut node, item)) in nodes[leaves_start..].iter_mut().zip(items) {
+                node.offset = feature_offset(item);
+                *target = node;
+            }
+        }
+        for level in bounds.windows(2) {
+            let ((children_start, children_end), (parents_start, _)) = (level[0], level[1]);
+            for (parent_index, first_child) in (children_start..children_end)
+                .step_by(node_size as usize)
+                .enumerate()
+            {
+                let mut parent = NodeItem::empty();
+                let last_child = (first_child + node_size as usize).min(children_end);
+                for child in &nodes[first_child..last_child] {
+                    parent.expand(child);
+                }
+                parent.offset = first_child as u64;
+                nodes[parents_start + parent_index] = parent;
+            }
+        }
+
+        let mut result = Vec::with_capacity(num_nodes * NODE_ITEM_SIZE);
+        for node in &nodes {
+            node.write(&mut result);
+        }
+        result
+    }
+}
+
+impl RTree<PackedIndexItem> {
+    /// Creates a tree from a packed Hilbert r-tree, e.g. the index section of a FlatGeobuf
+    /// file.
+    ///
+    /// Every leaf of the index becomes an item of the tree which stores the feature offset
+    /// as its data. The inner nodes of the index are not used, the tree is bulk loaded from
+    /// the leaves.
+    pub fn from_packed_index(
+        index: &[u8],
+        num_items: usize,
+        node_size: u16,
+    ) -> Result<Self, PackedIndexError> {
+        Self::from_packed_index_with_params(index, num_items, node_size)
+    }
+}
+
+impl<Params> RTree<PackedIndexItem, Params>
+where
+    Params: RTreeParams,
+{
+    /// Creates a tree with custom parameters from a packed Hilbert r-tree.
+    ///
+    /// For more information refer to [RTree::from_packed_index].
+    pub fn from_packed_index_with_params(
+        index: &[u8],
+        num_items: usize,
+        node_size: u16,
+    ) -> Result<Self, PackedIndexError> {
+        if node_size < 2 {
+            return Err(PackedIndexError::InvalidNodeSize(node_size));
+        }
+        let expected = checked_index_size(num_items, node_size)
+            .ok_or(PackedIndexError::TooManyItems(num_items))?;
+        if index.len() != expected {
+            return Err(PackedIndexError::UnexpectedLength {
+                expected,
+                found: index.len(),
+            });
+        }
+        let leaves_start = num_items
+            .checked_mul(NODE_ITEM_SIZE)
+            .and_then(|leaves| expected.checked_sub(leaves))
+            .ok_or(PackedIndexError::TooManyItems(num_items))?;
+        let items = index[leaves_start..]
+            .chunks_exact(NODE_ITEM_SIZE)
+            .map(|bytes| {
+                let node = NodeItem::read(bytes);
+                let [min_x, min_y, max_x, max_y] = node.bounds;
+                GeomWithData::new(
+                    Rectangle::from_corners([min_x, min_y], [max_x, max_y]),
+                    node.offset,
+                )
+            })
+            .collect();
+        Ok(Self::bulk_load_with_params(items))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{level_bounds, packed_index_size, NodeItem, PackedIndexError, NODE_ITEM_SIZE};
+    use crate::test_utilities::{create_random_points, SEED_1};
+    use crate::{RTree, AABB};
+
+    #[test]
+    fn test_level_bounds() {
+        assert_eq!(level_bounds(0, 16), Some(vec![]));
+        assert_eq!(level_bounds(1, 16), Some(vec![(1, 2), (0, 1)]));
+        assert_eq!(level_bounds(20, 16), Some(vec![(3, 23), (1, 3), (0, 1)]));
+        assert_eq!(packed_index_size(20, 16), 23 * NODE_ITEM_SIZE);
+        assert_eq!(level_bounds(usize::MAX, 2), None);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let points = create_random_points(1000, SEED_1);
+        let tree = RTree::bulk_load(points.clone());
+        let mut offsets = Vec::new();
+        let index = tree.to_packed_index(16, |point| {
+            offsets.push(*point);
+            offsets.len() as u64 - 1
+        });
+        assert_eq!(index.len(), packed_index_size(1000, 16));
+
+        // The root covers all items and refers to the first node of the second level.
+        let root = NodeItem::read(&index);
+        let envelope = tree.root().envelope();
+        assert_eq!(root.bounds[..2], envelope.lower());
+        assert_eq!(root.bounds[2..], envelope.upper());
+        assert_eq!(root.offset, 1);
+
+        let imported = RTree::from_packed_index(&index, 1000, 16).unwrap();
+        assert_eq!(imported.size(), 1000);
+        for point in &points {
+            let item = imported.locate_at_point(point).unwrap();
+            assert_eq!(&offsets[item.data as usize], point);
+        }
+        let query = AABB::from_corners([-0.5, -0.5], [0.0, 0.0]);
+        assert_eq!(
+            imported.locate_in_envelope(&query).count(),
+            tree.locate_in_envelope(&query).count()
+        );
+    }
+
+    #[test]
+    fn test_invalid_index() {
+        assert_eq!(
+            RTree::from_packed_index(&[0; 40], 1, 16).map(|tree| tree.size()),
+            Err(PackedIndexError::UnexpectedLength {
+                expected: 80,
+                found: 40
+            })
+        );
+        assert_eq!(
+            RTree::from_packed_index(&[], 0, 1).map(|tree| tree.size()),
+            Err(PackedIndexError::InvalidNodeSize(1))
+        );
+        assert_eq!(
+            RTree::from_packed_index(&[], 0, 16).map(|tree| tree.size()),
+            Ok(0)
+        );
+        for num_items in [usize::MAX, usize::MAX / NODE_ITEM_SIZE] {
+            assert_eq!(
+                RTree::from_packed_index(&[0; 40], num_items, 16).map(|tree| tree.size()),
+                Err(PackedIndexError::TooManyItems(num_items))
+            );
+        }
+        let empty: RTree<[f64; 2]> = RTree::new();
+        assert!(empty.to_packed_index(16, |_| 0).is_empty());
+    }
+}
@@ -0,0 +1,171 @@
+use crate::packed::NodeStorage;
+
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use std::collections::HashMap;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::sync::{Mutex, PoisonError};
+
+/// A [NodeStorage] that reads an encoded [PackedRTree](crate::PackedRTree) from a file (or
+/// any other `Read + Seek` source) through a page cache of bounded size.
+///
+/// The source is read in pages of a fixed size, at most `max_pages` pages are kept in
+/// memory. Once the cache is full, the least recently used page is evicted. Since queries
+/// mostly visit the nodes close to the root, these nodes tend to stay cached.
+///
+/// Like reading from a memory mapped file, reading from the storage cannot fail: an I/O error
+/// while a tree is queried causes a panic. Requires the `std` feature.
+///
+/// # Example
+/// ```
+/// use rstar::{PackedRTree, PagedStorage};
+/// use std::io::Cursor;
+///
+/// let bytes = PackedRTree::bulk_load(vec![[0.0, 0.0], [1.0, 1.0], [2.0, 0.5]]).into_inner();
+/// // A file would usually be used instead of a cursor.
+/// let storage = PagedStorage::new(Cursor::new(bytes), 4096, 16).unwrap();
+/// let tree = PackedRTree::<[f64; 2], _>::from_storage(storage).unwrap();
+/// assert_eq!(tree.nearest_neighbor(&[0.8, 0.9]), Some([1.0, 1.0]));
+/// ```
+pub struct PagedStorage<R> {
+    cache: Mutex<PageCache<R>>,
+    size: usize,
+    page_size: usize,
+}
+
+struct PageCache<R> {
+    source: R,
+    pages: HashMap<usize, (Vec<u8>, u64)>,
+    max_pages: usize,
+    clock: u64,
+}
+
+impl<R> PagedStorage<R>
+where
+    R: Read + Seek,
+{
+    /// Creates a new storage reading from `source`.
+    ///
+    /// # Panics
+    /// Panics if `page_size` or `max_pages` is zero.
+    pub fn new(mut source: R, page_size: usize, max_pages: usize) -> io::Result<Self> {
+        assert!(page_size > 0, "Page size must be at least 1");
+        assert!(max_pages > 0, "At least one page must be cached");
+        let size = usize::try_from(source.seek(SeekFrom::End(0))?)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "source is too large"))?;
+        Ok(PagedStorage {
+            cache: Mutex::new(PageCache {
+                source,
+                pages: HashMap::new(),
+                max_pages,
+                clock: 0,
+            }),
+            size,
+            page_size,
+        })
+    }
+
+    /// Returns the number of pages that are currently cached.
+    pub fn cached_pages(&self) -> usize {
+        self.lock().pages.len()
+    }
+
+    /// Consumes the storage and returns the underlying source.
+    pub fn into_inner(self) -> R {
+        self.cache
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner)
+            .source
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, PageCache<R>> {
+        self.cache.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<R> PageCache<R>
+where
+    R: Read + Seek,
+{
+    fn page(&mut self, index: usize, page_size: usize, size: usize) -> io::Result<&[u8]> {
+        self.clock += 1;
+        let clock = self.clock;
+        if !self.pages.contains_key(&index) {
+            if self.pages.len() >= self.max_pages {
+                let oldest = self
+                    .pages
+                    .iter()
+                    .min_by_key(|(_, (_, last_used))| *last_used)
+                    .map(|(&index, _)| index);
+                if let Some(oldest) = oldest {
+                    self.pages.remove(&oldest);
+                }
+            }
+            let start = index * page_size;
+            let mut page = alloc::vec![0; page_size.min(size - start)];
+            self.source.seek(SeekFrom::Start(start as u64))?;
+            self.source.read_exact(&mut page)?;
+            self.pages.insert(index, (page, clock));
+        }
+        let (page, last_used) = self.pages.get_mut(&index).unwrap();
+        *last_used = clock;
+        Ok(page)
+    }
+}
+
+impl<R> NodeStorage for PagedStorage<R>
+where
+    R: Read + Seek,
+{
+    fn size(&self) -> usize {
+        self.size
+    }
+
+    fn read(&self, offset: usize, target: &mut [u8]) {
+        let mut cache = self.lock();
+        let mut position = offset;
+        let mut target = target;
+        while !target.is_empty() {
+            let page = cache
+                .page(position / self.page_size, self.page_size, self.size)
+                .unwrap_or_else(|error| panic!("Failed to read page: {}", error));
+            let start = position % self.page_size;
+            let length = target.len().min(page.len() - start);
+            let (head, tail) = target.split_at_mut(length);
+            head.copy_from_slice(&page[start..start + length]);
+            target = tail;
+            position += length;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::PagedStorage;
+    use crate::test_utilities::{create_random_points, SEED_1, SEED_2};
+    use crate::{PackedRTree, AABB};
+    use std::io::Cursor;
+
+    #[test]
+    fn test_paged_storage() {
+        let in_memory = PackedRTree::bulk_load(create_random_points(1000, SEED_1));
+        let bytes = in_memory.as_bytes().to_vec();
+        // Pages are smaller than nodes, so reads often span several pages.
+        let storage = PagedStorage::new(Cursor::new(bytes), 20, 8).unwrap();
+        let paged = PackedRTree::<[f64; 2], _>::from_storage(storage).unwrap();
+        assert_eq!(paged.size(), 1000);
+        for query in create_random_points(50, SEED_2) {
+            assert_eq!(
+                paged.nearest_neighbor(&query),
+                in_memory.nearest_neighbor(&query)
+            );
+            let envelope = AABB::from_corners(query, [query[0] + 0.2, query[1] + 0.1]);
+            assert!(paged
+                .locate_in_envelope(&envelope)
+                .eq(in_memory.locate_in_envelope(&envelope)));
+        }
+        let storage = paged.into_inner();
+        assert_eq!(storage.cached_pages(), 8);
+        assert_eq!(storage.into_inner().into_inner(), in_memory.into_inner());
+    }
+}
@@ -0,0 +1,369 @@
+use crate::algorithm::iterators::RTreeIterator;
+use crate::node::{ParentNode, RTreeNode};
+use crate::params::RTreeParams;
+use crate::point::Point;
+use crate::{Envelope, PointDistance, RTree, RTreeObject};
+
+use alloc::vec::Vec;
+use core::iter::Once;
+use rayon::iter::plumbing::UnindexedConsumer;
+use rayon::iter::{
+    Either, FlatMapIter, IntoParallelIterator, IntoParallelRefIterator, ParallelIterator,
+};
+
+type SubtreeIter<'a, T> = Either<Once<&'a T>, RTreeIterator<'a, T>>;
+type IterSubtree<'a, T> = fn(&'a RTreeNode<T>) -> SubtreeIter<'a, T>;
+type Scalar<T> = <<<T as RTreeObject>::Envelope as Envelope>::Point as Point>::Scalar;
+
+/// Parallel iterator over all elements of an [RTree], see [RTree::par_iter].
+pub struct ParIter<'a, T>
+where
+    T: RTreeObject + Sync,
+    T::Envelope: Sync,
+{
+    inner: FlatMapIter<rayon::vec::IntoIter<&'a RTreeNode<T>>, IterSubtree<'a, T>>,
+}
+
+impl<'a, T> ParallelIterator for ParIter<'a, T>
+where
+    T: RTreeObject + Sync,
+    T::Envelope: Sync,
+{
+    type Item = &'a T;
+
+    fn drive_unindexed<C>(self, consumer: C) -> C::Result
+    where
+        C: UnindexedConsumer<Self::Item>,
+    {
+        self.inner.drive_unindexed(consumer)
+    }
+}
+
+fn iter_subtree<T>(node: &RTreeNode<T>) -> SubtreeIter<'_, T>
+where
+    T: RTreeObject,
+{
+    match node {
+        RTreeNode::Leaf(t) => Either::Left(core::iter::once(t)),
+        RTreeNode::Parent(data) => Either::Right(RTreeIterator::new(data)),
+    }
+}
+
+impl<T, Params> RTree<T, Params>
+where
+    T: RTreeObject + Sync,
+    T::Envelope: Sync,
+    Params: RTreeParams,
+{
+    /// Returns a parallel iterator over all elements of the tree.
+    ///
+    /// Work is split along the tree's subtrees: the tree is descended until there are a few
+    /// subtrees for every thread of the current rayon thread pool. Requires the `rayon`
+    /// feature.
+    ///
+    /// # Example
+    /// ```
+    /// use rayon::prelude::*;
+    /// use rstar::RTree;
+    ///
+    /// let tree = RTree::bulk_load((0..1000).map(|i| [i as f64, 0.0]).collect());
+    /// let sum: f64 = tree.par_iter().map(|point| point[0]).sum();
+    /// assert_eq!(sum, 499_500.0);
+    /// ```
+    pub fn par_iter(&self) -> ParIter<'_, T> {
+        let target = rayon::current_num_threads() * 4;
+        let mut subtrees: Vec<_> = self.root().children.iter().collect();
+        while subtrees.len() < target && subtrees.first().map_or(false, |node| !node.is_leaf()) {
+            subtrees = subtrees
+                .into_iter()
+                .flat_map(|node| match node {
+                    RTreeNode::Parent(data) => data.children.iter(),
+                    RTreeNode::Leaf(_) => unreachable!("Unbalanced tree. This is a bug in rstar."),
+                })
+                .collect();
+        }
+        ParIter {
+            inner: subtrees
+                .into_par_iter()
+                .flat_map_iter(iter_subtree as IterSubtree<'_, T>),
+        }
+    }
+}
+
+impl<T, Params> RTree<T, Params>
+where
+    T: RTreeObject + Sync,
+    T::Envelope: Sync,
+    Params: RTreeParams,
+{
+    /// Runs [RTree::locate_in_envelope_intersecting] for many envelopes in parallel.
+    ///
+    /// The results are returned in the order of `envelopes`. Requires the `rayon` feature.
+    ///
+    /// # Example
+    /// ```
+    /// use rstar::{RTree, AABB};
+    ///
+    /// let tree = RTree::bulk_load(vec![[0.0, 0.0], [1.0, 1.0], [2.0, 2.0]]);
+    /// let envelopes = [
+    ///     AABB::from_corners([-0.5, -0.5], [0.5, 0.5]),
+    ///     AABB::from_corners([0.5, 0.5], [2.5, 2.5]),
+    /// ];
+    /// let results = tree.par_locate_in_envelope_intersecting(&envelopes);
+    /// assert_eq!(results[0], vec![&[0.0, 0.0]]);
+    /// assert_eq!(results[1].len(), 2);
+    /// ```
+    pub fn par_locate_in_envelope_intersecting(&self, envelopes: &[T::Envelope]) -> Vec<Vec<&T>> {
+        envelopes
+            .par_iter()
+            .map(|envelope| self.locate_in_envelope_intersecting(envelope).collect())
+            .collect()
+    }
+}
+
+impl<T, Params> RTree<T, Params>
+where
+    T: PointDistance + Sync,
+    T::Envelope: Sync,
+    <T::Envelope as Envelope>::Point: Sync,
+    Params: RTreeParams,
+{
+    /// Runs [RTree::nearest_neighbor] for many query points in parallel.
+    ///
+    /// The results are returned in the order of `query_points`. Requires the `rayon` feature.
+    ///
+    /// # Example
+    /// ```
+    /// use rstar::RTree;
+    ///
+    /// let tree = RTree::bulk_load(vec![[0.0, 0.0], [1.0, 1.0]]);
+    /// let nearest = tree.par_nearest_neighbor(&[[0.2, 0.1], [0.9, 1.3]]);
+    /// assert_eq!(nearest, vec![Some(&[0.0, 0.0]), Some(&[1.0, 1.0])]);
+    /// ```
+    pub fn par_nearest_neighbor(
+        &self,
+        query_points: &[<T::Envelope as Envelope>::Point],
+    ) -> Vec<Option<&T>> {
+        query_points
+            .par_iter()
+            .map(|query_point| self.nearest_neighbor(query_point))
+            .collect()
+    }
+}
+
+impl<T, Params> RTree<T, Params>
+where
+    T: PointDistance + Sync,
+    T::Envelope: Sync,
+    Params: RTreeParams,
+{
+    /// Returns the `k` nearest neighbors of every element in the tree.
+    ///
+    /// The result contains one entry for every element, pairing the element with its
+    /// neighbors ordered by increasing distance. An element is never its own neighbor, but
+    /// other elements at the same position are. Distances are measured from the center of
+    /// an element's envelope. If the tree contains fewer than `k + 1` elements, all other
+    /// elements are returned.
+    ///
+    /// This uses a dual-tree traversal: all elements of a leaf node are queried together,
+    /// sharing one traversal of the tree. Leaf nodes are processed in parallel. Requires the
+    /// `rayon` feature.
+    ///
+    /// # Example
+    /// ```
+    /// use rstar::RTree;
+    ///
+    /// let tree = RTree::bulk_load(vec![[0.0, 0.0], [1.0, 0.0], [3.0, 0.0]]);
+    /// let graph = tree.knn_graph(1);
+    /// assert_eq!(graph.len(), 3);
+    /// let (_, neighbors) = graph.iter().find(|(element, _)| **element == [3.0, 0.0]).unwrap();
+    /// assert_eq!(neighbors, &vec![&[1.0, 0.0]]);
+    /// ```
+    pub fn knn_graph(&self, k: usize) -> Vec<(&T, Vec<&T>)> {
+        let mut leaf_parents = Vec::new();
+        let mut stack = alloc::vec![self.root()];
+        while let Some(node) = stack.pop() {
+            match node.children.first() {
+                Some(RTreeNode::Parent(_)) => {
+                    stack.extend(node.children.iter().map(|child| match child {
+                        RTreeNode::Parent(data) => data,
+                        RTreeNode::Leaf(_) => {
+                            unreachable!("Unbalanced tree. This is a bug in rstar.")
+                        }
+                    }))
+                }
+                Some(RTreeNode::Leaf(_)) => leaf_parents.push(node),
+                None => {}
+            }
+        }
+        leaf_parents
+            .into_par_iter()
+            .flat_map_iter(|node| knn_of_leaves(self.root(), node, k))
+            .collect()
+    }
+}
+
+fn knn_of_leaves<'a, T>(
+    root: &'a ParentNode<T>,
+    queries: &'a ParentNode<T>,
+    k: usize,
+) -> Vec<(&'a T, Vec<&'a T>)>
+where
+    T: PointDistance,
+{
+    let queries: Vec<_> = queries
+        .children
+        .iter()
+        .map(|child| match child {
+            RTreeNode::Leaf(t) => (t, t.envelope().center()),
+            RTreeNode::Parent(_) => unreachable!("Unbalanced tree. This is a bug in rstar."),
+        })
+        .collect();
+    // The current neighbors of every query, ordered by increasing distance.
+    let mut neighbors: Vec<Vec<(Scalar<T>, &T)>> =
+        queries.iter().map(|_| Vec::with_capacity(k + 1)).collect();
+    // A reference node can be skipped if it can't contain a closer neighbor for any query.
+    let is_relevant = |envelope: &T::Envelope, neighbors: &[Vec<(Scalar<T>, &T)>]| {
+        queries.iter().zip(neighbors).any(|((_, center), found)| {
+            found.len() < k || envelope.distance_2(center) < found[k - 1].0
+        })
+    };
+    let min_distance = |envelope: &T::Envelope| {
+        queries
+            .iter()
+            .map(|(_, center)| envelope.distance_2(center))
+            .fold(None, |min: Option<Scalar<T>>, distance| match min {
+                Some(min) if min <= distance => Some(min),
+                _ => Some(distance),
+            })
+    };
+
+    if k > 0 {
+        let mut stack = alloc::vec![root];
+        while let Some(node) = stack.pop() {
+            if !is_relevant(&node.envelope, &neighbors) {
+                continue;
+            }
+            match node.children.first() {
+                Some(RTreeNode::Parent(_)) => {
+                    let mut children: Vec<_> = node
+                        .children
+                        .iter()
+                        .filter_map(|child| match child {
+                            RTreeNode::Parent(data) => Some((min_distance(&data.envelope), data)),
+                            RTreeNode::Leaf(_) => None,
+                        })
+                        .collect();
+                    // Visit the closest children first, they are pushed last.
+                    children.sort_by(|(l, _), (r, _)| r.partial_cmp(l).unwrap());
+                    stack.extend(children.into_iter().map(|(_, data)| data));
+                }
+                _ => {
+                    for child in &node.children {
+                        let reference = match child {
+                            RTreeNode::Leaf(t) => t,
+                            RTreeNode::Parent(_) => {
+                                unreachable!("Unbalanced tree. This is a bug in rstar.")
+                            }
+                        };
+                        for ((query, center), found) in queries.iter().zip(&mut neighbors) {
+                            if core::ptr::eq(*query, reference) {
+                                continue;
+                            }
+                            let distance = reference.distance_2(center);
+                            if found.len() < k || distance < found[k - 1].0 {
+                                let index = found.partition_point(|(d, _)| *d <= distance);
+                                found.insert(index, (distance, reference));
+                                found.truncate(k);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    queries
+        .into_iter()
+        .zip(neighbors)
+        .map(|((query, _), found)| (query, found.into_iter().map(|(_, t)| t).collect()))
+        .collect()
+}
+
+impl<'a, T, Params> IntoParallelIterator for &'a RTree<T, Params>
+where
+    T: RTreeObject + Sync,
+    T::Envelope: Sync,
+    Params: RTreeParams,
+{
+    type Iter = ParIter<'a, T>;
+    type Item = &'a T;
+
+    fn into_par_iter(self) -> Self::Iter {
+        self.par_iter()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_utilities::{create_random_points, SEED_1, SEED_2};
+    use crate::{RTree, AABB};
+    use rayon::prelude::*;
+
+    #[test]
+    fn test_par_iter() {
+        let points = create_random_points(1000, SEED_1);
+        let tree = RTree::bulk_load(points.clone());
+        let mut collected: Vec<_> = tree.par_iter().copied().collect();
+        let mut expected = points;
+        collected.sort_by(|l, r| l.partial_cmp(r).unwrap());
+        expected.sort_by(|l, r| l.partial_cmp(r).unwrap());
+        assert_eq!(collected, expected);
+        assert_eq!((&tree).into_par_iter().count(), 1000);
+        assert_eq!(RTree::<[f64; 2]>::new().par_iter().count(), 0);
+    }
+
+    #[test]
+    fn test_par_queries() {
+        let tree = RTree::bulk_load(create_random_points(1000, SEED_1));
+        let queries = create_random_points(100, SEED_2);
+        let nearest = tree.par_nearest_neighbor(&queries);
+        let envelopes: Vec<_> = queries
+            .iter()
+            .map(|&[x, y]| AABB::from_corners([x, y], [x + 0.1, y + 0.1]))
+            .collect();
+        let located = tree.par_locate_in_envelope_intersecting(&envelopes);
+        for ((query, envelope), (nearest, located)) in queries
+            .iter()
+            .zip(&envelopes)
+            .zip(nearest.iter().zip(&located))
+        {
+            assert_eq!(*nearest, tree.nearest_neighbor(query));
+            assert_eq!(
+                located.len(),
+                tree.locate_in_envelope_intersecting(envelope).count()
+            );
+        }
+    }
+
+    #[test]
+    fn test_knn_graph() {
+        let points = create_random_points(1000, SEED_1);
+        let tree = RTree::bulk_load(points.clone());
+        for k in [0, 1, 5] {
+            let graph = tree.knn_graph(k);
+            assert_eq!(graph.len(), 1000);
+            for (point, neighbors) in graph {
+                let expected: Vec<_> = tree.nearest_neighbor_iter(point).skip(1).take(k).collect();
+                assert_eq!(neighbors, expected);
+            }
+        }
+
+        let small = RTree::bulk_load(points[..3].to_vec());
+        assert!(small
+            .knn_graph(5)
+            .iter()
+            .all(|(_, neighbors)| neighbors.len() == 2));
+        assert!(RTree::<[f64; 2]>::new().knn_graph(3).is_empty());
+    }
+}
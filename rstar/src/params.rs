@@ -1,4 +1,5 @@
-use crate::algorithm::rstar::RStarInsertionStrategy;
+use crate::algorithm::rstar::{RStarInsertionStrategy, RStarSplitStrategy};
+use crate::node::{ParentNode, RTreeNode};
 use crate::{Envelope, Point, RTree, RTreeObject};
 
 /// Defines static parameters for an r-tree.
@@ -45,13 +46,31 @@ pub trait RTreeParams: Send + Sync {
 
     /// The maximum size of an internal node. Larger values will improve insertion performance
     /// but increase the average query time.
+    ///
+    /// Leaf nodes, whose children are elements, use `MAX_LEAF_SIZE` instead.
     const MAX_SIZE: usize;
 
+    /// The maximum size of a leaf node, defaults to `MAX_SIZE`.
+    ///
+    /// Disk oriented r-trees often store many small elements per leaf page while keeping
+    /// internal nodes small. `MIN_SIZE` and `REINSERTION_COUNT` apply to leaf nodes as well,
+    /// which puts the same constraints on `MAX_LEAF_SIZE` as on `MAX_SIZE`.
+    const MAX_LEAF_SIZE: usize = Self::MAX_SIZE;
+
     /// The number of nodes that the insertion strategy tries to occasionally reinsert to
     /// maintain a good tree quality. Must be smaller than `MAX_SIZE` - `MIN_SIZE`.
     /// Larger values will improve query times but increase insertion time.
     const REINSERTION_COUNT: usize;
 
+    /// The maximum height of the tree, unlimited by default. Must be at least 1.
+    ///
+    /// The height is the number of node levels, a tree whose root only contains elements has
+    /// height 1. Limiting it bounds the recursion depth and the worst case latency of queries,
+    /// which matters on embedded targets with small stacks. [RTree::insert] panics and
+    /// [RTree::try_insert] returns an error if an insertion could exceed this limit. Bulk
+    /// loading does not respect the limit.
+    const MAX_HEIGHT: usize = usize::MAX;
+
     /// The insertion strategy which is used when calling [RTree::insert].
     type DefaultInsertionStrategy: InsertionStrategy;
 }
@@ -72,13 +91,17 @@ impl RTreeParams for DefaultParams {
 /// Different strategies try to minimize both _insertion time_ (how long does it take to add a new
 /// object into the tree?) and _querying time_ (how long does an average nearest neighbor query
 /// take?).
-/// Currently, only one insertion strategy is implemented: R* (R-star) insertion. R* insertion
-/// tries to minimize querying performance while yielding reasonable insertion times, making it a
-/// good default strategy. More strategies may be implemented in the future.
+/// Three insertion strategies are implemented: R* (R-star) insertion tries to minimize querying
+/// performance while yielding reasonable insertion times, making it a good default strategy.
+/// [SplitOnlyInsertionStrategy](crate::SplitOnlyInsertionStrategy) skips R*'s forced
+/// reinsertion for more predictable insertion times.
+/// [MinimalOverlapInsertionStrategy](crate::MinimalOverlapInsertionStrategy) reduces the
+/// overlap between siblings, which benefits point queries over overlapping rectangles.
 ///
 /// Only calls to [RTree::insert] are affected by this strategy.
 ///
-/// This trait is not meant to be implemented by the user.
+/// This trait is not meant to be implemented by the user. Custom heuristics can be plugged
+/// in with [CustomInsertionStrategy](crate::CustomInsertionStrategy) instead.
 pub trait InsertionStrategy {
     #[doc(hidden)]
     fn insert<T, Params>(tree: &mut RTree<T, Params>, t: T)
@@ -87,30 +110,302 @@ pub trait InsertionStrategy {
         T: RTreeObject;
 }
 
-pub fn verify_parameters<T: RTreeObject, P: RTreeParams>() {
-    assert!(
-        P::MAX_SIZE >= 4,
-        "MAX_SIZE too small. Must be larger than 4."
-    );
+/// How an overflowing node is treated during insertion, see [InsertionHeuristic].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OverflowTreatment {
+    /// Splits the node into two nodes.
+    Split,
+    /// Removes [RTreeParams::REINSERTION_COUNT] children farthest from the node's center
+    /// and inserts them again from the root, as done by the r*-tree.
+    ///
+    /// Only the first overflow of an insertion may be resolved by reinsertion, subsequent
+    /// overflows are always split. Nodes are also split if `REINSERTION_COUNT` is zero.
+    Reinsert,
+}
 
-    assert!(P::MIN_SIZE > 0, "MIN_SIZE must be at least 1",);
-    let max_min_size = (P::MAX_SIZE + 1) / 2;
-    assert!(
-        P::MIN_SIZE <= max_min_size,
-        "MIN_SIZE too large. Must be less or equal to {:?}",
-        max_min_size
-    );
+/// The decisions made while inserting an element, see
+/// [CustomInsertionStrategy](crate::CustomInsertionStrategy).
+///
+/// All methods default to the r*-tree's behavior, implementations only need to override
+/// the decisions they want to change. The built-in insertion strategies implement this trait
+/// as well, which allows to fall back to them, e.g. by calling
+/// `<RStarInsertionStrategy as InsertionHeuristic>::choose_subtree`.
+pub trait InsertionHeuristic {
+    /// Returns the index of the child of `node` that should receive an object with the given
+    /// envelope.
+    ///
+    /// Only called for nodes whose children are parent nodes. The index must be smaller
+    /// than the number of children. By default, the child whose envelope needs the least
+    /// overlap enlargement (on the level above the leaves) or area enlargement (on all other
+    /// levels) is chosen.
+    fn choose_subtree<T: RTreeObject>(node: &ParentNode<T>, envelope: &T::Envelope) -> usize {
+        crate::algorithm::rstar::rstar_choose_subtree(node, envelope, false)
+    }
+
+    /// Decides how an overflowing node at the given depth is treated. The root has depth
+    /// zero.
+    ///
+    /// Leaf nodes overflow with more than [RTreeParams::MAX_LEAF_SIZE] children, all other
+    /// nodes with more than [RTreeParams::MAX_SIZE] children.
+    ///
+    /// Defaults to [OverflowTreatment::Reinsert].
+    fn overflow_treatment<T, Params>(node: &ParentNode<T>, depth: usize) -> OverflowTreatment
+    where
+        T: RTreeObject,
+        Params: RTreeParams,
+    {
+        let _ = (node, depth);
+        OverflowTreatment::Reinsert
+    }
+
+    #[doc(hidden)]
+    fn split<T, Params>(node: &mut ParentNode<T>) -> RTreeNode<T>
+    where
+        T: RTreeObject,
+        Params: RTreeParams,
+    {
+        RStarSplitStrategy::split::<_, Params>(node)
+    }
+}
+
+/// Defines how overflowing nodes are split into two nodes.
+///
+/// The split strategy has a large impact on both insertion time and the quality of the
+/// resulting tree. Three strategies are implemented: the R* split
+/// ([RStarSplitStrategy](crate::RStarSplitStrategy)) creates nodes with little overlap and is
+/// used by default. Guttman's quadratic
+/// ([QuadraticSplitStrategy](crate::QuadraticSplitStrategy)) and linear
+/// ([LinearSplitStrategy](crate::LinearSplitStrategy)) splits are faster, at the cost of
+/// slower queries.
+///
+/// The split strategy is chosen with the type parameter of
+/// [RStarInsertionStrategy](crate::RStarInsertionStrategy) or
+/// [SplitOnlyInsertionStrategy](crate::SplitOnlyInsertionStrategy), e.g.
+/// `RStarInsertionStrategy<QuadraticSplitStrategy>`. Only nodes split by [RTree::insert] are
+/// affected by this strategy.
+/// [MinimalOverlapInsertionStrategy](crate::MinimalOverlapInsertionStrategy) always uses its
+/// own split.
+///
+/// This trait is not meant to be implemented by the user.
+pub trait SplitStrategy {
+    #[doc(hidden)]
+    fn split<T, Params>(node: &mut ParentNode<T>) -> RTreeNode<T>
+    where
+        Params: RTreeParams,
+        T: RTreeObject;
+}
+
+/// A violated constraint of [RTreeParams], returned by [check_parameters].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParamsError {
+    /// [RTreeParams::MAX_SIZE] is smaller than 4.
+    MaxSizeTooSmall {
+        /// The configured maximum size.
+        max_size: usize,
+    },
+    /// [RTreeParams::MIN_SIZE] is zero.
+    MinSizeZero,
+    /// [RTreeParams::MIN_SIZE] is larger than half of [RTreeParams::MAX_SIZE], rounded up.
+    MinSizeTooLarge {
+        /// The configured minimum size.
+        min_size: usize,
+        /// The largest allowed minimum size.
+        limit: usize,
+    },
+    /// [RTreeParams::REINSERTION_COUNT] is not smaller than `MAX_SIZE - MIN_SIZE`.
+    ReinsertionCountTooLarge {
+        /// The configured reinsertion count.
+        reinsertion_count: usize,
+        /// The exclusive upper bound of the reinsertion count.
+        limit: usize,
+    },
+    /// [RTreeParams::MAX_LEAF_SIZE] is too small for the configured minimum size and
+    /// reinsertion count.
+    MaxLeafSizeTooSmall {
+        /// The configured maximum leaf size.
+        max_leaf_size: usize,
+        /// The smallest allowed maximum leaf size.
+        limit: usize,
+    },
+    /// [RTreeParams::MAX_HEIGHT] is zero.
+    MaxHeightZero,
+    /// The point type has less than two dimensions.
+    DimensionTooSmall {
+        /// The point type's dimension.
+        dimensions: usize,
+    },
+}
 
+impl core::fmt::Display for ParamsError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ParamsError::MaxSizeTooSmall { max_size } => write!(
+                f,
+                "MAX_SIZE too small. Must be at least 4, but is {}",
+                max_size
+            ),
+            ParamsError::MinSizeZero => write!(f, "MIN_SIZE must be at least 1"),
+            ParamsError::MinSizeTooLarge { min_size, limit } => write!(
+                f,
+                "MIN_SIZE too large. Must be less or equal to {}, but is {}",
+                limit, min_size
+            ),
+            ParamsError::ReinsertionCountTooLarge {
+                reinsertion_count,
+                limit,
+            } => write!(
+                f,
+                "REINSERTION_COUNT too large. Must be smaller than {}, but is {}",
+                limit, reinsertion_count
+            ),
+            ParamsError::MaxLeafSizeTooSmall {
+                max_leaf_size,
+                limit,
+            } => write!(
+                f,
+                "MAX_LEAF_SIZE too small. Must be at least {}, but is {}",
+                limit, max_leaf_size
+            ),
+            ParamsError::MaxHeightZero => write!(f, "MAX_HEIGHT must be at least 1"),
+            ParamsError::DimensionTooSmall { dimensions } => write!(
+                f,
+                "Point dimension too small - must be at least 2, but is {}",
+                dimensions
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParamsError {}
+
+/// Checks if a set of parameters can be used for a tree containing elements of type `T`.
+///
+/// Trees created with invalid parameters panic on construction. Use this function, or
+/// [RTree::try_new_with_params] and [RTree::try_bulk_load_with_params], to detect invalid
+/// parameters before that.
+///
+/// # Example
+/// ```
+/// use rstar::{check_parameters, DefaultParams, ParamsError, RStarInsertionStrategy, RTreeParams};
+///
+/// struct TooLargeMinSize;
+///
+/// impl RTreeParams for TooLargeMinSize {
+///     const MIN_SIZE: usize = 5;
+///     const MAX_SIZE: usize = 6;
+///     const REINSERTION_COUNT: usize = 0;
+///     type DefaultInsertionStrategy = RStarInsertionStrategy;
+/// }
+///
+/// assert_eq!(check_parameters::<[f64; 2], DefaultParams>(), Ok(()));
+/// assert_eq!(
+///     check_parameters::<[f64; 2], TooLargeMinSize>(),
+///     Err(ParamsError::MinSizeTooLarge { min_size: 5, limit: 3 })
+/// );
+/// ```
+pub fn check_parameters<T: RTreeObject, P: RTreeParams>() -> Result<(), ParamsError> {
+    if P::MAX_SIZE < 4 {
+        return Err(ParamsError::MaxSizeTooSmall {
+            max_size: P::MAX_SIZE,
+        });
+    }
+    if P::MIN_SIZE == 0 {
+        return Err(ParamsError::MinSizeZero);
+    }
+    let max_min_size = (P::MAX_SIZE + 1) / 2;
+    if P::MIN_SIZE > max_min_size {
+        return Err(ParamsError::MinSizeTooLarge {
+            min_size: P::MIN_SIZE,
+            limit: max_min_size,
+        });
+    }
     let max_reinsertion_count = P::MAX_SIZE - P::MIN_SIZE;
-    assert!(
-        P::REINSERTION_COUNT < max_reinsertion_count,
-        "REINSERTION_COUNT too large. Must be smaller than {:?}",
-        max_reinsertion_count
-    );
-
-    let dimension = <T::Envelope as Envelope>::Point::DIMENSIONS;
-    assert!(
-        dimension > 1,
-        "Point dimension too small - must be at least 2"
-    );
+    if P::REINSERTION_COUNT >= max_reinsertion_count {
+        return Err(ParamsError::ReinsertionCountTooLarge {
+            reinsertion_count: P::REINSERTION_COUNT,
+            limit: max_reinsertion_count,
+        });
+    }
+    let min_leaf_size = (2 * P::MIN_SIZE - 1)
+        .max(P::MIN_SIZE + P::REINSERTION_COUNT + 1)
+        .max(4);
+    if P::MAX_LEAF_SIZE < min_leaf_size {
+        return Err(ParamsError::MaxLeafSizeTooSmall {
+            max_leaf_size: P::MAX_LEAF_SIZE,
+            limit: min_leaf_size,
+        });
+    }
+    if P::MAX_HEIGHT == 0 {
+        return Err(ParamsError::MaxHeightZero);
+    }
+    let dimensions = <T::Envelope as Envelope>::Point::DIMENSIONS;
+    if dimensions < 2 {
+        return Err(ParamsError::DimensionTooSmall { dimensions });
+    }
+    Ok(())
+}
+
+pub fn verify_parameters<T: RTreeObject, P: RTreeParams>() {
+    if let Err(error) = check_parameters::<T, P>() {
+        panic!("{}", error);
+    }
+}
+
+/// Parameters given as const generic arguments, checked at compile time.
+///
+/// Uses R* insertion and splits. Unlike a custom [RTreeParams] implementation, invalid
+/// parameters are rejected when the tree type is compiled instead of when a tree is created.
+///
+/// # Example
+/// ```
+/// use rstar::{ConstParams, RTree};
+///
+/// let mut tree: RTree<[f64; 2], ConstParams<4, 12, 3>> = RTree::new_with_params();
+/// tree.insert([0.0, 1.0]);
+/// ```
+///
+/// Invalid parameters fail to compile:
+/// ```compile_fail
+/// use rstar::{ConstParams, RTree};
+///
+/// let mut tree: RTree<[f64; 2], ConstParams<8, 12, 3>> = RTree::new_with_params();
+/// tree.insert([0.0, 1.0]);
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct ConstParams<const MIN_SIZE: usize, const MAX_SIZE: usize, const REINSERTION_COUNT: usize>;
+
+impl<const MIN_SIZE: usize, const MAX_SIZE: usize, const REINSERTION_COUNT: usize>
+    ConstParams<MIN_SIZE, MAX_SIZE, REINSERTION_COUNT>
+{
+    const VALID: () = {
+        assert!(MAX_SIZE >= 4, "MAX_SIZE too small. Must be at least 4.");
+        assert!(MIN_SIZE > 0, "MIN_SIZE must be at least 1");
+        assert!(
+            MIN_SIZE <= (MAX_SIZE + 1) / 2,
+            "MIN_SIZE too large. Must be less or equal to half of MAX_SIZE."
+        );
+        assert!(
+            REINSERTION_COUNT < MAX_SIZE - MIN_SIZE,
+            "REINSERTION_COUNT too large. Must be smaller than MAX_SIZE - MIN_SIZE."
+        );
+    };
+}
+
+impl<const MIN_SIZE: usize, const MAX_SIZE: usize, const REINSERTION_COUNT: usize> RTreeParams
+    for ConstParams<MIN_SIZE, MAX_SIZE, REINSERTION_COUNT>
+{
+    const MIN_SIZE: usize = {
+        let () = Self::VALID;
+        MIN_SIZE
+    };
+    const MAX_SIZE: usize = {
+        let () = Self::VALID;
+        MAX_SIZE
+    };
+    const REINSERTION_COUNT: usize = {
+        let () = Self::VALID;
+        REINSERTION_COUNT
+    };
+    type DefaultInsertionStrategy = RStarInsertionStrategy;
 }
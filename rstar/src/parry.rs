@@ -0,0 +1,190 @@
+//! Adapters for shapes of the [parry](https://parry.rs) collision detection library.
+//!
+//! Only available with the `parry` feature, or with the `parry2d` and `parry3d` features for
+//! one of both dimensions. [dim2] and [dim3] implement [RTreeObject](crate::RTreeObject) and
+//! [PointDistance](crate::PointDistance) for parry's bounding boxes and for shapes placed at a
+//! position, which allows to use an r-tree as broad phase without writing geometry adapters:
+//!
+//! ```
+//! use parry2d::math::Isometry;
+//! use parry2d::shape::SharedShape;
+//! use rstar::parry::dim2::PositionedShape;
+//! use rstar::primitives::GeomWithData;
+//! use rstar::{RTree, RTreeObject};
+//!
+//! let ball = SharedShape::ball(1.0);
+//! let tree = RTree::bulk_load(vec![
+//!     GeomWithData::new(PositionedShape::new(ball.clone(), Isometry::translation(0.0, 0.0)), 0),
+//!     GeomWithData::new(PositionedShape::new(ball, Isometry::translation(5.0, 0.0)), 1),
+//! ]);
+//! let query = PositionedShape::new(SharedShape::cuboid(1.0, 1.0), Isometry::translation(3.5, 0.0));
+//! let candidates: Vec<_> = tree
+//!     .locate_in_envelope_intersecting(&query.envelope())
+//!     .map(|shape| shape.data)
+//!     .collect();
+//! assert_eq!(candidates, vec![1]);
+//! ```
+//!
+//! Positioned shapes store the envelope of their placement, moving a shape requires to remove
+//! and reinsert it. Additional data like collider handles can be attached with
+//! [GeomWithData](crate::primitives::GeomWithData).
+
+macro_rules! parry_adapters {
+    ($parry:ident, $dimensions:literal) => {
+        use crate::aabb::AABB;
+        use crate::envelope::Envelope;
+        use crate::object::{PointDistance, RTreeObject};
+
+        use $parry::bounding_volume::Aabb;
+        use $parry::math::{Isometry, Point, Real};
+        use $parry::shape::SharedShape;
+
+        fn to_point(point: &Point<Real>) -> [Real; $dimensions] {
+            point.coords.into()
+        }
+
+        fn from_point(point: &[Real; $dimensions]) -> Point<Real> {
+            Point::from(*point)
+        }
+
+        impl RTreeObject for Aabb {
+            type Envelope = AABB<[Real; $dimensions]>;
+
+            fn envelope(&self) -> Self::Envelope {
+                AABB::from_corners(to_point(&self.mins), to_point(&self.maxs))
+            }
+        }
+
+        impl PointDistance for Aabb {
+            fn distance_2(&self, point: &[Real; $dimensions]) -> Real {
+                self.envelope().distance_2(point)
+            }
+
+            fn contains_point(&self, point: &[Real; $dimensions]) -> bool {
+                self.envelope().contains_point(point)
+            }
+        }
+
+        /// A parry shape placed at a position.
+        ///
+        /// The envelope is the shape's bounding box at its position, distances are measured to
+        /// the solid shape, so points inside of it have a distance of zero.
+        #[derive(Clone, Debug)]
+        pub struct PositionedShape {
+            /// The shape.
+            pub shape: SharedShape,
+            /// The position of the shape.
+            pub position: Isometry<Real>,
+        }
+
+        impl PositionedShape {
+            /// Places a shape at a position.
+            pub fn new(shape: SharedShape, position: Isometry<Real>) -> Self {
+                PositionedShape { shape, position }
+            }
+        }
+
+        impl RTreeObject for PositionedShape {
+            type Envelope = AABB<[Real; $dimensions]>;
+
+            fn envelope(&self) -> Self::Envelope {
+                self.shape.compute_aabb(&self.position).envelope()
+            }
+        }
+
+        impl PointDistance for PositionedShape {
+            fn distance_2(&self, point: &[Real; $dimensions]) -> Real {
+                let distance =
+                    self.shape
+                        .distance_to_point(&self.position, &from_point(point), true);
+                distance * distance
+            }
+
+            fn contains_point(&self, point: &[Real; $dimensions]) -> bool {
+                self.shape
+                    .contains_point(&self.position, &from_point(point))
+            }
+        }
+    };
+}
+
+/// Adapters for [parry2d](https://crates.io/crates/parry2d), see the [module documentation](self).
+#[cfg(feature = "parry2d")]
+pub mod dim2 {
+    parry_adapters!(parry2d, 2);
+}
+
+/// Adapters for [parry3d](https://crates.io/crates/parry3d), see the [module documentation](self).
+#[cfg(feature = "parry3d")]
+pub mod dim3 {
+    parry_adapters!(parry3d, 3);
+}
+
+#[cfg(all(test, feature = "parry2d"))]
+mod test {
+    use super::dim2::PositionedShape;
+    use crate::primitives::GeomWithData;
+    use crate::test_utilities::{create_random_points, SEED_1, SEED_2};
+    use crate::{PointDistance, RTree, RTreeObject};
+    use parry2d::bounding_volume::{Aabb, BoundingVolume};
+    use parry2d::math::{Isometry, Point};
+    use parry2d::shape::SharedShape;
+
+    #[test]
+    fn test_parry_shapes() {
+        let shapes: Vec<_> = create_random_points(200, SEED_1)
+            .into_iter()
+            .enumerate()
+            .map(|(index, [x, y])| {
+                let shape = if index % 2 == 0 {
+                    SharedShape::ball(0.01)
+                } else {
+                    SharedShape::cuboid(0.02, 0.01)
+                };
+                let position = Isometry::new([x as f32, y as f32].into(), index as f32);
+                GeomWithData::new(PositionedShape::new(shape, position), index)
+            })
+            .collect();
+        let tree = RTree::bulk_load(shapes.clone());
+
+        for [x, y] in create_random_points(20, SEED_2) {
+            let point = [x as f32, y as f32];
+            let nearest = tree.nearest_neighbor(&point).unwrap();
+            let min_distance = shapes
+                .iter()
+                .map(|shape| shape.distance_2(&point))
+                .fold(f32::INFINITY, f32::min);
+            assert_eq!(nearest.distance_2(&point), min_distance);
+
+            let query = Aabb::new(
+                Point::new(point[0], point[1]),
+                Point::new(x as f32 + 0.1, y as f32 + 0.1),
+            );
+            let mut found: Vec<_> = tree
+                .locate_in_envelope_intersecting(&query.envelope())
+                .map(|shape| shape.data)
+                .collect();
+            found.sort_unstable();
+            let expected: Vec<_> = shapes
+                .iter()
+                .filter(|shape| {
+                    shape
+                        .geom()
+                        .shape
+                        .compute_aabb(&shape.geom().position)
+                        .intersects(&query)
+                })
+                .map(|shape| shape.data)
+                .collect();
+            assert_eq!(found, expected);
+        }
+
+        let ball = PositionedShape::new(SharedShape::ball(1.0), Isometry::translation(1.0, 0.0));
+        assert!(ball.contains_point(&[1.5, 0.5]));
+        assert!(!ball.contains_point(&[1.5, 1.0]));
+        assert_eq!(ball.distance_2(&[4.0, 0.0]), 4.0);
+        let aabb = Aabb::new(Point::new(0.0, 0.0), Point::new(1.0, 1.0));
+        assert_eq!(aabb.distance_2(&[2.0, 0.5]), 1.0);
+        assert!(aabb.contains_point(&[0.5, 0.5]));
+    }
+}
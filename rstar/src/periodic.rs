@@ -0,0 +1,414 @@
+use crate::aabb::AABB;
+use crate::point::{max_inline, min_inline, Point};
+use crate::{Envelope, RTreeObject};
+use core::fmt::{Debug, Formatter};
+use core::marker::PhantomData;
+use num_traits::{Bounded, One, Signed, Zero};
+
+/// Defines a periodic domain, e.g. a simulation box that wraps around at its borders.
+///
+/// Along every periodic axis, coordinates are identified modulo the axis' period: the
+/// coordinates `0.0` and `period` denote the same position. Objects are expected to lie within
+/// the canonical cell `[0, period)` of each periodic axis, see
+/// [PeriodicPoint](crate::primitives::PeriodicPoint).
+///
+/// Similar to [RTreeParams](crate::RTreeParams), the domain is defined on the type level.
+///
+/// # Example
+/// ```
+/// use rstar::PeriodicDomain;
+///
+/// // A unit cube that wraps around along the x and y axis but not along the z axis.
+/// struct Slab;
+///
+/// impl PeriodicDomain for Slab {
+///     type Point = [f64; 3];
+///
+///     fn period(axis: usize) -> Option<f64> {
+///         if axis < 2 {
+///             Some(1.0)
+///         } else {
+///             None
+///         }
+///     }
+/// }
+/// ```
+pub trait PeriodicDomain {
+    /// The domain's point type.
+    type Point: Point;
+
+    /// Returns the period along an axis or `None` if the domain does not wrap around along
+    /// that axis.
+    fn period(axis: usize) -> Option<<Self::Point as Point>::Scalar>;
+
+    /// Maps a point into the domain's canonical cell.
+    fn wrap(point: &Self::Point) -> Self::Point {
+        let zero = Zero::zero();
+        Self::Point::generate(|axis| {
+            let value = point.nth(axis);
+            match Self::period(axis) {
+                Some(period) => {
+                    let wrapped = value % period;
+                    if wrapped < zero {
+                        wrapped + period
+                    } else {
+                        wrapped
+                    }
+                }
+                None => value,
+            }
+        })
+    }
+
+    /// Returns the squared distance between two points, measured between their closest images.
+    fn distance_2(from: &Self::Point, to: &Self::Point) -> <Self::Point as Point>::Scalar {
+        let (from, to) = (Self::wrap(from), Self::wrap(to));
+        let mut result = Zero::zero();
+        for axis in 0..Self::Point::DIMENSIONS {
+            let direct = (from.nth(axis) - to.nth(axis)).abs();
+            let distance = match Self::period(axis) {
+                Some(period) => min_inline(direct, period - direct),
+                None => direct,
+            };
+            result = result + distance * distance;
+        }
+        result
+    }
+}
+
+/// An axis aligned bounding box within a [PeriodicDomain].
+///
+/// This envelope behaves like an [AABB] in all regards except for distance
+/// calculations: [Envelope::distance_2] returns the distance to the closest image of the
+/// box, which makes [RTree::nearest_neighbor](crate::RTree::nearest_neighbor),
+/// [RTree::locate_within_distance](crate::RTree::locate_within_distance) and
+/// related queries consider neighbors across the domain's borders.
+///
+/// Query points may lie outside the canonical cell, they are wrapped into it. Envelope queries
+/// like [RTree::locate_in_envelope](crate::RTree::locate_in_envelope) are not periodic.
+///
+/// Use [PeriodicPoint](crate::primitives::PeriodicPoint) to insert points into a periodic tree.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "D::Point: serde::Serialize",
+        deserialize = "D::Point: serde::Deserialize<'de>"
+    ))
+)]
+pub struct PeriodicAABB<D>
+where
+    D: PeriodicDomain,
+{
+    aabb: AABB<D::Point>,
+    _domain: PhantomData<D>,
+}
+
+impl<D> PeriodicAABB<D>
+where
+    D: PeriodicDomain,
+{
+    /// Creates a new envelope from a (non periodic) bounding box.
+    pub fn from_aabb(aabb: AABB<D::Point>) -> Self {
+        PeriodicAABB {
+            aabb,
+            _domain: PhantomData,
+        }
+    }
+
+    /// Returns the envelope's bounding box within the canonical cell.
+    pub fn aabb(&self) -> &AABB<D::Point> {
+        &self.aabb
+    }
+
+    /// Calls `f` with the wrapped query coordinate, the box's bounds and the period of each
+    /// axis and sums up the squared results.
+    ///
+    /// An empty box is infinitely far away. Returns early in that case, the bounds of an
+    /// empty box overflow integer arithmetic.
+    fn fold_axes(
+        &self,
+        point: &D::Point,
+        mut f: impl FnMut(
+            <D::Point as Point>::Scalar,
+            <D::Point as Point>::Scalar,
+            <D::Point as Point>::Scalar,
+            Option<<D::Point as Point>::Scalar>,
+        ) -> <D::Point as Point>::Scalar,
+    ) -> <D::Point as Point>::Scalar {
+        let point = D::wrap(point);
+        let (lower, upper) = (self.aabb.lower(), self.aabb.upper());
+        if (0..D::Point::DIMENSIONS).any(|axis| lower.nth(axis) > upper.nth(axis)) {
+            return Bounded::max_value();
+        }
+        let mut result = Zero::zero();
+        for axis in 0..D::Point::DIMENSIONS {
+            let distance = f(
+                point.nth(axis),
+                lower.nth(axis),
+                upper.nth(axis),
+                D::period(axis),
+            );
+            result = result + distance * distance;
+        }
+        result
+    }
+}
+
+impl<D> Clone for PeriodicAABB<D>
+where
+    D: PeriodicDomain,
+{
+    fn clone(&self) -> Self {
+        Self::from_aabb(self.aabb.clone())
+    }
+}
+
+impl<D> PartialEq for PeriodicAABB<D>
+where
+    D: PeriodicDomain,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.aabb == other.aabb
+    }
+}
+
+impl<D> Debug for PeriodicAABB<D>
+where
+    D: PeriodicDomain,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("PeriodicAABB").field(&self.aabb).finish()
+    }
+}
+
+impl<D> Envelope for PeriodicAABB<D>
+where
+    D: PeriodicDomain,
+{
+    type Point = D::Point;
+
+    fn new_empty() -> Self {
+        Self::from_aabb(AABB::new_empty())
+    }
+
+    fn contains_point(&self, point: &Self::Point) -> bool {
+        self.aabb.contains_point(&D::wrap(point))
+    }
+
+    fn contains_envelope(&self, other: &Self) -> bool {
+        self.aabb.contains_envelope(&other.aabb)
+    }
+
+    fn merge(&mut self, other: &Self) {
+        self.aabb.merge(&other.aabb)
+    }
+
+    fn merged(&self, other: &Self) -> Self {
+        Self::from_aabb(self.aabb.merged(&other.aabb))
+    }
+
+    fn intersects(&self, other: &Self) -> bool {
+        self.aabb.intersects(&other.aabb)
+    }
+
+    fn intersection_area(&self, other: &Self) -> <Self::Point as Point>::Scalar {
+        self.aabb.intersection_area(&other.aabb)
+    }
+
+    fn area(&self) -> <Self::Point as Point>::Scalar {
+        self.aabb.area()
+    }
+
+    fn distance_2(&self, point: &Self::Point) -> <Self::Point as Point>::Scalar {
+        let zero = Zero::zero();
+        self.fold_axes(point, |p, lower, upper, period| {
+            if lower <= p && p <= upper {
+                return zero;
+            }
+            let direct = max_inline(lower - p, p - upper);
+            match period {
+                // Both the point and the box lie within the canonical cell, so the closest
+                // image is at most one period away.
+                Some(period) if p < lower => min_inline(direct, p + period - upper),
+                Some(period) => min_inline(direct, lower + period - p),
+                None => direct,
+            }
+        })
+    }
+
+    fn min_max_dist_2(&self, point: &Self::Point) -> <Self::Point as Point>::Scalar {
+        // The distance to the farthest point of the box is a valid upper bound.
+        let one: <Self::Point as Point>::Scalar = One::one();
+        let two = one + one;
+        self.fold_axes(point, |p, lower, upper, period| {
+            let to_lower = (p - lower).abs();
+            let to_upper = (p - upper).abs();
+            match period {
+                Some(period) => {
+                    let half = period / two;
+                    let antipode = if p < half { p + half } else { p - half };
+                    if lower <= antipode && antipode <= upper {
+                        half
+                    } else {
+                        max_inline(
+                            min_inline(to_lower, period - to_lower),
+                            min_inline(to_upper, period - to_upper),
+                        )
+                    }
+                }
+                None => max_inline(to_lower, to_upper),
+            }
+        })
+    }
+
+    fn center(&self) -> Self::Point {
+        self.aabb.center()
+    }
+
+    fn perimeter_value(&self) -> <Self::Point as Point>::Scalar {
+        self.aabb.perimeter_value()
+    }
+
+    fn sort_envelopes<T: RTreeObject<Envelope = Self>>(axis: usize, envelopes: &mut [T]) {
+        envelopes.sort_unstable_by(|l, r| {
+            l.envelope()
+                .aabb
+                .lower()
+                .nth(axis)
+                .partial_cmp(&r.envelope().aabb.lower().nth(axis))
+                .unwrap()
+        });
+    }
+
+    fn partition_envelopes<T: RTreeObject<Envelope = Self>>(
+        axis: usize,
+        envelopes: &mut [T],
+        selection_size: usize,
+    ) {
+        envelopes.select_nth_unstable_by(selection_size, |l, r| {
+            l.envelope()
+                .aabb
+                .lower()
+                .nth(axis)
+                .partial_cmp(&r.envelope().aabb.lower().nth(axis))
+                .unwrap()
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{PeriodicAABB, PeriodicDomain};
+    use crate::primitives::PeriodicPoint;
+    use crate::test_utilities::{create_random_points, SEED_1, SEED_2};
+    use crate::{Envelope, RTree, AABB};
+    use approx::*;
+
+    struct UnitTorus;
+
+    impl PeriodicDomain for UnitTorus {
+        type Point = [f64; 2];
+
+        fn period(_: usize) -> Option<f64> {
+            Some(1.0)
+        }
+    }
+
+    struct Cylinder;
+
+    impl PeriodicDomain for Cylinder {
+        type Point = [f64; 2];
+
+        fn period(axis: usize) -> Option<f64> {
+            if axis == 0 {
+                Some(1.0)
+            } else {
+                None
+            }
+        }
+    }
+
+    struct IntegerTorus;
+
+    impl PeriodicDomain for IntegerTorus {
+        type Point = [i32; 2];
+
+        fn period(_: usize) -> Option<i32> {
+            Some(10)
+        }
+    }
+
+    #[test]
+    fn test_envelope_distance() {
+        let envelope =
+            PeriodicAABB::<UnitTorus>::from_aabb(AABB::from_corners([0.1, 0.1], [0.2, 0.2]));
+        assert_abs_diff_eq!(envelope.distance_2(&[0.95, 0.15]), 0.15 * 0.15);
+        assert_abs_diff_eq!(envelope.distance_2(&[-0.05, 0.15]), 0.15 * 0.15);
+        assert_abs_diff_eq!(envelope.distance_2(&[0.3, 0.15]), 0.1 * 0.1);
+        assert_abs_diff_eq!(envelope.distance_2(&[0.15, 0.15]), 0.0);
+        assert!(envelope.min_max_dist_2(&[0.95, 0.95]) >= envelope.distance_2(&[0.95, 0.95]));
+
+        let envelope =
+            PeriodicAABB::<Cylinder>::from_aabb(AABB::from_corners([0.1, 0.1], [0.2, 0.2]));
+        assert_abs_diff_eq!(envelope.distance_2(&[0.15, 0.95]), 0.75 * 0.75);
+
+        let empty = PeriodicAABB::<IntegerTorus>::new_empty();
+        assert_eq!(empty.distance_2(&[3, 4]), i32::MAX);
+        assert_eq!(empty.min_max_dist_2(&[3, 4]), i32::MAX);
+    }
+
+    fn check_queries<D: PeriodicDomain<Point = [f64; 2]>>() {
+        let points: Vec<_> = create_random_points(500, SEED_1)
+            .into_iter()
+            .map(PeriodicPoint::<D>::new)
+            .collect();
+        let bulk_loaded = RTree::bulk_load(points.clone());
+        let mut inserted = RTree::new();
+        for point in &points {
+            inserted.insert(point.clone());
+        }
+
+        for query in create_random_points(50, SEED_2) {
+            let expected = points
+                .iter()
+                .map(|p| D::distance_2(p.position(), &query))
+                .fold(f64::INFINITY, f64::min);
+            let expected_count = points
+                .iter()
+                .filter(|p| D::distance_2(p.position(), &query) <= 0.01)
+                .count();
+            for tree in &[&bulk_loaded, &inserted] {
+                let nearest = tree.nearest_neighbor(&query).unwrap();
+                assert_abs_diff_eq!(D::distance_2(nearest.position(), &query), expected);
+                assert_eq!(
+                    tree.locate_within_distance(query, 0.01).count(),
+                    expected_count
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_periodic_queries() {
+        check_queries::<UnitTorus>();
+        check_queries::<Cylinder>();
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_periodic_serialization() {
+        let points: Vec<_> = create_random_points(100, SEED_1)
+            .into_iter()
+            .map(PeriodicPoint::<UnitTorus>::new)
+            .collect();
+        let tree = RTree::bulk_load(points);
+        let json = serde_json::to_string(&tree).unwrap();
+        let parsed: RTree<PeriodicPoint<UnitTorus>> = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.size(), 100);
+        let nearest = parsed.nearest_neighbor(&[0.99, 0.01]).unwrap();
+        let expected = tree.nearest_neighbor(&[0.99, 0.01]).unwrap();
+        assert_relative_eq!(nearest.position()[0], expected.position()[0]);
+        assert_relative_eq!(nearest.position()[1], expected.position()[1]);
+    }
+}
@@ -1,5 +1,5 @@
 use crate::envelope::Envelope;
-use crate::object::PointDistance;
+use crate::object::{ExactPredicate, PointDistance};
 use crate::{object::RTreeObject, point::Point};
 use core::ops::Deref;
 
@@ -51,6 +51,12 @@ impl<T: PointDistance> PointDistance for CachedEnvelope<T> {
     }
 }
 
+impl<T: ExactPredicate> ExactPredicate for CachedEnvelope<T> {
+    fn intersects_envelope(&self, envelope: &Self::Envelope) -> bool {
+        self.inner.intersects_envelope(envelope)
+    }
+}
+
 impl<T: RTreeObject> CachedEnvelope<T> {
     /// Create a new [CachedEnvelope] struct using the provided geometry.
     pub fn new(inner: T) -> Self {
@@ -0,0 +1,295 @@
+use crate::aabb::AABB;
+use crate::envelope::Envelope;
+use crate::object::PointDistance;
+use crate::object::RTreeObject;
+use crate::point::{PointExt, RTreeNum};
+use num_traits::{Float, Zero};
+
+/// A great-circle arc between two points on the unit sphere.
+///
+/// Geodesic lines live in three dimensional space: every position on the sphere is
+/// represented by its unit vector, see [GeodesicLine::unit_vector]. Query points must be
+/// given in the same representation.
+///
+/// The line's envelope bounds the whole arc, not only its end points. Distances are
+/// measured along the shortest path from the query point onto the arc (the cross-track
+/// distance if the query point lies abeam of the arc). To keep distances consistent with
+/// the envelope, [distance_2](PointDistance::distance_2) returns the *squared chord length*
+/// corresponding to that angular distance. Use [GeodesicLine::chord_2] to convert an angular
+/// radius into a squared chord length for
+/// [locate_within_distance](crate::RTree::locate_within_distance).
+///
+/// The end points must not be antipodal.
+///
+/// # Example
+/// ```
+/// use rstar::primitives::GeodesicLine;
+/// use rstar::RTree;
+///
+/// // From Frankfurt to New York and from Sydney to Singapore
+/// let routes = RTree::bulk_load(vec![
+///     GeodesicLine::from_degrees([8.57, 50.03], [-73.78, 40.64]),
+///     GeodesicLine::from_degrees([151.18, -33.95], [103.99, 1.36]),
+/// ]);
+///
+/// // Dublin lies roughly 30 km off the first route even though it is far away from the
+/// // straight line between both airports in longitude / latitude coordinates.
+/// let dublin = GeodesicLine::unit_vector(-6.27, 53.42);
+/// let radius = GeodesicLine::chord_2(100.0 / 6371.0);
+/// assert_eq!(routes.locate_within_distance(dublin, radius).count(), 1);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GeodesicLine<S>
+where
+    S: RTreeNum + Float,
+{
+    from: [S; 3],
+    to: [S; 3],
+}
+
+impl<S> GeodesicLine<S>
+where
+    S: RTreeNum + Float,
+{
+    /// Creates a new geodesic line between two vectors. The vectors are normalized.
+    pub fn new(from: [S; 3], to: [S; 3]) -> Self {
+        GeodesicLine {
+            from: normalize(from),
+            to: normalize(to),
+        }
+    }
+
+    /// Creates a new geodesic line between two `[longitude, latitude]` positions given in
+    /// degrees.
+    pub fn from_degrees(from: [S; 2], to: [S; 2]) -> Self {
+        GeodesicLine {
+            from: Self::unit_vector(from[0], from[1]),
+            to: Self::unit_vector(to[0], to[1]),
+        }
+    }
+
+    /// Returns the unit vector of a position given in longitude and latitude degrees.
+    pub fn unit_vector(longitude: S, latitude: S) -> [S; 3] {
+        let (lon, lat) = (longitude.to_radians(), latitude.to_radians());
+        [lat.cos() * lon.cos(), lat.cos() * lon.sin(), lat.sin()]
+    }
+
+    /// Returns the squared chord length of an angular distance given in radians.
+    ///
+    /// On a sphere with radius `r`, a distance `d` corresponds to the angle `d / r`.
+    pub fn chord_2(angle: S) -> S {
+        let two = S::one() + S::one();
+        let chord = two * (angle.min(pi::<S>()) / two).sin();
+        chord * chord
+    }
+
+    /// Returns the unit vector of the line's start point.
+    pub fn from(&self) -> [S; 3] {
+        self.from
+    }
+
+    /// Returns the unit vector of the line's end point.
+    pub fn to(&self) -> [S; 3] {
+        self.to
+    }
+
+    /// Returns the angle between the start and the end point in radians.
+    pub fn angular_length(&self) -> S {
+        angle_between(&self.from, &self.to)
+    }
+
+    /// Returns the angle from a point on the sphere to the closest point of the arc, in
+    /// radians.
+    pub fn angular_distance(&self, point: &[S; 3]) -> S {
+        angle_between(&normalize(*point), &self.nearest_point(point))
+    }
+
+    /// Returns the point of the arc closest to a given point.
+    pub fn nearest_point(&self, point: &[S; 3]) -> [S; 3] {
+        let (u, length) = match self.direction() {
+            Some(direction) => direction,
+            None => return self.from,
+        };
+        let point = normalize(*point);
+        let angle = point.dot(&u).atan2(point.dot(&self.from));
+        if angle >= Zero::zero() && angle <= length {
+            return self.at(&u, angle);
+        }
+        if PointExt::distance_2(&point, &self.from) <= PointExt::distance_2(&point, &self.to) {
+            self.from
+        } else {
+            self.to
+        }
+    }
+
+    /// Returns the unit vector orthogonal to `from` pointing along the arc and the
+    /// arc's angle, or `None` if both end points coincide.
+    fn direction(&self) -> Option<([S; 3], S)> {
+        let along = self.to.sub(&self.from.mul(self.from.dot(&self.to)));
+        let length = along.length_2().sqrt();
+        if length <= S::epsilon() {
+            None
+        } else {
+            Some((along.mul(S::one() / length), self.angular_length()))
+        }
+    }
+
+    fn at(&self, u: &[S; 3], angle: S) -> [S; 3] {
+        self.from.mul(angle.cos()).add(&u.mul(angle.sin()))
+    }
+}
+
+fn pi<S: Float>() -> S {
+    S::from(core::f64::consts::PI).unwrap()
+}
+
+fn normalize<S: RTreeNum + Float>(vector: [S; 3]) -> [S; 3] {
+    let length = vector.length_2().sqrt();
+    if length > Zero::zero() {
+        vector.mul(S::one() / length)
+    } else {
+        vector
+    }
+}
+
+fn angle_between<S: RTreeNum + Float>(l: &[S; 3], r: &[S; 3]) -> S {
+    let cross = [
+        l[1] * r[2] - l[2] * r[1],
+        l[2] * r[0] - l[0] * r[2],
+        l[0] * r[1] - l[1] * r[0],
+    ];
+    cross.length_2().sqrt().atan2(l.dot(r))
+}
+
+impl<S> RTreeObject for GeodesicLine<S>
+where
+    S: RTreeNum + Float,
+{
+    type Envelope = AABB<[S; 3]>;
+
+    fn envelope(&self) -> Self::Envelope {
+        let mut envelope = AABB::from_corners(self.from, self.to);
+        if let Some((u, length)) = self.direction() {
+            // Along every axis, the great circle reaches its extreme values at the angles
+            // `atan2(u_i, from_i)` and `atan2(u_i, from_i) + PI`.
+            let two_pi = pi::<S>() + pi::<S>();
+            for axis in 0..3 {
+                let mut extreme = u[axis].atan2(self.from[axis]);
+                for _ in 0..2 {
+                    if extreme < Zero::zero() {
+                        extreme = extreme + two_pi;
+                    }
+                    if extreme <= length {
+                        envelope.merge(&AABB::from_point(self.at(&u, extreme)));
+                    }
+                    extreme = extreme + pi::<S>();
+                    if extreme >= two_pi {
+                        extreme = extreme - two_pi;
+                    }
+                }
+            }
+        }
+        envelope
+    }
+}
+
+impl<S> PointDistance for GeodesicLine<S>
+where
+    S: RTreeNum + Float,
+{
+    fn distance_2(&self, point: &[S; 3]) -> S {
+        Self::chord_2(self.angular_distance(point))
+    }
+
+    fn contains_point(&self, point: &[S; 3]) -> bool {
+        self.angular_distance(point) <= S::epsilon().sqrt()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::GeodesicLine;
+    use crate::object::{PointDistance, RTreeObject};
+    use crate::test_utilities::{create_random_points, SEED_1, SEED_2};
+    use crate::{Envelope, RTree};
+    use approx::*;
+
+    fn sample(line: &GeodesicLine<f64>, steps: usize) -> Vec<[f64; 3]> {
+        let (u, length) = line.direction().unwrap();
+        (0..=steps)
+            .map(|step| line.at(&u, length * step as f64 / steps as f64))
+            .collect()
+    }
+
+    fn random_lines() -> Vec<GeodesicLine<f64>> {
+        create_random_points(200, SEED_1)
+            .into_iter()
+            .zip(create_random_points(200, SEED_2))
+            .map(|([a, b], [c, d])| {
+                GeodesicLine::from_degrees(
+                    [a * 360.0 - 180.0, b * 170.0 - 85.0],
+                    [c * 360.0 - 180.0, d * 170.0 - 85.0],
+                )
+            })
+            .filter(|line| line.angular_length() < 3.0)
+            .collect()
+    }
+
+    #[test]
+    fn test_envelope_bounds_arc() {
+        for line in random_lines() {
+            let envelope = line.envelope();
+            for point in sample(&line, 50) {
+                let expanded = envelope.merged(&crate::AABB::from_point(point));
+                assert_abs_diff_eq!(expanded.area(), envelope.area(), epsilon = 1e-9);
+            }
+        }
+        // An arc along the equator crossing the prime meridian bulges out along the x axis.
+        let line = GeodesicLine::from_degrees([-45.0, 0.0], [45.0, 0.0]);
+        assert_abs_diff_eq!(line.envelope().upper()[0], 1.0);
+    }
+
+    #[test]
+    fn test_cross_track_distance() {
+        let line = GeodesicLine::from_degrees([0.0, 0.0], [90.0, 0.0]);
+        let above = GeodesicLine::unit_vector(45.0, 10.0);
+        assert_abs_diff_eq!(
+            line.angular_distance(&above),
+            10f64.to_radians(),
+            epsilon = 1e-12
+        );
+        let behind = GeodesicLine::unit_vector(-10.0, 0.0);
+        assert_abs_diff_eq!(
+            line.angular_distance(&behind),
+            10f64.to_radians(),
+            epsilon = 1e-12
+        );
+        assert_abs_diff_eq!(
+            line.distance_2(&above),
+            GeodesicLine::chord_2(10f64.to_radians())
+        );
+        assert!(line.contains_point(&GeodesicLine::unit_vector(30.0, 0.0)));
+    }
+
+    #[test]
+    fn test_within_distance() {
+        let lines = random_lines();
+        let tree = RTree::bulk_load(lines.clone());
+        let radius = GeodesicLine::chord_2(0.2);
+        for [lon, lat] in create_random_points(50, SEED_2) {
+            let query = GeodesicLine::unit_vector(lon * 360.0 - 180.0, lat * 170.0 - 85.0);
+            let expected = lines
+                .iter()
+                .filter(|line| line.angular_distance(&query) <= 0.2)
+                .count();
+            assert_eq!(tree.locate_within_distance(query, radius).count(), expected);
+            let nearest = tree.nearest_neighbor(&query).unwrap();
+            let min = lines
+                .iter()
+                .map(|line| line.angular_distance(&query))
+                .fold(f64::INFINITY, f64::min);
+            assert_abs_diff_eq!(nearest.angular_distance(&query), min);
+        }
+    }
+}
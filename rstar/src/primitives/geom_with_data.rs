@@ -1,5 +1,5 @@
 use crate::envelope::Envelope;
-use crate::object::PointDistance;
+use crate::object::{ExactPredicate, PointDistance};
 use crate::{object::RTreeObject, point::Point};
 
 /// An [RTreeObject] with a geometry and some associated data that can be inserted into an r-tree.
@@ -66,6 +66,12 @@ impl<R: PointDistance, T> PointDistance for GeomWithData<R, T> {
     }
 }
 
+impl<R: ExactPredicate, T> ExactPredicate for GeomWithData<R, T> {
+    fn intersects_envelope(&self, envelope: &Self::Envelope) -> bool {
+        self.geom.intersects_envelope(envelope)
+    }
+}
+
 impl<R: RTreeObject, T> GeomWithData<R, T> {
     /// Create a new [GeomWithData] struct using the provided geometry and data.
     pub fn new(geom: R, data: T) -> Self {
@@ -1,9 +1,9 @@
 use crate::aabb::AABB;
 use crate::envelope::Envelope;
 use crate::object::PointDistance;
-use crate::object::RTreeObject;
+use crate::object::{ExactPredicate, RTreeObject};
 use crate::point::{Point, PointExt};
-use num_traits::{One, Zero};
+use num_traits::{Float, One, Zero};
 
 /// A line defined by a start and and end point.
 ///
@@ -115,10 +115,23 @@ where
     }
 }
 
+impl<P> ExactPredicate for Line<P>
+where
+    P: Point,
+    P::Scalar: Float,
+{
+    fn intersects_envelope(&self, envelope: &AABB<P>) -> bool {
+        envelope
+            .ray_intersection(&self.from, &self.to.sub(&self.from))
+            .map_or(false, |(near, _)| near <= One::one())
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::Line;
-    use crate::object::PointDistance;
+    use crate::object::{ExactPredicate, PointDistance};
+    use crate::AABB;
     use approx::*;
 
     #[test]
@@ -139,4 +152,18 @@ mod test {
         let line = Line::new([1, -1], [5, 5]);
         assert_eq!(line.length_2(), 16 + 36);
     }
+
+    #[test]
+    fn intersects_envelope() {
+        let line = Line::new([0.0, 0.0], [2.0, 2.0]);
+        assert!(line.intersects_envelope(&AABB::from_corners([0.5, 0.0], [1.5, 1.0])));
+        assert!(line.intersects_envelope(&AABB::from_corners([1.0, 1.0], [3.0, 3.0])));
+        assert!(line.intersects_envelope(&AABB::from_point([2.0, 2.0])));
+        assert!(!line.intersects_envelope(&AABB::from_corners([1.5, 0.0], [2.0, 0.5])));
+        assert!(!line.intersects_envelope(&AABB::from_corners([2.5, 2.5], [3.0, 3.0])));
+
+        let point = Line::new([1.0, 1.0], [1.0, 1.0]);
+        assert!(point.intersects_envelope(&AABB::from_corners([0.0, 0.0], [1.0, 1.0])));
+        assert!(!point.intersects_envelope(&AABB::from_corners([0.0, 0.0], [0.5, 1.0])));
+    }
 }
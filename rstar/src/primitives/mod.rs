@@ -1,13 +1,23 @@
 //! Contains primitives ready for insertion into an r-tree.
 
 mod cached_envelope;
+mod geodesic_line;
 mod geom_with_data;
 mod line;
+mod periodic_point;
 mod point_with_data;
+mod polyline;
 mod rectangle;
+mod timed;
+mod timestamped;
 
 pub use self::cached_envelope::CachedEnvelope;
+pub use self::geodesic_line::GeodesicLine;
 pub use self::geom_with_data::GeomWithData;
 pub use self::line::Line;
+pub use self::periodic_point::PeriodicPoint;
 pub use self::point_with_data::PointWithData;
+pub use self::polyline::Polyline;
 pub use self::rectangle::Rectangle;
+pub use self::timed::Timed;
+pub use self::timestamped::Timestamped;
@@ -0,0 +1,131 @@
+use crate::aabb::AABB;
+use crate::periodic::{PeriodicAABB, PeriodicDomain};
+use crate::{Envelope, Point, PointDistance, RTreeObject};
+use core::fmt::{Debug, Formatter};
+use core::marker::PhantomData;
+
+/// A point within a [PeriodicDomain] that can be inserted into an r-tree.
+///
+/// Nearest neighbor and distance queries on a tree of periodic points consider the periodic
+/// images of every point, so there is no need to insert additional "ghost" copies close to the
+/// domain's borders.
+///
+/// # Example
+/// ```
+/// use rstar::primitives::PeriodicPoint;
+/// use rstar::{PeriodicDomain, RTree};
+///
+/// struct SimulationBox;
+///
+/// impl PeriodicDomain for SimulationBox {
+///     type Point = [f64; 2];
+///
+///     fn period(_axis: usize) -> Option<f64> {
+///         Some(10.0)
+///     }
+/// }
+///
+/// type Particle = PeriodicPoint<SimulationBox>;
+///
+/// let tree = RTree::bulk_load(vec![
+///     Particle::new([0.5, 5.0]),
+///     Particle::new([7.0, 5.0]),
+/// ]);
+///
+/// // The particle at x = 0.5 is closer to x = 9.5 than the one at x = 7.0
+/// let nearest = tree.nearest_neighbor(&[9.5, 5.0]).unwrap();
+/// assert_eq!(nearest.position(), &[0.5, 5.0]);
+///
+/// // Coordinates outside of the canonical cell are wrapped.
+/// assert_eq!(Particle::new([-1.0, 12.0]).position(), &[9.0, 2.0]);
+/// ```
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "D::Point: serde::Serialize",
+        deserialize = "D::Point: serde::Deserialize<'de>"
+    ))
+)]
+pub struct PeriodicPoint<D>
+where
+    D: PeriodicDomain,
+{
+    point: D::Point,
+    _domain: PhantomData<D>,
+}
+
+impl<D> PeriodicPoint<D>
+where
+    D: PeriodicDomain,
+{
+    /// Creates a new periodic point. The point is wrapped into the domain's canonical cell.
+    pub fn new(point: D::Point) -> Self {
+        PeriodicPoint {
+            point: D::wrap(&point),
+            _domain: PhantomData,
+        }
+    }
+
+    /// Returns this point's position within the canonical cell.
+    pub fn position(&self) -> &D::Point {
+        &self.point
+    }
+}
+
+impl<D> Clone for PeriodicPoint<D>
+where
+    D: PeriodicDomain,
+{
+    fn clone(&self) -> Self {
+        PeriodicPoint {
+            point: self.point.clone(),
+            _domain: PhantomData,
+        }
+    }
+}
+
+impl<D> PartialEq for PeriodicPoint<D>
+where
+    D: PeriodicDomain,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.point == other.point
+    }
+}
+
+impl<D> Debug for PeriodicPoint<D>
+where
+    D: PeriodicDomain,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("PeriodicPoint").field(&self.point).finish()
+    }
+}
+
+impl<D> RTreeObject for PeriodicPoint<D>
+where
+    D: PeriodicDomain,
+{
+    type Envelope = PeriodicAABB<D>;
+
+    fn envelope(&self) -> Self::Envelope {
+        PeriodicAABB::from_aabb(AABB::from_point(self.point.clone()))
+    }
+}
+
+impl<D> PointDistance for PeriodicPoint<D>
+where
+    D: PeriodicDomain,
+{
+    fn distance_2(
+        &self,
+        point: &<Self::Envelope as Envelope>::Point,
+    ) -> <<Self::Envelope as Envelope>::Point as Point>::Scalar {
+        D::distance_2(&self.point, point)
+    }
+
+    fn contains_point(&self, point: &<Self::Envelope as Envelope>::Point) -> bool {
+        self.point == D::wrap(point)
+    }
+}
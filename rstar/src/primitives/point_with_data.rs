@@ -1,4 +1,4 @@
-use crate::{Point, PointDistance, RTreeObject, AABB};
+use crate::{ExactPredicate, Point, PointDistance, RTreeObject, AABB};
 
 /// A point with some associated data that can be inserted into an r-tree.
 ///
@@ -70,3 +70,12 @@ where
         self.point.contains_point(point)
     }
 }
+
+impl<T, P> ExactPredicate for PointWithData<T, P>
+where
+    P: Point,
+{
+    fn intersects_envelope(&self, envelope: &AABB<P>) -> bool {
+        self.point.intersects_envelope(envelope)
+    }
+}
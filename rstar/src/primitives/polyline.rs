@@ -0,0 +1,149 @@
+use crate::aabb::AABB;
+use crate::object::RTreeObject;
+use crate::point::{Point, PointExt};
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// A sequence of connected line segments, e.g. the trajectory of a vehicle.
+///
+/// The envelope is computed once when the polyline is created.
+///
+/// # Example
+/// ```
+/// use rstar::primitives::Polyline;
+/// use rstar::RTree;
+///
+/// let trip = Polyline::new(vec![[0.0, 0.0], [1.0, 0.0], [1.0, 1.0]]);
+/// let tree = RTree::bulk_load(vec![trip.clone()]);
+/// assert_eq!(tree.iter().next(), Some(&trip));
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Polyline<P>
+where
+    P: Point,
+{
+    vertices: Vec<P>,
+    envelope: AABB<P>,
+}
+
+impl<P> Polyline<P>
+where
+    P: Point,
+{
+    /// Creates a new polyline through the given vertices.
+    ///
+    /// # Panics
+    /// Panics if `vertices` is empty.
+    pub fn new(vertices: Vec<P>) -> Self {
+        assert!(
+            !vertices.is_empty(),
+            "A polyline requires at least one vertex"
+        );
+        let envelope = AABB::from_points(&vertices);
+        Polyline { vertices, envelope }
+    }
+
+    /// Returns the polyline's vertices.
+    pub fn vertices(&self) -> &[P] {
+        &self.vertices
+    }
+
+    /// Returns the squared discrete Hausdorff distance to another polyline.
+    ///
+    /// This is the largest distance from a vertex of either polyline to the closest vertex of
+    /// the other one. It never exceeds the discrete Fréchet distance.
+    ///
+    /// # Example
+    /// ```
+    /// use rstar::primitives::Polyline;
+    ///
+    /// let first = Polyline::new(vec![[0, 0], [4, 0]]);
+    /// let second = Polyline::new(vec![[0, 1], [4, 0], [4, 2]]);
+    /// assert_eq!(first.discrete_hausdorff_distance_2(&second), 4);
+    /// ```
+    pub fn discrete_hausdorff_distance_2(&self, other: &Self) -> P::Scalar {
+        let directed = |from: &[P], to: &[P]| {
+            from.iter()
+                .map(|vertex| {
+                    to.iter()
+                        .map(|other| vertex.distance_2(other))
+                        .fold(None, min_option)
+                        .unwrap()
+                })
+                .fold(None, max_option)
+                .unwrap()
+        };
+        let first = directed(&self.vertices, &other.vertices);
+        let second = directed(&other.vertices, &self.vertices);
+        if first > second {
+            first
+        } else {
+            second
+        }
+    }
+
+    /// Returns the squared discrete Fréchet distance to another polyline.
+    ///
+    /// This is the smallest possible maximal distance between two walkers moving forward
+    /// along the vertices of either polyline, which takes the direction of the polylines
+    /// into account. Requires `O(n * m)` time for polylines with `n` and `m` vertices.
+    ///
+    /// # Example
+    /// ```
+    /// use rstar::primitives::Polyline;
+    ///
+    /// let first = Polyline::new(vec![[0, 0], [4, 0]]);
+    /// let reversed = Polyline::new(vec![[4, 0], [0, 0]]);
+    /// assert_eq!(first.discrete_hausdorff_distance_2(&reversed), 0);
+    /// assert_eq!(first.discrete_frechet_distance_2(&reversed), 16);
+    /// ```
+    pub fn discrete_frechet_distance_2(&self, other: &Self) -> P::Scalar {
+        // The coupling distances of the previous and current vertex of `self`.
+        let mut previous: Vec<P::Scalar> = vec![];
+        for (index, vertex) in self.vertices.iter().enumerate() {
+            let mut current: Vec<P::Scalar> = Vec::with_capacity(other.vertices.len());
+            for (other_index, other_vertex) in other.vertices.iter().enumerate() {
+                let distance = vertex.distance_2(other_vertex);
+                let reachable = match (index, other_index) {
+                    (0, 0) => None,
+                    (0, _) => Some(current[other_index - 1]),
+                    (_, 0) => Some(previous[0]),
+                    (_, _) => min_option(
+                        min_option(Some(previous[other_index]), previous[other_index - 1]),
+                        current[other_index - 1],
+                    ),
+                };
+                current.push(max_option(reachable, distance).unwrap());
+            }
+            previous = current;
+        }
+        previous[other.vertices.len() - 1]
+    }
+}
+
+fn min_option<S: PartialOrd>(current: Option<S>, value: S) -> Option<S> {
+    match current {
+        Some(current) if current <= value => Some(current),
+        _ => Some(value),
+    }
+}
+
+fn max_option<S: PartialOrd>(current: Option<S>, value: S) -> Option<S> {
+    match current {
+        Some(current) if current >= value => Some(current),
+        _ => Some(value),
+    }
+}
+
+impl<P> RTreeObject for Polyline<P>
+where
+    P: Point,
+{
+    type Envelope = AABB<P>;
+
+    fn envelope(&self) -> Self::Envelope {
+        self.envelope.clone()
+    }
+}
@@ -1,6 +1,6 @@
 use crate::aabb::AABB;
 use crate::envelope::Envelope;
-use crate::object::{PointDistance, RTreeObject};
+use crate::object::{ExactPredicate, PointDistance, RTreeObject};
 use crate::point::{Point, PointExt};
 
 /// An n-dimensional rectangle defined by its two corners.
@@ -113,6 +113,15 @@ where
     }
 }
 
+impl<P> ExactPredicate for Rectangle<P>
+where
+    P: Point,
+{
+    fn intersects_envelope(&self, envelope: &AABB<P>) -> bool {
+        self.aabb.intersects(envelope)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::Rectangle;
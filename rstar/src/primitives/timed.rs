@@ -0,0 +1,76 @@
+use crate::envelope::Envelope;
+use crate::object::RTreeObject;
+use crate::point::Point;
+use crate::temporal::TemporalPoint;
+use crate::AABB;
+
+use core::ops::RangeInclusive;
+
+/// An [RTreeObject] that exists during a closed time interval.
+///
+/// The envelope of a `Timed` object has one more dimension than the envelope of its geometry:
+/// time is appended as last coordinate. This allows to query for objects existing at a certain
+/// time, see [RTree::locate_in_envelope_during](crate::RTree::locate_in_envelope_during).
+///
+/// # Example
+/// ```
+/// use rstar::primitives::Timed;
+/// use rstar::{RTree, AABB};
+///
+/// let visits = vec![
+///     Timed::new([0.0, 0.0], 0.0..=10.0),
+///     Timed::new([1.0, 1.0], 5.0..=6.0),
+///     Timed::new([1.0, 1.0], 20.0..=30.0),
+/// ];
+/// let tree = RTree::bulk_load(visits);
+/// let area = AABB::from_corners([0.5, 0.5], [1.5, 1.5]);
+/// assert_eq!(tree.locate_in_envelope_during(&area, 0.0..=10.0).count(), 1);
+/// assert_eq!(tree.locate_in_envelope_at(&area, 25.0).count(), 1);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Timed<T>
+where
+    T: RTreeObject,
+{
+    geom: T,
+    start: <<T::Envelope as Envelope>::Point as Point>::Scalar,
+    end: <<T::Envelope as Envelope>::Point as Point>::Scalar,
+}
+
+impl<T, P> Timed<T>
+where
+    T: RTreeObject<Envelope = AABB<P>>,
+    P: Point,
+{
+    /// Creates a new object that exists during the given time interval.
+    pub fn new(geom: T, interval: RangeInclusive<P::Scalar>) -> Self {
+        let (start, end) = interval.into_inner();
+        Timed { geom, start, end }
+    }
+
+    /// Returns the object's geometry.
+    pub fn geom(&self) -> &T {
+        &self.geom
+    }
+
+    /// Returns the time interval during which the object exists.
+    pub fn interval(&self) -> RangeInclusive<P::Scalar> {
+        self.start..=self.end
+    }
+}
+
+impl<T, P> RTreeObject for Timed<T>
+where
+    T: RTreeObject<Envelope = AABB<P>>,
+    P: TemporalPoint,
+{
+    type Envelope = AABB<P::WithTime>;
+
+    fn envelope(&self) -> Self::Envelope {
+        let envelope = self.geom.envelope();
+        AABB::from_corners(
+            envelope.lower().with_time(self.start),
+            envelope.upper().with_time(self.end),
+        )
+    }
+}
@@ -0,0 +1,73 @@
+use crate::object::RTreeObject;
+use crate::temporal::{TemporalPoint, Timestamp};
+use crate::AABB;
+
+use num_traits::FromPrimitive;
+
+/// An [RTreeObject] recorded at a single point in time.
+///
+/// Unlike [Timed](crate::primitives::Timed), the timestamp does not need to have the
+/// geometry's scalar type: it can be an `i64` or, with the `std` feature, a
+/// [SystemTime](std::time::SystemTime), see [Timestamp]. The envelope has time appended as
+/// last coordinate, which allows to query for objects recorded within a time range, see
+/// [RTree::locate_in_envelope_between](crate::RTree::locate_in_envelope_between).
+///
+/// # Example
+/// ```
+/// use rstar::primitives::Timestamped;
+/// use rstar::{RTree, AABB};
+///
+/// let positions = vec![
+///     Timestamped::new([0.0, 0.0], 1_000),
+///     Timestamped::new([1.0, 1.0], 2_000),
+///     Timestamped::new([1.0, 1.0], 9_000),
+/// ];
+/// let tree = RTree::bulk_load(positions);
+/// let area = AABB::from_corners([0.5, 0.5], [1.5, 1.5]);
+/// assert_eq!(tree.locate_in_envelope_between(&area, 0, 5_000).count(), 1);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Timestamped<T, Time = i64> {
+    geom: T,
+    timestamp: Time,
+}
+
+impl<T, Time> Timestamped<T, Time>
+where
+    T: RTreeObject,
+    Time: Timestamp,
+{
+    /// Creates a new object recorded at `timestamp`.
+    pub fn new(geom: T, timestamp: Time) -> Self {
+        Timestamped { geom, timestamp }
+    }
+
+    /// Returns the object's geometry.
+    pub fn geom(&self) -> &T {
+        &self.geom
+    }
+
+    /// Returns the time at which the object was recorded.
+    pub fn timestamp(&self) -> Time {
+        self.timestamp
+    }
+}
+
+impl<T, P, Time> RTreeObject for Timestamped<T, Time>
+where
+    T: RTreeObject<Envelope = AABB<P>>,
+    P: TemporalPoint,
+    P::Scalar: FromPrimitive,
+    Time: Timestamp,
+{
+    type Envelope = AABB<P::WithTime>;
+
+    fn envelope(&self) -> Self::Envelope {
+        let envelope = self.geom.envelope();
+        let time = self.timestamp.to_coordinate();
+        AABB::from_corners(
+            envelope.lower().with_time(time),
+            envelope.upper().with_time(time),
+        )
+    }
+}
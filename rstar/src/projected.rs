@@ -0,0 +1,299 @@
+use crate::params::{DefaultParams, RTreeParams};
+use crate::{Point, RTree};
+use alloc::vec::Vec;
+
+/// A coordinate transformation between two point types.
+///
+/// [ProjectedRTree] uses a projection to store points in a coordinate system in which
+/// euclidean distances are meaningful, e.g. a local metric projection of longitude /
+/// latitude coordinates.
+///
+/// # Example
+/// ```
+/// use rstar::Projection;
+///
+/// /// An equirectangular projection around a reference latitude, in meters.
+/// struct Equirectangular {
+///     cos_latitude: f64,
+/// }
+///
+/// impl Projection for Equirectangular {
+///     type Source = [f64; 2];
+///     type Target = [f64; 2];
+///
+///     fn forward(&self, [lon, lat]: &[f64; 2]) -> [f64; 2] {
+///         const METERS_PER_DEGREE: f64 = 111_320.0;
+///         [lon * METERS_PER_DEGREE * self.cos_latitude, lat * METERS_PER_DEGREE]
+///     }
+///
+///     fn inverse(&self, [x, y]: &[f64; 2]) -> [f64; 2] {
+///         const METERS_PER_DEGREE: f64 = 111_320.0;
+///         [x / METERS_PER_DEGREE / self.cos_latitude, y / METERS_PER_DEGREE]
+///     }
+/// }
+/// ```
+pub trait Projection {
+    /// The point type of the source coordinate system, e.g. longitude / latitude.
+    type Source;
+    /// The point type of the projected coordinate system which is stored in the tree.
+    type Target: Point;
+
+    /// Transforms a point into the projected coordinate system.
+    fn forward(&self, point: &Self::Source) -> Self::Target;
+
+    /// Transforms a projected point back into the source coordinate system.
+    fn inverse(&self, point: &Self::Target) -> Self::Source;
+}
+
+/// An r-tree of points that are transformed by a [Projection].
+///
+/// Points are given in the projection's source coordinate system. They are projected on
+/// insertion and the query points of all queries are projected as well. Returned points are
+/// transformed back into the source coordinate system. All distances are measured in the
+/// projected coordinate system.
+///
+/// Use [ProjectedRTree::tree] to access the underlying tree of projected points.
+///
+/// # Example
+/// ```
+/// use rstar::{ProjectedRTree, Projection};
+///
+/// /// Stretches the y axis by a factor of ten.
+/// struct Stretch;
+///
+/// impl Projection for Stretch {
+///     type Source = [f64; 2];
+///     type Target = [f64; 2];
+///
+///     fn forward(&self, [x, y]: &[f64; 2]) -> [f64; 2] {
+///         [*x, y * 10.0]
+///     }
+///
+///     fn inverse(&self, [x, y]: &[f64; 2]) -> [f64; 2] {
+///         [*x, y / 10.0]
+///     }
+/// }
+///
+/// let tree = ProjectedRTree::bulk_load(Stretch, vec![[2.0, 0.0], [0.0, 1.0]]);
+/// // [0.0, 1.0] is closer in source coordinates but farther away after projection.
+/// assert_eq!(tree.nearest_neighbor(&[0.0, 0.0]), Some([2.0, 0.0]));
+/// assert_eq!(tree.nearest_neighbor_iter_with_distance_2(&[0.0, 0.0]).nth(1), Some(([0.0, 1.0], 100.0)));
+/// ```
+pub struct ProjectedRTree<Pr, Params = DefaultParams>
+where
+    Pr: Projection,
+    Params: RTreeParams,
+{
+    tree: RTree<Pr::Target, Params>,
+    projection: Pr,
+}
+
+impl<Pr> ProjectedRTree<Pr>
+where
+    Pr: Projection,
+{
+    /// Creates a new, empty tree using the given projection.
+    pub fn new(projection: Pr) -> Self {
+        Self::new_with_params(projection)
+    }
+
+    /// Creates a new tree from a set of points using the given projection.
+    ///
+    /// See [RTree::bulk_load].
+    pub fn bulk_load(projection: Pr, points: Vec<Pr::Source>) -> Self {
+        Self::bulk_load_with_params(projection, points)
+    }
+}
+
+impl<Pr, Params> ProjectedRTree<Pr, Params>
+where
+    Pr: Projection,
+    Params: RTreeParams,
+{
+    /// Creates a new, empty tree with custom parameters.
+    pub fn new_with_params(projection: Pr) -> Self {
+        ProjectedRTree {
+            tree: RTree::new_with_params(),
+            projection,
+        }
+    }
+
+    /// Creates a new tree from a set of points with custom parameters.
+    pub fn bulk_load_with_params(projection: Pr, points: Vec<Pr::Source>) -> Self {
+        let projected = points
+            .iter()
+            .map(|point| projection.forward(point))
+            .collect();
+        ProjectedRTree {
+            tree: RTree::bulk_load_with_params(projected),
+            projection,
+        }
+    }
+
+    /// Returns the tree's projection.
+    pub fn projection(&self) -> &Pr {
+        &self.projection
+    }
+
+    /// Returns the underlying tree of projected points.
+    pub fn tree(&self) -> &RTree<Pr::Target, Params> {
+        &self.tree
+    }
+
+    /// Consumes this wrapper and returns the underlying tree of projected points.
+    pub fn into_tree(self) -> RTree<Pr::Target, Params> {
+        self.tree
+    }
+
+    /// Returns the number of points in the tree.
+    pub fn size(&self) -> usize {
+        self.tree.size()
+    }
+
+    /// Returns all points of the tree in source coordinates, in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = Pr::Source> + '_ {
+        self.tree
+            .iter()
+            .map(move |point| self.projection.inverse(point))
+    }
+
+    /// Projects and inserts a point.
+    ///
+    /// See [RTree::insert].
+    pub fn insert(&mut self, point: Pr::Source) {
+        self.tree.insert(self.projection.forward(&point));
+    }
+
+    /// Removes a point and returns it in source coordinates.
+    ///
+    /// See [RTree::remove].
+    pub fn remove(&mut self, point: &Pr::Source) -> Option<Pr::Source> {
+        let removed = self.tree.remove(&self.projection.forward(point));
+        removed.map(|point| self.projection.inverse(&point))
+    }
+
+    /// Returns `true` if the tree contains the projection of the given point.
+    pub fn contains(&self, point: &Pr::Source) -> bool {
+        self.tree.contains(&self.projection.forward(point))
+    }
+
+    /// Returns the nearest neighbor of a point.
+    ///
+    /// See [RTree::nearest_neighbor].
+    pub fn nearest_neighbor(&self, query_point: &Pr::Source) -> Option<Pr::Source> {
+        self.tree
+            .nearest_neighbor(&self.projection.forward(query_point))
+            .map(|point| self.projection.inverse(point))
+    }
+
+    /// Returns all points sorted by their distance to a given point.
+    ///
+    /// See [RTree::nearest_neighbor_iter].
+    pub fn nearest_neighbor_iter(
+        &self,
+        query_point: &Pr::Source,
+    ) -> impl Iterator<Item = Pr::Source> + '_ {
+        self.tree
+            .nearest_neighbor_iter(&self.projection.forward(query_point))
+            .map(move |point| self.projection.inverse(point))
+    }
+
+    /// Returns `(point, distance^2)` tuples sorted by their distance to a given point.
+    ///
+    /// The squared distance is measured in the projected coordinate system.
+    pub fn nearest_neighbor_iter_with_distance_2(
+        &self,
+        query_point: &Pr::Source,
+    ) -> impl Iterator<Item = (Pr::Source, <Pr::Target as Point>::Scalar)> + '_ {
+        self.tree
+            .nearest_neighbor_iter_with_distance_2(&self.projection.forward(query_point))
+            .map(move |(point, distance_2)| (self.projection.inverse(point), distance_2))
+    }
+
+    /// Returns all points within a certain distance of a point.
+    ///
+    /// The squared radius is measured in the projected coordinate system. See
+    /// [RTree::locate_within_distance].
+    pub fn locate_within_distance(
+        &self,
+        query_point: &Pr::Source,
+        max_squared_radius: <Pr::Target as Point>::Scalar,
+    ) -> impl Iterator<Item = Pr::Source> + '_ {
+        self.tree
+            .locate_within_distance(self.projection.forward(query_point), max_squared_radius)
+            .map(move |point| self.projection.inverse(point))
+    }
+
+    /// Removes the nearest neighbor of a point and returns it in source coordinates.
+    ///
+    /// See [RTree::pop_nearest_neighbor].
+    pub fn pop_nearest_neighbor(&mut self, query_point: &Pr::Source) -> Option<Pr::Source> {
+        let removed = self
+            .tree
+            .pop_nearest_neighbor(&self.projection.forward(query_point));
+        removed.map(|point| self.projection.inverse(&point))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ProjectedRTree, Projection};
+    use crate::test_utilities::{create_random_points, SEED_1, SEED_2};
+    use crate::PointDistance;
+
+    /// Scales the first axis by a factor of four.
+    struct Scale;
+
+    impl Projection for Scale {
+        type Source = (f64, f64);
+        type Target = [f64; 2];
+
+        fn forward(&self, &(x, y): &(f64, f64)) -> [f64; 2] {
+            [x * 4.0, y]
+        }
+
+        fn inverse(&self, &[x, y]: &[f64; 2]) -> (f64, f64) {
+            (x / 4.0, y)
+        }
+    }
+
+    #[test]
+    fn test_projected_queries() {
+        let points: Vec<_> = create_random_points(200, SEED_1)
+            .into_iter()
+            .map(|[x, y]| (x, y))
+            .collect();
+        let mut tree = ProjectedRTree::bulk_load(Scale, points.clone());
+        assert_eq!(tree.size(), 200);
+
+        for [x, y] in create_random_points(20, SEED_2) {
+            let query = (x, y);
+            let projected = Scale.forward(&query);
+            let expected = points
+                .iter()
+                .map(|point| Scale.forward(point).distance_2(&projected))
+                .fold(f64::INFINITY, f64::min);
+            let nearest = tree.nearest_neighbor(&query).unwrap();
+            assert_eq!(Scale.forward(&nearest).distance_2(&projected), expected);
+            let (_, distance_2) = tree
+                .nearest_neighbor_iter_with_distance_2(&query)
+                .next()
+                .unwrap();
+            assert_eq!(distance_2, expected);
+
+            let within = points
+                .iter()
+                .filter(|point| Scale.forward(point).distance_2(&projected) <= 0.1)
+                .count();
+            assert_eq!(tree.locate_within_distance(&query, 0.1).count(), within);
+        }
+
+        let first = points[0];
+        assert!(tree.contains(&first));
+        assert_eq!(tree.remove(&first), Some(first));
+        assert!(!tree.contains(&first));
+        tree.insert(first);
+        assert_eq!(tree.pop_nearest_neighbor(&first), Some(first));
+        assert_eq!(tree.iter().count(), 199);
+    }
+}
@@ -0,0 +1,176 @@
+use crate::node::{ParentNode, RTreeNode};
+use crate::params::RTreeParams;
+use crate::point::Point;
+use crate::{Envelope, RTree, RTreeObject};
+
+use alloc::vec;
+use alloc::vec::Vec;
+use num_traits::Zero;
+
+/// Quality measures of all nodes at the same depth of a tree, see [TreeQuality].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LevelQuality<S> {
+    /// The depth of the level, the root has depth zero.
+    pub depth: usize,
+    /// The number of parent nodes at this depth.
+    pub nodes: usize,
+    /// The sum of the areas of all node envelopes.
+    pub area: S,
+    /// The sum of the margins of all node envelopes, see [Envelope::perimeter_value].
+    pub margin: S,
+    /// The sum of the intersection areas of all pairs of sibling nodes.
+    pub overlap: S,
+}
+
+/// Standard r-tree quality measures, returned by [RTree::quality].
+///
+/// Smaller values are better for all measures: a small total area reduces the number of
+/// nodes visited by queries that do not hit any element, small margins yield square nodes
+/// and little overlap between siblings reduces the number of paths a point query has to
+/// follow. Only parent nodes are measured, elements themselves do not contribute.
+///
+/// The measures are comparable between trees containing the same elements, which allows to
+/// evaluate bulk loading variants or [RTreeParams] on real data.
+///
+/// # Example
+/// ```
+/// use rstar::RTree;
+///
+/// let points: Vec<_> = (0..1000).map(|i| [(i % 40) as f64, (i / 40) as f64]).collect();
+/// let bulk_loaded = RTree::bulk_load(points.clone());
+/// let mut inserted = RTree::new();
+/// for point in points {
+///     inserted.insert(point);
+/// }
+/// let bulk_loaded = bulk_loaded.quality();
+/// let inserted = inserted.quality();
+/// assert_eq!(bulk_loaded.levels()[0].nodes, 1);
+/// println!(
+///     "overlap: {} vs {}",
+///     bulk_loaded.total_overlap(),
+///     inserted.total_overlap()
+/// );
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct TreeQuality<S> {
+    levels: Vec<LevelQuality<S>>,
+}
+
+impl<S> TreeQuality<S>
+where
+    S: Copy + Zero,
+{
+    /// Returns the measures of every level, starting with the root.
+    pub fn levels(&self) -> &[LevelQuality<S>] {
+        &self.levels
+    }
+
+    /// Returns the sum of the areas of all nodes.
+    pub fn total_area(&self) -> S {
+        self.sum(|level| level.area)
+    }
+
+    /// Returns the sum of the margins of all nodes.
+    pub fn total_margin(&self) -> S {
+        self.sum(|level| level.margin)
+    }
+
+    /// Returns the sum of the overlap between siblings on all levels.
+    pub fn total_overlap(&self) -> S {
+        self.sum(|level| level.overlap)
+    }
+
+    fn sum(&self, value: impl Fn(&LevelQuality<S>) -> S) -> S {
+        self.levels
+            .iter()
+            .fold(S::zero(), |sum, level| sum + value(level))
+    }
+}
+
+impl<T, Params> RTree<T, Params>
+where
+    T: RTreeObject,
+    Params: RTreeParams,
+{
+    /// Computes area, margin and sibling overlap of all nodes, see [TreeQuality].
+    ///
+    /// Visits every parent node once and compares all pairs of siblings.
+    pub fn quality(&self) -> TreeQuality<<<T::Envelope as Envelope>::Point as Point>::Scalar> {
+        let mut levels = Vec::new();
+        // All nodes of the current level, grouped by their parent.
+        let mut groups: Vec<Vec<&ParentNode<T>>> = vec![vec![self.root()]];
+        while !groups.is_empty() {
+            let zero = Zero::zero();
+            let mut level = LevelQuality {
+                depth: levels.len(),
+                nodes: 0,
+                area: zero,
+                margin: zero,
+                overlap: zero,
+            };
+            let mut next_groups = Vec::new();
+            for group in groups {
+                for (index, node) in group.iter().enumerate() {
+                    level.nodes += 1;
+                    level.area = level.area + node.envelope.area();
+                    level.margin = level.margin + node.envelope.perimeter_value();
+                    for sibling in &group[index + 1..] {
+                        level.overlap =
+                            level.overlap + node.envelope.intersection_area(&sibling.envelope);
+                    }
+                    let children: Vec<_> = node
+                        .children
+                        .iter()
+                        .filter_map(|child| match child {
+                            RTreeNode::Parent(ref data) => Some(data),
+                            RTreeNode::Leaf(_) => None,
+                        })
+                        .collect();
+                    if !children.is_empty() {
+                        next_groups.push(children);
+                    }
+                }
+            }
+            levels.push(level);
+            groups = next_groups;
+        }
+        TreeQuality { levels }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_utilities::{create_random_rectangles, SEED_1};
+    use crate::{Envelope, RTree, RTreeObject};
+
+    #[test]
+    fn test_quality() {
+        let rectangles = create_random_rectangles(1000, SEED_1);
+        let tree = RTree::bulk_load(rectangles);
+        let quality = tree.quality();
+        let levels = quality.levels();
+        assert_eq!(levels[0].nodes, 1);
+        assert_eq!(levels[0].area, tree.root().envelope().area());
+        assert_eq!(levels[0].overlap, 0.0);
+        assert!(levels.iter().all(|level| level.overlap >= 0.0));
+        assert_eq!(
+            quality.total_area(),
+            levels.iter().map(|level| level.area).sum::<f64>()
+        );
+
+        let children: Vec<_> = tree
+            .root()
+            .children()
+            .iter()
+            .map(|child| child.envelope())
+            .collect();
+        let mut overlap = 0.0;
+        for (index, envelope) in children.iter().enumerate() {
+            for sibling in &children[index + 1..] {
+                overlap += envelope.intersection_area(sibling);
+            }
+        }
+        assert_eq!(levels[1].nodes, children.len());
+        assert_eq!(levels[1].overlap, overlap);
+    }
+}
@@ -0,0 +1,196 @@
+use crate::params::{DefaultParams, RTreeParams};
+use crate::{Envelope, RTree, RTreeObject};
+
+use alloc::vec::Vec;
+
+/// An r-tree that caches the results of envelope queries.
+///
+/// [CachedRTree::locate_in_envelope_intersecting] stores the results of the most recent
+/// queries, repeating a query with an identical envelope returns the stored result without
+/// searching the tree. This is useful if the same viewport is queried every frame while the
+/// tree changes rarely.
+///
+/// Modifications only invalidate the results they can change: inserting or removing an
+/// element drops the results of queries whose envelope intersects the element's envelope.
+/// Results of all other queries stay valid.
+///
+/// The results are stored as clones of the elements. Once the cache contains `capacity`
+/// results, the least recently used result is dropped.
+///
+/// # Example
+/// ```
+/// use rstar::{CachedRTree, RTree, AABB};
+///
+/// let mut tree = CachedRTree::from_tree(RTree::bulk_load(vec![[0.0, 0.0], [1.0, 1.0]]), 8);
+/// let viewport = AABB::from_corners([-0.5, -0.5], [0.5, 0.5]);
+/// assert_eq!(tree.locate_in_envelope_intersecting(&viewport), &[[0.0, 0.0]]);
+/// // Answered from the cache.
+/// assert_eq!(tree.locate_in_envelope_intersecting(&viewport), &[[0.0, 0.0]]);
+///
+/// // Inserting far away from the viewport keeps the cached result.
+/// tree.insert([5.0, 5.0]);
+/// assert_eq!(tree.cached_queries(), 1);
+/// tree.insert([0.1, 0.1]);
+/// assert_eq!(tree.cached_queries(), 0);
+/// assert_eq!(tree.locate_in_envelope_intersecting(&viewport).len(), 2);
+/// ```
+pub struct CachedRTree<T, Params = DefaultParams>
+where
+    T: RTreeObject,
+    Params: RTreeParams,
+{
+    tree: RTree<T, Params>,
+    /// Cached query envelopes and their results, most recently used first.
+    entries: Vec<(T::Envelope, Vec<T>)>,
+    capacity: usize,
+}
+
+impl<T> CachedRTree<T>
+where
+    T: RTreeObject + Clone,
+    T::Envelope: PartialEq,
+{
+    /// Creates a new, empty tree caching the results of up to `capacity` queries.
+    pub fn new(capacity: usize) -> Self {
+        Self::from_tree(RTree::new(), capacity)
+    }
+}
+
+impl<T, Params> CachedRTree<T, Params>
+where
+    T: RTreeObject + Clone,
+    T::Envelope: PartialEq,
+    Params: RTreeParams,
+{
+    /// Caches the query results of an existing tree.
+    pub fn from_tree(tree: RTree<T, Params>, capacity: usize) -> Self {
+        CachedRTree {
+            tree,
+            entries: Vec::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Returns the underlying tree.
+    pub fn tree(&self) -> &RTree<T, Params> {
+        &self.tree
+    }
+
+    /// Consumes this wrapper and returns the underlying tree.
+    pub fn into_tree(self) -> RTree<T, Params> {
+        self.tree
+    }
+
+    /// Returns the number of cached query results.
+    pub fn cached_queries(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Drops all cached query results.
+    pub fn clear_cache(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Inserts an element and drops all cached results it affects.
+    ///
+    /// See [RTree::insert].
+    pub fn insert(&mut self, t: T) {
+        self.invalidate(&t.envelope());
+        self.tree.insert(t);
+    }
+
+    /// Returns all elements whose envelope intersects an envelope.
+    ///
+    /// See [RTree::locate_in_envelope_intersecting]. The result is taken from the cache if the
+    /// same envelope has been queried before and no affecting modification happened since.
+    pub fn locate_in_envelope_intersecting(&mut self, envelope: &T::Envelope) -> &[T] {
+        match self.entries.iter().position(|(key, _)| key == envelope) {
+            Some(index) => {
+                let entry = self.entries.remove(index);
+                self.entries.insert(0, entry);
+            }
+            None => {
+                if self.capacity == 0 {
+                    return &[];
+                }
+                let result = self
+                    .tree
+                    .locate_in_envelope_intersecting(envelope)
+                    .cloned()
+                    .collect();
+                self.entries.truncate(self.capacity - 1);
+                self.entries.insert(0, (envelope.clone(), result));
+            }
+        }
+        &self.entries[0].1
+    }
+
+    fn invalidate(&mut self, envelope: &T::Envelope) {
+        self.entries.retain(|(key, _)| !key.intersects(envelope));
+    }
+}
+
+impl<T, Params> CachedRTree<T, Params>
+where
+    T: RTreeObject + Clone + PartialEq,
+    T::Envelope: PartialEq,
+    Params: RTreeParams,
+{
+    /// Removes an element and drops all cached results it affects.
+    ///
+    /// See [RTree::remove].
+    pub fn remove(&mut self, t: &T) -> Option<T> {
+        let removed = self.tree.remove(t)?;
+        self.invalidate(&removed.envelope());
+        Some(removed)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::CachedRTree;
+    use crate::test_utilities::{create_random_points, SEED_1, SEED_2};
+    use crate::{RTree, AABB};
+
+    #[test]
+    fn test_cached_queries() {
+        let points = create_random_points(600, SEED_1);
+        let mut tree = CachedRTree::from_tree(RTree::bulk_load(points[..300].to_vec()), 4);
+        let mut expected = RTree::bulk_load(points[..300].to_vec());
+        let viewports: Vec<_> = create_random_points(6, SEED_2)
+            .into_iter()
+            .map(|[x, y]| AABB::from_corners([x, y], [x + 0.2, y + 0.2]))
+            .collect();
+
+        for (index, point) in points[300..].iter().enumerate() {
+            for viewport in &viewports[index % 3..index % 3 + 4] {
+                let mut found = tree.locate_in_envelope_intersecting(viewport).to_vec();
+                let mut reference: Vec<_> = expected
+                    .locate_in_envelope_intersecting(viewport)
+                    .copied()
+                    .collect();
+                found.sort_by(|l, r| l.partial_cmp(r).unwrap());
+                reference.sort_by(|l, r| l.partial_cmp(r).unwrap());
+                assert_eq!(found, reference);
+            }
+            assert!(tree.cached_queries() <= 4);
+            if index % 2 == 0 {
+                tree.insert(*point);
+                expected.insert(*point);
+            } else {
+                let removed = points[index];
+                assert_eq!(tree.remove(&removed), expected.remove(&removed));
+            }
+        }
+
+        let far = [5.0, 5.0];
+        let cached = tree.cached_queries();
+        tree.insert(far);
+        assert_eq!(tree.cached_queries(), cached);
+        assert_eq!(tree.remove(&far), Some(far));
+        assert_eq!(tree.cached_queries(), cached);
+        tree.clear_cache();
+        assert_eq!(tree.cached_queries(), 0);
+        assert_eq!(tree.into_tree().size(), expected.size());
+    }
+}
@@ -4,16 +4,25 @@ use crate::algorithm::iterators::*;
 use crate::algorithm::nearest_neighbor;
 use crate::algorithm::nearest_neighbor::NearestNeighborDistance2Iterator;
 use crate::algorithm::nearest_neighbor::NearestNeighborIterator;
+use crate::algorithm::nearest_neighbor::{NearestNeighborState, NearestNeighborStateIterator};
 use crate::algorithm::removal;
 use crate::algorithm::removal::DrainIterator;
 use crate::algorithm::selection_functions::*;
+use crate::algorithm::weighted_nearest_neighbor::Weighting;
+use crate::capacity::SpareNodes;
 use crate::envelope::Envelope;
-use crate::node::ParentNode;
-use crate::object::{PointDistance, RTreeObject};
-use crate::params::{verify_parameters, DefaultParams, InsertionStrategy, RTreeParams};
-use crate::Point;
+use crate::node::{ParentNode, RTreeNode};
+use crate::object::{ExactPredicate, PointDistance, RTreeObject};
+use crate::params::{
+    check_parameters, verify_parameters, DefaultParams, InsertionStrategy, ParamsError, RTreeParams,
+};
+use crate::spatial_reference::{SpatialReference, SpatialReferenceMismatch};
+#[cfg(doc)]
+use crate::{AdditiveWeight, MultiplicativeWeight};
+use crate::{Point, AABB};
 
 use alloc::vec::Vec;
+use core::fmt;
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
@@ -134,6 +143,20 @@ where
 /// # (De)Serialization
 /// Enable the `serde` feature for [Serde](https://crates.io/crates/serde) support.
 ///
+/// # Cheap clones
+/// Cloning an `RTree` copies all of its nodes and elements. If a tree needs to be cloned
+/// often, e.g. to hand read-only views to worker threads, wrap it in a
+/// [CowRTree](crate::CowRTree): its clones share the tree until one of them is modified.
+/// If shared trees are modified often, convert the tree into a
+/// [FrozenRTree](crate::FrozenRTree) instead, whose modifications only copy the nodes on
+/// the modified path. Converting it back into an `RTree` copies only those nodes that are
+/// still shared.
+///
+/// # Serialization
+/// With the `serde` feature, trees can be serialized and deserialized. The exact node
+/// structure is stored, so a deserialized tree answers queries exactly like the original
+/// one without being rebuilt.
+///
 #[derive(Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[cfg_attr(
@@ -150,6 +173,10 @@ where
 {
     root: ParentNode<T>,
     size: usize,
+    #[cfg_attr(feature = "serde", serde(default))]
+    spatial_reference: Option<SpatialReference>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    spare_nodes: SpareNodes<T>,
     _params: ::core::marker::PhantomData<Params>,
 }
 
@@ -205,6 +232,12 @@ where
     /// This method implements the overlap minimizing top-down bulk loading algorithm (OMT)
     /// as described in [this paper by Lee and Lee (2003)](http://ceur-ws.org/Vol-74/files/FORUM_18.pdf).
     ///
+    /// Bulk loading is not restricted to [AABB](crate::AABB)s, elements with any
+    /// [Envelope] can be bulk loaded, e.g. [GeoAABB](crate::GeoAABB) or
+    /// [PeriodicAABB](crate::PeriodicAABB). The elements are divided into slabs with
+    /// [Envelope::partition_envelopes], which needs to order envelopes consistently along
+    /// every axis.
+    ///
     /// # Runtime
     /// Bulk loading runs in `O(n * log(n))`, where `n` is the number of loaded
     /// elements.
@@ -230,10 +263,30 @@ where
         RTree {
             root: ParentNode::new_root::<Params>(),
             size: 0,
+            spatial_reference: None,
+            spare_nodes: SpareNodes::default(),
             _params: Default::default(),
         }
     }
 
+    /// Creates a new, empty r-tree or returns an error if its parameters are invalid.
+    ///
+    /// Unlike [RTree::new_with_params], this method does not panic for invalid
+    /// parameters. See [check_parameters](crate::check_parameters).
+    pub fn try_new_with_params() -> Result<Self, ParamsError> {
+        check_parameters::<T, Params>()?;
+        Ok(Self::new_with_params())
+    }
+
+    /// Bulk loads a new r-tree or returns an error if its parameters are invalid.
+    ///
+    /// Unlike [RTree::bulk_load_with_params], this method does not panic for invalid
+    /// parameters.
+    pub fn try_bulk_load_with_params(elements: Vec<T>) -> Result<Self, ParamsError> {
+        check_parameters::<T, Params>()?;
+        Ok(Self::bulk_load_with_params(elements))
+    }
+
     /// Creates a new r-tree with some given elements and configurable parameters.
     ///
     /// For more information refer to [RTree::bulk_load]
@@ -276,7 +329,46 @@ where
     /// }
     /// ```
     pub fn iter(&self) -> RTreeIterator<T> {
-        RTreeIterator::new(&self.root, SelectAllFunc)
+        RTreeIterator::new(&self.root)
+    }
+
+    /// Returns an iterator over the nodes at the lowest level of the tree.
+    ///
+    /// Every [LeafNode] provides the elements of one node together with their envelope. The
+    /// elements of a node are spatially close and stored contiguously, which makes them a
+    /// natural unit for batch processing, e.g. with SIMD or on a GPU.
+    ///
+    /// Every element is part of exactly one leaf node. An empty tree has no leaf nodes.
+    ///
+    /// # Example
+    /// ```
+    /// use rstar::RTree;
+    /// let tree = RTree::bulk_load(vec![(0.0, 0.1), (0.3, 0.2), (0.4, 0.2)]);
+    /// for leaf in tree.iter_leaf_nodes() {
+    ///     let batch: Vec<_> = leaf.iter().collect();
+    ///     println!("{} points within {:?}", batch.len(), leaf.envelope());
+    /// }
+    /// ```
+    pub fn iter_leaf_nodes(&self) -> LeafNodeIterator<'_, T> {
+        LeafNodeIterator::new(&self.root)
+    }
+
+    /// Returns an iterator over all nodes of the tree in level order, together with their depth.
+    ///
+    /// The root has a depth of `0` and is always returned, even if the tree is empty. All nodes
+    /// of one level are returned before the nodes of the next level, all leaf nodes have the
+    /// same depth.
+    ///
+    /// # Example
+    /// ```
+    /// use rstar::RTree;
+    /// let tree = RTree::bulk_load((0..1000).map(|i| [i as f64, 0.0]).collect());
+    /// for (depth, node) in tree.iter_nodes_breadth_first() {
+    ///     println!("{}{:?}", "  ".repeat(depth), node.envelope());
+    /// }
+    /// ```
+    pub fn iter_nodes_breadth_first(&self) -> BreadthFirstNodeIterator<'_, T> {
+        BreadthFirstNodeIterator::new(&self.root)
     }
 
     /// Returns an iterator over all mutable elements contained in the tree.
@@ -290,7 +382,7 @@ where
     /// and reinsert it.
     ///
     pub fn iter_mut(&mut self) -> RTreeIteratorMut<T> {
-        RTreeIteratorMut::new(&mut self.root, SelectAllFunc)
+        RTreeIteratorMut::new(&mut self.root)
     }
 
     /// Returns all elements contained in an [Envelope].
@@ -390,6 +482,23 @@ where
         )
     }
 
+    /// Returns all elements whose geometry intersects a given envelope.
+    ///
+    /// Unlike [RTree::locate_in_envelope_intersecting], every element whose envelope intersects
+    /// `envelope` is additionally checked with [ExactPredicate::intersects_envelope].
+    pub fn locate_in_envelope_intersecting_exact(
+        &self,
+        envelope: &T::Envelope,
+    ) -> LocateInEnvelopeIntersectingExact<'_, T>
+    where
+        T: ExactPredicate,
+    {
+        LocateInEnvelopeIntersectingExact::new(
+            &self.root,
+            SelectInEnvelopeFuncIntersectingExact::new(envelope.clone()),
+        )
+    }
+
     /// Mutable variant of [locate_in_envelope_intersecting](#method.locate_in_envelope_intersecting)
     pub fn locate_in_envelope_intersecting_mut(
         &mut self,
@@ -423,10 +532,50 @@ where
         SelectionIteratorMut::new(&mut self.root, selection_function)
     }
 
+    /// Returns a selection query that can be paused and resumed.
+    ///
+    /// See [LocateCursor] for details.
+    ///
+    /// # Example
+    /// ```
+    /// use rstar::{RTree, SelectionFunction, AABB};
+    ///
+    /// struct LeftHalf;
+    ///
+    /// impl SelectionFunction<[f64; 2]> for LeftHalf {
+    ///     fn should_unpack_parent(&self, envelope: &AABB<[f64; 2]>) -> bool {
+    ///         envelope.lower()[0] < 500.0
+    ///     }
+    ///
+    ///     fn should_unpack_leaf(&self, point: &[f64; 2]) -> bool {
+    ///         point[0] < 500.0
+    ///     }
+    /// }
+    ///
+    /// let tree = RTree::bulk_load((0..1000).map(|i| [i as f64, 0.0]).collect());
+    /// let mut cursor = tree.locate_cursor(LeftHalf);
+    /// let mut found = Vec::new();
+    /// // Visit at most 100 nodes or elements per frame.
+    /// while !cursor.poll(100, &mut found) {
+    ///     // Render the current frame...
+    /// }
+    /// assert_eq!(found.len(), 500);
+    /// ```
+    pub fn locate_cursor<S: SelectionFunction<T>>(
+        &self,
+        selection_function: S,
+    ) -> LocateCursor<'_, T, S> {
+        LocateCursor::new(&self.root, selection_function)
+    }
+
     /// Returns all possible intersecting objects of this and another tree.
     ///
     /// This will return all objects whose _envelopes_ intersect. No geometric intersection
     /// checking is performed.
+    ///
+    /// # Panics
+    /// In debug builds, this method panics if the trees' [spatial references](SpatialReference)
+    /// don't match.
     pub fn intersection_candidates_with_other_tree<'a, U>(
         &'a self,
         other: &'a RTree<U>,
@@ -434,9 +583,58 @@ where
     where
         U: RTreeObject<Envelope = T::Envelope>,
     {
+        debug_assert_eq!(
+            SpatialReference::check_compatible(self.spatial_reference(), other.spatial_reference()),
+            Ok(()),
+            "Cannot intersect trees with different spatial references"
+        );
         IntersectionIterator::new(self.root(), other.root())
     }
 
+    /// Returns the spatial reference this tree is tagged with.
+    pub fn spatial_reference(&self) -> Option<&SpatialReference> {
+        self.spatial_reference.as_ref()
+    }
+
+    /// Tags this tree with a spatial reference.
+    ///
+    /// The spatial reference is not interpreted by the tree. It is used to check
+    /// that trees that are combined use the same coordinate system.
+    pub fn set_spatial_reference(&mut self, spatial_reference: Option<SpatialReference>) {
+        self.spatial_reference = spatial_reference;
+    }
+
+    /// Returns this tree tagged with a spatial reference.
+    ///
+    /// See [RTree::set_spatial_reference].
+    pub fn with_spatial_reference(mut self, spatial_reference: SpatialReference) -> Self {
+        self.spatial_reference = Some(spatial_reference);
+        self
+    }
+
+    /// Moves all elements of another tree into this tree.
+    ///
+    /// If this tree is not tagged with a spatial reference, it adopts the spatial reference
+    /// of `other`.
+    ///
+    /// Returns an error and leaves both trees unchanged if their spatial references
+    /// don't match.
+    pub fn merge(&mut self, mut other: Self) -> Result<(), SpatialReferenceMismatch> {
+        SpatialReference::check_compatible(self.spatial_reference(), other.spatial_reference())?;
+        let spatial_reference = self
+            .spatial_reference
+            .take()
+            .or(other.spatial_reference.take());
+        if other.size > self.size {
+            ::core::mem::swap(self, &mut other);
+        }
+        for element in other.drain() {
+            self.insert(element);
+        }
+        self.spatial_reference = spatial_reference;
+        Ok(())
+    }
+
     /// Returns the tree's root node.
     ///
     /// Usually, you will not need to call this method. However, for debugging purposes or for
@@ -450,6 +648,29 @@ where
         &mut self.root
     }
 
+    pub(crate) fn root_and_spare_nodes_mut(&mut self) -> (&mut ParentNode<T>, &mut SpareNodes<T>) {
+        (&mut self.root, &mut self.spare_nodes)
+    }
+
+    pub(crate) fn spare_nodes(&self) -> &SpareNodes<T> {
+        &self.spare_nodes
+    }
+
+    pub(crate) fn into_root(self) -> ParentNode<T> {
+        self.root
+    }
+
+    pub(crate) fn from_root(root: ParentNode<T>) -> Self {
+        verify_parameters::<T, Params>();
+        RTree {
+            size: root.size,
+            root,
+            spatial_reference: None,
+            spare_nodes: SpareNodes::default(),
+            _params: Default::default(),
+        }
+    }
+
     fn new_from_bulk_loading(
         elements: Vec<T>,
         root_loader: impl Fn(Vec<T>) -> ParentNode<T>,
@@ -464,6 +685,8 @@ where
         RTree {
             root,
             size,
+            spatial_reference: None,
+            spare_nodes: SpareNodes::default(),
             _params: Default::default(),
         }
     }
@@ -511,6 +734,35 @@ where
     }
 }
 
+impl<T, P, Params> RTree<T, Params>
+where
+    T: RTreeObject<Envelope = AABB<P>>,
+    P: Point,
+    Params: RTreeParams,
+{
+    /// Returns all elements contained in an AABB grown by `margin` in every direction.
+    ///
+    /// This is a buffered window query, e.g. to find all elements within a tolerance around
+    /// a viewport. See [AABB::expanded_by] and [RTree::locate_in_envelope].
+    ///
+    /// # Example
+    /// ```
+    /// use rstar::{RTree, AABB};
+    ///
+    /// let tree = RTree::bulk_load(vec![[0.0, 0.0], [1.5, 1.0], [3.0, 3.0]]);
+    /// let window = AABB::from_corners([0.0, 0.0], [1.0, 1.0]);
+    /// assert_eq!(tree.locate_in_envelope(&window).count(), 1);
+    /// assert_eq!(tree.locate_in_envelope_expanded(&window, 0.5).count(), 2);
+    /// ```
+    pub fn locate_in_envelope_expanded(
+        &self,
+        envelope: &AABB<P>,
+        margin: P::Scalar,
+    ) -> LocateInEnvelope<'_, T> {
+        self.locate_in_envelope(&envelope.expanded_by(margin))
+    }
+}
+
 impl<T, Params> RTree<T, Params>
 where
     Params: RTreeParams,
@@ -696,6 +948,81 @@ where
         nearest_neighbor::nearest_neighbors(&self.root, query_point.clone())
     }
 
+    /// Returns all elements tied for being nearest to a given point, in a deterministic order.
+    ///
+    /// Elements whose squared distance exceeds the minimal squared distance by at most
+    /// `tolerance` are considered tied, a tolerance of zero only returns elements at exactly
+    /// the same distance. The result is sorted with `compare`, which makes the order
+    /// independent of the tree's structure as long as `compare` is a total order.
+    /// Returns an empty `Vec` if the tree is empty.
+    ///
+    /// # Example
+    /// ```
+    /// use rstar::RTree;
+    /// let tree = RTree::bulk_load(vec![
+    ///   [1.0, 0.0],
+    ///   [0.0, 1.0],
+    ///   [0.0, 1.001],
+    ///   [2.0, 2.0],
+    /// ]);
+    /// let compare = |a: &&[f64; 2], b: &&[f64; 2]| a.partial_cmp(b).unwrap();
+    /// assert_eq!(
+    ///     tree.nearest_neighbors_by(&[0.0, 0.0], 0.01, compare),
+    ///     &[&[0.0, 1.0], &[0.0, 1.001], &[1.0, 0.0]]
+    /// );
+    /// ```
+    pub fn nearest_neighbors_by<F>(
+        &self,
+        query_point: &<T::Envelope as Envelope>::Point,
+        tolerance: <<T::Envelope as Envelope>::Point as Point>::Scalar,
+        compare: F,
+    ) -> Vec<&T>
+    where
+        F: FnMut(&&T, &&T) -> core::cmp::Ordering,
+    {
+        nearest_neighbor::nearest_neighbors_by(&self.root, query_point.clone(), tolerance, compare)
+    }
+
+    /// Returns all elements of the tree sorted by their weighted distance to a given point.
+    ///
+    /// The weighted distance combines the euclidean (not squared) distance of an element with
+    /// a per-element weight, e.g. `distance - weight` for an [AdditiveWeight] or
+    /// `distance * weight` for a [MultiplicativeWeight]. The iterator yields each element
+    /// together with its weighted distance.
+    ///
+    /// Subtrees are pruned with a lower bound of the weighted distance derived from the
+    /// weighting's global weight bound: the closer that bound is to the actual weights, the
+    /// fewer nodes need to be visited.
+    ///
+    /// # Example
+    /// ```
+    /// use rstar::primitives::GeomWithData;
+    /// use rstar::{AdditiveWeight, RTree};
+    ///
+    /// let tree = RTree::bulk_load(vec![
+    ///     GeomWithData::new([1.0, 0.0], 0.0),
+    ///     GeomWithData::new([3.0, 0.0], 2.5),
+    /// ]);
+    /// let weighting = AdditiveWeight::new(|e: &GeomWithData<[f64; 2], f64>| e.data, 2.5);
+    /// let (nearest, distance) = tree
+    ///     .nearest_neighbor_iter_weighted(&[0.0, 0.0], weighting)
+    ///     .next()
+    ///     .unwrap();
+    /// assert_eq!(nearest.geom(), &[3.0, 0.0]);
+    /// assert_eq!(distance, 0.5);
+    /// ```
+    pub fn nearest_neighbor_iter_weighted<W>(
+        &self,
+        query_point: &<T::Envelope as Envelope>::Point,
+        weighting: W,
+    ) -> WeightedNearestNeighborIterator<'_, T, W>
+    where
+        <<T::Envelope as Envelope>::Point as Point>::Scalar: num_traits::Float,
+        W: Weighting<T>,
+    {
+        WeightedNearestNeighborIterator::new(&self.root, query_point.clone(), weighting)
+    }
+
     /// Returns all elements of the tree within a certain distance.
     ///
     /// The elements may be returned in any order. Each returned element
@@ -752,6 +1079,65 @@ where
         nearest_neighbor::NearestNeighborIterator::new(&self.root, query_point.clone())
     }
 
+    /// Consumes the tree and returns all of its elements sorted by their distance to a given
+    /// point.
+    ///
+    /// This method behaves like [RTree::nearest_neighbor_iter] but returns owned elements,
+    /// avoiding to clone them. The tree's nodes are unpacked while iterating.
+    ///
+    /// # Example
+    /// ```
+    /// use rstar::RTree;
+    /// let tree = RTree::bulk_load(vec![[0.0, 0.0], [0.0, 1.0], [3.0, 0.0]]);
+    ///
+    /// let sorted: Vec<_> = tree.into_iter_sorted_by_distance(&[0.5, 0.9]).collect();
+    /// assert_eq!(sorted, vec![[0.0, 1.0], [0.0, 0.0], [3.0, 0.0]]);
+    /// ```
+    pub fn into_iter_sorted_by_distance(
+        self,
+        query_point: &<T::Envelope as Envelope>::Point,
+    ) -> IntoNearestNeighborIterator<T> {
+        IntoNearestNeighborIterator::new(self.root, query_point.clone())
+    }
+
+    /// Returns a nearest neighbor query that can be paused and resumed.
+    ///
+    /// See [NearestNeighborCursor] for details.
+    ///
+    /// # Example
+    /// ```
+    /// use rstar::RTree;
+    /// let tree = RTree::bulk_load((0..1000).map(|i| [i as f64, 0.0]).collect());
+    /// let mut cursor = tree.nearest_neighbor_cursor(&[500.0, 0.0]);
+    /// let mut nearest = Vec::new();
+    /// // Visit at most 100 nodes or elements per frame.
+    /// while !cursor.poll(100, &mut nearest) {
+    ///     // Render the current frame...
+    /// }
+    /// assert_eq!(nearest.len(), 1000);
+    /// assert_eq!(nearest[0], (&[500.0, 0.0], 0.0));
+    /// ```
+    pub fn nearest_neighbor_cursor(
+        &self,
+        query_point: &<T::Envelope as Envelope>::Point,
+    ) -> NearestNeighborCursor<'_, T> {
+        NearestNeighborCursor::new(&self.root, query_point.clone())
+    }
+
+    /// Returns all elements of the tree sorted by their distance to a given point, reusing the
+    /// buffers of a [NearestNeighborState].
+    ///
+    /// This method behaves like [RTree::nearest_neighbor_iter] but does not allocate once the
+    /// state's buffers have grown large enough. Refer to [NearestNeighborState] for an
+    /// example.
+    pub fn nearest_neighbor_iter_with<'a, 's>(
+        &'a self,
+        state: &'s mut NearestNeighborState<'a, T>,
+        query_point: &<T::Envelope as Envelope>::Point,
+    ) -> NearestNeighborStateIterator<'s, 'a, T> {
+        NearestNeighborStateIterator::new(&self.root, state, query_point.clone())
+    }
+
     /// Returns `(element, distance^2)` tuples of the tree sorted by their distance to a given point.
     ///
     /// The distance is calculated by calling
@@ -817,12 +1203,113 @@ where
     /// This method runs in `O(log(n))`.
     /// The [r-tree documentation](RTree) contains more information about
     /// r-tree performance.
+    ///
+    /// # Panics
+    /// Panics if the insertion could exceed [RTreeParams::MAX_HEIGHT], see [RTree::try_insert].
     pub fn insert(&mut self, t: T) {
+        if let Err(error) = self.try_insert(t) {
+            panic!("{}", error);
+        }
+    }
+
+    /// Inserts a new element unless this could exceed [RTreeParams::MAX_HEIGHT].
+    ///
+    /// The tree only grows when its root is split. Once the tree has reached its maximum
+    /// height, the element is returned if the root has less than
+    /// `max(1, REINSERTION_COUNT)` free slots, as this many splits may propagate up to the
+    /// root during a single insertion. The check is conservative: some elements are rejected
+    /// even though inserting them would not have split the root.
+    ///
+    /// # Example
+    /// ```
+    /// use rstar::{RStarInsertionStrategy, RTree, RTreeParams};
+    ///
+    /// struct FlatParams;
+    ///
+    /// impl RTreeParams for FlatParams {
+    ///     const MIN_SIZE: usize = 3;
+    ///     const MAX_SIZE: usize = 6;
+    ///     const REINSERTION_COUNT: usize = 2;
+    ///     const MAX_HEIGHT: usize = 2;
+    ///     type DefaultInsertionStrategy = RStarInsertionStrategy;
+    /// }
+    ///
+    /// let mut tree: RTree<_, FlatParams> = RTree::new_with_params();
+    /// let mut rejected = None;
+    /// for i in 0..100 {
+    ///     if let Err(error) = tree.try_insert([i as f64, 0.0]) {
+    ///         rejected = Some(error.element);
+    ///         break;
+    ///     }
+    /// }
+    /// assert!(rejected.is_some());
+    /// assert_eq!(tree.height(), 2);
+    /// ```
+    pub fn try_insert(&mut self, t: T) -> Result<(), HeightLimitExceeded<T>> {
+        if Params::MAX_HEIGHT != usize::MAX && self.height() >= Params::MAX_HEIGHT {
+            let max_size = self.root.max_size::<Params>();
+            let free_slots = max_size.saturating_sub(self.root.children.len());
+            if free_slots < Params::REINSERTION_COUNT.max(1) {
+                return Err(HeightLimitExceeded {
+                    element: t,
+                    max_height: Params::MAX_HEIGHT,
+                });
+            }
+        }
         Params::DefaultInsertionStrategy::insert(self, t);
         self.size += 1;
+        Ok(())
+    }
+
+    /// Returns the number of node levels of the tree.
+    ///
+    /// A tree whose root only contains elements, including the empty tree, has height 1.
+    ///
+    /// # Example
+    /// ```
+    /// use rstar::RTree;
+    ///
+    /// let mut tree = RTree::new();
+    /// assert_eq!(tree.height(), 1);
+    /// for i in 0..100 {
+    ///     tree.insert([i as f64, 0.0]);
+    /// }
+    /// assert!(tree.height() > 1);
+    /// ```
+    pub fn height(&self) -> usize {
+        let mut height = 1;
+        let mut node = &self.root;
+        while let Some(RTreeNode::Parent(ref child)) = node.children.first() {
+            node = child;
+            height += 1;
+        }
+        height
     }
 }
 
+/// Error returned by [RTree::try_insert] if inserting an element could exceed
+/// [RTreeParams::MAX_HEIGHT].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HeightLimitExceeded<T> {
+    /// The element that was not inserted.
+    pub element: T,
+    /// The tree's maximum height.
+    pub max_height: usize,
+}
+
+impl<T> fmt::Display for HeightLimitExceeded<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "inserting the element could exceed the maximum tree height of {}",
+            self.max_height
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: fmt::Debug> std::error::Error for HeightLimitExceeded<T> {}
+
 impl<T, Params> RTree<T, Params>
 where
     T: RTreeObject,
@@ -831,6 +1318,69 @@ where
 {
 }
 
+impl<T, Params> RTree<T, Params>
+where
+    T: RTreeObject + Clone,
+    Params: RTreeParams,
+{
+    /// Calls `callback` with chunks of all elements selected by a selection function.
+    ///
+    /// The selected elements are cloned into a buffer of `chunk_size` elements which is passed
+    /// to `callback` whenever it is full, and once more for the remaining elements. Handling
+    /// elements in contiguous chunks reduces the per element overhead if results need to cross
+    /// an FFI or channel boundary.
+    ///
+    /// # Panics
+    /// Panics if `chunk_size` is zero.
+    pub fn for_each_chunk_with_selection_function<S, F>(
+        &self,
+        selection_function: S,
+        chunk_size: usize,
+        mut callback: F,
+    ) where
+        S: SelectionFunction<T>,
+        F: FnMut(&[T]),
+    {
+        assert!(chunk_size > 0, "Chunk size must be at least 1");
+        let mut chunk = Vec::with_capacity(chunk_size);
+        for t in self.locate_with_selection_function(selection_function) {
+            chunk.push(t.clone());
+            if chunk.len() == chunk_size {
+                callback(&chunk);
+                chunk.clear();
+            }
+        }
+        if !chunk.is_empty() {
+            callback(&chunk);
+        }
+    }
+
+    /// Calls `callback` with chunks of all elements contained in an envelope.
+    ///
+    /// See [RTree::for_each_chunk_with_selection_function] for details.
+    ///
+    /// # Example
+    /// ```
+    /// use rstar::{RTree, AABB};
+    /// let tree = RTree::bulk_load((0..100).map(|i| [i as f64, 0.0]).collect());
+    /// let envelope = AABB::from_corners([10.0, 0.0], [59.0, 0.0]);
+    ///
+    /// let mut chunk_sizes = Vec::new();
+    /// tree.for_each_in_envelope(&envelope, 16, |chunk| chunk_sizes.push(chunk.len()));
+    /// assert_eq!(chunk_sizes, vec![16, 16, 16, 2]);
+    /// ```
+    pub fn for_each_in_envelope<F>(&self, envelope: &T::Envelope, chunk_size: usize, callback: F)
+    where
+        F: FnMut(&[T]),
+    {
+        self.for_each_chunk_with_selection_function(
+            SelectInEnvelopeFunction::new(envelope.clone()),
+            chunk_size,
+            callback,
+        )
+    }
+}
+
 impl<'a, T, Params> IntoIterator for &'a RTree<T, Params>
 where
     T: RTreeObject,
@@ -860,10 +1410,10 @@ where
 #[cfg(test)]
 mod test {
     use super::RTree;
-    use crate::algorithm::rstar::RStarInsertionStrategy;
-    use crate::params::RTreeParams;
+    use crate::algorithm::rstar::{RStarInsertionStrategy, RStarSplitStrategy};
+    use crate::params::{ConstParams, ParamsError, RTreeParams};
     use crate::test_utilities::{create_random_points, SEED_1};
-    use crate::DefaultParams;
+    use crate::{DefaultParams, AABB};
 
     struct TestParams;
     impl RTreeParams for TestParams {
@@ -873,6 +1423,26 @@ mod test {
         type DefaultInsertionStrategy = RStarInsertionStrategy;
     }
 
+    #[test]
+    fn test_for_each_in_envelope() {
+        let tree = RTree::bulk_load(create_random_points(500, SEED_1));
+        let envelope = AABB::from_corners([0.1, 0.1], [0.9, 0.8]);
+        let mut found = Vec::new();
+        tree.for_each_in_envelope(&envelope, 7, |chunk| {
+            assert!(!chunk.is_empty() && chunk.len() <= 7);
+            found.extend_from_slice(chunk);
+        });
+        let mut expected: Vec<_> = tree.locate_in_envelope(&envelope).cloned().collect();
+        assert!(expected.len() > 100);
+        expected.sort_by(|l, r| l.partial_cmp(r).unwrap());
+        found.sort_by(|l, r| l.partial_cmp(r).unwrap());
+        assert_eq!(found, expected);
+
+        let mut calls = 0;
+        RTree::<[f64; 2]>::new().for_each_in_envelope(&envelope, 7, |_| calls += 1);
+        assert_eq!(calls, 0);
+    }
+
     #[test]
     fn test_remove_capacity() {
         pub struct WeirdParams;
@@ -922,6 +1492,324 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_insert_and_remove_deep_tree() {
+        struct NarrowParams;
+        impl RTreeParams for NarrowParams {
+            const MIN_SIZE: usize = 1;
+            const MAX_SIZE: usize = 4;
+            const REINSERTION_COUNT: usize = 1;
+            type DefaultInsertionStrategy = RStarInsertionStrategy;
+        }
+
+        let points = create_random_points(2000, SEED_1);
+        let mut tree: RTree<_, NarrowParams> = RTree::new_with_params();
+        for point in &points {
+            tree.insert(*point);
+        }
+        tree.root().sanity_check::<NarrowParams>(true);
+        for point in &points[..1000] {
+            assert_eq!(tree.remove(point), Some(*point));
+        }
+        tree.root().sanity_check::<NarrowParams>(false);
+        assert_eq!(tree.size(), 1000);
+        assert!(points[1000..].iter().all(|point| tree.contains(point)));
+    }
+
+    #[test]
+    fn test_split_only_insertion() {
+        use crate::SplitOnlyInsertionStrategy;
+
+        struct SplitOnlyParams;
+        impl RTreeParams for SplitOnlyParams {
+            const MIN_SIZE: usize = 3;
+            const MAX_SIZE: usize = 6;
+            const REINSERTION_COUNT: usize = 2;
+            type DefaultInsertionStrategy = SplitOnlyInsertionStrategy;
+        }
+
+        let points = create_random_points(1000, SEED_1);
+        let mut tree: RTree<_, SplitOnlyParams> = RTree::new_with_params();
+        for point in &points {
+            tree.insert(*point);
+        }
+        tree.root().sanity_check::<SplitOnlyParams>(true);
+        assert_eq!(tree.size(), 1000);
+        assert!(points.iter().all(|point| tree.contains(point)));
+    }
+
+    #[test]
+    fn test_try_new_with_invalid_params() {
+        struct InvalidParams;
+        impl RTreeParams for InvalidParams {
+            const MIN_SIZE: usize = 2;
+            const MAX_SIZE: usize = 6;
+            const REINSERTION_COUNT: usize = 4;
+            type DefaultInsertionStrategy = RStarInsertionStrategy;
+        }
+
+        let expected = ParamsError::ReinsertionCountTooLarge {
+            reinsertion_count: 4,
+            limit: 4,
+        };
+        assert_eq!(
+            RTree::<[f64; 2], InvalidParams>::try_new_with_params().err(),
+            Some(expected)
+        );
+        assert_eq!(
+            RTree::<[f64; 2], InvalidParams>::try_bulk_load_with_params(vec![[0.0, 0.0]]).err(),
+            Some(expected)
+        );
+        let tree = RTree::<_, DefaultParams>::try_bulk_load_with_params(vec![[0.0, 0.0]]);
+        assert_eq!(tree.map(|tree| tree.size()), Ok(1));
+
+        let mut tree: RTree<_, ConstParams<2, 5, 1>> = RTree::new_with_params();
+        for point in create_random_points(100, SEED_1) {
+            tree.insert(point);
+        }
+        tree.root().sanity_check::<ConstParams<2, 5, 1>>(true);
+    }
+
+    #[test]
+    fn test_try_insert_max_height() {
+        struct LowParams;
+        impl RTreeParams for LowParams {
+            const MIN_SIZE: usize = 3;
+            const MAX_SIZE: usize = 6;
+            const REINSERTION_COUNT: usize = 2;
+            const MAX_HEIGHT: usize = 3;
+            type DefaultInsertionStrategy = RStarInsertionStrategy;
+        }
+
+        let points = create_random_points(1000, SEED_1);
+        let mut tree: RTree<_, LowParams> = RTree::new_with_params();
+        let mut inserted = 0;
+        for point in &points {
+            match tree.try_insert(*point) {
+                Ok(()) => inserted += 1,
+                Err(error) => {
+                    assert_eq!(error.element, *point);
+                    assert_eq!(error.max_height, 3);
+                }
+            }
+            assert!(tree.height() <= 3);
+        }
+        assert!(inserted < 1000);
+        assert_eq!(tree.size(), inserted);
+        assert_eq!(tree.height(), 3);
+        tree.root().sanity_check::<LowParams>(true);
+
+        struct ZeroHeightParams;
+        impl RTreeParams for ZeroHeightParams {
+            const MIN_SIZE: usize = 3;
+            const MAX_SIZE: usize = 6;
+            const REINSERTION_COUNT: usize = 2;
+            const MAX_HEIGHT: usize = 0;
+            type DefaultInsertionStrategy = RStarInsertionStrategy;
+        }
+        assert_eq!(
+            RTree::<[f64; 2], ZeroHeightParams>::try_new_with_params().err(),
+            Some(ParamsError::MaxHeightZero)
+        );
+    }
+
+    #[test]
+    fn test_max_leaf_size() {
+        use crate::node::RTreeNode;
+
+        struct LargeLeafParams;
+        impl RTreeParams for LargeLeafParams {
+            const MIN_SIZE: usize = 3;
+            const MAX_SIZE: usize = 6;
+            const MAX_LEAF_SIZE: usize = 32;
+            const REINSERTION_COUNT: usize = 2;
+            type DefaultInsertionStrategy = RStarInsertionStrategy;
+        }
+
+        fn max_leaf_node_size<T: crate::RTreeObject>(node: &super::ParentNode<T>) -> usize {
+            match node.children.first() {
+                Some(RTreeNode::Parent(_)) => node
+                    .children
+                    .iter()
+                    .map(|child| match child {
+                        RTreeNode::Parent(ref data) => max_leaf_node_size(data),
+                        RTreeNode::Leaf(_) => unreachable!(),
+                    })
+                    .max()
+                    .unwrap(),
+                _ => node.children.len(),
+            }
+        }
+
+        let points = create_random_points(1000, SEED_1);
+        let mut tree: RTree<_, LargeLeafParams> = RTree::new_with_params();
+        for point in &points {
+            tree.insert(*point);
+        }
+        tree.root().sanity_check::<LargeLeafParams>(true);
+        assert!(max_leaf_node_size(tree.root()) > LargeLeafParams::MAX_SIZE);
+        assert!(points.iter().all(|point| tree.contains(point)));
+
+        let tree: RTree<_, LargeLeafParams> = RTree::bulk_load_with_params(points.clone());
+        assert_eq!(tree.height(), 3);
+        assert!(max_leaf_node_size(tree.root()) <= LargeLeafParams::MAX_LEAF_SIZE);
+        assert!(points.iter().all(|point| tree.contains(point)));
+
+        struct SmallLeafParams;
+        impl RTreeParams for SmallLeafParams {
+            const MIN_SIZE: usize = 3;
+            const MAX_SIZE: usize = 8;
+            const MAX_LEAF_SIZE: usize = 5;
+            const REINSERTION_COUNT: usize = 2;
+            type DefaultInsertionStrategy = RStarInsertionStrategy;
+        }
+        assert_eq!(
+            RTree::<[f64; 2], SmallLeafParams>::try_new_with_params().err(),
+            Some(ParamsError::MaxLeafSizeTooSmall {
+                max_leaf_size: 5,
+                limit: 6
+            })
+        );
+    }
+
+    #[test]
+    fn test_split_strategies() {
+        use crate::{
+            InsertionStrategy, LinearSplitStrategy, QuadraticSplitStrategy,
+            SplitOnlyInsertionStrategy,
+        };
+
+        fn check<I: InsertionStrategy>() {
+            struct SplitParams<I>(core::marker::PhantomData<fn() -> I>);
+            impl<I: InsertionStrategy> RTreeParams for SplitParams<I> {
+                const MIN_SIZE: usize = 3;
+                const MAX_SIZE: usize = 8;
+                const REINSERTION_COUNT: usize = 2;
+                type DefaultInsertionStrategy = I;
+            }
+
+            let points = create_random_points(1000, SEED_1);
+            let mut tree: RTree<_, SplitParams<I>> = RTree::new_with_params();
+            for point in &points {
+                tree.insert(*point);
+            }
+            tree.root().sanity_check::<SplitParams<I>>(true);
+            assert_eq!(tree.size(), 1000);
+            assert!(points.iter().all(|point| tree.contains(point)));
+        }
+
+        check::<RStarInsertionStrategy<LinearSplitStrategy>>();
+        check::<RStarInsertionStrategy<QuadraticSplitStrategy>>();
+        check::<RStarInsertionStrategy<RStarSplitStrategy>>();
+        check::<SplitOnlyInsertionStrategy<QuadraticSplitStrategy>>();
+    }
+
+    #[test]
+    fn test_minimal_overlap_insertion() {
+        use crate::test_utilities::{create_random_rectangles, SEED_2};
+        use crate::{Envelope, MinimalOverlapInsertionStrategy, RTreeObject};
+
+        struct MinimalOverlapParams;
+        impl RTreeParams for MinimalOverlapParams {
+            const MIN_SIZE: usize = 3;
+            const MAX_SIZE: usize = 6;
+            const REINSERTION_COUNT: usize = 2;
+            type DefaultInsertionStrategy = MinimalOverlapInsertionStrategy;
+        }
+
+        let rectangles = create_random_rectangles(1000, SEED_1);
+        let mut tree: RTree<_, MinimalOverlapParams> = RTree::new_with_params();
+        for rectangle in &rectangles {
+            tree.insert(*rectangle);
+        }
+        tree.root().sanity_check::<MinimalOverlapParams>(true);
+        assert_eq!(tree.size(), 1000);
+        for point in create_random_points(100, SEED_2) {
+            let expected = rectangles
+                .iter()
+                .filter(|rectangle| rectangle.envelope().contains_point(&point))
+                .count();
+            assert_eq!(tree.locate_all_at_point(&point).count(), expected);
+        }
+    }
+
+    #[test]
+    fn test_custom_insertion_strategy() {
+        use crate::node::ParentNode;
+        use crate::{CustomInsertionStrategy, InsertionHeuristic, OverflowTreatment, RTreeObject};
+
+        // Always descends into the last child and only reinserts below the root.
+        struct LastChild;
+        impl InsertionHeuristic for LastChild {
+            fn choose_subtree<T: RTreeObject>(node: &ParentNode<T>, _: &T::Envelope) -> usize {
+                node.children.len() - 1
+            }
+
+            fn overflow_treatment<T, Params>(_: &ParentNode<T>, depth: usize) -> OverflowTreatment
+            where
+                T: RTreeObject,
+                Params: RTreeParams,
+            {
+                if depth == 0 {
+                    OverflowTreatment::Split
+                } else {
+                    OverflowTreatment::Reinsert
+                }
+            }
+        }
+
+        struct LastChildParams;
+        impl RTreeParams for LastChildParams {
+            const MIN_SIZE: usize = 3;
+            const MAX_SIZE: usize = 6;
+            const REINSERTION_COUNT: usize = 2;
+            type DefaultInsertionStrategy = CustomInsertionStrategy<LastChild>;
+        }
+
+        let points = create_random_points(1000, SEED_1);
+        let mut tree: RTree<_, LastChildParams> = RTree::new_with_params();
+        for point in &points {
+            tree.insert(*point);
+        }
+        tree.root().sanity_check::<LastChildParams>(true);
+        assert_eq!(tree.size(), 1000);
+        assert!(points.iter().all(|point| tree.contains(point)));
+    }
+
+    #[test]
+    fn test_subtree_sizes() {
+        use crate::node::{ParentNode, RTreeNode};
+
+        fn check_sizes(node: &ParentNode<[f64; 2]>) -> usize {
+            let size = node
+                .children
+                .iter()
+                .map(|child| match child {
+                    RTreeNode::Leaf(_) => 1,
+                    RTreeNode::Parent(ref parent) => check_sizes(parent),
+                })
+                .sum();
+            assert_eq!(node.size(), size);
+            size
+        }
+
+        let points = create_random_points(1000, SEED_1);
+        let mut tree = RTree::bulk_load(points[..500].to_vec());
+        assert_eq!(check_sizes(tree.root()), 500);
+        for point in &points[500..] {
+            tree.insert(*point);
+        }
+        assert_eq!(check_sizes(tree.root()), 1000);
+
+        let half = AABB::from_corners([0.0, 0.0], [0.5, 1.0]);
+        let drained = tree.drain_in_envelope(half).count();
+        assert_eq!(check_sizes(tree.root()), 1000 - drained);
+        for point in &points[..100] {
+            tree.remove(point);
+        }
+        assert_eq!(check_sizes(tree.root()), tree.size());
+    }
+
     #[test]
     fn test_fmt_debug() {
         let tree = RTree::bulk_load(vec![[0, 1], [0, 1]]);
@@ -935,6 +1823,43 @@ mod test {
         assert_eq!(tree.size(), 0);
     }
 
+    #[test]
+    fn test_merge_spatial_references() {
+        use crate::SpatialReference;
+
+        let points = create_random_points(300, SEED_1);
+        let mut tree = RTree::bulk_load(points[..100].to_vec());
+        let other = RTree::bulk_load(points[100..].to_vec())
+            .with_spatial_reference(SpatialReference::Epsg(3857));
+        tree.merge(other).unwrap();
+        assert_eq!(tree.size(), 300);
+        assert_eq!(
+            tree.spatial_reference(),
+            Some(&SpatialReference::Epsg(3857))
+        );
+        tree.root().sanity_check::<DefaultParams>(false);
+        for point in &points {
+            assert!(tree.contains(point));
+        }
+
+        let other = RTree::new().with_spatial_reference(SpatialReference::Epsg(4326));
+        let error = tree.merge(other).unwrap_err();
+        assert_eq!(error.left, SpatialReference::Epsg(3857));
+        assert_eq!(tree.size(), 300);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serialize_spatial_reference() {
+        use crate::SpatialReference;
+
+        let tree = RTree::bulk_load(vec![[0, 1]])
+            .with_spatial_reference(SpatialReference::Named("local".into()));
+        let json = serde_json::to_string(&tree).unwrap();
+        let parsed: RTree<[i32; 2]> = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.spatial_reference(), tree.spatial_reference());
+    }
+
     #[cfg(feature = "serde")]
     #[test]
     fn test_serialization() {
@@ -953,6 +1878,45 @@ mod test {
         }
     }
 
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_deserialization_recomputes_node_sizes() {
+        let tree = RTree::bulk_load(crate::test_utilities::create_random_integers::<[i32; 2]>(
+            100, SEED_1,
+        ));
+        let mut json = serde_json::to_value(&tree).unwrap();
+        let root = json["root"].as_object_mut().unwrap();
+        assert!(!root.contains_key("size"));
+        // Sizes contained in the input are ignored.
+        root.insert("size".into(), 5.into());
+        let parsed: RTree<[i32; 2]> = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed.root().size(), 100);
+        assert_eq!(parsed.iter().len(), 100);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serialization_preserves_structure() {
+        let points: Vec<_> = create_random_points(500, SEED_1)
+            .into_iter()
+            .map(|[x, y]| [(x * 1000.0) as i32, (y * 1000.0) as i32])
+            .collect();
+        let mut tree = RTree::new();
+        for point in &points {
+            tree.insert(*point);
+        }
+        let json = serde_json::to_string(&tree).unwrap();
+        let parsed: RTree<[i32; 2]> = serde_json::from_str(&json).unwrap();
+        // Serializing the parsed tree again only yields the same output if every node
+        // is restored exactly.
+        assert_eq!(serde_json::to_string(&parsed).unwrap(), json);
+        assert_eq!(parsed.size(), 500);
+        assert_eq!(parsed.root().children().len(), tree.root().children().len());
+        for point in &points[..10] {
+            assert_eq!(parsed.nearest_neighbor(point), Some(point));
+        }
+    }
+
     #[test]
     fn test_bulk_load_crash() {
         let bulk_nodes = vec![
@@ -0,0 +1,188 @@
+use crate::node::{ParentNode, RTreeNode};
+use crate::params::RTreeParams;
+use crate::{Envelope, RTree, RTreeObject};
+
+use alloc::collections::BTreeSet;
+use alloc::vec::Vec;
+use rand::Rng;
+
+impl<T, Params> RTree<T, Params>
+where
+    T: RTreeObject,
+    Params: RTreeParams,
+{
+    /// Draws up to `k` distinct elements uniformly at random.
+    ///
+    /// Every subset of `k` elements is equally likely. If the tree contains at most `k`
+    /// elements, all elements are returned. The subtree sizes stored in the tree are used
+    /// to locate the sampled elements, which takes `O(k log(n))` instead of iterating all
+    /// elements. The elements are returned in arbitrary order.
+    ///
+    /// Requires the `sampling` feature.
+    ///
+    /// # Example
+    /// ```
+    /// use rstar::RTree;
+    ///
+    /// let tree = RTree::bulk_load((0..1000).map(|i| [i as f64, 0.0]).collect());
+    /// let preview = tree.sample(&mut rand::thread_rng(), 10);
+    /// assert_eq!(preview.len(), 10);
+    /// ```
+    pub fn sample<R: Rng + ?Sized>(&self, rng: &mut R, k: usize) -> Vec<&T> {
+        let ranks = sample_ranks(rng, self.size(), k);
+        let mut result = Vec::with_capacity(ranks.len());
+        select(self.root().children(), &ranks, 0, &mut result);
+        result
+    }
+
+    /// Draws up to `k` distinct elements uniformly at random from the elements whose
+    /// envelope intersects `envelope`.
+    ///
+    /// Like [RTree::sample], but restricted to the elements returned by
+    /// [RTree::locate_in_envelope_intersecting]. Only the nodes at the border of
+    /// the envelope are visited, subtrees fully contained in it are sampled using their
+    /// sizes.
+    ///
+    /// Requires the `sampling` feature.
+    ///
+    /// # Example
+    /// ```
+    /// use rstar::{RTree, AABB};
+    ///
+    /// let tree = RTree::bulk_load((0..1000).map(|i| [i as f64, 0.0]).collect());
+    /// let envelope = AABB::from_corners([100.0, -1.0], [199.0, 1.0]);
+    /// let preview = tree.sample_in_envelope(&mut rand::thread_rng(), &envelope, 10);
+    /// assert_eq!(preview.len(), 10);
+    /// assert!(preview.iter().all(|p| p[0] >= 100.0 && p[0] <= 199.0));
+    /// ```
+    pub fn sample_in_envelope<R: Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+        envelope: &T::Envelope,
+        k: usize,
+    ) -> Vec<&T> {
+        let mut candidates = Vec::new();
+        collect_candidates(self.root(), envelope, &mut candidates);
+        let size = candidates.iter().map(|node| node.size()).sum();
+        let ranks = sample_ranks(rng, size, k);
+        let mut result = Vec::with_capacity(ranks.len());
+        select(candidates.iter().copied(), &ranks, 0, &mut result);
+        result
+    }
+}
+
+/// Draws `min(k, n)` distinct ranks from `0..n`, sorted ascendingly.
+///
+/// Uses Floyd's algorithm, which needs exactly one random number per rank.
+fn sample_ranks<R: Rng + ?Sized>(rng: &mut R, n: usize, k: usize) -> Vec<usize> {
+    if k >= n {
+        return (0..n).collect();
+    }
+    let mut ranks = BTreeSet::new();
+    for j in n - k..n {
+        let rank = rng.gen_range(0, j + 1);
+        if !ranks.insert(rank) {
+            ranks.insert(j);
+        }
+    }
+    ranks.into_iter().collect()
+}
+
+/// Pushes the elements with the given sorted ranks, counting elements in the order of `nodes`
+/// and starting at `offset`.
+fn select<'a, T, I>(nodes: I, mut ranks: &[usize], mut offset: usize, result: &mut Vec<&'a T>)
+where
+    T: RTreeObject,
+    I: IntoIterator<Item = &'a RTreeNode<T>>,
+{
+    for node in nodes {
+        if ranks.is_empty() {
+            return;
+        }
+        let end = offset + node.size();
+        let count = ranks.iter().take_while(|rank| **rank < end).count();
+        if count > 0 {
+            match node {
+                RTreeNode::Leaf(t) => result.push(t),
+                RTreeNode::Parent(data) => select(data.children(), &ranks[..count], offset, result),
+            }
+            ranks = &ranks[count..];
+        }
+        offset = end;
+    }
+}
+
+/// Collects elements and subtrees which together contain exactly the elements intersecting
+/// `envelope`.
+fn collect_candidates<'a, T>(
+    node: &'a ParentNode<T>,
+    envelope: &T::Envelope,
+    candidates: &mut Vec<&'a RTreeNode<T>>,
+) where
+    T: RTreeObject,
+{
+    for child in node.children() {
+        if !child.envelope().intersects(envelope) {
+            continue;
+        }
+        match child {
+            RTreeNode::Parent(data) if !envelope.contains_envelope(&data.envelope) => {
+                collect_candidates(data, envelope, candidates)
+            }
+            _ => candidates.push(child),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_utilities::{create_random_points, SEED_1, SEED_2};
+    use crate::{RTree, AABB};
+    use rand::SeedableRng;
+    use rand_hc::Hc128Rng;
+
+    #[test]
+    fn test_sample() {
+        let mut rng = Hc128Rng::from_seed(*SEED_2);
+        let points = create_random_points(1000, SEED_1);
+        let tree = RTree::bulk_load(points.clone());
+        let mut sample = tree.sample(&mut rng, 100);
+        assert_eq!(sample.len(), 100);
+        sample.sort_by(|l, r| l.partial_cmp(r).unwrap());
+        sample.dedup();
+        assert_eq!(sample.len(), 100);
+        assert_eq!(tree.sample(&mut rng, 2000).len(), 1000);
+        assert!(RTree::<[f64; 2]>::new().sample(&mut rng, 10).is_empty());
+
+        // Every element is drawn about equally often.
+        let small = RTree::bulk_load(points[..20].to_vec());
+        let mut counts = [0; 20];
+        for _ in 0..4000 {
+            for point in small.sample(&mut rng, 5) {
+                counts[points.iter().position(|p| p == point).unwrap()] += 1;
+            }
+        }
+        assert!(counts.iter().all(|count| (800..1200).contains(count)));
+    }
+
+    #[test]
+    fn test_sample_in_envelope() {
+        let mut rng = Hc128Rng::from_seed(*SEED_2);
+        let tree = RTree::bulk_load(create_random_points(1000, SEED_1));
+        for [x, y] in create_random_points(20, SEED_2) {
+            let envelope = AABB::from_corners([x, y], [x + 0.3, y + 0.3]);
+            let mut expected: Vec<_> = tree.locate_in_envelope_intersecting(&envelope).collect();
+            let mut sample = tree.sample_in_envelope(&mut rng, &envelope, 10);
+            assert_eq!(sample.len(), expected.len().min(10));
+            assert!(sample.iter().all(|point| expected.contains(point)));
+
+            let mut all = tree.sample_in_envelope(&mut rng, &envelope, 1000);
+            all.sort_by(|l, r| l.partial_cmp(r).unwrap());
+            expected.sort_by(|l, r| l.partial_cmp(r).unwrap());
+            assert_eq!(all, expected);
+            sample.sort_by(|l, r| l.partial_cmp(r).unwrap());
+            sample.dedup();
+            assert_eq!(sample.len(), expected.len().min(10));
+        }
+    }
+}
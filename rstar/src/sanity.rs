@@ -0,0 +1,282 @@
+use crate::envelope::Envelope;
+use crate::node::{size_for_children, ParentNode, RTreeNode};
+use crate::object::RTreeObject;
+use crate::params::RTreeParams;
+use crate::RTree;
+
+use core::fmt;
+
+/// An invariant violated by a tree, returned by [RTree::sanity_check] and
+/// [RTree::sanity_check_strict].
+///
+/// The depth of a node is its distance to the root, the root itself has depth zero.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InvariantViolation {
+    /// A node's envelope does not contain the envelope of one of its children. Elements
+    /// within that child may not be found by queries anymore.
+    EnvelopeNotCovering {
+        /// The depth of the node.
+        depth: usize,
+    },
+    /// A node's envelope contains all of its children but is larger than their combined
+    /// envelope. Queries stay correct but visit the node more often than necessary.
+    EnvelopeNotTight {
+        /// The depth of the node.
+        depth: usize,
+    },
+    /// A node other than the root has fewer children than [RTreeParams::MIN_SIZE]. Only
+    /// reported by [RTree::sanity_check_strict].
+    Underfull {
+        /// The depth of the node.
+        depth: usize,
+        /// The node's number of children.
+        children: usize,
+    },
+    /// A node has more children than [RTreeParams::MAX_SIZE]. Only reported by
+    /// [RTree::sanity_check_strict].
+    Overfull {
+        /// The depth of the node.
+        depth: usize,
+        /// The node's number of children.
+        children: usize,
+    },
+    /// A node is empty although it is not the root.
+    EmptyNode {
+        /// The depth of the node.
+        depth: usize,
+    },
+    /// Leaves are found at different depths.
+    UnbalancedLeaves {
+        /// The depth of the first leaf that was found.
+        expected: usize,
+        /// The depth of a leaf that differs from it.
+        found: usize,
+    },
+    /// The number of elements cached in a node differs from the number of elements within it.
+    NodeSizeMismatch {
+        /// The depth of the node.
+        depth: usize,
+        /// The number of elements cached in the node.
+        stored: usize,
+        /// The number of elements reachable from the node.
+        actual: usize,
+    },
+    /// The tree's size differs from the number of elements reachable from its root.
+    SizeMismatch {
+        /// The tree's size, see [RTree::size].
+        stored: usize,
+        /// The number of elements reachable from the root.
+        actual: usize,
+    },
+}
+
+impl fmt::Display for InvariantViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InvariantViolation::EnvelopeNotCovering { depth } => write!(
+                f,
+                "node at depth {} does not cover the envelopes of its children",
+                depth
+            ),
+            InvariantViolation::EnvelopeNotTight { depth } => write!(
+                f,
+                "node at depth {} has a larger envelope than its children",
+                depth
+            ),
+            InvariantViolation::Underfull { depth, children } => {
+                write!(f, "node at depth {} has only {} children", depth, children)
+            }
+            InvariantViolation::Overfull { depth, children } => write!(
+                f,
+                "node at depth {} has {} children, more than allowed",
+                depth, children
+            ),
+            InvariantViolation::EmptyNode { depth } => {
+                write!(f, "node at depth {} is empty", depth)
+            }
+            InvariantViolation::UnbalancedLeaves { expected, found } => write!(
+                f,
+                "leaves found at depth {} and at depth {}",
+                expected, found
+            ),
+            InvariantViolation::NodeSizeMismatch {
+                depth,
+                stored,
+                actual,
+            } => write!(
+                f,
+                "node at depth {} stores size {} but contains {} elements",
+                depth, stored, actual
+            ),
+            InvariantViolation::SizeMismatch { stored, actual } => write!(
+                f,
+                "tree stores size {} but contains {} elements",
+                stored, actual
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for InvariantViolation {}
+
+impl<T, Params> RTree<T, Params>
+where
+    T: RTreeObject,
+    Params: RTreeParams,
+{
+    /// Checks the structural invariants that all queries rely on.
+    ///
+    /// The following invariants are verified, the first violation found is returned:
+    ///  - every node's envelope equals the combined envelope of its children,
+    ///  - no node except for the root is empty,
+    ///  - all leaves are at the same depth,
+    ///  - the sizes cached in the nodes and the tree match the number of reachable elements.
+    ///
+    /// A tree built and modified with this crate's methods never violates these invariants.
+    /// This method is meant for fuzzing, tests and for verifying trees that were assembled by
+    /// other means, e.g. from deserialized data. It visits every node and element once.
+    ///
+    /// # Example
+    /// ```
+    /// use rstar::RTree;
+    ///
+    /// let tree = RTree::bulk_load((0..1000).map(|i| [i as f64, 0.0]).collect());
+    /// assert_eq!(tree.sanity_check(), Ok(()));
+    /// ```
+    pub fn sanity_check(&self) -> Result<(), InvariantViolation> {
+        self.check(false)
+    }
+
+    /// Checks the structural invariants and the fill of every node.
+    ///
+    /// In addition to [RTree::sanity_check], every node except for the root must have between
+    /// [RTreeParams::MIN_SIZE] and [RTreeParams::MAX_SIZE] children. Insertions maintain these
+    /// bounds, but bulk loading packs nodes independently of them and removals may leave
+    /// nodes underfull. Violations of the fill bounds only affect performance, not
+    /// correctness.
+    ///
+    /// # Example
+    /// ```
+    /// use rstar::RTree;
+    ///
+    /// let mut tree = RTree::new();
+    /// for i in 0..1000 {
+    ///     tree.insert([i as f64, 0.0]);
+    /// }
+    /// assert_eq!(tree.sanity_check_strict(), Ok(()));
+    /// ```
+    pub fn sanity_check_strict(&self) -> Result<(), InvariantViolation> {
+        self.check(true)
+    }
+
+    fn check(&self, check_fill: bool) -> Result<(), InvariantViolation> {
+        let mut leaf_depth = None;
+        let actual = check_node::<T, Params>(self.root(), 0, check_fill, &mut leaf_depth)?;
+        if actual != self.size() {
+            return Err(InvariantViolation::SizeMismatch {
+                stored: self.size(),
+                actual,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Checks a node's subtree and returns its number of elements.
+fn check_node<T, Params>(
+    node: &ParentNode<T>,
+    depth: usize,
+    check_fill: bool,
+    leaf_depth: &mut Option<usize>,
+) -> Result<usize, InvariantViolation>
+where
+    T: RTreeObject,
+    Params: RTreeParams,
+{
+    let children = node.children.len();
+    if depth > 0 && children == 0 {
+        return Err(InvariantViolation::EmptyNode { depth });
+    }
+    if check_fill && depth > 0 && children < Params::MIN_SIZE {
+        return Err(InvariantViolation::Underfull { depth, children });
+    }
+    if check_fill && children > node.max_size::<Params>() {
+        return Err(InvariantViolation::Overfull { depth, children });
+    }
+
+    let mut envelope = T::Envelope::new_empty();
+    let mut actual = 0;
+    for child in &node.children {
+        let child_envelope = match child {
+            RTreeNode::Leaf(ref t) => {
+                let expected = *leaf_depth.get_or_insert(depth + 1);
+                if expected != depth + 1 {
+                    return Err(InvariantViolation::UnbalancedLeaves {
+                        expected,
+                        found: depth + 1,
+                    });
+                }
+                actual += 1;
+                t.envelope()
+            }
+            RTreeNode::Parent(ref data) => {
+                actual += check_node::<T, Params>(data, depth + 1, check_fill, leaf_depth)?;
+                data.envelope.clone()
+            }
+        };
+        if !node.envelope.contains_envelope(&child_envelope) {
+            return Err(InvariantViolation::EnvelopeNotCovering { depth });
+        }
+        envelope.merge(&child_envelope);
+    }
+    if children > 0 && node.envelope != envelope {
+        return Err(InvariantViolation::EnvelopeNotTight { depth });
+    }
+    if node.size() != actual || size_for_children(&node.children) != actual {
+        return Err(InvariantViolation::NodeSizeMismatch {
+            depth,
+            stored: node.size(),
+            actual,
+        });
+    }
+    Ok(actual)
+}
+
+#[cfg(test)]
+mod test {
+    use super::InvariantViolation;
+    use crate::test_utilities::{create_random_points, create_random_rectangles, SEED_1};
+    use crate::RTree;
+
+    #[test]
+    fn test_sanity_check() {
+        let points = create_random_points(1000, SEED_1);
+        let mut tree = RTree::new();
+        for point in &points {
+            tree.insert(*point);
+        }
+        assert_eq!(tree.sanity_check_strict(), Ok(()));
+        for point in &points[..800] {
+            tree.remove(point);
+        }
+        assert_eq!(tree.sanity_check(), Ok(()));
+        assert_eq!(RTree::<[f64; 2]>::new().sanity_check(), Ok(()));
+
+        let rectangles = RTree::bulk_load(create_random_rectangles(1000, SEED_1));
+        assert_eq!(rectangles.sanity_check(), Ok(()));
+        assert!(matches!(
+            rectangles.sanity_check_strict(),
+            Err(InvariantViolation::Overfull { .. })
+        ));
+
+        *tree.size_mut() += 1;
+        assert_eq!(
+            tree.sanity_check(),
+            Err(InvariantViolation::SizeMismatch {
+                stored: 201,
+                actual: 200
+            })
+        );
+    }
+}
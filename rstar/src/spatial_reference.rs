@@ -0,0 +1,79 @@
+use alloc::string::String;
+use core::fmt;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Describes the coordinate reference system an [RTree](crate::RTree)'s coordinates refer to.
+///
+/// A tree can optionally be tagged with a spatial reference, see
+/// [RTree::set_spatial_reference](crate::RTree::set_spatial_reference). Operations combining
+/// two trees check that their spatial references match in order to catch mixups of
+/// e.g. longitude / latitude and projected coordinates early. Untagged trees are compatible
+/// with any tree.
+///
+/// # Example
+/// ```
+/// use rstar::{RTree, SpatialReference};
+///
+/// let wgs84 = RTree::bulk_load(vec![[13.4, 52.5]]).with_spatial_reference(SpatialReference::Epsg(4326));
+/// let mut utm = RTree::bulk_load(vec![[391_000.0, 5_820_000.0]])
+///     .with_spatial_reference(SpatialReference::Epsg(32633));
+///
+/// assert!(utm.merge(wgs84).is_err());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum SpatialReference {
+    /// A coordinate reference system of the EPSG registry, e.g. `Epsg(4326)` for WGS 84
+    /// longitude / latitude coordinates.
+    Epsg(u32),
+    /// Any other coordinate system or unit, identified by name.
+    Named(String),
+}
+
+impl SpatialReference {
+    /// Checks if two optional spatial references are compatible.
+    ///
+    /// References are compatible if they are equal or if at least one of them is `None`.
+    pub fn check_compatible(
+        left: Option<&SpatialReference>,
+        right: Option<&SpatialReference>,
+    ) -> Result<(), SpatialReferenceMismatch> {
+        match (left, right) {
+            (Some(left), Some(right)) if left != right => Err(SpatialReferenceMismatch {
+                left: left.clone(),
+                right: right.clone(),
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl fmt::Display for SpatialReference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SpatialReference::Epsg(code) => write!(f, "EPSG:{}", code),
+            SpatialReference::Named(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+/// Error returned when two trees with different spatial references are combined.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SpatialReferenceMismatch {
+    /// The spatial reference of the first tree.
+    pub left: SpatialReference,
+    /// The spatial reference of the second tree.
+    pub right: SpatialReference,
+}
+
+impl fmt::Display for SpatialReferenceMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "mismatching spatial references: {} and {}",
+            self.left, self.right
+        )
+    }
+}
@@ -0,0 +1,404 @@
+use crate::algorithm::selection_functions::{
+    SelectEqualsFunction, SelectInEnvelopeFuncIntersecting, SelectInEnvelopeFunction,
+    SelectionFunction,
+};
+use crate::point::Point;
+use crate::{Envelope, PointDistance, RTreeObject};
+
+use core::cmp::Ordering;
+use core::ops::Range;
+
+/// The maximum number of children of a node.
+const NODE_SIZE: usize = 8;
+/// Enough levels for `usize::MAX` elements.
+const MAX_LEVELS: usize = 24;
+/// The maximum number of pending nodes and elements of a search.
+const STACK_SIZE: usize = MAX_LEVELS * NODE_SIZE;
+
+/// An r-tree with a fixed capacity that never allocates.
+///
+/// All elements and nodes are stored inline in arrays of `CAP` entries, the tree can thus be
+/// used without a heap, e.g. on firmware, or be placed in a `static`. Queries use a bounded
+/// stack instead of allocating as well.
+///
+/// The elements are kept packed into nodes of eight children. Every insertion and removal
+/// repacks the tree in place, which takes `O(n log(n))` and is intended for capacities of up
+/// to a few thousand elements. Queries take `O(log(n))` like for an [RTree](crate::RTree).
+///
+/// # Example
+/// ```
+/// use rstar::{StaticRTree, AABB};
+///
+/// let mut tree: StaticRTree<[i32; 2], 4> = StaticRTree::new();
+/// assert_eq!(tree.insert([0, 0]), Ok(()));
+/// assert_eq!(tree.insert([5, 5]), Ok(()));
+/// assert_eq!(tree.insert([9, 9]), Ok(()));
+/// assert_eq!(tree.insert([1, 1]), Ok(()));
+/// // The tree is full.
+/// assert_eq!(tree.insert([2, 2]), Err([2, 2]));
+///
+/// let envelope = AABB::from_corners([0, 0], [4, 4]);
+/// assert_eq!(tree.locate_in_envelope(&envelope).count(), 2);
+/// assert_eq!(tree.nearest_neighbor(&[8, 7]), Some(&[9, 9]));
+/// assert_eq!(tree.remove(&[9, 9]), Some([9, 9]));
+/// assert_eq!(tree.size(), 3);
+/// ```
+pub struct StaticRTree<T, const CAP: usize>
+where
+    T: RTreeObject,
+{
+    /// The elements, packed such that every eight consecutive elements form a leaf node.
+    elements: heapless::Vec<T, CAP>,
+    /// The envelopes of all nodes, level by level starting with the leaf nodes. A tree with
+    /// `n` elements has at most `n` nodes.
+    nodes: heapless::Vec<T::Envelope, CAP>,
+    /// The range of every level within `nodes`, the last level contains only the root.
+    levels: heapless::Vec<Range<usize>, MAX_LEVELS>,
+}
+
+impl<T, const CAP: usize> StaticRTree<T, CAP>
+where
+    T: RTreeObject,
+{
+    /// Creates a new, empty tree.
+    pub const fn new() -> Self {
+        StaticRTree {
+            elements: heapless::Vec::new(),
+            nodes: heapless::Vec::new(),
+            levels: heapless::Vec::new(),
+        }
+    }
+
+    /// Creates a new tree from a set of elements.
+    ///
+    /// Returns the first element exceeding the capacity as error.
+    pub fn bulk_load(elements: impl IntoIterator<Item = T>) -> Result<Self, T> {
+        let mut tree = Self::new();
+        for element in elements {
+            tree.elements.push(element)?;
+        }
+        tree.repack();
+        Ok(tree)
+    }
+
+    /// Returns the number of elements in the tree.
+    pub fn size(&self) -> usize {
+        self.elements.len()
+    }
+
+    /// Returns the maximum number of elements.
+    pub fn capacity(&self) -> usize {
+        CAP
+    }
+
+    /// Returns all elements of the tree in arbitrary order.
+    pub fn iter(&self) -> core::slice::Iter<'_, T> {
+        self.elements.iter()
+    }
+
+    /// Inserts an element.
+    ///
+    /// Returns the element as error if the tree is full.
+    pub fn insert(&mut self, t: T) -> Result<(), T> {
+        self.elements.push(t)?;
+        self.repack();
+        Ok(())
+    }
+
+    /// Returns all elements contained in an envelope.
+    pub fn locate_in_envelope<'a>(
+        &'a self,
+        envelope: &T::Envelope,
+    ) -> impl Iterator<Item = &'a T> + 'a {
+        self.locate_with_selection_function(SelectInEnvelopeFunction::new(envelope.clone()))
+    }
+
+    /// Returns all elements whose envelope intersects an envelope.
+    pub fn locate_in_envelope_intersecting<'a>(
+        &'a self,
+        envelope: &T::Envelope,
+    ) -> impl Iterator<Item = &'a T> + 'a {
+        self.locate_with_selection_function(SelectInEnvelopeFuncIntersecting::new(envelope.clone()))
+    }
+
+    /// Returns all elements selected by a [SelectionFunction].
+    ///
+    /// See [RTree::locate_with_selection_function](crate::RTree::locate_with_selection_function).
+    pub fn locate_with_selection_function<'a, F>(
+        &'a self,
+        function: F,
+    ) -> impl Iterator<Item = &'a T> + 'a
+    where
+        F: SelectionFunction<T> + 'a,
+    {
+        let mut search = Search::new(self, function);
+        core::iter::from_fn(move || search.next_index().map(|index| &self.elements[index]))
+    }
+
+    /// Packs the elements into nodes and recomputes the node envelopes.
+    fn repack(&mut self) {
+        pack(&mut self.elements, 0);
+        self.nodes.clear();
+        self.levels.clear();
+        if self.elements.is_empty() {
+            return;
+        }
+        for group in self.elements.chunks(NODE_SIZE) {
+            let envelope = merge_all(group.iter().map(RTreeObject::envelope));
+            push_within_capacity(&mut self.nodes, envelope);
+        }
+        push_within_capacity(&mut self.levels, 0..self.nodes.len());
+        let mut level = 0..self.nodes.len();
+        while level.len() > 1 {
+            let mut start = level.start;
+            while start < level.end {
+                let end = (start + NODE_SIZE).min(level.end);
+                let envelope = merge_all(self.nodes[start..end].iter().cloned());
+                push_within_capacity(&mut self.nodes, envelope);
+                start = end;
+            }
+            level = level.end..self.nodes.len();
+            push_within_capacity(&mut self.levels, level.clone());
+        }
+    }
+
+    /// Returns the envelope of a node.
+    fn node(&self, level: usize, index: usize) -> &T::Envelope {
+        &self.nodes[self.levels[level].start + index]
+    }
+
+    /// Returns the indices of the children of a node, which are elements for level `0` and
+    /// nodes of the level below otherwise.
+    fn children(&self, level: usize, index: usize) -> Range<usize> {
+        let count = match level {
+            0 => self.elements.len(),
+            _ => self.levels[level - 1].len(),
+        };
+        index * NODE_SIZE..((index + 1) * NODE_SIZE).min(count)
+    }
+}
+
+impl<T, const CAP: usize> StaticRTree<T, CAP>
+where
+    T: RTreeObject + PartialEq,
+{
+    /// Removes an element and returns it, or `None` if the element is not contained in the
+    /// tree.
+    pub fn remove(&mut self, t: &T) -> Option<T> {
+        let index = Search::new(self, SelectEqualsFunction::new(t)).next_index()?;
+        let removed = self.elements.swap_remove(index);
+        self.repack();
+        Some(removed)
+    }
+}
+
+impl<T, const CAP: usize> StaticRTree<T, CAP>
+where
+    T: PointDistance,
+{
+    /// Returns the element nearest to a point, or `None` if the tree is empty.
+    pub fn nearest_neighbor(&self, query_point: &<T::Envelope as Envelope>::Point) -> Option<&T> {
+        let root = self.levels.len().checked_sub(1)?;
+        let mut best = None;
+        self.nearest_recursive(root, 0, query_point, &mut best);
+        best.map(|(index, _)| &self.elements[index])
+    }
+
+    fn nearest_recursive(
+        &self,
+        level: usize,
+        index: usize,
+        query_point: &<T::Envelope as Envelope>::Point,
+        best: &mut Option<(usize, <<T::Envelope as Envelope>::Point as Point>::Scalar)>,
+    ) {
+        for child in self.children(level, index) {
+            if level == 0 {
+                let distance_2 = self.elements[child].distance_2(query_point);
+                if best.map_or(true, |(_, best)| distance_2 < best) {
+                    *best = Some((child, distance_2));
+                }
+            } else {
+                let distance_2 = self.node(level - 1, child).distance_2(query_point);
+                if best.map_or(true, |(_, best)| distance_2 < best) {
+                    self.nearest_recursive(level - 1, child, query_point, best);
+                }
+            }
+        }
+    }
+}
+
+impl<T, const CAP: usize> Default for StaticRTree<T, CAP>
+where
+    T: RTreeObject,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A pending node or element of a [Search].
+enum Pending {
+    Node { level: usize, index: usize },
+    Element(usize),
+}
+
+/// A depth first search using a fixed size stack.
+struct Search<'a, T, F, const CAP: usize>
+where
+    T: RTreeObject,
+{
+    tree: &'a StaticRTree<T, CAP>,
+    function: F,
+    stack: heapless::Vec<Pending, STACK_SIZE>,
+}
+
+impl<'a, T, F, const CAP: usize> Search<'a, T, F, CAP>
+where
+    T: RTreeObject,
+    F: SelectionFunction<T>,
+{
+    fn new(tree: &'a StaticRTree<T, CAP>, function: F) -> Self {
+        let mut stack = heapless::Vec::new();
+        if let Some(root) = tree.levels.len().checked_sub(1) {
+            push_within_capacity(
+                &mut stack,
+                Pending::Node {
+                    level: root,
+                    index: 0,
+                },
+            );
+        }
+        Search {
+            tree,
+            function,
+            stack,
+        }
+    }
+
+    /// Returns the index of the next selected element.
+    fn next_index(&mut self) -> Option<usize> {
+        while let Some(pending) = self.stack.pop() {
+            match pending {
+                Pending::Element(index) => {
+                    if self.function.should_unpack_leaf(&self.tree.elements[index]) {
+                        return Some(index);
+                    }
+                }
+                Pending::Node { level, index } => {
+                    if !self
+                        .function
+                        .should_unpack_parent(self.tree.node(level, index))
+                    {
+                        continue;
+                    }
+                    // At most `NODE_SIZE` entries are pushed per level.
+                    for child in self.tree.children(level, index).rev() {
+                        let child = match level {
+                            0 => Pending::Element(child),
+                            _ => Pending::Node {
+                                level: level - 1,
+                                index: child,
+                            },
+                        };
+                        push_within_capacity(&mut self.stack, child);
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Pushes to a vector whose capacity is known to suffice.
+fn push_within_capacity<T, const N: usize>(vec: &mut heapless::Vec<T, N>, value: T) {
+    if vec.push(value).is_err() {
+        unreachable!("capacity exceeded");
+    }
+}
+
+fn merge_all<E: Envelope>(envelopes: impl Iterator<Item = E>) -> E {
+    envelopes.fold(E::new_empty(), |mut result, envelope| {
+        result.merge(&envelope);
+        result
+    })
+}
+
+/// Sorts elements in place such that groups of `NODE_SIZE` consecutive elements are close to
+/// each other, using sort tile recursion.
+fn pack<T: RTreeObject>(elements: &mut [T], axis: usize) {
+    let dimensions = <T::Envelope as Envelope>::Point::DIMENSIONS;
+    if elements.len() <= NODE_SIZE {
+        return;
+    }
+    elements.sort_unstable_by(|l, r| {
+        let l = l.envelope().center().nth(axis);
+        let r = r.envelope().center().nth(axis);
+        l.partial_cmp(&r).unwrap_or(Ordering::Equal)
+    });
+    if axis + 1 >= dimensions {
+        return;
+    }
+    let groups = (elements.len() + NODE_SIZE - 1) / NODE_SIZE;
+    // The smallest number of slabs whose power of the remaining dimensions covers all groups.
+    let mut slabs = 1;
+    while (slabs as u128).pow((dimensions - axis) as u32) < groups as u128 {
+        slabs += 1;
+    }
+    let slab_size = (groups + slabs - 1) / slabs * NODE_SIZE;
+    for slab in elements.chunks_mut(slab_size) {
+        pack(slab, axis + 1);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::StaticRTree;
+    use crate::test_utilities::{create_random_points, create_random_rectangles, SEED_1, SEED_2};
+    use crate::{Envelope, PointDistance, RTreeObject, AABB};
+
+    #[test]
+    fn test_static_tree() {
+        let points = create_random_points(1000, SEED_1);
+        let mut tree = StaticRTree::<_, 1000>::bulk_load(points[..500].iter().copied()).unwrap();
+        for point in &points[500..] {
+            assert_eq!(tree.insert(*point), Ok(()));
+        }
+        assert_eq!(tree.insert([0.5, 0.5]), Err([0.5, 0.5]));
+        for point in &points[..300] {
+            assert_eq!(tree.remove(point), Some(*point));
+        }
+        assert_eq!(tree.remove(&points[0]), None);
+        let remaining = &points[300..];
+        assert_eq!(tree.size(), 700);
+
+        for query in create_random_points(50, SEED_2) {
+            let envelope = AABB::from_corners(query, [query[0] + 0.2, query[1] + 0.2]);
+            let expected = remaining
+                .iter()
+                .filter(|point| envelope.contains_point(point))
+                .count();
+            assert_eq!(tree.locate_in_envelope(&envelope).count(), expected);
+            let nearest = tree.nearest_neighbor(&query).unwrap();
+            let expected = remaining
+                .iter()
+                .map(|point| point.distance_2(&query))
+                .fold(f64::INFINITY, f64::min);
+            assert_eq!(nearest.distance_2(&query), expected);
+        }
+
+        let rectangles = create_random_rectangles(100, SEED_1);
+        let tree = StaticRTree::<_, 128>::bulk_load(rectangles.clone()).unwrap();
+        let envelope = AABB::from_corners([0.3, 0.3], [0.6, 0.6]);
+        let expected = rectangles
+            .iter()
+            .filter(|rectangle| rectangle.envelope().intersects(&envelope))
+            .count();
+        assert_eq!(
+            tree.locate_in_envelope_intersecting(&envelope).count(),
+            expected
+        );
+        assert!(StaticRTree::<[f64; 2], 8>::new()
+            .nearest_neighbor(&[0.0, 0.0])
+            .is_none());
+    }
+}
@@ -0,0 +1,197 @@
+use crate::algorithm::selection_functions::SelectionFunction;
+use crate::node::{ParentNode, RTreeNode};
+use crate::RTreeObject;
+use alloc::vec::Vec;
+
+#[cfg(feature = "stats")]
+use crate::algorithm::nearest_neighbor;
+#[cfg(feature = "stats")]
+use crate::algorithm::selection_functions::*;
+#[cfg(feature = "stats")]
+use crate::params::RTreeParams;
+#[cfg(feature = "stats")]
+use crate::{Envelope, Point, PointDistance, RTree};
+
+/// Describes the work performed by a single query.
+///
+/// Returned by the `*_with_stats` query variants of [RTree], which are available if the
+/// `stats` feature is enabled. Useful for comparing tree parameters or bulk loading
+/// strategies empirically.
+///
+/// # Example
+/// ```
+/// use rstar::{RTree, AABB};
+///
+/// let tree = RTree::bulk_load((0..1000).map(|i| [i as f64, 0.0]).collect());
+/// let (result, stats) = tree.locate_in_envelope_with_stats(&AABB::from_corners([0.0, 0.0], [9.0, 0.0]));
+/// assert_eq!(result.len(), 10);
+/// assert_eq!(stats.leaves_tested + stats.leaves_pruned, 1000);
+/// assert!(stats.pruning_efficiency() > 0.9);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct QueryStats {
+    /// The number of internal nodes whose envelope was inspected, including the root.
+    pub nodes_visited: usize,
+    /// The number of inspected nodes whose subtree was skipped.
+    pub nodes_pruned: usize,
+    /// The number of elements that were tested individually.
+    pub leaves_tested: usize,
+    /// The number of elements that were skipped as part of a pruned subtree.
+    pub leaves_pruned: usize,
+    /// The number of elements returned by the query.
+    pub results: usize,
+}
+
+#[cfg_attr(not(feature = "stats"), allow(dead_code))]
+impl QueryStats {
+    /// Returns the fraction of the tree's elements that were never tested individually.
+    ///
+    /// Returns `0.0` for queries on empty trees.
+    pub fn pruning_efficiency(&self) -> f64 {
+        let total = self.leaves_tested + self.leaves_pruned;
+        if total == 0 {
+            0.0
+        } else {
+            self.leaves_pruned as f64 / total as f64
+        }
+    }
+}
+
+pub(crate) fn locate_with_stats<'a, T, S>(
+    root: &'a ParentNode<T>,
+    selection_function: &S,
+) -> (Vec<&'a T>, QueryStats)
+where
+    T: RTreeObject,
+    S: SelectionFunction<T>,
+{
+    let mut result = Vec::new();
+    let mut stats = QueryStats {
+        nodes_visited: 1,
+        ..Default::default()
+    };
+    if !selection_function.should_unpack_parent_at_depth(&root.envelope, 0) {
+        stats.nodes_pruned = 1;
+        stats.leaves_pruned = root.size();
+        return (result, stats);
+    }
+    let mut stack = Vec::new();
+    stack.push((root, 0));
+    while let Some((node, depth)) = stack.pop() {
+        for child in &node.children {
+            match child {
+                RTreeNode::Leaf(ref t) => {
+                    stats.leaves_tested += 1;
+                    if selection_function.should_unpack_leaf(t) {
+                        result.push(t);
+                    }
+                }
+                RTreeNode::Parent(ref data) => {
+                    stats.nodes_visited += 1;
+                    if selection_function.should_unpack_parent_at_depth(&data.envelope, depth + 1) {
+                        stack.push((data, depth + 1));
+                    } else {
+                        stats.nodes_pruned += 1;
+                        stats.leaves_pruned += data.size();
+                    }
+                }
+            }
+        }
+    }
+    stats.results = result.len();
+    (result, stats)
+}
+
+#[cfg(feature = "stats")]
+impl<T, Params> RTree<T, Params>
+where
+    T: RTreeObject,
+    Params: RTreeParams,
+{
+    /// Variant of [RTree::locate_with_selection_function] that also reports [QueryStats].
+    pub fn locate_with_selection_function_with_stats<S: SelectionFunction<T>>(
+        &self,
+        selection_function: S,
+    ) -> (Vec<&T>, QueryStats) {
+        locate_with_stats(self.root(), &selection_function)
+    }
+
+    /// Variant of [RTree::locate_in_envelope] that also reports [QueryStats].
+    pub fn locate_in_envelope_with_stats(&self, envelope: &T::Envelope) -> (Vec<&T>, QueryStats) {
+        self.locate_with_selection_function_with_stats(SelectInEnvelopeFunction::new(
+            envelope.clone(),
+        ))
+    }
+
+    /// Variant of [RTree::locate_in_envelope_intersecting] that also reports [QueryStats].
+    pub fn locate_in_envelope_intersecting_with_stats(
+        &self,
+        envelope: &T::Envelope,
+    ) -> (Vec<&T>, QueryStats) {
+        self.locate_with_selection_function_with_stats(SelectInEnvelopeFuncIntersecting::new(
+            envelope.clone(),
+        ))
+    }
+}
+
+#[cfg(feature = "stats")]
+impl<T, Params> RTree<T, Params>
+where
+    T: PointDistance,
+    Params: RTreeParams,
+{
+    /// Variant of [RTree::locate_within_distance] that also reports [QueryStats].
+    pub fn locate_within_distance_with_stats(
+        &self,
+        query_point: <T::Envelope as Envelope>::Point,
+        max_squared_radius: <<T::Envelope as Envelope>::Point as Point>::Scalar,
+    ) -> (Vec<&T>, QueryStats) {
+        self.locate_with_selection_function_with_stats(SelectWithinDistanceFunction::new(
+            query_point,
+            max_squared_radius,
+        ))
+    }
+
+    /// Variant of [RTree::nearest_neighbor] that also reports [QueryStats].
+    ///
+    /// Nodes are counted as pruned if they were never expanded while searching the nearest
+    /// neighbor.
+    pub fn nearest_neighbor_with_stats(
+        &self,
+        query_point: &<T::Envelope as Envelope>::Point,
+    ) -> (Option<&T>, QueryStats) {
+        nearest_neighbor::nearest_neighbor_with_stats(self.root(), query_point)
+    }
+}
+
+#[cfg(all(test, feature = "stats"))]
+mod test {
+    use crate::test_utilities::{create_random_points, SEED_1, SEED_2};
+    use crate::{RTree, AABB};
+
+    #[test]
+    fn test_query_stats() {
+        let points = create_random_points(1000, SEED_1);
+        let tree = RTree::bulk_load(points.clone());
+
+        let envelope = AABB::from_corners([0.2, 0.2], [0.4, 0.3]);
+        let (result, stats) = tree.locate_in_envelope_with_stats(&envelope);
+        assert_eq!(result.len(), tree.locate_in_envelope(&envelope).count());
+        assert_eq!(stats.results, result.len());
+        assert_eq!(stats.leaves_tested + stats.leaves_pruned, tree.size());
+        assert!(stats.nodes_pruned > 0);
+
+        for query in create_random_points(20, SEED_2) {
+            let (nearest, stats) = tree.nearest_neighbor_with_stats(&query);
+            assert_eq!(nearest, tree.nearest_neighbor(&query));
+            assert_eq!(stats.results, 1);
+            assert_eq!(stats.leaves_tested + stats.leaves_pruned, tree.size());
+            assert!(stats.pruning_efficiency() > 0.5);
+        }
+
+        let empty = RTree::<[f64; 2]>::new();
+        let (nearest, stats) = empty.nearest_neighbor_with_stats(&[0.0, 0.0]);
+        assert_eq!(nearest, None);
+        assert_eq!(stats.pruning_efficiency(), 0.0);
+    }
+}
@@ -0,0 +1,160 @@
+use crate::algorithm::selection_functions::SelectEqualsFunction;
+use crate::params::RTreeParams;
+use crate::{RTree, RTreeObject};
+
+use core::hash::{Hash, Hasher};
+
+impl<T, Params> RTree<T, Params>
+where
+    T: RTreeObject,
+    Params: RTreeParams,
+{
+    /// Returns `true` if both trees contain the same elements, regardless of their structure.
+    ///
+    /// Elements are compared as a multiset: every element must occur as often in `other` as in
+    /// `self`. Two trees built from the same elements in different order or with different
+    /// bulk loading strategies are structurally equal even though their nodes differ.
+    ///
+    /// Every element of `self` is looked up in both trees, no elements are copied or sorted.
+    ///
+    /// # Example
+    /// ```
+    /// use rstar::RTree;
+    ///
+    /// let points: Vec<_> = (0..100).map(|i| [i as f64, (i % 7) as f64]).collect();
+    /// let bulk_loaded = RTree::bulk_load(points.clone());
+    /// let mut inserted = RTree::new();
+    /// for point in points.into_iter().rev() {
+    ///     inserted.insert(point);
+    /// }
+    /// assert!(bulk_loaded.structural_eq(&inserted));
+    /// inserted.insert([0.0, 0.0]);
+    /// assert!(!bulk_loaded.structural_eq(&inserted));
+    /// ```
+    pub fn structural_eq<OtherParams>(&self, other: &RTree<T, OtherParams>) -> bool
+    where
+        T: PartialEq,
+        OtherParams: RTreeParams,
+    {
+        self.size() == other.size()
+            && self.iter().all(|t| {
+                let count = self
+                    .locate_with_selection_function(SelectEqualsFunction::new(t))
+                    .count();
+                let other_count = other
+                    .locate_with_selection_function(SelectEqualsFunction::new(t))
+                    .count();
+                count == other_count
+            })
+    }
+
+    /// Returns a hash of all elements that is independent of insertion order and structure.
+    ///
+    /// Trees for which [RTree::structural_eq] returns `true` have the same content hash. The
+    /// hash does not depend on the process or on a random seed, which allows to compare
+    /// replicas on different machines as long as the elements' [Hash] implementations
+    /// produce the same input on both, see [RTree::content_hash_with] for elements that
+    /// cannot implement `Hash`.
+    ///
+    /// # Example
+    /// ```
+    /// use rstar::RTree;
+    ///
+    /// let first = RTree::bulk_load(vec![[0, 0], [1, 2], [1, 2]]);
+    /// let second = RTree::bulk_load(vec![[1, 2], [0, 0], [1, 2]]);
+    /// assert_eq!(first.content_hash(), second.content_hash());
+    /// ```
+    pub fn content_hash(&self) -> u64
+    where
+        T: Hash,
+    {
+        self.content_hash_with(|t| {
+            let mut hasher = StableHasher::new();
+            t.hash(&mut hasher);
+            hasher.finish()
+        })
+    }
+
+    /// Returns a content hash based on a custom hash of every element.
+    ///
+    /// Useful for elements which do not implement [Hash], e.g. points with floating point
+    /// coordinates. See [RTree::content_hash].
+    ///
+    /// # Example
+    /// ```
+    /// use rstar::RTree;
+    ///
+    /// let hash_point = |point: &[f64; 2]| point[0].to_bits() ^ point[1].to_bits().rotate_left(32);
+    /// let first = RTree::bulk_load(vec![[0.5, 1.0], [2.0, 3.0]]);
+    /// let second = RTree::bulk_load(vec![[2.0, 3.0], [0.5, 1.0]]);
+    /// assert_eq!(first.content_hash_with(hash_point), second.content_hash_with(hash_point));
+    /// ```
+    pub fn content_hash_with<F>(&self, mut hash: F) -> u64
+    where
+        F: FnMut(&T) -> u64,
+    {
+        // Summing the mixed hashes makes the result independent of the iteration order while
+        // still distinguishing duplicates.
+        let sum = self
+            .iter()
+            .fold(0u64, |sum, t| sum.wrapping_add(mix(hash(t))));
+        mix(sum ^ self.size() as u64)
+    }
+}
+
+/// The finalizer of splitmix64, spreads every input bit over the whole output.
+fn mix(mut value: u64) -> u64 {
+    value = (value ^ (value >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    value = (value ^ (value >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    value ^ (value >> 31)
+}
+
+/// A 64 bit FNV-1a hasher. Unlike the standard library's hashers, its output is fixed.
+struct StableHasher(u64);
+
+impl StableHasher {
+    fn new() -> Self {
+        StableHasher(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for StableHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = (self.0 ^ u64::from(*byte)).wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_utilities::{create_random_points, SEED_1};
+    use crate::RTree;
+
+    #[test]
+    fn test_structural_eq_and_content_hash() {
+        let mut points: Vec<[i32; 2]> = create_random_points(500, SEED_1)
+            .into_iter()
+            .map(|[x, y]| [(x * 1000.0) as i32, (y * 1000.0) as i32])
+            .collect();
+        points.extend(points[..20].to_vec());
+        let bulk_loaded = RTree::bulk_load(points.clone());
+        let mut inserted = RTree::new();
+        for point in points.iter().rev() {
+            inserted.insert(*point);
+        }
+        assert!(bulk_loaded.structural_eq(&inserted));
+        assert!(inserted.structural_eq(&bulk_loaded));
+        assert_eq!(bulk_loaded.content_hash(), inserted.content_hash());
+
+        // Replacing a duplicate keeps the size but changes the content.
+        inserted.remove(&points[0]);
+        inserted.insert(points[100]);
+        assert!(!bulk_loaded.structural_eq(&inserted));
+        assert_ne!(bulk_loaded.content_hash(), inserted.content_hash());
+    }
+}
@@ -0,0 +1,293 @@
+use crate::algorithm::selection_functions::{SelectAllFunc, SelectionFunction};
+use crate::node::{ParentNode, RTreeNode};
+use crate::object::RTreeObject;
+use crate::params::RTreeParams;
+use crate::point::Point;
+use crate::{RTree, AABB};
+
+use alloc::string::String;
+use core::fmt::{self, Write};
+use num_traits::ToPrimitive;
+
+/// The width of the rendered image in pixels, the height is derived from the tree's aspect
+/// ratio.
+const WIDTH: f64 = 800.0;
+const PADDING: f64 = 10.0;
+
+impl<T, P, Params> RTree<T, Params>
+where
+    T: RTreeObject<Envelope = AABB<P>>,
+    P: Point,
+    P::Scalar: ToPrimitive,
+    Params: RTreeParams,
+{
+    /// Renders the tree as SVG image.
+    ///
+    /// Draws the envelope of every leaf and the envelopes of all parent nodes, colored by
+    /// their depth. Leaves with an empty area, e.g. points, are drawn as small circles. Trees
+    /// with more than two dimensions are projected onto their first two axes.
+    ///
+    /// Only available with the `debug-render` feature.
+    ///
+    /// # Example
+    /// ```
+    /// use rstar::RTree;
+    ///
+    /// let tree = RTree::bulk_load((0..100).map(|i| [i as f64, (i % 10) as f64]).collect());
+    /// let mut svg = String::new();
+    /// tree.write_svg(&mut svg).unwrap();
+    /// assert!(svg.starts_with("<svg"));
+    /// ```
+    pub fn write_svg<W: Write>(&self, out: &mut W) -> fmt::Result {
+        self.render_svg(out, None::<SelectAllFunc>)
+    }
+
+    /// Renders the tree as SVG image and highlights the nodes visited by a query.
+    ///
+    /// Parent nodes which are unpacked by the selection function are drawn with a thick
+    /// outline, leaves selected by it are filled. This shows how many nodes a query has to
+    /// visit, large overlapping envelopes stand out immediately.
+    ///
+    /// Only available with the `debug-render` feature.
+    ///
+    /// # Example
+    /// ```
+    /// use rstar::{RTree, SelectInEnvelopeFuncIntersecting, AABB};
+    ///
+    /// let tree = RTree::bulk_load((0..100).map(|i| [i as f64, (i % 10) as f64]).collect());
+    /// let query = SelectInEnvelopeFuncIntersecting::new(AABB::from_corners([0.0, 0.0], [5.0, 5.0]));
+    /// let mut svg = String::new();
+    /// tree.write_svg_with_query(&mut svg, query).unwrap();
+    /// ```
+    pub fn write_svg_with_query<W, Function>(&self, out: &mut W, function: Function) -> fmt::Result
+    where
+        W: Write,
+        Function: SelectionFunction<T>,
+    {
+        self.render_svg(out, Some(function))
+    }
+
+    /// Renders the tree as SVG image.
+    ///
+    /// See [RTree::write_svg].
+    pub fn to_svg(&self) -> String {
+        let mut result = String::new();
+        self.write_svg(&mut result)
+            .expect("Writing to a String cannot fail");
+        result
+    }
+
+    fn render_svg<W, Function>(&self, out: &mut W, function: Option<Function>) -> fmt::Result
+    where
+        W: Write,
+        Function: SelectionFunction<T>,
+    {
+        let transform = Transform::new(&self.root().envelope);
+        writeln!(
+            out,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{:.0}\" height=\"{:.0}\">",
+            WIDTH,
+            transform.height()
+        )?;
+        let mut renderer = Renderer {
+            out,
+            transform,
+            function,
+        };
+        let visited = renderer.visits(true, &self.root().envelope, 0);
+        renderer.render_node(self.root(), 0, visited)?;
+        writeln!(renderer.out, "</svg>")
+    }
+}
+
+/// Maps the first two coordinates of the tree onto the image.
+struct Transform {
+    min_x: f64,
+    max_y: f64,
+    scale: f64,
+    height: f64,
+}
+
+impl Transform {
+    fn new<P>(envelope: &AABB<P>) -> Self
+    where
+        P: Point,
+        P::Scalar: ToPrimitive,
+    {
+        let [min_x, min_y] = coordinates(&envelope.lower());
+        let [max_x, max_y] = coordinates(&envelope.upper());
+        let extent = (max_x - min_x).max(max_y - min_y);
+        let scale = if extent > 0.0 && extent.is_finite() {
+            (WIDTH - 2.0 * PADDING) / extent
+        } else {
+            1.0
+        };
+        let height = if max_y > min_y && max_y.is_finite() {
+            (max_y - min_y) * scale + 2.0 * PADDING
+        } else {
+            2.0 * PADDING
+        };
+        Transform {
+            min_x,
+            max_y,
+            scale,
+            height,
+        }
+    }
+
+    fn height(&self) -> f64 {
+        self.height
+    }
+
+    /// Returns the x, y, width and height of an envelope in image coordinates.
+    fn rectangle<P>(&self, envelope: &AABB<P>) -> [f64; 4]
+    where
+        P: Point,
+        P::Scalar: ToPrimitive,
+    {
+        let [min_x, min_y] = coordinates(&envelope.lower());
+        let [max_x, max_y] = coordinates(&envelope.upper());
+        [
+            (min_x - self.min_x) * self.scale + PADDING,
+            (self.max_y - max_y) * self.scale + PADDING,
+            (max_x - min_x) * self.scale,
+            (max_y - min_y) * self.scale,
+        ]
+    }
+}
+
+fn coordinates<P>(point: &P) -> [f64; 2]
+where
+    P: Point,
+    P::Scalar: ToPrimitive,
+{
+    let coordinate = |index| {
+        if index < P::DIMENSIONS {
+            point.nth(index).to_f64().unwrap_or(0.0)
+        } else {
+            0.0
+        }
+    };
+    [coordinate(0), coordinate(1)]
+}
+
+/// Returns the stroke color of nodes at the given depth.
+fn depth_color(depth: usize) -> String {
+    let mut color = String::new();
+    let _ = write!(color, "hsl({}, 70%, 40%)", (depth * 137) % 360);
+    color
+}
+
+struct Renderer<'a, W, Function> {
+    out: &'a mut W,
+    transform: Transform,
+    /// The query whose visited nodes are highlighted, if any.
+    function: Option<Function>,
+}
+
+impl<'a, W, Function> Renderer<'a, W, Function>
+where
+    W: Write,
+{
+    /// Returns `true` if the query visits a node whose parent is visited as well.
+    fn visits<T>(&self, parent_visited: bool, envelope: &T::Envelope, depth: usize) -> bool
+    where
+        T: RTreeObject,
+        Function: SelectionFunction<T>,
+    {
+        parent_visited
+            && self.function.as_ref().map_or(false, |function| {
+                function.should_unpack_parent_at_depth(envelope, depth)
+            })
+    }
+
+    fn render_node<T, P>(
+        &mut self,
+        node: &ParentNode<T>,
+        depth: usize,
+        visited: bool,
+    ) -> fmt::Result
+    where
+        T: RTreeObject<Envelope = AABB<P>>,
+        P: Point,
+        P::Scalar: ToPrimitive,
+        Function: SelectionFunction<T>,
+    {
+        for child in &node.children {
+            match child {
+                RTreeNode::Parent(ref data) => {
+                    let child_visited = self.visits(visited, &data.envelope, depth + 1);
+                    self.render_node(data, depth + 1, child_visited)?;
+                }
+                RTreeNode::Leaf(ref t) => {
+                    let selected = visited
+                        && self
+                            .function
+                            .as_ref()
+                            .map_or(false, |function| function.should_unpack_leaf(t));
+                    self.render_leaf(&t.envelope(), selected)?;
+                }
+            }
+        }
+        // Parents are drawn after their children to keep their outline visible.
+        let [x, y, width, height] = self.transform.rectangle(&node.envelope);
+        writeln!(
+            self.out,
+            "  <rect x=\"{:.2}\" y=\"{:.2}\" width=\"{:.2}\" height=\"{:.2}\" fill=\"none\" stroke=\"{}\" stroke-width=\"{}\"/>",
+            x,
+            y,
+            width,
+            height,
+            depth_color(depth),
+            if visited { 3 } else { 1 }
+        )
+    }
+
+    fn render_leaf<P>(&mut self, envelope: &AABB<P>, selected: bool) -> fmt::Result
+    where
+        P: Point,
+        P::Scalar: ToPrimitive,
+    {
+        let fill = if selected { "red" } else { "none" };
+        let [x, y, width, height] = self.transform.rectangle(envelope);
+        if width == 0.0 || height == 0.0 {
+            writeln!(
+                self.out,
+                "  <circle cx=\"{:.2}\" cy=\"{:.2}\" r=\"2\" fill=\"{}\" stroke=\"black\"/>",
+                x + width / 2.0,
+                y + height / 2.0,
+                if selected { "red" } else { "black" }
+            )
+        } else {
+            writeln!(
+                self.out,
+                "  <rect x=\"{:.2}\" y=\"{:.2}\" width=\"{:.2}\" height=\"{:.2}\" fill=\"{}\" stroke=\"black\" stroke-width=\"0.5\"/>",
+                x, y, width, height, fill
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_utilities::{create_random_points, create_random_rectangles, SEED_1};
+    use crate::{RTree, SelectInEnvelopeFuncIntersecting, AABB};
+
+    #[test]
+    fn test_write_svg() {
+        let points = RTree::bulk_load(create_random_points(500, SEED_1));
+        let svg = points.to_svg();
+        assert_eq!(svg.matches("<circle").count(), 500);
+        assert!(svg.trim_end().ends_with("</svg>"));
+
+        let rectangles = RTree::bulk_load(create_random_rectangles(200, SEED_1));
+        let query =
+            SelectInEnvelopeFuncIntersecting::new(AABB::from_corners([0.0, 0.0], [0.2, 0.2]));
+        let expected = rectangles
+            .locate_in_envelope_intersecting(&AABB::from_corners([0.0, 0.0], [0.2, 0.2]))
+            .count();
+        let mut svg = String::new();
+        rectangles.write_svg_with_query(&mut svg, query).unwrap();
+        assert_eq!(svg.matches("fill=\"red\"").count(), expected);
+    }
+}
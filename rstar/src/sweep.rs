@@ -0,0 +1,407 @@
+use crate::envelope::Envelope;
+use crate::node::{ParentNode, RTreeNode};
+use crate::params::RTreeParams;
+use crate::point::Point;
+use crate::{RTree, RTreeObject, SelectIntersectingSwept, AABB};
+
+use alloc::collections::BinaryHeap;
+use core::cmp::Ordering;
+use num_traits::Float;
+use smallvec::SmallVec;
+
+impl<T, P, Params> RTree<T, Params>
+where
+    T: RTreeObject<Envelope = AABB<P>>,
+    P: Point,
+    P::Scalar: Float,
+    Params: RTreeParams,
+{
+    /// Returns all elements whose envelope intersects the volume swept by `aabb` while moving
+    /// by `displacement`.
+    ///
+    /// Every element is returned together with its time of impact: the smallest `t` in `[0, 1]`
+    /// for which `aabb` translated by `t * displacement` touches the element's envelope.
+    /// Elements already intersecting `aabb` have a time of impact of zero. The elements are
+    /// returned in any order.
+    ///
+    /// # Example
+    /// ```
+    /// use rstar::primitives::Rectangle;
+    /// use rstar::{RTree, AABB};
+    ///
+    /// let tree = RTree::bulk_load(vec![
+    ///     Rectangle::from_corners([2.0, 0.0], [3.0, 1.0]),
+    ///     Rectangle::from_corners([2.0, 3.0], [3.0, 4.0]),
+    /// ]);
+    /// let player = AABB::from_corners([0.0, 0.0], [1.0, 1.0]);
+    /// let hits: Vec<_> = tree.locate_intersecting_swept(&player, &[4.0, 0.0]).collect();
+    /// assert_eq!(hits, vec![(&Rectangle::from_corners([2.0, 0.0], [3.0, 1.0]), 0.25)]);
+    /// ```
+    pub fn locate_intersecting_swept<'a>(
+        &'a self,
+        aabb: &AABB<P>,
+        displacement: &P,
+    ) -> impl Iterator<Item = (&'a T, P::Scalar)> + 'a
+    where
+        P: 'a,
+    {
+        let selection = SelectIntersectingSwept::new(aabb.clone(), displacement.clone());
+        let (aabb, displacement) = (aabb.clone(), displacement.clone());
+        self.locate_with_selection_function(selection)
+            .filter_map(move |element| {
+                element
+                    .envelope()
+                    .sweep_intersection(&aabb, &displacement)
+                    .map(|time| (element, time))
+            })
+    }
+
+    /// Sweeps a sphere along a segment and returns all elements it hits, ordered by the hit
+    /// parameter.
+    ///
+    /// The sphere with radius `radius` moves from `from` to `to`. Every element whose envelope
+    /// is touched by the sphere is returned together with the smallest `t` in `[0, 1]` for
+    /// which the sphere centered at `from + t * (to - from)` touches it. In two dimensions,
+    /// the sphere is a disc. The iterator is lazy: taking only the first hits avoids visiting
+    /// the remaining tree.
+    ///
+    /// # Example
+    /// ```
+    /// use rstar::primitives::Rectangle;
+    /// use rstar::RTree;
+    ///
+    /// let tree = RTree::bulk_load(vec![
+    ///     Rectangle::from_corners([6.0, -1.0], [7.0, 1.0]),
+    ///     Rectangle::from_corners([3.0, 0.0], [4.0, 2.0]),
+    ///     Rectangle::from_corners([3.0, 3.0], [4.0, 4.0]),
+    /// ]);
+    /// let hits: Vec<_> = tree
+    ///     .sweep_sphere_iter(&[0.0, 0.0], &[10.0, 0.0], 1.0)
+    ///     .map(|(rectangle, t)| (rectangle.lower(), t))
+    ///     .collect();
+    /// assert_eq!(hits, vec![([3.0, 0.0], 0.2), ([6.0, -1.0], 0.5)]);
+    /// ```
+    pub fn sweep_sphere_iter<'a>(
+        &'a self,
+        from: &P,
+        to: &P,
+        radius: P::Scalar,
+    ) -> impl Iterator<Item = (&'a T, P::Scalar)> + 'a
+    where
+        P: 'a,
+    {
+        let (from, to) = (from.clone(), to.clone());
+        let radius_2 = radius * radius;
+        OrderedHits::new(self.root(), move |envelope: &AABB<P>| {
+            envelope.segment_hit(&from, &to, radius_2)
+        })
+    }
+
+    /// Returns the first element hit by a ray and its hit parameter.
+    ///
+    /// The ray starts at `origin` and points in `direction`. The hit parameter of an element
+    /// is the smallest `t >= 0` for which `origin + t * direction` lies within the element's
+    /// envelope. Elements with a hit parameter larger than `max_t` are ignored, use infinity
+    /// for an unbounded ray.
+    ///
+    /// The search visits children in order of their hit parameter and skips all nodes that
+    /// are hit later than `max_t` or the best element found so far, which makes it much
+    /// faster than finding all elements along the ray.
+    ///
+    /// # Example
+    /// ```
+    /// use rstar::primitives::Rectangle;
+    /// use rstar::RTree;
+    ///
+    /// let tree = RTree::bulk_load(vec![
+    ///     Rectangle::from_corners([4.0, -1.0], [5.0, 1.0]),
+    ///     Rectangle::from_corners([2.0, -1.0], [3.0, 1.0]),
+    ///     Rectangle::from_corners([2.0, 2.0], [3.0, 3.0]),
+    /// ]);
+    /// let (hit, t) = tree.cast_ray_first(&[0.0, 0.0], &[1.0, 0.0], 10.0).unwrap();
+    /// assert_eq!((hit.lower(), t), ([2.0, -1.0], 2.0));
+    /// assert!(tree.cast_ray_first(&[0.0, 0.0], &[1.0, 0.0], 1.5).is_none());
+    /// ```
+    pub fn cast_ray_first(
+        &self,
+        origin: &P,
+        direction: &P,
+        max_t: P::Scalar,
+    ) -> Option<(&T, P::Scalar)> {
+        let mut best = None;
+        let mut max_t = max_t;
+        cast_ray_first_recursive(self.root(), origin, direction, &mut max_t, &mut best);
+        best.map(|element| (element, max_t))
+    }
+}
+
+fn cast_ray_first_recursive<'a, T, P>(
+    node: &'a ParentNode<T>,
+    origin: &P,
+    direction: &P,
+    max_t: &mut P::Scalar,
+    best: &mut Option<&'a T>,
+) where
+    T: RTreeObject<Envelope = AABB<P>>,
+    P: Point,
+    P::Scalar: Float,
+{
+    let mut hits: SmallVec<[_; 16]> = node
+        .children
+        .iter()
+        .filter_map(|child| {
+            let (near, _) = child.envelope().ray_intersection(origin, direction)?;
+            Some((near, child))
+        })
+        .filter(|(near, _)| *near <= *max_t)
+        .collect();
+    hits.sort_by(|l, r| l.0.partial_cmp(&r.0).unwrap_or(Ordering::Equal));
+    for (near, child) in hits {
+        if near > *max_t {
+            break;
+        }
+        match child {
+            RTreeNode::Leaf(ref t) => {
+                if best.is_none() || near < *max_t {
+                    *max_t = near;
+                    *best = Some(t);
+                }
+            }
+            RTreeNode::Parent(ref data) => {
+                cast_ray_first_recursive(data, origin, direction, max_t, best)
+            }
+        }
+    }
+}
+
+/// Traverses a tree best first and returns all elements hit by a query, ordered by their hit
+/// parameter.
+///
+/// `hit` must return the hit parameter of an envelope, or `None` if it is missed. The hit
+/// parameter of an envelope must not exceed the parameter of any envelope contained in it.
+struct OrderedHits<'a, T, F>
+where
+    T: RTreeObject,
+{
+    nodes: BinaryHeap<Hit<'a, T>>,
+    hit: F,
+}
+
+struct Hit<'a, T>
+where
+    T: RTreeObject,
+{
+    node: &'a RTreeNode<T>,
+    parameter: <<T::Envelope as Envelope>::Point as Point>::Scalar,
+}
+
+impl<'a, T, F> OrderedHits<'a, T, F>
+where
+    T: RTreeObject,
+    F: FnMut(&T::Envelope) -> Option<<<T::Envelope as Envelope>::Point as Point>::Scalar>,
+{
+    fn new(root: &'a ParentNode<T>, hit: F) -> Self {
+        let mut result = OrderedHits {
+            nodes: BinaryHeap::new(),
+            hit,
+        };
+        if (result.hit)(&root.envelope).is_some() {
+            result.extend(&root.children);
+        }
+        result
+    }
+
+    fn extend(&mut self, children: &'a [RTreeNode<T>]) {
+        for node in children {
+            if let Some(parameter) = (self.hit)(&node.envelope()) {
+                self.nodes.push(Hit { node, parameter });
+            }
+        }
+    }
+}
+
+impl<'a, T, F> Iterator for OrderedHits<'a, T, F>
+where
+    T: RTreeObject,
+    F: FnMut(&T::Envelope) -> Option<<<T::Envelope as Envelope>::Point as Point>::Scalar>,
+{
+    type Item = (&'a T, <<T::Envelope as Envelope>::Point as Point>::Scalar);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(current) = self.nodes.pop() {
+            match current.node {
+                RTreeNode::Parent(ref data) => self.extend(&data.children),
+                RTreeNode::Leaf(ref t) => return Some((t, current.parameter)),
+            }
+        }
+        None
+    }
+}
+
+impl<'a, T> PartialEq for Hit<'a, T>
+where
+    T: RTreeObject,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.parameter == other.parameter
+    }
+}
+
+impl<'a, T> Eq for Hit<'a, T> where T: RTreeObject {}
+
+impl<'a, T> PartialOrd for Hit<'a, T>
+where
+    T: RTreeObject,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<'a, T> Ord for Hit<'a, T>
+where
+    T: RTreeObject,
+{
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Inverse comparison creates a min heap
+        other
+            .parameter
+            .partial_cmp(&self.parameter)
+            .unwrap_or(Ordering::Equal)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::primitives::Rectangle;
+    use crate::test_utilities::{create_random_points, create_random_rectangles, SEED_1, SEED_2};
+    use crate::{Envelope, PointDistance, RTree, RTreeObject, AABB};
+
+    #[test]
+    fn test_locate_intersecting_swept() {
+        let rectangles: Vec<Rectangle<[f64; 2]>> = create_random_rectangles(300, SEED_1);
+        let tree = RTree::bulk_load(rectangles.clone());
+        let points = create_random_points(40, SEED_2);
+
+        for pair in points.chunks_exact(2) {
+            let [x, y] = pair[0];
+            let aabb = AABB::from_corners([x, y], [x + 0.05, y + 0.02]);
+            let displacement = [pair[1][0] - x, pair[1][1] - y];
+            let mut hits: Vec<_> = tree
+                .locate_intersecting_swept(&aabb, &displacement)
+                .collect();
+            hits.sort_by(|l, r| l.1.partial_cmp(&r.1).unwrap());
+
+            // Sample the sweep, every sampled intersection must be reported no later than
+            // the sample.
+            let steps = 200;
+            for step in 0..=steps {
+                let t = step as f64 / steps as f64;
+                let moved = AABB::from_corners(
+                    [x + t * displacement[0], y + t * displacement[1]],
+                    [
+                        x + 0.05 + t * displacement[0],
+                        y + 0.02 + t * displacement[1],
+                    ],
+                );
+                for rectangle in &rectangles {
+                    if rectangle.envelope().intersects(&moved) {
+                        let (_, time) = hits
+                            .iter()
+                            .find(|(hit, _)| *hit == rectangle)
+                            .expect("Missed an intersection");
+                        assert!(*time <= t);
+                    }
+                }
+            }
+            for (rectangle, time) in hits {
+                assert!((0.0..=1.0).contains(&time));
+                let moved = AABB::from_corners(
+                    [x + time * displacement[0], y + time * displacement[1]],
+                    [
+                        x + 0.05 + time * displacement[0],
+                        y + 0.02 + time * displacement[1],
+                    ],
+                );
+                let grown = AABB::from_corners(
+                    [moved.lower()[0] - 1e-9, moved.lower()[1] - 1e-9],
+                    [moved.upper()[0] + 1e-9, moved.upper()[1] + 1e-9],
+                );
+                assert!(rectangle.envelope().intersects(&grown));
+            }
+        }
+    }
+
+    #[test]
+    fn test_sweep_sphere_iter() {
+        let rectangles: Vec<Rectangle<[f64; 2]>> = create_random_rectangles(300, SEED_1);
+        let tree = RTree::bulk_load(rectangles.clone());
+        let points = create_random_points(40, SEED_2);
+        let radius = 0.03;
+
+        for pair in points.chunks_exact(2) {
+            let (from, to) = (pair[0], pair[1]);
+            let hits: Vec<_> = tree.sweep_sphere_iter(&from, &to, radius).collect();
+            assert!(hits.windows(2).all(|window| window[0].1 <= window[1].1));
+
+            let at = |t: f64| {
+                [
+                    from[0] + t * (to[0] - from[0]),
+                    from[1] + t * (to[1] - from[1]),
+                ]
+            };
+            let steps = 200;
+            let mut expected = 0;
+            for rectangle in &rectangles {
+                let hit = hits.iter().find(|(hit, _)| *hit == rectangle);
+                let first_sample = (0..=steps)
+                    .map(|step| step as f64 / steps as f64)
+                    .find(|t| rectangle.envelope().distance_2(&at(*t)) <= radius * radius);
+                if let Some(sample) = first_sample {
+                    let (_, t) = hit.expect("Missed a hit");
+                    assert!(*t <= sample);
+                }
+                if let Some((_, t)) = hit {
+                    expected += 1;
+                    let distance_2 = rectangle.distance_2(&at(*t));
+                    assert!((distance_2.sqrt() - radius).abs() < 1e-9 || *t == 0.0);
+                }
+            }
+            assert_eq!(hits.len(), expected);
+        }
+    }
+
+    #[test]
+    fn test_cast_ray_first() {
+        let rectangles: Vec<Rectangle<[f64; 2]>> = create_random_rectangles(500, SEED_1);
+        let tree = RTree::bulk_load(rectangles.clone());
+        let points = create_random_points(100, SEED_2);
+
+        for pair in points.chunks_exact(2) {
+            let origin = [pair[0][0] * 2.0 - 0.5, pair[0][1] * 2.0 - 0.5];
+            let direction = [pair[1][0] - 0.5, pair[1][1] - 0.5];
+            for max_t in [0.1, 1.0, f64::INFINITY] {
+                let expected = rectangles
+                    .iter()
+                    .filter_map(|rectangle| {
+                        let (near, _) =
+                            rectangle.envelope().ray_intersection(&origin, &direction)?;
+                        Some(near)
+                    })
+                    .filter(|near| *near <= max_t)
+                    .fold(None, |min: Option<f64>, near| {
+                        Some(min.map_or(near, |min| min.min(near)))
+                    });
+                let found = tree.cast_ray_first(&origin, &direction, max_t);
+                assert_eq!(found.map(|(_, t)| t), expected);
+                if let Some((rectangle, t)) = found {
+                    let (near, _) = rectangle
+                        .envelope()
+                        .ray_intersection(&origin, &direction)
+                        .unwrap();
+                    assert_eq!(near, t);
+                }
+            }
+        }
+    }
+}
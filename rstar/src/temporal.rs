@@ -0,0 +1,218 @@
+use crate::params::RTreeParams;
+use crate::point::Point;
+use crate::primitives::{Timed, Timestamped};
+use crate::{RTree, RTreeNum, RTreeObject, AABB};
+
+use core::ops::RangeInclusive;
+use num_traits::FromPrimitive;
+#[cfg(feature = "std")]
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A point that can be extended by a time coordinate.
+///
+/// [Timed] objects store their envelope in the extended point type, with time as the last
+/// coordinate. This trait is implemented for arrays with up to eight dimensions.
+pub trait TemporalPoint: Point {
+    /// The point type with one additional dimension for time.
+    type WithTime: Point<Scalar = Self::Scalar>;
+
+    /// Returns this point with `time` appended as last coordinate.
+    fn with_time(&self, time: Self::Scalar) -> Self::WithTime {
+        Self::WithTime::generate(|index| {
+            if index < Self::DIMENSIONS {
+                self.nth(index)
+            } else {
+                time
+            }
+        })
+    }
+}
+
+macro_rules! implement_temporal_point_for_array {
+    ($($dimensions:expr),*) => {
+        $(
+            impl<S> TemporalPoint for [S; $dimensions]
+            where
+                S: crate::RTreeNum,
+            {
+                type WithTime = [S; $dimensions + 1];
+            }
+        )*
+    };
+}
+
+implement_temporal_point_for_array!(2, 3, 4, 5, 6, 7, 8);
+
+/// A point in time that can be stored in a [Timestamped] object.
+///
+/// Implemented for `i64`, e.g. milliseconds since an epoch, and for
+/// [SystemTime](std::time::SystemTime) with the `std` feature.
+pub trait Timestamp: Copy + Ord {
+    /// Converts this timestamp into a coordinate of the time axis.
+    ///
+    /// The conversion may lose precision but must be monotonic. Timestamps exceeding the
+    /// coordinate's range are clamped to its bounds.
+    fn to_coordinate<S>(self) -> S
+    where
+        S: RTreeNum + FromPrimitive;
+}
+
+impl Timestamp for i64 {
+    fn to_coordinate<S>(self) -> S
+    where
+        S: RTreeNum + FromPrimitive,
+    {
+        S::from_i64(self).unwrap_or_else(|| clamp_coordinate(self < 0))
+    }
+}
+
+/// Converts times to seconds relative to the unix epoch.
+#[cfg(feature = "std")]
+impl Timestamp for SystemTime {
+    fn to_coordinate<S>(self) -> S
+    where
+        S: RTreeNum + FromPrimitive,
+    {
+        let seconds = match self.duration_since(UNIX_EPOCH) {
+            Ok(duration) => duration.as_secs_f64(),
+            Err(error) => -error.duration().as_secs_f64(),
+        };
+        S::from_f64(seconds).unwrap_or_else(|| clamp_coordinate(seconds < 0.0))
+    }
+}
+
+fn clamp_coordinate<S>(negative: bool) -> S
+where
+    S: RTreeNum,
+{
+    if negative {
+        S::min_value()
+    } else {
+        S::max_value()
+    }
+}
+
+impl<T, P, Params> RTree<Timed<T>, Params>
+where
+    T: RTreeObject<Envelope = AABB<P>>,
+    P: TemporalPoint,
+    Params: RTreeParams,
+{
+    /// Returns all objects whose envelope intersects `envelope` at any time within `interval`.
+    ///
+    /// Both the objects' and the query's time intervals are closed, objects existing until
+    /// the start of the query interval are returned as well.
+    pub fn locate_in_envelope_during(
+        &self,
+        envelope: &AABB<P>,
+        interval: RangeInclusive<P::Scalar>,
+    ) -> impl Iterator<Item = &Timed<T>> + '_ {
+        let (start, end) = interval.into_inner();
+        let envelope = AABB::from_corners(
+            envelope.lower().with_time(start),
+            envelope.upper().with_time(end),
+        );
+        self.locate_in_envelope_intersecting(&envelope)
+    }
+
+    /// Returns all objects whose envelope intersects `envelope` at the given time.
+    pub fn locate_in_envelope_at(
+        &self,
+        envelope: &AABB<P>,
+        time: P::Scalar,
+    ) -> impl Iterator<Item = &Timed<T>> + '_ {
+        self.locate_in_envelope_during(envelope, time..=time)
+    }
+}
+
+impl<T, P, Time, Params> RTree<Timestamped<T, Time>, Params>
+where
+    T: RTreeObject<Envelope = AABB<P>>,
+    P: TemporalPoint,
+    P::Scalar: FromPrimitive,
+    Time: Timestamp,
+    Params: RTreeParams,
+{
+    /// Returns all objects whose envelope intersects `envelope` and whose timestamp lies
+    /// within `start` and `end`, both inclusive.
+    ///
+    /// Timestamps are compared exactly, precision lost when converting them to coordinates
+    /// does not affect the result.
+    pub fn locate_in_envelope_between(
+        &self,
+        envelope: &AABB<P>,
+        start: Time,
+        end: Time,
+    ) -> impl Iterator<Item = &Timestamped<T, Time>> + '_ {
+        let envelope = AABB::from_corners(
+            envelope.lower().with_time(start.to_coordinate()),
+            envelope.upper().with_time(end.to_coordinate()),
+        );
+        self.locate_in_envelope_intersecting(&envelope)
+            .filter(move |object| start <= object.timestamp() && object.timestamp() <= end)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Timestamp;
+    use crate::primitives::{Line, Timed, Timestamped};
+    use crate::test_utilities::{create_random_lines, create_random_points, SEED_1, SEED_2};
+    use crate::{Envelope, RTree, RTreeObject, AABB};
+
+    #[test]
+    fn test_locate_during() {
+        let timed: Vec<Timed<Line<[f64; 2]>>> = create_random_lines(500, SEED_1)
+            .into_iter()
+            .zip(create_random_points(500, SEED_2))
+            .map(|(line, [start, length])| Timed::new(line, start..=start + length * 0.2))
+            .collect();
+        let tree = RTree::bulk_load(timed.clone());
+
+        for [x, y] in create_random_points(20, SEED_1) {
+            let envelope = AABB::from_corners([x, y], [x + 0.2, y + 0.2]);
+            for (start, end) in [(0.0, 1.0), (0.2, 0.3), (x, x)] {
+                let expected = timed
+                    .iter()
+                    .filter(|t| {
+                        let interval = t.interval();
+                        t.geom().envelope().intersects(&envelope)
+                            && *interval.start() <= end
+                            && start <= *interval.end()
+                    })
+                    .count();
+                let found = tree.locate_in_envelope_during(&envelope, start..=end);
+                assert_eq!(found.count(), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_locate_between() {
+        let stamped: Vec<Timestamped<[f64; 2]>> = create_random_points(500, SEED_1)
+            .into_iter()
+            .enumerate()
+            .map(|(index, point)| Timestamped::new(point, index as i64 * 1_000_000_007))
+            .collect();
+        let tree = RTree::bulk_load(stamped.clone());
+
+        for [x, y] in create_random_points(20, SEED_2) {
+            let envelope = AABB::from_corners([x, y], [x + 0.3, y + 0.3]);
+            let start = (x * 500.0) as i64 * 1_000_000_007;
+            for end in [start, start + 1, start + 100_000_000_000, i64::MAX] {
+                let expected = stamped
+                    .iter()
+                    .filter(|s| {
+                        envelope.contains_point(s.geom())
+                            && start <= s.timestamp()
+                            && s.timestamp() <= end
+                    })
+                    .count();
+                let found = tree.locate_in_envelope_between(&envelope, start, end);
+                assert_eq!(found.count(), expected);
+            }
+        }
+        assert_eq!(i64::MIN.to_coordinate::<i32>(), i32::MIN);
+        assert_eq!(i64::MAX.to_coordinate::<i32>(), i32::MAX);
+    }
+}
@@ -1,14 +1,35 @@
+//! Reproducible random data used by this crate's own tests.
+//!
+//! Only available with the `test-utilities` feature. All functions are deterministic for a
+//! given seed, which allows benchmarks and tests of downstream crates to use the same data
+//! distributions as rstar's tests.
+//!
+//! # Example
+//! ```
+//! use rstar::test_utilities::{create_random_points, SEED_1};
+//! use rstar::RTree;
+//!
+//! let points = create_random_points(1000, SEED_1);
+//! assert_eq!(points, create_random_points(1000, SEED_1));
+//! let tree = RTree::bulk_load(points);
+//! assert_eq!(tree.size(), 1000);
+//! ```
 use crate::primitives::*;
 use crate::{Point, RTreeObject};
+use alloc::vec::Vec;
 use rand::distributions::Uniform;
 use rand::{Rng, SeedableRng};
 use rand_hc::Hc128Rng;
 
+/// A seed for the random number generator used by this module.
 pub type Seed = [u8; 32];
 
+/// The seed used for most of the data in this crate's tests.
 pub const SEED_1: &Seed = b"wPYxAkIiHcEmSBAxQFoXFrpYToCe1B71";
+/// A second seed, e.g. for query points independent of the elements of a tree.
 pub const SEED_2: &Seed = b"4KbTVjPT4DXSwWAsQM5dkWWywPKZRfCX";
 
+/// Creates points with integer coordinates uniformly distributed in `[-100_000, 100_000)`.
 pub fn create_random_integers<P: Point<Scalar = i32>>(num_points: usize, seed: &Seed) -> Vec<P> {
     let mut result = Vec::with_capacity(num_points);
     let mut rng = Hc128Rng::from_seed(*seed);
@@ -21,6 +42,7 @@ pub fn create_random_integers<P: Point<Scalar = i32>>(num_points: usize, seed: &
     result
 }
 
+/// Creates points uniformly distributed in the unit square `[0, 1)²`.
 pub fn create_random_points(num_points: usize, seed: &Seed) -> Vec<[f64; 2]> {
     let mut result = Vec::with_capacity(num_points);
     let mut rng = Hc128Rng::from_seed(*seed);
@@ -30,6 +52,9 @@ pub fn create_random_points(num_points: usize, seed: &Seed) -> Vec<[f64; 2]> {
     result
 }
 
+/// Creates diagonal lines starting in the unit square.
+///
+/// Lines get shorter the more lines are created, which keeps their overlap roughly constant.
 pub fn create_random_lines(num_lines: usize, seed: &Seed) -> Vec<Line<[f64; 2]>> {
     let mut result = Vec::with_capacity(num_lines);
     let mut rng = Hc128Rng::from_seed(*seed);
@@ -45,6 +70,7 @@ pub fn create_random_lines(num_lines: usize, seed: &Seed) -> Vec<Line<[f64; 2]>>
     result
 }
 
+/// Creates rectangles that are the envelopes of [create_random_lines].
 pub fn create_random_rectangles(num_rectangles: usize, seed: &Seed) -> Vec<Rectangle<[f64; 2]>> {
     let lines = create_random_lines(num_rectangles, seed);
     lines.iter().map(|line| line.envelope().into()).collect()
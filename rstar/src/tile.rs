@@ -0,0 +1,278 @@
+use crate::algorithm::iterators::LocateInEnvelopeIntersecting;
+use crate::params::RTreeParams;
+use crate::{Envelope, Point, RTree, RTreeObject, AABB};
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::f64::consts::PI;
+use num_traits::{Float, NumCast, ToPrimitive};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The maximum latitude covered by web mercator tiles, in degrees.
+const MAX_LATITUDE: f64 = 85.051_128_779_806_59;
+
+/// A web mercator map tile in the XYZ scheme used by most slippy map and vector tile services.
+///
+/// Tile `(0, 0)` of every zoom level is located in the north west corner of the map. Tile
+/// coordinates are expected to be given in longitude / latitude degrees, the longitude
+/// being the first axis.
+///
+/// # Example
+/// ```
+/// use rstar::Tile;
+///
+/// let tile = Tile::new(3, 4, 2);
+/// assert_eq!(tile.quadkey(), "120");
+/// assert_eq!(Tile::from_quadkey("120"), Some(tile));
+/// assert_eq!(tile.parent(), Some(Tile::new(2, 2, 1)));
+/// assert_eq!(Tile::at(3, &[10.0, 50.0]), tile);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Tile {
+    z: u8,
+    x: u32,
+    y: u32,
+}
+
+impl Tile {
+    /// The maximum supported zoom level.
+    pub const MAX_ZOOM: u8 = 31;
+
+    /// Creates a new tile.
+    ///
+    /// # Panics
+    /// Panics if `z` exceeds [Tile::MAX_ZOOM] or if `x` or `y` are not valid tile
+    /// coordinates of zoom level `z`.
+    pub fn new(z: u8, x: u32, y: u32) -> Self {
+        assert!(z <= Self::MAX_ZOOM, "Zoom level must not exceed 31");
+        assert!(
+            x < 1 << z && y < 1 << z,
+            "Tile coordinates out of range for zoom level {}",
+            z
+        );
+        Tile { z, x, y }
+    }
+
+    /// Returns the tile of zoom level `z` containing a longitude / latitude point.
+    ///
+    /// Points outside of the web mercator bounds are clamped to the closest tile.
+    pub fn at<P>(z: u8, point: &P) -> Self
+    where
+        P: Point,
+        P::Scalar: Float,
+    {
+        let (x, y) = tile_coordinates(z, point);
+        Tile::new(z, x, y)
+    }
+
+    /// Returns the tile's zoom level.
+    pub fn z(&self) -> u8 {
+        self.z
+    }
+
+    /// Returns the tile's column, counted from west to east.
+    pub fn x(&self) -> u32 {
+        self.x
+    }
+
+    /// Returns the tile's row, counted from north to south.
+    pub fn y(&self) -> u32 {
+        self.y
+    }
+
+    /// Returns the tile of the next lower zoom level containing this tile.
+    pub fn parent(&self) -> Option<Self> {
+        if self.z == 0 {
+            None
+        } else {
+            Some(Tile::new(self.z - 1, self.x / 2, self.y / 2))
+        }
+    }
+
+    /// Returns the tile's Bing Maps quadkey.
+    pub fn quadkey(&self) -> String {
+        (1..=self.z)
+            .rev()
+            .map(|level| {
+                let mask = 1 << (level - 1);
+                let digit = (self.x & mask != 0) as u8 + 2 * (self.y & mask != 0) as u8;
+                (b'0' + digit) as char
+            })
+            .collect()
+    }
+
+    /// Parses a Bing Maps quadkey. Returns `None` if the quadkey is invalid.
+    pub fn from_quadkey(quadkey: &str) -> Option<Self> {
+        if quadkey.len() > Self::MAX_ZOOM as usize {
+            return None;
+        }
+        let (mut x, mut y) = (0, 0);
+        for digit in quadkey.bytes() {
+            let digit = match digit {
+                b'0'..=b'3' => digit - b'0',
+                _ => return None,
+            };
+            x = 2 * x + (digit & 1) as u32;
+            y = 2 * y + (digit >> 1) as u32;
+        }
+        Some(Tile::new(quadkey.len() as u8, x, y))
+    }
+
+    /// Returns the tile's bounds in longitude / latitude degrees.
+    pub fn envelope<P>(&self) -> AABB<P>
+    where
+        P: Point,
+        P::Scalar: Float,
+    {
+        assert_eq!(P::DIMENSIONS, 2, "Tiles are two dimensional");
+        let n = (1u64 << self.z) as f64;
+        let longitude = |x: u32| x as f64 / n * 360.0 - 180.0;
+        let latitude =
+            |y: u32| Float::atan(Float::sinh(PI * (1.0 - 2.0 * y as f64 / n))).to_degrees();
+        let corner = |lon: f64, lat: f64| {
+            P::generate(|axis| {
+                let value = if axis == 0 { lon } else { lat };
+                <P::Scalar as NumCast>::from(value).unwrap()
+            })
+        };
+        AABB::from_corners(
+            corner(longitude(self.x), latitude(self.y + 1)),
+            corner(longitude(self.x + 1), latitude(self.y)),
+        )
+    }
+}
+
+/// Returns the (clamped) tile coordinates of a longitude / latitude point.
+fn tile_coordinates<P>(z: u8, point: &P) -> (u32, u32)
+where
+    P: Point,
+    P::Scalar: Float,
+{
+    assert!(z <= Tile::MAX_ZOOM, "Zoom level must not exceed 31");
+    let n = (1u64 << z) as f64;
+    let lon = point.nth(0).to_f64().unwrap_or(0.0);
+    let lat = point
+        .nth(1)
+        .to_f64()
+        .unwrap_or(0.0)
+        .clamp(-MAX_LATITUDE, MAX_LATITUDE)
+        .to_radians();
+    let x = (lon + 180.0) / 360.0 * n;
+    let y = (1.0 - Float::ln(Float::tan(lat) + 1.0 / Float::cos(lat)) / PI) / 2.0 * n;
+    let clamp = |value: f64| value.clamp(0.0, n - 1.0) as u32;
+    (clamp(x), clamp(y))
+}
+
+impl<T, P, Params> RTree<T, Params>
+where
+    T: RTreeObject<Envelope = AABB<P>>,
+    P: Point,
+    P::Scalar: Float,
+    Params: RTreeParams,
+{
+    /// Returns all elements intersecting a web mercator tile.
+    ///
+    /// The tree's elements must use longitude / latitude coordinates, see [Tile].
+    ///
+    /// # Example
+    /// ```
+    /// use rstar::RTree;
+    ///
+    /// let tree = RTree::bulk_load(vec![[13.4, 52.5], [-74.0, 40.7]]);
+    /// // Tile 1 / 1 / 0 covers the north eastern quarter of the map
+    /// assert_eq!(tree.locate_in_tile(1, 1, 0).collect::<Vec<_>>(), vec![&[13.4, 52.5]]);
+    /// ```
+    pub fn locate_in_tile(&self, z: u8, x: u32, y: u32) -> LocateInEnvelopeIntersecting<'_, T> {
+        self.locate_in_envelope_intersecting(&Tile::new(z, x, y).envelope())
+    }
+
+    /// Distributes all elements of the tree into the tiles of zoom level `z`.
+    ///
+    /// Elements whose envelope spans several tiles are added to each of them. Tiles that
+    /// would not contain any element are omitted.
+    ///
+    /// # Example
+    /// ```
+    /// use rstar::{RTree, Tile};
+    ///
+    /// let tree = RTree::bulk_load(vec![[13.4, 52.5], [13.5, 52.6], [-74.0, 40.7]]);
+    /// let buckets = tree.partition_into_tiles(4);
+    /// assert_eq!(buckets.len(), 2);
+    /// assert_eq!(buckets[&Tile::new(4, 8, 5)].len(), 2);
+    /// ```
+    pub fn partition_into_tiles(&self, z: u8) -> BTreeMap<Tile, Vec<&T>> {
+        let mut result = BTreeMap::<Tile, Vec<&T>>::new();
+        for element in self.iter() {
+            let envelope = element.envelope();
+            let (min_x, max_y) = tile_coordinates(z, &envelope.lower());
+            let (max_x, min_y) = tile_coordinates(z, &envelope.upper());
+            for x in min_x..=max_x {
+                for y in min_y..=max_y {
+                    let tile = Tile { z, x, y };
+                    if envelope.intersects(&tile.envelope()) {
+                        result.entry(tile).or_default().push(element);
+                    }
+                }
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Tile;
+    use crate::primitives::Rectangle;
+    use crate::test_utilities::{create_random_points, SEED_1, SEED_2};
+    use crate::{Envelope, RTree, AABB};
+
+    #[test]
+    fn test_tile_coordinates() {
+        assert_eq!(Tile::at(0, &[123.0, -45.0]), Tile::new(0, 0, 0));
+        assert_eq!(Tile::at(1, &[-180.0, 90.0]), Tile::new(1, 0, 0));
+        assert_eq!(Tile::at(1, &[180.0, -90.0]), Tile::new(1, 1, 1));
+        assert_eq!(Tile::at(10, &[0.0, 0.0]), Tile::new(10, 512, 512));
+
+        let envelope: AABB<[f64; 2]> = Tile::new(1, 1, 0).envelope();
+        assert_eq!(envelope.lower(), [0.0, 0.0]);
+        assert!((envelope.upper()[1] - super::MAX_LATITUDE).abs() < 1e-9);
+
+        for z in 0..8 {
+            let tile = Tile::at(z, &[7.5, 47.3]);
+            assert_eq!(Tile::from_quadkey(&tile.quadkey()), Some(tile));
+            assert!(tile.envelope::<[f64; 2]>().contains_point(&[7.5, 47.3]));
+        }
+        assert_eq!(Tile::from_quadkey(""), Some(Tile::new(0, 0, 0)));
+        assert_eq!(Tile::from_quadkey("014"), None);
+    }
+
+    #[test]
+    fn test_partition_into_tiles() {
+        let rectangles: Vec<_> = create_random_points(500, SEED_1)
+            .into_iter()
+            .zip(create_random_points(500, SEED_2))
+            .map(|([x, y], [width, height])| {
+                let lower = [x * 350.0 - 180.0, y * 150.0 - 80.0];
+                Rectangle::from_corners(lower, [lower[0] + width * 10.0, lower[1] + height * 10.0])
+            })
+            .collect();
+        let tree = RTree::bulk_load(rectangles);
+        let buckets = tree.partition_into_tiles(3);
+        let mut total = 0;
+        for x in 0..8 {
+            for y in 0..8 {
+                let located = tree.locate_in_tile(3, x, y).count();
+                let bucket = buckets
+                    .get(&Tile::new(3, x, y))
+                    .map(|bucket| bucket.len())
+                    .unwrap_or(0);
+                assert_eq!(located, bucket);
+                total += bucket;
+            }
+        }
+        assert!(total >= tree.size());
+    }
+}
@@ -0,0 +1,270 @@
+use crate::algorithm::selection_functions::*;
+use crate::node::{ParentNode, RTreeNode};
+use crate::params::RTreeParams;
+use crate::point::Point;
+use crate::{RTree, RTreeObject, AABB};
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{self, Write};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Whether a step of a [QueryTrace] inspected a parent node or an element.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TraceStepKind {
+    /// A parent node's envelope was tested.
+    Node,
+    /// An element was tested.
+    Leaf,
+}
+
+/// A single decision made by a query, see [QueryTrace].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TraceStep<E> {
+    /// Whether a node or an element was tested.
+    pub kind: TraceStepKind,
+    /// The depth of the tested node or element, the root has depth zero.
+    pub depth: usize,
+    /// The envelope of the tested node or element.
+    pub envelope: E,
+    /// The number of elements within the tested node, `1` for elements.
+    pub size: usize,
+    /// `true` if a node was unpacked or an element was returned, `false` if it was pruned.
+    pub accepted: bool,
+}
+
+/// Records every node and element inspected by a single query in the order of inspection.
+///
+/// Returned by the `*_traced` query variants of [RTree], which are available if the `trace`
+/// feature is enabled. Where [QueryStats](crate::QueryStats) only tell how much work a query
+/// performed, a trace tells which nodes caused it: nodes that are unpacked without
+/// contributing any results usually point to large or overlapping envelopes.
+///
+/// # Example
+/// ```
+/// use rstar::{RTree, AABB};
+///
+/// let tree = RTree::bulk_load((0..1000).map(|i| [i as f64, 0.0]).collect());
+/// let envelope = AABB::from_corners([0.0, 0.0], [9.0, 0.0]);
+/// let (result, trace) = tree.locate_in_envelope_intersecting_traced(&envelope);
+/// assert_eq!(result.len(), 10);
+/// assert!(trace.leaves_tested() < 100);
+/// let json = trace.to_json();
+/// assert!(json.starts_with("["));
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct QueryTrace<E> {
+    steps: Vec<TraceStep<E>>,
+}
+
+impl<E> QueryTrace<E> {
+    /// Returns all recorded steps in the order of inspection.
+    pub fn steps(&self) -> &[TraceStep<E>] {
+        &self.steps
+    }
+
+    /// Consumes the trace and returns its steps.
+    pub fn into_steps(self) -> Vec<TraceStep<E>> {
+        self.steps
+    }
+
+    /// Returns the number of parent nodes whose envelope was tested, including the root.
+    pub fn nodes_visited(&self) -> usize {
+        self.count(TraceStepKind::Node)
+    }
+
+    /// Returns the number of elements that were tested individually.
+    pub fn leaves_tested(&self) -> usize {
+        self.count(TraceStepKind::Leaf)
+    }
+
+    fn count(&self, kind: TraceStepKind) -> usize {
+        self.steps.iter().filter(|step| step.kind == kind).count()
+    }
+}
+
+impl<P> QueryTrace<AABB<P>>
+where
+    P: Point,
+{
+    /// Writes the trace as JSON array with one object per step.
+    ///
+    /// Each step is written as
+    /// `{"kind":"node","depth":1,"size":6,"accepted":true,"lower":[0.0,0.0],"upper":[5.0,0.0]}`.
+    /// Coordinates are written with their `Debug` representation.
+    pub fn write_json<W: Write>(&self, out: &mut W) -> fmt::Result {
+        out.write_char('[')?;
+        for (index, step) in self.steps.iter().enumerate() {
+            if index > 0 {
+                out.write_char(',')?;
+            }
+            let kind = match step.kind {
+                TraceStepKind::Node => "node",
+                TraceStepKind::Leaf => "leaf",
+            };
+            write!(
+                out,
+                "{{\"kind\":\"{}\",\"depth\":{},\"size\":{},\"accepted\":{},\"lower\":",
+                kind, step.depth, step.size, step.accepted
+            )?;
+            write_point(out, &step.envelope.lower())?;
+            out.write_str(",\"upper\":")?;
+            write_point(out, &step.envelope.upper())?;
+            out.write_char('}')?;
+        }
+        out.write_char(']')
+    }
+
+    /// Returns the trace as JSON array, see [QueryTrace::write_json].
+    pub fn to_json(&self) -> String {
+        let mut result = String::new();
+        self.write_json(&mut result)
+            .expect("Writing to a String cannot fail");
+        result
+    }
+}
+
+fn write_point<W: Write, P: Point>(out: &mut W, point: &P) -> fmt::Result {
+    out.write_char('[')?;
+    for index in 0..P::DIMENSIONS {
+        if index > 0 {
+            out.write_char(',')?;
+        }
+        write!(out, "{:?}", point.nth(index))?;
+    }
+    out.write_char(']')
+}
+
+pub(crate) fn locate_traced<'a, T, S>(
+    root: &'a ParentNode<T>,
+    selection_function: &S,
+) -> (Vec<&'a T>, QueryTrace<T::Envelope>)
+where
+    T: RTreeObject,
+    S: SelectionFunction<T>,
+{
+    let mut result = Vec::new();
+    let mut steps = Vec::new();
+    let unpack_root = selection_function.should_unpack_parent_at_depth(&root.envelope, 0);
+    steps.push(TraceStep {
+        kind: TraceStepKind::Node,
+        depth: 0,
+        envelope: root.envelope.clone(),
+        size: root.size(),
+        accepted: unpack_root,
+    });
+    let mut stack = Vec::new();
+    if unpack_root {
+        stack.push((root, 0));
+    }
+    while let Some((node, depth)) = stack.pop() {
+        for child in &node.children {
+            let step = match child {
+                RTreeNode::Leaf(ref t) => {
+                    let accepted = selection_function.should_unpack_leaf(t);
+                    if accepted {
+                        result.push(t);
+                    }
+                    TraceStep {
+                        kind: TraceStepKind::Leaf,
+                        depth: depth + 1,
+                        envelope: t.envelope(),
+                        size: 1,
+                        accepted,
+                    }
+                }
+                RTreeNode::Parent(ref data) => {
+                    let accepted =
+                        selection_function.should_unpack_parent_at_depth(&data.envelope, depth + 1);
+                    if accepted {
+                        stack.push((data, depth + 1));
+                    }
+                    TraceStep {
+                        kind: TraceStepKind::Node,
+                        depth: depth + 1,
+                        envelope: data.envelope.clone(),
+                        size: data.size(),
+                        accepted,
+                    }
+                }
+            };
+            steps.push(step);
+        }
+    }
+    (result, QueryTrace { steps })
+}
+
+impl<T, Params> RTree<T, Params>
+where
+    T: RTreeObject,
+    Params: RTreeParams,
+{
+    /// Variant of [RTree::locate_with_selection_function] that also returns a [QueryTrace].
+    pub fn locate_with_selection_function_traced<S: SelectionFunction<T>>(
+        &self,
+        selection_function: S,
+    ) -> (Vec<&T>, QueryTrace<T::Envelope>) {
+        locate_traced(self.root(), &selection_function)
+    }
+
+    /// Variant of [RTree::locate_in_envelope] that also returns a [QueryTrace].
+    pub fn locate_in_envelope_traced(
+        &self,
+        envelope: &T::Envelope,
+    ) -> (Vec<&T>, QueryTrace<T::Envelope>) {
+        self.locate_with_selection_function_traced(SelectInEnvelopeFunction::new(envelope.clone()))
+    }
+
+    /// Variant of [RTree::locate_in_envelope_intersecting] that also returns a [QueryTrace].
+    pub fn locate_in_envelope_intersecting_traced(
+        &self,
+        envelope: &T::Envelope,
+    ) -> (Vec<&T>, QueryTrace<T::Envelope>) {
+        self.locate_with_selection_function_traced(SelectInEnvelopeFuncIntersecting::new(
+            envelope.clone(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::TraceStepKind;
+    use crate::test_utilities::{create_random_rectangles, SEED_1};
+    use crate::{RTree, AABB};
+
+    #[test]
+    fn test_locate_traced() {
+        let tree = RTree::bulk_load(create_random_rectangles(1000, SEED_1));
+        let envelope = AABB::from_corners([0.2, 0.2], [0.4, 0.4]);
+        let (result, trace) = tree.locate_in_envelope_intersecting_traced(&envelope);
+        assert_eq!(
+            result.len(),
+            tree.locate_in_envelope_intersecting(&envelope).count()
+        );
+
+        let steps = trace.steps();
+        assert_eq!(steps[0].depth, 0);
+        assert_eq!(steps[0].size, 1000);
+        let accepted_leaves = steps
+            .iter()
+            .filter(|step| step.kind == TraceStepKind::Leaf && step.accepted)
+            .count();
+        assert_eq!(accepted_leaves, result.len());
+        // Every element is either returned or part of a pruned node or element.
+        let pruned: usize = steps
+            .iter()
+            .filter(|step| !step.accepted)
+            .map(|step| step.size)
+            .sum();
+        assert_eq!(pruned + accepted_leaves, 1000);
+
+        let json = trace.to_json();
+        assert_eq!(json.matches("\"kind\"").count(), steps.len());
+        assert!(json.ends_with("]}]"));
+    }
+}
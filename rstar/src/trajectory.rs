@@ -0,0 +1,149 @@
+use crate::algorithm::selection_functions::SelectionFunction;
+use crate::params::RTreeParams;
+use crate::point::Point;
+use crate::primitives::Polyline;
+use crate::{RTree, RTreeObject, AABB};
+
+impl<P, Params> RTree<Polyline<P>, Params>
+where
+    P: Point,
+    Params: RTreeParams,
+{
+    /// Returns all trajectories whose discrete Hausdorff distance to a query trajectory is at
+    /// most a given distance.
+    ///
+    /// The distance is given squared, see [Polyline::discrete_hausdorff_distance_2]. As the
+    /// Hausdorff distance never exceeds the discrete Fréchet distance, the result contains all
+    /// trajectories within the given Fréchet distance as well. Refine it with
+    /// [Polyline::discrete_frechet_distance_2] if the direction of travel matters.
+    ///
+    /// Every vertex of the query must be close to a matching trajectory, so only nodes close
+    /// to all of the query's vertices are visited. Trajectories are compared only if their
+    /// vertices are close to the query's envelope and vice versa.
+    ///
+    /// # Example
+    /// ```
+    /// use rstar::primitives::Polyline;
+    /// use rstar::RTree;
+    ///
+    /// let trips = vec![
+    ///     Polyline::new(vec![[0.0, 0.0], [5.0, 0.0], [10.0, 0.0]]),
+    ///     Polyline::new(vec![[10.0, 0.2], [5.0, 0.2], [0.0, 0.2]]),
+    ///     Polyline::new(vec![[0.0, 0.0], [0.0, 10.0]]),
+    /// ];
+    /// let tree = RTree::bulk_load(trips);
+    /// let query = Polyline::new(vec![[0.0, 0.1], [5.0, 0.1], [10.0, 0.1]]);
+    /// let similar: Vec<_> = tree.locate_similar_trajectories(&query, 0.5 * 0.5).collect();
+    /// assert_eq!(similar.len(), 2);
+    /// // Only the first trip shares the direction of travel.
+    /// let same_direction = similar
+    ///     .iter()
+    ///     .filter(|trip| trip.discrete_frechet_distance_2(&query) <= 0.5 * 0.5)
+    ///     .count();
+    /// assert_eq!(same_direction, 1);
+    /// ```
+    pub fn locate_similar_trajectories<'a>(
+        &'a self,
+        query: &'a Polyline<P>,
+        max_squared_distance: P::Scalar,
+    ) -> impl Iterator<Item = &'a Polyline<P>> + 'a
+    where
+        P: 'a,
+    {
+        self.locate_with_selection_function(SelectSimilarTrajectories {
+            query,
+            max_squared_distance,
+        })
+    }
+}
+
+struct SelectSimilarTrajectories<'a, P>
+where
+    P: Point,
+{
+    query: &'a Polyline<P>,
+    max_squared_distance: P::Scalar,
+}
+
+impl<'a, P> SelectSimilarTrajectories<'a, P>
+where
+    P: Point,
+{
+    /// Returns `true` if all given vertices are close enough to an envelope.
+    fn all_close(&self, vertices: &[P], envelope: &AABB<P>) -> bool {
+        vertices
+            .iter()
+            .all(|vertex| envelope.distance_2(vertex) <= self.max_squared_distance)
+    }
+}
+
+impl<'a, P> SelectionFunction<Polyline<P>> for SelectSimilarTrajectories<'a, P>
+where
+    P: Point,
+{
+    fn should_unpack_parent(&self, envelope: &AABB<P>) -> bool {
+        self.all_close(self.query.vertices(), envelope)
+    }
+
+    fn should_unpack_leaf(&self, trajectory: &Polyline<P>) -> bool {
+        self.all_close(self.query.vertices(), &trajectory.envelope())
+            && self.all_close(trajectory.vertices(), &self.query.envelope())
+            && trajectory.discrete_hausdorff_distance_2(self.query) <= self.max_squared_distance
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::primitives::Polyline;
+    use crate::test_utilities::{create_random_points, SEED_1, SEED_2};
+    use crate::RTree;
+
+    fn trajectories(count: usize, seed: &[u8; 32]) -> Vec<Polyline<[f64; 2]>> {
+        let steps = create_random_points(count * 5, seed);
+        steps
+            .chunks(5)
+            .map(|chunk| {
+                let start = chunk[0];
+                let vertices = chunk
+                    .iter()
+                    .scan(start, |position, step| {
+                        *position = [
+                            position[0] + 0.05 * (step[0] - 0.5),
+                            position[1] + 0.05 * (step[1] - 0.5),
+                        ];
+                        Some(*position)
+                    })
+                    .collect();
+                Polyline::new(vertices)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_locate_similar_trajectories() {
+        let trajectories = trajectories(1000, SEED_1);
+        let tree = RTree::bulk_load(trajectories.clone());
+        let max_squared_distance = 0.08 * 0.08;
+        let mut found_any = false;
+        for query in self::trajectories(50, SEED_2) {
+            let mut found: Vec<_> = tree
+                .locate_similar_trajectories(&query, max_squared_distance)
+                .collect();
+            let mut expected: Vec<_> = trajectories
+                .iter()
+                .filter(|t| t.discrete_hausdorff_distance_2(&query) <= max_squared_distance)
+                .collect();
+            found.sort_by(|l, r| l.vertices().partial_cmp(r.vertices()).unwrap());
+            expected.sort_by(|l, r| l.vertices().partial_cmp(r.vertices()).unwrap());
+            assert_eq!(found, expected);
+            found_any |= !found.is_empty();
+            for trajectory in &trajectories[..20] {
+                assert!(
+                    trajectory.discrete_hausdorff_distance_2(&query)
+                        <= trajectory.discrete_frechet_distance_2(&query)
+                );
+            }
+        }
+        assert!(found_any);
+    }
+}
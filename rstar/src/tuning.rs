@@ -0,0 +1,166 @@
+//! Helpers for choosing [RTreeParams] empirically.
+//!
+//! The best node sizes depend on the data and on the query mix. [tune_params] builds a tree
+//! for each of a small grid of parameter sets and measures how much work a representative
+//! set of queries causes.
+//!
+//! # Example
+//! ```
+//! use rstar::tuning::{tune_params, TuningQuery};
+//! use rstar::AABB;
+//!
+//! let sample: Vec<_> = (0..500).map(|i| [(i % 25) as f64, (i / 25) as f64]).collect();
+//! let queries = vec![
+//!     TuningQuery::NearestNeighbor([3.3, 4.2]),
+//!     TuningQuery::InEnvelope(AABB::from_corners([1.0, 1.0], [5.0, 5.0])),
+//! ];
+//! let candidates = tune_params(&sample, &queries);
+//! let best = &candidates[0];
+//! assert!(candidates.iter().all(|candidate| candidate.cost >= best.cost));
+//! // Use best.min_size, best.max_size and best.reinsertion_count to implement RTreeParams.
+//! ```
+use crate::algorithm::nearest_neighbor;
+use crate::algorithm::selection_functions::{
+    SelectInEnvelopeFuncIntersecting, SelectInEnvelopeFunction,
+};
+use crate::params::RTreeParams;
+use crate::stats::{locate_with_stats, QueryStats};
+use crate::{Envelope, PointDistance, RStarInsertionStrategy, RTree};
+use alloc::vec::Vec;
+
+/// A query used to evaluate parameter candidates, see [tune_params].
+#[derive(Clone, Debug)]
+pub enum TuningQuery<T>
+where
+    T: PointDistance,
+{
+    /// Corresponds to [RTree::locate_in_envelope].
+    InEnvelope(T::Envelope),
+    /// Corresponds to [RTree::locate_in_envelope_intersecting].
+    IntersectingEnvelope(T::Envelope),
+    /// Corresponds to [RTree::nearest_neighbor].
+    NearestNeighbor(<T::Envelope as Envelope>::Point),
+}
+
+/// A parameter set evaluated by [tune_params].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ParamsCandidate {
+    /// See [RTreeParams::MIN_SIZE].
+    pub min_size: usize,
+    /// See [RTreeParams::MAX_SIZE].
+    pub max_size: usize,
+    /// See [RTreeParams::REINSERTION_COUNT].
+    pub reinsertion_count: usize,
+    /// The number of node envelopes and elements tested while running all queries.
+    pub cost: usize,
+}
+
+/// Evaluates a grid of parameter sets and returns them ordered by increasing cost.
+///
+/// For every candidate, a tree is built by inserting the sample elements one by one. The
+/// cost of a candidate is the total number of node envelopes and elements that were tested
+/// while running `queries` on that tree. Unlike timings, this measure is deterministic and
+/// available without `std`; it does not account for cache effects of larger nodes though.
+pub fn tune_params<T>(sample: &[T], queries: &[TuningQuery<T>]) -> Vec<ParamsCandidate>
+where
+    T: PointDistance + Clone,
+{
+    let mut candidates = evaluate_candidates(sample, queries);
+    candidates.sort_by_key(|candidate| candidate.cost);
+    candidates
+}
+
+fn evaluate<T, Params>(sample: &[T], queries: &[TuningQuery<T>]) -> ParamsCandidate
+where
+    T: PointDistance + Clone,
+    Params: RTreeParams,
+{
+    let mut tree: RTree<T, Params> = RTree::new_with_params();
+    for element in sample {
+        tree.insert(element.clone());
+    }
+    let root = tree.root();
+    let cost = queries
+        .iter()
+        .map(|query| {
+            let stats: QueryStats = match query {
+                TuningQuery::InEnvelope(envelope) => {
+                    locate_with_stats(root, &SelectInEnvelopeFunction::new(envelope.clone())).1
+                }
+                TuningQuery::IntersectingEnvelope(envelope) => {
+                    let function = SelectInEnvelopeFuncIntersecting::new(envelope.clone());
+                    locate_with_stats(root, &function).1
+                }
+                TuningQuery::NearestNeighbor(point) => {
+                    nearest_neighbor::nearest_neighbor_with_stats(root, point).1
+                }
+            };
+            stats.nodes_visited + stats.leaves_tested
+        })
+        .sum();
+    ParamsCandidate {
+        min_size: Params::MIN_SIZE,
+        max_size: Params::MAX_SIZE,
+        reinsertion_count: Params::REINSERTION_COUNT,
+        cost,
+    }
+}
+
+macro_rules! candidates {
+    ($($name:ident: $min:expr, $max:expr, $reinsertion:expr;)*) => {
+        $(
+            enum $name {}
+
+            impl RTreeParams for $name {
+                const MIN_SIZE: usize = $min;
+                const MAX_SIZE: usize = $max;
+                const REINSERTION_COUNT: usize = $reinsertion;
+                type DefaultInsertionStrategy = RStarInsertionStrategy;
+            }
+        )*
+
+        fn evaluate_candidates<T>(sample: &[T], queries: &[TuningQuery<T>]) -> Vec<ParamsCandidate>
+        where
+            T: PointDistance + Clone,
+        {
+            alloc::vec![$(evaluate::<T, $name>(sample, queries)),*]
+        }
+    };
+}
+
+candidates! {
+    Small: 3, 6, 0;
+    SmallReinserting: 3, 6, 2;
+    Medium: 4, 12, 0;
+    MediumReinserting: 4, 12, 4;
+    Large: 8, 24, 0;
+    LargeReinserting: 8, 24, 8;
+    Huge: 16, 48, 0;
+    HugeReinserting: 16, 48, 16;
+}
+
+#[cfg(test)]
+mod test {
+    use super::{tune_params, TuningQuery};
+    use crate::test_utilities::{create_random_points, SEED_1, SEED_2};
+    use crate::AABB;
+
+    #[test]
+    fn test_tune_params() {
+        let sample = create_random_points(300, SEED_1);
+        let mut queries: Vec<_> = create_random_points(10, SEED_2)
+            .into_iter()
+            .map(TuningQuery::NearestNeighbor)
+            .collect();
+        queries.push(TuningQuery::InEnvelope(AABB::from_corners(
+            [0.1, 0.1],
+            [0.3, 0.3],
+        )));
+        let candidates = tune_params(&sample, &queries);
+        assert_eq!(candidates.len(), 8);
+        assert!(candidates
+            .windows(2)
+            .all(|pair| pair[0].cost <= pair[1].cost));
+        assert!(candidates.iter().all(|candidate| candidate.cost > 0));
+    }
+}
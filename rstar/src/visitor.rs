@@ -0,0 +1,169 @@
+use crate::node::{ParentNode, RTreeNode};
+use crate::object::RTreeObject;
+use crate::params::RTreeParams;
+use crate::RTree;
+
+use core::ops::ControlFlow;
+
+/// Callbacks for a depth-first traversal of a tree, see [RTree::accept].
+///
+/// Every node is passed to [TreeVisitor::visit_node] before its children are visited. The
+/// visitor decides whether the node's subtree should be visited at all, which allows to prune
+/// whole subtrees based on their envelope, size or on state accumulated so far. Returning
+/// `ControlFlow::Break` from either callback stops the traversal immediately.
+///
+/// # Example
+/// Counting the elements within an envelope without visiting them one by one:
+/// ```
+/// use core::ops::ControlFlow;
+/// use rstar::{Envelope, ParentNode, RTree, TreeVisitor, AABB};
+///
+/// struct CountInEnvelope {
+///     envelope: AABB<[f64; 2]>,
+///     count: usize,
+/// }
+///
+/// impl TreeVisitor<[f64; 2]> for CountInEnvelope {
+///     type Break = ();
+///
+///     fn visit_node(&mut self, node: &ParentNode<[f64; 2]>) -> ControlFlow<(), bool> {
+///         if self.envelope.contains_envelope(&node.envelope()) {
+///             // All elements are contained, no need to look at them.
+///             self.count += node.size();
+///             ControlFlow::Continue(false)
+///         } else {
+///             ControlFlow::Continue(self.envelope.intersects(&node.envelope()))
+///         }
+///     }
+///
+///     fn visit_leaf(&mut self, point: &[f64; 2]) -> ControlFlow<()> {
+///         if self.envelope.contains_point(point) {
+///             self.count += 1;
+///         }
+///         ControlFlow::Continue(())
+///     }
+/// }
+///
+/// let tree = RTree::bulk_load((0..100).map(|i| [i as f64, 0.0]).collect());
+/// let mut visitor = CountInEnvelope {
+///     envelope: AABB::from_corners([10.0, -1.0], [29.5, 1.0]),
+///     count: 0,
+/// };
+/// tree.accept(&mut visitor);
+/// assert_eq!(visitor.count, 20);
+/// ```
+pub trait TreeVisitor<T>
+where
+    T: RTreeObject,
+{
+    /// The value that stops the traversal, returned by [RTree::accept].
+    type Break;
+
+    /// Called for every visited node, including the root.
+    ///
+    /// Returns `ControlFlow::Continue(true)` to visit the node's children and
+    /// `ControlFlow::Continue(false)` to skip them. By default, all children are visited.
+    fn visit_node(&mut self, node: &ParentNode<T>) -> ControlFlow<Self::Break, bool> {
+        let _ = node;
+        ControlFlow::Continue(true)
+    }
+
+    /// Called for every element contained in a visited node.
+    fn visit_leaf(&mut self, leaf: &T) -> ControlFlow<Self::Break>;
+}
+
+fn visit<T, V>(node: &ParentNode<T>, visitor: &mut V) -> ControlFlow<V::Break>
+where
+    T: RTreeObject,
+    V: TreeVisitor<T> + ?Sized,
+{
+    match visitor.visit_node(node) {
+        ControlFlow::Break(value) => return ControlFlow::Break(value),
+        ControlFlow::Continue(false) => return ControlFlow::Continue(()),
+        ControlFlow::Continue(true) => {}
+    }
+    for child in &node.children {
+        let flow = match child {
+            RTreeNode::Leaf(ref t) => visitor.visit_leaf(t),
+            RTreeNode::Parent(ref data) => visit(data, visitor),
+        };
+        if let ControlFlow::Break(value) = flow {
+            return ControlFlow::Break(value);
+        }
+    }
+    ControlFlow::Continue(())
+}
+
+impl<T, Params> RTree<T, Params>
+where
+    T: RTreeObject,
+    Params: RTreeParams,
+{
+    /// Traverses the tree depth-first, calling `visitor` for every node and element.
+    ///
+    /// Returns the value the traversal was stopped with, see [TreeVisitor] for an example.
+    pub fn accept<V>(&self, visitor: &mut V) -> ControlFlow<V::Break>
+    where
+        V: TreeVisitor<T> + ?Sized,
+    {
+        visit(self.root(), visitor)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::TreeVisitor;
+    use crate::test_utilities::{create_random_points, SEED_1};
+    use crate::{ParentNode, RTree};
+    use core::ops::ControlFlow;
+
+    struct Collect {
+        nodes: usize,
+        leaves: Vec<[f64; 2]>,
+        limit: usize,
+    }
+
+    impl TreeVisitor<[f64; 2]> for Collect {
+        type Break = [f64; 2];
+
+        fn visit_node(&mut self, node: &ParentNode<[f64; 2]>) -> ControlFlow<[f64; 2], bool> {
+            self.nodes += 1;
+            ControlFlow::Continue(node.envelope().lower()[0] < 0.5)
+        }
+
+        fn visit_leaf(&mut self, leaf: &[f64; 2]) -> ControlFlow<[f64; 2]> {
+            self.leaves.push(*leaf);
+            if self.leaves.len() == self.limit {
+                ControlFlow::Break(*leaf)
+            } else {
+                ControlFlow::Continue(())
+            }
+        }
+    }
+
+    #[test]
+    fn test_accept() {
+        let points = create_random_points(1000, SEED_1);
+        let tree = RTree::bulk_load(points.clone());
+        let mut visitor = Collect {
+            nodes: 0,
+            leaves: Vec::new(),
+            limit: usize::MAX,
+        };
+        assert_eq!(tree.accept(&mut visitor), ControlFlow::Continue(()));
+        assert!(visitor.nodes < tree.iter_nodes_breadth_first().count());
+        let expected = points.iter().filter(|p| p[0] < 0.5).count();
+        let found = visitor.leaves.iter().filter(|p| p[0] < 0.5).count();
+        assert_eq!(found, expected);
+        assert!(visitor.leaves.len() < points.len());
+
+        let mut visitor = Collect {
+            nodes: 0,
+            leaves: Vec::new(),
+            limit: 10,
+        };
+        let last = tree.accept(&mut visitor);
+        assert_eq!(visitor.leaves.len(), 10);
+        assert_eq!(last, ControlFlow::Break(visitor.leaves[9]));
+    }
+}
@@ -0,0 +1,262 @@
+use crate::params::{DefaultParams, RTreeParams};
+use crate::point::Point;
+use crate::primitives::{GeomWithData, Rectangle};
+use crate::{Envelope, RTree, RTreeObject, AABB};
+
+use alloc::vec::Vec;
+
+type WatchedRegion<P> = GeomWithData<Rectangle<P>, usize>;
+
+/// An element entering or leaving a region of a [Watcher].
+///
+/// Regions are identified by the ids returned by [Watcher::add_region].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RegionEvent {
+    /// The element entered the region with the given id.
+    Enter(usize),
+    /// The element left the region with the given id.
+    Leave(usize),
+}
+
+/// A tree that reports elements entering and leaving registered regions.
+///
+/// Regions are axis aligned boxes, an element is inside of a region if its envelope
+/// intersects the region. Every modification of the tree returns the events it caused,
+/// which avoids comparing the results of region queries before and after every update.
+/// The regions are stored in a tree as well, so modifications only look at the regions
+/// near the modified elements.
+///
+/// # Example
+/// ```
+/// use rstar::{RegionEvent, Watcher, AABB};
+///
+/// let mut watcher = Watcher::new();
+/// let home = watcher.add_region(AABB::from_corners([0.0, 0.0], [10.0, 10.0]));
+/// let work = watcher.add_region(AABB::from_corners([20.0, 0.0], [30.0, 10.0]));
+///
+/// assert_eq!(watcher.insert([5.0, 5.0]), vec![RegionEvent::Enter(home)]);
+/// assert_eq!(
+///     watcher.relocate(&[5.0, 5.0], [25.0, 5.0]),
+///     Some(vec![RegionEvent::Leave(home), RegionEvent::Enter(work)])
+/// );
+/// assert_eq!(watcher.remove(&[25.0, 5.0]), Some(([25.0, 5.0], vec![RegionEvent::Leave(work)])));
+/// ```
+pub struct Watcher<T, Params = DefaultParams>
+where
+    T: RTreeObject,
+    Params: RTreeParams,
+{
+    tree: RTree<T, Params>,
+    regions: RTree<WatchedRegion<<T::Envelope as Envelope>::Point>>,
+    region_envelopes: Vec<Option<T::Envelope>>,
+}
+
+impl<T, P> Watcher<T>
+where
+    T: RTreeObject<Envelope = AABB<P>>,
+    P: Point,
+{
+    /// Creates a new watcher without elements and regions.
+    pub fn new() -> Self {
+        Self::new_with_params()
+    }
+}
+
+impl<T, P> Default for Watcher<T>
+where
+    T: RTreeObject<Envelope = AABB<P>>,
+    P: Point,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, P, Params> Watcher<T, Params>
+where
+    T: RTreeObject<Envelope = AABB<P>>,
+    P: Point,
+    Params: RTreeParams,
+{
+    /// Creates a new watcher with custom parameters.
+    pub fn new_with_params() -> Self {
+        Self::from_tree(RTree::new_with_params())
+    }
+
+    /// Creates a watcher for the elements of an existing tree.
+    pub fn from_tree(tree: RTree<T, Params>) -> Self {
+        Watcher {
+            tree,
+            regions: RTree::new(),
+            region_envelopes: Vec::new(),
+        }
+    }
+
+    /// Returns the watched tree, e.g. for queries.
+    pub fn tree(&self) -> &RTree<T, Params> {
+        &self.tree
+    }
+
+    /// Returns the watched tree, dropping all regions.
+    pub fn into_tree(self) -> RTree<T, Params> {
+        self.tree
+    }
+
+    /// Registers a region and returns its id.
+    ///
+    /// Ids are not reused after a region is removed. Elements already inside of the region
+    /// don't cause events, see [Watcher::elements_in_region].
+    pub fn add_region(&mut self, region: AABB<P>) -> usize {
+        let id = self.region_envelopes.len();
+        self.regions
+            .insert(GeomWithData::new(Rectangle::from_aabb(region.clone()), id));
+        self.region_envelopes.push(Some(region));
+        id
+    }
+
+    /// Removes a region and returns it, or `None` if there is no region with this id.
+    pub fn remove_region(&mut self, id: usize) -> Option<AABB<P>> {
+        let region = self.region_envelopes.get_mut(id)?.take()?;
+        self.regions
+            .remove(&GeomWithData::new(Rectangle::from_aabb(region.clone()), id));
+        Some(region)
+    }
+
+    /// Returns all elements inside of a region, nothing if there is no region with this id.
+    pub fn elements_in_region(&self, id: usize) -> impl Iterator<Item = &T> + '_ {
+        let region = self
+            .region_envelopes
+            .get(id)
+            .cloned()
+            .flatten()
+            .unwrap_or_else(Envelope::new_empty);
+        self.tree.locate_in_envelope_intersecting(&region)
+    }
+
+    /// Inserts an element and returns the regions it entered, ordered by id.
+    pub fn insert(&mut self, element: T) -> Vec<RegionEvent> {
+        let events = self
+            .region_ids(&element.envelope())
+            .into_iter()
+            .map(RegionEvent::Enter)
+            .collect();
+        self.tree.insert(element);
+        events
+    }
+
+    fn region_ids(&self, envelope: &AABB<P>) -> Vec<usize> {
+        let mut ids: Vec<_> = self
+            .regions
+            .locate_in_envelope_intersecting(envelope)
+            .map(|region| region.data)
+            .collect();
+        ids.sort_unstable();
+        ids
+    }
+}
+
+impl<T, P, Params> Watcher<T, Params>
+where
+    T: RTreeObject<Envelope = AABB<P>> + PartialEq,
+    P: Point,
+    Params: RTreeParams,
+{
+    /// Removes an element and returns it together with the regions it left, ordered by id.
+    ///
+    /// Returns `None` if the element is not contained in the tree.
+    pub fn remove(&mut self, element: &T) -> Option<(T, Vec<RegionEvent>)> {
+        let removed = self.tree.remove(element)?;
+        let events = self
+            .region_ids(&removed.envelope())
+            .into_iter()
+            .map(RegionEvent::Leave)
+            .collect();
+        Some((removed, events))
+    }
+
+    /// Replaces `old` with `new`, usually the same element at a new position.
+    ///
+    /// Returns the regions the element left, followed by the regions it entered, both ordered
+    /// by id. Regions containing both the old and the new element don't cause events. Returns
+    /// `None` and doesn't insert `new` if `old` is not contained in the tree.
+    pub fn relocate(&mut self, old: &T, new: T) -> Option<Vec<RegionEvent>> {
+        let old = self.tree.remove(old)?;
+        let before = self.region_ids(&old.envelope());
+        let after = self.region_ids(&new.envelope());
+        self.tree.insert(new);
+        let left = before
+            .iter()
+            .filter(|id| after.binary_search(id).is_err())
+            .map(|id| RegionEvent::Leave(*id));
+        let entered = after
+            .iter()
+            .filter(|id| before.binary_search(id).is_err())
+            .map(|id| RegionEvent::Enter(*id));
+        Some(left.chain(entered).collect())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{RegionEvent, Watcher};
+    use crate::test_utilities::{create_random_points, create_random_rectangles, SEED_1, SEED_2};
+    use crate::{Envelope, RTreeObject};
+
+    #[test]
+    fn test_watcher_events() {
+        let regions = create_random_rectangles(50, SEED_1);
+        let points = create_random_points(200, SEED_2);
+        let mut watcher = Watcher::new();
+        let ids: Vec<_> = regions
+            .iter()
+            .map(|region| watcher.add_region(region.envelope()))
+            .collect();
+        let inside = |point: &[f64; 2]| -> Vec<usize> {
+            ids.iter()
+                .copied()
+                .filter(|id| regions[*id].envelope().contains_point(point))
+                .collect()
+        };
+
+        for point in &points {
+            let events = watcher.insert(*point);
+            let expected: Vec<_> = inside(point).into_iter().map(RegionEvent::Enter).collect();
+            assert_eq!(events, expected);
+        }
+        let expected = points
+            .iter()
+            .filter(|point| regions[3].envelope().contains_point(point))
+            .count();
+        assert_eq!(watcher.elements_in_region(ids[3]).count(), expected);
+
+        for window in points.windows(2) {
+            let (old, new) = (window[0], [window[1][1], window[1][0]]);
+            let events = watcher.relocate(&old, new).unwrap();
+            let (before, after) = (inside(&old), inside(&new));
+            for event in &events {
+                match event {
+                    RegionEvent::Leave(id) => assert!(before.contains(id) && !after.contains(id)),
+                    RegionEvent::Enter(id) => assert!(!before.contains(id) && after.contains(id)),
+                }
+            }
+            let changed = before.iter().filter(|id| !after.contains(id)).count()
+                + after.iter().filter(|id| !before.contains(id)).count();
+            assert_eq!(events.len(), changed);
+            watcher.relocate(&new, old).unwrap();
+        }
+        assert_eq!(watcher.relocate(&[2.0, 2.0], [0.5, 0.5]), None);
+
+        assert_eq!(watcher.remove_region(ids[0]), Some(regions[0].envelope()));
+        assert_eq!(watcher.remove_region(ids[0]), None);
+        assert_eq!(watcher.elements_in_region(ids[0]).count(), 0);
+        let (removed, events) = watcher.remove(&points[0]).unwrap();
+        assert_eq!(removed, points[0]);
+        let expected: Vec<_> = inside(&points[0])
+            .into_iter()
+            .filter(|id| *id != ids[0])
+            .map(RegionEvent::Leave)
+            .collect();
+        assert_eq!(events, expected);
+        assert_eq!(watcher.tree().size(), 199);
+    }
+}
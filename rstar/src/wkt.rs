@@ -0,0 +1,609 @@
+//! Parsing of primitives from well-known text (WKT) and well-known binary (WKB).
+//!
+//! Geometries exported from spatial databases like PostGIS can be converted into
+//! r-tree primitives without an intermediate geometry crate:
+//!
+//! ```
+//! use rstar::primitives::{Line, Rectangle};
+//! use rstar::wkt::FromWkt;
+//! use rstar::RTree;
+//!
+//! let rows = ["LINESTRING (0 0, 1 1)", "LINESTRING (1 0, 2 2)"];
+//! let lines = rows
+//!     .iter()
+//!     .map(|row| Line::<[f64; 2]>::from_wkt(row))
+//!     .collect::<Result<Vec<_>, _>>()
+//!     .unwrap();
+//! let tree = RTree::bulk_load(lines);
+//! assert_eq!(tree.size(), 2);
+//!
+//! let rectangle = Rectangle::<[f64; 2]>::from_wkt("POLYGON ((0 0, 2 0, 2 1, 0 1, 0 0))").unwrap();
+//! assert_eq!(rectangle.upper(), [2.0, 1.0]);
+//! ```
+//!
+//! Supported geometries are `POINT` (for any [Point] type), `LINESTRING`s with exactly two
+//! vertices (for [Line]) and axis aligned `POLYGON`s (for [Rectangle]). The number of
+//! coordinates of each vertex must match the point type's dimension.
+
+use crate::primitives::{Line, Rectangle};
+use crate::{Point, AABB};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use num_traits::NumCast;
+
+/// Error returned when parsing WKT or WKB fails.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParseError {
+    /// The input ended unexpectedly.
+    UnexpectedEnd,
+    /// The input contains invalid syntax at the given byte offset.
+    InvalidSyntax(usize),
+    /// A coordinate could not be parsed or converted into the point's scalar type.
+    InvalidCoordinate,
+    /// The geometry type does not match the primitive that should be created.
+    UnexpectedGeometryType,
+    /// The geometry's dimension does not match the point type's dimension.
+    DimensionMismatch {
+        /// The dimension of the point type.
+        expected: usize,
+        /// The dimension found in the input.
+        found: usize,
+    },
+    /// The geometry has a different number of vertices than required.
+    UnexpectedVertexCount(usize),
+    /// A polygon is not an axis aligned rectangle.
+    NotARectangle,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::UnexpectedEnd => write!(f, "unexpected end of input"),
+            ParseError::InvalidSyntax(offset) => write!(f, "invalid syntax at offset {}", offset),
+            ParseError::InvalidCoordinate => write!(f, "invalid coordinate"),
+            ParseError::UnexpectedGeometryType => write!(f, "unexpected geometry type"),
+            ParseError::DimensionMismatch { expected, found } => write!(
+                f,
+                "expected {}-dimensional coordinates, found {} dimensions",
+                expected, found
+            ),
+            ParseError::UnexpectedVertexCount(count) => {
+                write!(f, "unexpected number of vertices: {}", count)
+            }
+            ParseError::NotARectangle => write!(f, "polygon is not an axis aligned rectangle"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseError {}
+
+/// Types that can be created from well-known text.
+pub trait FromWkt: Sized {
+    /// Parses a WKT string, e.g. `POINT (1 2)`.
+    fn from_wkt(wkt: &str) -> Result<Self, ParseError>;
+}
+
+/// Types that can be created from well-known binary.
+///
+/// Both ISO WKB and PostGIS' extended WKB (EWKB) are supported. An embedded SRID is ignored.
+pub trait FromWkb: Sized {
+    /// Parses a WKB encoded geometry. Bytes following the geometry are rejected.
+    fn from_wkb(wkb: &[u8]) -> Result<Self, ParseError>;
+}
+
+const WKB_POINT: u32 = 1;
+const WKB_LINESTRING: u32 = 2;
+const WKB_POLYGON: u32 = 3;
+
+/// The rings of vertices of a parsed geometry.
+struct RawGeometry<P> {
+    rings: Vec<Vec<P>>,
+}
+
+impl<P> FromWkt for P
+where
+    P: Point,
+    P::Scalar: NumCast,
+{
+    fn from_wkt(wkt: &str) -> Result<Self, ParseError> {
+        parse_wkt(wkt, WKB_POINT).and_then(single_vertex)
+    }
+}
+
+impl<P> FromWkb for P
+where
+    P: Point,
+    P::Scalar: NumCast,
+{
+    fn from_wkb(wkb: &[u8]) -> Result<Self, ParseError> {
+        parse_wkb(wkb, WKB_POINT).and_then(single_vertex)
+    }
+}
+
+impl<P> FromWkt for Line<P>
+where
+    P: Point,
+    P::Scalar: NumCast,
+{
+    fn from_wkt(wkt: &str) -> Result<Self, ParseError> {
+        parse_wkt(wkt, WKB_LINESTRING).and_then(line_from_vertices)
+    }
+}
+
+impl<P> FromWkb for Line<P>
+where
+    P: Point,
+    P::Scalar: NumCast,
+{
+    fn from_wkb(wkb: &[u8]) -> Result<Self, ParseError> {
+        parse_wkb(wkb, WKB_LINESTRING).and_then(line_from_vertices)
+    }
+}
+
+impl<P> FromWkt for Rectangle<P>
+where
+    P: Point,
+    P::Scalar: NumCast,
+{
+    fn from_wkt(wkt: &str) -> Result<Self, ParseError> {
+        parse_wkt(wkt, WKB_POLYGON).and_then(rectangle_from_rings)
+    }
+}
+
+impl<P> FromWkb for Rectangle<P>
+where
+    P: Point,
+    P::Scalar: NumCast,
+{
+    fn from_wkb(wkb: &[u8]) -> Result<Self, ParseError> {
+        parse_wkb(wkb, WKB_POLYGON).and_then(rectangle_from_rings)
+    }
+}
+
+fn single_vertex<P>(geometry: RawGeometry<P>) -> Result<P, ParseError> {
+    let mut vertices = flatten(geometry);
+    match vertices.len() {
+        1 => Ok(vertices.remove(0)),
+        count => Err(ParseError::UnexpectedVertexCount(count)),
+    }
+}
+
+fn line_from_vertices<P: Point>(geometry: RawGeometry<P>) -> Result<Line<P>, ParseError> {
+    let mut vertices = flatten(geometry);
+    match vertices.len() {
+        2 => {
+            let to = vertices.pop().unwrap();
+            let from = vertices.pop().unwrap();
+            Ok(Line::new(from, to))
+        }
+        count => Err(ParseError::UnexpectedVertexCount(count)),
+    }
+}
+
+fn rectangle_from_rings<P: Point>(geometry: RawGeometry<P>) -> Result<Rectangle<P>, ParseError> {
+    if geometry.rings.len() != 1 {
+        return Err(ParseError::NotARectangle);
+    }
+    let ring = &geometry.rings[0];
+    let mut vertices = &ring[..];
+    if vertices.len() > 1 && vertices.first() == vertices.last() {
+        vertices = &vertices[..vertices.len() - 1];
+    }
+    if P::DIMENSIONS != 2 {
+        return Err(ParseError::NotARectangle);
+    }
+    if vertices.len() != 4 {
+        return Err(ParseError::UnexpectedVertexCount(ring.len()));
+    }
+    let aabb = AABB::from_points(vertices.iter());
+    let (lower, upper) = (aabb.lower(), aabb.upper());
+    // All four corners of the bounding box must be vertices of the polygon.
+    let is_rectangle = [
+        (&lower, &lower),
+        (&lower, &upper),
+        (&upper, &lower),
+        (&upper, &upper),
+    ]
+    .iter()
+    .all(|(x, y)| {
+        vertices
+            .iter()
+            .any(|vertex| vertex.nth(0) == x.nth(0) && vertex.nth(1) == y.nth(1))
+    });
+    if !is_rectangle {
+        return Err(ParseError::NotARectangle);
+    }
+    Ok(Rectangle::from_aabb(aabb))
+}
+
+fn flatten<P>(geometry: RawGeometry<P>) -> Vec<P> {
+    geometry.rings.into_iter().flatten().collect()
+}
+
+fn make_point<P>(coordinates: &[f64]) -> Result<P, ParseError>
+where
+    P: Point,
+    P::Scalar: NumCast,
+{
+    if coordinates.len() != P::DIMENSIONS {
+        return Err(ParseError::DimensionMismatch {
+            expected: P::DIMENSIONS,
+            found: coordinates.len(),
+        });
+    }
+    let mut result = Ok(());
+    let point = P::generate(|axis| match NumCast::from(coordinates[axis]) {
+        Some(value) => value,
+        None => {
+            result = Err(ParseError::InvalidCoordinate);
+            num_traits::Zero::zero()
+        }
+    });
+    result.map(|_| point)
+}
+
+struct WktReader<'a> {
+    input: &'a str,
+    position: usize,
+}
+
+impl<'a> WktReader<'a> {
+    fn skip_whitespace(&mut self) {
+        let rest = &self.input[self.position..];
+        self.position += rest.len() - rest.trim_start().len();
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_whitespace();
+        self.input[self.position..].chars().next()
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), ParseError> {
+        match self.peek() {
+            Some(c) if c == expected => {
+                self.position += c.len_utf8();
+                Ok(())
+            }
+            Some(_) => Err(ParseError::InvalidSyntax(self.position)),
+            None => Err(ParseError::UnexpectedEnd),
+        }
+    }
+
+    fn token(&mut self) -> &'a str {
+        self.skip_whitespace();
+        let rest = &self.input[self.position..];
+        let length = rest
+            .find(|c: char| c.is_whitespace() || c == '(' || c == ')' || c == ',')
+            .unwrap_or(rest.len());
+        self.position += length;
+        &rest[..length]
+    }
+
+    fn ring<P>(&mut self) -> Result<Vec<P>, ParseError>
+    where
+        P: Point,
+        P::Scalar: NumCast,
+    {
+        self.expect('(')?;
+        let mut vertices = Vec::new();
+        loop {
+            let mut coordinates = Vec::with_capacity(P::DIMENSIONS);
+            while let Some(c) = self.peek() {
+                if c == ',' || c == ')' {
+                    break;
+                }
+                let offset = self.position;
+                let coordinate = self
+                    .token()
+                    .parse::<f64>()
+                    .map_err(|_| ParseError::InvalidSyntax(offset))?;
+                coordinates.push(coordinate);
+            }
+            vertices.push(make_point(&coordinates)?);
+            match self.peek() {
+                Some(',') => self.expect(',')?,
+                _ => break,
+            }
+        }
+        self.expect(')')?;
+        Ok(vertices)
+    }
+}
+
+fn parse_wkt<P>(wkt: &str, expected_type: u32) -> Result<RawGeometry<P>, ParseError>
+where
+    P: Point,
+    P::Scalar: NumCast,
+{
+    let mut reader = WktReader {
+        input: wkt,
+        position: 0,
+    };
+    let keyword = reader.token();
+    let geometry_type = if keyword.eq_ignore_ascii_case("POINT") {
+        WKB_POINT
+    } else if keyword.eq_ignore_ascii_case("LINESTRING") {
+        WKB_LINESTRING
+    } else if keyword.eq_ignore_ascii_case("POLYGON") {
+        WKB_POLYGON
+    } else if keyword.is_empty() {
+        return Err(ParseError::UnexpectedEnd);
+    } else {
+        return Err(ParseError::UnexpectedGeometryType);
+    };
+    if geometry_type != expected_type {
+        return Err(ParseError::UnexpectedGeometryType);
+    }
+    if reader.peek() != Some('(') {
+        // Skip an optional dimension tag (`Z`, `M` or `ZM`); the number of coordinates is
+        // checked for every vertex.
+        let offset = reader.position;
+        let tag = reader.token();
+        if tag.eq_ignore_ascii_case("EMPTY") {
+            return Err(ParseError::UnexpectedVertexCount(0));
+        }
+        if !["Z", "M", "ZM"].iter().any(|t| tag.eq_ignore_ascii_case(t)) {
+            return Err(ParseError::InvalidSyntax(offset));
+        }
+    }
+    let rings = if geometry_type == WKB_POLYGON {
+        reader.expect('(')?;
+        let mut rings = vec![reader.ring()?];
+        while reader.peek() == Some(',') {
+            reader.expect(',')?;
+            rings.push(reader.ring()?);
+        }
+        reader.expect(')')?;
+        rings
+    } else {
+        vec![reader.ring()?]
+    };
+    match reader.peek() {
+        None => Ok(RawGeometry { rings }),
+        Some(_) => Err(ParseError::InvalidSyntax(reader.position)),
+    }
+}
+
+struct WkbReader<'a> {
+    input: &'a [u8],
+    little_endian: bool,
+}
+
+impl<'a> WkbReader<'a> {
+    fn bytes<const N: usize>(&mut self) -> Result<[u8; N], ParseError> {
+        if self.input.len() < N {
+            return Err(ParseError::UnexpectedEnd);
+        }
+        let (head, tail) = self.input.split_at(N);
+        self.input = tail;
+        let mut result = [0; N];
+        result.copy_from_slice(head);
+        Ok(result)
+    }
+
+    fn u32(&mut self) -> Result<u32, ParseError> {
+        let bytes = self.bytes()?;
+        Ok(if self.little_endian {
+            u32::from_le_bytes(bytes)
+        } else {
+            u32::from_be_bytes(bytes)
+        })
+    }
+
+    fn f64(&mut self) -> Result<f64, ParseError> {
+        let bytes = self.bytes()?;
+        Ok(if self.little_endian {
+            f64::from_le_bytes(bytes)
+        } else {
+            f64::from_be_bytes(bytes)
+        })
+    }
+
+    fn vertices<P>(&mut self, count: usize, dimensions: usize) -> Result<Vec<P>, ParseError>
+    where
+        P: Point,
+        P::Scalar: NumCast,
+    {
+        let mut result = Vec::with_capacity(count.min(self.input.len() / 8));
+        let mut coordinates = Vec::with_capacity(dimensions);
+        for _ in 0..count {
+            coordinates.clear();
+            for _ in 0..dimensions {
+                coordinates.push(self.f64()?);
+            }
+            result.push(make_point(&coordinates)?);
+        }
+        Ok(result)
+    }
+}
+
+fn parse_wkb<P>(wkb: &[u8], expected_type: u32) -> Result<RawGeometry<P>, ParseError>
+where
+    P: Point,
+    P::Scalar: NumCast,
+{
+    const EWKB_Z: u32 = 0x8000_0000;
+    const EWKB_M: u32 = 0x4000_0000;
+    const EWKB_SRID: u32 = 0x2000_0000;
+
+    let mut reader = WkbReader {
+        input: wkb,
+        little_endian: false,
+    };
+    reader.little_endian = match reader.bytes::<1>()?[0] {
+        0 => false,
+        1 => true,
+        _ => return Err(ParseError::InvalidSyntax(0)),
+    };
+    let raw_type = reader.u32()?;
+    if raw_type & EWKB_SRID != 0 {
+        reader.u32()?;
+    }
+    let flag_dimensions = (raw_type & EWKB_Z != 0) as usize + (raw_type & EWKB_M != 0) as usize;
+    let iso_type = raw_type & 0x0fff_ffff;
+    let geometry_type = iso_type % 1000;
+    let iso_dimensions = match iso_type / 1000 {
+        0 => 0,
+        1 | 2 => 1,
+        3 => 2,
+        _ => return Err(ParseError::UnexpectedGeometryType),
+    };
+    let dimensions = 2 + flag_dimensions.max(iso_dimensions);
+    if geometry_type != expected_type {
+        return Err(ParseError::UnexpectedGeometryType);
+    }
+    let rings = match geometry_type {
+        WKB_POINT => vec![reader.vertices(1, dimensions)?],
+        WKB_LINESTRING => {
+            let count = reader.u32()? as usize;
+            vec![reader.vertices(count, dimensions)?]
+        }
+        _ => {
+            let ring_count = reader.u32()?;
+            let mut rings = Vec::new();
+            for _ in 0..ring_count {
+                let count = reader.u32()? as usize;
+                rings.push(reader.vertices(count, dimensions)?);
+            }
+            rings
+        }
+    };
+    if !reader.input.is_empty() {
+        return Err(ParseError::InvalidSyntax(wkb.len() - reader.input.len()));
+    }
+    Ok(RawGeometry { rings })
+}
+
+#[cfg(test)]
+mod test {
+    use super::{FromWkb, FromWkt, ParseError};
+    use crate::primitives::{Line, Rectangle};
+
+    #[test]
+    fn test_points_from_wkt() {
+        assert_eq!(<[f64; 2]>::from_wkt("POINT (1 2)"), Ok([1.0, 2.0]));
+        assert_eq!(<[f64; 2]>::from_wkt("point(1.5 -2e1)"), Ok([1.5, -20.0]));
+        assert_eq!(<[f32; 3]>::from_wkt("POINT Z (1 2 3)"), Ok([1.0, 2.0, 3.0]));
+        assert_eq!(<(i32, i32)>::from_wkt(" POINT ( 4 5 ) "), Ok((4, 5)));
+        assert_eq!(
+            <[f64; 3]>::from_wkt("POINT (1 2)"),
+            Err(ParseError::DimensionMismatch {
+                expected: 3,
+                found: 2
+            })
+        );
+        assert_eq!(
+            <[f64; 2]>::from_wkt("LINESTRING (1 2, 3 4)"),
+            Err(ParseError::UnexpectedGeometryType)
+        );
+        assert_eq!(
+            <[f64; 2]>::from_wkt("POINT EMPTY"),
+            Err(ParseError::UnexpectedVertexCount(0))
+        );
+        assert_eq!(
+            <[f64; 2]>::from_wkt("POINT (1 a)"),
+            Err(ParseError::InvalidSyntax(9))
+        );
+        assert_eq!(
+            <[f64; 2]>::from_wkt("POINT (1 2"),
+            Err(ParseError::UnexpectedEnd)
+        );
+        assert!(<[f64; 2]>::from_wkt("POINT (1 2) trailing").is_err());
+    }
+
+    #[test]
+    fn test_lines_and_rectangles_from_wkt() {
+        assert_eq!(
+            Line::from_wkt("LINESTRING (0 0, 1 1)"),
+            Ok(Line::new([0.0, 0.0], [1.0, 1.0]))
+        );
+        assert_eq!(
+            Line::<[f64; 2]>::from_wkt("LINESTRING (0 0, 1 1, 2 2)"),
+            Err(ParseError::UnexpectedVertexCount(3))
+        );
+        assert_eq!(
+            Rectangle::from_wkt("POLYGON ((0 0, 0 1, 2 1, 2 0, 0 0))"),
+            Ok(Rectangle::from_corners([0.0, 0.0], [2.0, 1.0]))
+        );
+        assert_eq!(
+            Rectangle::<[f64; 2]>::from_wkt("POLYGON ((0 0, 0 1, 2 2, 2 0, 0 0))"),
+            Err(ParseError::NotARectangle)
+        );
+        assert_eq!(
+            Rectangle::<[f64; 2]>::from_wkt(
+                "POLYGON ((0 0, 0 1, 2 1, 2 0, 0 0), (1 0, 1 1, 2 1, 2 0, 1 0))"
+            ),
+            Err(ParseError::NotARectangle)
+        );
+    }
+
+    fn wkb(
+        little_endian: bool,
+        geometry_type: u32,
+        counts: &[u32],
+        coordinates: &[f64],
+    ) -> Vec<u8> {
+        let mut result = vec![little_endian as u8];
+        for value in core::iter::once(&geometry_type).chain(counts) {
+            if little_endian {
+                result.extend_from_slice(&value.to_le_bytes());
+            } else {
+                result.extend_from_slice(&value.to_be_bytes());
+            }
+        }
+        for coordinate in coordinates {
+            if little_endian {
+                result.extend_from_slice(&coordinate.to_le_bytes());
+            } else {
+                result.extend_from_slice(&coordinate.to_be_bytes());
+            }
+        }
+        result
+    }
+
+    #[test]
+    fn test_from_wkb() {
+        let point = wkb(true, 1, &[], &[1.0, 2.0]);
+        assert_eq!(<[f64; 2]>::from_wkb(&point), Ok([1.0, 2.0]));
+        let point = wkb(false, 1001, &[], &[1.0, 2.0, 3.0]);
+        assert_eq!(<[f64; 3]>::from_wkb(&point), Ok([1.0, 2.0, 3.0]));
+        assert_eq!(
+            <[f64; 2]>::from_wkb(&point),
+            Err(ParseError::DimensionMismatch {
+                expected: 2,
+                found: 3
+            })
+        );
+        assert_eq!(
+            <[f64; 2]>::from_wkb(&point[..10]),
+            Err(ParseError::UnexpectedEnd)
+        );
+        let mut trailing = wkb(true, 1, &[], &[1.0, 2.0]);
+        trailing.push(0);
+        assert_eq!(
+            <[f64; 2]>::from_wkb(&trailing),
+            Err(ParseError::InvalidSyntax(21))
+        );
+
+        let line = wkb(false, 2, &[2], &[0.0, 1.0, 2.0, 3.0]);
+        assert_eq!(Line::from_wkb(&line), Ok(Line::new([0.0, 1.0], [2.0, 3.0])));
+        assert_eq!(
+            Rectangle::<[f64; 2]>::from_wkb(&line),
+            Err(ParseError::UnexpectedGeometryType)
+        );
+
+        // An EWKB polygon with an SRID
+        let polygon = wkb(
+            true,
+            3 | 0x2000_0000,
+            &[4326, 1, 5],
+            &[0.0, 0.0, 1.0, 0.0, 1.0, 2.0, 0.0, 2.0, 0.0, 0.0],
+        );
+        assert_eq!(
+            Rectangle::from_wkb(&polygon),
+            Ok(Rectangle::from_corners([0.0, 0.0], [1.0, 2.0]))
+        );
+    }
+}
@@ -0,0 +1,104 @@
+//! Checks how often insertions call the allocator.
+//!
+//! The counting allocator is installed for this test binary only. Allocations are counted per
+//! thread, tests running in parallel don't affect each other.
+
+use rstar::{RStarInsertionStrategy, RTree, RTreeParams, SplitOnlyInsertionStrategy};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+fn count_allocation() {
+    // The counter may already be destroyed while the thread exits.
+    let _ = ALLOCATIONS.try_with(|allocations| allocations.set(allocations.get() + 1));
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        count_allocation();
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        count_allocation();
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn allocations() -> usize {
+    ALLOCATIONS.with(Cell::get)
+}
+
+/// Nodes too large for the scratch space a stable sort keeps on the stack.
+struct LargeNodes;
+
+impl RTreeParams for LargeNodes {
+    const MIN_SIZE: usize = 32;
+    const MAX_SIZE: usize = 128;
+    const REINSERTION_COUNT: usize = 5;
+    type DefaultInsertionStrategy = RStarInsertionStrategy;
+}
+
+/// The default node sizes, without forced reinsertion.
+struct SplitOnly;
+
+impl RTreeParams for SplitOnly {
+    const MIN_SIZE: usize = 3;
+    const MAX_SIZE: usize = 6;
+    const REINSERTION_COUNT: usize = 2;
+    type DefaultInsertionStrategy = SplitOnlyInsertionStrategy;
+}
+
+fn points(len: usize) -> Vec<[f64; 2]> {
+    let mut state = 0x2545_f491_4f6c_dd1du64;
+    let mut next = move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        (state >> 11) as f64 / (1u64 << 53) as f64
+    };
+    (0..len).map(|_| [next(), next()]).collect()
+}
+
+fn check_insertion_without_allocation<Params: RTreeParams>() {
+    let points = points(5000);
+    let mut tree: RTree<[f64; 2], Params> = RTree::with_capacity_with_params(points.len());
+    let before = allocations();
+    for point in &points {
+        assert!(tree.try_insert_within_capacity(*point).is_ok());
+    }
+    assert_eq!(allocations(), before);
+    assert_eq!(tree.size(), points.len());
+}
+
+#[test]
+fn test_insert_within_capacity_does_not_allocate() {
+    check_insertion_without_allocation::<rstar::DefaultParams>();
+    check_insertion_without_allocation::<LargeNodes>();
+}
+
+#[test]
+fn test_insert_allocates_once_per_node() {
+    let points = points(5000);
+    let mut tree: RTree<[f64; 2], SplitOnly> = RTree::new_with_params();
+    let before = allocations();
+    for point in &points {
+        tree.insert(*point);
+    }
+    let allocations = allocations() - before;
+    // Every node created by a split allocates its children once, they never grow.
+    let nodes = tree.iter_nodes_breadth_first().count();
+    assert_eq!(allocations, nodes - 1);
+}